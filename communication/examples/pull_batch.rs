@@ -0,0 +1,51 @@
+//! Compares pulling messages one at a time with pulling them in batches, on a thread-local channel.
+//!
+//! Run with `cargo run --release --example pull_batch -- <messages> <rounds>`.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use timely_communication::{Push, Pull};
+use timely_communication::allocator::Thread;
+
+fn main() {
+
+    let messages: usize = std::env::args().nth(1).map_or(1_000, |arg| arg.parse().unwrap());
+    let rounds: usize = std::env::args().nth(2).map_or(10_000, |arg| arg.parse().unwrap());
+
+    // Channels as a worker allocates them, behind boxed trait objects and message counters.
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let (pusher, puller) = Thread::new_from::<usize>(0, Rc::clone(&events));
+    let mut pusher: Box<dyn Push<usize>> = Box::new(pusher);
+    let mut puller: Box<dyn Pull<usize>> = Box::new(puller);
+
+    // Only the time spent pulling is measured, as pushing costs the same for both.
+    let mut elapsed = Duration::default();
+    let mut sum = 0;
+    for _ in 0 .. rounds {
+        for message in 0 .. messages { pusher.send(message); }
+        pusher.done();
+        events.borrow_mut().clear();
+        let timer = Instant::now();
+        while let Some(message) = puller.recv() { sum += message; }
+        elapsed += timer.elapsed();
+    }
+    println!("pull:       {:?}\t(sum: {})", elapsed, sum);
+
+    elapsed = Duration::default();
+    sum = 0;
+    for _ in 0 .. rounds {
+        for message in 0 .. messages { pusher.send(message); }
+        pusher.done();
+        events.borrow_mut().clear();
+        let timer = Instant::now();
+        loop {
+            let batch = puller.pull_batch();
+            if batch.iter().all(Option::is_none) { break; }
+            sum += batch.iter_mut().filter_map(Option::take).sum::<usize>();
+        }
+        elapsed += timer.elapsed();
+    }
+    println!("pull_batch: {:?}\t(sum: {})", elapsed, sum);
+}
@@ -142,4 +142,22 @@ impl<T, P: Pull<T>> Pull<T> for Puller<T, P> {
 
         result
     }
+    #[inline]
+    fn pull_batch(&mut self) -> &mut [Option<T>] {
+        let batch = self.puller.pull_batch();
+        let pulled = batch.iter().filter(|element| element.is_some()).count();
+        if pulled == 0 {
+            if self.count != 0 {
+                self.events
+                    .borrow_mut()
+                    .push(self.index);
+                self.count = 0;
+            }
+        }
+        else {
+            self.count += pulled;
+        }
+
+        batch
+    }
 }
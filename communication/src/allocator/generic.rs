@@ -8,7 +8,7 @@ use std::cell::RefCell;
 
 use crate::allocator::thread::ThreadBuilder;
 use crate::allocator::process::ProcessBuilder as TypedProcessBuilder;
-use crate::allocator::{Allocate, AllocateBuilder, Exchangeable, Thread, Process};
+use crate::allocator::{Allocate, AllocateBuilder, Exchangeable, Thread, Process, Topology};
 use crate::allocator::zero_copy::allocator_process::{ProcessBuilder, ProcessAllocator};
 use crate::allocator::zero_copy::allocator::{TcpBuilder, TcpAllocator};
 
@@ -50,6 +50,26 @@ impl Generic {
             Generic::ZeroCopyBinary(z) => z.peers(),
         }
     }
+    /// The process and host of each worker.
+    pub fn topology(&self) -> Topology {
+        match self {
+            Generic::Thread(t) => t.topology(),
+            Generic::Process(p) => p.topology(),
+            Generic::ProcessBinary(pb) => pb.topology(),
+            Generic::ZeroCopy(z) => z.topology(),
+            Generic::ZeroCopyBinary(z) => z.topology(),
+        }
+    }
+    /// True if channels among all workers may be pipelines when there is one worker.
+    pub fn exchange_by_pipeline(&self) -> bool {
+        match self {
+            Generic::Thread(t) => t.exchange_by_pipeline(),
+            Generic::Process(p) => p.exchange_by_pipeline(),
+            Generic::ProcessBinary(pb) => pb.exchange_by_pipeline(),
+            Generic::ZeroCopy(z) => z.exchange_by_pipeline(),
+            Generic::ZeroCopyBinary(z) => z.exchange_by_pipeline(),
+        }
+    }
     /// Constructs several send endpoints and one receive endpoint.
     fn allocate<T: Exchangeable>(&mut self, identifier: usize) -> (Vec<Box<dyn Push<T>>>, Box<dyn Pull<T>>) {
         match self {
@@ -104,6 +124,8 @@ impl Generic {
 impl Allocate for Generic {
     fn index(&self) -> usize { self.index() }
     fn peers(&self) -> usize { self.peers() }
+    fn topology(&self) -> Topology { self.topology() }
+    fn exchange_by_pipeline(&self) -> bool { self.exchange_by_pipeline() }
     fn allocate<T: Exchangeable>(&mut self, identifier: usize) -> (Vec<Box<dyn Push<T>>>, Box<dyn Pull<T>>) {
         self.allocate(identifier)
     }
@@ -14,6 +14,7 @@ pub mod generic;
 
 pub mod canary;
 pub mod counters;
+pub mod recording;
 
 pub mod zero_copy;
 
@@ -102,6 +103,80 @@ pub trait Allocate {
         let (pushers, pull) = self.allocate(identifier);
         (Box::new(Broadcaster { spare: None, pushers }), pull)
     }
+
+    /// The process and host of each peer in the communication group.
+    ///
+    /// By default, all peers are assumed to share one process.
+    fn topology(&self) -> Topology {
+        Topology::single_process(self.peers())
+    }
+
+    /// True if channels among all peers may be constructed with `pipeline` when there is one peer.
+    ///
+    /// A single peer exchanges only with itself, and a pipeline channel then moves its data without
+    /// serialization. Allocators that observe or intercept the channels they allocate should keep
+    /// the default of `false`, so that all channels are allocated through them.
+    fn exchange_by_pipeline(&self) -> bool { false }
+}
+
+/// Where a peer runs, relative to the other peers of the communication group.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PeerLocation {
+    /// The index of the peer's process.
+    pub process: usize,
+    /// An identifier of the peer's host, shared by processes on the same host.
+    pub host: usize,
+}
+
+/// The locations of all peers of a communication group, indexed by peer.
+///
+/// Peers in the same process exchange data without serialization, and peers on the same host
+/// without crossing the network, which operators can use to prefer nearby peers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Topology {
+    locations: Vec<PeerLocation>,
+}
+
+impl Topology {
+    /// Creates a topology from the location of each peer.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely_communication::{PeerLocation, Topology};
+    ///
+    /// // Two processes of two workers each, on one host.
+    /// let topology = Topology::new((0 .. 4).map(|peer| PeerLocation { process: peer / 2, host: 0 }).collect());
+    /// assert!(topology.same_process(0, 1));
+    /// assert!(!topology.same_process(1, 2));
+    /// assert!(topology.same_host(1, 2));
+    /// ```
+    pub fn new(locations: Vec<PeerLocation>) -> Self {
+        Topology { locations }
+    }
+    /// Creates the topology of `peers` peers sharing one process.
+    pub fn single_process(peers: usize) -> Self {
+        Topology::new(vec![PeerLocation { process: 0, host: 0 }; peers])
+    }
+    /// The number of peers.
+    pub fn peers(&self) -> usize {
+        self.locations.len()
+    }
+    /// The location of `peer`.
+    pub fn location(&self, peer: usize) -> PeerLocation {
+        self.locations[peer]
+    }
+    /// The locations of all peers, indexed by peer.
+    pub fn locations(&self) -> &[PeerLocation] {
+        &self.locations
+    }
+    /// True iff peers `x` and `y` run in the same process.
+    pub fn same_process(&self, x: usize, y: usize) -> bool {
+        self.locations[x].process == self.locations[y].process
+    }
+    /// True iff peers `x` and `y` run on the same host.
+    pub fn same_host(&self, x: usize, y: usize) -> bool {
+        self.locations[x].host == self.locations[y].host
+    }
 }
 
 /// An adapter to broadcast any pushed element.
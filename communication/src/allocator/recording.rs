@@ -0,0 +1,259 @@
+//! An allocator that records the order in which workers receive messages, and replays it.
+//!
+//! Messages sent to a worker by several others arrive in an order that depends on the timing of
+//! their threads, and a bug that depends on this order may be hard to reproduce. A
+//! [`RecordingAllocator`] wraps the allocator of each worker, and in record mode logs, for each
+//! worker and channel, the worker that sent each message the worker receives. In replay mode it
+//! delivers the messages of each worker and channel in the logged order, holding back messages
+//! that arrive ahead of their turn, so that a run with the same dataflows and inputs receives its
+//! messages as the recorded run did, however the threads are timed.
+//!
+//! The wrapped allocator must deliver the messages pushed to a worker in the order they were
+//! pushed across all senders, as the [`Process`](super::Process) and [`Thread`](super::Thread)
+//! allocators do. The allocators of multiple processes do not, and are not supported.
+//!
+//! A replay can diverge from its recording, for example when progress messages, whose number
+//! depends on timing, differ from those recorded. A worker that has waited for `patience` for a
+//! message from the logged sender, while holding messages from other senders, delivers the
+//! channel's messages in their order of arrival from then on, and the divergence is counted in the
+//! [`DeliveryLog`].
+
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::allocator::{Allocate, AllocateBuilder, Exchangeable, Topology};
+use crate::{Push, Pull};
+
+/// For each channel and receiving worker, the senders of its messages in the order they were pushed.
+type Senders = Arc<Mutex<HashMap<(usize, usize), Arc<Mutex<VecDeque<usize>>>>>>;
+
+/// The order in which workers received messages, by the worker that sent each one.
+///
+/// Clones of a log share its contents, and so a log can be recorded by the workers of one run and
+/// replayed by those of another.
+#[derive(Clone, Debug, Default)]
+pub struct DeliveryLog {
+    /// For each receiving worker and channel, the sender of each message received, in order.
+    deliveries: Arc<Mutex<HashMap<(usize, usize), Vec<usize>>>>,
+    /// The number of channels whose replay diverged from the log.
+    divergences: Arc<Mutex<usize>>,
+}
+
+impl DeliveryLog {
+    /// Allocates an empty log.
+    pub fn new() -> Self { Self::default() }
+
+    /// The sender of each message `worker` received on `channel`, in the order received.
+    pub fn senders(&self, worker: usize, channel: usize) -> Vec<usize> {
+        self.deliveries.lock().expect("delivery log poisoned").get(&(worker, channel)).cloned().unwrap_or_default()
+    }
+
+    /// The number of channels whose replay diverged from the log, and delivered messages in their order of arrival.
+    pub fn divergences(&self) -> usize {
+        *self.divergences.lock().expect("delivery log poisoned")
+    }
+
+    fn record(&self, worker: usize, channel: usize, sender: usize) {
+        self.deliveries.lock().expect("delivery log poisoned").entry((worker, channel)).or_default().push(sender);
+    }
+}
+
+/// Builds a [`RecordingAllocator`] around the allocator of `B`.
+pub struct RecordingBuilder<B> {
+    inner: B,
+    log: DeliveryLog,
+    /// The patience of a replay, or `None` to record.
+    patience: Option<Duration>,
+    senders: Senders,
+}
+
+impl<B: AllocateBuilder> RecordingBuilder<B> {
+    /// Wraps the builders of all workers, to record the order in which they receive messages to `log`.
+    ///
+    /// The log should be empty, and the builders should be those of all workers of one process.
+    pub fn record(builders: Vec<B>, log: &DeliveryLog) -> Vec<Self> {
+        Self::wrap(builders, log, None)
+    }
+
+    /// Wraps the builders of all workers, to deliver messages in the order recorded in `log`.
+    ///
+    /// A worker holding messages that arrived ahead of their turn waits at most `patience` for the
+    /// message whose turn it is, before it delivers the messages of the channel in their order of
+    /// arrival.
+    pub fn replay(builders: Vec<B>, log: &DeliveryLog, patience: Duration) -> Vec<Self> {
+        Self::wrap(builders, log, Some(patience))
+    }
+
+    fn wrap(builders: Vec<B>, log: &DeliveryLog, patience: Option<Duration>) -> Vec<Self> {
+        let senders = Senders::default();
+        builders.into_iter().map(|inner| RecordingBuilder { inner, log: log.clone(), patience, senders: Arc::clone(&senders) }).collect()
+    }
+}
+
+impl<B: AllocateBuilder> AllocateBuilder for RecordingBuilder<B> {
+    type Allocator = RecordingAllocator<B::Allocator>;
+    fn build(self) -> Self::Allocator {
+        RecordingAllocator {
+            inner: self.inner.build(),
+            log: self.log,
+            patience: self.patience,
+            senders: self.senders,
+        }
+    }
+}
+
+/// An allocator that records the order in which its worker receives messages, or replays a recorded order.
+pub struct RecordingAllocator<A> {
+    inner: A,
+    log: DeliveryLog,
+    patience: Option<Duration>,
+    senders: Senders,
+}
+
+impl<A: Allocate> Allocate for RecordingAllocator<A> {
+    fn index(&self) -> usize { self.inner.index() }
+    fn peers(&self) -> usize { self.inner.peers() }
+    fn allocate<T: Exchangeable>(&mut self, identifier: usize) -> (Vec<Box<dyn Push<T>>>, Box<dyn Pull<T>>) {
+        let (pushers, puller) = self.inner.allocate(identifier);
+        let index = self.index();
+        let mut senders = self.senders.lock().expect("senders poisoned");
+        let mut senders_to = |target: usize| Arc::clone(senders.entry((identifier, target)).or_default());
+
+        let pushers = pushers.into_iter().enumerate().map(|(target, inner)| {
+            Box::new(SenderPusher { inner, index, senders: senders_to(target) }) as Box<dyn Push<T>>
+        }).collect();
+
+        let replay = self.patience.map(|patience| Replay {
+            turns: self.log.senders(index, identifier).into(),
+            patience,
+            held: (0 .. self.peers()).map(|_| VecDeque::new()).collect(),
+            arrivals: VecDeque::new(),
+            waiting_since: None,
+        });
+        let puller = RecordingPuller {
+            inner: puller,
+            senders: senders_to(index),
+            index,
+            identifier,
+            log: self.log.clone(),
+            replay,
+            events: Rc::clone(self.inner.events()),
+            current: None,
+        };
+        (pushers, Box::new(puller))
+    }
+    fn events(&self) -> &Rc<RefCell<Vec<usize>>> { self.inner.events() }
+    fn await_events(&self, duration: Option<Duration>) { self.inner.await_events(duration) }
+    fn receive(&mut self) { self.inner.receive() }
+    fn release(&mut self) { self.inner.release() }
+    fn topology(&self) -> Topology { self.inner.topology() }
+}
+
+/// Pushes messages, and notes its worker as their sender.
+struct SenderPusher<T> {
+    inner: Box<dyn Push<T>>,
+    index: usize,
+    senders: Arc<Mutex<VecDeque<usize>>>,
+}
+
+impl<T> Push<T> for SenderPusher<T> {
+    fn push(&mut self, element: &mut Option<T>) {
+        if element.is_some() {
+            // Holding the lock while pushing keeps the senders in the order of the messages.
+            let mut senders = self.senders.lock().expect("senders poisoned");
+            self.inner.push(element);
+            senders.push_back(self.index);
+        }
+        else {
+            self.inner.push(element);
+        }
+    }
+}
+
+/// The state of a replay of one channel to one worker.
+struct Replay<T> {
+    /// The senders of the messages still to deliver, in their recorded order.
+    turns: VecDeque<usize>,
+    patience: Duration,
+    /// For each sender, the messages received from it but not yet delivered.
+    held: Vec<VecDeque<T>>,
+    /// The senders of the held messages, in their order of arrival.
+    arrivals: VecDeque<usize>,
+    /// When the worker started to wait for the message whose turn it is.
+    waiting_since: Option<Instant>,
+}
+
+/// Pulls messages, and records their senders or delivers them in a recorded order.
+struct RecordingPuller<T> {
+    inner: Box<dyn Pull<T>>,
+    senders: Arc<Mutex<VecDeque<usize>>>,
+    index: usize,
+    identifier: usize,
+    log: DeliveryLog,
+    /// The state of a replay, or `None` to record.
+    replay: Option<Replay<T>>,
+    events: Rc<RefCell<Vec<usize>>>,
+    current: Option<T>,
+}
+
+impl<T> RecordingPuller<T> {
+    /// The sender of the latest message pulled from the wrapped puller.
+    fn sender(&self) -> usize {
+        self.senders.lock().expect("senders poisoned").pop_front().expect("message without sender")
+    }
+}
+
+impl<T> Pull<T> for RecordingPuller<T> {
+    fn pull(&mut self) -> &mut Option<T> {
+        if self.replay.is_none() {
+            self.current = self.inner.pull().take();
+            if self.current.is_some() {
+                let sender = self.sender();
+                self.log.record(self.index, self.identifier, sender);
+            }
+            return &mut self.current;
+        }
+
+        while let Some(message) = self.inner.pull().take() {
+            let sender = self.sender();
+            let replay = self.replay.as_mut().expect("replay present");
+            replay.held[sender].push_back(message);
+            replay.arrivals.push_back(sender);
+        }
+
+        let replay = self.replay.as_mut().expect("replay present");
+        if replay.arrivals.is_empty() {
+            self.current = None;
+            return &mut self.current;
+        }
+        let sender = match replay.turns.front() {
+            Some(&turn) if !replay.held[turn].is_empty() => {
+                replay.turns.pop_front();
+                replay.waiting_since = None;
+                turn
+            }
+            Some(_) => {
+                let since = *replay.waiting_since.get_or_insert_with(Instant::now);
+                if since.elapsed() < replay.patience {
+                    // Try again on the next step, as the message whose turn it is may not raise an event.
+                    self.events.borrow_mut().push(self.identifier);
+                    self.current = None;
+                    return &mut self.current;
+                }
+                // Out of patience: deliver the channel's messages in their order of arrival.
+                replay.turns.clear();
+                replay.waiting_since = None;
+                *self.log.divergences.lock().expect("delivery log poisoned") += 1;
+                replay.arrivals[0]
+            }
+            None => replay.arrivals[0],
+        };
+        let position = replay.arrivals.iter().position(|arrival| *arrival == sender).expect("held message has an arrival");
+        replay.arrivals.remove(position);
+        self.current = replay.held[sender].pop_front();
+        &mut self.current
+    }
+}
@@ -29,6 +29,7 @@ pub struct Thread {
 impl Allocate for Thread {
     fn index(&self) -> usize { 0 }
     fn peers(&self) -> usize { 1 }
+    fn exchange_by_pipeline(&self) -> bool { true }
     fn allocate<T: 'static>(&mut self, identifier: usize) -> (Vec<Box<dyn Push<T>>>, Box<dyn Pull<T>>) {
         let (pusher, puller) = Thread::new_from(identifier, Rc::clone(&self.events));
         (vec![Box::new(pusher)], Box::new(puller))
@@ -61,7 +62,7 @@ impl Thread {
         let shared = Rc::new(RefCell::new((VecDeque::<T>::new(), VecDeque::<T>::new())));
         let pusher = Pusher { target: Rc::clone(&shared) };
         let pusher = CountPusher::new(pusher, identifier, Rc::clone(&events));
-        let puller = Puller { source: shared, current: None };
+        let puller = Puller { source: shared, current: None, batch: Vec::new() };
         let puller = CountPuller::new(puller, identifier, events);
         (pusher, puller)
     }
@@ -87,6 +88,7 @@ impl<T> Push<T> for Pusher<T> {
 /// The pull half of an intra-thread channel.
 pub struct Puller<T> {
     current: Option<T>,
+    batch: Vec<Option<T>>,
     source: Rc<RefCell<(VecDeque<T>, VecDeque<T>)>>,
 }
 
@@ -103,4 +105,11 @@ impl<T> Pull<T> for Puller<T> {
         self.current = borrow.0.pop_front();
         &mut self.current
     }
+    #[inline]
+    fn pull_batch(&mut self) -> &mut [Option<T>] {
+        // Elements left in the previous batch are dropped, as those of `pull` are.
+        self.batch.clear();
+        self.batch.extend(self.source.borrow_mut().0.drain(..).map(Some));
+        &mut self.batch[..]
+    }
 }
@@ -3,17 +3,20 @@ use std::rc::Rc;
 use std::cell::RefCell;
 use std::collections::{VecDeque, HashMap, hash_map::Entry};
 use std::sync::mpsc::{Sender, Receiver};
+use std::time::{Duration, Instant};
 
 use timely_bytes::arc::Bytes;
 
 use crate::networking::MessageHeader;
 
 use crate::{Allocate, Push, Pull};
-use crate::allocator::{AllocateBuilder, Exchangeable};
+use crate::allocator::{AllocateBuilder, Exchangeable, PeerLocation, Topology};
 use crate::allocator::canary::Canary;
 use crate::allocator::zero_copy::bytes_slab::BytesRefill;
 use super::bytes_exchange::{BytesPull, SendEndpoint, MergeQueue};
 use super::push_pull::{Pusher, PullerInner};
+use super::credits::{self, SendCredits, CREDIT_CHANNEL, DEFAULT_CHANNEL_CREDITS};
+use super::buffer_pool::{BufferPool, DEFAULT_BUFFER_POOL_BYTES};
 
 /// Builds an instance of a TcpAllocator.
 ///
@@ -25,6 +28,11 @@ pub struct TcpBuilder<A: AllocateBuilder> {
     inner:  A,
     index:  usize,                      // number out of peers
     peers:  usize,                      // number of peer allocators.
+    hosts:  Vec<usize>,                 // host identifier of each process.
+    max_frame_bytes: Option<usize>,     // maximum bytes of each frame sent to other processes.
+    channel_credits: Option<usize>,     // credits of each channel and target in other processes.
+    buffer_pool_bytes: Option<usize>,   // bytes retained by the pool of buffers of queued messages.
+    startup_timeout: Option<Duration>,  // time to allocate channels other workers have sent messages on.
     futures:   Vec<Receiver<MergeQueue>>,  // to receive queues to each network thread.
     promises:   Vec<Sender<MergeQueue>>,    // to send queues from each network thread.
     /// Byte slab refill function.
@@ -69,6 +77,11 @@ pub fn new_vector<A: AllocateBuilder>(
                 inner,
                 index: my_process * threads + index,
                 peers: threads * processes,
+                hosts: (0 .. processes).collect(),
+                max_frame_bytes: None,
+                channel_credits: Some(DEFAULT_CHANNEL_CREDITS),
+                buffer_pool_bytes: Some(DEFAULT_BUFFER_POOL_BYTES),
+                startup_timeout: None,
                 promises,
                 futures,
                 refill: refill.clone(),
@@ -80,6 +93,56 @@ pub fn new_vector<A: AllocateBuilder>(
 
 impl<A: AllocateBuilder> TcpBuilder<A> {
 
+    /// Sets the host identifier of each process, which otherwise each have their own host.
+    pub(crate) fn set_hosts(&mut self, hosts: Vec<usize>) {
+        self.hosts = hosts;
+    }
+
+    /// Limits the number of bytes of each frame sent to other processes, its header included.
+    ///
+    /// Pushing a message whose frame exceeds the limit panics with a [`FrameTooLarge`](super::push_pull::FrameTooLarge) error,
+    /// naming the channel and the frame size, before writing anything to the byte stream.
+    /// By default frames are unlimited.
+    pub fn set_max_frame_bytes(&mut self, max_frame_bytes: Option<usize>) {
+        self.max_frame_bytes = max_frame_bytes;
+    }
+
+    /// Sets the number of messages of each channel a worker may send to each worker in another process
+    /// before that worker has pulled them, or disables flow control for `None`.
+    ///
+    /// Messages beyond the credits are queued at the sender until the receiving worker grants more,
+    /// as described in [`credits`](super::credits). Broadcast channels are not flow controlled.
+    /// By default each channel has [`DEFAULT_CHANNEL_CREDITS`] credits.
+    pub fn set_channel_credits(&mut self, channel_credits: Option<usize>) {
+        self.channel_credits = channel_credits;
+    }
+
+    /// Limits the number of bytes retained by the pool recycling the buffers of messages queued for lack of credits,
+    /// or disables the pool for `None`.
+    ///
+    /// Without a pool, each queued message is serialized into a newly allocated buffer that is freed once
+    /// the message is sent. The pool instead retains sent buffers, by size class, for later messages, and
+    /// frees any that would take it past the limit. By default the pool retains [`DEFAULT_BUFFER_POOL_BYTES`].
+    pub fn set_buffer_pool_bytes(&mut self, buffer_pool_bytes: Option<usize>) {
+        self.buffer_pool_bytes = buffer_pool_bytes;
+    }
+
+    /// Limits the time a worker may take to allocate a channel after receiving messages for it, or removes the limit for `None`.
+    ///
+    /// Workers that construct their dataflows too slowly, or construct different dataflows, leave
+    /// other workers waiting on them. A worker that has not allocated a channel within the timeout
+    /// of first receiving messages for it panics, naming itself, the channel, and the sender of
+    /// the messages, rather than leaving its peers waiting indefinitely. By default workers may
+    /// take any time.
+    ///
+    /// The timeout applies only at startup, to the channels that receive messages before the worker
+    /// allocates its first channel, as it constructs its first dataflow. Dataflows constructed later
+    /// may receive messages for arbitrarily long before the worker constructs them, as when workers
+    /// construct a dataflow once they have completed an earlier one at different times.
+    pub fn set_startup_timeout(&mut self, startup_timeout: Option<Duration>) {
+        self.startup_timeout = startup_timeout;
+    }
+
     /// Builds a `TcpAllocator`, instantiating `Rc<RefCell<_>>` elements.
     pub fn build(self) -> TcpAllocator<A::Allocator> {
 
@@ -103,10 +166,22 @@ impl<A: AllocateBuilder> TcpBuilder<A> {
         // let sends: Vec<_> = self.sends.into_iter().map(
         //     |send| Rc::new(RefCell::new(SendEndpoint::new(send)))).collect();
 
+        // Peers are numbered consecutively within each process.
+        let threads = self.peers / self.hosts.len();
+        let locations = (0 .. self.peers).map(|peer| PeerLocation { process: peer / threads, host: self.hosts[peer / threads] }).collect();
+
         TcpAllocator {
             inner: self.inner.build(),
             index: self.index,
             peers: self.peers,
+            topology: Topology::new(locations),
+            max_frame_bytes: self.max_frame_bytes,
+            channel_credits: self.channel_credits,
+            buffer_pool: self.buffer_pool_bytes.map(|bytes| Rc::new(RefCell::new(BufferPool::new(bytes)))),
+            startup_timeout: self.startup_timeout,
+            unallocated: HashMap::new(),
+            credits: HashMap::new(),
+            grants: Rc::new(RefCell::new(Vec::new())),
             canaries: Rc::new(RefCell::new(Vec::new())),
             channel_id_bound: None,
             staged: Vec::new(),
@@ -124,6 +199,14 @@ pub struct TcpAllocator<A: Allocate> {
 
     index:      usize,                              // number out of peers
     peers:      usize,                              // number of peer allocators (for typed channel allocation).
+    topology:   Topology,                           // process and host of each peer.
+    max_frame_bytes: Option<usize>,                 // maximum bytes of each frame sent to other processes.
+    channel_credits: Option<usize>,                 // initial credits of each channel and target, if flow controlled.
+    buffer_pool: Option<Rc<RefCell<BufferPool>>>,   // recycles the buffers of messages queued for credits.
+    startup_timeout: Option<Duration>,              // time to allocate channels after receiving messages for them.
+    unallocated: HashMap<usize, (usize, Instant)>,  // for channels with messages before the first allocation, the first sender and arrival.
+    credits:    HashMap<(usize, usize), Rc<RefCell<SendCredits>>>,  // credits for each (channel, target).
+    grants:     Rc<RefCell<Vec<(usize, usize)>>>,   // (channel, source) of pulled messages to grant credits for.
 
     staged:     Vec<Bytes>,                         // staging area for incoming Bytes
     canaries:   Rc<RefCell<Vec<usize>>>,
@@ -139,6 +222,8 @@ pub struct TcpAllocator<A: Allocate> {
 impl<A: Allocate> Allocate for TcpAllocator<A> {
     fn index(&self) -> usize { self.index }
     fn peers(&self) -> usize { self.peers }
+    fn topology(&self) -> Topology { self.topology.clone() }
+    fn exchange_by_pipeline(&self) -> bool { self.inner.exchange_by_pipeline() }
     fn allocate<T: Exchangeable>(&mut self, identifier: usize) -> (Vec<Box<dyn Push<T>>>, Box<dyn Pull<T>>) {
 
         // Assume and enforce in-order identifier allocation.
@@ -146,6 +231,7 @@ impl<A: Allocate> Allocate for TcpAllocator<A> {
             assert!(bound < identifier);
         }
         self.channel_id_bound = Some(identifier);
+        self.unallocated.remove(&identifier);
 
         // Result list of boxed pushers.
         let mut pushes = Vec::<Box<dyn Push<T>>>::new();
@@ -175,7 +261,17 @@ impl<A: Allocate> Allocate for TcpAllocator<A> {
 
                 // create, box, and stash new process_binary pusher.
                 if process_id > self.index / inner_peers { process_id -= 1; }
-                pushes.push(Box::new(Pusher::new(header, Rc::clone(&self.sends[process_id]))));
+                let mut pusher = Pusher::new(header, Rc::clone(&self.sends[process_id])).with_max_frame_bytes(self.max_frame_bytes);
+                if let Some(channel_credits) = self.channel_credits {
+                    let mut credits = SendCredits::new(channel_credits);
+                    if let Some(pool) = &self.buffer_pool {
+                        credits = credits.with_pool(Rc::clone(pool));
+                    }
+                    let credits = Rc::new(RefCell::new(credits));
+                    self.credits.insert((identifier, target_index), Rc::clone(&credits));
+                    pusher = pusher.with_credits(credits);
+                }
+                pushes.push(Box::new(pusher));
             }
         }
 
@@ -183,7 +279,11 @@ impl<A: Allocate> Allocate for TcpAllocator<A> {
 
         use crate::allocator::counters::Puller as CountPuller;
         let canary = Canary::new(identifier, Rc::clone(&self.canaries));
-        let puller = Box::new(CountPuller::new(PullerInner::new(inner_recv, channel, canary), identifier, Rc::clone(self.events())));
+        let mut puller = PullerInner::new(inner_recv, channel, canary);
+        if self.channel_credits.is_some() {
+            puller = puller.with_grants(Rc::clone(&self.grants));
+        }
+        let puller = Box::new(CountPuller::new(puller, identifier, Rc::clone(self.events())));
 
         (pushes, puller, )
     }
@@ -195,6 +295,7 @@ impl<A: Allocate> Allocate for TcpAllocator<A> {
             assert!(bound < identifier);
         }
         self.channel_id_bound = Some(identifier);
+        self.unallocated.remove(&identifier);
 
         // Result list of boxed pushers.
         // One entry for each process.
@@ -217,7 +318,7 @@ impl<A: Allocate> Allocate for TcpAllocator<A> {
                 length: 0,
                 seqno: 0,
             };
-            pushes.push(Box::new(Pusher::new(header, Rc::clone(send))))
+            pushes.push(Box::new(Pusher::new(header, Rc::clone(send)).with_max_frame_bytes(self.max_frame_bytes)))
         }
 
         let channel = Rc::clone(self.to_local.entry(identifier).or_default());
@@ -246,6 +347,7 @@ impl<A: Allocate> Allocate for TcpAllocator<A> {
             // workers will drop the dataflow too, without blocking indefinitely
             // on events from it.
             // assert!(dropped.borrow().is_empty());
+            self.credits.retain(|(channel, _), _| *channel != dropped_channel);
         }
         ::std::mem::drop(canaries);
 
@@ -265,9 +367,19 @@ impl<A: Allocate> Allocate for TcpAllocator<A> {
 
                 if let Some(header) = MessageHeader::try_read(&bytes[..]) {
 
-                    // Get the header and payload, ditch the header.
-                    let mut peel = bytes.extract_to(header.required_bytes());
-                    let _ = peel.extract_to(::std::mem::size_of::<MessageHeader>());
+                    // Get the header and payload, which pullers peel apart.
+                    let peel = bytes.extract_to(header.required_bytes());
+
+                    // Grants of credits go to pushers, rather than pullers.
+                    if header.channel == CREDIT_CHANNEL {
+                        let (channel, granted) = credits::read_grant(&peel[header.header_bytes() ..]).expect("failed to read grant!");
+                        // Credits for channels since dropped are discarded.
+                        if let Some(credits) = self.credits.get(&(channel, header.source)) {
+                            let process_id = send_index(self.index, header.source, self.inner.peers());
+                            credits.borrow_mut().grant(granted, &mut self.sends[process_id].borrow_mut());
+                        }
+                        continue;
+                    }
 
                     // Increment message count for channel.
                     // Safe to do this even if the channel has been dropped.
@@ -278,6 +390,10 @@ impl<A: Allocate> Allocate for TcpAllocator<A> {
                         Entry::Vacant(entry) => {
                             // We may receive data before allocating, and shouldn't block.
                             if self.channel_id_bound.map(|b| b < header.channel).unwrap_or(true) {
+                                // Only messages received before the first allocation are held to the startup timeout.
+                                if self.startup_timeout.is_some() && self.channel_id_bound.is_none() {
+                                    self.unallocated.insert(header.channel, (header.source, Instant::now()));
+                                }
                                 entry.insert(Rc::new(RefCell::new(VecDeque::new())))
                                     .borrow_mut()
                                     .push_back(peel);
@@ -293,10 +409,28 @@ impl<A: Allocate> Allocate for TcpAllocator<A> {
                 }
             }
         }
+
+        // Fail on channels other workers have sent messages on at startup, that this worker has not allocated in time.
+        if let Some(timeout) = self.startup_timeout {
+            if let Some((channel, (source, _))) = self.unallocated.iter().find(|(_, (_, arrival))| arrival.elapsed() > timeout) {
+                panic!("timely communication error: worker {} did not allocate channel {} within {:?} of receiving messages for it from worker {}", self.index, channel, timeout, source);
+            }
+        }
     }
 
     // Perform postparatory work, most likely sending un-full binary buffers.
     fn release(&mut self) {
+        // Grant credits for pulled messages back to their senders.
+        let mut grants = self.grants.borrow_mut();
+        grants.sort_unstable();
+        for group in grants.chunk_by(|x, y| x == y) {
+            let (channel, source) = group[0];
+            let process_id = send_index(self.index, source, self.inner.peers());
+            credits::write_grant(&mut self.sends[process_id].borrow_mut(), channel, self.index, source, group.len());
+        }
+        grants.clear();
+        ::std::mem::drop(grants);
+
         // Publish outgoing byte ledgers.
         for send in self.sends.iter_mut() {
             send.borrow_mut().publish();
@@ -318,3 +452,9 @@ impl<A: Allocate> Allocate for TcpAllocator<A> {
         self.inner.await_events(duration);
     }
 }
+
+/// The index in `sends` of the process of `worker`, a worker in another process than worker `index`.
+fn send_index(index: usize, worker: usize, inner_peers: usize) -> usize {
+    let process_id = worker / inner_peers;
+    if process_id > index / inner_peers { process_id - 1 } else { process_id }
+}
@@ -0,0 +1,64 @@
+//! A pool of byte buffers, recycled by size class, that retains a bounded number of bytes.
+//!
+//! Pushers out of credits serialize each message into a buffer of its own, and queue the buffer
+//! until credits are granted and its bytes are copied into the send slab. Under heavy exchange this
+//! allocates and frees a buffer per message; the pool instead hands released buffers back out for
+//! messages of similar sizes. Buffers are grouped into power-of-two size classes, and a buffer for
+//! `capacity` bytes comes from the smallest class that holds them. Released buffers are retained
+//! only while the pool holds at most its maximum number of bytes, and others are freed, so that a
+//! burst of queued messages does not leave its memory retained indefinitely.
+
+/// The number of bytes the pool of each worker retains by default.
+pub const DEFAULT_BUFFER_POOL_BYTES: usize = 1 << 24;
+
+/// Byte buffers recycled by size class, retaining at most a maximum number of bytes.
+#[derive(Debug)]
+pub struct BufferPool {
+    /// For each size class `i`, released buffers of capacity `1 << i`.
+    classes: Vec<Vec<Vec<u8>>>,
+    /// The sum of the capacities of the retained buffers.
+    retained_bytes: usize,
+    /// The maximum of `retained_bytes`.
+    max_retained_bytes: usize,
+}
+
+impl BufferPool {
+    /// Creates an empty pool retaining at most `max_retained_bytes` bytes.
+    pub fn new(max_retained_bytes: usize) -> Self {
+        BufferPool { classes: Vec::new(), retained_bytes: 0, max_retained_bytes }
+    }
+    /// An empty buffer with capacity for at least `capacity` bytes, recycled if the pool has one.
+    pub fn acquire(&mut self, capacity: usize) -> Vec<u8> {
+        let class = Self::class_of(capacity);
+        match self.classes.get_mut(class).and_then(|buffers| buffers.pop()) {
+            Some(buffer) => {
+                self.retained_bytes -= buffer.capacity();
+                buffer
+            },
+            None => Vec::with_capacity(1 << class),
+        }
+    }
+    /// Returns `buffer` to the pool, unless retaining it would exceed the maximum or it has no size class.
+    pub fn release(&mut self, mut buffer: Vec<u8>) {
+        let capacity = buffer.capacity();
+        // Buffers of other capacities, for example grown past their class, are freed.
+        if capacity == 0 || !capacity.is_power_of_two() || self.retained_bytes + capacity > self.max_retained_bytes {
+            return;
+        }
+        let class = capacity.trailing_zeros() as usize;
+        if self.classes.len() <= class {
+            self.classes.resize_with(class + 1, Vec::new);
+        }
+        buffer.clear();
+        self.classes[class].push(buffer);
+        self.retained_bytes += capacity;
+    }
+    /// The number of bytes of the buffers the pool retains.
+    pub fn retained_bytes(&self) -> usize { self.retained_bytes }
+    /// The maximum number of bytes the pool retains.
+    pub fn max_retained_bytes(&self) -> usize { self.max_retained_bytes }
+    /// The size class of buffers for `capacity` bytes: the log of the smallest power of two at least `capacity`.
+    fn class_of(capacity: usize) -> usize {
+        capacity.max(1).next_power_of_two().trailing_zeros() as usize
+    }
+}
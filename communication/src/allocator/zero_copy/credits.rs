@@ -0,0 +1,119 @@
+//! Credit-based flow control for channels between processes.
+//!
+//! Each pusher to a worker in another process holds credits for its channel and target, and spends
+//! one credit for each message it sends. A pusher without credits instead queues its serialized
+//! messages, in order, until the target grants it more credits. A worker grants one credit back to
+//! the sender of each message its puller has received, with control messages on the reserved channel
+//! [`CREDIT_CHANNEL`]. This bounds the number of messages of each channel and sender that are either
+//! in flight to a worker, or received but not yet pulled by it.
+//!
+//! Broadcast channels are not flow controlled.
+//!
+//! The buffers of queued messages may come from a [`BufferPool`], which recycles them once they are sent.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::io;
+
+use byteorder::{ReadBytesExt, WriteBytesExt};
+
+use crate::networking::MessageHeader;
+
+use super::buffer_pool::BufferPool;
+use super::bytes_exchange::{BytesPush, SendEndpoint};
+
+/// The channel identifier reserved for control messages granting credits.
+pub const CREDIT_CHANNEL: usize = usize::MAX;
+
+/// The number of messages of each channel a worker may send to each other worker before receiving credits.
+pub const DEFAULT_CHANNEL_CREDITS: usize = 1024;
+
+/// The number of bytes of the payload of a control message granting credits.
+const GRANT_BYTES: usize = 2 * std::mem::size_of::<u64>();
+
+/// The byte order for writing grants, matching that of message headers.
+type ByteOrder = byteorder::BigEndian;
+
+/// The credits of a pusher for one channel and target, and the messages queued for lack of them.
+#[derive(Debug)]
+pub struct SendCredits {
+    available: usize,
+    queued: VecDeque<Vec<u8>>,
+    pool: Option<Rc<RefCell<BufferPool>>>,
+}
+
+impl SendCredits {
+    /// Creates credits for sending `credits` messages.
+    pub fn new(credits: usize) -> Self {
+        SendCredits { available: credits, queued: VecDeque::new(), pool: None }
+    }
+    /// Serializes queued messages into buffers from `pool`, and returns the buffers to it once the messages are sent.
+    pub fn with_pool(mut self, pool: Rc<RefCell<BufferPool>>) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+    /// The number of messages that can be sent before receiving further credits.
+    pub fn available(&self) -> usize { self.available }
+    /// The number of serialized messages waiting for credits.
+    pub fn queued(&self) -> usize { self.queued.len() }
+    /// Spends a credit, unless there are none or messages are already queued ahead.
+    pub(crate) fn try_acquire(&mut self) -> bool {
+        if self.queued.is_empty() && self.available > 0 {
+            self.available -= 1;
+            true
+        }
+        else {
+            false
+        }
+    }
+    /// An empty buffer for serializing a message of `bytes` bytes, its header included, to queue.
+    pub(crate) fn frame(&self, bytes: usize) -> Vec<u8> {
+        match &self.pool {
+            Some(pool) => pool.borrow_mut().acquire(bytes),
+            None => Vec::with_capacity(bytes),
+        }
+    }
+    /// Queues a serialized message, its header included, until credits are granted.
+    pub(crate) fn enqueue(&mut self, frame: Vec<u8>) {
+        self.queued.push_back(frame);
+    }
+    /// Adds `credits`, and writes to `sender` as many queued messages as the credits allow.
+    pub fn grant<P: BytesPush>(&mut self, credits: usize, sender: &mut SendEndpoint<P>) {
+        self.available += credits;
+        while self.available > 0 {
+            let Some(frame) = self.queued.pop_front() else { break };
+            sender.reserve(frame.len())[.. frame.len()].copy_from_slice(&frame[..]);
+            sender.make_valid(frame.len());
+            self.available -= 1;
+            if let Some(pool) = &self.pool {
+                pool.borrow_mut().release(frame);
+            }
+        }
+    }
+}
+
+/// Writes to `sender` a control message from worker `source` granting `credits` for `channel` to worker `target`.
+pub fn write_grant<P: BytesPush>(sender: &mut SendEndpoint<P>, channel: usize, source: usize, target: usize, credits: usize) {
+    let header = MessageHeader {
+        channel: CREDIT_CHANNEL,
+        source,
+        target_lower: target,
+        target_upper: target + 1,
+        length: GRANT_BYTES,
+        seqno: 0,
+    };
+    let mut bytes = sender.reserve(header.required_bytes());
+    header.write_to(&mut bytes).expect("failed to write header!");
+    bytes.write_u64::<ByteOrder>(channel as u64).expect("failed to write grant!");
+    bytes.write_u64::<ByteOrder>(credits as u64).expect("failed to write grant!");
+    sender.make_valid(header.required_bytes());
+}
+
+/// Reads the channel and number of credits from the payload of a control message granting credits.
+pub fn read_grant(payload: &[u8]) -> Option<(usize, usize)> {
+    let mut cursor = io::Cursor::new(payload);
+    let channel = cursor.read_u64::<ByteOrder>().ok()?;
+    let credits = cursor.read_u64::<ByteOrder>().ok()?;
+    Some((channel as usize, credits as usize))
+}
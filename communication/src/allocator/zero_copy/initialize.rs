@@ -1,11 +1,12 @@
 //! Network initialization.
 
 use std::sync::Arc;
+use std::time::Duration;
 use timely_logging::Logger;
 use crate::allocator::PeerBuilder;
 use crate::allocator::zero_copy::bytes_slab::BytesRefill;
 use crate::logging::CommunicationEventBuilder;
-use crate::networking::create_sockets;
+use crate::networking::create_sockets_within;
 use super::tcp::{send_loop, recv_loop};
 use super::allocator::{TcpBuilder, new_vector};
 use super::stream::Stream;
@@ -35,18 +36,34 @@ impl Drop for CommsGuard {
 use crate::logging::CommunicationSetup;
 
 /// Initializes network connections
+///
+/// Fails with an error of kind [`std::io::ErrorKind::TimedOut`] if other processes do not connect within
+/// `startup_timeout`, which is also the time each worker has to allocate the channels other workers send it
+/// messages on before it constructs its first dataflow.
 pub fn initialize_networking<P: PeerBuilder>(
     addresses: Vec<String>,
     my_index: usize,
     threads: usize,
     noisy: bool,
+    startup_timeout: Option<Duration>,
     refill: BytesRefill,
     log_sender: Arc<dyn Fn(CommunicationSetup)->Option<Logger<CommunicationEventBuilder>>+Send+Sync>,
 )
 -> ::std::io::Result<(Vec<TcpBuilder<P::Peer>>, CommsGuard)>
 {
-    let sockets = create_sockets(addresses, my_index, noisy)?;
-    initialize_networking_from_sockets::<_, P>(sockets, my_index, threads, refill, log_sender)
+    // Processes share a host identifier if their addresses name the same host.
+    let mut names = Vec::new();
+    let hosts = addresses.iter().map(|address| {
+        let name = address.rsplit_once(':').map_or(address.as_str(), |(name, _port)| name);
+        names.iter().position(|known| *known == name).unwrap_or_else(|| { names.push(name); names.len() - 1 })
+    }).collect::<Vec<_>>();
+    let sockets = create_sockets_within(addresses, my_index, noisy, startup_timeout)?;
+    let (mut builders, guard) = initialize_networking_from_sockets::<_, P>(sockets, my_index, threads, refill, log_sender)?;
+    for builder in builders.iter_mut() {
+        builder.set_hosts(hosts.clone());
+        builder.set_startup_timeout(startup_timeout);
+    }
+    Ok((builders, guard))
 }
 
 /// Initialize send and recv threads from sockets.
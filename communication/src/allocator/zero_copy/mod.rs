@@ -15,4 +15,6 @@ pub mod allocator;
 pub mod allocator_process;
 pub mod initialize;
 pub mod push_pull;
+pub mod credits;
+pub mod buffer_pool;
 pub mod stream;
\ No newline at end of file
@@ -11,6 +11,7 @@ use crate::networking::MessageHeader;
 use crate::{Bytesable, Push, Pull};
 
 use super::bytes_exchange::{BytesPush, SendEndpoint};
+use super::credits::SendCredits;
 
 /// An adapter into which one may push elements of type `T`.
 ///
@@ -19,6 +20,8 @@ use super::bytes_exchange::{BytesPush, SendEndpoint};
 pub struct Pusher<T, P: BytesPush> {
     header:     MessageHeader,
     sender:     Rc<RefCell<SendEndpoint<P>>>,
+    max_frame_bytes: Option<usize>,
+    credits:    Option<Rc<RefCell<SendCredits>>>,
     phantom:    ::std::marker::PhantomData<T>,
 }
 
@@ -28,21 +31,74 @@ impl<T, P: BytesPush> Pusher<T, P> {
         Pusher {
             header,
             sender,
+            max_frame_bytes: None,
+            credits:    None,
             phantom:    ::std::marker::PhantomData,
         }
     }
+    /// Limits the number of bytes of each frame, its header included, or removes the limit for `None`.
+    pub fn with_max_frame_bytes(mut self, max_frame_bytes: Option<usize>) -> Self {
+        self.max_frame_bytes = max_frame_bytes;
+        self
+    }
+    /// Spends one of `credits` for each message sent, and queues messages while out of credits.
+    ///
+    /// Queued messages are sent as [`SendCredits::grant`] supplies further credits.
+    pub fn with_credits(mut self, credits: Rc<RefCell<SendCredits>>) -> Self {
+        self.credits = Some(credits);
+        self
+    }
 }
 
-impl<T: Bytesable, P: BytesPush> Push<T> for Pusher<T, P> {
-    #[inline]
-    fn push(&mut self, element: &mut Option<T>) {
+/// A message whose frame exceeds the maximum frame size.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameTooLarge {
+    /// The channel the message was sent on.
+    pub channel: usize,
+    /// The number of bytes of the frame, its header included.
+    pub frame_bytes: usize,
+    /// The maximum number of bytes of a frame.
+    pub max_frame_bytes: usize,
+}
+
+impl std::fmt::Display for FrameTooLarge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "message on channel {} serializes to a frame of {} bytes, exceeding the maximum frame size of {} bytes", self.channel, self.frame_bytes, self.max_frame_bytes)
+    }
+}
+
+impl std::error::Error for FrameTooLarge { }
+
+impl<T: Bytesable, P: BytesPush> Pusher<T, P> {
+    /// Serializes and sends `element`, unless its frame exceeds the maximum frame size.
+    ///
+    /// An oversized element is neither sent nor taken from `element`, and nothing is written
+    /// to the byte stream, which remains valid for further messages.
+    pub fn try_push(&mut self, element: &mut Option<T>) -> Result<(), FrameTooLarge> {
         if let Some(ref mut element) = *element {
 
             // determine byte lengths and build header.
             let mut header = self.header;
-            self.header.seqno += 1;
             header.length = element.length_in_bytes();
             assert!(header.length > 0);
+            if let Some(max_frame_bytes) = self.max_frame_bytes {
+                if header.required_bytes() > max_frame_bytes {
+                    return Err(FrameTooLarge { channel: header.channel, frame_bytes: header.required_bytes(), max_frame_bytes });
+                }
+            }
+            self.header.seqno += 1;
+
+            // without credits, serialize the message and queue it.
+            if let Some(credits) = &self.credits {
+                let mut credits = credits.borrow_mut();
+                if !credits.try_acquire() {
+                    let mut frame = credits.frame(header.required_bytes());
+                    header.write_to(&mut frame).expect("failed to write header!");
+                    element.into_bytes(&mut frame);
+                    credits.enqueue(frame);
+                    return Ok(());
+                }
+            }
 
             // acquire byte buffer and write header, element.
             let mut borrow = self.sender.borrow_mut();
@@ -55,6 +111,16 @@ impl<T: Bytesable, P: BytesPush> Push<T> for Pusher<T, P> {
             }
             borrow.make_valid(header.required_bytes());
         }
+        Ok(())
+    }
+}
+
+impl<T: Bytesable, P: BytesPush> Push<T> for Pusher<T, P> {
+    #[inline]
+    fn push(&mut self, element: &mut Option<T>) {
+        if let Err(error) = self.try_push(element) {
+            panic!("timely communication error: {}", error);
+        }
     }
 }
 
@@ -104,19 +170,26 @@ pub struct PullerInner<T> {
     inner: Box<dyn Pull<T>>,               // inner pullable (e.g. intra-process typed queue)
     _canary: Canary,
     current: Option<T>,
-    receiver: Rc<RefCell<VecDeque<Bytes>>>,     // source of serialized buffers
+    receiver: Rc<RefCell<VecDeque<Bytes>>>,     // source of serialized buffers, with headers
+    grants: Option<Rc<RefCell<Vec<(usize, usize)>>>>,  // (channel, source) of each pulled message
 }
 
 impl<T: Bytesable> PullerInner<T> {
-    /// Creates a new `PullerInner` instance from a shared queue.
+    /// Creates a new `PullerInner` instance from a shared queue of messages, each with its header.
     pub fn new(inner: Box<dyn Pull<T>>, receiver: Rc<RefCell<VecDeque<Bytes>>>, _canary: Canary) -> Self {
         PullerInner {
             inner,
             _canary,
             current: None,
             receiver,
+            grants: None,
         }
     }
+    /// Records in `grants` the channel and source of each message pulled from the shared queue.
+    pub fn with_grants(mut self, grants: Rc<RefCell<Vec<(usize, usize)>>>) -> Self {
+        self.grants = Some(grants);
+        self
+    }
 }
 
 impl<T: Bytesable> Pull<T> for PullerInner<T> {
@@ -128,11 +201,19 @@ impl<T: Bytesable> Pull<T> for PullerInner<T> {
             inner
         }
         else {
+            let grants = &self.grants;
             self.current =
             self.receiver
                 .borrow_mut()
                 .pop_front()
-                .map(T::from_bytes);
+                .map(|mut bytes| {
+                    let header = MessageHeader::try_read(&bytes[..]).expect("failed to read header!");
+                    let _ = bytes.extract_to(header.header_bytes());
+                    if let Some(grants) = grants {
+                        grants.borrow_mut().push((header.channel, header.source));
+                    }
+                    T::from_bytes(bytes)
+                });
 
             &mut self.current
         }
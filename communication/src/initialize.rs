@@ -6,6 +6,7 @@ use std::io::BufRead;
 use std::sync::Arc;
 use std::fmt::{Debug, Formatter};
 use std::any::Any;
+use std::time::Duration;
 use std::ops::DerefMut;
 #[cfg(feature = "getopts")]
 use getopts;
@@ -39,6 +40,20 @@ pub enum Config {
         report: bool,
         /// Enable intra-process zero-copy
         zerocopy: bool,
+        /// Maximum number of bytes of each frame sent to other processes, or `None` for no limit
+        max_frame_bytes: Option<usize>,
+        /// Number of messages of each channel a worker may send to each worker in another process before
+        /// that worker pulls them, or `None` to disable flow control
+        channel_credits: Option<usize>,
+        /// Maximum number of bytes each worker retains to recycle the buffers of messages queued for lack
+        /// of credits, or `None` to allocate a buffer for each queued message
+        buffer_pool_bytes: Option<usize>,
+        /// Time to wait for other processes to connect, and for each worker to allocate the channels other
+        /// workers send it messages on before it constructs its first dataflow, or `None` to wait indefinitely
+        startup_timeout: Option<Duration>,
+        /// Pre-touch each byte buffer for exchanging messages as it is allocated, placing its memory on
+        /// the NUMA node of the allocating thread (see [`BytesRefill::pretouch`])
+        pretouch: bool,
         /// Closure to create a new logger for a communication thread
         log_fn: Arc<dyn Fn(CommunicationSetup) -> Option<Logger<CommunicationEventBuilder>> + Send + Sync>,
     }
@@ -50,13 +65,18 @@ impl Debug for Config {
             Config::Thread => write!(f, "Config::Thread()"),
             Config::Process(n) => write!(f, "Config::Process({})", n),
             Config::ProcessBinary(n) => write!(f, "Config::ProcessBinary({})", n),
-            Config::Cluster { threads, process, addresses, report, zerocopy, log_fn: _ } => f
+            Config::Cluster { threads, process, addresses, report, zerocopy, max_frame_bytes, channel_credits, buffer_pool_bytes, startup_timeout, pretouch, log_fn: _ } => f
                 .debug_struct("Config::Cluster")
                 .field("threads", threads)
                 .field("process", process)
                 .field("addresses", addresses)
                 .field("report", report)
                 .field("zerocopy", zerocopy)
+                .field("max_frame_bytes", max_frame_bytes)
+                .field("channel_credits", channel_credits)
+                .field("buffer_pool_bytes", buffer_pool_bytes)
+                .field("startup_timeout", startup_timeout)
+                .field("pretouch", pretouch)
                 .finish_non_exhaustive()
         }
     }
@@ -80,6 +100,11 @@ impl Config {
         opts.optopt("h", "hostfile", "text file whose lines are process addresses", "FILE");
         opts.optflag("r", "report", "reports connection progress");
         opts.optflag("z", "zerocopy", "enable zero-copy for intra-process communication");
+        opts.optopt("", "max-frame-bytes", "maximum bytes of each frame sent to other processes", "BYTES");
+        opts.optopt("", "channel-credits", "messages of each channel in flight to each worker in another process, or 0 to disable flow control", "NUM");
+        opts.optopt("", "buffer-pool-bytes", "bytes each worker retains to recycle buffers of messages queued for credits, or 0 to disable", "BYTES");
+        opts.optopt("", "startup-timeout", "milliseconds to wait for processes to connect and workers to allocate channels", "MILLIS");
+        opts.optflag("", "pretouch", "write to each page of exchange buffers as they are allocated, for first-touch NUMA placement");
     }
 
     /// Instantiates a configuration based upon the parsed options in `matches`.
@@ -97,6 +122,13 @@ impl Config {
         let processes = matches.opt_get_default("n", 1_usize).map_err(|e| e.to_string())?;
         let report = matches.opt_present("report");
         let zerocopy = matches.opt_present("zerocopy");
+        let max_frame_bytes = matches.opt_get::<usize>("max-frame-bytes").map_err(|e| e.to_string())?;
+        let channel_credits = matches.opt_get_default("channel-credits", crate::allocator::zero_copy::credits::DEFAULT_CHANNEL_CREDITS).map_err(|e| e.to_string())?;
+        let channel_credits = if channel_credits > 0 { Some(channel_credits) } else { None };
+        let buffer_pool_bytes = matches.opt_get_default("buffer-pool-bytes", crate::allocator::zero_copy::buffer_pool::DEFAULT_BUFFER_POOL_BYTES).map_err(|e| e.to_string())?;
+        let buffer_pool_bytes = if buffer_pool_bytes > 0 { Some(buffer_pool_bytes) } else { None };
+        let startup_timeout = matches.opt_get::<u64>("startup-timeout").map_err(|e| e.to_string())?.map(Duration::from_millis);
+        let pretouch = matches.opt_present("pretouch");
 
         if processes > 1 {
             let mut addresses = Vec::new();
@@ -123,6 +155,11 @@ impl Config {
                 addresses,
                 report,
                 zerocopy,
+                max_frame_bytes,
+                channel_credits,
+                buffer_pool_bytes,
+                startup_timeout,
+                pretouch,
                 log_fn: Arc::new(|_| None),
             })
         } else if threads > 1 {
@@ -151,11 +188,14 @@ impl Config {
     }
 
     /// Attempts to assemble the described communication infrastructure.
+    ///
+    /// Byte buffers are allocated as zeroed vectors, and pre-touched if the configuration asks for it.
     pub fn try_build(self) -> Result<(Vec<GenericBuilder>, Box<dyn Any+Send>), String> {
         let refill = BytesRefill {
             logic: Arc::new(|size| Box::new(vec![0_u8; size]) as Box<dyn DerefMut<Target=[u8]>>),
             limit: None,
         };
+        let refill = if matches!(self, Config::Cluster { pretouch: true, .. }) { refill.pretouch() } else { refill };
         self.try_build_with(refill)
     }
 
@@ -171,17 +211,27 @@ impl Config {
             Config::ProcessBinary(threads) => {
                 Ok((ProcessBuilder::new_vector(threads, refill).into_iter().map(GenericBuilder::ProcessBinary).collect(), Box::new(())))
             },
-            Config::Cluster { threads, process, addresses, report, zerocopy: false, log_fn } => {
-                match initialize_networking::<Process>(addresses, process, threads, report, refill, log_fn) {
-                    Ok((stuff, guard)) => {
+            Config::Cluster { threads, process, addresses, report, zerocopy: false, max_frame_bytes, channel_credits, buffer_pool_bytes, startup_timeout, pretouch: _, log_fn } => {
+                match initialize_networking::<Process>(addresses, process, threads, report, startup_timeout, refill, log_fn) {
+                    Ok((mut stuff, guard)) => {
+                        for builder in stuff.iter_mut() {
+                            builder.set_max_frame_bytes(max_frame_bytes);
+                            builder.set_channel_credits(channel_credits);
+                            builder.set_buffer_pool_bytes(buffer_pool_bytes);
+                        }
                         Ok((stuff.into_iter().map(GenericBuilder::ZeroCopy).collect(), Box::new(guard)))
                     },
                     Err(err) => Err(format!("failed to initialize networking: {}", err))
                 }
             },
-            Config::Cluster { threads, process, addresses, report, zerocopy: true, log_fn } => {
-                match initialize_networking::<ProcessBuilder>(addresses, process, threads, report, refill, log_fn) {
-                    Ok((stuff, guard)) => {
+            Config::Cluster { threads, process, addresses, report, zerocopy: true, max_frame_bytes, channel_credits, buffer_pool_bytes, startup_timeout, pretouch: _, log_fn } => {
+                match initialize_networking::<ProcessBuilder>(addresses, process, threads, report, startup_timeout, refill, log_fn) {
+                    Ok((mut stuff, guard)) => {
+                        for builder in stuff.iter_mut() {
+                            builder.set_max_frame_bytes(max_frame_bytes);
+                            builder.set_channel_credits(channel_credits);
+                            builder.set_buffer_pool_bytes(buffer_pool_bytes);
+                        }
                         Ok((stuff.into_iter().map(GenericBuilder::ZeroCopyBinary).collect(), Box::new(guard)))
                     },
                     Err(err) => Err(format!("failed to initialize networking: {}", err))
@@ -405,7 +455,16 @@ impl<T:Send+'static> WorkerGuards<T> {
     pub fn join(mut self) -> Vec<Result<T, String>> {
         self.guards
             .drain(..)
-            .map(|guard| guard.join().map_err(|e| format!("{:?}", e)))
+            .map(|guard| guard.join().map_err(|payload| {
+                // Report the panic message, if the payload is one of the types produced by `panic!`.
+                match payload.downcast::<String>() {
+                    Ok(message) => *message,
+                    Err(payload) => match payload.downcast::<&'static str>() {
+                        Ok(message) => (*message).to_owned(),
+                        Err(payload) => format!("{:?}", payload),
+                    },
+                }
+            }))
             .collect()
     }
 }
@@ -101,7 +101,7 @@ pub mod logging;
 pub mod buzzer;
 
 pub use allocator::Generic as Allocator;
-pub use allocator::{Allocate, Exchangeable};
+pub use allocator::{Allocate, Exchangeable, PeerLocation, Topology};
 pub use initialize::{initialize, initialize_from, Config, WorkerGuards};
 
 use std::sync::mpsc::{Sender, Receiver};
@@ -160,11 +160,38 @@ pub trait Pull<T> {
     /// Takes an `Option<T>` and leaves `None` behind.
     #[inline]
     fn recv(&mut self) -> Option<T> { self.pull().take() }
+    /// Pulls a batch of elements and provides the opportunity to take ownership of each.
+    ///
+    /// As with `pull`, the puller may mutate the elements of the batch. If the batch holds no
+    /// `Some` elements, in particular if it is empty, this conventionally signals that no more data
+    /// is available at the moment. The default implementation pulls a batch of the one element of
+    /// `pull`, and implementors that can hand over several elements at once, amortizing the cost
+    /// of each call, may instead return all elements available.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    /// use timely_communication::{Push, Pull};
+    /// use timely_communication::allocator::Thread;
+    ///
+    /// let (mut pusher, mut puller) = Thread::new_from::<u64>(0, Rc::new(RefCell::new(Vec::new())));
+    /// for element in 0 .. 3 { pusher.send(element); }
+    /// pusher.done();
+    ///
+    /// let batch = puller.pull_batch().iter_mut().filter_map(Option::take).collect::<Vec<_>>();
+    /// assert_eq!(batch, vec![0, 1, 2]);
+    /// assert!(puller.pull_batch().iter().all(Option::is_none));
+    /// ```
+    #[inline]
+    fn pull_batch(&mut self) -> &mut [Option<T>] { std::slice::from_mut(self.pull()) }
 }
 
 impl<T, P: ?Sized + Pull<T>> Pull<T> for Box<P> {
     #[inline]
     fn pull(&mut self) -> &mut Option<T> { (**self).pull() }
+    #[inline]
+    fn pull_batch(&mut self) -> &mut [Option<T>] { (**self).pull_batch() }
 }
 
 
@@ -6,7 +6,7 @@ use std::net::{TcpListener, TcpStream};
 use std::sync::Arc;
 use std::thread;
 use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use byteorder::{ReadBytesExt, WriteBytesExt};
 use columnar::Columnar;
@@ -104,12 +104,20 @@ impl MessageHeader {
 /// The item at index `i` in the resulting vec, is a `Some(TcpSocket)` to process `i`, except
 /// for item `my_index` which is `None` (no socket to self).
 pub fn create_sockets(addresses: Vec<String>, my_index: usize, noisy: bool) -> Result<Vec<Option<TcpStream>>> {
+    create_sockets_within(addresses, my_index, noisy, None)
+}
+
+/// Creates socket connections from a list of host addresses, failing unless all connect within `timeout`.
+///
+/// Behaves as [`create_sockets`], which waits for other processes indefinitely, but returns an error
+/// of kind [`io::ErrorKind::TimedOut`] naming a process that did not connect within `timeout`.
+pub fn create_sockets_within(addresses: Vec<String>, my_index: usize, noisy: bool, timeout: Option<Duration>) -> Result<Vec<Option<TcpStream>>> {
 
     let hosts1 = Arc::new(addresses);
     let hosts2 = Arc::clone(&hosts1);
 
-    let start_task = thread::spawn(move || start_connections(hosts1, my_index, noisy));
-    let await_task = thread::spawn(move || await_connections(hosts2, my_index, noisy));
+    let start_task = thread::spawn(move || start_connections_within(hosts1, my_index, noisy, timeout));
+    let await_task = thread::spawn(move || await_connections_within(hosts2, my_index, noisy, timeout));
 
     let mut results = start_task.join().unwrap()?;
     results.push(None);
@@ -124,7 +132,13 @@ pub fn create_sockets(addresses: Vec<String>, my_index: usize, noisy: bool) -> R
 
 /// Result contains connections `[0, my_index - 1]`.
 pub fn start_connections(addresses: Arc<Vec<String>>, my_index: usize, noisy: bool) -> Result<Vec<Option<TcpStream>>> {
-    let results = addresses.iter().take(my_index).enumerate().map(|(index, address)| {
+    start_connections_within(addresses, my_index, noisy, None)
+}
+
+/// Result contains connections `[0, my_index - 1]`, unless some are not made within `timeout`.
+fn start_connections_within(addresses: Arc<Vec<String>>, my_index: usize, noisy: bool, timeout: Option<Duration>) -> Result<Vec<Option<TcpStream>>> {
+    let deadline = timeout.map(|timeout| (Instant::now() + timeout, timeout));
+    addresses.iter().take(my_index).enumerate().map(|(index, address)| {
         loop {
             match TcpStream::connect(address) {
                 Ok(mut stream) => {
@@ -132,26 +146,50 @@ pub fn start_connections(addresses: Arc<Vec<String>>, my_index: usize, noisy: bo
                     stream.write_u64::<ByteOrder>(HANDSHAKE_MAGIC).expect("failed to encode/send handshake magic");
                     stream.write_u64::<ByteOrder>(my_index as u64).expect("failed to encode/send worker index");
                     if noisy { println!("worker {}:\tconnection to worker {}", my_index, index); }
-                    break Some(stream);
+                    break Ok(stream);
                 },
                 Err(error) => {
+                    if let Some((_, waited)) = deadline.filter(|(deadline, _)| Instant::now() >= *deadline) {
+                        let message = format!("process {} did not connect to process {} at {} within {:?}: {}", my_index, index, address, waited, error);
+                        break Err(io::Error::new(io::ErrorKind::TimedOut, message));
+                    }
                     println!("worker {}:\terror connecting to worker {}: {}; retrying", my_index, index, error);
                     sleep(Duration::from_secs(1));
                 },
             }
         }
-    }).collect();
-
-    Ok(results)
+    }).map(|stream| stream.map(Some)).collect()
 }
 
 /// Result contains connections `[my_index + 1, addresses.len() - 1]`.
 pub fn await_connections(addresses: Arc<Vec<String>>, my_index: usize, noisy: bool) -> Result<Vec<Option<TcpStream>>> {
+    await_connections_within(addresses, my_index, noisy, None)
+}
+
+/// Result contains connections `[my_index + 1, addresses.len() - 1]`, unless some are not made within `timeout`.
+fn await_connections_within(addresses: Arc<Vec<String>>, my_index: usize, noisy: bool, timeout: Option<Duration>) -> Result<Vec<Option<TcpStream>>> {
+    let deadline = timeout.map(|timeout| (Instant::now() + timeout, timeout));
     let mut results: Vec<_> = (0..(addresses.len() - my_index - 1)).map(|_| None).collect();
     let listener = TcpListener::bind(&addresses[my_index][..])?;
+    // Poll for connections, to notice the deadline passing.
+    listener.set_nonblocking(deadline.is_some())?;
 
     for _ in (my_index + 1) .. addresses.len() {
-        let mut stream = listener.accept()?.0;
+        let mut stream = loop {
+            match listener.accept() {
+                Ok((stream, _)) => break stream,
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => {
+                    if let Some((_, waited)) = deadline.filter(|(deadline, _)| Instant::now() >= *deadline) {
+                        let missing = results.iter().enumerate().filter(|(_, stream)| stream.is_none()).map(|(index, _)| (my_index + 1 + index).to_string()).collect::<Vec<_>>();
+                        let message = format!("process {} did not receive connections from processes {} within {:?}", my_index, missing.join(", "), waited);
+                        return Err(io::Error::new(io::ErrorKind::TimedOut, message));
+                    }
+                    sleep(Duration::from_millis(10));
+                },
+                Err(error) => return Err(error),
+            }
+        };
+        stream.set_nonblocking(false)?;
         stream.set_nodelay(true).expect("set_nodelay call failed");
         let mut buffer = [0u8;16];
         stream.read_exact(&mut buffer)?;
@@ -51,6 +51,20 @@ pub trait SizableContainer {
     /// Assume that the `stash` is in an undefined state, and properly clear it
     /// before re-using it.
     fn ensure_capacity(&mut self, stash: &mut Option<Self>) where Self: Sized;
+    /// Restores `self` to a capacity of `capacity` elements, if it can control its capacity.
+    ///
+    /// The same considerations about `stash` apply as for [`Self::ensure_capacity`]. The default
+    /// implementation ignores `capacity` and restores the desired capacity instead.
+    #[inline]
+    fn ensure_capacity_for(&mut self, stash: &mut Option<Self>, _capacity: usize) where Self: Sized {
+        self.ensure_capacity(stash)
+    }
+    /// The number of bytes `self` has allocated to hold its elements, if it can tell.
+    ///
+    /// Used to account for the allocations of containers, for example by
+    /// [`strategy::Instrumented`]. The default implementation reports zero.
+    #[inline]
+    fn allocated_bytes(&self) -> usize { 0 }
 }
 
 /// A container that can absorb items of a specific type.
@@ -121,22 +135,33 @@ pub trait LengthPreservingContainerBuilder : ContainerBuilder { }
 /// Maintains a single empty allocation between [`Self::push_into`] and [`Self::extract`], but not
 /// across [`Self::finish`] to maintain a low memory footprint.
 ///
+/// The capacity of each container is chosen by the [`AllocationStrategy`] `S`, which defaults to
+/// the container's preferred capacity.
+///
 /// Maintains FIFO order.
 #[derive(Default, Debug)]
-pub struct CapacityContainerBuilder<C>{
+pub struct CapacityContainerBuilder<C, S = strategy::Preferred>{
     /// Container that we're writing to.
     current: C,
     /// Empty allocation.
     empty: Option<C>,
     /// Completed containers pending to be sent.
     pending: VecDeque<C>,
+    /// Policy for the capacity of new containers.
+    strategy: S,
 }
 
-impl<T, C: SizableContainer + Default + PushInto<T>> PushInto<T> for CapacityContainerBuilder<C> {
+impl<C, S> CapacityContainerBuilder<C, S> {
+    /// The allocation strategy of the builder, for example to obtain the metrics of a [`strategy::Instrumented`] strategy.
+    #[inline]
+    pub fn strategy(&self) -> &S { &self.strategy }
+}
+
+impl<T, C: SizableContainer + Default + PushInto<T>, S: AllocationStrategy> PushInto<T> for CapacityContainerBuilder<C, S> {
     #[inline]
     fn push_into(&mut self, item: T) {
         // Ensure capacity
-        self.current.ensure_capacity(&mut self.empty);
+        self.strategy.ensure_capacity(&mut self.current, &mut self.empty);
 
         // Push item
         self.current.push_into(item);
@@ -144,11 +169,12 @@ impl<T, C: SizableContainer + Default + PushInto<T>> PushInto<T> for CapacityCon
         // Maybe flush
         if self.current.at_capacity() {
             self.pending.push_back(std::mem::take(&mut self.current));
+            self.strategy.shipped();
         }
     }
 }
 
-impl<C: Accountable + Default> ContainerBuilder for CapacityContainerBuilder<C> {
+impl<C: Accountable + Default, S: AllocationStrategy> ContainerBuilder for CapacityContainerBuilder<C, S> {
     type Container = C;
 
     #[inline]
@@ -166,12 +192,474 @@ impl<C: Accountable + Default> ContainerBuilder for CapacityContainerBuilder<C>
         if !self.current.is_empty() {
             self.pending.push_back(std::mem::take(&mut self.current));
         }
+        self.strategy.finished();
         self.empty = self.pending.pop_front();
         self.empty.as_mut()
     }
 }
 
-impl<C: Accountable + SizableContainer + Default> LengthPreservingContainerBuilder for CapacityContainerBuilder<C> { }
+impl<C: Accountable + SizableContainer + Default, S: AllocationStrategy> LengthPreservingContainerBuilder for CapacityContainerBuilder<C, S> { }
+
+/// A policy for the capacity of containers formed by a [`CapacityContainerBuilder`].
+///
+/// Implementations in the [`strategy`] module size containers at their preferred capacity,
+/// at a fixed capacity, or at geometrically growing capacities.
+pub trait AllocationStrategy: Default {
+    /// Prepares `container` to receive another element, possibly using the allocation in `stash`.
+    fn ensure_capacity<C: SizableContainer>(&mut self, container: &mut C, stash: &mut Option<C>);
+    /// Indicates that a container reached its capacity and was set aside to be sent.
+    #[inline]
+    fn shipped(&mut self) { }
+    /// Indicates that the builder was drained with [`ContainerBuilder::finish`].
+    #[inline]
+    fn finished(&mut self) { }
+}
+
+pub mod strategy {
+    //! Allocation strategies for [`CapacityContainerBuilder`](crate::CapacityContainerBuilder).
+
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use crate::{AllocationStrategy, SizableContainer};
+
+    /// Sizes containers at their preferred capacity, as determined by [`SizableContainer::ensure_capacity`].
+    #[derive(Default, Debug, Clone, Copy)]
+    pub struct Preferred;
+
+    impl AllocationStrategy for Preferred {
+        #[inline]
+        fn ensure_capacity<C: SizableContainer>(&mut self, container: &mut C, stash: &mut Option<C>) {
+            container.ensure_capacity(stash);
+        }
+    }
+
+    /// Sizes containers to hold `N` elements.
+    ///
+    /// Suited to workloads with predictable batch sizes, where containers of the batch size
+    /// avoid both reallocation and over-allocation.
+    #[derive(Default, Debug, Clone, Copy)]
+    pub struct Fixed<const N: usize>;
+
+    impl<const N: usize> AllocationStrategy for Fixed<N> {
+        #[inline]
+        fn ensure_capacity<C: SizableContainer>(&mut self, container: &mut C, stash: &mut Option<C>) {
+            container.ensure_capacity_for(stash, N);
+        }
+    }
+
+    /// Sizes the first container after each `finish` to hold `INITIAL` elements, and each following
+    /// container to hold twice as many as its predecessor, up to `MAX` elements.
+    ///
+    /// Suited to workloads with varying batch sizes, where small batches should not pay for large
+    /// allocations but large batches should not be split into many small containers.
+    #[derive(Default, Debug, Clone, Copy)]
+    pub struct Geometric<const INITIAL: usize, const MAX: usize> {
+        /// The capacity of the next container, or zero if it should be `INITIAL`.
+        next: usize,
+    }
+
+    impl<const INITIAL: usize, const MAX: usize> Geometric<INITIAL, MAX> {
+        #[inline]
+        fn capacity(&self) -> usize {
+            if self.next == 0 { INITIAL.min(MAX) } else { self.next }
+        }
+    }
+
+    impl<const INITIAL: usize, const MAX: usize> AllocationStrategy for Geometric<INITIAL, MAX> {
+        #[inline]
+        fn ensure_capacity<C: SizableContainer>(&mut self, container: &mut C, stash: &mut Option<C>) {
+            container.ensure_capacity_for(stash, self.capacity());
+        }
+        #[inline]
+        fn shipped(&mut self) {
+            self.next = self.capacity().saturating_mul(2).min(MAX);
+        }
+        #[inline]
+        fn finished(&mut self) {
+            self.next = 0;
+        }
+    }
+
+    /// The allocations of containers counted by an [`Instrumented`] strategy.
+    ///
+    /// Bytes are those reported by [`SizableContainer::allocated_bytes`], and containers that do
+    /// not report them are counted with zero bytes. A resized allocation counts the bytes of its
+    /// new size.
+    #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct AllocationCounts {
+        /// Allocations made for containers that had none.
+        pub allocations: usize,
+        /// Allocations resized, or replaced by an allocation of another size.
+        pub reallocations: usize,
+        /// Stashed allocations reused as they were.
+        pub recycled: usize,
+        /// Bytes of the allocations made and resized.
+        pub bytes: usize,
+    }
+
+    /// A handle to the [`AllocationCounts`] of an [`Instrumented`] strategy.
+    ///
+    /// Clones of a handle share its counts, and remain valid after the strategy is dropped.
+    #[derive(Default, Debug, Clone)]
+    pub struct AllocationMetrics {
+        counts: Rc<Cell<AllocationCounts>>,
+    }
+
+    impl AllocationMetrics {
+        /// The allocations counted so far.
+        #[inline]
+        pub fn counts(&self) -> AllocationCounts { self.counts.get() }
+        /// Resets the counts to zero.
+        #[inline]
+        pub fn reset(&self) { self.counts.take(); }
+    }
+
+    /// Sizes containers as the strategy `S` does, and counts the allocations it makes.
+    ///
+    /// The counts are read through the [`AllocationMetrics`] of [`Self::metrics`], which a
+    /// [`CapacityContainerBuilder`](crate::CapacityContainerBuilder) exposes through its
+    /// [`strategy`](crate::CapacityContainerBuilder::strategy). The builder sizes containers only
+    /// when it is about to push into them, and ships them once full, so that these are all of
+    /// its allocations. Strategies other than `Instrumented` count nothing, and have no overhead.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely_container::{CapacityContainerBuilder, ContainerBuilder, PushInto};
+    /// use timely_container::strategy::{Fixed, Instrumented};
+    ///
+    /// let mut builder = CapacityContainerBuilder::<Vec<u64>, Instrumented<Fixed<4>>>::default();
+    /// let metrics = builder.strategy().metrics();
+    /// for record in 0 .. 10 {
+    ///     builder.push_into(record);
+    ///     while let Some(container) = builder.extract() {
+    ///         std::mem::take(container);
+    ///     }
+    /// }
+    /// // Each container sent away leaves the builder to allocate the next.
+    /// assert_eq!(metrics.counts().allocations, 3);
+    /// assert_eq!(metrics.counts().bytes, 3 * 4 * 8);
+    /// ```
+    #[derive(Default, Debug)]
+    pub struct Instrumented<S = Preferred> {
+        inner: S,
+        metrics: AllocationMetrics,
+    }
+
+    impl<S> Instrumented<S> {
+        /// A handle to the counts of the strategy.
+        #[inline]
+        pub fn metrics(&self) -> AllocationMetrics { self.metrics.clone() }
+    }
+
+    impl<S: AllocationStrategy> AllocationStrategy for Instrumented<S> {
+        #[inline]
+        fn ensure_capacity<C: SizableContainer>(&mut self, container: &mut C, stash: &mut Option<C>) {
+            let before = container.allocated_bytes();
+            let stashed = stash.as_ref().map_or(0, C::allocated_bytes);
+            self.inner.ensure_capacity(container, stash);
+            let after = container.allocated_bytes();
+            if after == before {
+                return;
+            }
+            // The allocation the container started from: its own, or the stashed one it took.
+            let taken = before == 0 && stashed > 0 && stash.as_ref().map_or(0, C::allocated_bytes) == 0;
+            let start = if taken { stashed } else { before };
+            let mut counts = self.metrics.counts.get();
+            if start == 0 {
+                counts.allocations += 1;
+                counts.bytes += after;
+            } else if after != start {
+                counts.reallocations += 1;
+                counts.bytes += after;
+            } else {
+                counts.recycled += 1;
+            }
+            self.metrics.counts.set(counts);
+        }
+        #[inline]
+        fn shipped(&mut self) { self.inner.shipped(); }
+        #[inline]
+        fn finished(&mut self) { self.inner.finished(); }
+    }
+}
+
+/// Assigns records to buckets, for use in a [`BucketingContainerBuilder`].
+///
+/// The bucketer is a type rather than a closure, as container builders are constructed with
+/// [`Default`]. Implementations may keep state, for example to remember a bucket width.
+pub trait Bucketer<T, B>: Default {
+    /// The bucket to which `item` belongs.
+    fn bucket(&mut self, item: &T) -> B;
+}
+
+/// A container builder that groups pushed records into one container per bucket.
+///
+/// Each pushed record is assigned a bucket by the [`Bucketer`] `F`, and pushed into a container
+/// for that bucket. Containers are tagged with their bucket, and extracted as `(bucket, container)`
+/// pairs: [`Self::extract`] returns buckets whose container reached its capacity, in the order in
+/// which they did, and [`Self::finish`] additionally returns all partially filled containers in
+/// increasing order of bucket. Within a bucket the order of records is preserved.
+///
+/// # Examples
+/// ```
+/// use timely_container::{Bucketer, BucketingContainerBuilder, ContainerBuilder, PushInto};
+///
+/// /// Buckets `(time, data)` pairs into windows of ten time units.
+/// #[derive(Default)]
+/// struct Window;
+/// impl Bucketer<(u64, char), u64> for Window {
+///     fn bucket(&mut self, (time, _): &(u64, char)) -> u64 { time / 10 }
+/// }
+///
+/// let mut builder = BucketingContainerBuilder::<u64, Vec<(u64, char)>, Window>::default();
+/// for record in [(13, 'a'), (2, 'b'), (17, 'c'), (5, 'd')] {
+///     builder.push_into(record);
+/// }
+/// let mut extracted = Vec::new();
+/// while let Some((window, records)) = builder.finish() {
+///     extracted.push((*window, std::mem::take(records)));
+/// }
+/// assert_eq!(extracted, vec![(0, vec![(2, 'b'), (5, 'd')]), (1, vec![(13, 'a'), (17, 'c')])]);
+/// ```
+#[derive(Debug)]
+pub struct BucketingContainerBuilder<B, C, F> {
+    /// Assigns records to buckets.
+    bucketer: F,
+    /// Containers being filled, for each bucket.
+    buckets: std::collections::BTreeMap<B, C>,
+    /// Completed containers pending to be sent.
+    pending: VecDeque<(B, C)>,
+    /// The most recently extracted container.
+    current: Option<(B, C)>,
+    /// Empty allocation.
+    empty: Option<C>,
+}
+
+impl<B, C, F: Default> Default for BucketingContainerBuilder<B, C, F> {
+    fn default() -> Self {
+        Self {
+            bucketer: F::default(),
+            buckets: Default::default(),
+            pending: Default::default(),
+            current: None,
+            empty: None,
+        }
+    }
+}
+
+impl<B, C, F> BucketingContainerBuilder<B, C, F> {
+    /// Stores `container` as the extracted container, recycling the allocation of its predecessor.
+    #[inline]
+    fn set_current(&mut self, container: Option<(B, C)>) -> Option<&mut (B, C)> {
+        if let Some((_, previous)) = std::mem::replace(&mut self.current, container) {
+            self.empty = Some(previous);
+        }
+        self.current.as_mut()
+    }
+}
+
+impl<T, B, C, F> PushInto<T> for BucketingContainerBuilder<B, C, F>
+where
+    B: Ord + Clone,
+    C: SizableContainer + Default + PushInto<T>,
+    F: Bucketer<T, B>,
+{
+    #[inline]
+    fn push_into(&mut self, item: T) {
+        let bucket = self.bucketer.bucket(&item);
+        let empty = &mut self.empty;
+        let container = self.buckets.entry(bucket.clone()).or_insert_with(|| {
+            let mut container = C::default();
+            container.ensure_capacity(empty);
+            container
+        });
+        container.push_into(item);
+        if container.at_capacity() {
+            let full = self.buckets.remove(&bucket).expect("bucket present");
+            self.pending.push_back((bucket, full));
+        }
+    }
+}
+
+impl<B: Ord, C: Accountable + Default, F: Default> ContainerBuilder for BucketingContainerBuilder<B, C, F> {
+    type Container = (B, C);
+
+    #[inline]
+    fn extract(&mut self) -> Option<&mut (B, C)> {
+        match self.pending.pop_front() {
+            Some(container) => self.set_current(Some(container)),
+            None => None,
+        }
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Option<&mut (B, C)> {
+        if self.pending.is_empty() {
+            self.pending.extend(std::mem::take(&mut self.buckets));
+        }
+        let container = self.pending.pop_front();
+        self.set_current(container)
+    }
+
+    #[inline]
+    fn relax(&mut self) {
+        self.empty = None;
+    }
+}
+
+impl<B: Ord, C: Accountable + Default, F: Default> LengthPreservingContainerBuilder for BucketingContainerBuilder<B, C, F> { }
+
+/// When a [`PolicyContainerBuilder`] extracts the partially filled containers of its builder.
+///
+/// Extracting containers early trades throughput for latency: records are sent sooner, in more
+/// and smaller containers. Whatever the policy, full containers are extracted as they fill.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtractionPolicy {
+    /// Extracts containers only once full, as the wrapped builder does.
+    #[default]
+    Capacity,
+    /// Extracts all containers once the given number of records were pushed since the last extraction of all of them.
+    Records(usize),
+    /// Extracts all containers once records of the given number of bytes were pushed since the last extraction of all of them.
+    ///
+    /// The bytes of a record are `size_of::<T>()` of its type `T`, and do not include allocations it owns.
+    Bytes(usize),
+    /// Extracts all containers once the given interval passed since the first record pushed after the last extraction of all of them.
+    ///
+    /// The interval is checked when [`ContainerBuilder::extract`] is called. An operator that
+    /// otherwise has no reason to run can request to be scheduled once the interval passes,
+    /// for example with `Activator::activate_after`, to extract records held past it.
+    Interval(std::time::Duration),
+}
+
+/// A container builder that extracts the containers of another builder according to an [`ExtractionPolicy`].
+///
+/// Pushed records are passed to the wrapped builder `CB`, and [`Self::extract`] returns its full
+/// containers. Once the policy is met it also returns its partially filled containers, as
+/// [`Self::finish`] would, and starts anew. The default policy is [`ExtractionPolicy::Capacity`],
+/// which behaves as `CB` does.
+///
+/// As builders of operators are constructed with [`Default`], an operator that does not own its
+/// builder sets the policy through the builder of its session, with [`Self::set_policy`].
+///
+/// # Examples
+/// ```
+/// use timely_container::{CapacityContainerBuilder, ContainerBuilder, ExtractionPolicy, PolicyContainerBuilder, PushInto};
+///
+/// let policy = ExtractionPolicy::Records(3);
+/// let mut builder = PolicyContainerBuilder::<CapacityContainerBuilder<Vec<u64>>>::with_policy(policy);
+/// let mut extracted = Vec::new();
+/// for record in 0 .. 7 {
+///     builder.push_into(record);
+///     while let Some(container) = builder.extract() {
+///         extracted.push(std::mem::take(container));
+///     }
+/// }
+/// assert_eq!(extracted, vec![vec![0, 1, 2], vec![3, 4, 5]]);
+/// ```
+#[derive(Default, Debug)]
+pub struct PolicyContainerBuilder<CB> {
+    /// The wrapped builder.
+    inner: CB,
+    /// When to extract partially filled containers.
+    policy: ExtractionPolicy,
+    /// Records pushed since all containers were last extracted.
+    records: usize,
+    /// Bytes of the records pushed since all containers were last extracted.
+    bytes: usize,
+    /// When the first record since all containers were last extracted was pushed.
+    since: Option<std::time::Instant>,
+    /// Whether the policy was met, and partially filled containers are being extracted.
+    flushing: bool,
+}
+
+impl<CB: Default> PolicyContainerBuilder<CB> {
+    /// Constructs a builder extracting the containers of a default `CB` according to `policy`.
+    pub fn with_policy(policy: ExtractionPolicy) -> Self {
+        Self { policy, ..Default::default() }
+    }
+}
+
+impl<CB> PolicyContainerBuilder<CB> {
+    /// The policy by which partially filled containers are extracted.
+    #[inline]
+    pub fn policy(&self) -> ExtractionPolicy { self.policy }
+
+    /// Sets the policy by which partially filled containers are extracted, from the next call to [`Self::extract`].
+    #[inline]
+    pub fn set_policy(&mut self, policy: ExtractionPolicy) { self.policy = policy; }
+
+    /// Returns `true` if the records pushed since all containers were last extracted meet the policy.
+    #[inline]
+    fn triggered(&self) -> bool {
+        match self.policy {
+            ExtractionPolicy::Capacity => false,
+            ExtractionPolicy::Records(records) => self.records > 0 && self.records >= records,
+            ExtractionPolicy::Bytes(bytes) => self.records > 0 && self.bytes >= bytes,
+            ExtractionPolicy::Interval(interval) => self.since.is_some_and(|since| since.elapsed() >= interval),
+        }
+    }
+
+    /// Forgets the records pushed, as all containers were extracted.
+    #[inline]
+    fn reset(&mut self) {
+        self.records = 0;
+        self.bytes = 0;
+        self.since = None;
+    }
+}
+
+impl<T, CB: PushInto<T>> PushInto<T> for PolicyContainerBuilder<CB> {
+    #[inline]
+    fn push_into(&mut self, item: T) {
+        self.records += 1;
+        self.bytes += std::mem::size_of::<T>();
+        if let ExtractionPolicy::Interval(_) = self.policy {
+            self.since.get_or_insert_with(std::time::Instant::now);
+        }
+        self.inner.push_into(item);
+    }
+}
+
+impl<CB: ContainerBuilder> ContainerBuilder for PolicyContainerBuilder<CB> {
+    type Container = CB::Container;
+
+    #[inline]
+    fn extract(&mut self) -> Option<&mut CB::Container> {
+        if !self.flushing && self.triggered() {
+            self.flushing = true;
+            self.reset();
+        }
+        if self.flushing {
+            let container = self.inner.finish();
+            self.flushing = container.is_some();
+            container
+        } else {
+            self.inner.extract()
+        }
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Option<&mut CB::Container> {
+        self.flushing = false;
+        self.reset();
+        self.inner.finish()
+    }
+
+    #[inline]
+    fn relax(&mut self) {
+        self.inner.relax();
+    }
+}
+
+impl<CB: LengthPreservingContainerBuilder> LengthPreservingContainerBuilder for PolicyContainerBuilder<CB> { }
+
+// Containers tagged with a value, such as the bucket of a `BucketingContainerBuilder`, count the records of the container.
+impl<B, C: Accountable> Accountable for (B, C) {
+    #[inline] fn record_count(&self) -> i64 { self.1.record_count() }
+    #[inline] fn is_empty(&self) -> bool { self.1.is_empty() }
+}
 
 impl<T> Accountable for Vec<T> {
     #[inline] fn record_count(&self) -> i64 { i64::try_from(Vec::len(self)).unwrap() }
@@ -200,6 +688,23 @@ impl<T> SizableContainer for Vec<T> {
             self.reserve(preferred - self.capacity());
         }
     }
+    fn ensure_capacity_for(&mut self, stash: &mut Option<Self>, capacity: usize) {
+        let capacity = capacity.max(1);
+        if self.capacity() == 0 {
+            *self = stash.take().unwrap_or_default();
+            self.clear();
+        }
+        if self.is_empty() && self.capacity() != capacity {
+            // The stashed allocation has the wrong size; replace it rather than grow it.
+            *self = Vec::with_capacity(capacity);
+        } else if self.capacity() < capacity {
+            self.reserve_exact(capacity - self.len());
+        }
+    }
+    #[inline]
+    fn allocated_bytes(&self) -> usize {
+        self.capacity() * std::mem::size_of::<T>()
+    }
 }
 
 impl<T> PushInto<T> for Vec<T> {
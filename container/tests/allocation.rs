@@ -0,0 +1,109 @@
+//! Compares the allocations of `CapacityContainerBuilder` under its allocation strategies.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use timely_container::{AllocationStrategy, CapacityContainerBuilder, ContainerBuilder, PushInto};
+use timely_container::strategy::{Fixed, Geometric, Instrumented, Preferred};
+
+/// Counts the allocations, and their bytes, made by the current thread.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<(usize, usize)> = const { Cell::new((0, 0)) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| {
+            let (allocations, bytes) = count.get();
+            count.set((allocations + 1, bytes + layout.size()));
+        });
+        unsafe { System.alloc(layout) }
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Pushes `batches` batches of `batch` records, takes ownership of all containers, and
+/// reports the allocations made along with the number of containers formed.
+fn measure<S: AllocationStrategy>(batches: usize, batch: usize) -> (usize, usize, usize) {
+    let mut builder = CapacityContainerBuilder::<Vec<u64>, S>::default();
+    let mut containers = Vec::with_capacity(batches * batch);
+    let before = ALLOCATIONS.with(Cell::get);
+    for _ in 0 .. batches {
+        for record in 0 .. batch as u64 {
+            builder.push_into(record);
+            while let Some(container) = builder.extract() {
+                containers.push(std::mem::take(container));
+            }
+        }
+        while let Some(container) = builder.finish() {
+            containers.push(std::mem::take(container));
+        }
+    }
+    let after = ALLOCATIONS.with(Cell::get);
+    assert_eq!(containers.iter().map(Vec::len).sum::<usize>(), batches * batch);
+    (after.0 - before.0, after.1 - before.1, containers.len())
+}
+
+#[test]
+fn allocation_strategies() {
+    let (batches, batch) = (50, 100);
+
+    let (preferred_allocs, preferred_bytes, preferred_containers) = measure::<Preferred>(batches, batch);
+    let (fixed_allocs, fixed_bytes, fixed_containers) = measure::<Fixed<100>>(batches, batch);
+    let (geometric_allocs, geometric_bytes, geometric_containers) = measure::<Geometric<16, 1024>>(batches, batch);
+
+    // The preferred capacity exceeds the batch size: one container per batch, mostly unused.
+    assert_eq!(preferred_containers, batches);
+    // A fixed capacity matching the batch size allocates the same number of containers, but
+    // only the bytes that are needed.
+    assert_eq!(fixed_containers, batches);
+    assert_eq!(fixed_allocs, preferred_allocs);
+    assert_eq!(preferred_bytes - fixed_bytes, batches * (timely_container::buffer::default_capacity::<u64>() - batch) * std::mem::size_of::<u64>());
+    // Geometric growth uses 16, 32, and 64 element containers for each batch.
+    assert_eq!(geometric_containers, 3 * batches);
+    assert_eq!(geometric_allocs, fixed_allocs + 2 * batches);
+    assert!(geometric_bytes < preferred_bytes);
+}
+
+#[test]
+fn instrumented_strategies_count_reallocations() {
+    let mut builder = CapacityContainerBuilder::<Vec<u64>, Instrumented<Geometric<2, 8>>>::default();
+    let metrics = builder.strategy().metrics();
+    // Warms up the builder's queue of pending containers, whose allocation is not a container's.
+    builder.push_into(0);
+    while builder.finish().is_some() { }
+    metrics.reset();
+
+    let before = ALLOCATIONS.with(Cell::get);
+    // Extracted containers are left with the builder, which resizes them to 2, 4, and 8 elements.
+    for record in 0 .. 14 {
+        builder.push_into(record);
+        while let Some(container) = builder.extract() {
+            assert!(!container.is_empty());
+        }
+    }
+    let counts = metrics.counts();
+    assert_eq!((counts.allocations, counts.reallocations, counts.recycled), (1, 2, 0));
+    assert_eq!(counts.bytes, (2 + 4 + 8) * std::mem::size_of::<u64>());
+    // Containers of the largest size are recycled as they are.
+    for record in 0 .. 16 {
+        builder.push_into(record);
+        while let Some(container) = builder.extract() {
+            assert_eq!(container.len(), 8);
+        }
+    }
+    assert_eq!(metrics.counts().recycled, 2);
+    let after = ALLOCATIONS.with(Cell::get);
+
+    // The counts agree with the allocations the builder made.
+    let total = metrics.counts();
+    assert_eq!(total.allocations + total.reallocations, after.0 - before.0);
+    assert_eq!(total.bytes, after.1 - before.1);
+}
@@ -0,0 +1,79 @@
+//! Compares how often `PolicyContainerBuilder` extracts containers under its extraction policies.
+
+use std::time::Duration;
+
+use timely_container::{CapacityContainerBuilder, ContainerBuilder, ExtractionPolicy, PolicyContainerBuilder, PushInto};
+
+type Builder = PolicyContainerBuilder<CapacityContainerBuilder<Vec<u64>>>;
+
+/// Pushes `records` records, extracting after each one, and reports the sizes of the containers
+/// extracted before and by `finish`.
+fn extractions(builder: &mut Builder, records: u64) -> (Vec<usize>, Vec<usize>) {
+    let mut extracted = Vec::new();
+    for record in 0 .. records {
+        builder.push_into(record);
+        while let Some(container) = builder.extract() {
+            extracted.push(std::mem::take(container).len());
+        }
+    }
+    let mut finished = Vec::new();
+    while let Some(container) = builder.finish() {
+        finished.push(std::mem::take(container).len());
+    }
+    (extracted, finished)
+}
+
+#[test]
+fn capacity_extracts_only_full_containers() {
+    let capacity = timely_container::buffer::default_capacity::<u64>();
+    let mut builder = Builder::default();
+    assert_eq!(builder.policy(), ExtractionPolicy::Capacity);
+    let (extracted, finished) = extractions(&mut builder, capacity as u64 + 10);
+    assert_eq!(extracted, vec![capacity]);
+    assert_eq!(finished, vec![10]);
+}
+
+#[test]
+fn records_and_bytes_extract_after_their_amounts() {
+    let mut records = Builder::with_policy(ExtractionPolicy::Records(10));
+    assert_eq!(extractions(&mut records, 45), (vec![10; 4], vec![5]));
+
+    // Ten records of eight bytes each.
+    let mut bytes = Builder::with_policy(ExtractionPolicy::Bytes(80));
+    assert_eq!(extractions(&mut bytes, 45), (vec![10; 4], vec![5]));
+
+    // The counts start anew after `finish`.
+    let mut reused = Builder::with_policy(ExtractionPolicy::Records(4));
+    assert_eq!(extractions(&mut reused, 3), (vec![], vec![3]));
+    assert_eq!(extractions(&mut reused, 3), (vec![], vec![3]));
+}
+
+#[test]
+fn intervals_extract_after_they_pass() {
+    // A zero interval extracts each record as it is pushed.
+    let mut zero = Builder::with_policy(ExtractionPolicy::Interval(Duration::ZERO));
+    assert_eq!(extractions(&mut zero, 5), (vec![1; 5], vec![]));
+
+    // A long interval does not pass while records are pushed.
+    let mut long = Builder::with_policy(ExtractionPolicy::Interval(Duration::from_secs(3600)));
+    assert_eq!(extractions(&mut long, 5), (vec![], vec![5]));
+
+    // Records are held until the interval passes, measured from the first of them.
+    let mut builder = Builder::with_policy(ExtractionPolicy::Interval(Duration::from_millis(20)));
+    builder.push_into(0);
+    builder.push_into(1);
+    assert!(builder.extract().is_none());
+    std::thread::sleep(Duration::from_millis(30));
+    assert_eq!(builder.extract().map(std::mem::take), Some(vec![0, 1]));
+    assert!(builder.extract().is_none());
+    builder.push_into(2);
+    assert!(builder.extract().is_none());
+}
+
+#[test]
+fn policies_can_change() {
+    let mut builder = Builder::default();
+    assert_eq!(extractions(&mut builder, 5), (vec![], vec![5]));
+    builder.set_policy(ExtractionPolicy::Records(2));
+    assert_eq!(extractions(&mut builder, 5), (vec![2, 2], vec![1]));
+}
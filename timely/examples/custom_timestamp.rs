@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use timely::order::PartialOrder;
+use timely::dataflow::Stream;
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::{Exchange, Operator, Probe, UnorderedInput};
+
+/// A time at which both the schema and the data of a table may change, each with its own version.
+///
+/// Times are ordered if both of their versions are, so that a change to the schema and a change to
+/// the data can complete independently of each other.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+struct Versions {
+    schema: u32,
+    data: u32,
+}
+
+impl PartialOrder for Versions {
+    fn less_equal(&self, other: &Self) -> bool {
+        self.schema <= other.schema && self.data <= other.data
+    }
+}
+
+timely::implement_timestamp!(Versions, Versions { schema: 0, data: 0 });
+
+fn main() {
+    // initializes and runs a timely dataflow.
+    timely::execute_from_args(std::env::args(), |worker| {
+
+        let index = worker.index();
+
+        // counts the records of each time, and reports them once the time is complete.
+        let ((mut input, capability), probe) = worker.dataflow::<Versions,_,_>(|scope| {
+            let (handles, stream) = scope.new_unordered_input::<u64>();
+            let mut counts = HashMap::new();
+            let completed: Stream<_, usize> =
+            stream
+                .exchange(|x| *x)
+                .unary_notify(Pipeline, "Count", vec![], move |input, output, notificator| {
+                    input.for_each_time(|time, data| {
+                        *counts.entry(time.time().clone()).or_insert(0) += data.map(|d| d.len()).sum::<usize>();
+                        notificator.notify_at(time.retain());
+                    });
+                    notificator.for_each(|time, _count, _notificator| {
+                        let count = counts.remove(time.time()).unwrap_or(0);
+                        println!("worker {}:\t{:?} complete with {} records", index, time.time(), count);
+                        output.session(&time).give(count);
+                    });
+                });
+            (handles, completed.probe())
+        });
+
+        // changes to the data, and then to the schema, each completing on its own.
+        let mut data = capability.delayed(&Versions { schema: 0, data: 1 });
+        let mut schema = capability.delayed(&Versions { schema: 1, data: 0 });
+        input.activate().session(&capability).give(index as u64);
+        drop(capability);
+
+        for round in 1..5 {
+            input.activate().session(&data).give(round as u64);
+            data.downgrade(&Versions { schema: 0, data: round + 1 });
+            while probe.less_equal(&Versions { schema: 0, data: round }) {
+                worker.step();
+            }
+            // the schema has not changed, and so times of the next schema are not complete.
+            assert!(probe.less_equal(&Versions { schema: 1, data: 0 }));
+        }
+
+        input.activate().session(&schema).give(100);
+        schema.downgrade(&Versions { schema: 2, data: 0 });
+        drop((input, data, schema));
+        while !probe.done() {
+            worker.step();
+        }
+    }).unwrap();
+}
@@ -23,7 +23,7 @@ fn main() {
         let mut input2 = InputHandle::new();
         let probe = ProbeHandle::new();
 
-        worker.dataflow(|scope| {
+        worker.dataflow::<u64,_,_>(|scope| {
 
             let stream1 = scope.input_from(&mut input1);
             let stream2 = scope.input_from(&mut input2);
@@ -0,0 +1,43 @@
+//! Compares sending records one at a time with sending them as assembled containers.
+//!
+//! Run with `cargo run --release --example send_container -- <batch> <rounds>`.
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Probe};
+
+fn main() {
+    // initializes and runs a timely dataflow.
+    timely::execute_from_args(std::env::args(), |worker| {
+
+        let batch = std::env::args().nth(1).map_or(1_000_000, |arg| arg.parse::<usize>().unwrap());
+        let rounds = std::env::args().nth(2).map_or(100, |arg| arg.parse::<usize>().unwrap());
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow::<u64,_,_>(|scope| scope.input_from(&mut input).probe());
+
+        // The containers are assembled before the timer starts, as if read from a file or a network frame.
+        let mut containers = (0 .. rounds).map(|_| (0 .. batch).collect::<Vec<usize>>()).collect::<Vec<_>>();
+
+        let mut timer = std::time::Instant::now();
+        for container in containers.iter() {
+            for record in container.iter() {
+                input.send(*record);
+            }
+            input.advance_to(input.time() + 1);
+            while probe.less_than(input.time()) {
+                worker.step();
+            }
+        }
+        println!("worker {}\tsend:           {:?}", worker.index(), timer.elapsed());
+
+        timer = std::time::Instant::now();
+        for container in containers.drain(..) {
+            input.send_container(container);
+            input.advance_to(input.time() + 1);
+            while probe.less_than(input.time()) {
+                worker.step();
+            }
+        }
+        println!("worker {}\tsend_container: {:?}", worker.index(), timer.elapsed());
+
+    }).unwrap();
+}
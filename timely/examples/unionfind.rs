@@ -22,7 +22,7 @@ fn main() {
         let mut input = InputHandle::new();
         let probe = ProbeHandle::new();
 
-        worker.dataflow(|scope| {
+        worker.dataflow::<u64,_,_>(|scope| {
             scope.input_from(&mut input)
                 //  .exchange(move |x: &(usize, usize)| (x.0 % (peers - 1)) as u64 + 1)
                  .union_find()
@@ -0,0 +1,293 @@
+//! Codecs that serialize the containers of messages exchanged between processes.
+//!
+//! Messages sent between processes are serialized as their source, sequence number, and time,
+//! followed by their container, which a [`Codec`] serializes. Exchange pacts use the [`Native`]
+//! codec, which defers to the container's [`ContainerBytes`] implementation, unless wrapped with
+//! [`WithCodec`](crate::dataflow::channels::pact::WithCodec) to select another. With the
+//! `abomonation` feature, `Abomonated` selects the `abomonation` crate, which serializes containers
+//! as their bytes in memory.
+
+use std::io::Write;
+use std::marker::PhantomData;
+
+use bytemuck::Pod;
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+
+use crate::bytes::arc::Bytes;
+use crate::communication::Bytesable;
+use crate::dataflow::channels::{ContainerBytes, Message};
+
+/// Serializes containers of type `C`.
+///
+/// Serialized containers must be a multiple of eight bytes long, so that messages following them
+/// in a buffer remain aligned.
+pub trait Codec<C> {
+    /// Deserializes a container from `bytes`, which may extend past its end.
+    fn decode(bytes: Bytes) -> C;
+    /// The number of bytes `encode` writes for `container`.
+    fn length_in_bytes(container: &C) -> usize;
+    /// Serializes `container` into `writer`.
+    fn encode<W: Write>(container: &C, writer: &mut W);
+}
+
+/// Serializes containers with their [`ContainerBytes`] implementation.
+#[derive(Debug)]
+pub struct Native;
+
+impl<C: ContainerBytes> Codec<C> for Native {
+    fn decode(bytes: Bytes) -> C { C::from_bytes(bytes) }
+    fn length_in_bytes(container: &C) -> usize { container.length_in_bytes() }
+    fn encode<W: Write>(container: &C, writer: &mut W) { container.into_bytes(writer) }
+}
+
+/// Serializes containers with `bincode`, which validates the bytes it decodes.
+///
+/// Applies to any container that implements `serde` traits, including those without a
+/// [`ContainerBytes`] implementation.
+#[derive(Debug)]
+pub struct Bincode;
+
+impl<C: Serialize + DeserializeOwned> Codec<C> for Bincode {
+    fn decode(bytes: Bytes) -> C {
+        ::bincode::deserialize(&bytes[..]).expect("bincode::deserialize() failed")
+    }
+    fn length_in_bytes(container: &C) -> usize {
+        let length = ::bincode::serialized_size(container).expect("bincode::serialized_size() failed") as usize;
+        (length + 7) & !7
+    }
+    fn encode<W: Write>(container: &C, writer: &mut W) {
+        ::bincode::serialize_into(&mut *writer, container).expect("bincode::serialize_into() failed");
+        let length = ::bincode::serialized_size(container).expect("bincode::serialized_size() failed") as usize;
+        writer.write_all(&[0u8; 8][.. ((length + 7) & !7) - length]).unwrap();
+    }
+}
+
+/// Serializes vectors of plain-old-data values as their length and their bytes in memory.
+///
+/// Encoding and decoding copy the values without inspecting them, which is faster than `bincode`
+/// for large batches of fixed-size records. Any bytes are valid values of a `Pod` type, so decoding
+/// cannot fail, but neither does it detect corrupted input.
+#[derive(Debug)]
+pub struct Raw;
+
+impl<T: Pod> Codec<Vec<T>> for Raw {
+    fn decode(bytes: Bytes) -> Vec<T> {
+        let len = u64::from_le_bytes(bytes[.. 8].try_into().unwrap()) as usize;
+        let mut values = vec![T::zeroed(); len];
+        let values_bytes: &mut [u8] = bytemuck::cast_slice_mut(&mut values);
+        values_bytes.copy_from_slice(&bytes[8 .. 8 + values_bytes.len()]);
+        values
+    }
+    fn length_in_bytes(container: &Vec<T>) -> usize {
+        8 + ((std::mem::size_of_val(container.as_slice()) + 7) & !7)
+    }
+    fn encode<W: Write>(container: &Vec<T>, writer: &mut W) {
+        let values: &[u8] = bytemuck::cast_slice(container.as_slice());
+        writer.write_all(&(container.len() as u64).to_le_bytes()).unwrap();
+        writer.write_all(values).unwrap();
+        writer.write_all(&[0u8; 8][.. ((values.len() + 7) & !7) - values.len()]).unwrap();
+    }
+}
+
+/// Selects serialization of containers with the `abomonation` crate: their bytes in memory,
+/// followed by the bytes they own.
+///
+/// Decoding restores the container in a copy of the received bytes by correcting its pointers,
+/// rather than by inspecting its values, and clones it out. This is faster than `bincode` for
+/// containers of nested records, but it trusts its input: decoding bytes that `encode` did not
+/// produce for the same type, for example corrupted ones, is undefined behavior. The codec is
+/// therefore not a [`Codec`] of its own, and is selected with the unsafe
+/// [`WithCodec::new_unchecked`](crate::dataflow::channels::pact::WithCodec::new_unchecked), whose
+/// caller vouches for the bytes received. Containers implement [`abomonation::Abomonation`] to
+/// be serialized this way.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::channels::codec::Abomonated;
+/// use timely::dataflow::channels::pact::{Exchange, WithCodec};
+/// use timely::dataflow::operators::{ToStream, Operator, Inspect};
+///
+/// let config = timely::Config {
+///     communication: timely::CommunicationConfig::ProcessBinary(2),
+///     worker: Default::default(),
+/// };
+/// timely::execute(config, |worker| {
+///     let index = worker.index() as u64;
+///     // SAFETY: the workers run the same binary, over channels that do not corrupt bytes, and
+///     // `(u64, String)` has no padding bytes.
+///     let pact = unsafe { WithCodec::<_, Abomonated>::new_unchecked(Exchange::new(|x: &(u64, String)| x.0)) };
+///     worker.dataflow::<u64,_,_>(|scope| {
+///         (0..100u64).map(|x| (x, x.to_string())).to_stream(scope)
+///                    .unary(pact, "Abomonated", |_, _| |input, output| {
+///                        input.for_each(|time, data| output.session(&time).give_container(data));
+///                    })
+///                    .inspect(move |(x, s)| assert_eq!((x % 2, x.to_string()), (index, s.clone())));
+///     });
+/// }).unwrap();
+/// ```
+#[cfg(feature = "abomonation")]
+#[derive(Debug)]
+pub struct Abomonated;
+
+#[cfg(feature = "abomonation")]
+pub(crate) use trusted::TrustedAbomonated;
+
+/// The codec that [`Abomonated`] selects, which cannot be named outside of this crate, so that its
+/// decoding of untrusted bytes is only reachable through `WithCodec::new_unchecked`.
+#[cfg(feature = "abomonation")]
+mod trusted {
+    use std::io::Write;
+
+    use crate::bytes::arc::Bytes;
+    use super::Codec;
+
+    /// Serializes containers with `abomonation`, as [`Abomonated`](super::Abomonated) describes.
+    #[derive(Debug)]
+    pub struct TrustedAbomonated;
+
+    impl<C: abomonation::Abomonation + Clone> Codec<C> for TrustedAbomonated {
+        fn decode(bytes: Bytes) -> C {
+            let len = u64::from_le_bytes(bytes[.. 8].try_into().unwrap()) as usize;
+            // Pointers are restored in place, in a copy that is aligned for the values it holds.
+            let mut aligned = vec![0u64; len.div_ceil(8)];
+            let aligned_bytes: &mut [u8] = bytemuck::cast_slice_mut(&mut aligned);
+            aligned_bytes[.. len].copy_from_slice(&bytes[8 .. 8 + len]);
+            // SAFETY: the caller of `WithCodec::new_unchecked` vouches that the bytes were written by `encode` for a container of type `C`.
+            let (container, _) = unsafe { abomonation::decode::<C>(aligned_bytes) }.expect("abomonation::decode() failed");
+            container.clone()
+        }
+        fn length_in_bytes(container: &C) -> usize {
+            8 + ((abomonation::measure(container) + 7) & !7)
+        }
+        fn encode<W: Write>(container: &C, writer: &mut W) {
+            let len = abomonation::measure(container);
+            writer.write_all(&(len as u64).to_le_bytes()).unwrap();
+            // SAFETY: the caller of `WithCodec::new_unchecked` vouches that `C` has no padding bytes, and the bytes are only decoded as a container of type `C`.
+            unsafe { abomonation::encode(container, writer) }.expect("abomonation::encode() failed");
+            writer.write_all(&[0u8; 8][.. ((len + 7) & !7) - len]).unwrap();
+        }
+    }
+}
+
+// Serialization of `Message` with the container serialized by `K`.
+//
+// Serialization of each field is meant to be `u64` aligned, so that each has the ability
+// to be decoded using safe transmutation, e.g. `bytemuck`.
+
+pub(crate) fn decode_message<T, C, K>(mut bytes: Bytes) -> Message<T, C>
+where
+    T: for<'a> Deserialize<'a> + Serialize,
+    K: Codec<C>,
+{
+    use byteorder::ReadBytesExt;
+    let mut slice = &bytes[..];
+    let from: usize = slice.read_u64::<byteorder::LittleEndian>().unwrap().try_into().unwrap();
+    let seq: usize = slice.read_u64::<byteorder::LittleEndian>().unwrap().try_into().unwrap();
+    let time: T = ::bincode::deserialize_from(&mut slice).expect("bincode::deserialize() failed");
+    let time_size = ::bincode::serialized_size(&time).expect("bincode::serialized_size() failed") as usize;
+    // We expect to find the `data` payload at `8 + 8 + round_up(time_size)`;
+    let bytes_read = 8 + 8 + ((time_size + 7) & !7);
+    bytes.extract_to(bytes_read);
+    let data = K::decode(bytes);
+    Message { time, data, from, seq }
+}
+
+pub(crate) fn message_length<T: Serialize, C, K: Codec<C>>(message: &Message<T, C>) -> usize {
+    let time_size = ::bincode::serialized_size(&message.time).expect("bincode::serialized_size() failed") as usize;
+    // 16 comes from the two `u64` fields: `from` and `seq`.
+    16 + ((time_size + 7) & !7) + K::length_in_bytes(&message.data)
+}
+
+pub(crate) fn encode_message<T: Serialize, C, K: Codec<C>, W: Write>(message: &Message<T, C>, writer: &mut W) {
+    use byteorder::WriteBytesExt;
+    writer.write_u64::<byteorder::LittleEndian>(message.from.try_into().unwrap()).unwrap();
+    writer.write_u64::<byteorder::LittleEndian>(message.seq.try_into().unwrap()).unwrap();
+    ::bincode::serialize_into(&mut *writer, &message.time).expect("bincode::serialize_into() failed");
+    let time_size = ::bincode::serialized_size(&message.time).expect("bincode::serialized_size() failed") as usize;
+    let time_slop = ((time_size + 7) & !7) - time_size;
+    writer.write_all(&[0u8; 8][..time_slop]).unwrap();
+    K::encode(&message.data, &mut *writer);
+}
+
+/// A message whose container is serialized by the codec `K`.
+pub struct Coded<T, C, K> {
+    /// The message.
+    pub message: Message<T, C>,
+    codec: PhantomData<fn() -> K>,
+}
+
+impl<T, C, K> From<Message<T, C>> for Coded<T, C, K> {
+    fn from(message: Message<T, C>) -> Self {
+        Coded { message, codec: PhantomData }
+    }
+}
+
+impl<T, C, K> Bytesable for Coded<T, C, K>
+where
+    T: Serialize + for<'a> Deserialize<'a>,
+    K: Codec<C>,
+{
+    fn from_bytes(bytes: Bytes) -> Self {
+        decode_message::<T, C, K>(bytes).into()
+    }
+    fn length_in_bytes(&self) -> usize {
+        message_length::<T, C, K>(&self.message)
+    }
+    fn into_bytes<W: Write>(&self, writer: &mut W) {
+        encode_message::<T, C, K, W>(&self.message, writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bytes::arc::BytesMut;
+    use crate::communication::Bytesable;
+    use crate::dataflow::channels::Message;
+    use super::{Bincode, Codec, Coded, Native, Raw};
+
+    /// Serializes `data` as a message with codec `K`, and deserializes it from a buffer followed by other bytes.
+    fn roundtrip<C: crate::Container, K: Codec<C>>(data: &C) -> Message<u64, C> {
+        let coded = Coded::<u64, C, K>::from(Message::new(3, data.clone(), 1, 2));
+        let mut serialized = Vec::new();
+        coded.into_bytes(&mut serialized);
+        assert_eq!(serialized.len(), coded.length_in_bytes());
+        assert_eq!(serialized.len() % 8, 0);
+        serialized.extend([0xff; 8]);
+        Coded::<u64, C, K>::from_bytes(BytesMut::from(serialized).freeze()).message
+    }
+
+    #[test]
+    fn roundtrip_each_codec() {
+        let strings = vec!["a".to_string(), "bcd".to_string(), String::new()];
+        for received in [roundtrip::<_, Native>(&strings), roundtrip::<_, Bincode>(&strings)] {
+            assert_eq!((received.time, received.from, received.seq), (3, 1, 2));
+            assert_eq!(received.data, strings);
+        }
+
+        let pairs = (0..13u32).map(|x| [x, x * x]).collect::<Vec<_>>();
+        let received = roundtrip::<_, Raw>(&pairs);
+        assert_eq!((received.time, received.from, received.seq), (3, 1, 2));
+        assert_eq!(received.data, pairs);
+        assert!(roundtrip::<Vec<u8>, Raw>(&Vec::new()).data.is_empty());
+    }
+
+    #[cfg(feature = "abomonation")]
+    #[test]
+    fn roundtrip_abomonated() {
+        use super::TrustedAbomonated;
+
+        let records = vec![(1u64, "a".to_string()), (2, String::new()), (3, "a longer string".to_string())];
+        let received = roundtrip::<_, TrustedAbomonated>(&records);
+        assert_eq!((received.time, received.from, received.seq), (3, 1, 2));
+        assert_eq!(received.data, records);
+
+        // Nested vectors with owned memory, of lengths that are not multiples of eight bytes.
+        let nested = vec![vec![1u8, 2, 3], Vec::new(), vec![4, 5, 6, 7, 8]];
+        assert_eq!(roundtrip::<_, TrustedAbomonated>(&nested).data, nested);
+        // Strings precede the vectors of later records, and so are of multiples of eight bytes, which keep those aligned.
+        let words = (0..10u64).map(|x| (x, vec!["wordword".repeat(x as usize); x as usize])).collect::<Vec<_>>();
+        assert_eq!(roundtrip::<_, TrustedAbomonated>(&words).data, words);
+        assert!(roundtrip::<Vec<String>, TrustedAbomonated>(&Vec::new()).data.is_empty());
+    }
+}
@@ -10,6 +10,12 @@ pub mod pushers;
 pub mod pullers;
 /// Parallelization contracts, describing how data must be exchanged between operators.
 pub mod pact;
+/// Container builders that bound the serialized size of containers.
+pub mod split;
+/// Containers of plain-old-data values that can be received without copying.
+pub mod pod;
+/// Codecs that serialize exchanged containers.
+pub mod codec;
 
 /// A serializable representation of timestamped data.
 #[derive(Clone)]
@@ -38,13 +44,36 @@ impl<T, C: Container> Message<T, C> {
         Message { time, data, from, seq }
     }
 
+    /// Creates a new message from a timestamp and a container.
+    ///
+    /// The `from` and `seq` fields are set to zero. Channels wrapped in a `LogPusher`, which
+    /// includes all channels produced by the standard parallelization contracts, overwrite
+    /// these fields with the sending worker and a per-channel sequence number. Callers should
+    /// not rely on setting them manually for messages that will be exchanged, as they will be
+    /// replaced before the message is sent.
+    pub fn from_parts(time: T, data: C) -> Self {
+        Self::new(time, data, 0, 0)
+    }
+
+    /// The index of the worker that sent the message.
+    ///
+    /// Only meaningful once the message has passed through a `LogPusher`.
+    #[inline]
+    pub fn from(&self) -> usize { self.from }
+
+    /// The sequence number of the message on its worker-to-worker channel.
+    ///
+    /// Only meaningful once the message has passed through a `LogPusher`.
+    #[inline]
+    pub fn seq(&self) -> usize { self.seq }
+
     /// Forms a message, and pushes contents at `pusher`. Replaces `buffer` with what the pusher
     /// leaves in place, or the container's default element. The buffer is left in an undefined state.
     #[inline]
     pub fn push_at<P: Push<Message<T, C>>>(buffer: &mut C, time: T, pusher: &mut P) {
 
         let data = ::std::mem::take(buffer);
-        let message = Message::new(time, data, 0, 0);
+        let message = Message::from_parts(time, data);
         let mut bundle = Some(message);
 
         pusher.push(&mut bundle);
@@ -55,44 +84,22 @@ impl<T, C: Container> Message<T, C> {
     }
 }
 
-// Instructions for serialization of `Message`.
-//
-// Serialization of each field is meant to be `u64` aligned, so that each has the ability
-// to be decoded using safe transmutation, e.g. `bytemuck`.
+// Serialization of `Message`, with its container serialized by `ContainerBytes`.
 impl<T, C> crate::communication::Bytesable for Message<T, C>
 where
     T: Serialize + for<'a> Deserialize<'a>,
     C: ContainerBytes,
 {
-    fn from_bytes(mut bytes: crate::bytes::arc::Bytes) -> Self {
-        use byteorder::ReadBytesExt;
-        let mut slice = &bytes[..];
-        let from: usize = slice.read_u64::<byteorder::LittleEndian>().unwrap().try_into().unwrap();
-        let seq: usize = slice.read_u64::<byteorder::LittleEndian>().unwrap().try_into().unwrap();
-        let time: T = ::bincode::deserialize_from(&mut slice).expect("bincode::deserialize() failed");
-        let time_size = ::bincode::serialized_size(&time).expect("bincode::serialized_size() failed") as usize;
-        // We expect to find the `data` payload at `8 + 8 + round_up(time_size)`;
-        let bytes_read = 8 + 8 + ((time_size + 7) & !7);
-        bytes.extract_to(bytes_read);
-        let data: C = ContainerBytes::from_bytes(bytes);
-        Self { time, data, from, seq }
+    fn from_bytes(bytes: crate::bytes::arc::Bytes) -> Self {
+        codec::decode_message::<T, C, codec::Native>(bytes)
     }
 
     fn length_in_bytes(&self) -> usize {
-        let time_size = ::bincode::serialized_size(&self.time).expect("bincode::serialized_size() failed") as usize;
-        // 16 comes from the two `u64` fields: `from` and `seq`.
-        16 + ((time_size + 7) & !7) + self.data.length_in_bytes()
+        codec::message_length::<T, C, codec::Native>(self)
     }
 
     fn into_bytes<W: ::std::io::Write>(&self, writer: &mut W) {
-        use byteorder::WriteBytesExt;
-        writer.write_u64::<byteorder::LittleEndian>(self.from.try_into().unwrap()).unwrap();
-        writer.write_u64::<byteorder::LittleEndian>(self.seq.try_into().unwrap()).unwrap();
-        ::bincode::serialize_into(&mut *writer, &self.time).expect("bincode::serialize_into() failed");
-        let time_size = ::bincode::serialized_size(&self.time).expect("bincode::serialized_size() failed") as usize;
-        let time_slop = ((time_size + 7) & !7) - time_size;
-        writer.write_all(&[0u8; 8][..time_slop]).unwrap();
-        self.data.into_bytes(&mut *writer);
+        codec::encode_message::<T, C, codec::Native, W>(self, writer)
     }
 }
 
@@ -10,6 +10,9 @@
 use std::fmt::Debug;
 use std::rc::Rc;
 
+use columnar::Columnar;
+use serde::{Deserialize, Serialize};
+
 use crate::Accountable;
 use crate::communication::allocator::thread::{ThreadPusher, ThreadPuller};
 use crate::communication::{Push, Pull};
@@ -17,6 +20,19 @@ use crate::dataflow::channels::Message;
 use crate::logging::TimelyLogger as Logger;
 use crate::worker::AsWorker;
 
+/// The pattern in which a pact moves data between workers.
+#[derive(Serialize, Deserialize, Columnar, Debug, Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub enum PactKind {
+    /// Each worker's data stays on that worker.
+    Pipeline,
+    /// Each worker's data is distributed among workers, each record to one of them.
+    Exchange,
+    /// Each worker's data is sent to all workers.
+    Broadcast,
+    /// The pattern is not reported.
+    Unknown,
+}
+
 /// A `ParallelizationContract` allocates paired `Push` and `Pull` implementors.
 pub trait ParallelizationContract<T, C> {
     /// Type implementing `Push` produced by this pact.
@@ -25,6 +41,10 @@ pub trait ParallelizationContract<T, C> {
     type Puller: Pull<Message<T, C>>+'static;
     /// Allocates a matched pair of push and pull endpoints implementing the pact.
     fn connect<A: AsWorker>(self, allocator: &mut A, identifier: usize, address: Rc<[usize]>, logging: Option<Logger>) -> (Self::Pusher, Self::Puller);
+    /// The pattern in which the pact moves data, for tools that analyze dataflows.
+    ///
+    /// It is reported with the channel the pact connects in [`ChannelsEvent`](crate::logging::ChannelsEvent).
+    fn kind(&self) -> PactKind { PactKind::Unknown }
 }
 
 /// A direct connection
@@ -39,22 +59,27 @@ impl<T: 'static, C: Accountable + 'static> ParallelizationContract<T, C> for Pip
         (LogPusher::new(pusher, allocator.index(), allocator.index(), identifier, logging.clone()),
          LogPuller::new(puller, allocator.index(), identifier, logging))
     }
+    fn kind(&self) -> PactKind { PactKind::Pipeline }
 }
 
-pub use exchange::{ExchangeCore, Exchange};
+pub use exchange::{ExchangeCore, Exchange, FieldExtractors, SubsetExchangeCore, SubsetExchange, ExchangeByTableCore, ExchangeByTable, ExchangePreRouted};
+pub use crate::dataflow::channels::pushers::exchange::RoutingTable;
 mod exchange {
 
-    use crate::Container;
+    use std::hash::{Hash, Hasher};
+
+    use crate::{Container, Data};
     use crate::container::{DrainContainer, LengthPreservingContainerBuilder, SizableContainer, CapacityContainerBuilder};
-    use crate::dataflow::channels::pushers::exchange::DrainContainerDistributor;
+    use crate::dataflow::channels::pushers::exchange::{DrainContainerDistributor, HashFinalizer, IdentityFinalizer, PreRoutedDistributor, RoutingTable, SubsetDistributor, TableDistributor};
+    use crate::dataflow::operators::sketch::StableHasher;
 
     use super::DistributorPact;
 
     /// An exchange between multiple observers by data
-    pub type ExchangeCore<CB, F> = DistributorPact<Box<dyn FnOnce(usize) -> DrainContainerDistributor<CB, F>>>;
+    pub type ExchangeCore<CB, F, Z = IdentityFinalizer> = DistributorPact<Box<dyn FnOnce(usize) -> DrainContainerDistributor<CB, F, Z>>>;
 
     /// [ExchangeCore] specialized to vector-based containers.
-    pub type Exchange<D, F> = ExchangeCore<CapacityContainerBuilder<Vec<D>>, F>;
+    pub type Exchange<D, F, Z = IdentityFinalizer> = ExchangeCore<CapacityContainerBuilder<Vec<D>>, F, Z>;
 
     impl<CB, F> ExchangeCore<CB, F>
     where
@@ -78,6 +103,303 @@ mod exchange {
             DistributorPact(Box::new(move |peers| DrainContainerDistributor::new(func, peers)))
         }
     }
+
+    impl<CB, F, Z> ExchangeCore<CB, F, Z>
+    where
+        CB: LengthPreservingContainerBuilder,
+        CB::Container: DrainContainer,
+        for<'a> F: FnMut(&<CB::Container as DrainContainer>::Item<'a>)->u64 + 'static,
+        Z: HashFinalizer + 'static,
+    {
+        /// Allocates a new `Exchange` pact from a distribution function, whose results are mixed by
+        /// `finalizer` before being reduced to worker indexes.
+        pub fn new_core_with_finalizer(func: F, finalizer: Z) -> ExchangeCore<CB, F, Z> {
+            DistributorPact(Box::new(move |peers| DrainContainerDistributor::with_finalizer(func, finalizer, peers)))
+        }
+    }
+
+    impl<C, F, Z> ExchangeCore<CapacityContainerBuilder<C>, F, Z>
+    where
+        C: Container + SizableContainer + DrainContainer,
+        for<'a> F: FnMut(&C::Item<'a>)->u64 + 'static,
+        Z: HashFinalizer + 'static,
+    {
+        /// Allocates a new `Exchange` pact from a distribution function, whose results are mixed by
+        /// `finalizer` before being reduced to worker indexes.
+        ///
+        /// # Examples
+        /// ```
+        /// use timely::dataflow::channels::pact::Exchange;
+        /// use timely::dataflow::channels::pushers::exchange::AvalancheFinalizer;
+        /// use timely::dataflow::operators::{ToStream, Operator, Inspect};
+        ///
+        /// timely::execute(timely::Config::process(4), |worker| {
+        ///     worker.dataflow::<u64,_,_>(|scope| {
+        ///         // Multiples of four would all be sent to the first worker without the finalizer.
+        ///         (0..100u64).map(|x| 4 * x).to_stream(scope)
+        ///                    .unary(Exchange::new_with_finalizer(|x: &u64| *x, AvalancheFinalizer), "Mixed", |_, _| |input, output| {
+        ///                        input.for_each(|time, data| output.session(&time).give_container(data));
+        ///                    })
+        ///                    .inspect(|x| assert_eq!(x % 4, 0));
+        ///     });
+        /// }).unwrap();
+        /// ```
+        pub fn new_with_finalizer(func: F, finalizer: Z) -> ExchangeCore<CapacityContainerBuilder<C>, F, Z> {
+            Self::new_core_with_finalizer(func, finalizer)
+        }
+    }
+
+    /// Extracts fields from a record, and feeds them to a hasher in order.
+    ///
+    /// Implemented for tuples of up to four functions from a record to hashable fields, used by
+    /// [`Exchange::by_fields`].
+    pub trait FieldExtractors<D> {
+        /// Feeds the fields of `datum` to `hasher`.
+        fn hash_fields<H: Hasher>(&self, datum: &D, hasher: &mut H);
+    }
+
+    macro_rules! implement_field_extractors {
+        ($($name:ident : $key:ident),+) => {
+            impl<D, $($name, $key),+> FieldExtractors<D> for ($($name,)+)
+            where
+                $($name: Fn(&D)->$key, $key: Hash),+
+            {
+                #[allow(non_snake_case)]
+                fn hash_fields<H: Hasher>(&self, datum: &D, hasher: &mut H) {
+                    let ($($name,)+) = self;
+                    $( $name(datum).hash(hasher); )+
+                }
+            }
+        }
+    }
+
+    implement_field_extractors!(F1: K1);
+    implement_field_extractors!(F1: K1, F2: K2);
+    implement_field_extractors!(F1: K1, F2: K2, F3: K3);
+    implement_field_extractors!(F1: K1, F2: K2, F3: K3, F4: K4);
+
+    impl<D: Data> Exchange<D, Box<dyn FnMut(&D)->u64>> {
+        /// Allocates a new `Exchange` pact that routes records by a combination of their fields.
+        ///
+        /// Each function of the `extractors` tuple extracts a field from a record, and the fields
+        /// are hashed together, in order, by a hasher of a fixed algorithm, so that records are
+        /// routed alike by all workers, and by builds with different versions of Rust. Unlike
+        /// combining separate hashes of the fields with, for example, exclusive or, records whose
+        /// fields are permutations of each other, or that share equal fields, are still spread
+        /// across the workers.
+        ///
+        /// # Examples
+        /// ```
+        /// use timely::dataflow::channels::pact::Exchange;
+        /// use timely::dataflow::operators::{ToStream, Operator, Inspect};
+        ///
+        /// timely::execute(timely::Config::process(2), |worker| {
+        ///     worker.dataflow::<u64,_,_>(|scope| {
+        ///         (0..10u64).map(|x| (x % 3, x % 2, x)).to_stream(scope)
+        ///                   .unary(Exchange::by_fields((|x: &(u64, u64, u64)| x.0, |x: &(u64, u64, u64)| x.1)), "ByFields", |_, _| |input, output| {
+        ///                       input.for_each(|time, data| output.session(&time).give_container(data));
+        ///                   })
+        ///                   .inspect(|x| println!("seen: {:?}", x));
+        ///     });
+        /// }).unwrap();
+        /// ```
+        pub fn by_fields<E: FieldExtractors<D>+'static>(extractors: E) -> Self {
+            Self::new(Box::new(move |datum: &D| hash_fields(&extractors, datum)))
+        }
+    }
+
+    /// The hash of the fields `extractors` extracts from `datum`, with a hasher of a fixed algorithm.
+    fn hash_fields<D, E: FieldExtractors<D>>(extractors: &E, datum: &D) -> u64 {
+        let mut hasher = StableHasher::default();
+        extractors.hash_fields(datum, &mut hasher);
+        hasher.finish()
+    }
+
+    /// An exchange by data that only sends to a subset of the workers.
+    pub type SubsetExchangeCore<CB, F> = DistributorPact<Box<dyn FnOnce(usize) -> SubsetDistributor<CB, F>>>;
+
+    /// [SubsetExchangeCore] specialized to vector-based containers.
+    pub type SubsetExchange<D, F> = SubsetExchangeCore<CapacityContainerBuilder<Vec<D>>, F>;
+
+    impl<CB, F> SubsetExchangeCore<CB, F>
+    where
+        CB: LengthPreservingContainerBuilder,
+        CB::Container: DrainContainer,
+        for<'a> F: FnMut(&<CB::Container as DrainContainer>::Item<'a>)->u64 + 'static
+    {
+        /// Allocates a new `SubsetExchange` pact from the eligible target workers and a distribution function.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `targets` is empty. When the pact is connected, panics if `targets` contains
+        /// an index that is not less than the number of workers.
+        pub fn new_core(targets: Vec<usize>, func: F) -> SubsetExchangeCore<CB, F> {
+            assert!(!targets.is_empty(), "subset exchange requires at least one target worker");
+            DistributorPact(Box::new(move |peers| SubsetDistributor::new(func, targets, peers)))
+        }
+    }
+
+    impl<C, F> SubsetExchangeCore<CapacityContainerBuilder<C>, F>
+    where
+        C: Container + SizableContainer + DrainContainer,
+        for<'a> F: FnMut(&C::Item<'a>)->u64 + 'static
+    {
+        /// Allocates a new `SubsetExchange` pact from the eligible target workers and a distribution function.
+        ///
+        /// Each record is sent to the target `targets[func(record) % targets.len()]`, and workers not
+        /// in `targets` receive no records on the channel.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `targets` is empty. When the pact is connected, panics if `targets` contains
+        /// an index that is not less than the number of workers.
+        ///
+        /// # Examples
+        /// ```
+        /// use timely::dataflow::channels::pact::SubsetExchange;
+        /// use timely::dataflow::operators::{ToStream, Operator, Inspect};
+        ///
+        /// timely::execute(timely::Config::process(3), |worker| {
+        ///     let index = worker.index();
+        ///     worker.dataflow::<u64,_,_>(|scope| {
+        ///         (0..10u64).to_stream(scope)
+        ///                   .unary(SubsetExchange::new(vec![0, 2], |x: &u64| *x), "Subset", |_, _| |input, output| {
+        ///                       input.for_each(|time, data| output.session(&time).give_container(data));
+        ///                   })
+        ///                   .inspect(move |_| assert_ne!(index, 1));
+        ///     });
+        /// }).unwrap();
+        /// ```
+        pub fn new(targets: Vec<usize>, func: F) -> SubsetExchangeCore<CapacityContainerBuilder<C>, F> {
+            Self::new_core(targets, func)
+        }
+    }
+
+    /// An exchange by data that routes according to a routing table.
+    pub type ExchangeByTableCore<CB, K, F> = DistributorPact<Box<dyn FnOnce(usize) -> TableDistributor<CB, K, F>>>;
+
+    /// [ExchangeByTableCore] specialized to vector-based containers.
+    pub type ExchangeByTable<D, K, F> = ExchangeByTableCore<CapacityContainerBuilder<Vec<D>>, K, F>;
+
+    impl<CB, K, F> ExchangeByTableCore<CB, K, F>
+    where
+        CB: LengthPreservingContainerBuilder,
+        CB::Container: DrainContainer,
+        K: 'static,
+        for<'a> F: FnMut(&<CB::Container as DrainContainer>::Item<'a>)->K + 'static
+    {
+        /// Allocates a new `ExchangeByTable` pact from a routing table and a key function.
+        pub fn new_core(table: RoutingTable<K>, func: F) -> ExchangeByTableCore<CB, K, F> {
+            DistributorPact(Box::new(move |peers| TableDistributor::new(table, func, peers)))
+        }
+    }
+
+    impl<C, K, F> ExchangeByTableCore<CapacityContainerBuilder<C>, K, F>
+    where
+        C: Container + SizableContainer + DrainContainer,
+        K: 'static,
+        for<'a> F: FnMut(&C::Item<'a>)->K + 'static
+    {
+        /// Allocates a new `ExchangeByTable` pact from a routing table and a key function.
+        ///
+        /// Each record is sent to the worker that `table` assigns the key `func(record)`. Streams
+        /// exchanged by the same table, or by the pact the table was captured from, place records
+        /// with equal keys at the same worker, which co-partitions them for joins without moving
+        /// records already in place.
+        ///
+        /// # Panics
+        ///
+        /// Panics when routing a record whose key the table assigns to a worker out of range.
+        ///
+        /// # Examples
+        /// ```
+        /// use timely::dataflow::channels::pact::{Exchange, ExchangeByTable, RoutingTable};
+        /// use timely::dataflow::operators::{ToStream, Operator, Inspect};
+        ///
+        /// timely::execute(timely::Config::process(3), |worker| {
+        ///     let index = worker.index();
+        ///     worker.dataflow::<u64,_,_>(|scope| {
+        ///         // A relation exchanged by hash, and a table capturing its routing.
+        ///         let table = RoutingTable::hashed(|key: &u64| *key);
+        ///         (0..10u64).to_stream(scope)
+        ///                   .unary(Exchange::new(|x: &u64| *x), "Relation", |_, _| |input, output| {
+        ///                       input.for_each(|time, data| output.session(&time).give_container(data));
+        ///                   })
+        ///                   .inspect(move |x| assert_eq!(*x as usize % 3, index));
+        ///         // Pairs keyed like the relation meet its records at the same workers.
+        ///         (0..10u64).map(|x| (x, x * x)).to_stream(scope)
+        ///                   .unary(ExchangeByTable::new(table, |pair: &(u64, u64)| pair.0), "Pairs", |_, _| |input, output| {
+        ///                       input.for_each(|time, data| output.session(&time).give_container(data));
+        ///                   })
+        ///                   .inspect(move |pair| assert_eq!(pair.0 as usize % 3, index));
+        ///     });
+        /// }).unwrap();
+        /// ```
+        pub fn new(table: RoutingTable<K>, func: F) -> ExchangeByTableCore<CapacityContainerBuilder<C>, K, F> {
+            Self::new_core(table, func)
+        }
+    }
+
+    /// An exchange of whole containers, each already holding the records of one worker.
+    pub type ExchangePreRouted<F> = DistributorPact<Box<dyn FnOnce(usize) -> PreRoutedDistributor<F>>>;
+
+    impl<F> ExchangePreRouted<F> {
+        /// Allocates a new `ExchangePreRouted` pact from a function from containers to their targets.
+        ///
+        /// Each non-empty container is sent whole to the worker `func(container) % peers`, whose
+        /// result should distinguish the worker of each container's records, for example the hash
+        /// of any one of them if upstream grouped records by that hash. Records are neither inspected
+        /// nor copied by the exchange, which relies on upstream operators to produce containers of a
+        /// single worker's records; a container mixing the records of several workers is sent whole
+        /// to one of them.
+        ///
+        /// # Examples
+        /// ```
+        /// use timely::dataflow::channels::pact::{ExchangePreRouted, Pipeline};
+        /// use timely::dataflow::operators::{ToStream, Operator, Inspect};
+        ///
+        /// timely::execute(timely::Config::process(3), |worker| {
+        ///     let index = worker.index();
+        ///     let peers = worker.peers();
+        ///     worker.dataflow::<u64,_,_>(|scope| {
+        ///         (0..10u64).to_stream(scope)
+        ///                   // Groups the records of each time into one container per worker.
+        ///                   .unary(Pipeline, "Bucket", move |_, _| move |input, output| {
+        ///                       input.for_each(|time, data| {
+        ///                           let mut buckets = vec![Vec::new(); peers];
+        ///                           for x in data.drain(..) { buckets[x as usize % peers].push(x); }
+        ///                           for mut bucket in buckets { output.session(&time).give_container(&mut bucket); }
+        ///                       });
+        ///                   })
+        ///                   .unary(ExchangePreRouted::new(|bucket: &Vec<u64>| bucket[0]), "Routed", |_, _| |input, output| {
+        ///                       input.for_each(|time, data| output.session(&time).give_container(data));
+        ///                   })
+        ///                   .inspect(move |x| assert_eq!(*x as usize % 3, index));
+        ///     });
+        /// }).unwrap();
+        /// ```
+        pub fn new<C>(func: F) -> ExchangePreRouted<F>
+        where
+            F: FnMut(&C)->u64 + 'static,
+        {
+            DistributorPact(Box::new(move |_peers| PreRoutedDistributor::new(func)))
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use crate::dataflow::operators::sketch::hash_of;
+        use super::hash_fields;
+
+        #[test]
+        fn fields_hash_as_their_tuple() {
+            let extractors = (|x: &(u64, u64)| x.0, |x: &(u64, u64)| x.1);
+            // The hash is that of the tuple of the fields, whose values are fixed across builds.
+            assert_eq!(hash_fields(&extractors, &(7, 3)), hash_of(&(7u64, 3u64)));
+            // Fields are hashed in order, and so permuted fields hash differently.
+            assert_ne!(hash_fields(&extractors, &(1, 2)), hash_fields(&extractors, &(2, 1)));
+        }
+    }
 }
 
 pub use distributor::DistributorPact;
@@ -94,7 +416,7 @@ mod distributor {
     use crate::progress::Timestamp;
     use crate::worker::AsWorker;
 
-    use super::{ParallelizationContract, LogPusher, LogPuller};
+    use super::{PactKind, ParallelizationContract, LogPusher, LogPuller};
 
     /// Intended to wrap a function from a `usize` to an `impl Distributor`.
     ///
@@ -113,11 +435,824 @@ mod distributor {
         type Pusher = Exchange<T, LogPusher<Box<dyn Push<Message<T, C>>>>, D>;
         type Puller = LogPuller<Box<dyn Pull<Message<T, C>>>>;
         fn connect<A: AsWorker>(self, allocator: &mut A, identifier: usize, address: Rc<[usize]>, logging: Option<TimelyLogger>) -> (Self::Pusher, Self::Puller) {
-            let (senders, receiver) = allocator.allocate::<Message<T, C>>(identifier, address);
+            let (senders, receiver) = if allocator.peers() == 1 && allocator.exchange_by_pipeline() {
+                // A single worker exchanges only with itself, which needs no serialization.
+                let (sender, receiver) = allocator.pipeline::<Message<T, C>>(identifier, address);
+                (vec![Box::new(sender) as Box<dyn Push<Message<T, C>>>], Box::new(receiver) as Box<dyn Pull<Message<T, C>>>)
+            }
+            else {
+                allocator.allocate::<Message<T, C>>(identifier, address)
+            };
             let senders = senders.into_iter().enumerate().map(|(i,x)| LogPusher::new(x, allocator.index(), i, identifier, logging.clone())).collect::<Vec<_>>();
             let distributor = (self.0)(allocator.peers());
             (Exchange::new(senders, distributor), LogPuller::new(receiver, allocator.index(), identifier, logging.clone()))
         }
+        fn kind(&self) -> PactKind { PactKind::Exchange }
+    }
+}
+
+pub use with_codec::WithCodec;
+/// Parallelization contract that serializes exchanged containers with a chosen codec.
+mod with_codec {
+
+    use std::marker::PhantomData;
+    use std::rc::Rc;
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::Accountable;
+    use crate::communication::{Push, Pull};
+    use crate::dataflow::channels::codec::{Codec, Coded};
+    #[cfg(feature = "abomonation")]
+    use crate::dataflow::channels::codec::{Abomonated, TrustedAbomonated};
+    use crate::dataflow::channels::pushers::{Exchange, exchange::Distributor};
+    use crate::dataflow::channels::Message;
+    use crate::logging::TimelyLogger;
+    use crate::worker::AsWorker;
+
+    use super::{DistributorPact, PactKind, ParallelizationContract, LogPusher, LogPuller};
+
+    /// Wraps a `DistributorPact`, such as an `Exchange`, to serialize its containers with the codec `K`.
+    ///
+    /// The codec only applies to containers sent to other processes; those exchanged between workers
+    /// of a process, or by a single worker with itself, are moved as they are. See
+    /// [`codec`](crate::dataflow::channels::codec) for the available codecs.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::channels::codec::Raw;
+    /// use timely::dataflow::channels::pact::{Exchange, WithCodec};
+    /// use timely::dataflow::operators::{ToStream, Operator, Inspect};
+    ///
+    /// let config = timely::Config {
+    ///     communication: timely::CommunicationConfig::ProcessBinary(2),
+    ///     worker: Default::default(),
+    /// };
+    /// timely::execute(config, |worker| {
+    ///     let index = worker.index() as u64;
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         (0..100u64).to_stream(scope)
+    ///                    .unary(WithCodec::<_, Raw>::new(Exchange::new(|x: &u64| *x)), "Raw", |_, _| |input, output| {
+    ///                        input.for_each(|time, data| output.session(&time).give_container(data));
+    ///                    })
+    ///                    .inspect(move |x| assert_eq!(x % 2, index));
+    ///     });
+    /// }).unwrap();
+    /// ```
+    pub struct WithCodec<P, K> {
+        pact: P,
+        codec: PhantomData<fn() -> K>,
+    }
+
+    impl<P, K> WithCodec<P, K> {
+        /// Wraps `pact` to serialize its containers with `K`.
+        pub fn new(pact: P) -> Self {
+            WithCodec { pact, codec: PhantomData }
+        }
+    }
+
+    #[cfg(feature = "abomonation")]
+    impl<P> WithCodec<P, Abomonated> {
+        /// Wraps `pact` to serialize its containers with the `abomonation` crate.
+        ///
+        /// # Safety
+        ///
+        /// Decoding trusts the bytes received, and encoding reads the memory of containers as bytes.
+        /// The caller must ensure that the processes exchanging through the pact run the same
+        /// build, and that the bytes each receives are exactly those sent, not corrupted, for
+        /// example by a `FaultPolicy`. The containers must meet the requirements of
+        /// `abomonation::encode` and `abomonation::decode`, and neither they nor the memory they
+        /// own may contain padding bytes. As `abomonation` writes the memory a container owns
+        /// without aligning it, each allocation must also follow owned memory whose length is a
+        /// multiple of its alignment: strings and vectors of bytes are only safe where no
+        /// allocation of greater alignment follows them, or of lengths that are multiples of eight.
+        pub unsafe fn new_unchecked(pact: P) -> WithCodec<P, TrustedAbomonated> {
+            WithCodec { pact, codec: PhantomData }
+        }
+    }
+
+    impl<T, B, C, D, K> ParallelizationContract<T, C> for WithCodec<DistributorPact<B>, K>
+    where
+        T: crate::progress::Timestamp + Serialize + for<'a> Deserialize<'a>,
+        B: FnOnce(usize) -> D,
+        C: Accountable + Send + 'static,
+        D: Distributor<C> + 'static,
+        K: Codec<C> + 'static,
+    {
+        type Pusher = Exchange<T, LogPusher<CodedPusher<T, C, K>>, D>;
+        type Puller = LogPuller<CodedPuller<T, C, K>>;
+        fn connect<A: AsWorker>(self, allocator: &mut A, identifier: usize, address: Rc<[usize]>, logging: Option<TimelyLogger>) -> (Self::Pusher, Self::Puller) {
+            let (senders, receiver) = if allocator.peers() == 1 && allocator.exchange_by_pipeline() {
+                // A single worker exchanges only with itself, which needs no serialization.
+                let (sender, receiver) = allocator.pipeline::<Coded<T, C, K>>(identifier, address);
+                (vec![Box::new(sender) as Box<dyn Push<Coded<T, C, K>>>], Box::new(receiver) as Box<dyn Pull<Coded<T, C, K>>>)
+            }
+            else {
+                allocator.allocate::<Coded<T, C, K>>(identifier, address)
+            };
+            let senders = senders.into_iter().enumerate().map(|(i,x)| LogPusher::new(CodedPusher { pusher: x }, allocator.index(), i, identifier, logging.clone())).collect::<Vec<_>>();
+            let distributor = (self.pact.0)(allocator.peers());
+            let puller = CodedPuller { puller: receiver, current: None };
+            (Exchange::new(senders, distributor), LogPuller::new(puller, allocator.index(), identifier, logging.clone()))
+        }
+        fn kind(&self) -> PactKind { PactKind::Exchange }
+    }
+
+    /// Pushes messages as `Coded` messages.
+    pub struct CodedPusher<T, C, K> {
+        pusher: Box<dyn Push<Coded<T, C, K>>>,
+    }
+
+    impl<T, C, K> Push<Message<T, C>> for CodedPusher<T, C, K> {
+        #[inline]
+        fn push(&mut self, message: &mut Option<Message<T, C>>) {
+            let mut coded = message.take().map(Coded::from);
+            self.pusher.push(&mut coded);
+            *message = coded.map(|coded| coded.message);
+        }
+    }
+
+    /// Pulls messages from `Coded` messages.
+    pub struct CodedPuller<T, C, K> {
+        puller: Box<dyn Pull<Coded<T, C, K>>>,
+        current: Option<Message<T, C>>,
+    }
+
+    impl<T, C, K> Pull<Message<T, C>> for CodedPuller<T, C, K> {
+        #[inline]
+        fn pull(&mut self) -> &mut Option<Message<T, C>> {
+            self.current = self.puller.pull().take().map(|coded| coded.message);
+            &mut self.current
+        }
+    }
+}
+
+pub use ordered::{OrderedExchange, OrderedPuller};
+/// Parallelization contract that restores per-source send order.
+mod ordered {
+
+    use std::collections::{BTreeMap, HashMap, VecDeque};
+    use std::rc::Rc;
+
+    use crate::communication::Pull;
+    use crate::container::{CapacityContainerBuilder, DrainContainer, SizableContainer};
+    use crate::dataflow::channels::Message;
+    use crate::logging::TimelyLogger;
+    use crate::worker::AsWorker;
+    use crate::Container;
+
+    use super::{ExchangeCore, PactKind, ParallelizationContract};
+
+    /// Wraps a pact so that messages from each source are received in the order they were sent.
+    ///
+    /// Each message is stamped with its source worker and a per-channel sequence number when it
+    /// is sent. The puller of an `OrderedExchange` holds back messages that arrive ahead of their
+    /// predecessors from the same source, and releases them once the gap is filled. The result is
+    /// a per-source FIFO guarantee: messages sent by one worker are received in the order it sent
+    /// them. The interleaving of messages from different sources remains unspecified.
+    ///
+    /// Holding back messages costs memory and latency, which is why the ordering is opt-in.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::channels::pact::OrderedExchange;
+    /// use timely::dataflow::operators::{ToStream, Operator, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10u64).to_stream(scope)
+    ///               .unary(OrderedExchange::new(|x: &u64| *x), "Ordered", |_, _| |input, output| {
+    ///                   input.for_each(|time, data| output.session(&time).give_container(data));
+    ///               })
+    ///               .inspect(|x| println!("seen: {:?}", x));
+    /// });
+    /// ```
+    #[derive(Debug)]
+    pub struct OrderedExchange<P> {
+        pact: P,
+    }
+
+    impl<P> OrderedExchange<P> {
+        /// Wraps an existing pact, restoring per-source order on its receiving side.
+        pub fn wrap(pact: P) -> Self {
+            Self { pact }
+        }
+    }
+
+    impl<C, F> OrderedExchange<ExchangeCore<CapacityContainerBuilder<C>, F>>
+    where
+        C: Container + SizableContainer + DrainContainer,
+        for<'a> F: FnMut(&C::Item<'a>)->u64 + 'static
+    {
+        /// Allocates a new ordered `Exchange` pact from a distribution function.
+        pub fn new(func: F) -> Self {
+            Self::wrap(ExchangeCore::new(func))
+        }
+    }
+
+    impl<T, C, P> ParallelizationContract<T, C> for OrderedExchange<P>
+    where
+        T: 'static,
+        C: 'static,
+        P: ParallelizationContract<T, C>,
+    {
+        type Pusher = P::Pusher;
+        type Puller = OrderedPuller<T, C, P::Puller>;
+        fn connect<A: AsWorker>(self, allocator: &mut A, identifier: usize, address: Rc<[usize]>, logging: Option<TimelyLogger>) -> (Self::Pusher, Self::Puller) {
+            let (pusher, puller) = self.pact.connect(allocator, identifier, address, logging);
+            (pusher, OrderedPuller::new(puller))
+        }
+        fn kind(&self) -> PactKind { self.pact.kind() }
+    }
+
+    /// Wraps a `Message<T,C>` puller to release messages in sequence order per source.
+    pub struct OrderedPuller<T, C, P> {
+        puller: P,
+        /// The next expected sequence number for each source.
+        expected: HashMap<usize, usize>,
+        /// Messages received ahead of their predecessors, by source and sequence number.
+        stash: HashMap<usize, BTreeMap<usize, Message<T, C>>>,
+        /// Messages in order and ready to be released.
+        ready: VecDeque<Message<T, C>>,
+        current: Option<Message<T, C>>,
+    }
+
+    impl<T, C, P> OrderedPuller<T, C, P> {
+        /// Allocates a new ordering puller wrapping `puller`.
+        pub fn new(puller: P) -> Self {
+            Self {
+                puller,
+                expected: HashMap::new(),
+                stash: HashMap::new(),
+                ready: VecDeque::new(),
+                current: None,
+            }
+        }
+
+        /// The number of messages held back awaiting their predecessors.
+        pub fn held_back(&self) -> usize {
+            self.stash.values().map(|messages| messages.len()).sum()
+        }
+
+        /// Records the release of `message`, and readies stashed successors from its source.
+        fn release(&mut self, message: Message<T, C>) -> &mut Option<Message<T, C>> {
+            let expected = self.expected.entry(message.from).or_insert(0);
+            *expected = message.seq + 1;
+            if let Some(stashed) = self.stash.get_mut(&message.from) {
+                while let Some(next) = stashed.remove(expected) {
+                    *expected += 1;
+                    self.ready.push_back(next);
+                }
+                if stashed.is_empty() {
+                    self.stash.remove(&message.from);
+                }
+            }
+            self.current = Some(message);
+            &mut self.current
+        }
+    }
+
+    impl<T, C, P: Pull<Message<T, C>>> Pull<Message<T, C>> for OrderedPuller<T, C, P> {
+        #[inline]
+        fn pull(&mut self) -> &mut Option<Message<T, C>> {
+            if let Some(message) = self.ready.pop_front() {
+                self.current = Some(message);
+                return &mut self.current;
+            }
+            while let Some(message) = self.puller.recv() {
+                if message.seq == self.expected.get(&message.from).copied().unwrap_or(0) {
+                    return self.release(message);
+                }
+                self.stash.entry(message.from).or_default().insert(message.seq, message);
+            }
+            self.current = None;
+            &mut self.current
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use crate::communication::Pull;
+        use crate::dataflow::channels::Message;
+        use super::OrderedPuller;
+
+        struct VecPuller(std::vec::IntoIter<Message<(), Vec<usize>>>, Option<Message<(), Vec<usize>>>);
+        impl Pull<Message<(), Vec<usize>>> for VecPuller {
+            fn pull(&mut self) -> &mut Option<Message<(), Vec<usize>>> {
+                self.1 = self.0.next();
+                &mut self.1
+            }
+        }
+
+        #[test]
+        fn reorders_per_source() {
+            let arrivals = [(0, 1), (1, 0), (0, 2), (1, 2), (0, 0), (1, 1)];
+            let messages = arrivals.iter().map(|&(from, seq)| Message::new((), vec![seq], from, seq)).collect::<Vec<_>>();
+            let mut puller = OrderedPuller::new(VecPuller(messages.into_iter(), None));
+            let mut received = Vec::new();
+            while let Some(message) = puller.recv() {
+                received.push((message.from, message.seq));
+            }
+            assert_eq!(puller.held_back(), 0);
+            for source in 0..2 {
+                let seqs = received.iter().filter(|(from, _)| *from == source).map(|(_, seq)| *seq).collect::<Vec<_>>();
+                assert_eq!(seqs, vec![0, 1, 2]);
+            }
+        }
+    }
+}
+
+pub use spilling::{MemoryPressure, Spilling, SpillPusher};
+/// Parallelization contract that spills exchanged messages to disk under memory pressure.
+mod spilling {
+
+    use std::fs::File;
+    use std::io::{BufReader, BufWriter, Read, Write};
+    use std::marker::PhantomData;
+    use std::path::PathBuf;
+    use std::rc::Rc;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::Accountable;
+    use crate::bytes::arc::BytesMut;
+    use crate::communication::{Bytesable, Push, Pull};
+    use crate::dataflow::channels::pushers::{Exchange, exchange::Distributor};
+    use crate::dataflow::channels::{ContainerBytes, Message};
+    use crate::logging::TimelyLogger;
+    use crate::progress::Timestamp;
+    use crate::worker::AsWorker;
+
+    use super::{DistributorPact, PactKind, ParallelizationContract, LogPusher, LogPuller};
+
+    /// Distinguishes the spill files created by a process.
+    static SPILL_FILES: AtomicUsize = AtomicUsize::new(0);
+
+    /// A shared signal that memory is scarce, set and cleared by a monitor such as another thread.
+    #[derive(Clone, Debug, Default)]
+    pub struct MemoryPressure {
+        flag: Arc<AtomicBool>,
+    }
+
+    impl MemoryPressure {
+        /// Creates a signal that is initially clear.
+        pub fn new() -> Self { Self::default() }
+        /// Sets or clears the signal.
+        pub fn set(&self, pressure: bool) { self.flag.store(pressure, Ordering::Relaxed); }
+        /// True iff the signal is set.
+        pub fn is_set(&self) -> bool { self.flag.load(Ordering::Relaxed) }
+    }
+
+    /// Wraps a `DistributorPact`, such as an `Exchange`, to spill messages to disk while `pressure` is set.
+    ///
+    /// While the signal is set, each message sent to a target is written to a temporary file for the
+    /// target rather than sent, and so do all later messages to the target until the file is drained,
+    /// to keep them in order. The exchange is flushed at the end of each invocation of the sending
+    /// operator; flushes while the signal is set leave the spilled messages on disk, and the first
+    /// flush once it clears reads them back and sends them in the order they were pushed, before the
+    /// flush itself. Memory is thus relieved of the messages sent to each target for as long as the
+    /// pressure lasts, at the cost of serializing them. Should the signal never clear, the messages
+    /// are sent when the pusher is dropped, as the sending operator completes, so that no message is
+    /// lost. Spill files are created in [`std::env::temp_dir`], and removed once drained.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::channels::pact::{Exchange, MemoryPressure, Spilling};
+    /// use timely::dataflow::operators::{ToStream, Operator, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     let pressure = MemoryPressure::new();
+    ///     pressure.set(true);
+    ///     (0..10u64).to_stream(scope)
+    ///               .unary(Spilling::new(Exchange::new(|x: &u64| *x), pressure), "Spilled", |_, _| |input, output| {
+    ///                   input.for_each(|time, data| output.session(&time).give_container(data));
+    ///               })
+    ///               .inspect(|x| println!("seen: {:?}", x));
+    /// });
+    /// ```
+    pub struct Spilling<P> {
+        pact: P,
+        pressure: MemoryPressure,
+    }
+
+    impl<P> Spilling<P> {
+        /// Wraps `pact` to spill its messages while `pressure` is set.
+        pub fn new(pact: P, pressure: MemoryPressure) -> Self {
+            Spilling { pact, pressure }
+        }
+    }
+
+    impl<T, B, C, D> ParallelizationContract<T, C> for Spilling<DistributorPact<B>>
+    where
+        T: Timestamp + Serialize + for<'a> Deserialize<'a>,
+        B: FnOnce(usize) -> D,
+        C: Accountable + ContainerBytes + Send + 'static,
+        D: Distributor<C> + 'static,
+    {
+        type Pusher = Exchange<T, LogPusher<SpillPusher<T, C, Box<dyn Push<Message<T, C>>>>>, D>;
+        type Puller = LogPuller<Box<dyn Pull<Message<T, C>>>>;
+        fn connect<A: AsWorker>(self, allocator: &mut A, identifier: usize, address: Rc<[usize]>, logging: Option<TimelyLogger>) -> (Self::Pusher, Self::Puller) {
+            let (senders, receiver) = allocator.allocate::<Message<T, C>>(identifier, address);
+            let senders = senders.into_iter().enumerate().map(|(i,x)| {
+                LogPusher::new(SpillPusher::new(x, self.pressure.clone()), allocator.index(), i, identifier, logging.clone())
+            }).collect::<Vec<_>>();
+            let distributor = (self.pact.0)(allocator.peers());
+            (Exchange::new(senders, distributor), LogPuller::new(receiver, allocator.index(), identifier, logging.clone()))
+        }
+        fn kind(&self) -> PactKind { PactKind::Exchange }
+    }
+
+    /// Sends messages to `pusher`, or spills them to a temporary file while memory pressure is set.
+    pub struct SpillPusher<T, C, P>
+    where
+        T: Serialize + for<'a> Deserialize<'a>,
+        C: ContainerBytes,
+        P: Push<Message<T, C>>,
+    {
+        pusher: P,
+        pressure: MemoryPressure,
+        /// The spill file and its writer, once a message has been spilled and until it is drained.
+        spill: Option<(PathBuf, BufWriter<File>)>,
+        phantom: PhantomData<(T, C)>,
+    }
+
+    impl<T, C, P> SpillPusher<T, C, P>
+    where
+        T: Serialize + for<'a> Deserialize<'a>,
+        C: ContainerBytes,
+        P: Push<Message<T, C>>,
+    {
+        /// Wraps `pusher` to spill messages while `pressure` is set.
+        pub fn new(pusher: P, pressure: MemoryPressure) -> Self {
+            SpillPusher { pusher, pressure, spill: None, phantom: PhantomData }
+        }
+
+        /// Sends the spilled messages to `pusher` in the order they were spilled, and removes the file.
+        fn drain(&mut self) {
+            if let Some((path, writer)) = self.spill.take() {
+                writer.into_inner().expect("failed to write spill file");
+                let file = File::open(&path).unwrap_or_else(|e| panic!("failed to open spill file {}: {e}", path.display()));
+                let mut reader = BufReader::new(file);
+                let mut length = [0u8; 8];
+                while reader.read_exact(&mut length).is_ok() {
+                    let mut bytes = vec![0u8; u64::from_le_bytes(length) as usize];
+                    reader.read_exact(&mut bytes).expect("failed to read spill file");
+                    self.pusher.push(&mut Some(Message::from_bytes(BytesMut::from(bytes).freeze())));
+                }
+                let _ = std::fs::remove_file(&path);
+            }
+        }
+    }
+
+    impl<T, C, P> Push<Message<T, C>> for SpillPusher<T, C, P>
+    where
+        T: Serialize + for<'a> Deserialize<'a>,
+        C: ContainerBytes,
+        P: Push<Message<T, C>>,
+    {
+        fn push(&mut self, message: &mut Option<Message<T, C>>) {
+            match message.take() {
+                // Spill while under pressure, and after spilling until drained to preserve order.
+                Some(spilled) if self.spill.is_some() || self.pressure.is_set() => {
+                    let (_, writer) = self.spill.get_or_insert_with(|| {
+                        let sequence = SPILL_FILES.fetch_add(1, Ordering::Relaxed);
+                        let path = std::env::temp_dir().join(format!("timely-exchange-spill-{}-{}", std::process::id(), sequence));
+                        let file = File::create(&path).unwrap_or_else(|e| panic!("failed to create spill file {}: {e}", path.display()));
+                        (path, BufWriter::new(file))
+                    });
+                    let length = spilled.length_in_bytes();
+                    writer.write_all(&(length as u64).to_le_bytes()).expect("failed to write spill file");
+                    spilled.into_bytes(writer);
+                },
+                Some(sent) => {
+                    *message = Some(sent);
+                    self.pusher.push(message);
+                },
+                None => {
+                    match &mut self.spill {
+                        // Under pressure the spill stays on disk, and only the buffered writes are flushed.
+                        Some((_, writer)) if self.pressure.is_set() => {
+                            writer.flush().expect("failed to write spill file");
+                        },
+                        _ => self.drain(),
+                    }
+                    self.pusher.push(message);
+                },
+            }
+        }
+    }
+
+    impl<T, C, P> Drop for SpillPusher<T, C, P>
+    where
+        T: Serialize + for<'a> Deserialize<'a>,
+        C: ContainerBytes,
+        P: Push<Message<T, C>>,
+    {
+        /// Sends any messages still spilled, which the progress tracker already counts as sent.
+        fn drop(&mut self) {
+            if std::thread::panicking() {
+                if let Some((path, _)) = self.spill.take() {
+                    let _ = std::fs::remove_file(&path);
+                }
+            }
+            else if self.spill.is_some() {
+                self.drain();
+                self.pusher.done();
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        use crate::communication::Push;
+        use crate::dataflow::channels::Message;
+        use super::{MemoryPressure, SpillPusher};
+
+        /// Records the sequence numbers of pushed messages, and `None` for each flush.
+        #[derive(Clone, Default)]
+        struct VecPusher(Rc<RefCell<Vec<Option<(usize, Vec<u64>)>>>>);
+        impl Push<Message<u64, Vec<u64>>> for VecPusher {
+            fn push(&mut self, message: &mut Option<Message<u64, Vec<u64>>>) {
+                self.0.borrow_mut().push(message.take().map(|message| (message.seq, message.data)));
+            }
+        }
+
+        #[test]
+        fn spilled_messages_are_sent_in_order_on_flush() {
+            let pressure = MemoryPressure::new();
+            let target = VecPusher::default();
+            let mut pusher = SpillPusher::new(target.clone(), pressure.clone());
+
+            pusher.push(&mut Some(Message::new(0, vec![0], 0, 0)));
+            pressure.set(true);
+            pusher.push(&mut Some(Message::new(0, vec![1, 2], 0, 1)));
+            pusher.push(&mut Some(Message::new(1, vec![3], 0, 2)));
+            // Messages after a spill are spilled until the flush, even once the pressure is released.
+            pressure.set(false);
+            pusher.push(&mut Some(Message::new(1, vec![4], 0, 3)));
+            assert_eq!(*target.0.borrow(), vec![Some((0, vec![0]))]);
+
+            pusher.done();
+            pusher.push(&mut Some(Message::new(2, vec![5], 0, 4)));
+            assert_eq!(*target.0.borrow(), vec![
+                Some((0, vec![0])),
+                Some((1, vec![1, 2])),
+                Some((2, vec![3])),
+                Some((3, vec![4])),
+                None,
+                Some((4, vec![5])),
+            ]);
+        }
+
+        #[test]
+        fn spilled_messages_stay_spilled_while_under_pressure() {
+            let pressure = MemoryPressure::new();
+            let target = VecPusher::default();
+            let mut pusher = SpillPusher::new(target.clone(), pressure.clone());
+
+            pressure.set(true);
+            for round in 0 .. 3 {
+                pusher.push(&mut Some(Message::new(round, vec![round], 0, round as usize)));
+                pusher.done();
+            }
+            // Each flush is forwarded, but the spilled messages are not read back.
+            assert_eq!(*target.0.borrow(), vec![None, None, None]);
+
+            // Messages pushed once the pressure clears follow those spilled before them.
+            pressure.set(false);
+            pusher.push(&mut Some(Message::new(3, vec![3], 0, 3)));
+            assert_eq!(*target.0.borrow(), vec![None, None, None]);
+            pusher.done();
+            assert_eq!(*target.0.borrow(), vec![
+                None, None, None,
+                Some((0, vec![0])),
+                Some((1, vec![1])),
+                Some((2, vec![2])),
+                Some((3, vec![3])),
+                None,
+            ]);
+        }
+
+        #[test]
+        fn spilled_messages_are_sent_on_drop() {
+            let pressure = MemoryPressure::new();
+            let target = VecPusher::default();
+            let mut pusher = SpillPusher::new(target.clone(), pressure.clone());
+
+            pressure.set(true);
+            pusher.push(&mut Some(Message::new(0, vec![0], 0, 0)));
+            pusher.done();
+            drop(pusher);
+            assert_eq!(*target.0.borrow(), vec![None, Some((0, vec![0])), None]);
+        }
+    }
+}
+
+#[cfg(feature = "testing")]
+pub use fault_injector::{FaultInjector, FaultPolicy, FaultPuller};
+/// Parallelization contract that injects faults into received messages, for testing.
+#[cfg(feature = "testing")]
+mod fault_injector {
+
+    use std::collections::VecDeque;
+    use std::rc::Rc;
+
+    use crate::Accountable;
+    use crate::communication::Pull;
+    use crate::dataflow::channels::Message;
+    use crate::logging::TimelyLogger;
+    use crate::worker::AsWorker;
+
+    use super::{PactKind, ParallelizationContract};
+
+    /// The faults a [`FaultInjector`] injects, and the seed of the generator that chooses the messages they affect.
+    ///
+    /// Each worker seeds its generator from the seed and its index, and so a dataflow that receives
+    /// the same messages in the same order is affected by the same faults in each run.
+    pub struct FaultPolicy<C> {
+        seed: u64,
+        reorder: f64,
+        corrupt: f64,
+        corruption: Option<Box<dyn FnMut(&mut C)>>,
+    }
+
+    impl<C> FaultPolicy<C> {
+        /// A policy that injects no faults, with generators seeded from `seed`.
+        pub fn new(seed: u64) -> Self {
+            FaultPolicy { seed, reorder: 0.0, corrupt: 0.0, corruption: None }
+        }
+        /// Moves each message, with probability `probability`, behind those received after it in the same scheduling.
+        pub fn reorder(mut self, probability: f64) -> Self {
+            self.reorder = probability;
+            self
+        }
+        /// Applies `corruption` to the container of each message with probability `probability`.
+        ///
+        /// The corruption may change records, but not their number, which progress tracking relies on.
+        pub fn corrupt(mut self, probability: f64, corruption: impl FnMut(&mut C)+'static) -> Self {
+            self.corrupt = probability;
+            self.corruption = Some(Box::new(corruption));
+            self
+        }
+    }
+
+    /// Wraps a pact so that the messages it delivers are reordered and corrupted by a [`FaultPolicy`].
+    ///
+    /// Faults are injected as messages are received, and so affect the messages of all sources,
+    /// including those of the receiving worker. A reordered message is still delivered in the
+    /// scheduling that receives it, after the messages received with it. Faults that change the
+    /// number of records, such as dropping or duplicating messages, cannot be injected by a pact,
+    /// as progress tracking would then never see the records of a time consumed, or see more
+    /// consumed than were produced; dataflows designed for at-least-once delivery can instead be
+    /// tested by an upstream operator that duplicates or withholds records.
+    ///
+    /// The pact is available with the `testing` feature.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::channels::pact::{Exchange, FaultInjector, FaultPolicy};
+    /// use timely::dataflow::operators::{ToStream, Operator, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     let policy = FaultPolicy::new(7).reorder(0.5).corrupt(0.1, |data: &mut Vec<u64>| data.iter_mut().for_each(|x| *x += 100));
+    ///     (0..10u64).to_stream(scope)
+    ///               .unary(FaultInjector::new(Exchange::new(|x: &u64| *x), policy), "Faulty", |_, _| |input, output| {
+    ///                   input.for_each(|time, data| output.session(&time).give_container(data));
+    ///               })
+    ///               .inspect(|x| println!("seen: {:?}", x));
+    /// });
+    /// ```
+    pub struct FaultInjector<P, C> {
+        pact: P,
+        policy: FaultPolicy<C>,
+    }
+
+    impl<P, C> FaultInjector<P, C> {
+        /// Wraps an existing pact, injecting the faults of `policy` into the messages it delivers.
+        pub fn new(pact: P, policy: FaultPolicy<C>) -> Self {
+            FaultInjector { pact, policy }
+        }
+    }
+
+    impl<T, C, P> ParallelizationContract<T, C> for FaultInjector<P, C>
+    where
+        T: 'static,
+        C: Accountable + 'static,
+        P: ParallelizationContract<T, C>,
+    {
+        type Pusher = P::Pusher;
+        type Puller = FaultPuller<T, C, P::Puller>;
+        fn connect<A: AsWorker>(self, allocator: &mut A, identifier: usize, address: Rc<[usize]>, logging: Option<TimelyLogger>) -> (Self::Pusher, Self::Puller) {
+            let index = allocator.index();
+            let (pusher, puller) = self.pact.connect(allocator, identifier, address, logging);
+            (pusher, FaultPuller::new(puller, self.policy, index))
+        }
+        fn kind(&self) -> PactKind { self.pact.kind() }
+    }
+
+    /// Wraps a `Message<T,C>` puller to reorder and corrupt the messages it receives.
+    pub struct FaultPuller<T, C, P> {
+        puller: P,
+        policy: FaultPolicy<C>,
+        /// The state of a xorshift generator.
+        state: u64,
+        /// Messages received and ready to be released, in their possibly reordered order.
+        ready: VecDeque<Message<T, C>>,
+        current: Option<Message<T, C>>,
+    }
+
+    impl<T, C, P> FaultPuller<T, C, P> {
+        /// Allocates a new puller wrapping `puller`, injecting the faults of `policy` as the worker `index`.
+        pub fn new(puller: P, policy: FaultPolicy<C>, index: usize) -> Self {
+            let state = (policy.seed ^ (index as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15)).max(1);
+            FaultPuller { puller, policy, state, ready: VecDeque::new(), current: None }
+        }
+
+        /// Returns `true` with probability `probability`.
+        fn chance(&mut self, probability: f64) -> bool {
+            self.state ^= self.state << 13;
+            self.state ^= self.state >> 7;
+            self.state ^= self.state << 17;
+            ((self.state >> 11) as f64 / (1u64 << 53) as f64) < probability
+        }
+    }
+
+    impl<T, C: Accountable, P: Pull<Message<T, C>>> Pull<Message<T, C>> for FaultPuller<T, C, P> {
+        #[inline]
+        fn pull(&mut self) -> &mut Option<Message<T, C>> {
+            if self.ready.is_empty() {
+                let mut deferred = Vec::new();
+                while let Some(mut message) = self.puller.recv() {
+                    if self.policy.corruption.is_some() && self.chance(self.policy.corrupt) {
+                        let records = message.data.record_count();
+                        (self.policy.corruption.as_mut().unwrap())(&mut message.data);
+                        assert_eq!(message.data.record_count(), records, "FaultInjector: corruption must preserve the number of records");
+                    }
+                    if self.chance(self.policy.reorder) { deferred.push(message); }
+                    else { self.ready.push_back(message); }
+                }
+                self.ready.extend(deferred);
+            }
+            self.current = self.ready.pop_front();
+            &mut self.current
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use crate::communication::Pull;
+        use crate::dataflow::channels::Message;
+        use super::{FaultPolicy, FaultPuller};
+
+        struct VecPuller(std::vec::IntoIter<Message<(), Vec<u64>>>, Option<Message<(), Vec<u64>>>);
+        impl Pull<Message<(), Vec<u64>>> for VecPuller {
+            fn pull(&mut self) -> &mut Option<Message<(), Vec<u64>>> {
+                self.1 = self.0.next();
+                &mut self.1
+            }
+        }
+
+        /// The contents of 100 messages of one record each, as received through `policy`.
+        fn received(policy: FaultPolicy<Vec<u64>>) -> Vec<u64> {
+            let messages = (0..100).map(|x| Message::new((), vec![x], 0, x as usize)).collect::<Vec<_>>();
+            let mut puller = FaultPuller::new(VecPuller(messages.into_iter(), None), policy, 0);
+            let mut received = Vec::new();
+            while let Some(message) = puller.recv() {
+                received.extend(message.data);
+            }
+            received
+        }
+
+        #[test]
+        fn messages_are_reordered_reproducibly() {
+            assert_eq!(received(FaultPolicy::new(1)), (0..100).collect::<Vec<_>>());
+            let reordered = received(FaultPolicy::new(1).reorder(0.5));
+            assert_ne!(reordered, (0..100).collect::<Vec<_>>());
+            let mut sorted = reordered.clone();
+            sorted.sort();
+            assert_eq!(sorted, (0..100).collect::<Vec<_>>());
+            assert_eq!(reordered, received(FaultPolicy::new(1).reorder(0.5)));
+            assert_ne!(reordered, received(FaultPolicy::new(2).reorder(0.5)));
+        }
+
+        #[test]
+        fn messages_are_corrupted_with_their_probability() {
+            let all = received(FaultPolicy::new(1).corrupt(1.0, |data: &mut Vec<u64>| data[0] += 1000));
+            assert_eq!(all, (1000..1100).collect::<Vec<_>>());
+            let some = received(FaultPolicy::new(1).corrupt(0.5, |data: &mut Vec<u64>| data[0] += 1000));
+            let count = some.iter().filter(|x| **x >= 1000).count();
+            assert!((20..80).contains(&count));
+        }
+
+        #[test]
+        #[should_panic(expected = "must preserve the number of records")]
+        fn corruption_may_not_drop_records() {
+            received(FaultPolicy::new(1).corrupt(1.0, |data: &mut Vec<u64>| data.clear()));
+        }
     }
 }
 
@@ -224,5 +1359,23 @@ mod push_pull {
 
             result
         }
+        #[inline]
+        fn pull_batch(&mut self) -> &mut [Option<Message<T, C>>] {
+            let batch = self.puller.pull_batch();
+            if let Some(logger) = self.logging.as_ref() {
+                for bundle in batch.iter().flatten() {
+                    logger.log(MessagesEvent {
+                        is_send: false,
+                        channel: self.channel,
+                        source: bundle.from,
+                        target: self.index,
+                        seq_no: bundle.seq,
+                        record_count: bundle.data.record_count(),
+                    });
+                }
+            }
+
+            batch
+        }
     }
 }
@@ -0,0 +1,295 @@
+//! A container of plain-old-data values that can borrow its contents from received bytes.
+
+use std::io::Write;
+
+use bytemuck::Pod;
+
+use crate::bytes::arc::Bytes;
+use crate::container::{Accountable, DrainContainer, PushInto, SizableContainer};
+use crate::dataflow::channels::ContainerBytes;
+
+/// A container of plain-old-data values.
+///
+/// A `PodContainer` serializes as its length followed by the bytes of its values. When received
+/// from another process, it references the received bytes in place, rather than copying them into
+/// a fresh allocation, whenever they are suitably aligned for `T`; otherwise it copies them into
+/// an owned vector. The contents are read through [`PodContainer::as_slice`] in either case, and
+/// a borrowed container copies its contents into an owned vector only if it is pushed into.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::{Exchange, InspectCore};
+/// use timely::dataflow::operators::core::ToStreamBuilder;
+/// use timely::dataflow::channels::pod::PodContainer;
+/// use timely::container::CapacityContainerBuilder;
+///
+/// let config = timely::Config {
+///     communication: timely::CommunicationConfig::ProcessBinary(2),
+///     worker: Default::default(),
+/// };
+/// timely::execute(config, |worker| {
+///     worker.dataflow::<u64,_,_>(|scope| {
+///         ToStreamBuilder::<CapacityContainerBuilder<PodContainer<u64>>>::to_stream_with_builder(0..100u64, scope)
+///             .exchange(|x| *x)
+///             .inspect_container(|event| {
+///                 if let Ok((_time, container)) = event {
+///                     assert!(container.as_slice().iter().all(|x| *x < 100));
+///                 }
+///             });
+///     });
+/// }).unwrap();
+/// ```
+#[derive(Clone)]
+pub struct PodContainer<T> {
+    contents: Contents<T>,
+}
+
+#[derive(Clone)]
+enum Contents<T> {
+    /// Values held in an owned vector.
+    Owned(Vec<T>),
+    /// The first `len` values stored in `bytes`, which are aligned for `T`.
+    Bytes { bytes: Bytes, len: usize },
+}
+
+impl<T: Pod> PodContainer<T> {
+    /// Creates an empty container.
+    pub fn new() -> Self {
+        Self::from(Vec::new())
+    }
+
+    /// The values of the container.
+    pub fn as_slice(&self) -> &[T] {
+        match &self.contents {
+            Contents::Owned(values) => values,
+            Contents::Bytes { bytes, len } => bytemuck::cast_slice(&bytes[.. len * std::mem::size_of::<T>()]),
+        }
+    }
+
+    /// The number of values in the container.
+    pub fn len(&self) -> usize {
+        match &self.contents {
+            Contents::Owned(values) => values.len(),
+            Contents::Bytes { len, .. } => *len,
+        }
+    }
+
+    /// Returns `true` if the container has no values.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if the values are borrowed from received bytes.
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self.contents, Contents::Bytes { .. })
+    }
+
+    /// The values of the container as an owned vector, copying any borrowed values into it.
+    pub fn make_owned(&mut self) -> &mut Vec<T> {
+        if let Contents::Bytes { .. } = self.contents {
+            self.contents = Contents::Owned(self.as_slice().to_vec());
+        }
+        match &mut self.contents {
+            Contents::Owned(values) => values,
+            Contents::Bytes { .. } => unreachable!("contents were made owned"),
+        }
+    }
+}
+
+impl<T: Pod> Default for PodContainer<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> From<Vec<T>> for PodContainer<T> {
+    fn from(values: Vec<T>) -> Self {
+        PodContainer { contents: Contents::Owned(values) }
+    }
+}
+
+impl<T: Pod + std::fmt::Debug> std::fmt::Debug for PodContainer<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.as_slice()).finish()
+    }
+}
+
+impl<T: Pod> Accountable for PodContainer<T> {
+    #[inline] fn record_count(&self) -> i64 { i64::try_from(self.len()).unwrap() }
+    #[inline] fn is_empty(&self) -> bool { PodContainer::is_empty(self) }
+}
+
+impl<T: Pod> DrainContainer for PodContainer<T> {
+    type Item<'a> = T where T: 'a;
+    type DrainIter<'a> = PodDrain<'a, T> where T: 'a;
+    fn drain(&mut self) -> Self::DrainIter<'_> {
+        // Borrowed values are moved into the iterator, leaving the container empty.
+        if self.is_borrowed() {
+            if let Contents::Bytes { bytes, len } = std::mem::replace(&mut self.contents, Contents::Owned(Vec::new())) {
+                return PodDrain::Bytes { bytes, len, index: 0 };
+            }
+        }
+        PodDrain::Owned(self.make_owned().drain(..))
+    }
+}
+
+/// Draining iterator of a [`PodContainer`].
+pub enum PodDrain<'a, T> {
+    /// Drains owned values.
+    Owned(std::vec::Drain<'a, T>),
+    /// Copies out values borrowed from received bytes.
+    Bytes {
+        /// The received bytes.
+        bytes: Bytes,
+        /// The number of values in `bytes`.
+        len: usize,
+        /// The index of the next value.
+        index: usize,
+    },
+}
+
+impl<T: Pod> Iterator for PodDrain<'_, T> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        match self {
+            PodDrain::Owned(values) => values.next(),
+            PodDrain::Bytes { bytes, len, index } => {
+                if index < len {
+                    let size = std::mem::size_of::<T>();
+                    let value = bytemuck::cast_slice::<u8, T>(&bytes[*index * size .. (*index + 1) * size])[0];
+                    *index += 1;
+                    Some(value)
+                }
+                else { None }
+            },
+        }
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = match self {
+            PodDrain::Owned(values) => values.len(),
+            PodDrain::Bytes { len, index, .. } => *len - *index,
+        };
+        (remaining, Some(remaining))
+    }
+}
+
+impl<T: Pod> SizableContainer for PodContainer<T> {
+    fn at_capacity(&self) -> bool {
+        match &self.contents {
+            Contents::Owned(values) => values.at_capacity(),
+            Contents::Bytes { .. } => true,
+        }
+    }
+    fn ensure_capacity(&mut self, stash: &mut Option<Self>) {
+        if self.is_borrowed() {
+            self.contents = Contents::Owned(Vec::new());
+        }
+        let mut stash_values = stash.take().and_then(|stash| match stash.contents {
+            Contents::Owned(values) => Some(values),
+            Contents::Bytes { .. } => None,
+        });
+        self.make_owned().ensure_capacity(&mut stash_values);
+    }
+    fn allocated_bytes(&self) -> usize {
+        match &self.contents {
+            Contents::Owned(values) => values.allocated_bytes(),
+            Contents::Bytes { .. } => 0,
+        }
+    }
+}
+
+impl<T: Pod> PushInto<T> for PodContainer<T> {
+    #[inline]
+    fn push_into(&mut self, item: T) {
+        self.make_owned().push(item);
+    }
+}
+
+impl<T: Pod> PushInto<&T> for PodContainer<T> {
+    #[inline]
+    fn push_into(&mut self, item: &T) {
+        self.make_owned().push(*item);
+    }
+}
+
+impl<T: Pod> ContainerBytes for PodContainer<T> {
+    fn from_bytes(mut bytes: Bytes) -> Self {
+        let len = u64::from_le_bytes(bytes[.. 8].try_into().unwrap()) as usize;
+        bytes.extract_to(8);
+        let size = std::mem::size_of::<T>();
+        if size == 0 {
+            return Self::from(vec![T::zeroed(); len]);
+        }
+        if bytemuck::try_cast_slice::<u8, T>(&bytes[.. len * size]).is_ok() {
+            PodContainer { contents: Contents::Bytes { bytes, len } }
+        }
+        else {
+            // The bytes are not aligned for `T`, and must be copied.
+            let mut values = vec![T::zeroed(); len];
+            bytemuck::cast_slice_mut::<T, u8>(&mut values).copy_from_slice(&bytes[.. len * size]);
+            Self::from(values)
+        }
+    }
+
+    fn length_in_bytes(&self) -> usize {
+        8 + ((self.len() * std::mem::size_of::<T>() + 7) & !7)
+    }
+
+    fn into_bytes<W: Write>(&self, writer: &mut W) {
+        let values: &[u8] = bytemuck::cast_slice(self.as_slice());
+        writer.write_all(&(self.len() as u64).to_le_bytes()).unwrap();
+        writer.write_all(values).unwrap();
+        let slop = ((values.len() + 7) & !7) - values.len();
+        writer.write_all(&[0u8; 8][.. slop]).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bytes::arc::BytesMut;
+    use crate::container::DrainContainer;
+    use crate::dataflow::channels::ContainerBytes;
+    use super::PodContainer;
+
+    /// A buffer of `u64`s, and so aligned for `u32`, presented as bytes.
+    struct Aligned(Vec<u64>);
+    impl std::ops::Deref for Aligned {
+        type Target = [u8];
+        fn deref(&self) -> &[u8] { bytemuck::cast_slice(&self.0) }
+    }
+    impl std::ops::DerefMut for Aligned {
+        fn deref_mut(&mut self) -> &mut [u8] { bytemuck::cast_slice_mut(&mut self.0) }
+    }
+
+    /// Serializes `container` at `offset` into an aligned buffer, and deserializes it from there.
+    fn roundtrip(container: &PodContainer<u32>, offset: usize) -> PodContainer<u32> {
+        let mut serialized = Vec::new();
+        container.into_bytes(&mut serialized);
+        assert_eq!(serialized.len(), container.length_in_bytes());
+        let mut buffer = Aligned(vec![0; (offset + serialized.len()).div_ceil(8)]);
+        buffer[offset .. offset + serialized.len()].copy_from_slice(&serialized);
+        let mut bytes = BytesMut::from(buffer).freeze();
+        bytes.extract_to(offset);
+        PodContainer::from_bytes(bytes)
+    }
+
+    #[test]
+    fn borrows_aligned_and_copies_unaligned() {
+        let container = PodContainer::from((0..11u32).collect::<Vec<_>>());
+
+        let mut aligned = roundtrip(&container, 0);
+        assert!(aligned.is_borrowed());
+        assert_eq!(aligned.as_slice(), container.as_slice());
+        assert_eq!(aligned.drain().collect::<Vec<_>>(), (0..11).collect::<Vec<_>>());
+        assert!(aligned.is_empty());
+
+        let unaligned = roundtrip(&container, 1);
+        assert!(!unaligned.is_borrowed());
+        assert_eq!(unaligned.as_slice(), container.as_slice());
+
+        let mut pushed = roundtrip(&container, 0);
+        crate::container::PushInto::push_into(&mut pushed, 11);
+        assert!(!pushed.is_borrowed());
+        assert_eq!(pushed.as_slice(), (0..12).collect::<Vec<_>>());
+    }
+}
@@ -1,6 +1,10 @@
 //! The exchange pattern distributes pushed data between many target pushees.
 
-use crate::ContainerBuilder;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::rc::Rc;
+
+use crate::{Container, ContainerBuilder};
 use crate::communication::Push;
 use crate::container::{DrainContainer, PushInto};
 use crate::dataflow::channels::Message;
@@ -23,35 +27,83 @@ pub trait Distributor<C> {
     fn relax(&mut self) { }
 }
 
+/// Maps the hash of a record to the value from which the index of its pusher is computed.
+///
+/// The index is the finalized hash modulo the number of pushers, which for a power of two number
+/// of pushers is its low bits. Hash functions whose outputs vary little in those bits, such as
+/// the identity on keys that are all multiples of some power of two, send most records to few
+/// pushers; a finalizer that mixes all bits of the hash into the low bits avoids this.
+pub trait HashFinalizer {
+    /// Finalizes the hash of a record.
+    fn finalize(&self, hash: u64) -> u64;
+}
+
+/// Uses the hash of a record unchanged, which is the default.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct IdentityFinalizer;
+
+impl HashFinalizer for IdentityFinalizer {
+    #[inline(always)]
+    fn finalize(&self, hash: u64) -> u64 { hash }
+}
+
+/// Mixes the bits of the hash of a record with the 64-bit finalizer of MurmurHash3, so that each
+/// bit of the hash affects all bits of the result.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AvalancheFinalizer;
+
+impl HashFinalizer for AvalancheFinalizer {
+    #[inline]
+    fn finalize(&self, mut hash: u64) -> u64 {
+        hash ^= hash >> 33;
+        hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+        hash ^= hash >> 33;
+        hash = hash.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+        hash ^= hash >> 33;
+        hash
+    }
+}
+
 /// A distributor creating containers from a drainable container based
 /// on a hash function of the container's item.
-pub struct DrainContainerDistributor<CB, H> {
+pub struct DrainContainerDistributor<CB, H, Z = IdentityFinalizer> {
     builders: Vec<CB>,
     hash_func: H,
+    finalizer: Z,
 }
 
 impl<CB: Default, H> DrainContainerDistributor<CB, H> {
     /// Constructs a new `DrainContainerDistributor` with the given hash function for a number of
     /// peers.
     pub fn new(hash_func: H, peers: usize) -> Self {
+        Self::with_finalizer(hash_func, IdentityFinalizer, peers)
+    }
+}
+
+impl<CB: Default, H, Z> DrainContainerDistributor<CB, H, Z> {
+    /// Constructs a new `DrainContainerDistributor` with the given hash function and finalizer for
+    /// a number of peers.
+    pub fn with_finalizer(hash_func: H, finalizer: Z, peers: usize) -> Self {
         Self {
             builders: std::iter::repeat_with(Default::default).take(peers).collect(),
             hash_func,
+            finalizer,
         }
     }
 }
 
-impl<CB, H> Distributor<CB::Container> for DrainContainerDistributor<CB, H>
+impl<CB, H, Z> Distributor<CB::Container> for DrainContainerDistributor<CB, H, Z>
 where
     CB: ContainerBuilder<Container: DrainContainer> + for<'a> PushInto<<CB::Container as DrainContainer>::Item<'a>>,
     for<'a> H: FnMut(&<CB::Container as DrainContainer>::Item<'a>) -> u64,
+    Z: HashFinalizer,
 {
     fn partition<T: Clone, P: Push<Message<T, CB::Container>>>(&mut self, container: &mut CB::Container, time: &T, pushers: &mut [P]) {
         debug_assert_eq!(self.builders.len(), pushers.len());
         if pushers.len().is_power_of_two() {
             let mask = (pushers.len() - 1) as u64;
             for datum in container.drain() {
-                let index = ((self.hash_func)(&datum) & mask) as usize;
+                let index = (self.finalizer.finalize((self.hash_func)(&datum)) & mask) as usize;
                 self.builders[index].push_into(datum);
                 while let Some(produced) = self.builders[index].extract() {
                     Message::push_at(produced, time.clone(), &mut pushers[index]);
@@ -61,7 +113,7 @@ where
         else {
             let num_pushers = pushers.len() as u64;
             for datum in container.drain() {
-                let index = ((self.hash_func)(&datum) % num_pushers) as usize;
+                let index = (self.finalizer.finalize((self.hash_func)(&datum)) % num_pushers) as usize;
                 self.builders[index].push_into(datum);
                 while let Some(produced) = self.builders[index].extract() {
                     Message::push_at(produced, time.clone(), &mut pushers[index]);
@@ -85,8 +137,210 @@ where
     }
 }
 
+/// A distributor creating containers from a drainable container based on a hash function of
+/// the container's item, which only distributes to a subset of the pushers.
+pub struct SubsetDistributor<CB, H> {
+    builders: Vec<CB>,
+    targets: Vec<usize>,
+    hash_func: H,
+}
+
+impl<CB: Default, H> SubsetDistributor<CB, H> {
+    /// Constructs a new `SubsetDistributor` with the given hash function, distributing among the
+    /// `targets` out of `peers` pushers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `targets` is empty, or contains an index of `peers` or greater.
+    pub fn new(hash_func: H, targets: Vec<usize>, peers: usize) -> Self {
+        assert!(!targets.is_empty(), "subset exchange requires at least one target worker");
+        if let Some(target) = targets.iter().find(|target| **target >= peers) {
+            panic!("subset exchange target worker {} out of range for {} peers", target, peers);
+        }
+        Self {
+            builders: std::iter::repeat_with(Default::default).take(targets.len()).collect(),
+            targets,
+            hash_func,
+        }
+    }
+}
+
+impl<CB, H> Distributor<CB::Container> for SubsetDistributor<CB, H>
+where
+    CB: ContainerBuilder<Container: DrainContainer> + for<'a> PushInto<<CB::Container as DrainContainer>::Item<'a>>,
+    for<'a> H: FnMut(&<CB::Container as DrainContainer>::Item<'a>) -> u64,
+{
+    fn partition<T: Clone, P: Push<Message<T, CB::Container>>>(&mut self, container: &mut CB::Container, time: &T, pushers: &mut [P]) {
+        let num_targets = self.targets.len() as u64;
+        for datum in container.drain() {
+            let index = ((self.hash_func)(&datum) % num_targets) as usize;
+            self.builders[index].push_into(datum);
+            while let Some(produced) = self.builders[index].extract() {
+                Message::push_at(produced, time.clone(), &mut pushers[self.targets[index]]);
+            }
+        }
+    }
+
+    fn flush<T: Clone, P: Push<Message<T, CB::Container>>>(&mut self, time: &T, pushers: &mut [P]) {
+        for (builder, target) in self.builders.iter_mut().zip(self.targets.iter()) {
+            while let Some(container) = builder.finish() {
+                Message::push_at(container, time.clone(), &mut pushers[*target]);
+            }
+        }
+    }
+
+    fn relax(&mut self) {
+        for builder in &mut self.builders {
+            builder.relax();
+        }
+    }
+}
+
+/// A reusable assignment of keys to workers.
+///
+/// A routing table captures how a relation has been partitioned among workers, so that other
+/// streams can be exchanged to the workers already holding each key, using
+/// [`ExchangeByTable`](crate::dataflow::channels::pact::ExchangeByTable). Clones of a table
+/// share its assignment, which must be the same on all workers.
+pub struct RoutingTable<K> {
+    route: Rc<dyn Fn(&K, usize) -> usize>,
+}
+
+impl<K> Clone for RoutingTable<K> {
+    fn clone(&self) -> Self {
+        Self { route: Rc::clone(&self.route) }
+    }
+}
+
+impl<K: 'static> RoutingTable<K> {
+    /// A table assigning each key to the worker `route(key, peers)`, for a custom partitioning.
+    pub fn new(route: impl Fn(&K, usize) -> usize + 'static) -> Self {
+        Self { route: Rc::new(route) }
+    }
+
+    /// A table assigning keys as an [`Exchange`](crate::dataflow::channels::pact::Exchange) with
+    /// the distribution function `hash` does.
+    pub fn hashed(hash: impl Fn(&K) -> u64 + 'static) -> Self {
+        Self::new(move |key, peers| (hash(key) % peers as u64) as usize)
+    }
+
+    /// A table assigning to worker `i` the keys from `splitters[i-1]` up to but not including
+    /// `splitters[i]`, the first worker the keys less than `splitters[0]`, and the worker after the
+    /// last splitter the remaining keys.
+    ///
+    /// The splitters must be sorted, and there should be fewer of them than there are workers.
+    pub fn ranges(splitters: Vec<K>) -> Self where K: Ord {
+        Self::new(move |key, _peers| splitters.partition_point(|splitter| splitter <= key))
+    }
+
+    /// A table assigning the keys of `assignments` to their workers, and other keys as `fallback` does.
+    pub fn explicit(assignments: HashMap<K, usize>, fallback: RoutingTable<K>) -> Self where K: Hash+Eq {
+        Self::new(move |key, peers| assignments.get(key).copied().unwrap_or_else(|| fallback.worker(key, peers)))
+    }
+
+    /// The worker to which the table assigns `key`, out of `peers` workers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the table assigns the key to a worker of index `peers` or greater.
+    pub fn worker(&self, key: &K, peers: usize) -> usize {
+        let worker = (self.route)(key, peers);
+        if worker >= peers {
+            panic!("routing table assigned a key to worker {} out of range for {} peers", worker, peers);
+        }
+        worker
+    }
+}
+
+/// A distributor creating containers from a drainable container based on a key of the container's
+/// item, which routes each item to the worker a routing table assigns its key.
+pub struct TableDistributor<CB, K, F> {
+    builders: Vec<CB>,
+    table: RoutingTable<K>,
+    key_func: F,
+}
+
+impl<CB: Default, K, F> TableDistributor<CB, K, F> {
+    /// Constructs a new `TableDistributor` with the given routing table and key function for a
+    /// number of peers.
+    pub fn new(table: RoutingTable<K>, key_func: F, peers: usize) -> Self {
+        Self {
+            builders: std::iter::repeat_with(Default::default).take(peers).collect(),
+            table,
+            key_func,
+        }
+    }
+}
+
+impl<CB, K: 'static, F> Distributor<CB::Container> for TableDistributor<CB, K, F>
+where
+    CB: ContainerBuilder<Container: DrainContainer> + for<'a> PushInto<<CB::Container as DrainContainer>::Item<'a>>,
+    for<'a> F: FnMut(&<CB::Container as DrainContainer>::Item<'a>) -> K,
+{
+    fn partition<T: Clone, P: Push<Message<T, CB::Container>>>(&mut self, container: &mut CB::Container, time: &T, pushers: &mut [P]) {
+        let peers = pushers.len();
+        for datum in container.drain() {
+            let index = self.table.worker(&(self.key_func)(&datum), peers);
+            self.builders[index].push_into(datum);
+            while let Some(produced) = self.builders[index].extract() {
+                Message::push_at(produced, time.clone(), &mut pushers[index]);
+            }
+        }
+    }
+
+    fn flush<T: Clone, P: Push<Message<T, CB::Container>>>(&mut self, time: &T, pushers: &mut [P]) {
+        for (builder, pusher) in self.builders.iter_mut().zip(pushers.iter_mut()) {
+            while let Some(container) = builder.finish() {
+                Message::push_at(container, time.clone(), pusher);
+            }
+        }
+    }
+
+    fn relax(&mut self) {
+        for builder in &mut self.builders {
+            builder.relax();
+        }
+    }
+}
+
+/// A distributor sending each container whole to the pusher a function of the container selects.
+///
+/// Upstream operators that already group records by destination produce containers whose records
+/// all belong to one worker, and this distributor routes each of them without inspecting or copying
+/// its records. The function is called once per non-empty container, and the index of its pusher is
+/// the result modulo the number of pushers, as for a hash in [`DrainContainerDistributor`].
+pub struct PreRoutedDistributor<F> {
+    route_func: F,
+}
+
+impl<F> PreRoutedDistributor<F> {
+    /// Constructs a new `PreRoutedDistributor` with the given function from containers to their targets.
+    pub fn new(route_func: F) -> Self {
+        Self { route_func }
+    }
+}
+
+impl<C: Container, F: FnMut(&C) -> u64> Distributor<C> for PreRoutedDistributor<F> {
+    fn partition<T: Clone, P: Push<Message<T, C>>>(&mut self, container: &mut C, time: &T, pushers: &mut [P]) {
+        if !container.is_empty() {
+            let index = ((self.route_func)(container) % pushers.len() as u64) as usize;
+            Message::push_at(container, time.clone(), &mut pushers[index]);
+        }
+    }
+
+    fn flush<T: Clone, P: Push<Message<T, C>>>(&mut self, _time: &T, _pushers: &mut [P]) { }
+}
+
 // TODO : Software write combining
 /// Distributes records among target pushees according to a distributor.
+///
+/// The distributor may buffer records for each target, and an `Exchange` only sends buffered
+/// records when the time of pushed messages changes, or when it is flushed by pushing `None`
+/// (as by [`Push::done`]). A flush sends all records buffered for all targets, relaxes the
+/// distributor, and then flushes each target in turn. Operator outputs flush their pushers at the
+/// end of each invocation of the operator, so records are not held back past the invocation that
+/// produced them, even if the operator then drops its capabilities; other users must flush the
+/// exchange themselves before relying on its records having been sent.
 pub struct Exchange<T, P, D> {
     pushers: Vec<P>,
     current: Option<T>,
@@ -136,7 +390,7 @@ where
             self.distributor.partition(data, time, &mut self.pushers);
         }
         else {
-            // flush
+            // flush all buffered records before flushing the targets, so that none are stranded.
             if let Some(time) = self.current.take() {
                 self.distributor.flush(&time, &mut self.pushers);
             }
@@ -147,3 +401,119 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use crate::communication::Push;
+    use crate::container::CapacityContainerBuilder;
+    use crate::dataflow::channels::Message;
+    use super::{AvalancheFinalizer, Distributor, DrainContainerDistributor, Exchange, IdentityFinalizer, PreRoutedDistributor, RoutingTable, TableDistributor};
+
+    /// Records the times and records of pushed messages, and `None` for each flush.
+    #[derive(Clone, Default)]
+    struct VecPusher(Rc<RefCell<Vec<Option<(u64, Vec<u64>)>>>>);
+    impl Push<Message<u64, Vec<u64>>> for VecPusher {
+        fn push(&mut self, message: &mut Option<Message<u64, Vec<u64>>>) {
+            self.0.borrow_mut().push(message.take().map(|message| (message.time, message.data)));
+        }
+    }
+
+    #[test]
+    fn flush_strands_no_records() {
+        for peers in [2, 3] {
+            let targets = (0..peers).map(|_| VecPusher::default()).collect::<Vec<_>>();
+            let distributor = DrainContainerDistributor::<CapacityContainerBuilder<Vec<u64>>, _>::new(|x: &u64| *x, peers);
+            let mut exchange = Exchange::new(targets.clone(), distributor);
+
+            // A burst of records at the final time, too few to fill any container.
+            exchange.push(&mut Some(Message::new(0, (0..10).collect(), 0, 0)));
+            exchange.push(&mut Some(Message::new(1, (10..20).collect(), 0, 0)));
+            exchange.done();
+
+            let mut received = Vec::new();
+            for (index, target) in targets.iter().enumerate() {
+                let pushed = target.0.borrow();
+                // Each target is flushed once, after all of its records.
+                assert_eq!(pushed.last(), Some(&None));
+                assert_eq!(pushed.iter().filter(|message| message.is_none()).count(), 1);
+                for (time, data) in pushed.iter().flatten() {
+                    assert!(data.iter().all(|x| *x as usize % peers == index));
+                    received.extend(data.iter().map(|x| (*time, *x)));
+                }
+            }
+            received.sort();
+            let expected = (0..20).map(|x| (x / 10, x)).collect::<Vec<_>>();
+            assert_eq!(received, expected);
+        }
+    }
+
+    /// The number of records sent to each of `peers` pushers, for keys that are multiples of `peers`.
+    fn spread<Z: super::HashFinalizer>(finalizer: Z, peers: usize) -> Vec<usize> {
+        let mut targets = (0..peers).map(|_| VecPusher::default()).collect::<Vec<_>>();
+        let mut distributor = DrainContainerDistributor::<CapacityContainerBuilder<Vec<u64>>, _, _>::with_finalizer(|x: &u64| *x, finalizer, peers);
+        let mut keys = (0..10_000).map(|x| x * peers as u64).collect();
+        distributor.partition(&mut keys, &0, &mut targets);
+        distributor.flush(&0, &mut targets);
+        targets.iter().map(|target| target.0.borrow().iter().flatten().map(|(_, data)| data.len()).sum()).collect()
+    }
+
+    #[test]
+    fn avalanche_balances_clustered_hashes() {
+        for peers in [4, 6] {
+            // Without mixing, every key is sent to the first pusher.
+            let identity = spread(IdentityFinalizer, peers);
+            assert_eq!(identity[0], 10_000);
+
+            // With mixing, each pusher receives within a fifth of its fair share.
+            let mixed = spread(AvalancheFinalizer, peers);
+            assert_eq!(mixed.iter().sum::<usize>(), 10_000);
+            let fair = 10_000 / peers;
+            assert!(mixed.iter().all(|count| count.abs_diff(fair) < fair / 5), "unbalanced: {:?}", mixed);
+        }
+    }
+
+    /// The records of `keys` that a distributor by `table` sends to each of `peers` pushers.
+    fn routed(table: RoutingTable<u64>, peers: usize, keys: Vec<u64>) -> Vec<Vec<u64>> {
+        let mut targets = (0..peers).map(|_| VecPusher::default()).collect::<Vec<_>>();
+        let mut distributor = TableDistributor::<CapacityContainerBuilder<Vec<u64>>, _, _>::new(table, |x: &u64| *x / 10, peers);
+        distributor.partition(&mut keys.clone(), &0, &mut targets);
+        distributor.flush(&0, &mut targets);
+        targets.iter().map(|target| target.0.borrow().iter().flatten().flat_map(|(_, data)| data.clone()).collect()).collect()
+    }
+
+    #[test]
+    fn tables_route_by_key() {
+        let keys = (0..60).step_by(5).collect::<Vec<u64>>();
+        // Hashed tables route as an exchange by the same function does.
+        assert_eq!(routed(RoutingTable::hashed(|key| *key), 3, keys.clone()), vec![vec![0, 5, 30, 35], vec![10, 15, 40, 45], vec![20, 25, 50, 55]]);
+        // Keys equal to a splitter start its range.
+        assert_eq!(routed(RoutingTable::ranges(vec![1, 4]), 3, keys.clone()), vec![vec![0, 5], vec![10, 15, 20, 25, 30, 35], vec![40, 45, 50, 55]]);
+        // Keys without an explicit assignment fall back to the other table.
+        let explicit = RoutingTable::explicit([(0, 2), (5, 1)].into_iter().collect(), RoutingTable::new(|_, _| 0));
+        assert_eq!(routed(explicit, 3, keys), vec![vec![10, 15, 20, 25, 30, 35, 40, 45], vec![50, 55], vec![0, 5]]);
+    }
+
+    #[test]
+    #[should_panic(expected = "routing table assigned a key to worker 3 out of range for 3 peers")]
+    fn tables_reject_workers_out_of_range() {
+        routed(RoutingTable::ranges(vec![1, 2, 3]), 3, vec![35]);
+    }
+
+    #[test]
+    fn pre_routed_containers_are_sent_whole() {
+        let targets = (0..3).map(|_| VecPusher::default()).collect::<Vec<_>>();
+        let mut exchange = Exchange::new(targets.clone(), PreRoutedDistributor::new(|container: &Vec<u64>| container[0]));
+
+        // Containers of one target's records each, and an empty container that is not routed.
+        for container in [vec![4, 7], vec![2], vec![], vec![1, 10, 13]] {
+            exchange.push(&mut Some(Message::new(0, container, 0, 0)));
+        }
+        exchange.done();
+
+        let received = targets.iter().map(|target| target.0.borrow().iter().flatten().map(|(_time, data)| data.clone()).collect::<Vec<_>>()).collect::<Vec<_>>();
+        assert_eq!(received, vec![vec![], vec![vec![4, 7], vec![1, 10, 13]], vec![vec![2]]]);
+    }
+}
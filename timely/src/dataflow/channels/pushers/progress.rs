@@ -25,6 +25,15 @@ impl<T: Timestamp, P> Progress<T, P> {
         debug_assert!(self.valid(capability), "Attempted to open output session with invalid capability");
         if !container.is_empty() { Message::push_at(container, capability.time().clone(), &mut self.pushee); }
     }
+    /// Ships a message without records using a provided capability.
+    ///
+    /// Unlike `give`, which does not ship empty containers, this schedules the recipients of the
+    /// output without conveying records or otherwise affecting progress. Exchanges do not forward
+    /// messages without records, and so only recipients connected by a pipeline are scheduled.
+    #[inline] pub fn give_empty<C: Container, CT: CapabilityTrait<T>>(&mut self, capability: &CT) where P: Push<Message<T, C>> {
+        debug_assert!(self.valid(capability), "Attempted to open output session with invalid capability");
+        Message::push_at(&mut C::default(), capability.time().clone(), &mut self.pushee);
+    }
     /// Activates a `Progress` into a `ProgressSession` which will flush when dropped.
     pub fn activate<'a, C>(&'a mut self) -> ProgressSession<'a, T, C, P> where P: Push<Message<T, C>> {
         ProgressSession {
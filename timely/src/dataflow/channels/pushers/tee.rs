@@ -12,6 +12,14 @@ use crate::{Container, Data};
 type PushList<T, C> = Rc<RefCell<Vec<Box<dyn Push<Message<T, C>>>>>>;
 
 /// Wraps a shared list of `Box<Push>` to forward pushes to. Owned by `Stream`.
+///
+/// Each message is cloned for all but the last recipient. Streams of reference-counted
+/// containers, for example those produced by `SharedStream::shared`, clone cheaply.
+///
+/// Clones are made with `Clone::clone_from`, into a buffer that holds whatever the previous
+/// recipient left behind. The method defaults to `Clone::clone`, and containers that can copy
+/// themselves in bulk, or reuse the allocation of the buffer, should override it to make fanning
+/// out to several recipients cheaper.
 pub struct Tee<T, C> {
     buffer: C,
     shared: PushList<T, C>,
@@ -112,3 +120,62 @@ impl<T, C> Debug for TeeHelper<T, C> {
         debug.finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use crate::communication::Push;
+    use crate::container::Accountable;
+    use crate::dataflow::channels::Message;
+    use super::Tee;
+
+    /// A container that counts the clones made with `clone_from`, and with `clone`.
+    #[derive(Default)]
+    struct Counted {
+        data: Vec<u64>,
+        counts: Rc<(Cell<usize>, Cell<usize>)>,
+    }
+
+    impl Clone for Counted {
+        fn clone(&self) -> Self {
+            self.counts.1.set(self.counts.1.get() + 1);
+            Counted { data: self.data.clone(), counts: Rc::clone(&self.counts) }
+        }
+        fn clone_from(&mut self, source: &Self) {
+            source.counts.0.set(source.counts.0.get() + 1);
+            self.data.clone_from(&source.data);
+            self.counts = Rc::clone(&source.counts);
+        }
+    }
+
+    impl Accountable for Counted {
+        fn record_count(&self) -> i64 { self.data.len() as i64 }
+    }
+
+    /// A recipient that counts the records it receives.
+    struct Receiver(Rc<Cell<usize>>);
+
+    impl Push<Message<u64, Counted>> for Receiver {
+        fn push(&mut self, message: &mut Option<Message<u64, Counted>>) {
+            if let Some(message) = message {
+                self.0.set(self.0.get() + message.data.data.len());
+            }
+        }
+    }
+
+    #[test]
+    fn fan_out_clones_with_clone_from() {
+        let (mut tee, helper) = Tee::<u64, Counted>::new();
+        let received = Rc::new(Cell::new(0));
+        for _ in 0..3 {
+            helper.add_pusher(Receiver(Rc::clone(&received)));
+        }
+        let counts = Rc::new((Cell::new(0), Cell::new(0)));
+        let data = Counted { data: vec![1, 2, 3], counts: Rc::clone(&counts) };
+        tee.push(&mut Some(Message::from_parts(0, data)));
+        assert_eq!(received.get(), 9);
+        assert_eq!((counts.0.get(), counts.1.get()), (2, 0));
+    }
+}
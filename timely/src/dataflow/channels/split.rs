@@ -0,0 +1,159 @@
+//! A container builder that bounds the serialized size of the containers it produces.
+
+use std::collections::VecDeque;
+
+use serde::Serialize;
+use crate::container::{CapacityContainerBuilder, ContainerBuilder, LengthPreservingContainerBuilder, PushInto};
+
+/// A container builder for `Vec<T>` whose containers serialize to at most `MAX_BYTES` bytes.
+///
+/// The builder accumulates records like [`CapacityContainerBuilder`], but before a container
+/// is handed out from [`ContainerBuilder::extract`] or [`ContainerBuilder::finish`] its serialized
+/// size is measured, and containers above `MAX_BYTES` are split into several smaller containers.
+/// The size is measured as the payload `ContainerBytes` would write for the whole container,
+/// including its length prefix and padding, but excluding the message header.
+///
+/// Splitting preserves the order of records and the total record count. A single record whose
+/// own serialization exceeds `MAX_BYTES` cannot be split, and is passed through in a container
+/// by itself; the network layer may still refuse to send such a container.
+#[derive(Debug)]
+pub struct SplittingContainerBuilder<T, const MAX_BYTES: usize = { 1 << 20 }> {
+    /// Builder accumulating the unsplit containers.
+    inner: CapacityContainerBuilder<Vec<T>>,
+    /// Split containers awaiting extraction.
+    pending: VecDeque<Vec<T>>,
+    /// The most recently extracted split container.
+    current: Option<Vec<T>>,
+}
+
+impl<T, const MAX_BYTES: usize> Default for SplittingContainerBuilder<T, MAX_BYTES> {
+    fn default() -> Self {
+        Self {
+            inner: Default::default(),
+            pending: Default::default(),
+            current: None,
+        }
+    }
+}
+
+impl<T: Serialize, const MAX_BYTES: usize> SplittingContainerBuilder<T, MAX_BYTES> {
+    /// The number of bytes needed to serialize a list of records of total size `records`.
+    #[inline]
+    fn framed(records: usize) -> usize {
+        // `bincode` prefixes the records with a `u64` length, and we round up to `u64` alignment.
+        (8 + records + 7) & !7
+    }
+
+    /// Ensures `container` fits, moving its contents into `pending` in pieces if it does not.
+    ///
+    /// Returns `true` if the container was split.
+    fn split(container: &mut Vec<T>, pending: &mut VecDeque<Vec<T>>) -> bool {
+        let sizes = container
+            .iter()
+            .map(|record| ::bincode::serialized_size(record).expect("bincode::serialized_size() failed") as usize)
+            .collect::<Vec<_>>();
+        if Self::framed(sizes.iter().sum()) <= MAX_BYTES {
+            return false;
+        }
+        let mut piece = Vec::new();
+        let mut piece_bytes = 0;
+        for (record, size) in container.drain(..).zip(sizes) {
+            if !piece.is_empty() && Self::framed(piece_bytes + size) > MAX_BYTES {
+                pending.push_back(std::mem::take(&mut piece));
+                piece_bytes = 0;
+            }
+            piece.push(record);
+            piece_bytes += size;
+        }
+        if !piece.is_empty() {
+            pending.push_back(piece);
+        }
+        true
+    }
+}
+
+impl<T, D, const MAX_BYTES: usize> PushInto<D> for SplittingContainerBuilder<T, MAX_BYTES>
+where
+    CapacityContainerBuilder<Vec<T>>: PushInto<D>,
+{
+    #[inline]
+    fn push_into(&mut self, item: D) {
+        self.inner.push_into(item);
+    }
+}
+
+impl<T: Serialize, const MAX_BYTES: usize> ContainerBuilder for SplittingContainerBuilder<T, MAX_BYTES> {
+    type Container = Vec<T>;
+
+    #[inline]
+    fn extract(&mut self) -> Option<&mut Vec<T>> {
+        if self.pending.is_empty() {
+            let container = self.inner.extract()?;
+            if !Self::split(container, &mut self.pending) {
+                return Some(container);
+            }
+        }
+        self.current = self.pending.pop_front();
+        self.current.as_mut()
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Option<&mut Vec<T>> {
+        if self.pending.is_empty() {
+            let container = self.inner.finish()?;
+            if !Self::split(container, &mut self.pending) {
+                return Some(container);
+            }
+        }
+        self.current = self.pending.pop_front();
+        self.current.as_mut()
+    }
+
+    #[inline]
+    fn relax(&mut self) {
+        self.current = None;
+        self.inner.relax();
+    }
+}
+
+impl<T: Serialize, const MAX_BYTES: usize> LengthPreservingContainerBuilder for SplittingContainerBuilder<T, MAX_BYTES> { }
+
+#[cfg(test)]
+mod tests {
+    use crate::container::{ContainerBuilder, PushInto};
+    use super::SplittingContainerBuilder;
+
+    fn drain<const MAX_BYTES: usize>(builder: &mut SplittingContainerBuilder<String, MAX_BYTES>) -> Vec<Vec<String>> {
+        let mut result = Vec::new();
+        while let Some(container) = builder.finish() {
+            result.push(std::mem::take(container));
+        }
+        result
+    }
+
+    #[test]
+    fn split_preserves_order_and_count() {
+        let mut builder = SplittingContainerBuilder::<String, 64>::default();
+        for i in 0..100 {
+            builder.push_into(format!("{:04}", i));
+        }
+        let containers = drain(&mut builder);
+        assert!(containers.len() > 1);
+        for container in containers.iter() {
+            let size = ::bincode::serialized_size(container).unwrap() as usize;
+            assert!(((size + 7) & !7) <= 64);
+        }
+        let flat = containers.into_iter().flatten().collect::<Vec<_>>();
+        assert_eq!(flat, (0..100).map(|i| format!("{:04}", i)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn oversized_record_passes_through() {
+        let mut builder = SplittingContainerBuilder::<String, 64>::default();
+        builder.push_into("a".to_string());
+        builder.push_into("x".repeat(1000));
+        builder.push_into("b".to_string());
+        let containers = drain(&mut builder);
+        assert_eq!(containers, vec![vec!["a".to_string()], vec!["x".repeat(1000)], vec!["b".to_string()]]);
+    }
+}
@@ -0,0 +1,166 @@
+//! Running sums per key, maintained across times.
+use std::hash::Hash;
+use std::ops::AddAssign;
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::operators::map::Map;
+use crate::dataflow::operators::sketch::hash_of;
+use super::StateMachine;
+
+/// Provides the `cumsum_by` method.
+pub trait CumSum<S: Scope, D: Data> {
+    /// Maintains a running sum of `value_fn` for each key of `key_fn`, across times.
+    ///
+    /// For each record the operator produces its key and the key's updated total, the sum of the
+    /// values of the key's records so far, starting from `V::default()`. Records are exchanged by
+    /// key and applied in time order as by [`StateMachine::state_machine`], though in no particular
+    /// order within a time: the total of a key once all records of a time are applied is
+    /// determined, but the totals produced along the way depend on the order in which the records
+    /// arrive.
+    ///
+    /// Each key's total is retained indefinitely, so memory grows with the number of distinct
+    /// keys. Use [`CumSum::cumsum_by_with_eviction`] to release the totals of keys no longer of interest.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect};
+    /// use timely::dataflow::operators::aggregation::CumSum;
+    ///
+    /// timely::example(|scope| {
+    ///     vec![(0u64, 3), (0, 4), (1, 5)]
+    ///         .to_stream(scope)
+    ///         .cumsum_by(|(account, _)| *account, |(_, amount)| *amount)
+    ///         .inspect(|(account, total)| println!("{}: {}", account, total));
+    /// });
+    /// ```
+    fn cumsum_by<K, V, KF, VF>(&self, key_fn: KF, value_fn: VF) -> Stream<S, (K, V)>
+    where
+        S::Timestamp: Hash+Eq,
+        K: ExchangeData+Hash+Eq,
+        V: ExchangeData+Default+AddAssign,
+        KF: Fn(&D)->K+'static,
+        VF: Fn(&D)->V+'static;
+
+    /// Maintains running sums as [`CumSum::cumsum_by`] does, releasing those for which `evict` returns true.
+    ///
+    /// Once a key's total is updated and produced, `evict` is called with the key and the total,
+    /// and if it returns true the total is released. A later record of the key starts anew, from
+    /// `V::default()`.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect};
+    /// use timely::dataflow::operators::aggregation::CumSum;
+    ///
+    /// timely::example(|scope| {
+    ///     // Releases the balance of an account once it is settled.
+    ///     vec![(7u64, 10i64), (7, -10)]
+    ///         .to_stream(scope)
+    ///         .cumsum_by_with_eviction(|(account, _)| *account, |(_, amount)| *amount, |_account, balance| *balance == 0)
+    ///         .inspect(|(account, balance)| println!("{}: {}", account, balance));
+    /// });
+    /// ```
+    fn cumsum_by_with_eviction<K, V, KF, VF, E>(&self, key_fn: KF, value_fn: VF, evict: E) -> Stream<S, (K, V)>
+    where
+        S::Timestamp: Hash+Eq,
+        K: ExchangeData+Hash+Eq,
+        V: ExchangeData+Default+AddAssign,
+        KF: Fn(&D)->K+'static,
+        VF: Fn(&D)->V+'static,
+        E: Fn(&K, &V)->bool+'static;
+}
+
+impl<S: Scope, D: Data> CumSum<S, D> for Stream<S, D> {
+    fn cumsum_by<K, V, KF, VF>(&self, key_fn: KF, value_fn: VF) -> Stream<S, (K, V)>
+    where
+        S::Timestamp: Hash+Eq,
+        K: ExchangeData+Hash+Eq,
+        V: ExchangeData+Default+AddAssign,
+        KF: Fn(&D)->K+'static,
+        VF: Fn(&D)->V+'static,
+    {
+        self.cumsum_by_with_eviction(key_fn, value_fn, |_, _| false)
+    }
+
+    fn cumsum_by_with_eviction<K, V, KF, VF, E>(&self, key_fn: KF, value_fn: VF, evict: E) -> Stream<S, (K, V)>
+    where
+        S::Timestamp: Hash+Eq,
+        K: ExchangeData+Hash+Eq,
+        V: ExchangeData+Default+AddAssign,
+        KF: Fn(&D)->K+'static,
+        VF: Fn(&D)->V+'static,
+        E: Fn(&K, &V)->bool+'static,
+    {
+        self.map(move |datum| (key_fn(&datum), value_fn(&datum)))
+            .state_machine(
+                move |key, value, total: &mut V| {
+                    *total += value;
+                    (evict(key, total), Some((key.clone(), total.clone())))
+                },
+                |key| hash_of(key),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::Config;
+    use crate::dataflow::operators::{Capture, Input, Probe};
+    use crate::dataflow::operators::capture::Extract;
+    use super::CumSum;
+
+    #[test]
+    fn totals_accumulate_across_times_per_key() {
+        let (send, recv) = std::sync::mpsc::channel();
+        let send = Arc::new(Mutex::new(send));
+        crate::execute(Config::process(2), move |worker| {
+            let send = send.lock().unwrap().clone();
+            let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<(char, u64)>();
+                let totals = stream.cumsum_by(|(key, _)| *key, |(_, value)| *value);
+                totals.capture_into(send);
+                (input, totals.probe())
+            });
+            // Each time holds one record of each key, from the first worker.
+            for round in 0..3u64 {
+                if worker.index() == 0 {
+                    input.send(('a', round + 1));
+                    input.send(('b', 10 * (round + 1)));
+                }
+                input.advance_to(round + 1);
+                worker.step_while(|| probe.less_than(input.time()));
+            }
+        }).unwrap();
+
+        assert_eq!(recv.extract(), vec![
+            (0, vec![('a', 1), ('b', 10)]),
+            (1, vec![('a', 3), ('b', 30)]),
+            (2, vec![('a', 6), ('b', 60)]),
+        ]);
+    }
+
+    #[test]
+    fn evicted_totals_start_anew() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<(u64, i64)>();
+                let balances = stream.cumsum_by_with_eviction(|(key, _)| *key, |(_, value)| *value, |_key, balance| *balance >= 5);
+                (input, balances.probe(), balances.capture())
+            });
+            // The total of 5 is released, and the next record starts from zero.
+            for (round, amount) in [5, -5, 2].into_iter().enumerate() {
+                input.send((7, amount));
+                input.advance_to(round as u64 + 1);
+                worker.step_while(|| probe.less_than(input.time()));
+            }
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        assert_eq!(captured.extract(), vec![(0, vec![(7, 5)]), (1, vec![(7, -5)]), (2, vec![(7, -3)])]);
+    }
+}
@@ -0,0 +1,153 @@
+//! Exponential moving averages per key, maintained across times.
+use std::hash::Hash;
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::operators::map::Map;
+use crate::dataflow::operators::sketch::hash_of;
+use super::StateMachine;
+
+/// Provides the `ema` method.
+pub trait Ema<S: Scope, D: Data> {
+    /// Maintains an exponential moving average of `value_fn` for each key of `key_fn`, across times.
+    ///
+    /// For each record the operator produces its key and the key's updated average, which is
+    /// `alpha * value + (1 - alpha) * previous`, or the value itself for the first record of a key.
+    /// Larger values of `alpha` weigh recent records more heavily. Records are exchanged by key and
+    /// applied in time order as by [`StateMachine::state_machine`], though in no particular order
+    /// within a time.
+    ///
+    /// Each key's average is retained indefinitely, so memory grows with the number of distinct
+    /// keys. Use [`Ema::ema_with_eviction`] to release the averages of keys no longer of interest.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `alpha` is not in `(0, 1]`.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect};
+    /// use timely::dataflow::operators::aggregation::Ema;
+    ///
+    /// timely::example(|scope| {
+    ///     vec![("cpu", 10.0), ("cpu", 20.0)]
+    ///         .into_iter()
+    ///         .map(|(name, load)| (name.to_string(), load))
+    ///         .to_stream(scope)
+    ///         .ema(|(name, _)| name.clone(), |(_, load)| *load, 0.5)
+    ///         .inspect(|(name, average)| println!("{}: {}", name, average));
+    /// });
+    /// ```
+    fn ema<K, KF, VF>(&self, key_fn: KF, value_fn: VF, alpha: f64) -> Stream<S, (K, f64)>
+    where
+        S::Timestamp: Hash+Eq,
+        K: ExchangeData+Hash+Eq,
+        KF: Fn(&D)->K+'static,
+        VF: Fn(&D)->f64+'static;
+
+    /// Maintains exponential moving averages as [`Ema::ema`] does, releasing those for which `evict` returns true.
+    ///
+    /// Once a key's average is updated and produced, `evict` is called with the key and the average,
+    /// and if it returns true the average is released. A later record of the key starts anew, from
+    /// its value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `alpha` is not in `(0, 1]`.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect};
+    /// use timely::dataflow::operators::aggregation::Ema;
+    ///
+    /// timely::example(|scope| {
+    ///     // Releases the average of a key once it decays to near zero.
+    ///     vec![(1u64, 4.0), (1, 0.0), (1, 0.0)]
+    ///         .to_stream(scope)
+    ///         .ema_with_eviction(|(key, _)| *key, |(_, value)| *value, 0.9, |_key, average| *average < 0.1)
+    ///         .inspect(|(key, average)| println!("{}: {}", key, average));
+    /// });
+    /// ```
+    fn ema_with_eviction<K, KF, VF, E>(&self, key_fn: KF, value_fn: VF, alpha: f64, evict: E) -> Stream<S, (K, f64)>
+    where
+        S::Timestamp: Hash+Eq,
+        K: ExchangeData+Hash+Eq,
+        KF: Fn(&D)->K+'static,
+        VF: Fn(&D)->f64+'static,
+        E: Fn(&K, &f64)->bool+'static;
+}
+
+impl<S: Scope, D: Data> Ema<S, D> for Stream<S, D> {
+    fn ema<K, KF, VF>(&self, key_fn: KF, value_fn: VF, alpha: f64) -> Stream<S, (K, f64)>
+    where
+        S::Timestamp: Hash+Eq,
+        K: ExchangeData+Hash+Eq,
+        KF: Fn(&D)->K+'static,
+        VF: Fn(&D)->f64+'static,
+    {
+        self.ema_with_eviction(key_fn, value_fn, alpha, |_, _| false)
+    }
+
+    fn ema_with_eviction<K, KF, VF, E>(&self, key_fn: KF, value_fn: VF, alpha: f64, evict: E) -> Stream<S, (K, f64)>
+    where
+        S::Timestamp: Hash+Eq,
+        K: ExchangeData+Hash+Eq,
+        KF: Fn(&D)->K+'static,
+        VF: Fn(&D)->f64+'static,
+        E: Fn(&K, &f64)->bool+'static,
+    {
+        assert!(alpha > 0.0 && alpha <= 1.0, "Ema: alpha must be in (0, 1], got {}", alpha);
+        self.map(move |datum| (key_fn(&datum), value_fn(&datum)))
+            .state_machine(
+                move |key, value, average: &mut Option<f64>| {
+                    let updated = average.map_or(value, |previous| alpha * value + (1.0 - alpha) * previous);
+                    *average = Some(updated);
+                    (evict(key, &updated), Some((key.clone(), updated)))
+                },
+                |key| hash_of(key),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use crate::dataflow::operators::{Input, Inspect, Probe};
+    use super::Ema;
+
+    #[test]
+    fn averages_carry_across_times_until_evicted() {
+        crate::execute_directly(|worker| {
+            let averages = Rc::new(RefCell::new(Vec::new()));
+            let averages_inner = Rc::clone(&averages);
+            let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<(u64, f64)>();
+                let probe = stream
+                    .ema_with_eviction(|(key, _)| *key, |(_, value)| *value, 0.5, |key, _| *key == 2)
+                    .inspect(move |x| averages_inner.borrow_mut().push(*x))
+                    .probe();
+                (input, probe)
+            });
+            for (round, value) in [8.0, 4.0, 0.0].into_iter().enumerate() {
+                input.send((1, value));
+                input.send((2, value));
+                input.advance_to(round as u64 + 1);
+                worker.step_while(|| probe.less_than(input.time()));
+            }
+            let mut averages = averages.borrow().clone();
+            averages.sort_by(|x, y| x.partial_cmp(y).unwrap());
+            assert_eq!(averages, vec![(1, 3.0), (1, 6.0), (1, 8.0), (2, 0.0), (2, 4.0), (2, 8.0)]);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "alpha must be in (0, 1]")]
+    fn rejects_alpha_out_of_range() {
+        crate::example(|scope| {
+            use crate::dataflow::operators::ToStream;
+            vec![(0u64, 1.0)].to_stream(scope).ema(|(key, _)| *key, |(_, value)| *value, 0.0);
+        });
+    }
+}
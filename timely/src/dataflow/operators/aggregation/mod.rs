@@ -3,16 +3,26 @@
 //! Two traits, `Aggregate` and `StateMachine`, which support the accumulation of streamed information.
 //!
 //! `Aggregate` accumulates records within times, and releases the accumulations once the time is complete.
+//! `GroupBySpilling` does the same, but writes accumulations to disk once they exceed a memory budget.
 //!
 //! `StateMachine` responds to a sequence of keyed events, maintaining and updating a state for each key.
 //! The user logic may produce output records for each transition, and optionally de-register the state to
-//! clean up when appropriate.
+//! clean up when appropriate. `Ema` uses it to maintain an exponential moving average for each key,
+//! `CumSum` a running sum, and `RollingAggregate` an aggregate of each key's most recent records.
 //!
 //! The two methods are often combined, using first `Aggregate` to reduce the volume of information, and then
 //! `StateMachine` to track an accumulation across timestamps.
 
 pub use self::aggregate::Aggregate;
 pub use self::state_machine::StateMachine;
+pub use self::spilling::GroupBySpilling;
+pub use self::ema::Ema;
+pub use self::cumsum::CumSum;
+pub use self::rolling::RollingAggregate;
 
 pub mod state_machine;
 pub mod aggregate;
+pub mod spilling;
+pub mod ema;
+pub mod cumsum;
+pub mod rolling;
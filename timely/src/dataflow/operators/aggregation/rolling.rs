@@ -0,0 +1,150 @@
+//! Aggregates of the most recent records per key, maintained across times.
+use std::collections::VecDeque;
+use std::hash::Hash;
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::operators::map::Map;
+use crate::dataflow::operators::sketch::hash_of;
+use super::StateMachine;
+
+/// Provides the `rolling_aggregate` method.
+pub trait RollingAggregate<S: Scope, D: ExchangeData> {
+    /// Maintains an aggregate of the last `n` records of each key of `key_fn`, across times.
+    ///
+    /// Each key has an accumulator, which starts as `init()`, and a window of its last `n` records.
+    /// A record is added to the accumulator with `add` and joins the window, and once the window holds
+    /// more than `n` records the oldest of them leaves it and is removed from the accumulator with
+    /// `remove`, which must undo `add`. For each record the operator produces its key and the key's
+    /// updated accumulator. The aggregate is maintained incrementally, with one `add` and at most one
+    /// `remove` per record, rather than recomputed from the window.
+    ///
+    /// Records are exchanged by key and applied in time order as by [`StateMachine::state_machine`],
+    /// though in no particular order within a time: the window of a key once all records of a time
+    /// are applied is determined if the key has at most one record per time, but which records of a
+    /// time leave the window first otherwise depends on the order in which they arrive.
+    ///
+    /// Each key retains up to `n` records and its accumulator indefinitely, so memory grows with `n`
+    /// and the number of distinct keys.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect};
+    /// use timely::dataflow::operators::aggregation::RollingAggregate;
+    ///
+    /// timely::example(|scope| {
+    ///     // The sum of the last two readings of each sensor.
+    ///     vec![(0u64, 3i64), (0, 4), (0, 5)]
+    ///         .to_stream(scope)
+    ///         .rolling_aggregate(|(sensor, _)| *sensor, 2, || 0, |sum, (_, x)| *sum += x, |sum, (_, x)| *sum -= x)
+    ///         .inspect(|(sensor, sum)| println!("{}: {}", sensor, sum));
+    /// });
+    /// ```
+    fn rolling_aggregate<K, A, KF, I, AF, RF>(&self, key_fn: KF, n: usize, init: I, add: AF, remove: RF) -> Stream<S, (K, A)>
+    where
+        S::Timestamp: Hash+Eq,
+        K: ExchangeData+Hash+Eq,
+        A: Data,
+        KF: Fn(&D)->K+'static,
+        I: Fn()->A+'static,
+        AF: Fn(&mut A, &D)+'static,
+        RF: Fn(&mut A, &D)+'static;
+}
+
+impl<S: Scope, D: ExchangeData> RollingAggregate<S, D> for Stream<S, D> {
+    fn rolling_aggregate<K, A, KF, I, AF, RF>(&self, key_fn: KF, n: usize, init: I, add: AF, remove: RF) -> Stream<S, (K, A)>
+    where
+        S::Timestamp: Hash+Eq,
+        K: ExchangeData+Hash+Eq,
+        A: Data,
+        KF: Fn(&D)->K+'static,
+        I: Fn()->A+'static,
+        AF: Fn(&mut A, &D)+'static,
+        RF: Fn(&mut A, &D)+'static,
+    {
+        assert!(n > 0, "RollingAggregate: n must be positive");
+        self.map(move |datum| (key_fn(&datum), datum))
+            .state_machine(
+                move |key, datum, window: &mut Window<D, A>| {
+                    let accumulator = window.accumulator.get_or_insert_with(&init);
+                    add(accumulator, &datum);
+                    window.records.push_back(datum);
+                    if window.records.len() > n {
+                        let oldest = window.records.pop_front().expect("non-empty window");
+                        remove(accumulator, &oldest);
+                    }
+                    (false, Some((key.clone(), accumulator.clone())))
+                },
+                |key| hash_of(key),
+            )
+    }
+}
+
+/// The last records of a key, and their accumulator.
+struct Window<D, A> {
+    /// The records of the window, oldest first.
+    records: VecDeque<D>,
+    /// The accumulator of the records, absent until the key's first record.
+    accumulator: Option<A>,
+}
+
+impl<D, A> Default for Window<D, A> {
+    fn default() -> Self {
+        Window { records: VecDeque::new(), accumulator: None }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::dataflow::operators::{Capture, Input, Probe, ToStream};
+    use crate::dataflow::operators::capture::Extract;
+    use super::RollingAggregate;
+
+    #[test]
+    fn aggregates_match_a_naive_recompute() {
+        // Records of three keys, one per time, with values from a simple generator.
+        let records = (0..300u64).map(|i| (i % 3, (i * 7919) % 101)).collect::<Vec<_>>();
+        let n = 10;
+        let records_inner = records.clone();
+
+        let captured = crate::execute_directly(move |worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<(u64, u64)>();
+                let sums = stream.rolling_aggregate(|(key, _)| *key, n, || (0u64, 0usize), |(sum, count), (_, x)| { *sum += x; *count += 1; }, |(sum, count), (_, x)| { *sum -= x; *count -= 1; });
+                (input, sums.probe(), sums.capture())
+            });
+            for (time, record) in records_inner.into_iter().enumerate() {
+                input.send(record);
+                input.advance_to(time as u64 + 1);
+                worker.step_while(|| probe.less_than(input.time()));
+            }
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        // The sum and count of the last `n` values of the key, recomputed at each record.
+        let mut seen = HashMap::<u64, Vec<u64>>::new();
+        let expected = records.iter().enumerate().map(|(time, (key, value))| {
+            let values = seen.entry(*key).or_default();
+            values.push(*value);
+            let window = &values[values.len().saturating_sub(n)..];
+            (time as u64, vec![(*key, (window.iter().sum::<u64>(), window.len()))])
+        }).collect::<Vec<_>>();
+        assert_eq!(captured.extract(), expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "n must be positive")]
+    fn empty_windows_panic() {
+        crate::example(|scope| {
+            (0..10u64).to_stream(scope).rolling_aggregate(|x| *x, 0, || 0, |sum, x| *sum += x, |sum, x| *sum -= x);
+        });
+    }
+}
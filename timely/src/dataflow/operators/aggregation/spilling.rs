@@ -0,0 +1,249 @@
+//! Intra-timestamp aggregation whose state may spill to disk
+use std::collections::HashMap;
+use std::fs::File;
+use std::hash::Hash;
+use std::io::{BufReader, BufWriter, ErrorKind, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::sketch::hash_of;
+use crate::dataflow::channels::pact::Exchange;
+
+/// The number of partitions into which the aggregates of each time are divided.
+const PARTITIONS: usize = 16;
+
+/// Distinguishes the spill files created by a process.
+static SPILL_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Generic intra-timestamp aggregation with bounded memory
+///
+/// Extension method supporting aggregation of keyed data within timestamp, like `Aggregate`,
+/// but moving aggregates to temporary files once they exceed a memory budget.
+pub trait GroupBySpilling<S: Scope, K: ExchangeData+Hash, V: ExchangeData> {
+    /// Aggregates data of the form `(key, val)`, using user-supplied logic, and spills
+    /// aggregates to disk once they occupy more than about `max_bytes` bytes.
+    ///
+    /// The arguments `fold`, `emit`, and `hash` are as for `Aggregate::aggregate`. The aggregates
+    /// of each time are divided into partitions by key. When the aggregates held in memory by an
+    /// operator exceed `max_bytes`, the largest partition is written to a temporary file, and later
+    /// values for keys in that partition are appended to the file rather than folded in memory. Once
+    /// a time is complete, each spilled partition is read back, its remaining values are folded into
+    /// its aggregates in the order in which they arrived, and the results are emitted. The output is
+    /// therefore identical to that of `aggregate`, though its order may differ.
+    ///
+    /// The memory used by aggregates is estimated as the size of `K` and `D` for each key, and
+    /// does not include memory that keys or aggregates own on the heap. Temporary files are created
+    /// in [`std::env::temp_dir`], and removed once read or when the operator is dropped.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Map, Inspect};
+    /// use timely::dataflow::operators::aggregation::GroupBySpilling;
+    ///
+    /// timely::example(|scope| {
+    ///
+    ///     (0..1000).to_stream(scope)
+    ///         .map(|x| (x % 100, x))
+    ///         .group_by_spilling(
+    ///             |_key, val, agg| { *agg += val; },
+    ///             |key, agg: i32| (key, agg),
+    ///             |key| *key as u64,
+    ///             1 << 10,
+    ///         )
+    ///         .inspect(|(key, sum)| assert_eq!(*sum, 10 * key + 4500));
+    /// });
+    /// ```
+    fn group_by_spilling<R: Data, D: ExchangeData+Default, F: Fn(&K, V, &mut D)+'static, E: Fn(K, D)->R+'static, H: Fn(&K)->u64+'static>(
+        &self,
+        fold: F,
+        emit: E,
+        hash: H,
+        max_bytes: usize) -> Stream<S, R> where S::Timestamp: Eq;
+}
+
+impl<S: Scope<Timestamp: ::std::hash::Hash>, K: ExchangeData+Hash+Eq, V: ExchangeData> GroupBySpilling<S, K, V> for Stream<S, (K, V)> {
+
+    fn group_by_spilling<R: Data, D: ExchangeData+Default, F: Fn(&K, V, &mut D)+'static, E: Fn(K, D)->R+'static, H: Fn(&K)->u64+'static>(
+        &self,
+        fold: F,
+        emit: E,
+        hash: H,
+        max_bytes: usize) -> Stream<S, R> where S::Timestamp: Eq {
+
+        let entry_bytes = std::mem::size_of::<(K, D)>().max(1);
+        let max_entries = max_bytes / entry_bytes;
+
+        let mut aggregates = HashMap::<S::Timestamp, Vec<Partition<K, V, D>>>::new();
+        let mut in_memory = 0;
+        self.unary_notify(Exchange::new(move |(k, _)| hash(k)), "GroupBySpilling", vec![], move |input, output, notificator| {
+
+            // read each input, fold into in-memory aggregates or append to spilled partitions
+            input.for_each_time(|time, data| {
+                let agg_time = aggregates
+                    .entry(time.time().clone())
+                    .or_insert_with(|| (0 .. PARTITIONS).map(|_| Partition::Memory(HashMap::new())).collect());
+                for (key, val) in data.flat_map(|d| d.drain(..)) {
+                    match &mut agg_time[hash_of(&key) as usize % PARTITIONS] {
+                        Partition::Memory(aggs) => {
+                            if !aggs.contains_key(&key) { in_memory += 1; }
+                            let agg = aggs.entry(key.clone()).or_insert_with(Default::default);
+                            fold(&key, val, agg);
+                        },
+                        Partition::Spilled(file) => file.append(&Spilled::Value(key, val)),
+                    }
+                }
+                notificator.notify_at(time.retain());
+
+                // spill the largest partitions until within budget
+                while in_memory > max_entries {
+                    let largest = aggregates
+                        .values_mut()
+                        .flat_map(|partitions| partitions.iter_mut())
+                        .filter_map(|partition| match partition {
+                            Partition::Memory(aggs) if !aggs.is_empty() => Some((aggs.len(), partition)),
+                            _ => None,
+                        })
+                        .max_by_key(|(len, _)| *len);
+                    let Some((len, partition)) = largest else { break };
+                    let mut file = SpillFile::create();
+                    if let Partition::Memory(aggs) = std::mem::replace(partition, Partition::Memory(HashMap::new())) {
+                        for (key, agg) in aggs {
+                            file.append(&Spilled::<K, V, D>::State(key, agg));
+                        }
+                    }
+                    *partition = Partition::Spilled(file);
+                    in_memory -= len;
+                }
+            });
+
+            // pop completed aggregates, merge spilled partitions, send along whatever
+            notificator.for_each(|time,_,_| {
+                if let Some(partitions) = aggregates.remove(time.time()) {
+                    let mut session = output.session(&time);
+                    for partition in partitions {
+                        let aggs = match partition {
+                            Partition::Memory(aggs) => {
+                                in_memory -= aggs.len();
+                                aggs
+                            },
+                            Partition::Spilled(file) => {
+                                let mut aggs = HashMap::new();
+                                file.read(|record| match record {
+                                    Spilled::State(key, agg) => { aggs.insert(key, agg); },
+                                    Spilled::Value(key, val) => {
+                                        let agg = aggs.entry(key.clone()).or_insert_with(Default::default);
+                                        fold(&key, val, agg);
+                                    },
+                                });
+                                aggs
+                            },
+                        };
+                        for (key, agg) in aggs {
+                            session.give(emit(key, agg));
+                        }
+                    }
+                }
+            });
+        })
+
+    }
+}
+
+/// The aggregates of a partition of keys at one time.
+enum Partition<K, V, D> {
+    /// Aggregates held in memory.
+    Memory(HashMap<K, D>),
+    /// Aggregates and subsequent values written to a temporary file.
+    Spilled(SpillFile<K, V, D>),
+}
+
+/// A record of a spilled partition: either an aggregate at the time of spilling, or a later value.
+#[derive(Serialize, Deserialize)]
+enum Spilled<K, V, D> {
+    State(K, D),
+    Value(K, V),
+}
+
+/// A temporary file of spilled records, removed when dropped.
+struct SpillFile<K, V, D> {
+    path: PathBuf,
+    writer: Option<BufWriter<File>>,
+    phantom: std::marker::PhantomData<(K, V, D)>,
+}
+
+impl<K: ExchangeData, V: ExchangeData, D: ExchangeData> SpillFile<K, V, D> {
+    /// Creates a new temporary file, named after the process and a per-process sequence number.
+    fn create() -> Self {
+        let sequence = SPILL_FILES.fetch_add(1, Ordering::Relaxed);
+        let path = std::env::temp_dir().join(format!("timely-spill-{}-{}", std::process::id(), sequence));
+        let file = File::create(&path).unwrap_or_else(|e| panic!("failed to create spill file {}: {e}", path.display()));
+        Self { path, writer: Some(BufWriter::new(file)), phantom: std::marker::PhantomData }
+    }
+
+    /// Appends `record` to the file.
+    fn append(&mut self, record: &Spilled<K, V, D>) {
+        let writer = self.writer.as_mut().expect("spill file already read");
+        ::bincode::serialize_into(writer, record).expect("failed to write spill file");
+    }
+
+    /// Reads the records of the file in the order they were appended, and removes the file.
+    fn read(mut self, mut logic: impl FnMut(Spilled<K, V, D>)) {
+        let mut writer = self.writer.take().expect("spill file already read");
+        writer.flush().expect("failed to write spill file");
+        drop(writer);
+        let file = File::open(&self.path).unwrap_or_else(|e| panic!("failed to open spill file {}: {e}", self.path.display()));
+        let mut reader = BufReader::new(file);
+        loop {
+            match ::bincode::deserialize_from(&mut reader) {
+                Ok(record) => logic(record),
+                Err(error) => match *error {
+                    ::bincode::ErrorKind::Io(ref io) if io.kind() == ErrorKind::UnexpectedEof => break,
+                    _ => panic!("failed to read spill file {}: {error}", self.path.display()),
+                },
+            }
+        }
+    }
+}
+
+impl<K, V, D> Drop for SpillFile<K, V, D> {
+    fn drop(&mut self) {
+        drop(self.writer.take());
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dataflow::operators::{ToStream, Map, Capture};
+    use crate::dataflow::operators::capture::Extract;
+    use crate::dataflow::operators::aggregation::{Aggregate, GroupBySpilling};
+
+    #[test]
+    fn spilling_matches_in_memory() {
+        let (spilled, in_memory) = crate::example(|scope| {
+            let data = (0..10_000u64).to_stream(scope).map(|x| (x % 1_000, x));
+            let spilled = data.group_by_spilling(|_key, val, agg: &mut Vec<u64>| agg.push(val), |key, agg| (key, agg), |key| *key, 256).capture();
+            let in_memory = data.aggregate(|_key, val, agg: &mut Vec<u64>| agg.push(val), |key, agg| (key, agg), |key| *key).capture();
+            (spilled, in_memory)
+        });
+        let mut spilled = spilled.extract();
+        let mut in_memory = in_memory.extract();
+        for (_, data) in spilled.iter_mut().chain(in_memory.iter_mut()) {
+            data.sort();
+        }
+        assert_eq!(spilled, in_memory);
+
+        // All spill files of this process have been removed.
+        let prefix = format!("timely-spill-{}-", std::process::id());
+        let remaining = std::fs::read_dir(std::env::temp_dir())
+            .unwrap()
+            .filter(|entry| entry.as_ref().unwrap().file_name().to_string_lossy().starts_with(&prefix))
+            .count();
+        assert_eq!(remaining, 0);
+    }
+}
@@ -0,0 +1,141 @@
+//! Extension methods for `Stream` that attach worker-unique identifiers, or worker indexes, to records.
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// Extension trait for `Stream`.
+pub trait AssignIds<S: Scope, D: Data> {
+    /// Pairs each record with a `u64` identifier that is unique across all workers.
+    ///
+    /// Each worker numbers its records with a local counter, and interleaves its counter with
+    /// those of other workers: the `n`th record seen by worker `index` of `peers` receives the
+    /// identifier `n * peers + index`. No coordination between workers is required, and the
+    /// identifiers are deterministic given the order in which each worker receives its input.
+    ///
+    /// The identifiers are unique, but they are neither densely packed nor ordered across workers,
+    /// as workers that see fewer records leave gaps in the sequence.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a worker's identifier would not fit in a `u64`, once it has numbered more than
+    /// `u64::MAX / peers` records, rather than assigning an identifier twice.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, AssignIds, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     // A single worker numbers its records in the order it receives them.
+    ///     (0..10u64).to_stream(scope)
+    ///               .assign_ids()
+    ///               .inspect(|(id, x)| assert_eq!(id, x));
+    /// });
+    /// ```
+    fn assign_ids(&self) -> Stream<S, (u64, D)>;
+
+    /// Pairs each record with the index of the worker that holds it.
+    ///
+    /// The index is that of the worker running the operator, and so placed after an exchange it
+    /// reports where the exchange sent each record, which helps to check the data distribution.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, AssignIds, Exchange, Inspect};
+    ///
+    /// timely::execute(timely::Config::process(2), |worker| {
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         (0..10u64).to_stream(scope)
+    ///                   .exchange(|x| *x)
+    ///                   .with_worker_id()
+    ///                   .inspect(|(worker, x)| assert_eq!(*worker as u64, x % 2));
+    ///     });
+    /// }).unwrap();
+    /// ```
+    fn with_worker_id(&self) -> Stream<S, (usize, D)>;
+}
+
+impl<S: Scope, D: Data> AssignIds<S, D> for Stream<S, D> {
+    fn assign_ids(&self) -> Stream<S, (u64, D)> {
+        let index = self.scope().index() as u64;
+        let peers = self.scope().peers() as u64;
+        let mut counter = 0u64;
+        self.unary(Pipeline, "AssignIds", move |_,_| move |input, output| {
+            input.for_each(|time, data| {
+                let mut session = output.session(&time);
+                for datum in data.drain(..) {
+                    session.give((identifier(counter, peers, index), datum));
+                    counter += 1;
+                }
+            });
+        })
+    }
+
+    fn with_worker_id(&self) -> Stream<S, (usize, D)> {
+        let index = self.scope().index();
+        self.unary(Pipeline, "WithWorkerId", move |_,_| move |input, output| {
+            input.for_each(|time, data| {
+                output.session(&time).give_iterator(data.drain(..).map(|datum| (index, datum)));
+            });
+        })
+    }
+}
+
+/// The identifier of the record numbered `counter` by worker `index` of `peers`.
+fn identifier(counter: u64, peers: u64, index: u64) -> u64 {
+    counter.checked_mul(peers).and_then(|id| id.checked_add(index))
+        .unwrap_or_else(|| panic!("AssignIds: worker {} numbered more than u64::MAX / {} records", index, peers))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::Config;
+    use crate::dataflow::operators::{AssignIds, Capture, Input};
+    use crate::dataflow::operators::capture::Extract;
+
+    /// The identified records of three workers, each of which sends `(worker, n)` for `n` in `0..10`, five at each of times 0 and 1.
+    fn identified() -> Vec<(u64, Vec<(u64, (u64, u64))>)> {
+        let (send, recv) = std::sync::mpsc::channel();
+        let send = Arc::new(Mutex::new(send));
+        crate::execute(Config::process(3), move |worker| {
+            let send = send.lock().unwrap().clone();
+            let index = worker.index() as u64;
+            worker.dataflow::<u64,_,_>(|scope| {
+                let (mut input, stream) = scope.new_input::<(u64, u64)>();
+                stream.assign_ids().capture_into(send);
+                for time in 0..2 {
+                    input.advance_to(time);
+                    for n in 0..5 { input.send((index, time * 5 + n)); }
+                }
+            });
+        }).unwrap();
+        recv.extract()
+    }
+
+    #[test]
+    fn identifiers_are_unique_and_deterministic() {
+        let first = identified();
+        // The `n`th record of each worker, counted across times, has the identifier `n * 3 + worker`.
+        for (_, records) in first.iter() {
+            for (id, (worker, n)) in records {
+                assert_eq!(*id, n * 3 + worker);
+            }
+        }
+        let mut ids = first.iter().flat_map(|(_, records)| records.iter().map(|(id, _)| *id)).collect::<Vec<_>>();
+        ids.sort();
+        assert_eq!(ids, (0..30).collect::<Vec<_>>());
+        // Another run assigns the same identifiers.
+        assert_eq!(first, identified());
+    }
+
+    #[test]
+    #[should_panic(expected = "AssignIds: worker 2 numbered more than u64::MAX / 3 records")]
+    fn identifiers_that_overflow_panic() {
+        let last = u64::MAX / 3;
+        assert_eq!(super::identifier(last, 3, 0), u64::MAX);
+        super::identifier(last, 3, 2);
+    }
+}
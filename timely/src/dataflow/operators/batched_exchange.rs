@@ -0,0 +1,168 @@
+//! Extension methods for `Stream` that exchange records in batches of at least a minimum size.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::ExchangeData;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::{ExchangePreRouted, Pipeline};
+use crate::dataflow::operators::Capability;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::generic::stash::Stash;
+
+/// Extension trait for `Stream`.
+pub trait BatchedExchange<G: Scope, D: ExchangeData> {
+    /// Exchanges records by `route`, as [`Exchange`](crate::dataflow::operators::Exchange) does, in batches of at least `min_batch` records.
+    ///
+    /// Each worker buffers the records of each time destined for each worker, the worker
+    /// `route(record) % peers`, and sends a buffer as one message once it holds `min_batch` records,
+    /// or once the input frontier has passed its time, whichever comes first. Each message of the
+    /// exchange is then a batch of at least `min_batch` records, except for the last one of each
+    /// time and destination, which reduces the number of messages for streams of many small records.
+    ///
+    /// The batching adds latency: a record is held until its buffer fills or its time completes,
+    /// and at a time that remains open, for example the current time of an input that is not
+    /// advanced, a buffer short of `min_batch` records is held for as long as the time does. The
+    /// exchange of the pact instead sends its buffers at the end of each scheduling of the upstream
+    /// operator, whatever their sizes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min_batch` is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, BatchedExchange, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .batched_exchange(4, |x| *x)
+    ///            .inspect(|x| println!("seen: {:?}", x));
+    /// });
+    /// ```
+    fn batched_exchange<F>(&self, min_batch: usize, route: F) -> Stream<G, D>
+    where
+        F: FnMut(&D)->u64+'static;
+}
+
+impl<G: Scope, D: ExchangeData> BatchedExchange<G, D> for Stream<G, D> {
+    fn batched_exchange<F>(&self, min_batch: usize, route: F) -> Stream<G, D>
+    where
+        F: FnMut(&D)->u64+'static,
+    {
+        assert!(min_batch > 0, "BatchedExchange: min_batch must be positive");
+        let peers = self.scope().peers();
+        let route = Rc::new(RefCell::new(route));
+        let batch_route = Rc::clone(&route);
+
+        let batches = self.unary_frontier(Pipeline, "BatchedExchange", move |_capability, _info| {
+            // For each incomplete time, the buffered records destined for each worker.
+            let mut buffers = Stash::<Capability<G::Timestamp>, Vec<Vec<D>>>::new();
+            move |(input, frontier), output| {
+                input.for_each_time(|time, data| {
+                    let buffers = buffers.get_or_retain(&time, || (0..peers).map(|_| Vec::new()).collect());
+                    let mut session = output.session(&time);
+                    for datum in data.flat_map(|d| d.drain(..)) {
+                        // The route is not borrowed while giving batches, as the exchange borrows it to send them.
+                        let target = (route.borrow_mut())(&datum) % peers as u64;
+                        let buffer = &mut buffers[target as usize];
+                        buffer.push(datum);
+                        if buffer.len() >= min_batch {
+                            session.give_container(&mut std::mem::replace(buffer, Vec::with_capacity(min_batch)));
+                        }
+                    }
+                });
+                buffers.release(|time| !frontier.less_equal(time), |capability, buffers| {
+                    let mut session = output.session(&capability);
+                    for mut buffer in buffers.into_iter().filter(|buffer| !buffer.is_empty()) {
+                        session.give_container(&mut buffer);
+                    }
+                });
+            }
+        });
+
+        // Batches are routed by a record of theirs, as all of their records share a destination.
+        let pact = ExchangePreRouted::new(move |batch: &Vec<D>| (batch_route.borrow_mut())(&batch[0]));
+        batches.unary(pact, "BatchedExchangeReceive", |_capability, _info| |input, output| {
+            input.for_each_time(|time, data| output.session(&time).give_containers(data));
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::Config;
+    use crate::dataflow::operators::{BatchedExchange, Capture, Input, InspectCore, Map, Probe, ToStream};
+    use crate::dataflow::operators::capture::{Event, Extract};
+
+    #[test]
+    fn records_reach_their_workers_in_batches() {
+        let (send, recv) = std::sync::mpsc::channel();
+        let send = Arc::new(Mutex::new(send));
+        let batches = Arc::new(Mutex::new(Vec::new()));
+        let batches_outer = Arc::clone(&batches);
+        crate::execute(Config::process(2), move |worker| {
+            let send = send.lock().unwrap().clone();
+            let batches = Arc::clone(&batches);
+            let index = worker.index();
+            let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<u64>();
+                let exchanged = stream.batched_exchange(3, |x| *x)
+                                      .inspect_container(move |event| {
+                                          if let Ok((_time, batch)) = event { batches.lock().unwrap().push(batch.len()); }
+                                      });
+                exchanged.map(move |x| (index, x)).capture_into(send);
+                (input, exchanged.probe())
+            });
+            if index == 0 {
+                // Seven records for each worker, sent across several steps within time 0.
+                for x in 0..14 {
+                    input.send(x);
+                    input.flush();
+                    worker.step();
+                }
+            }
+            input.advance_to(1);
+            worker.step_while(|| probe.less_than(input.time()));
+        }).unwrap();
+
+        let results = recv.extract();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].1.len(), 14);
+        assert!(results[0].1.iter().all(|(index, x)| *x as usize % 2 == *index));
+        // Each worker receives two full batches, and the rest once the time completes.
+        let mut sizes = batches_outer.lock().unwrap().clone();
+        sizes.sort();
+        assert_eq!(sizes, vec![1, 1, 3, 3, 3, 3]);
+    }
+
+    #[test]
+    fn incomplete_batches_wait_for_the_frontier() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<u64>();
+                let exchanged = stream.batched_exchange(10, |x| *x);
+                (input, exchanged.probe(), exchanged.capture())
+            });
+            input.send(1);
+            input.send(2);
+            input.flush();
+            for _ in 0..5 { worker.step(); }
+            assert!(captured.try_iter().all(|event| matches!(event, Event::Progress(_))));
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+        assert_eq!(captured.extract(), vec![(0, vec![1, 2])]);
+    }
+
+    #[test]
+    #[should_panic(expected = "min_batch must be positive")]
+    fn zero_min_batch_panics() {
+        crate::example(|scope| {
+            (0..10u64).to_stream(scope).batched_exchange(0, |x| *x);
+        });
+    }
+}
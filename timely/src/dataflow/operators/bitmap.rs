@@ -0,0 +1,422 @@
+//! Extension methods for `Stream` that collect integer keys into compressed bitmaps, and combine streams of bitmaps.
+
+use std::hash::Hash;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Exchange;
+use crate::dataflow::operators::Capability;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::generic::stash::Stash;
+use crate::dataflow::operators::sketch::{hash_of, summarize_by_key};
+
+/// The most values an array container holds; larger containers are bitmaps, which then take less space.
+const ARRAY_LIMIT: usize = 4096;
+
+/// The number of 64-bit words of a bitmap container, one bit for each of its 65536 values.
+const BITMAP_WORDS: usize = 1024;
+
+/// A set of `u32` values, compressed as a Roaring bitmap.
+///
+/// Values are partitioned by their upper 16 bits into chunks of 65536 values, and each chunk with
+/// values is stored in a container: a sorted array of the lower 16 bits of its values if it has at
+/// most 4096 of them, and otherwise a bitmap of 8 KiB. Sets of dense integers, such as identifiers
+/// allocated in sequence, thus take about a bit per value, and sparse sets about two bytes per
+/// value, and unions and intersections proceed a container at a time. Containers are always in
+/// this form, and so equal sets have equal bitmaps.
+///
+/// This follows the design of the Roaring format, without its run containers, and its serialized
+/// form is that of `serde` rather than the Roaring interchange format.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::bitmap::RoaringBitmap;
+///
+/// let mut evens = (0..100_000).step_by(2).collect::<RoaringBitmap>();
+/// let threes = (0..100_000).step_by(3).collect::<RoaringBitmap>();
+/// assert_eq!(evens.len(), 50_000);
+/// evens.intersect_with(&threes);
+/// assert!(evens.iter().all(|x| x % 6 == 0));
+/// assert_eq!(evens.len(), 16_667);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RoaringBitmap {
+    /// The containers of the chunks with values, by the upper 16 bits of their values, in order.
+    containers: Vec<(u16, Chunk)>,
+}
+
+/// The lower 16 bits of the values of a chunk.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+enum Chunk {
+    /// At most `ARRAY_LIMIT` values, sorted.
+    Array(Vec<u16>),
+    /// More than `ARRAY_LIMIT` values, as `BITMAP_WORDS` words, and their number.
+    Bitmap(Vec<u64>, u32),
+}
+
+impl Chunk {
+    fn len(&self) -> usize {
+        match self {
+            Chunk::Array(values) => values.len(),
+            Chunk::Bitmap(_, len) => *len as usize,
+        }
+    }
+
+    fn contains(&self, low: u16) -> bool {
+        match self {
+            Chunk::Array(values) => values.binary_search(&low).is_ok(),
+            Chunk::Bitmap(words, _) => words[usize::from(low) / 64] & (1 << (low % 64)) != 0,
+        }
+    }
+
+    /// Inserts `low`, and returns `true` if it was not present.
+    fn insert(&mut self, low: u16) -> bool {
+        match self {
+            Chunk::Array(values) => {
+                let Err(position) = values.binary_search(&low) else { return false; };
+                values.insert(position, low);
+                if values.len() > ARRAY_LIMIT {
+                    *self = Chunk::bitmap_of(values.iter().copied());
+                }
+                true
+            }
+            Chunk::Bitmap(words, len) => {
+                let (word, bit) = (usize::from(low) / 64, 1 << (low % 64));
+                let absent = words[word] & bit == 0;
+                words[word] |= bit;
+                *len += u32::from(absent);
+                absent
+            }
+        }
+    }
+
+    /// The values of the chunk, in order.
+    fn iter(&self) -> Box<dyn Iterator<Item = u16> + '_> {
+        match self {
+            Chunk::Array(values) => Box::new(values.iter().copied()),
+            Chunk::Bitmap(words, _) => Box::new(words.iter().enumerate().flat_map(|(index, word)| {
+                (0..64u16).filter(move |bit| word & (1 << bit) != 0).map(move |bit| index as u16 * 64 + bit)
+            })),
+        }
+    }
+
+    fn bitmap_of(values: impl Iterator<Item = u16>) -> Self {
+        let mut words = vec![0u64; BITMAP_WORDS];
+        for low in values {
+            words[usize::from(low) / 64] |= 1 << (low % 64);
+        }
+        Chunk::from_words(words)
+    }
+
+    /// The chunk of the values of `words`, as an array if there are few enough of them.
+    fn from_words(words: Vec<u64>) -> Self {
+        let len = words.iter().map(|word| word.count_ones()).sum::<u32>();
+        let chunk = Chunk::Bitmap(words, len);
+        if len as usize <= ARRAY_LIMIT { Chunk::Array(chunk.iter().collect()) } else { chunk }
+    }
+
+    fn union(&self, other: &Chunk) -> Chunk {
+        match (self, other) {
+            (Chunk::Array(mine), Chunk::Array(theirs)) => {
+                let mut values = Vec::with_capacity(mine.len() + theirs.len());
+                let (mut i, mut j) = (0, 0);
+                while i < mine.len() && j < theirs.len() {
+                    match mine[i].cmp(&theirs[j]) {
+                        std::cmp::Ordering::Less => { values.push(mine[i]); i += 1; }
+                        std::cmp::Ordering::Greater => { values.push(theirs[j]); j += 1; }
+                        std::cmp::Ordering::Equal => { values.push(mine[i]); i += 1; j += 1; }
+                    }
+                }
+                values.extend_from_slice(&mine[i..]);
+                values.extend_from_slice(&theirs[j..]);
+                if values.len() > ARRAY_LIMIT { Chunk::bitmap_of(values.into_iter()) } else { Chunk::Array(values) }
+            }
+            (Chunk::Bitmap(words, _), Chunk::Array(values)) | (Chunk::Array(values), Chunk::Bitmap(words, _)) => {
+                let mut words = words.clone();
+                for low in values {
+                    words[usize::from(*low) / 64] |= 1 << (low % 64);
+                }
+                Chunk::from_words(words)
+            }
+            (Chunk::Bitmap(mine, _), Chunk::Bitmap(theirs, _)) => {
+                Chunk::from_words(mine.iter().zip(theirs.iter()).map(|(x, y)| x | y).collect())
+            }
+        }
+    }
+
+    fn intersection(&self, other: &Chunk) -> Chunk {
+        match (self, other) {
+            (Chunk::Array(values), chunk) | (chunk, Chunk::Array(values)) => {
+                Chunk::Array(values.iter().copied().filter(|low| chunk.contains(*low)).collect())
+            }
+            (Chunk::Bitmap(mine, _), Chunk::Bitmap(theirs, _)) => {
+                Chunk::from_words(mine.iter().zip(theirs.iter()).map(|(x, y)| x & y).collect())
+            }
+        }
+    }
+}
+
+impl RoaringBitmap {
+    /// Allocates an empty bitmap.
+    pub fn new() -> Self { Self::default() }
+
+    /// Inserts `value`, and returns `true` if it was not present.
+    pub fn insert(&mut self, value: u32) -> bool {
+        let (high, low) = ((value >> 16) as u16, value as u16);
+        match self.containers.binary_search_by_key(&high, |(key, _)| *key) {
+            Ok(position) => self.containers[position].1.insert(low),
+            Err(position) => {
+                self.containers.insert(position, (high, Chunk::Array(vec![low])));
+                true
+            }
+        }
+    }
+
+    /// Returns `true` if `value` is present.
+    pub fn contains(&self, value: u32) -> bool {
+        let (high, low) = ((value >> 16) as u16, value as u16);
+        self.containers.binary_search_by_key(&high, |(key, _)| *key).is_ok_and(|position| self.containers[position].1.contains(low))
+    }
+
+    /// The number of values present.
+    pub fn len(&self) -> u64 {
+        self.containers.iter().map(|(_, chunk)| chunk.len() as u64).sum()
+    }
+
+    /// Returns `true` if no values are present.
+    pub fn is_empty(&self) -> bool { self.containers.is_empty() }
+
+    /// The values present, in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        self.containers.iter().flat_map(|(high, chunk)| chunk.iter().map(move |low| (u32::from(*high) << 16) | u32::from(low)))
+    }
+
+    /// Inserts the values of `other`, forming the union of the two sets.
+    pub fn union_with(&mut self, other: &Self) {
+        let mut containers = Vec::with_capacity(self.containers.len() + other.containers.len());
+        let mut theirs = other.containers.iter().peekable();
+        for (high, chunk) in std::mem::take(&mut self.containers) {
+            while let Some((other_high, other_chunk)) = theirs.next_if(|(other_high, _)| *other_high < high) {
+                containers.push((*other_high, other_chunk.clone()));
+            }
+            match theirs.next_if(|(other_high, _)| *other_high == high) {
+                Some((_, other_chunk)) => containers.push((high, chunk.union(other_chunk))),
+                None => containers.push((high, chunk)),
+            }
+        }
+        containers.extend(theirs.cloned());
+        self.containers = containers;
+    }
+
+    /// Removes the values not present in `other`, forming the intersection of the two sets.
+    pub fn intersect_with(&mut self, other: &Self) {
+        let mut theirs = other.containers.iter().peekable();
+        self.containers = std::mem::take(&mut self.containers).into_iter().filter_map(|(high, chunk)| {
+            while theirs.next_if(|(other_high, _)| *other_high < high).is_some() { }
+            let (_, other_chunk) = theirs.next_if(|(other_high, _)| *other_high == high)?;
+            let both = chunk.intersection(other_chunk);
+            (both.len() > 0).then_some((high, both))
+        }).collect();
+    }
+}
+
+impl FromIterator<u32> for RoaringBitmap {
+    fn from_iter<I: IntoIterator<Item = u32>>(values: I) -> Self {
+        let mut bitmap = Self::new();
+        bitmap.extend(values);
+        bitmap
+    }
+}
+
+impl Extend<u32> for RoaringBitmap {
+    fn extend<I: IntoIterator<Item = u32>>(&mut self, values: I) {
+        for value in values {
+            self.insert(value);
+        }
+    }
+}
+
+/// Extension trait for `Stream`.
+pub trait ToBitmap<G: Scope, D: Data> {
+    /// Collects the keys `key_fn` produces for the records of each time into a [`RoaringBitmap`], across all workers.
+    ///
+    /// Each worker inserts the keys of its records into a bitmap per time, and once the input
+    /// frontier has passed a time, the bitmaps of all workers for that time are exchanged to one
+    /// worker and merged by union, which produces the bitmap of the time. Records themselves are not
+    /// exchanged, and memory use is that of the bitmaps of incomplete times, which for dense keys is
+    /// about a bit per key. Times without records produce no bitmap.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, ToBitmap, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..1_000u32).to_stream(scope)
+    ///                  .to_bitmap(|x| x % 100)
+    ///                  .inspect(|bitmap| assert_eq!(bitmap.len(), 100));
+    /// });
+    /// ```
+    fn to_bitmap<F: FnMut(&D)->u32+'static>(&self, key_fn: F) -> Stream<G, RoaringBitmap>;
+}
+
+impl<G: Scope<Timestamp: Hash>, D: Data> ToBitmap<G, D> for Stream<G, D> {
+    fn to_bitmap<F: FnMut(&D)->u32+'static>(&self, mut key_fn: F) -> Stream<G, RoaringBitmap> {
+        summarize_by_key(self, "ToBitmap", hash_of,
+            move |bitmaps, datum| { bitmaps.entry(()).or_insert_with(RoaringBitmap::new).insert(key_fn(&datum)); },
+            |bitmap, other| bitmap.union_with(&other),
+            |(), bitmap| bitmap,
+        )
+    }
+}
+
+/// Extension trait for `Stream`.
+pub trait BitmapSetOps<G: Scope> {
+    /// Produces, for each time, the union of the bitmaps of either stream at the time.
+    ///
+    /// The bitmaps of both streams are exchanged to one worker, which once the frontiers of both
+    /// inputs have passed a time produces the union of all the bitmaps at the time, for each time
+    /// at which either stream has a bitmap.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, ToBitmap, BitmapSetOps, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     let evens = (0..100u32).to_stream(scope).to_bitmap(|x| x * 2);
+    ///     let threes = (0..100u32).to_stream(scope).to_bitmap(|x| x * 3);
+    ///     evens.bitmap_union(&threes)
+    ///          .inspect(|bitmap| assert_eq!(bitmap.len(), 166));
+    /// });
+    /// ```
+    fn bitmap_union(&self, other: &Self) -> Self;
+
+    /// Produces, for each time, the intersection of the union of the bitmaps of `self` at the time and that of `other`.
+    ///
+    /// The bitmaps of both streams are exchanged to one worker, which once the frontiers of both
+    /// inputs have passed a time produces the values present in a bitmap of each stream at the
+    /// time, for each time at which either stream has a bitmap. A time at which only one stream has
+    /// bitmaps produces an empty bitmap.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, ToBitmap, BitmapSetOps, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     let evens = (0..100u32).to_stream(scope).to_bitmap(|x| x * 2);
+    ///     let threes = (0..100u32).to_stream(scope).to_bitmap(|x| x * 3);
+    ///     evens.bitmap_intersection(&threes)
+    ///          .inspect(|bitmap| assert!(bitmap.iter().all(|x| x % 6 == 0)));
+    /// });
+    /// ```
+    fn bitmap_intersection(&self, other: &Self) -> Self;
+}
+
+impl<G: Scope> BitmapSetOps<G> for Stream<G, RoaringBitmap> {
+    fn bitmap_union(&self, other: &Self) -> Self {
+        combine(self, other, "BitmapUnion", |mut left, right| { left.union_with(&right); left })
+    }
+
+    fn bitmap_intersection(&self, other: &Self) -> Self {
+        combine(self, other, "BitmapIntersection", |mut left, right| { left.intersect_with(&right); left })
+    }
+}
+
+/// Produces, for each time at which either stream has bitmaps, `logic` of the unions of the bitmaps of each stream at the time.
+fn combine<G, L>(left: &Stream<G, RoaringBitmap>, right: &Stream<G, RoaringBitmap>, name: &str, mut logic: L) -> Stream<G, RoaringBitmap>
+where
+    G: Scope,
+    L: FnMut(RoaringBitmap, RoaringBitmap)->RoaringBitmap+'static,
+{
+    left.binary_frontier(right, Exchange::new(|_| 0), Exchange::new(|_| 0), name, move |_capability, _info| {
+        // For each incomplete time, a capability for it and the union of each stream's bitmaps.
+        let mut pending = Stash::<Capability<G::Timestamp>, (RoaringBitmap, RoaringBitmap)>::new();
+        move |(input1, frontier1), (input2, frontier2), output| {
+            input1.for_each_time(|time, data| {
+                let (bitmap, _) = pending.get_or_retain(&time, Default::default);
+                for other in data.flat_map(|d| d.drain(..)) { bitmap.union_with(&other); }
+            });
+            input2.for_each_time(|time, data| {
+                let (_, bitmap) = pending.get_or_retain(&time, Default::default);
+                for other in data.flat_map(|d| d.drain(..)) { bitmap.union_with(&other); }
+            });
+            pending.release(|time| !frontier1.less_equal(time) && !frontier2.less_equal(time), |capability, (bitmap1, bitmap2)| {
+                output.session(&capability).give(logic(bitmap1, bitmap2));
+            });
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::Config;
+    use crate::dataflow::operators::{BitmapSetOps, Capture, Concat, Input, Map, ToBitmap};
+    use crate::dataflow::operators::capture::Extract;
+    use super::RoaringBitmap;
+
+    #[test]
+    fn bitmaps_are_sets() {
+        // Dense and sparse chunks, and a chunk that becomes dense as values are inserted.
+        let values = (0..10_000).chain((70_000..200_000).step_by(7)).chain([u32::MAX, 5, 70_000]).collect::<Vec<_>>();
+        let mut expected = values.clone();
+        expected.sort();
+        expected.dedup();
+        let bitmap = values.iter().copied().collect::<RoaringBitmap>();
+        assert_eq!(bitmap.len(), expected.len() as u64);
+        assert_eq!(bitmap.iter().collect::<Vec<_>>(), expected);
+        assert!(bitmap.contains(u32::MAX) && bitmap.contains(70_007) && !bitmap.contains(70_001) && !bitmap.contains(10_000));
+
+        let other = (5_000..80_000).step_by(2).collect::<RoaringBitmap>();
+        let mut union = bitmap.clone();
+        union.union_with(&other);
+        let mut intersection = bitmap.clone();
+        intersection.intersect_with(&other);
+        let expected_union = expected.iter().copied().chain((5_000..80_000).step_by(2)).collect::<std::collections::BTreeSet<_>>();
+        let expected_intersection = expected.iter().copied().filter(|x| (5_000..80_000).contains(x) && x % 2 == 0).collect::<Vec<_>>();
+        assert_eq!(union.iter().collect::<Vec<_>>(), expected_union.into_iter().collect::<Vec<_>>());
+        assert_eq!(intersection.iter().collect::<Vec<_>>(), expected_intersection);
+        // Sets built in different orders, and through different operations, are equal.
+        assert_eq!(intersection, expected_intersection.iter().rev().copied().collect::<RoaringBitmap>());
+        intersection.intersect_with(&RoaringBitmap::new());
+        assert!(intersection.is_empty());
+    }
+
+    #[test]
+    fn bitmaps_are_the_union_of_all_workers() {
+        let (send, recv) = std::sync::mpsc::channel();
+        let send = Arc::new(Mutex::new(send));
+        crate::execute(Config::process(3), move |worker| {
+            let send = send.lock().unwrap().clone();
+            let index = worker.index() as u32;
+            worker.dataflow::<u64,_,_>(|scope| {
+                let (mut lefts, left_stream) = scope.new_input::<u32>();
+                let (mut rights, right_stream) = scope.new_input::<u32>();
+                let left = left_stream.to_bitmap(|x| *x);
+                let right = right_stream.to_bitmap(|x| *x);
+                left.map(|bitmap| ("left", bitmap.iter().collect::<Vec<_>>()))
+                    .concat(&left.bitmap_union(&right).map(|bitmap| ("union", bitmap.iter().collect())))
+                    .concat(&left.bitmap_intersection(&right).map(|bitmap| ("intersection", bitmap.iter().collect())))
+                    .capture_into(send);
+                // Each worker has a third of the left values, and of the right values, at time 0,
+                // and at time 1 the left values alone.
+                for value in (0..30).filter(|x| x % 3 == index) { lefts.send(value); }
+                for value in (20..40).filter(|x| x % 3 == index) { rights.send(value); }
+                lefts.advance_to(1);
+                rights.advance_to(1);
+                lefts.send(index);
+            });
+        }).unwrap();
+
+        assert_eq!(recv.extract(), vec![
+            (0, vec![
+                ("intersection", (20..30).collect()),
+                ("left", (0..30).collect()),
+                ("union", (0..40).collect()),
+            ]),
+            (1, vec![("intersection", vec![]), ("left", vec![0, 1, 2]), ("union", vec![0, 1, 2])]),
+        ]);
+    }
+}
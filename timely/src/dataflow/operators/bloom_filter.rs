@@ -0,0 +1,151 @@
+//! Extension methods for `Stream` that filter records against a Bloom filter of keys.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::Broadcast;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::sketch::{summarize_local, BloomFilter};
+
+/// Extension trait for `Stream`.
+pub trait BloomFilterBy<G: Scope, D: Data> {
+    /// Drops records whose key is certainly absent from `keys` at the same time.
+    ///
+    /// Each worker inserts the keys it receives at each time into a [`BloomFilter`] sized for
+    /// `expected_keys` keys at the false positive rate `false_positive_rate`. Once the key stream
+    /// has passed a time, the filters for that time are broadcast to and merged at all workers.
+    /// Records of `self` are held until both inputs have passed their time, and then those for which
+    /// `key_fn` produces a key possibly in the merged filter are emitted, and the filter is released.
+    ///
+    /// Every record whose key is present in `keys` is retained, but some records whose key is absent
+    /// may also be retained, at roughly the configured false positive rate. The operator is meant as
+    /// a cheap pre-filter ahead of an exact join with a small key stream, and memory use per worker
+    /// is bounded by the filter size plus the records held for incomplete times.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, BloomFilterBy, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     let keys = (0..10u64).map(|x| x * 100).to_stream(scope);
+    ///     (0..1000u64).to_stream(scope)
+    ///                 .bloom_filter_by(&keys, |x| *x, 10, 0.01)
+    ///                 .inspect(|x| println!("possible match: {:?}", x));
+    /// });
+    /// ```
+    fn bloom_filter_by<K, F>(&self, keys: &Stream<G, K>, key_fn: F, expected_keys: usize, false_positive_rate: f64) -> Stream<G, D>
+    where
+        K: Data+Hash,
+        F: FnMut(&D)->K+'static;
+}
+
+impl<G: Scope<Timestamp: Hash>, D: Data> BloomFilterBy<G, D> for Stream<G, D> {
+    fn bloom_filter_by<K, F>(&self, keys: &Stream<G, K>, mut key_fn: F, expected_keys: usize, false_positive_rate: f64) -> Stream<G, D>
+    where
+        K: Data+Hash,
+        F: FnMut(&D)->K+'static,
+    {
+        // All workers must construct filters of the same shape for them to merge.
+        let empty = BloomFilter::with_rate(expected_keys, false_positive_rate);
+
+        let template = empty.clone();
+        let filters = summarize_local(keys, "BloomFilterBuild", move |filters, key| {
+            filters.entry(()).or_insert_with(|| template.clone()).insert(&key);
+        });
+
+        let mut stash = HashMap::<G::Timestamp, Vec<D>>::new();
+        let mut merged = HashMap::<G::Timestamp, BloomFilter>::new();
+        self.binary_notify(&filters.broadcast(), Pipeline, Pipeline, "BloomFilterBy", vec![], move |input1, input2, output, notificator| {
+            input1.for_each_time(|time, data| {
+                let records = stash.entry(time.time().clone()).or_default();
+                for container in data {
+                    records.append(container);
+                }
+                notificator.notify_at(time.retain());
+            });
+            input2.for_each_time(|time, data| {
+                let filter = merged.entry(time.time().clone()).or_insert_with(|| empty.clone());
+                for ((), other) in data.flat_map(|d| d.iter()) {
+                    filter.merge(other);
+                }
+                notificator.notify_at(time.retain());
+            });
+            notificator.for_each(|time, _, _| {
+                let filter = merged.remove(time.time());
+                if let Some(records) = stash.remove(time.time()) {
+                    // Without a filter no key arrived at this time, and no record can match.
+                    if let Some(filter) = filter {
+                        output.session(&time).give_iterator(records.into_iter().filter(|record| filter.contains(&key_fn(record))));
+                    }
+                }
+            });
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::Config;
+    use crate::dataflow::operators::{Capture, Filter, Input, Probe, ToStream, BloomFilterBy};
+    use crate::dataflow::operators::capture::Extract;
+
+    #[test]
+    fn records_match_keys_of_their_own_time() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut keys, mut records, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (keys, key_stream) = scope.new_input::<u64>();
+                let (records, record_stream) = scope.new_input::<u64>();
+                let retained = record_stream.bloom_filter_by(&key_stream, |x| *x, 100, 0.001);
+                (keys, records, retained.probe(), retained.capture())
+            });
+            // Time 0 has keys and records, time 1 only records, time 2 only keys.
+            for key in [1, 2, 3] { keys.send(key); }
+            for record in 0..10 { records.send(record); }
+            keys.advance_to(1);
+            records.advance_to(1);
+            records.send(1);
+            keys.advance_to(2);
+            records.advance_to(2);
+            keys.send(4);
+            keys.advance_to(3);
+            records.advance_to(3);
+            // Keys of earlier times do not retain records.
+            keys.send(5);
+            records.send(1);
+            records.send(5);
+            keys.close();
+            records.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        assert_eq!(captured.extract(), vec![(0, vec![1, 2, 3]), (3, vec![5])]);
+    }
+
+    #[test]
+    fn filters_merge_across_workers() {
+        let (send, recv) = std::sync::mpsc::channel();
+        let send = Arc::new(Mutex::new(send));
+        crate::execute(Config::process(2), move |worker| {
+            let send = send.lock().unwrap().clone();
+            let index = worker.index();
+            worker.dataflow::<u64,_,_>(move |scope| {
+                // Keys arrive only at the first worker, and records only at the second.
+                let keys = (0..10_000u64).filter(|x| x % 7 == 0).to_stream(scope).filter(move |_| index == 0);
+                (0..10_000u64).to_stream(scope)
+                              .filter(move |_| index == 1)
+                              .bloom_filter_by(&keys, |x| *x, 2_000, 0.01)
+                              .capture_into(send);
+            });
+        }).unwrap();
+
+        let retained = recv.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+        assert!((0..10_000u64).filter(|x| x % 7 == 0).all(|x| retained.binary_search(&x).is_ok()));
+        assert!(retained.len() < 2_000, "retained {} records for 1429 keys", retained.len());
+    }
+}
@@ -33,6 +33,32 @@ pub trait Branch<S: Scope, D: Data> {
         &self,
         condition: impl Fn(&S::Timestamp, &D) -> bool + 'static,
     ) -> (Stream<S, D>, Stream<S, D>);
+
+    /// Takes one input stream and splits it into two output streams by the size of each record.
+    /// For each record, the supplied closure is called to estimate its size in bytes. If the size
+    /// exceeds `threshold_bytes`, the record will be sent to the second returned stream, otherwise
+    /// it will be sent to the first.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Branch, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     let (small, large) = vec!["a".to_string(), "a longer string".to_string()]
+    ///         .to_stream(scope)
+    ///         .branch_by_size(8, |x| x.len());
+    ///
+    ///     small.inspect(|x| assert_eq!(x, "a"));
+    ///     large.inspect(|x| assert_eq!(x, "a longer string"));
+    /// });
+    /// ```
+    fn branch_by_size(
+        &self,
+        threshold_bytes: usize,
+        size_fn: impl Fn(&D) -> usize + 'static,
+    ) -> (Stream<S, D>, Stream<S, D>) {
+        self.branch(move |_time, datum| size_fn(datum) > threshold_bytes)
+    }
 }
 
 impl<S: Scope, D: Data> Branch<S, D> for Stream<S, D> {
@@ -129,3 +155,23 @@ impl<S: Scope, C: Container> BranchWhen<S::Timestamp> for StreamCore<S, C> {
         (stream1, stream2)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::dataflow::operators::{Branch, Capture, ToStream};
+    use crate::dataflow::operators::capture::Extract;
+
+    #[test]
+    fn records_split_at_size_threshold() {
+        let (small, large) = crate::example(|scope| {
+            let (small, large) = vec![vec![0u8; 3], vec![0u8; 10], vec![], vec![0u8; 4], vec![0u8; 5], vec![0u8; 100]]
+                .to_stream(scope)
+                .branch_by_size(4, |x| x.len());
+            (small.capture(), large.capture())
+        });
+        // Records of exactly the threshold size are not large.
+        let lengths = |captured: Vec<(u64, Vec<Vec<u8>>)>| captured.into_iter().flat_map(|(_, data)| data).map(|x| x.len()).collect::<Vec<_>>();
+        assert_eq!(lengths(small.extract()), vec![0, 3, 4]);
+        assert_eq!(lengths(large.extract()), vec![5, 10, 100]);
+    }
+}
@@ -8,15 +8,31 @@ use crate::dataflow::operators::{Map, Exchange};
 pub trait Broadcast<D: ExchangeData> {
     /// Broadcast records to all workers.
     ///
+    /// Each record is replicated to every worker, at its time, and so each worker's output holds the
+    /// records of all workers' inputs, for example to make a small configuration or dimension table
+    /// available everywhere. Progress tracking counts each replica as a record, and so a time with
+    /// `n` records across the workers' inputs has `n * peers` records in the output, `n` at each worker.
+    /// The records of a time from different workers may arrive at each worker in a different order.
+    ///
     /// # Examples
     /// ```
-    /// use timely::dataflow::operators::{ToStream, Broadcast, Inspect};
+    /// use timely::dataflow::operators::{ToStream, Broadcast, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
     ///
-    /// timely::example(|scope| {
-    ///     (0..10).to_stream(scope)
-    ///            .broadcast()
-    ///            .inspect(|x| println!("seen: {:?}", x));
-    /// });
+    /// timely::execute(timely::Config::process(3), |worker| {
+    ///     let index = worker.index() as u64;
+    ///     let (send, recv) = std::sync::mpsc::channel();
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         // Each worker contributes two records.
+    ///         (0..2).map(move |x| 10 * index + x)
+    ///               .to_stream(scope)
+    ///               .broadcast()
+    ///               .capture_into(send);
+    ///     });
+    ///     while worker.step() { }
+    ///     // Every worker sees the records of all workers.
+    ///     assert_eq!(recv.extract(), vec![(0, vec![0, 1, 10, 11, 20, 21])]);
+    /// }).unwrap();
     /// ```
     fn broadcast(&self) -> Self;
 }
@@ -0,0 +1,104 @@
+//! Extension methods for `Stream` that hold records for a wall-clock duration.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::Capability;
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// Extension trait for `Stream`.
+pub trait BufferedDelay<G: Scope, D: Data> {
+    /// Holds each record for `duration` of wall-clock time after it arrives, and then produces it at its own time.
+    ///
+    /// Unlike [`Delay::delay`](crate::dataflow::operators::Delay::delay), which moves records to
+    /// later logical times, the operator leaves times unchanged and only postpones records in real
+    /// time, for example to give late records a grace period in processing-time terms. Using a timer
+    /// activation, it is scheduled as the oldest held records come due, and produces records in the
+    /// order in which they arrived.
+    ///
+    /// The operator retains a capability for the time of each batch of records it holds, and so the
+    /// output frontier does not pass a time while records at it are held: downstream operators see
+    /// the frontier pass a time only after all its records have been released, up to `duration`
+    /// after the input frontier has passed it. Once nothing is held, the output frontier is that of
+    /// the input. Memory is bounded by the records that arrive within `duration`.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use timely::dataflow::operators::{ToStream, BufferedDelay, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .buffered_delay(Duration::from_millis(10))
+    ///            .inspect(|x| println!("released: {:?}", x));
+    /// });
+    /// ```
+    fn buffered_delay(&self, duration: Duration) -> Stream<G, D>;
+}
+
+impl<G: Scope, D: Data> BufferedDelay<G, D> for Stream<G, D> {
+    fn buffered_delay(&self, duration: Duration) -> Stream<G, D> {
+        let scope = self.scope();
+        self.unary(Pipeline, "BufferedDelay", move |_capability, info| {
+            let activator = scope.activator_for(info.address);
+            // Held batches in order of arrival, and so of when they come due.
+            let mut held = VecDeque::<(Instant, Capability<G::Timestamp>, Vec<D>)>::new();
+            move |input, output| {
+                let now = Instant::now();
+                input.for_each_time(|time, data| {
+                    let mut records = Vec::new();
+                    for container in data { records.append(container); }
+                    held.push_back((now + duration, time.retain(), records));
+                });
+
+                while held.front().is_some_and(|(due, _, _)| *due <= now) {
+                    let (_due, capability, mut records) = held.pop_front().expect("held batch");
+                    output.session(&capability).give_container(&mut records);
+                }
+                if let Some((due, _, _)) = held.front() {
+                    activator.activate_after(due.saturating_duration_since(now));
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::dataflow::operators::{BufferedDelay, Capture, Input, Probe};
+    use crate::dataflow::operators::capture::Extract;
+
+    #[test]
+    fn records_are_released_after_the_duration_at_their_times() {
+        let duration = Duration::from_millis(50);
+        let captured = crate::execute_directly(move |worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<u64>();
+                let delayed = stream.buffered_delay(duration);
+                (input, delayed.probe(), delayed.capture())
+            });
+            let start = Instant::now();
+            input.send(1);
+            input.advance_to(2);
+            input.send(2);
+            input.advance_to(3);
+            // The held records hold the output frontier back at their times until released.
+            for _ in 0..10 { worker.step(); }
+            if start.elapsed() < duration {
+                assert!(probe.less_equal(&0));
+            }
+            worker.step_or_park_while(Some(duration), || probe.less_than(&3));
+            assert!(start.elapsed() >= duration);
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        assert_eq!(captured.extract(), vec![(0, vec![1]), (2, vec![2])]);
+    }
+}
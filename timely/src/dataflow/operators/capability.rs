@@ -60,9 +60,15 @@ impl<T: Timestamp, C: CapabilityTrait<T>> CapabilityTrait<T> for &mut C {
 /// when user code retains the ability to send messages on dataflow edges. All capabilities are
 /// constructed by the system, and should eventually be dropped by the user. Failure to drop
 /// a capability (for whatever reason) will cause timely dataflow's progress tracking to stall.
+///
+/// With the `capability-provenance` feature, each capability records where it was created, and
+/// the capabilities a thread holds are listed by `provenance::outstanding` and in the reports of a
+/// [`StallDetector`](crate::dataflow::operators::stall::StallDetector).
 pub struct Capability<T: Timestamp> {
     time: T,
     internal: Rc<RefCell<ChangeBatch<T>>>,
+    #[cfg(feature = "capability-provenance")]
+    origin: provenance::Origin,
 }
 
 impl<T: Timestamp> CapabilityTrait<T> for Capability<T> {
@@ -75,10 +81,13 @@ impl<T: Timestamp> CapabilityTrait<T> for Capability<T> {
 impl<T: Timestamp> Capability<T> {
     /// Creates a new capability at `time` while incrementing (and keeping a reference to) the provided
     /// [`ChangeBatch`].
+    #[cfg_attr(feature = "capability-provenance", track_caller)]
     pub(crate) fn new(time: T, internal: Rc<RefCell<ChangeBatch<T>>>) -> Self {
         internal.borrow_mut().update(time.clone(), 1);
 
         Self {
+            #[cfg(feature = "capability-provenance")]
+            origin: provenance::Origin::new(&time),
             time,
             internal,
         }
@@ -93,6 +102,7 @@ impl<T: Timestamp> Capability<T> {
     /// the source capability (`self`).
     ///
     /// This method panics if `self.time` is not less or equal to `new_time`.
+    #[cfg_attr(feature = "capability-provenance", track_caller)]
     pub fn delayed(&self, new_time: &T) -> Capability<T> {
         /// Makes the panic branch cold & outlined to decrease code bloat & give
         /// the inner function the best chance possible of being inlined with
@@ -117,6 +127,7 @@ impl<T: Timestamp> Capability<T> {
     /// greater or equal to the timestamp of the source capability (`self`).
     ///
     /// Returns [`None`] `self.time` is not less or equal to `new_time`.
+    #[cfg_attr(feature = "capability-provenance", track_caller)]
     pub fn try_delayed(&self, new_time: &T) -> Option<Capability<T>> {
         if self.time.less_equal(new_time) {
             Some(Self::new(new_time.clone(), Rc::clone(&self.internal)))
@@ -152,7 +163,13 @@ impl<T: Timestamp> Capability<T> {
     ///
     /// Returns a [DowngradeError] if `self.time` is not less or equal to `new_time`.
     pub fn try_downgrade(&mut self, new_time: &T) -> Result<(), DowngradeError> {
-        if let Some(new_capability) = self.try_delayed(new_time) {
+        #[cfg_attr(not(feature = "capability-provenance"), allow(unused_mut))]
+        if let Some(mut new_capability) = self.try_delayed(new_time) {
+            // A downgraded capability keeps its origin.
+            #[cfg(feature = "capability-provenance")] {
+                std::mem::swap(&mut self.origin, &mut new_capability.origin);
+                new_capability.origin.retime(new_time);
+            }
             *self = new_capability;
             Ok(())
         } else {
@@ -171,6 +188,7 @@ impl<T: Timestamp> Drop for Capability<T> {
 }
 
 impl<T: Timestamp> Clone for Capability<T> {
+    #[cfg_attr(feature = "capability-provenance", track_caller)]
     fn clone(&self) -> Capability<T> {
         Self::new(self.time.clone(), Rc::clone(&self.internal))
     }
@@ -275,11 +293,13 @@ impl<T: Timestamp> InputCapability<T> {
     /// the source capability (`self`).
     ///
     /// This method panics if `self.time` is not less or equal to `new_time`.
+    #[cfg_attr(feature = "capability-provenance", track_caller)]
     pub fn delayed(&self, new_time: &T) -> Capability<T> {
         self.delayed_for_output(new_time, 0)
     }
 
     /// Delays capability for a specific output port.
+    #[cfg_attr(feature = "capability-provenance", track_caller)]
     pub fn delayed_for_output(&self, new_time: &T, output_port: usize) -> Capability<T> {
         use crate::progress::timestamp::PathSummary;
         if let Some(path) = self.summaries.borrow().get(output_port) {
@@ -301,6 +321,7 @@ impl<T: Timestamp> InputCapability<T> {
     /// as long as they are required, as failing to drop them may result in livelock.
     ///
     /// This method panics if the timestamp summary to output zero strictly advances the time.
+    #[cfg_attr(feature = "capability-provenance", track_caller)]
     pub fn retain(self) -> Capability<T> {
         self.retain_for_output(0)
     }
@@ -308,6 +329,7 @@ impl<T: Timestamp> InputCapability<T> {
     /// Transforms to an owned capability for a specific output port.
     ///
     /// This method panics if the timestamp summary to `output_port` strictly advances the time.
+    #[cfg_attr(feature = "capability-provenance", track_caller)]
     pub fn retain_for_output(self, output_port: usize) -> Capability<T> {
         use crate::progress::timestamp::PathSummary;
         let self_time = self.time().clone();
@@ -373,6 +395,7 @@ impl<T: Timestamp> ActivateCapability<T> {
     }
 
     /// Creates a new delayed capability.
+    #[cfg_attr(feature = "capability-provenance", track_caller)]
     pub fn delayed(&self, time: &T) -> Self {
         ActivateCapability {
             capability: self.capability.delayed(time),
@@ -454,6 +477,7 @@ impl<T: Timestamp> CapabilitySet<T> {
     /// Creates a new capability to send data at `time`.
     ///
     /// This method panics if there does not exist a capability in `self.elements` less or equal to `time`.
+    #[cfg_attr(feature = "capability-provenance", track_caller)]
     pub fn delayed(&self, time: &T) -> Capability<T> {
         /// Makes the panic branch cold & outlined to decrease code bloat & give
         /// the inner function the best chance possible of being inlined with
@@ -477,11 +501,11 @@ impl<T: Timestamp> CapabilitySet<T> {
     /// Attempts to create a new capability to send data at `time`.
     ///
     /// Returns [`None`] if there does not exist a capability in `self.elements` less or equal to `time`.
+    #[cfg_attr(feature = "capability-provenance", track_caller)]
     pub fn try_delayed(&self, time: &T) -> Option<Capability<T>> {
-        self.elements
-            .iter()
-            .find(|capability| capability.time().less_equal(time))
-            .and_then(|capability| capability.try_delayed(time))
+        // Not a closure, which would hide the location of the caller from `try_delayed`.
+        let capability = self.elements.iter().find(|capability| capability.time().less_equal(time))?;
+        capability.try_delayed(time)
     }
 
     /// Downgrades the set of capabilities to correspond with the times in `frontier`.
@@ -560,3 +584,80 @@ impl<T: Timestamp> Deref for CapabilitySet<T> {
         &self.elements
     }
 }
+
+/// Records of where outstanding capabilities were created, to diagnose capabilities that are never dropped.
+///
+/// With the `capability-provenance` feature, each capability records the location in the source of
+/// the call that created it, by cloning, delaying, or retaining another capability, and its time, in
+/// a registry of the thread that holds it. A downgraded capability keeps the location it was created
+/// at. When a dataflow fails to make progress, [`outstanding`] lists the capabilities the worker of
+/// the calling thread still holds, and where they came from, as do the reports of a
+/// [`StallDetector`](crate::dataflow::operators::stall::StallDetector). Without the feature,
+/// capabilities record nothing, and this module does not exist.
+#[cfg(feature = "capability-provenance")]
+pub mod provenance {
+
+    use std::cell::RefCell;
+    use std::collections::BTreeMap;
+    use std::fmt::{self, Debug, Display};
+    use std::panic::Location;
+
+    /// The origin of an outstanding capability.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct CapabilityOrigin {
+        /// The time of the capability, formatted with `Debug`.
+        pub time: String,
+        /// The location of the call that created the capability.
+        pub location: &'static Location<'static>,
+    }
+
+    impl Display for CapabilityOrigin {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "capability at {} created at {}", self.time, self.location)
+        }
+    }
+
+    thread_local! {
+        /// The next identifier, and the origins of the outstanding capabilities of the thread by identifier.
+        static OUTSTANDING: RefCell<(u64, BTreeMap<u64, CapabilityOrigin>)> = const { RefCell::new((0, BTreeMap::new())) };
+    }
+
+    /// The origins of the capabilities held by the calling thread, in the order of their creation.
+    pub fn outstanding() -> Vec<CapabilityOrigin> {
+        OUTSTANDING.with(|outstanding| outstanding.borrow().1.values().cloned().collect())
+    }
+
+    /// The registration of a capability, which it removes when dropped.
+    pub(crate) struct Origin(u64);
+
+    impl Origin {
+        /// Registers a capability at `time`, created by the caller.
+        #[track_caller]
+        pub(crate) fn new(time: &dyn Debug) -> Self {
+            let origin = CapabilityOrigin { time: format!("{:?}", time), location: Location::caller() };
+            OUTSTANDING.with(|outstanding| {
+                let mut outstanding = outstanding.borrow_mut();
+                let identifier = outstanding.0;
+                outstanding.0 += 1;
+                outstanding.1.insert(identifier, origin);
+                Origin(identifier)
+            })
+        }
+
+        /// Updates the time of the registered capability.
+        pub(crate) fn retime(&self, time: &dyn Debug) {
+            OUTSTANDING.with(|outstanding| {
+                if let Some(origin) = outstanding.borrow_mut().1.get_mut(&self.0) {
+                    origin.time = format!("{:?}", time);
+                }
+            });
+        }
+    }
+
+    impl Drop for Origin {
+        fn drop(&mut self) {
+            // Capabilities dropped as the thread exits may outlive its registry.
+            let _ = OUTSTANDING.try_with(|outstanding| outstanding.borrow_mut().1.remove(&self.0));
+        }
+    }
+}
@@ -0,0 +1,112 @@
+//! Extension methods for `Stream` that sequence the records of two streams at each time.
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::Capability;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::generic::stash::Stash;
+
+/// Extension trait for `Stream`.
+pub trait Chain<G: Scope, D: Data> {
+    /// Produces the records of `self` at each time, followed by those of `other` at the time.
+    ///
+    /// The records of `self` pass through as they arrive. The records of `other` at a time are held
+    /// until the frontier of `self` has passed the time, when no further records of `self` at the
+    /// time can arrive, and are then produced, as are those that arrive later. At each time, the
+    /// operator's output thus holds all of the records of `self` before any of `other`, unlike
+    /// [`Concat::concat`](crate::dataflow::operators::Concat::concat), which interleaves them as they
+    /// arrive. This suits streams of ordered sections, for example headers then bodies.
+    ///
+    /// The order is that of the records each worker produces, as the inputs are not exchanged, and
+    /// holds downstream only through channels that preserve it, for example those of operators with
+    /// a `Pipeline` contract. Memory is bounded by the records of `other` at times not yet passed by
+    /// the frontier of `self`, and so a `self` that falls behind holds all records of `other` since.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Chain, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     let body = vec!["body"].into_iter().to_stream(scope);
+    ///     vec!["header"].into_iter()
+    ///                   .to_stream(scope)
+    ///                   .chain(&body)
+    ///                   .inspect(|section| println!("{}", section));
+    /// });
+    /// ```
+    fn chain(&self, other: &Stream<G, D>) -> Stream<G, D>;
+}
+
+impl<G: Scope, D: Data> Chain<G, D> for Stream<G, D> {
+    fn chain(&self, other: &Stream<G, D>) -> Stream<G, D> {
+        self.binary_frontier(other, Pipeline, Pipeline, "Chain", move |_capability, _info| {
+            // The records of `other` at times not yet passed by the frontier of `self`.
+            let mut held = Stash::<Capability<G::Timestamp>, Vec<D>>::new();
+            move |(input1, frontier1), (input2, _frontier2), output| {
+                input1.for_each_time(|time, data| {
+                    let mut session = output.session(&time);
+                    for container in data { session.give_container(container); }
+                });
+                held.release(|time| !frontier1.less_equal(time), |capability, mut records| {
+                    output.session(&capability).give_container(&mut records);
+                });
+                input2.for_each_time(|time, data| {
+                    if frontier1.less_equal(time.time()) {
+                        let records = held.get_or_retain(&time, Vec::new);
+                        for container in data { records.append(container); }
+                    }
+                    else {
+                        let mut session = output.session(&time);
+                        for container in data { session.give_container(container); }
+                    }
+                });
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dataflow::operators::{Capture, Chain, Input, Probe};
+    use crate::dataflow::operators::capture::Event;
+
+    #[test]
+    fn records_of_other_follow_those_of_self() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut first, mut second, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (first, first_stream) = scope.new_input::<&'static str>();
+                let (second, second_stream) = scope.new_input::<&'static str>();
+                let chained = first_stream.chain(&second_stream);
+                (first, second, chained.probe(), chained.capture())
+            });
+            // The second input runs ahead, and its records wait for the first to pass their times.
+            second.send("body 0");
+            second.advance_to(1);
+            second.send("body 1");
+            second.close();
+            for _ in 0..3 { worker.step(); }
+            first.send("header 0");
+            worker.step();
+            first.send("trailer 0");
+            first.advance_to(1);
+            first.send("header 1");
+            first.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        // The output records of each time, in the order in which they were produced.
+        let mut sections = std::collections::BTreeMap::<u64, Vec<_>>::new();
+        for event in captured.try_iter() {
+            if let Event::Messages(time, mut data) = event {
+                sections.entry(time).or_default().append(&mut data);
+            }
+        }
+        let sections = sections.into_iter().collect::<Vec<_>>();
+        assert_eq!(sections, vec![
+            (0, vec!["header 0", "trailer 0", "body 0"]),
+            (1, vec!["header 1", "body 1"]),
+        ]);
+    }
+}
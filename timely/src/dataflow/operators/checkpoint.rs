@@ -0,0 +1,153 @@
+//! Barriers that trigger operator snapshots as they pass through a dataflow.
+//!
+//! A barrier is a timestamp injected through [`Barriers::inject`]. It passes an operator once the
+//! operator's input frontier is no longer less or equal to the barrier, at which point every record
+//! at times less or equal to the barrier has reached the operator, and none at later times can be
+//! affected by those still in flight. Operators attached with [`Checkpoint::checkpoint`] invoke a
+//! callback as each barrier passes them, which is where state can be snapshotted, and the handle
+//! reports which barriers have passed all of its operators.
+
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use crate::Container;
+use crate::dataflow::{Scope, StreamCore};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::Operator;
+use crate::scheduling::Activator;
+
+/// A shared handle through which barriers are injected and their completion observed.
+///
+/// Barriers are local to a worker: each worker injects its own barriers, and observes their
+/// passage through its own operators.
+#[derive(Debug)]
+pub struct Barriers<T> {
+    state: Rc<RefCell<BarrierState<T>>>,
+}
+
+#[derive(Debug)]
+struct BarrierState<T> {
+    /// Injected barriers, and for each the number of operators it has passed.
+    injected: Vec<(T, usize)>,
+    /// Activators for the attached operators, so that they observe newly injected barriers.
+    operators: Vec<Activator>,
+}
+
+impl<T: Clone> Barriers<T> {
+    /// Creates a handle with no barriers and no attached operators.
+    pub fn new() -> Self {
+        Barriers { state: Rc::new(RefCell::new(BarrierState { injected: Vec::new(), operators: Vec::new() })) }
+    }
+
+    /// Injects a barrier at `time`.
+    ///
+    /// The barrier should be injected before the inputs of the dataflow advance past `time`. A barrier
+    /// injected later passes each attached operator at its next invocation, but the operators may then
+    /// have received records at later times.
+    pub fn inject(&self, time: T) {
+        let mut state = self.state.borrow_mut();
+        state.injected.push((time, 0));
+        for activator in state.operators.iter() {
+            activator.activate();
+        }
+    }
+
+    /// The injected barriers that have passed all attached operators, in the order they were injected.
+    pub fn completed(&self) -> Vec<T> {
+        let state = self.state.borrow();
+        state.injected
+            .iter()
+            .filter(|(_, passed)| *passed == state.operators.len())
+            .map(|(time, _)| time.clone())
+            .collect()
+    }
+}
+
+impl<T: Clone> Default for Barriers<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for Barriers<T> {
+    fn clone(&self) -> Self {
+        Barriers { state: Rc::clone(&self.state) }
+    }
+}
+
+/// Extension trait for `StreamCore`.
+pub trait Checkpoint<G: Scope, C: Container> {
+    /// Passes the stream through unchanged, and invokes `callback` with each barrier of `barriers` as it passes.
+    ///
+    /// The callback for a barrier is invoked once, in the first invocation of the operator after its
+    /// input frontier has passed the barrier, and after the records received in that invocation have
+    /// been passed on. Operators in a worker are scheduled in the order they were constructed, which
+    /// for dataflows without cycles is a topological order, and so when a barrier passes several
+    /// checkpoint operators at once their callbacks are invoked upstream first.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    /// use timely::dataflow::operators::{Input, Map, Probe};
+    /// use timely::dataflow::operators::checkpoint::{Barriers, Checkpoint};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     let barriers = Barriers::new();
+    ///     let snapshots = Rc::new(RefCell::new(Vec::new()));
+    ///     let (snapshots1, snapshots2) = (Rc::clone(&snapshots), Rc::clone(&snapshots));
+    ///     let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+    ///         let (input, stream) = scope.new_input::<u64>();
+    ///         let probe = stream
+    ///             .checkpoint(&barriers, move |time| snapshots1.borrow_mut().push(("source", *time)))
+    ///             .map(|x| x + 1)
+    ///             .checkpoint(&barriers, move |time| snapshots2.borrow_mut().push(("mapped", *time)))
+    ///             .probe();
+    ///         (input, probe)
+    ///     });
+    ///
+    ///     barriers.inject(3);
+    ///     for round in 0..5 {
+    ///         input.send(round);
+    ///         input.advance_to(round + 1);
+    ///         worker.step_while(|| probe.less_than(input.time()));
+    ///     }
+    ///     assert_eq!(barriers.completed(), vec![3]);
+    ///     assert_eq!(*snapshots.borrow(), vec![("source", 3), ("mapped", 3)]);
+    /// });
+    /// ```
+    fn checkpoint<F>(&self, barriers: &Barriers<G::Timestamp>, callback: F) -> StreamCore<G, C>
+    where
+        F: FnMut(&G::Timestamp)+'static;
+}
+
+impl<G: Scope, C: Container> Checkpoint<G, C> for StreamCore<G, C> {
+    fn checkpoint<F>(&self, barriers: &Barriers<G::Timestamp>, mut callback: F) -> StreamCore<G, C>
+    where
+        F: FnMut(&G::Timestamp)+'static,
+    {
+        let state = Rc::clone(&barriers.state);
+        self.unary_frontier(Pipeline, "Checkpoint", move |_capability, info| {
+            state.borrow_mut().operators.push(self.scope().activator_for(info.address));
+            // Indices of injected barriers that have not yet passed this operator.
+            let mut pending = Vec::new();
+            let mut observed = 0;
+            move |(input, frontier), output| {
+                input.for_each_time(|time, data| {
+                    output.session(&time).give_containers(data);
+                });
+
+                let mut state = state.borrow_mut();
+                pending.extend(observed .. state.injected.len());
+                observed = state.injected.len();
+                pending.retain(|index: &usize| {
+                    let (barrier, passed) = &mut state.injected[*index];
+                    if frontier.less_equal(barrier) { return true; }
+                    callback(barrier);
+                    *passed += 1;
+                    false
+                });
+            }
+        })
+    }
+}
@@ -0,0 +1,96 @@
+//! Extension methods for `Stream` that summarize the records of each time in an order-independent checksum.
+
+use std::hash::Hash;
+
+use crate::ExchangeData;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::operators::sketch::{hash_of, summarize_by_key};
+
+/// Extension trait for `Stream`.
+pub trait Checksum<G: Scope, D: ExchangeData> {
+    /// Produces, for each time with records, a checksum of the records at that time across all workers.
+    ///
+    /// The checksum is the wrapping sum of a hash of each record, which is the same on all workers,
+    /// and so does not depend on the order in which records arrive nor on the worker that holds them.
+    /// Unlike the exclusive or of the hashes, the sum does not cancel out records present an even
+    /// number of times. Each worker sums the hashes of its records at a time, and once the input
+    /// frontier has passed the time sends the partial sum to worker zero, which produces the total
+    /// once all partial sums have arrived.
+    ///
+    /// Checksums of a stream before and after an exchange, or any other operator that should only
+    /// move records between workers, are equal at each time unless records were lost, duplicated,
+    /// or corrupted, which makes them useful to validate channels and serialization end to end.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use timely::dataflow::operators::{ToStream, Checksum, Exchange, Inspect};
+    ///
+    /// let checksums = Arc::new(Mutex::new(Vec::new()));
+    /// let before = Arc::clone(&checksums);
+    /// let after = Arc::clone(&checksums);
+    /// timely::example(move |scope| {
+    ///     let stream = (0..100u64).to_stream(scope);
+    ///     stream.checksum().inspect(move |x| before.lock().unwrap().push(*x));
+    ///     stream.exchange(|x| *x).checksum().inspect(move |x| after.lock().unwrap().push(*x));
+    /// });
+    /// let checksums = checksums.lock().unwrap();
+    /// assert_eq!(checksums[0], checksums[1]);
+    /// ```
+    fn checksum(&self) -> Stream<G, u64>;
+}
+
+impl<G: Scope<Timestamp: Hash>, D: ExchangeData+Hash> Checksum<G, D> for Stream<G, D> {
+    fn checksum(&self) -> Stream<G, u64> {
+        summarize_by_key(self, "Checksum", |_| 0,
+            |sums, datum| {
+                let sum: &mut u64 = sums.entry(()).or_default();
+                *sum = sum.wrapping_add(hash_of(&datum));
+            },
+            |sum, other| *sum = sum.wrapping_add(other),
+            |(), total| total,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use crate::dataflow::operators::{Input, Inspect, Map, Probe};
+    use super::Checksum;
+
+    #[test]
+    fn checksums_are_order_independent_and_detect_changes() {
+        crate::execute_directly(|worker| {
+            let checksums = Rc::new(RefCell::new(Vec::new()));
+            let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, records) = scope.new_input::<u64>();
+                let probe = crate::dataflow::ProbeHandle::new();
+                for (index, stream) in [records.clone(), records.map(|x| x + (x == 7) as u64)].into_iter().enumerate() {
+                    let checksums = Rc::clone(&checksums);
+                    stream.checksum()
+                          .inspect_time(move |time, x| checksums.borrow_mut().push((*time, index, *x)))
+                          .probe_with(&probe);
+                }
+                (input, probe)
+            });
+
+            // The same records in two orders, each alongside their copy with one record changed.
+            for round in 0..2 {
+                let records: Vec<u64> = if round == 0 { (0..10).collect() } else { (0..10).rev().collect() };
+                for record in records { input.send(record); }
+                input.advance_to(round + 1);
+                worker.step_while(|| probe.less_than(input.time()));
+            }
+
+            let checksums = checksums.borrow();
+            let unchanged = checksums.iter().filter(|(_, index, _)| *index == 0).map(|(time, _, x)| (*time, *x)).collect::<Vec<_>>();
+            let changed = checksums.iter().filter(|(_, index, _)| *index == 1).map(|(time, _, x)| (*time, *x)).collect::<Vec<_>>();
+            assert_eq!(unchanged.len(), 2);
+            assert_eq!(unchanged[0].1, unchanged[1].1);
+            assert!(unchanged.iter().zip(changed.iter()).all(|(x, y)| x.0 == y.0 && x.1 != y.1));
+        });
+    }
+}
@@ -0,0 +1,156 @@
+//! Extension methods for `Stream` that compute the connected components of graphs of edges.
+
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::hash::Hash;
+
+use crate::ExchangeData;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Exchange;
+use crate::dataflow::operators::{Concat, ConnectLoop, Enter, Leave, LoopVariable, Map};
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::sketch::{hash_of, summarize_by_key};
+use crate::order::PartialOrder;
+
+/// Extension trait for `Stream`.
+pub trait ConnectedComponents<G: Scope, N: ExchangeData> {
+    /// Labels each node of the graph of the edges of each time with the least node of its connected component.
+    ///
+    /// The edges `(a, b)` of each time form an undirected graph, and once the input frontier has
+    /// passed the time the operator produces `(node, component)` once for each node with an edge,
+    /// where `component` is the least node connected to it. The graphs of different times are
+    /// independent: a time's components are those of its own edges only.
+    ///
+    /// Components are found by label propagation in an iterative scope. Each node starts labeled by
+    /// itself, and proposes its label to its neighbours, which adopt and propose in turn each label
+    /// less than their own, until no label improves and the iteration drains. Nodes and their edges
+    /// are exchanged by node, and each worker holds the adjacency lists and labels of its nodes for
+    /// each time until the iteration for the time completes. The number of rounds is bounded by the
+    /// diameter of the graph, and each round proposes each improved label to each neighbour.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, ConnectedComponents, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     // Two components, a path 0 - 1 - 2 and a single edge 3 - 4.
+    ///     vec![(0u64, 1), (1, 2), (3, 4)]
+    ///         .to_stream(scope)
+    ///         .connected_components()
+    ///         .inspect(|(node, component)| println!("{} is in component {}", node, component));
+    /// });
+    /// ```
+    fn connected_components(&self) -> Stream<G, (N, N)>;
+}
+
+impl<G: Scope<Timestamp: Hash>, N: ExchangeData+Hash+Ord> ConnectedComponents<G, N> for Stream<G, (N, N)> {
+    fn connected_components(&self) -> Stream<G, (N, N)> {
+        let proposals = self.scope().iterative::<u64, _, _>(|inner| {
+            let edges = self.enter(inner).flat_map(|(a, b)| [(a.clone(), b.clone()), (b, a)]);
+            let (handle, cycle) = inner.loop_variable(1);
+            // Proposals `(node, label)` of labels for nodes, starting with each node's own.
+            let proposals = edges.map(|(node, _)| (node.clone(), node)).concat(&cycle);
+            proposals
+                .binary_frontier(&edges, Exchange::new(|(node, _)| hash_of(node)), Exchange::new(|(node, _)| hash_of(node)), "ConnectedComponents", |_capability, _info| {
+                    // For each incomplete outer time, the neighbours and least label of each node.
+                    let mut graphs = HashMap::<G::Timestamp, (HashMap<N, Vec<N>>, HashMap<N, N>)>::new();
+                    move |(input1, frontier1), (input2, frontier2), output| {
+                        input2.for_each_time(|time, data| {
+                            let (neighbours, labels) = graphs.entry(time.time().outer.clone()).or_default();
+                            let mut session = output.session(&time);
+                            for (node, neighbour) in data.flat_map(|d| d.drain(..)) {
+                                if let Some(label) = labels.get(&node) {
+                                    session.give((neighbour.clone(), label.clone()));
+                                }
+                                neighbours.entry(node).or_default().push(neighbour);
+                            }
+                        });
+                        input1.for_each_time(|time, data| {
+                            let (neighbours, labels) = graphs.entry(time.time().outer.clone()).or_default();
+                            let mut session = output.session(&time);
+                            for (node, label) in data.flat_map(|d| d.drain(..)) {
+                                let improved = match labels.entry(node.clone()) {
+                                    Entry::Occupied(mut entry) if label < *entry.get() => { entry.insert(label.clone()); true },
+                                    Entry::Occupied(_) => false,
+                                    Entry::Vacant(entry) => { entry.insert(label.clone()); true },
+                                };
+                                if improved {
+                                    let proposed = neighbours.get(&node).into_iter().flatten();
+                                    session.give_iterator(proposed.map(|neighbour| (neighbour.clone(), label.clone())));
+                                }
+                            }
+                        });
+                        // Release the graphs of outer times the iteration has completed.
+                        graphs.retain(|time, _| {
+                            frontier1.frontier().iter().chain(frontier2.frontier().iter()).any(|frontier_time| frontier_time.outer.less_equal(time))
+                        });
+                    }
+                })
+                .connect_loop(handle);
+            proposals.leave()
+        });
+
+        // The label of each node is the least label proposed for it.
+        summarize_by_key(&proposals, "ConnectedComponentsLabels", hash_of,
+            |labels, (node, label)| match labels.entry(node) {
+                Entry::Occupied(mut entry) => if label < *entry.get() { entry.insert(label); },
+                Entry::Vacant(entry) => { entry.insert(label); },
+            },
+            |label, other| if other < *label { *label = other; },
+            |node, label| (node, label),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::Config;
+    use crate::dataflow::operators::{Capture, ConnectedComponents, Input, Probe, ToStream};
+    use crate::dataflow::operators::capture::Extract;
+
+    #[test]
+    fn components_are_labeled_by_their_least_node() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<(u64, u64)>();
+                let components = stream.connected_components();
+                (input, components.probe(), components.capture())
+            });
+            // A path 5 - 3 - 4 - 1, a cycle 2 - 6 - 7, and a self-loop at 8.
+            for edge in [(5, 3), (4, 3), (4, 1), (2, 6), (7, 6), (2, 7), (8, 8)] { input.send(edge); }
+            input.advance_to(1);
+            // The graph of the next time joins the path and cycle, and is independent of the first.
+            for edge in [(5, 3), (4, 3), (4, 1), (2, 6), (6, 5)] { input.send(edge); }
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        assert_eq!(captured.extract(), vec![
+            (0, vec![(1, 1), (2, 2), (3, 1), (4, 1), (5, 1), (6, 2), (7, 2), (8, 8)]),
+            (1, vec![(1, 1), (2, 1), (3, 1), (4, 1), (5, 1), (6, 1)]),
+        ]);
+    }
+
+    #[test]
+    fn components_span_workers() {
+        let (send, recv) = std::sync::mpsc::channel();
+        let send = Arc::new(Mutex::new(send));
+        crate::execute(Config::process(3), move |worker| {
+            let send = send.lock().unwrap().clone();
+            let index = worker.index() as u64;
+            worker.dataflow::<u64,_,_>(|scope| {
+                // Each worker holds a third of the edges of a path 0 - 1 - ... - 29, and of a path 30 - ... - 59.
+                (0..29u64).chain(30..59).filter(move |node| node % 3 == index).map(|node| (node + 1, node))
+                    .to_stream(scope)
+                    .connected_components()
+                    .capture_into(send);
+            });
+        }).unwrap();
+
+        let expected = (0..60u64).map(|node| (node, if node < 30 { 0 } else { 30 })).collect::<Vec<_>>();
+        assert_eq!(recv.extract(), vec![(0, expected)]);
+    }
+}
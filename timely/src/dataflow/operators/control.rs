@@ -0,0 +1,208 @@
+//! Extension methods for `Stream` that update an operator's parameters from a broadcast control stream.
+
+use std::collections::BTreeMap;
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::{Broadcast, Capability};
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::generic::stash::Stash;
+use crate::order::TotalOrder;
+
+/// Extension trait for `Stream`.
+pub trait WithControl<G: Scope, D: Data> {
+    /// Processes each record with `logic` and the operator's state, which `apply` updates with the parameter updates of `control`.
+    ///
+    /// The state starts as `state`, and `logic(&mut state, record)` produces the outputs of each
+    /// record, as with [`Map::flat_map`](crate::dataflow::operators::Map::flat_map). The updates of
+    /// `control` are broadcast, and each worker applies them to its own copy of the state with
+    /// `apply(&mut state, update)`, for example to change a rate limit or a threshold at runtime
+    /// without restarting the dataflow.
+    ///
+    /// Updates take effect for the records at times after their own: a record at time `t` is
+    /// processed with exactly the updates at times strictly less than `t`, however the records of the
+    /// two streams interleave as they arrive, and so an update sent at the same time as some records
+    /// applies from the next time on. Several updates at the same time are applied in the order they
+    /// are received. To this end a record at `t` is held until the frontier of `control` has reached
+    /// `t`, and an update at `s` is applied once the frontier of `control` has passed `s` and that of
+    /// `self` has passed it too, when no record of time `s` or earlier can arrive. Records are
+    /// produced at their own times, and are not exchanged.
+    ///
+    /// Records and updates are held while they wait for the frontiers: a control stream that falls
+    /// behind delays all records, and so should advance with `self` even when it has no updates.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{Input, Inspect, Probe, WithControl};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     let (mut records, mut thresholds, probe) = worker.dataflow::<u64,_,_>(|scope| {
+    ///         let (records, record_stream) = scope.new_input::<u64>();
+    ///         let (thresholds, threshold_stream) = scope.new_input::<u64>();
+    ///         let probe = record_stream
+    ///             .with_control(&threshold_stream, 10, |threshold, update| *threshold = update, |threshold, x| (x >= *threshold).then_some(x))
+    ///             .inspect(|x| println!("passed: {:?}", x))
+    ///             .probe();
+    ///         (records, thresholds, probe)
+    ///     });
+    ///
+    ///     // The threshold set at time 0 applies to the records at time 1, but not to those at time 0.
+    ///     records.send(5);
+    ///     thresholds.send(3);
+    ///     records.advance_to(1);
+    ///     thresholds.advance_to(1);
+    ///     records.send(5);
+    ///     records.close();
+    ///     thresholds.close();
+    ///     worker.step_while(|| !probe.done());
+    /// });
+    /// ```
+    fn with_control<C, S, R, I, A, L>(&self, control: &Stream<G, C>, state: S, apply: A, logic: L) -> Stream<G, R>
+    where
+        G::Timestamp: TotalOrder,
+        C: ExchangeData,
+        S: 'static,
+        R: Data,
+        I: IntoIterator<Item=R>,
+        A: FnMut(&mut S, C)+'static,
+        L: FnMut(&mut S, D)->I+'static;
+}
+
+impl<G: Scope, D: Data> WithControl<G, D> for Stream<G, D> {
+    fn with_control<C, S, R, I, A, L>(&self, control: &Stream<G, C>, mut state: S, mut apply: A, mut logic: L) -> Stream<G, R>
+    where
+        G::Timestamp: TotalOrder,
+        C: ExchangeData,
+        S: 'static,
+        R: Data,
+        I: IntoIterator<Item=R>,
+        A: FnMut(&mut S, C)+'static,
+        L: FnMut(&mut S, D)->I+'static,
+    {
+        self.binary_frontier(&control.broadcast(), Pipeline, Pipeline, "WithControl", move |_capability, _info| {
+            // Updates not yet applied, by time.
+            let mut updates = BTreeMap::<G::Timestamp, Vec<C>>::new();
+            // Records held until the state reflects the updates of earlier times.
+            let mut held = Stash::<Capability<G::Timestamp>, Vec<D>>::new();
+            move |(input1, frontier1), (input2, frontier2), output| {
+                input2.for_each_time(|time, data| {
+                    let pending = updates.entry(time.time().clone()).or_default();
+                    for container in data { pending.append(container); }
+                });
+                input1.for_each_time(|time, data| {
+                    let records = held.get_or_retain(&time, Vec::new);
+                    for container in data { records.append(container); }
+                });
+
+                // Produce records and apply updates in order of time, the records of a time before its updates.
+                loop {
+                    let update = updates.keys().next().cloned();
+                    let record = held.iter_mut().map(|(capability, _)| capability.time().clone()).min();
+                    match (record, update) {
+                        (Some(time), update) if update.as_ref().is_none_or(|update| time <= *update) => {
+                            if frontier2.less_than(&time) { break; }
+                            held.release(|held_time| *held_time == time, |capability, records| {
+                                let mut session = output.session(&capability);
+                                for datum in records {
+                                    session.give_iterator(logic(&mut state, datum).into_iter());
+                                }
+                            });
+                        }
+                        (_, Some(time)) => {
+                            if frontier2.less_equal(&time) || frontier1.less_equal(&time) { break; }
+                            for parameters in updates.remove(&time).expect("update present") {
+                                apply(&mut state, parameters);
+                            }
+                        }
+                        _ => break,
+                    }
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::Config;
+    use crate::dataflow::operators::{Capture, Input, Probe, WithControl};
+    use crate::dataflow::operators::capture::Extract;
+
+    #[test]
+    fn updates_apply_to_records_at_later_times() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut records, mut control, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (records, record_stream) = scope.new_input::<u64>();
+                let (control, control_stream) = scope.new_input::<u64>();
+                let scaled = record_stream.with_control(&control_stream, 1, |factor, update| *factor = update, |factor, x| Some(x * *factor));
+                (records, control, scaled.probe(), scaled.capture())
+            });
+            // The control stream runs ahead of the records: its updates at times 1 and 3 arrive first.
+            control.advance_to(1);
+            control.send(10);
+            control.advance_to(3);
+            control.send(100);
+            control.close();
+            for _ in 0..3 { worker.step(); }
+            for time in 0..5 {
+                records.advance_to(time);
+                records.send(time);
+                worker.step_while(|| probe.less_than(records.time()));
+            }
+            records.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        assert_eq!(captured.extract(), vec![(0, vec![0]), (1, vec![1]), (2, vec![20]), (3, vec![30]), (4, vec![400])]);
+    }
+
+    #[test]
+    fn records_wait_for_the_control_stream() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut records, mut control, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (records, record_stream) = scope.new_input::<u64>();
+                let (control, control_stream) = scope.new_input::<u64>();
+                let passed = record_stream.with_control(&control_stream, 0, |threshold, update| *threshold = update, |threshold, x| (x >= *threshold).then_some(x));
+                (records, control, passed.probe(), passed.capture())
+            });
+            // Records run ahead of the control stream, and are held until it reaches their time.
+            records.advance_to(2);
+            records.send(1);
+            records.send(5);
+            records.close();
+            for _ in 0..3 { worker.step(); }
+            assert!(captured.try_iter().all(|event| matches!(event, crate::dataflow::operators::capture::Event::Progress(_))));
+            control.send(3);
+            control.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        assert_eq!(captured.extract(), vec![(2, vec![5])]);
+    }
+
+    #[test]
+    fn each_worker_applies_all_updates() {
+        let (send, recv) = std::sync::mpsc::channel();
+        let send = Arc::new(Mutex::new(send));
+        crate::execute(Config::process(3), move |worker| {
+            let send = send.lock().unwrap().clone();
+            let index = worker.index() as u64;
+            worker.dataflow::<u64,_,_>(|scope| {
+                let (mut records, record_stream) = scope.new_input::<u64>();
+                let (mut control, control_stream) = scope.new_input::<u64>();
+                record_stream.with_control(&control_stream, 0, |total, update| *total += update, |total, x| Some((x, *total))).capture_into(send);
+                // Each worker sends one update, and has one record at a later time.
+                control.send(index + 1);
+                records.advance_to(1);
+                records.send(index);
+            });
+        }).unwrap();
+
+        assert_eq!(recv.extract(), vec![(1, vec![(0, 6), (1, 6), (2, 6)])]);
+    }
+}
@@ -0,0 +1,164 @@
+//! Extension methods for `Stream` that count the pairs of items occurring together in groups.
+
+use std::collections::BTreeSet;
+use std::hash::Hash;
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::operators::map::Map;
+use crate::dataflow::operators::sketch::{hash_of, summarize_by_key};
+
+/// Extension trait for `Stream`.
+pub trait Cooccurrence<G: Scope, D: Data> {
+    /// Counts, for each pair of items, the groups of each time in which both occur.
+    ///
+    /// The `group_key_fn` and `item_fn` closures extract a group and an item from each record. The
+    /// items of each group and time form a set, and once the input frontier has passed the time the
+    /// operator produces `(a, b, count)` for each pair of distinct items `a < b` that occur together
+    /// in some group, where `count` is the number of groups of the time in which both occur. An item
+    /// that occurs several times in a group counts as one.
+    ///
+    /// Records are summarized into the set of items of each group on each worker, and the sets are
+    /// exchanged by group and merged. The pairs of each group are then counted on each worker, and
+    /// the counts exchanged by pair and summed.
+    ///
+    /// A group of `n` items has `n (n - 1) / 2` pairs, all of which are enumerated and counted: the
+    /// work and memory grow quadratically with the size of the largest groups, and a single large
+    /// group can dominate the cost of the whole time. Memory is proportional to the distinct items
+    /// of each group and the distinct pairs of each time. [`Cooccurrence::cooccurrence_capped`]
+    /// bounds the number of items considered per group.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Cooccurrence, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     // Two baskets, of which only the first holds both bread and butter.
+    ///     vec![(0, "bread"), (0, "butter"), (0, "milk"), (1, "bread"), (1, "milk")]
+    ///         .to_stream(scope)
+    ///         .cooccurrence(|(basket, _)| *basket, |(_, item)| item.to_string())
+    ///         .inspect(|(a, b, count)| println!("{} and {}: {}", a, b, count));
+    /// });
+    /// ```
+    fn cooccurrence<K, I, KF, IF>(&self, group_key_fn: KF, item_fn: IF) -> Stream<G, (I, I, u64)>
+    where
+        K: ExchangeData+Hash+Eq,
+        I: ExchangeData+Hash+Ord,
+        KF: FnMut(&D)->K+'static,
+        IF: FnMut(&D)->I+'static,
+    {
+        self.cooccurrence_capped(group_key_fn, item_fn, usize::MAX)
+    }
+
+    /// Counts pairs of items as [`Cooccurrence::cooccurrence`] does, considering at most `max_items` items of each group.
+    ///
+    /// A group with more than `max_items` distinct items at a time contributes only the pairs of its
+    /// `max_items` least items, and so at most `max_items (max_items - 1) / 2` pairs. The counts of
+    /// pairs involving the other items are undercounted, but which items are considered depends
+    /// only on the items, and not on the order in which records arrive.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Cooccurrence, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     // Only the pairs among items 0, 1, and 2 of the single group are counted.
+    ///     (0..100u64).to_stream(scope)
+    ///                .cooccurrence_capped(|_| (), |x| *x, 3)
+    ///                .inspect(|(a, b, _count)| assert!(*a < 3 && *b < 3));
+    /// });
+    /// ```
+    fn cooccurrence_capped<K, I, KF, IF>(&self, group_key_fn: KF, item_fn: IF, max_items: usize) -> Stream<G, (I, I, u64)>
+    where
+        K: ExchangeData+Hash+Eq,
+        I: ExchangeData+Hash+Ord,
+        KF: FnMut(&D)->K+'static,
+        IF: FnMut(&D)->I+'static;
+}
+
+impl<G: Scope<Timestamp: Hash>, D: Data> Cooccurrence<G, D> for Stream<G, D> {
+    fn cooccurrence_capped<K, I, KF, IF>(&self, mut group_key_fn: KF, mut item_fn: IF, max_items: usize) -> Stream<G, (I, I, u64)>
+    where
+        K: ExchangeData+Hash+Eq,
+        I: ExchangeData+Hash+Ord,
+        KF: FnMut(&D)->K+'static,
+        IF: FnMut(&D)->I+'static,
+    {
+        let groups = summarize_by_key(self, "CooccurrenceGroups", hash_of,
+            move |groups, datum| {
+                groups.entry(group_key_fn(&datum)).or_insert_with(BTreeSet::new).insert(item_fn(&datum));
+            },
+            |items, other| items.extend(other),
+            move |_group, items| items.into_iter().take(max_items).collect::<Vec<_>>(),
+        );
+
+        let pairs = groups.flat_map(|items| {
+            let mut pairs = Vec::with_capacity(items.len() * items.len().saturating_sub(1) / 2);
+            for (index, a) in items.iter().enumerate() {
+                pairs.extend(items[index + 1 ..].iter().map(|b| (a.clone(), b.clone())));
+            }
+            pairs
+        });
+
+        summarize_by_key(&pairs, "CooccurrencePairs", hash_of,
+            |counts, pair| *counts.entry(pair).or_insert(0) += 1,
+            |count, other| *count += other,
+            |(a, b), count| (a, b, count),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::Config;
+    use crate::dataflow::operators::{Capture, Cooccurrence, Input, Probe, ToStream};
+    use crate::dataflow::operators::capture::Extract;
+
+    #[test]
+    fn pairs_are_counted_per_group_and_time() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<(u64, char)>();
+                let pairs = stream.cooccurrence(|(group, _)| *group, |(_, item)| *item);
+                (input, pairs.probe(), pairs.capture())
+            });
+            // Group 0 holds a, b, c, with b twice, and group 1 holds a, b.
+            for record in [(0, 'a'), (0, 'b'), (1, 'b'), (0, 'c'), (1, 'a'), (0, 'b')] { input.send(record); }
+            input.advance_to(1);
+            // At the next time, group 0 holds c and a, and group 2 holds a single item.
+            for record in [(0, 'c'), (0, 'a'), (2, 'z')] { input.send(record); }
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        assert_eq!(captured.extract(), vec![
+            (0, vec![('a', 'b', 2), ('a', 'c', 1), ('b', 'c', 1)]),
+            (1, vec![('a', 'c', 1)]),
+        ]);
+    }
+
+    #[test]
+    fn groups_spread_across_workers_are_merged() {
+        let (send, recv) = std::sync::mpsc::channel();
+        let send = Arc::new(Mutex::new(send));
+        let (send_capped, recv_capped) = std::sync::mpsc::channel();
+        let send_capped = Arc::new(Mutex::new(send_capped));
+        crate::execute(Config::process(3), move |worker| {
+            let send = send.lock().unwrap().clone();
+            let send_capped = send_capped.lock().unwrap().clone();
+            let index = worker.index() as u64;
+            worker.dataflow::<u64,_,_>(move |scope| {
+                // Each worker holds its own item in each of the groups 0 and 1.
+                let records = vec![(0, index), (1, index)].to_stream(scope);
+                records.cooccurrence(|(group, _)| *group, |(_, item)| *item).capture_into(send);
+                records.cooccurrence_capped(|(group, _)| *group, |(_, item)| *item, 2).capture_into(send_capped);
+            });
+        }).unwrap();
+
+        assert_eq!(recv.extract(), vec![(0, vec![(0, 1, 2), (0, 2, 2), (1, 2, 2)])]);
+        assert_eq!(recv_capped.extract(), vec![(0, vec![(0, 1, 2)])]);
+    }
+}
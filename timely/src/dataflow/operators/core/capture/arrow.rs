@@ -0,0 +1,403 @@
+//! Capture of streams into Arrow IPC files, one for each time.
+//!
+//! The `ArrowFileWriter` is an `EventPusher` that accumulates the records of each time into the
+//! columns of an Arrow record batch, and once the captured frontier has passed the time writes the
+//! batch to a file in the Arrow IPC file format with the `arrow` crate's `FileWriter`, for tools of
+//! the Arrow ecosystem to read. Records describe their columns through the `ArrowRecord` trait.
+//!
+//! Only the types of column in `DataType` are supported, without nulls.
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, BooleanArray, Float64Array, Int64Array, RecordBatch, StringArray, UInt64Array};
+use arrow::datatypes::Schema;
+use arrow::error::ArrowError;
+use arrow::ipc::writer::FileWriter;
+
+use crate::progress::Timestamp;
+use crate::progress::frontier::MutableAntichain;
+use crate::dataflow::operators::generic::stash::Stash;
+use super::{Event, EventPusher};
+
+/// The type of the values of a column.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DataType {
+    /// Signed 64-bit integers.
+    Int64,
+    /// Unsigned 64-bit integers.
+    UInt64,
+    /// 64-bit floating point numbers.
+    Float64,
+    /// Booleans.
+    Boolean,
+    /// UTF-8 strings.
+    Utf8,
+}
+
+impl DataType {
+    /// The corresponding Arrow data type.
+    fn to_arrow(self) -> arrow::datatypes::DataType {
+        match self {
+            DataType::Int64 => arrow::datatypes::DataType::Int64,
+            DataType::UInt64 => arrow::datatypes::DataType::UInt64,
+            DataType::Float64 => arrow::datatypes::DataType::Float64,
+            DataType::Boolean => arrow::datatypes::DataType::Boolean,
+            DataType::Utf8 => arrow::datatypes::DataType::Utf8,
+        }
+    }
+}
+
+/// A named column of a schema.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Field {
+    /// The name of the column.
+    pub name: String,
+    /// The type of the values of the column.
+    pub data_type: DataType,
+}
+
+impl Field {
+    /// A column named `name` with values of type `data_type`.
+    pub fn new(name: impl Into<String>, data_type: DataType) -> Self {
+        Field { name: name.into(), data_type }
+    }
+}
+
+/// The values of a column for the records of a batch.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Column {
+    /// Signed 64-bit integers.
+    Int64(Vec<i64>),
+    /// Unsigned 64-bit integers.
+    UInt64(Vec<u64>),
+    /// 64-bit floating point numbers.
+    Float64(Vec<f64>),
+    /// Booleans.
+    Boolean(Vec<bool>),
+    /// UTF-8 strings.
+    Utf8(Vec<String>),
+}
+
+impl Column {
+    /// An empty column of values of type `data_type`.
+    pub fn new(data_type: DataType) -> Self {
+        match data_type {
+            DataType::Int64 => Column::Int64(Vec::new()),
+            DataType::UInt64 => Column::UInt64(Vec::new()),
+            DataType::Float64 => Column::Float64(Vec::new()),
+            DataType::Boolean => Column::Boolean(Vec::new()),
+            DataType::Utf8 => Column::Utf8(Vec::new()),
+        }
+    }
+    /// The type of the values of the column.
+    pub fn data_type(&self) -> DataType {
+        match self {
+            Column::Int64(_) => DataType::Int64,
+            Column::UInt64(_) => DataType::UInt64,
+            Column::Float64(_) => DataType::Float64,
+            Column::Boolean(_) => DataType::Boolean,
+            Column::Utf8(_) => DataType::Utf8,
+        }
+    }
+    /// The number of values in the column.
+    pub fn len(&self) -> usize {
+        match self {
+            Column::Int64(values) => values.len(),
+            Column::UInt64(values) => values.len(),
+            Column::Float64(values) => values.len(),
+            Column::Boolean(values) => values.len(),
+            Column::Utf8(values) => values.len(),
+        }
+    }
+    /// True if the column has no values.
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+    /// Appends a value to an `Int64` column.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the column is of another type, as do the other `push_` methods.
+    pub fn push_i64(&mut self, value: i64) {
+        match self { Column::Int64(values) => values.push(value), other => other.mismatch(DataType::Int64) }
+    }
+    /// Appends a value to a `UInt64` column.
+    pub fn push_u64(&mut self, value: u64) {
+        match self { Column::UInt64(values) => values.push(value), other => other.mismatch(DataType::UInt64) }
+    }
+    /// Appends a value to a `Float64` column.
+    pub fn push_f64(&mut self, value: f64) {
+        match self { Column::Float64(values) => values.push(value), other => other.mismatch(DataType::Float64) }
+    }
+    /// Appends a value to a `Boolean` column.
+    pub fn push_bool(&mut self, value: bool) {
+        match self { Column::Boolean(values) => values.push(value), other => other.mismatch(DataType::Boolean) }
+    }
+    /// Appends a value to a `Utf8` column.
+    pub fn push_str(&mut self, value: &str) {
+        match self { Column::Utf8(values) => values.push(value.to_owned()), other => other.mismatch(DataType::Utf8) }
+    }
+    fn mismatch(&self, pushed: DataType) {
+        panic!("ArrowFileWriter: pushed a value of {:?} to a column of {:?}", pushed, self.data_type());
+    }
+}
+
+/// A record that can be written as a row of an Arrow record batch.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::capture::arrow::{ArrowRecord, Column, DataType, Field};
+///
+/// struct Trade { symbol: String, price: f64 }
+///
+/// impl ArrowRecord for Trade {
+///     fn schema() -> Vec<Field> {
+///         vec![Field::new("symbol", DataType::Utf8), Field::new("price", DataType::Float64)]
+///     }
+///     fn append_to(&self, columns: &mut [Column]) {
+///         columns[0].push_str(&self.symbol);
+///         columns[1].push_f64(self.price);
+///     }
+/// }
+/// ```
+pub trait ArrowRecord {
+    /// The columns of the rows, the same for all records of the type.
+    fn schema() -> Vec<Field>;
+    /// Appends the values of the record to `columns`, one for each field of the schema, in order.
+    fn append_to(&self, columns: &mut [Column]);
+}
+
+macro_rules! implement_arrow_record {
+    ($type:ty, $data_type:ident, $push:ident) => {
+        /// A single column named `value`.
+        impl ArrowRecord for $type {
+            fn schema() -> Vec<Field> { vec![Field::new("value", DataType::$data_type)] }
+            fn append_to(&self, columns: &mut [Column]) { columns[0].$push(*self) }
+        }
+    }
+}
+
+implement_arrow_record!(i64, Int64, push_i64);
+implement_arrow_record!(u64, UInt64, push_u64);
+implement_arrow_record!(f64, Float64, push_f64);
+implement_arrow_record!(bool, Boolean, push_bool);
+
+/// A single column named `value`.
+impl ArrowRecord for String {
+    fn schema() -> Vec<Field> { vec![Field::new("value", DataType::Utf8)] }
+    fn append_to(&self, columns: &mut [Column]) { columns[0].push_str(self) }
+}
+
+/// An `EventPusher` writing the records of each time to an Arrow IPC file.
+///
+/// The records of each time are accumulated into one record batch, which is written, with the
+/// schema of `D`, to the file at `path(time)` once the captured frontier has passed the time,
+/// replacing any file there. Times without records produce no file. Each worker captures its own
+/// part of a stream, and so `path` should distinguish the files of different workers.
+///
+/// Errors writing files cause a panic, as `EventPusher` cannot report them.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::{Capture, ToStream};
+/// use timely::dataflow::operators::capture::ArrowFileWriter;
+///
+/// timely::execute(timely::Config::thread(), |worker| {
+///     let directory = std::env::temp_dir();
+///     let index = worker.index();
+///     worker.dataflow::<u64,_,_>(|scope| {
+///         (0..10u64).to_stream(scope)
+///                   .capture_into(ArrowFileWriter::new(move |time: &u64| directory.join(format!("epoch-{}-worker-{}.arrow", time, index))));
+///     });
+/// }).unwrap();
+/// ```
+pub struct ArrowFileWriter<T: Timestamp, D, P> {
+    path: P,
+    frontier: MutableAntichain<T>,
+    batches: Stash<T, Vec<Column>>,
+    phantom: std::marker::PhantomData<D>,
+}
+
+impl<T: Timestamp, D: ArrowRecord, P: FnMut(&T)->PathBuf> ArrowFileWriter<T, D, P> {
+    /// A writer of the records of each time `time` to the file at `path(time)`.
+    pub fn new(path: P) -> Self {
+        ArrowFileWriter {
+            path,
+            frontier: MutableAntichain::new_bottom(T::minimum()),
+            batches: Stash::new(),
+            phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T: Timestamp, D: ArrowRecord, P: FnMut(&T)->PathBuf> EventPusher<T, Vec<D>> for ArrowFileWriter<T, D, P> {
+    fn push(&mut self, event: Event<T, Vec<D>>) {
+        match event {
+            Event::Messages(time, data) => {
+                if data.is_empty() { return; }
+                let columns = self.batches.get_or_insert_with(&time, || time.clone(), || D::schema().into_iter().map(|field| Column::new(field.data_type)).collect());
+                for datum in data.iter() {
+                    datum.append_to(columns);
+                }
+            },
+            Event::Progress(updates) => {
+                self.frontier.update_iter(updates);
+                let frontier = &self.frontier;
+                let path = &mut self.path;
+                self.batches.release(|time| !frontier.less_equal(time), |time, columns| {
+                    let path = path(&time);
+                    write(&path, &D::schema(), columns)
+                        .unwrap_or_else(|error| panic!("ArrowFileWriter: writing {} failed: {}", path.display(), error));
+                });
+            },
+        }
+    }
+}
+
+/// Writes the schema `fields` and a record batch of `columns` to an Arrow IPC file at `path`.
+fn write(path: &Path, fields: &[Field], columns: Vec<Column>) -> Result<(), ArrowError> {
+    let schema = Arc::new(Schema::new(fields.iter().map(|field| arrow::datatypes::Field::new(&field.name, field.data_type.to_arrow(), false)).collect::<Vec<_>>()));
+    let arrays = columns.into_iter().map(|column| match column {
+        Column::Int64(values) => Arc::new(Int64Array::from(values)) as ArrayRef,
+        Column::UInt64(values) => Arc::new(UInt64Array::from(values)),
+        Column::Float64(values) => Arc::new(Float64Array::from(values)),
+        Column::Boolean(values) => Arc::new(BooleanArray::from(values)),
+        Column::Utf8(values) => Arc::new(StringArray::from(values)),
+    }).collect();
+    let batch = RecordBatch::try_new(Arc::clone(&schema), arrays)?;
+    let mut writer = FileWriter::try_new(BufWriter::new(File::create(path)?), &schema)?;
+    writer.write(&batch)?;
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::path::Path;
+
+    use arrow::array::AsArray;
+    use arrow::datatypes::{DataType as ArrowType, Float64Type, Int64Type, UInt64Type};
+    use arrow::ipc::reader::FileReader;
+
+    use crate::dataflow::operators::{Capture, Input, Probe};
+    use super::{ArrowFileWriter, ArrowRecord, Column, DataType, Field};
+
+    #[derive(Clone)]
+    struct Reading { sensor: String, value: f64, count: u64, delta: i64, valid: bool }
+
+    impl ArrowRecord for Reading {
+        fn schema() -> Vec<Field> {
+            vec![
+                Field::new("sensor", DataType::Utf8),
+                Field::new("value", DataType::Float64),
+                Field::new("count", DataType::UInt64),
+                Field::new("delta", DataType::Int64),
+                Field::new("valid", DataType::Boolean),
+            ]
+        }
+        fn append_to(&self, columns: &mut [Column]) {
+            columns[0].push_str(&self.sensor);
+            columns[1].push_f64(self.value);
+            columns[2].push_u64(self.count);
+            columns[3].push_i64(self.delta);
+            columns[4].push_bool(self.valid);
+        }
+    }
+
+    /// The schema and the columns of the single record batch of the Arrow IPC file at `path`, read with the `arrow` crate.
+    fn read(path: &Path) -> (Vec<Field>, Vec<Column>) {
+        let mut reader = FileReader::try_new(File::open(path).unwrap(), None).unwrap();
+        let fields = reader.schema().fields().iter().map(|field| {
+            assert!(!field.is_nullable());
+            let data_type = match field.data_type() {
+                ArrowType::Int64 => DataType::Int64,
+                ArrowType::UInt64 => DataType::UInt64,
+                ArrowType::Float64 => DataType::Float64,
+                ArrowType::Boolean => DataType::Boolean,
+                ArrowType::Utf8 => DataType::Utf8,
+                other => panic!("unexpected column type {:?}", other),
+            };
+            Field::new(field.name().clone(), data_type)
+        }).collect();
+        let batch = reader.next().unwrap().unwrap();
+        assert!(reader.next().is_none());
+        let columns = batch.columns().iter().map(|array| match array.data_type() {
+            ArrowType::Int64 => Column::Int64(array.as_primitive::<Int64Type>().values().to_vec()),
+            ArrowType::UInt64 => Column::UInt64(array.as_primitive::<UInt64Type>().values().to_vec()),
+            ArrowType::Float64 => Column::Float64(array.as_primitive::<Float64Type>().values().to_vec()),
+            ArrowType::Boolean => Column::Boolean(array.as_boolean().iter().map(Option::unwrap).collect()),
+            ArrowType::Utf8 => Column::Utf8(array.as_string::<i32>().iter().map(|value| value.unwrap().to_owned()).collect()),
+            other => panic!("unexpected column type {:?}", other),
+        }).collect();
+        (fields, columns)
+    }
+
+    fn reading(sensor: &str, value: f64, count: u64, delta: i64, valid: bool) -> Reading {
+        Reading { sensor: sensor.to_owned(), value, count, delta, valid }
+    }
+
+    #[test]
+    fn each_time_is_written_to_its_own_file() {
+        let directory = std::env::temp_dir().join(format!("timely-arrow-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = { let directory = directory.clone(); move |time: &u64| directory.join(format!("{}.arrow", time)) };
+
+        crate::execute_directly({
+            let path = path.clone();
+            move |worker| {
+                let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+                    let (input, stream) = scope.new_input::<Reading>();
+                    let probe = stream.probe();
+                    stream.capture_into(ArrowFileWriter::new(path.clone()));
+                    (input, probe)
+                });
+                input.send(reading("a", 1.5, 3, -2, true));
+                input.send(reading("", 0.0, 0, 0, false));
+                worker.step();
+                // Records of one time received over several invocations are written to one batch.
+                for valid in 0 .. 10 {
+                    input.send(reading("ünïcode", -0.25, u64::MAX, i64::MIN, valid % 3 == 0));
+                }
+                input.advance_to(1);
+                worker.step_while(|| probe.less_than(&1));
+                // Nothing is written for time 1, which has no records, and time 2 is written once complete.
+                input.advance_to(2);
+                input.send(reading("b", 2.0, 1, 1, true));
+                worker.step_while(|| probe.less_than(&2));
+                assert!(!path(&2).exists());
+                input.close();
+                worker.step_while(|| !probe.done());
+            }
+        });
+
+        let (fields, columns) = read(&path(&0));
+        assert_eq!(fields, Reading::schema());
+        assert_eq!(columns, vec![
+            Column::Utf8(["a", ""].into_iter().chain(std::iter::repeat_n("ünïcode", 10)).map(String::from).collect()),
+            Column::Float64([1.5, 0.0].into_iter().chain(std::iter::repeat_n(-0.25, 10)).collect()),
+            Column::UInt64([3, 0].into_iter().chain(std::iter::repeat_n(u64::MAX, 10)).collect()),
+            Column::Int64([-2, 0].into_iter().chain(std::iter::repeat_n(i64::MIN, 10)).collect()),
+            Column::Boolean([true, false].into_iter().chain((0..10).map(|valid| valid % 3 == 0)).collect()),
+        ]);
+        assert!(!path(&1).exists());
+        let (_, later) = read(&path(&2));
+        assert_eq!(later[0], Column::Utf8(vec!["b".to_owned()]));
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn files_are_read_by_arrow() {
+        let path = std::env::temp_dir().join(format!("timely-arrow-single-{}.arrow", std::process::id()));
+        super::write(&path, &u64::schema(), vec![Column::UInt64(vec![7, u64::MAX])]).unwrap();
+        assert_eq!(read(&path), (vec![Field::new("value", DataType::UInt64)], vec![Column::UInt64(vec![7, u64::MAX])]));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "pushed a value of Int64 to a column of Utf8")]
+    fn mismatched_values_panic() {
+        Column::new(DataType::Utf8).push_i64(1);
+    }
+}
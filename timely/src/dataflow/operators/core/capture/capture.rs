@@ -5,16 +5,25 @@
 //! and there are several default implementations, including a linked-list, Rust's MPSC
 //! queue, and a binary serializer wrapping any `W: Write`.
 
+use std::io::Write;
+use std::rc::Rc;
+use std::sync::mpsc::TrySendError;
+use std::time::Duration;
+
+use serde::Serialize;
+
 use crate::dataflow::{Scope, StreamCore};
 use crate::dataflow::channels::pact::Pipeline;
 use crate::dataflow::channels::pullers::Counter as PullCounter;
 use crate::dataflow::operators::generic::builder_raw::OperatorBuilder;
+use crate::dataflow::operators::probe::Handle;
 
 use crate::Container;
 use crate::progress::ChangeBatch;
 use crate::progress::Timestamp;
 
-use super::{Event, EventPusher};
+use super::{Event, EventPusher, EventWriter};
+use super::event::draining::{DrainingEventPusher, DrainReport};
 
 /// Capture a stream of timestamped data for later replay.
 pub trait Capture<T: Timestamp, C: Container> {
@@ -105,6 +114,143 @@ pub trait Capture<T: Timestamp, C: Container> {
     /// ```
     fn capture_into<P: EventPusher<T, C>+'static>(&self, pusher: P);
 
+    /// Captures a stream as [`Capture::capture_into`] does, pushing to `pusher` on a thread of its own through a queue of at most `capacity` events.
+    ///
+    /// A slow pusher, for example one writing to a network connection, does not block the worker,
+    /// and its queue does not grow without bound. Once the queue is full, the capturing operator
+    /// stops reading its input, and resumes when the pusher's thread has taken an event from the
+    /// queue. As with the input limits of
+    /// [`OperatorBuilder::set_input_limit`](crate::dataflow::operators::generic::builder_rc::OperatorBuilder::set_input_limit),
+    /// records not yet read remain in the operator's input channel, where they hold back its input
+    /// frontier. Events are queued in order, and `pusher` receives the same events, in the same order,
+    /// as with `capture_into`.
+    ///
+    /// The returned handle is the frontier of the events queued for `pusher`, and producers should
+    /// wait for it as they would for a probe, for example with `step_while(|| handle.less_than(..))`,
+    /// so that a slow pusher slows them rather than the records accumulating in the input channel.
+    /// The frontier advances whenever the queue has room, however slowly `pusher` receives the
+    /// events, and so the dataflow continues to make progress, at the pusher's pace. The capturing
+    /// operator holds no capabilities, and backpressure does not retain those of other operators:
+    /// operators upstream may complete times, and release their capabilities, while the records of
+    /// those times wait in the capturing operator's input channel. The dataflow completes only once
+    /// all events are queued, after which the pusher's thread pushes the remaining events and drops
+    /// `pusher`. Should the pusher panic, its thread discards the remaining events.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{Capture, Input};
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let (send, recv) = std::sync::mpsc::channel();
+    /// timely::execute_directly(move |worker| {
+    ///     let (mut input, handle) = worker.dataflow::<u64,_,_>(|scope| {
+    ///         let (input, stream) = scope.new_input::<u64>();
+    ///         (input, stream.capture_into_with_backpressure(send, 16))
+    ///     });
+    ///     for round in 0..100 {
+    ///         input.send(round);
+    ///         input.advance_to(round + 1);
+    ///         // Wait for the capture to keep up before producing more records.
+    ///         worker.step_while(|| handle.less_than(input.time()));
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(recv.extract().len(), 100);
+    /// ```
+    fn capture_into_with_backpressure<P>(&self, pusher: P, capacity: usize) -> Handle<T>
+    where
+        T: Send+'static,
+        C: Send+'static,
+        P: EventPusher<T, C>+Send+'static;
+
+    /// Captures a stream as [`Capture::capture_into`] does, pushing to `pusher` on a thread of its own, and waiting at most `drain_timeout` for it on teardown.
+    ///
+    /// The events of the stream are queued for `pusher`, which receives them on a thread of its own
+    /// through a [`DrainingEventPusher`], so that a slow pusher, for example one writing to a network
+    /// connection, does not block the worker. When the capturing operator is dropped, once its
+    /// dataflow completes or is dropped, it waits for `pusher` to receive the queued events, for at
+    /// most `drain_timeout`. With a `drain_timeout` of `None` it waits for as long as it takes, as
+    /// `capture_into` does. Otherwise, once the timeout passes, it stops waiting, and the remaining
+    /// events are discarded. The returned report records the number of events that were not
+    /// delivered, which should be checked: events are lost only if it is non-zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use timely::dataflow::operators::{Capture, ToStream};
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let (send, recv) = std::sync::mpsc::channel();
+    /// let report = timely::execute_directly(move |worker| {
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         (0..10).to_stream(scope)
+    ///                .capture_into_with_drain_timeout(send, Some(Duration::from_secs(10)))
+    ///     })
+    /// });
+    ///
+    /// // The worker has shut down, and all events were delivered.
+    /// assert_eq!(report.undelivered(), Some(0));
+    /// assert_eq!(recv.extract()[0].1, (0..10).collect::<Vec<_>>());
+    /// ```
+    fn capture_into_with_drain_timeout<P>(&self, pusher: P, drain_timeout: Option<Duration>) -> DrainReport
+    where
+        T: Send+'static,
+        C: Send+'static,
+        P: EventPusher<T, C>+Send+'static,
+    {
+        let (pusher, report) = DrainingEventPusher::new(pusher, drain_timeout);
+        self.capture_into(pusher);
+        report
+    }
+
+    /// Writes the stream to `writer` as a replayable log, and returns the stream to continue downstream unchanged.
+    ///
+    /// The stream is captured into an [`EventWriter`] wrapping `writer`, as an additional consumer
+    /// of the stream alongside those of the returned stream, which is `self`. Each container the
+    /// stream produces is cloned for the log, which costs the main path only that clone and the
+    /// time of its write, as the log is written as the capturing operator runs; a `writer` that
+    /// buffers, such as a `BufWriter`, keeps writes short. The log holds all of the stream's
+    /// messages and progress, including the final progress once the stream completes, and so can
+    /// be replayed with an [`EventReader`](super::EventReader), for example to audit or debug the
+    /// stream later. A buffering writer is flushed as the capturing operator is dropped, once its
+    /// dataflow completes or is dropped.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{Capture, Inspect, ToStream};
+    /// use timely::dataflow::operators::capture::{EventReader, Extract, Replay};
+    ///
+    /// let path = std::env::temp_dir().join(format!("timely-tee-to-log-{}", std::process::id()));
+    /// let log = std::io::BufWriter::new(std::fs::File::create(&path).unwrap());
+    /// timely::example(move |scope| {
+    ///     (0..10u64).to_stream(scope)
+    ///               .tee_to_log(log)
+    ///               .inspect(|x| println!("seen: {:?}", x));
+    /// });
+    ///
+    /// // The log replays the stream.
+    /// let file = std::fs::File::open(&path).unwrap();
+    /// let replayed = timely::example(move |scope| {
+    ///     Some(EventReader::<_, Vec<u64>, _>::new(file)).replay_into(scope).capture()
+    /// });
+    /// assert_eq!(replayed.extract(), vec![(0, (0..10).collect::<Vec<_>>())]);
+    /// std::fs::remove_file(&path).unwrap();
+    /// ```
+    fn tee_to_log<W>(&self, writer: W) -> Self
+    where
+        Self: Clone,
+        T: Serialize+'static,
+        C: Serialize+'static,
+        W: Write+'static,
+    {
+        self.capture_into(EventWriter::new(writer));
+        self.clone()
+    }
+
     /// Captures a stream using Rust's MPSC channels.
     fn capture(&self) -> ::std::sync::mpsc::Receiver<Event<T, C>> {
         let (send, recv) = ::std::sync::mpsc::channel();
@@ -146,4 +292,87 @@ impl<S: Scope, C: Container> Capture<S::Timestamp, C> for StreamCore<S, C> {
             }
         );
     }
+
+    fn capture_into_with_backpressure<P>(&self, mut pusher: P, capacity: usize) -> Handle<S::Timestamp>
+    where
+        S::Timestamp: Send+'static,
+        C: Send+'static,
+        P: EventPusher<S::Timestamp, C>+Send+'static,
+    {
+        assert!(capacity > 0, "capture_into_with_backpressure: capacity must be positive");
+
+        let mut builder = OperatorBuilder::new("CaptureWithBackpressure".to_owned(), self.scope());
+        let mut input = PullCounter::new(builder.new_input(self, Pipeline));
+        let activator = self.scope().sync_activator_for(builder.operator_info().address.to_vec());
+
+        let (sender, receiver) = std::sync::mpsc::sync_channel::<Event<S::Timestamp, C>>(capacity);
+        std::thread::Builder::new()
+            .name("timely:capture-backpressure".to_owned())
+            .spawn(move || {
+                for event in receiver {
+                    // Room in the queue, so the operator may queue further events.
+                    let _ = activator.activate();
+                    pusher.push(event);
+                }
+            })
+            .expect("failed to spawn capture thread");
+
+        // The handle reflects the initial capability the events discard, until they report otherwise.
+        let handle = Handle::new();
+        handle.frontier.borrow_mut().update_iter([(S::Timestamp::minimum(), 1)]);
+        let shared_frontier = Rc::downgrade(&handle.frontier);
+        // An event the queue had no room for, to queue before any other.
+        let mut blocked = None;
+        let mut started = false;
+
+        // Queues `event`, and returns `false` if the queue is full, in which case `event` moves to `slot`.
+        let offer = move |event: Event<S::Timestamp, C>, slot: &mut Option<Event<S::Timestamp, C>>| {
+            let changes = match &event {
+                Event::Progress(changes) => changes.clone(),
+                Event::Messages(_, _) => Vec::new(),
+            };
+            match sender.try_send(event) {
+                Err(TrySendError::Full(event)) => {
+                    *slot = Some(event);
+                    false
+                }
+                // A disconnected queue means the pusher panicked, and its events are discarded.
+                Ok(()) | Err(TrySendError::Disconnected(_)) => {
+                    if let Some(shared_frontier) = shared_frontier.upgrade() {
+                        shared_frontier.borrow_mut().update_iter(changes);
+                    }
+                    true
+                }
+            }
+        };
+
+        builder.build(
+            move |progress| {
+
+                if !started {
+                    // discard initial capability.
+                    progress.frontiers[0].update(S::Timestamp::minimum(), -1);
+                    started = true;
+                }
+
+                // queue events in order until the queue is full: first a blocked event, then frontier progress, then messages.
+                let mut open = blocked.take().is_none_or(|event| offer(event, &mut blocked));
+                if open && !progress.frontiers[0].is_empty() {
+                    let to_send = std::mem::take(&mut progress.frontiers[0]);
+                    open = offer(Event::Progress(to_send.into_inner().to_vec()), &mut blocked);
+                }
+                while open {
+                    let Some(message) = input.next() else { break };
+                    let time = &message.time;
+                    let vector = std::mem::take(&mut message.data);
+                    open = offer(Event::Messages(time.clone(), vector), &mut blocked);
+                }
+                input.consumed().borrow_mut().drain_into(&mut progress.consumeds[0]);
+                // not complete while an event waits to be queued.
+                blocked.is_some()
+            }
+        );
+
+        handle
+    }
 }
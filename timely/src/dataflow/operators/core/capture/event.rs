@@ -128,10 +128,158 @@ pub mod link {
     }
 }
 
+/// An event pusher and iterator over an in-process `std::sync::mpsc` channel.
+///
+/// Captured events are moved through the channel without serialization, which suits tests of
+/// capture and replay, and pipelines that hand streams between dataflows of one process.
+pub mod channel {
+
+    use std::borrow::Cow;
+    use std::sync::mpsc::{Receiver, Sender, TryRecvError};
+
+    use super::{Event, EventPusher, EventIterator};
+
+    /// Creates a connected writer and reader, for capturing a stream into and replaying it from.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{Capture, ToStream, Inspect};
+    /// use timely::dataflow::operators::capture::Replay;
+    /// use timely::dataflow::operators::capture::event::channel::channel;
+    ///
+    /// timely::execute(timely::Config::thread(), |worker| {
+    ///     let (writer, reader) = channel();
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         (0..10u64).to_stream(scope).capture_into(writer);
+    ///     });
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         Some(reader).replay_into(scope)
+    ///                     .inspect(|x: &u64| println!("replayed: {:?}", x));
+    ///     });
+    /// }).unwrap();
+    /// ```
+    pub fn channel<T, C>() -> (ChannelEventWriter<T, C>, ChannelEventReader<T, C>) {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        (ChannelEventWriter::new(sender), ChannelEventReader::new(receiver))
+    }
+
+    /// A wrapper for a `Sender<Event<T, C>>` implementing `EventPusher<T, C>`.
+    ///
+    /// Once the receiver is dropped, the writer stops forwarding events, and discards them instead.
+    pub struct ChannelEventWriter<T, C> {
+        sender: Option<Sender<Event<T, C>>>,
+    }
+
+    impl<T, C> ChannelEventWriter<T, C> {
+        /// Allocates a new `ChannelEventWriter` forwarding events to `sender`.
+        pub fn new(sender: Sender<Event<T, C>>) -> Self {
+            Self { sender: Some(sender) }
+        }
+        /// Returns `true` once an event could not be sent because the receiver was dropped.
+        pub fn is_disconnected(&self) -> bool {
+            self.sender.is_none()
+        }
+    }
+
+    impl<T, C> EventPusher<T, C> for ChannelEventWriter<T, C> {
+        fn push(&mut self, event: Event<T, C>) {
+            if let Some(sender) = &self.sender {
+                if sender.send(event).is_err() {
+                    self.sender = None;
+                }
+            }
+        }
+    }
+
+    /// A wrapper for a `Receiver<Event<T, C>>` implementing `EventIterator<T, C>`.
+    ///
+    /// The reader does not block, and yields no event while the channel is empty or disconnected.
+    pub struct ChannelEventReader<T, C> {
+        receiver: Receiver<Event<T, C>>,
+        disconnected: bool,
+    }
+
+    impl<T, C> ChannelEventReader<T, C> {
+        /// Allocates a new `ChannelEventReader` reading events from `receiver`.
+        pub fn new(receiver: Receiver<Event<T, C>>) -> Self {
+            Self { receiver, disconnected: false }
+        }
+        /// Returns `true` once all events have been read and the sender has been dropped.
+        pub fn is_disconnected(&self) -> bool {
+            self.disconnected
+        }
+    }
+
+    impl<T: Clone, C: Clone> EventIterator<T, C> for ChannelEventReader<T, C> {
+        fn next(&mut self) -> Option<Cow<'_, Event<T, C>>> {
+            match self.receiver.try_recv() {
+                Ok(event) => Some(Cow::Owned(event)),
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Disconnected) => {
+                    self.disconnected = true;
+                    None
+                },
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use crate::dataflow::operators::{Capture, Probe, ToStream};
+        use crate::dataflow::operators::capture::{Extract, Replay};
+        use super::super::{Event, EventIterator, EventPusher};
+        use super::channel;
+
+        #[test]
+        fn streams_replay_from_the_channel() {
+            let captured = crate::execute_directly(|worker| {
+                let (writer, reader) = channel();
+                worker.dataflow::<u64,_,_>(|scope| {
+                    (0..5u64).to_stream(scope).capture_into(writer);
+                });
+                let (probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                    let replayed = Some(reader).replay_into(scope);
+                    (replayed.probe(), replayed.capture())
+                });
+                worker.step_while(|| !probe.done());
+                captured
+            });
+            assert_eq!(captured.extract(), vec![(0, (0..5).collect::<Vec<u64>>())]);
+        }
+
+        #[test]
+        fn dropped_receivers_stop_forwarding() {
+            let (mut writer, mut reader) = channel::<u64, Vec<u64>>();
+            writer.push(Event::Messages(0, vec![1]));
+            assert!(!writer.is_disconnected());
+            assert_eq!(reader.next().map(|event| event.into_owned()), Some(Event::Messages(0, vec![1])));
+            assert_eq!(reader.next(), None);
+            assert!(!reader.is_disconnected());
+            drop(reader);
+            writer.push(Event::Messages(1, vec![2]));
+            assert!(writer.is_disconnected());
+        }
+
+        #[test]
+        fn dropped_senders_disconnect_readers() {
+            let (writer, mut reader) = channel::<u64, Vec<u64>>();
+            drop(writer);
+            assert_eq!(reader.next(), None);
+            assert!(reader.is_disconnected());
+        }
+    }
+}
+
 /// A binary event pusher and iterator.
+///
+/// The binary format begins with a header identifying the format and its version, followed by a
+/// sequence of events, each serialized with `bincode` and prefixed by its length as a `u64`. Readers
+/// reject streams whose version they do not support, rather than misinterpreting their contents.
+/// Streams written before the header was introduced are recognized, and read as version zero.
 pub mod binary {
 
     use std::borrow::Cow;
+    use std::collections::HashMap;
     use std::io::ErrorKind;
     use std::ops::DerefMut;
     use std::sync::Arc;
@@ -141,17 +289,29 @@ pub mod binary {
 
     use super::{Event, EventPusher, EventIterator};
 
+    /// The bytes identifying the start of a versioned binary event stream.
+    const MAGIC: [u8; 8] = *b"TDEVENTS";
+
+    /// The version of the binary event format written by [`EventWriter`].
+    ///
+    /// Version zero denotes streams without a header, whose events have the same layout as version one.
+    pub const FORMAT_VERSION: u64 = 1;
+
     /// A wrapper for `W: Write` implementing `EventPusher<T, C>`.
     pub struct EventWriter<T, C, W: ::std::io::Write> {
         stream: W,
+        header_written: bool,
         phant: ::std::marker::PhantomData<(T, C)>,
     }
 
     impl<T, C, W: ::std::io::Write> EventWriter<T, C, W> {
         /// Allocates a new `EventWriter` wrapping a supplied writer.
+        ///
+        /// The format header is written along with the first event.
         pub fn new(w: W) -> Self {
             Self {
                 stream: w,
+                header_written: false,
                 phant: ::std::marker::PhantomData,
             }
         }
@@ -160,16 +320,33 @@ pub mod binary {
     impl<T: Serialize, C: Serialize, W: ::std::io::Write> EventPusher<T, C> for EventWriter<T, C, W> {
         fn push(&mut self, event: Event<T, C>) {
             // TODO: `push` has no mechanism to report errors, so we `unwrap`.
+            if !self.header_written {
+                self.stream.write_all(&MAGIC).expect("Event write failed");
+                self.stream.write_all(&FORMAT_VERSION.to_le_bytes()).expect("Event write failed");
+                self.header_written = true;
+            }
             let len = ::bincode::serialized_size(&event).expect("Event bincode failed");
             self.stream.write_all(&len.to_le_bytes()).expect("Event write failed");
             ::bincode::serialize_into(&mut self.stream, &event).expect("Event bincode failed");
         }
     }
 
+    /// A function converting the serialization of an event from an older version to the current one.
+    type Migration = Box<dyn FnMut(&[u8]) -> Vec<u8>>;
+
     /// A Wrapper for `R: Read` implementing `EventIterator<T, D>`.
+    ///
+    /// # Panics
+    ///
+    /// Reading panics if the stream's format version is neither [`FORMAT_VERSION`], zero, nor a
+    /// version for which a migration was registered with [`EventReader::migrate`].
     pub struct EventReader<T, C, R: ::std::io::Read> {
         reader: R,
         buf: BytesSlab,
+        /// The format version of the stream, once its header has been read.
+        version: Option<u64>,
+        /// Migrations of events from older format versions, by version.
+        migrations: HashMap<u64, Migration>,
         phant: ::std::marker::PhantomData<(T, C)>,
     }
 
@@ -185,9 +362,64 @@ pub mod binary {
             Self {
                 reader: r,
                 buf: BytesSlab::new(20, refill),
+                version: None,
+                migrations: HashMap::new(),
                 phant: ::std::marker::PhantomData,
             }
         }
+
+        /// Registers a function that rewrites the `bincode` serialization of an event written in
+        /// format `version` into the serialization of the same event in the current format.
+        ///
+        /// # Examples
+        /// ```
+        /// use timely::dataflow::operators::capture::{Event, EventPusher, EventReader, EventWriter};
+        /// use timely::dataflow::operators::capture::event::EventIterator;
+        ///
+        /// let mut bytes = Vec::new();
+        /// EventWriter::<u64, Vec<u64>, _>::new(&mut bytes).push(Event::Messages(0, vec![1, 2, 3]));
+        /// // Pretend the stream was written by a hypothetical version two, which reversed records.
+        /// bytes[8] = 2;
+        ///
+        /// let mut reader = EventReader::<u64, Vec<u64>, _>::new(&bytes[..]).migrate(2, |event| {
+        ///     let (time, mut data): (u64, Vec<u64>) = match bincode::deserialize(event).unwrap() {
+        ///         Event::Messages(time, data) => (time, data),
+        ///         Event::Progress(_) => unreachable!(),
+        ///     };
+        ///     data.reverse();
+        ///     bincode::serialize(&Event::<u64, Vec<u64>>::Messages(time, data)).unwrap()
+        /// });
+        /// match reader.next().map(|event| event.into_owned()) {
+        ///     Some(Event::Messages(0, data)) => assert_eq!(data, vec![3, 2, 1]),
+        ///     _ => panic!("expected migrated messages"),
+        /// }
+        /// ```
+        pub fn migrate<M: FnMut(&[u8]) -> Vec<u8> + 'static>(mut self, version: u64, migration: M) -> Self {
+            self.migrations.insert(version, Box::new(migration));
+            self
+        }
+
+        /// The format version of the stream, once its header has been read.
+        pub fn version(&self) -> Option<u64> {
+            self.version
+        }
+
+        /// Reads the format header, if enough bytes are available, and validates the version.
+        fn read_header(&mut self) {
+            let valid = self.buf.valid();
+            if valid.len() >= 8 && valid[..8] != MAGIC {
+                // Streams written before the introduction of the header start with an event length.
+                self.version = Some(0);
+            }
+            else if valid.len() >= 16 {
+                let version = u64::from_le_bytes(valid[8..16].try_into().unwrap());
+                if version != FORMAT_VERSION && !self.migrations.contains_key(&version) {
+                    panic!("binary event stream has format version {version}, but this reader supports version {FORMAT_VERSION}; register a migration with `EventReader::migrate` to read it");
+                }
+                let _ = self.buf.extract(16);
+                self.version = Some(version);
+            }
+        }
     }
 
     impl<T: DeserializeOwned + Clone, C: DeserializeOwned + Clone, R: ::std::io::Read> EventIterator<T, C> for EventReader<T, C, R> {
@@ -200,6 +432,11 @@ pub mod binary {
                 Err(e) => panic!("read failed: {e}"),
             };
 
+            if self.version.is_none() {
+                self.read_header();
+            }
+            let version = self.version?;
+
             let valid = self.buf.valid();
             if valid.len() >= 8 {
                 let event_len = u64::from_le_bytes([
@@ -208,7 +445,12 @@ pub mod binary {
                 let required_bytes = (event_len + 8) as usize;
                 if valid.len() >= required_bytes {
                     let bytes = self.buf.extract(required_bytes);
-                    let event = ::bincode::deserialize(&bytes[8..]).expect("Event decode failed");
+                    let event = match self.migrations.get_mut(&version) {
+                        Some(migration) if version != FORMAT_VERSION => {
+                            ::bincode::deserialize(&migration(&bytes[8..])).expect("Event decode failed")
+                        },
+                        _ => ::bincode::deserialize(&bytes[8..]).expect("Event decode failed"),
+                    };
                     Some(Cow::Owned(event))
                 } else {
                     None
@@ -218,4 +460,201 @@ pub mod binary {
             }
         }
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::{EventReader, EventWriter};
+        use super::super::{Event, EventIterator, EventPusher};
+
+        fn written(events: Vec<Event<u64, Vec<u64>>>) -> Vec<u8> {
+            let mut bytes = Vec::new();
+            let mut writer = EventWriter::new(&mut bytes);
+            for event in events {
+                writer.push(event);
+            }
+            bytes
+        }
+
+        fn read(bytes: &[u8]) -> Vec<Event<u64, Vec<u64>>> {
+            let mut reader = EventReader::<u64, Vec<u64>, _>::new(bytes);
+            let mut events = Vec::new();
+            // The reader consumes at most a slab's worth of bytes per call.
+            for _ in 0 .. 10 {
+                if let Some(event) = reader.next() {
+                    events.push(event.into_owned());
+                }
+            }
+            events
+        }
+
+        #[test]
+        fn roundtrip_and_legacy() {
+            let events = vec![Event::Messages(0, vec![1, 2]), Event::Progress(vec![(0, -1), (1, 1)])];
+            let bytes = written(events.clone());
+            assert_eq!(read(&bytes), events);
+            // Streams without a header are read as version zero.
+            assert_eq!(read(&bytes[16..]), events);
+        }
+
+        #[test]
+        #[should_panic(expected = "format version 7")]
+        fn reject_unknown_version() {
+            let mut bytes = written(vec![Event::Messages(0, vec![1, 2])]);
+            bytes[8] = 7;
+            read(&bytes);
+        }
+    }
+}
+
+/// An event pusher that forwards events to another pusher on a thread of its own, and waits a bounded time for it to drain.
+pub mod draining {
+
+    use std::sync::{Arc, Mutex};
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::mpsc::{Receiver, Sender};
+    use std::time::Duration;
+
+    use super::{Event, EventPusher};
+
+    /// A wrapper for `P: EventPusher<T, C>` that pushes events on a thread of its own.
+    ///
+    /// Events pushed to the wrapper are queued, and pushed to `P` by the thread in order, so that a
+    /// slow pusher does not block the worker. When the wrapper is dropped, for example once the
+    /// dataflow of a capturing operator completes or is dropped, it waits for the thread to push the
+    /// queued events and drop `P`, for at most the drain timeout if there is one. If the timeout
+    /// passes first, the wrapper stops waiting, the thread discards the events it has not yet pushed,
+    /// and the [`DrainReport`] records how many events were not delivered.
+    pub struct DrainingEventPusher<T, C> {
+        sender: Option<Sender<Event<T, C>>>,
+        /// Receives once the thread has pushed all events and dropped its pusher.
+        drained: Receiver<()>,
+        drain_timeout: Option<Duration>,
+        /// The number of events queued.
+        queued: usize,
+        /// The number of events pushed by the thread.
+        delivered: Arc<AtomicUsize>,
+        /// Set once the wrapper stops waiting, after which the thread discards its events.
+        abandoned: Arc<AtomicBool>,
+        report: DrainReport,
+    }
+
+    impl<T: Send+'static, C: Send+'static> DrainingEventPusher<T, C> {
+        /// Allocates a new `DrainingEventPusher` pushing events to `pusher`, and waiting up to `drain_timeout` for it to drain.
+        ///
+        /// With a `drain_timeout` of `None` the wrapper waits until all events are pushed, however long it takes.
+        pub fn new<P: EventPusher<T, C>+Send+'static>(mut pusher: P, drain_timeout: Option<Duration>) -> (Self, DrainReport) {
+            let (sender, receiver) = std::sync::mpsc::channel::<Event<T, C>>();
+            let (drained_sender, drained) = std::sync::mpsc::channel();
+            let delivered = Arc::new(AtomicUsize::new(0));
+            let abandoned = Arc::new(AtomicBool::new(false));
+            let thread_delivered = Arc::clone(&delivered);
+            let thread_abandoned = Arc::clone(&abandoned);
+            std::thread::Builder::new()
+                .name("timely:capture-drain".to_owned())
+                .spawn(move || {
+                    for event in receiver {
+                        if !thread_abandoned.load(Ordering::SeqCst) {
+                            pusher.push(event);
+                            thread_delivered.fetch_add(1, Ordering::SeqCst);
+                        }
+                    }
+                    drop(pusher);
+                    let _ = drained_sender.send(());
+                })
+                .expect("failed to spawn capture drain thread");
+            let report = DrainReport::default();
+            (Self { sender: Some(sender), drained, drain_timeout, queued: 0, delivered, abandoned, report: report.clone() }, report)
+        }
+    }
+
+    impl<T, C> EventPusher<T, C> for DrainingEventPusher<T, C> {
+        fn push(&mut self, event: Event<T, C>) {
+            self.queued += 1;
+            // A send only fails if the thread has panicked, and the event counts as undelivered.
+            if let Some(sender) = &self.sender {
+                let _ = sender.send(event);
+            }
+        }
+    }
+
+    impl<T, C> Drop for DrainingEventPusher<T, C> {
+        fn drop(&mut self) {
+            // Dropping the sender ends the thread once it has pushed the queued events.
+            drop(self.sender.take());
+            let drained = match self.drain_timeout {
+                Some(timeout) => self.drained.recv_timeout(timeout).is_ok(),
+                None => self.drained.recv().is_ok(),
+            };
+            if !drained {
+                self.abandoned.store(true, Ordering::SeqCst);
+            }
+            let undelivered = self.queued - self.delivered.load(Ordering::SeqCst);
+            *self.report.undelivered.lock().expect("drain report poisoned") = Some(undelivered);
+        }
+    }
+
+    /// Reports how many events a [`DrainingEventPusher`] did not deliver.
+    ///
+    /// Clones of a report observe the same outcome.
+    #[derive(Clone, Debug, Default)]
+    pub struct DrainReport {
+        undelivered: Arc<Mutex<Option<usize>>>,
+    }
+
+    impl DrainReport {
+        /// The number of events not delivered when the pusher was dropped, or `None` if it has not been dropped.
+        ///
+        /// The number is zero if all events were delivered within the drain timeout. Otherwise it
+        /// counts the events not yet pushed when the timeout passed, including one which the thread
+        /// may have been pushing at the time, and which may yet be delivered. A pusher that panics
+        /// on its thread does not deliver the event it panicked on, nor any later events.
+        pub fn undelivered(&self) -> Option<usize> {
+            *self.undelivered.lock().expect("drain report poisoned")
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::sync::mpsc::Sender;
+        use std::time::Duration;
+
+        use super::super::{Event, EventPusher};
+        use super::DrainingEventPusher;
+
+        /// Forwards events to a channel, sleeping before each one.
+        struct Slow(Sender<Event<u64, Vec<u64>>>, Duration);
+
+        impl EventPusher<u64, Vec<u64>> for Slow {
+            fn push(&mut self, event: Event<u64, Vec<u64>>) {
+                std::thread::sleep(self.1);
+                let _ = self.0.send(event);
+            }
+        }
+
+        #[test]
+        fn unlimited_timeouts_deliver_all_events() {
+            let (sender, receiver) = std::sync::mpsc::channel();
+            let (mut pusher, report) = DrainingEventPusher::new(Slow(sender, Duration::from_millis(5)), None);
+            for round in 0..5 { pusher.push(Event::Messages(round, vec![round])); }
+            assert_eq!(report.undelivered(), None);
+            drop(pusher);
+            assert_eq!(report.undelivered(), Some(0));
+            assert_eq!(receiver.iter().count(), 5);
+        }
+
+        #[test]
+        fn timeouts_stop_waiting_and_count_undelivered_events() {
+            let (sender, receiver) = std::sync::mpsc::channel();
+            let (mut pusher, report) = DrainingEventPusher::new(Slow(sender, Duration::from_millis(100)), Some(Duration::from_millis(150)));
+            for round in 0..20 { pusher.push(Event::Messages(round, vec![round])); }
+            let start = std::time::Instant::now();
+            drop(pusher);
+            assert!(start.elapsed() < Duration::from_secs(1));
+            // One or two events are delivered by the timeout, and perhaps one more after it.
+            let undelivered = report.undelivered().unwrap();
+            assert!((17..=19).contains(&undelivered), "{}", undelivered);
+            let delivered = receiver.iter().count();
+            assert!(delivered + undelivered >= 20 && delivered + undelivered <= 21, "{} {}", delivered, undelivered);
+        }
+    }
 }
@@ -6,7 +6,8 @@
 //!
 //! The `capture_into` method requires a `P: EventPusher<T, D>`, which is some type accepting
 //! `Event<T, D>` inputs. This module provides several examples, including the linked list
-//! `EventLink<T, D>`, and the binary `EventWriter<T, D, W>` wrapping any `W: Write`.
+//! `EventLink<T, D>`, the binary `EventWriter<T, D, W>` wrapping any `W: Write`, and the
+//! `ChannelEventWriter<T, D>` wrapping a `std::sync::mpsc::Sender`.
 //!
 //! Streams are captured at the worker granularity, and one can replay an arbitrary subset of
 //! the captured streams on any number of workers (fewer, more, or as many as were captured).
@@ -86,10 +87,16 @@ pub use self::replay::Replay;
 pub use self::extract::Extract;
 pub use self::event::{Event, EventPusher};
 pub use self::event::link::EventLink;
+pub use self::event::channel::{ChannelEventReader, ChannelEventWriter};
 pub use self::event::binary::EventReader;
 pub use self::event::binary::EventWriter;
+pub use self::event::draining::{DrainingEventPusher, DrainReport};
+#[cfg(feature = "arrow")]
+pub use self::arrow::ArrowFileWriter;
 
 pub mod capture;
 pub mod replay;
 pub mod extract;
 pub mod event;
+#[cfg(feature = "arrow")]
+pub mod arrow;
@@ -0,0 +1,70 @@
+//! Extension methods for `StreamCore` that convert between container types.
+
+use crate::container::{DrainContainer, PushInto};
+use crate::{Container, ContainerBuilder};
+use crate::dataflow::{Scope, StreamCore};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// Extension trait for `StreamCore`.
+pub trait ConvertContainer<S: Scope, C: DrainContainer> {
+    /// Rebuilds the records of each container into containers formed by the builder `CB`.
+    ///
+    /// Each container is drained, and each of its items pushed into `CB`, which forms containers of
+    /// its own type. This bridges operators that prefer different representations of the same
+    /// records, for example row-oriented `Vec` containers and columnar containers, within one
+    /// dataflow. Conversion visits and rebuilds every record, and is not free.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::container::CapacityContainerBuilder;
+    /// use timely::dataflow::operators::{ToStream, Inspect};
+    /// use timely::dataflow::operators::core::ConvertContainer;
+    /// use timely::dataflow::operators::rc::SharedStream;
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .shared()
+    ///            // Clones the records of the shared containers into owned containers.
+    ///            .convert_container::<CapacityContainerBuilder<Vec<u64>>>()
+    ///            .inspect(|x| println!("seen: {:?}", x));
+    /// });
+    /// ```
+    fn convert_container<CB>(&self) -> StreamCore<S, CB::Container>
+    where
+        CB: ContainerBuilder + for<'a> PushInto<C::Item<'a>>;
+}
+
+impl<S: Scope, C: Container + DrainContainer> ConvertContainer<S, C> for StreamCore<S, C> {
+    fn convert_container<CB>(&self) -> StreamCore<S, CB::Container>
+    where
+        CB: ContainerBuilder + for<'a> PushInto<C::Item<'a>>,
+    {
+        self.unary::<CB, _, _, _>(Pipeline, "ConvertContainer", move |_, _| move |input, output| {
+            input.for_each_time(|time, data| {
+                output.session_with_builder(&time)
+                      .give_iterator(data.flat_map(|d| d.drain()));
+            });
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::container::CapacityContainerBuilder;
+    use crate::dataflow::operators::{Capture, ToStream};
+    use crate::dataflow::operators::capture::Extract;
+    use crate::dataflow::operators::rc::SharedStream;
+    use super::ConvertContainer;
+
+    #[test]
+    fn converts_shared_containers_into_owned_containers() {
+        let data = crate::example(|scope| {
+            (0..10u64).to_stream(scope)
+                      .shared()
+                      .convert_container::<CapacityContainerBuilder<Vec<u64>>>()
+                      .capture()
+        });
+        assert_eq!(data.extract(), vec![(0, (0..10).collect::<Vec<_>>())]);
+    }
+}
@@ -25,13 +25,16 @@ use std::rc::Rc;
 use crate::logging::{TimelyLogger, MessagesEvent};
 use crate::progress::Timestamp;
 use crate::progress::timestamp::Refines;
+use crate::order::Product;
 use crate::progress::{Source, Target};
-use crate::{Accountable, Container};
+use crate::{Accountable, Container, Data};
 use crate::communication::Push;
 use crate::dataflow::channels::pushers::{Counter, Tee};
 use crate::dataflow::channels::Message;
 use crate::worker::AsWorker;
-use crate::dataflow::{StreamCore, Scope};
+use crate::dataflow::{StreamCore, Stream, Scope};
+use crate::dataflow::channels::pact::{PactKind, Pipeline};
+use crate::dataflow::operators::generic::Operator;
 use crate::dataflow::scopes::Child;
 
 /// Extension trait to move a `Stream` into a child of its current `Scope`.
@@ -71,9 +74,9 @@ impl<G: Scope, T: Timestamp+Refines<G::Timestamp>, C: Container> Enter<G, T, C>
 
         if let Some(logger) = scope.logging() {
             let pusher = LogPusher::new(ingress, channel_id, scope.index(), logger);
-            self.connect_to(input, pusher, channel_id);
+            self.connect_to_with_kind(input, pusher, channel_id, PactKind::Pipeline);
         } else {
-            self.connect_to(input, ingress, channel_id);
+            self.connect_to_with_kind(input, ingress, channel_id, PactKind::Pipeline);
         }
 
         StreamCore::new(
@@ -116,9 +119,9 @@ impl<G: Scope, C: Container, T: Timestamp+Refines<G::Timestamp>> Leave<G, C> for
 
         if let Some(logger) = scope.logging() {
             let pusher = LogPusher::new(egress, channel_id, scope.index(), logger);
-            self.connect_to(target, pusher, channel_id);
+            self.connect_to_with_kind(target, pusher, channel_id, PactKind::Pipeline);
         } else {
-            self.connect_to(target, egress, channel_id);
+            self.connect_to_with_kind(target, egress, channel_id, PactKind::Pipeline);
         }
 
         StreamCore::new(
@@ -130,6 +133,43 @@ impl<G: Scope, C: Container, T: Timestamp+Refines<G::Timestamp>> Leave<G, C> for
 }
 
 
+/// Extension trait to move a `Stream` out of an iterative `Scope`, retaining the inner timestamp coordinate.
+pub trait LeaveWithTime<G: Scope, TInner, D> {
+    /// Moves a `Stream` to the parent of its current `Scope`, pairing each record with the inner
+    /// coordinate of the `Product` timestamp at which it left.
+    ///
+    /// The outer coordinate of each record's timestamp is unchanged, exactly as with `leave`, and
+    /// progress tracking is unaffected. Only the records change, each gaining the inner coordinate,
+    /// for example the iteration of a loop in which it was produced.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::Scope;
+    /// use timely::dataflow::operators::{Enter, LeaveWithTime, ToStream, Inspect};
+    ///
+    /// timely::example(|outer| {
+    ///     let stream = (0..9).to_stream(outer);
+    ///     outer.iterative::<u32,_,_>(|inner| {
+    ///         stream.enter(inner).leave_with_time()
+    ///     })
+    ///     .inspect(|(x, iteration)| println!("{:?} left at iteration {:?}", x, iteration));
+    /// });
+    /// ```
+    fn leave_with_time(&self) -> Stream<G, (D, TInner)>;
+}
+
+impl<G: Scope, TInner: Timestamp, D: Data> LeaveWithTime<G, TInner, D> for Stream<Child<'_, G, Product<G::Timestamp, TInner>>, D> {
+    fn leave_with_time(&self) -> Stream<G, (D, TInner)> {
+        self.unary(Pipeline, "AttachTime", |_, _| |input, output| {
+            input.for_each_time(|time, data| {
+                let inner = &time.time().inner;
+                output.session(&time).give_iterator(data.flat_map(|d| d.drain(..)).map(|datum| (datum, inner.clone())));
+            });
+        })
+        .leave()
+    }
+}
+
 struct IngressNub<TOuter: Timestamp, TInner: Timestamp+Refines<TOuter>, TContainer: Container> {
     targets: Counter<TInner, Tee<TInner, TContainer>>,
     phantom: ::std::marker::PhantomData<TOuter>,
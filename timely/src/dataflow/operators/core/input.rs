@@ -360,24 +360,54 @@ impl<T: Timestamp, CB: ContainerBuilder> Handle<T, CB> {
     #[inline]
     fn extract_and_send(&mut self) {
         while let Some(container) = self.builder.extract() {
-            Self::send_container(container, &mut self.buffer, &mut self.pushers, &self.now_at);
+            Self::push_to_each(container, &mut self.buffer, &mut self.pushers, &self.now_at);
         }
     }
 
     /// Flush all contents and distribute to downstream operators.
+    ///
+    /// Records buffered by the container builder are sent immediately, at the current epoch,
+    /// rather than when a container fills or the epoch advances. Flushing does not change the
+    /// epoch nor the capability the handle holds, so downstream frontiers do not advance; only
+    /// [`Self::advance_to`] and dropping the handle do that. It is safe to call this method at any
+    /// time, for example on a timer in a long-running service, and as often as needed.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{Input, Inspect, Probe};
+    /// use timely::dataflow::ProbeHandle;
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     let seen = std::rc::Rc::new(std::cell::Cell::new(0));
+    ///     let probe = ProbeHandle::new();
+    ///     let mut input = worker.dataflow(|scope| {
+    ///         let (input, stream) = scope.new_input::<u64>();
+    ///         let seen = std::rc::Rc::clone(&seen);
+    ///         stream.inspect(move |_| seen.set(seen.get() + 1))
+    ///               .probe_with(&probe);
+    ///         input
+    ///     });
+    ///
+    ///     input.send(0);
+    ///     input.flush();
+    ///     worker.step();
+    ///     // The record has been delivered, but the epoch remains open.
+    ///     assert_eq!(seen.get(), 1);
+    ///     assert!(probe.less_equal(&0));
+    /// });
+    /// ```
     #[inline]
     pub fn flush(&mut self) {
         while let Some(container) = self.builder.finish() {
-            Self::send_container(container, &mut self.buffer, &mut self.pushers, &self.now_at);
+            Self::push_to_each(container, &mut self.buffer, &mut self.pushers, &self.now_at);
         }
     }
 
     /// Sends a container at each of the destinations. There can be more than one; clone if needed.
     /// Does not take `self` because `flush` and `extract` borrow `self` mutably.
     /// Leaves the container in an undefined state.
-    // TODO: Find a better name for this function.
     #[inline]
-    fn send_container(
+    fn push_to_each(
         container: &mut CB::Container,
         buffer: &mut CB::Container,
         pushers: &mut [Counter<T, Tee<T, CB::Container>>],
@@ -442,10 +472,46 @@ impl<T: Timestamp, CB: ContainerBuilder> Handle<T, CB> {
         if !buffer.is_empty() {
             // flush buffered elements to ensure local fifo.
             self.flush();
-            Self::send_container(buffer, &mut self.buffer, &mut self.pushers, &self.now_at);
+            Self::push_to_each(buffer, &mut self.buffer, &mut self.pushers, &self.now_at);
         }
     }
 
+    /// Sends a container of records into the corresponding timely dataflow [StreamCore], at the current epoch.
+    ///
+    /// The container is handed to the dataflow as it is, without pushing its records one at a time
+    /// through the container builder, which suits records already assembled in a container, for
+    /// example from reading a file or a network frame. Like [`Self::send_batch`], this first flushes
+    /// records previously sent with `send`, to keep the insertion order, and empty containers are
+    /// not sent. The container is sent at the current epoch, and counts for its records as if they
+    /// were sent one at a time. Only when there is more than one downstream operator is the
+    /// container copied, for each but the last.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{Input, Inspect, Probe};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     let seen = std::rc::Rc::new(std::cell::Cell::new(0));
+    ///     let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+    ///         let (input, stream) = scope.new_input::<u64>();
+    ///         let seen = std::rc::Rc::clone(&seen);
+    ///         let probe = stream.inspect_batch(move |_time, data| seen.set(seen.get() + data.len()))
+    ///                           .probe();
+    ///         (input, probe)
+    ///     });
+    ///
+    ///     for round in 0..10 {
+    ///         input.send_container((0..1000).collect());
+    ///         input.advance_to(round + 1);
+    ///         worker.step_while(|| probe.less_than(input.time()));
+    ///     }
+    ///     assert_eq!(seen.get(), 10_000);
+    /// });
+    /// ```
+    pub fn send_container(&mut self, mut container: CB::Container) {
+        self.send_batch(&mut container);
+    }
+
     /// Advances the current epoch to `next`.
     ///
     /// This method allows timely dataflow to issue progress notifications as it can now determine
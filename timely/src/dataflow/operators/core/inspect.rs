@@ -1,5 +1,7 @@
 //! Extension trait and implementation for observing and action on streamed data.
 
+use std::ops::ControlFlow;
+
 use crate::Container;
 use crate::dataflow::channels::pact::Pipeline;
 use crate::dataflow::{Scope, StreamCore};
@@ -52,6 +54,42 @@ where
         })
     }
 
+    /// Runs a supplied closure on each observed data element, its time, and the input frontier.
+    ///
+    /// The frontier is that of the operator's input as the element is observed. It is read but not
+    /// held back, and the element's time is always greater or equal to some element of it, though
+    /// the frontier may have advanced well beyond the times of other elements seen before.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Map, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .inspect_frontier(|f, t, x| println!("seen at: {:?}\t{:?}\twith frontier {:?}", t, x, f));
+    /// });
+    /// ```
+    fn inspect_frontier<F>(&self, mut func: F) -> Self
+    where
+        F: for<'a> FnMut(&[G::Timestamp], &G::Timestamp, <&'a C as IntoIterator>::Item) + 'static,
+    {
+        use crate::progress::timestamp::Timestamp;
+        let mut frontier = vec![G::Timestamp::minimum()];
+        self.inspect_core(move |event| {
+            match event {
+                Ok((time, data)) => {
+                    for datum in data.into_iter() {
+                        func(&frontier, time, datum);
+                    }
+                }
+                Err(advanced) => {
+                    frontier.clear();
+                    frontier.extend_from_slice(advanced);
+                }
+            }
+        })
+    }
+
     /// Runs a supplied closure on each observed data batch (time and data slice).
     ///
     /// # Examples
@@ -124,6 +162,45 @@ pub trait InspectCore<G: Scope, C> {
     /// });
     /// ```
     fn inspect_container<F>(&self, func: F) -> StreamCore<G, C> where F: FnMut(Result<(&G::Timestamp, &C), &[G::Timestamp]>)+'static;
+
+    /// Runs a supplied closure on each observed container, until it returns `ControlFlow::Break`.
+    ///
+    /// Containers pass through unchanged, including the one for which the closure breaks. From then
+    /// on the operator is halted: it no longer calls the closure, and discards the containers it
+    /// receives rather than passing them on. The operator holds no capabilities, and so its output
+    /// frontier continues to follow its input frontier, and downstream operators complete as they
+    /// would without the discarded data.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::ops::ControlFlow;
+    /// use timely::dataflow::operators::{ToStream, Inspect, InspectCore};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .peek(|_time, data| if data.contains(&3) { ControlFlow::Break(()) } else { ControlFlow::Continue(()) })
+    ///            .inspect(|x| println!("seen: {:?}", x));
+    /// });
+    /// ```
+    fn peek<F>(&self, func: F) -> StreamCore<G, C> where F: FnMut(&G::Timestamp, &C)->ControlFlow<()>+'static;
+
+    /// Runs a supplied closure on the time and record count of each observed container.
+    ///
+    /// The closure sees only the container's time and the number of records it holds, and never its
+    /// records, which makes it cheaper than [`Inspect::inspect_batch`] to observe progress and
+    /// timing, in particular for containers whose records are expensive to access, such as columnar
+    /// containers. Containers pass through unchanged, and are neither cloned nor consumed.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, InspectCore};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .inspect_counts(|time, count| println!("{:?} records at {:?}", count, time));
+    /// });
+    /// ```
+    fn inspect_counts<F>(&self, func: F) -> StreamCore<G, C> where F: FnMut(&G::Timestamp, usize)+'static;
 }
 
 impl<G: Scope, C: Container> InspectCore<G, C> for StreamCore<G, C> {
@@ -148,4 +225,118 @@ impl<G: Scope, C: Container> InspectCore<G, C> for StreamCore<G, C> {
             });
         })
     }
+
+    fn peek<F>(&self, mut func: F) -> StreamCore<G, C>
+        where F: FnMut(&G::Timestamp, &C)->ControlFlow<()>+'static
+    {
+        let mut halted = false;
+        self.unary(Pipeline, "Peek", move |_,_| move |input, output| {
+            input.for_each_time(|time, data| {
+                let mut session = output.session(&time);
+                for container in data {
+                    if !halted {
+                        halted = func(&time, &*container).is_break();
+                        session.give_container(container);
+                    }
+                }
+            });
+        })
+    }
+
+    fn inspect_counts<F>(&self, mut func: F) -> StreamCore<G, C>
+        where F: FnMut(&G::Timestamp, usize)+'static
+    {
+        self.inspect_container(move |event| {
+            if let Ok((time, data)) = event {
+                func(time, usize::try_from(data.record_count()).unwrap_or(0));
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::ops::ControlFlow;
+    use std::rc::Rc;
+
+    use crate::dataflow::operators::{Capture, Input, Inspect, InspectCore, Probe};
+    use crate::dataflow::operators::capture::Extract;
+
+    #[test]
+    fn peek_halts_after_breaking() {
+        let (seen, captured) = crate::execute_directly(|worker| {
+            let seen = Rc::new(RefCell::new(Vec::new()));
+            let seen_inner = Rc::clone(&seen);
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<u64>();
+                let peeked = stream.peek(move |time, data: &Vec<u64>| {
+                    seen_inner.borrow_mut().push((*time, data.clone()));
+                    if data.contains(&2) { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+                });
+                (input, peeked.probe(), peeked.capture())
+            });
+            for round in 0..4 {
+                input.send(round);
+                input.advance_to(round + 1);
+                worker.step_while(|| probe.less_than(input.time()));
+            }
+            // The frontier continues to advance once halted.
+            assert!(!probe.less_than(&4));
+            input.close();
+            worker.step_while(|| !probe.done());
+            (seen.take(), captured)
+        });
+
+        assert_eq!(seen, vec![(0, vec![0]), (1, vec![1]), (2, vec![2])]);
+        assert_eq!(captured.extract(), vec![(0, vec![0]), (1, vec![1]), (2, vec![2])]);
+    }
+
+    #[test]
+    fn inspect_counts_sees_each_container() {
+        let (seen, captured) = crate::execute_directly(|worker| {
+            let seen = Rc::new(RefCell::new(Vec::new()));
+            let seen_inner = Rc::clone(&seen);
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<u64>();
+                let counted = stream.inspect_counts(move |time, count| seen_inner.borrow_mut().push((*time, count)));
+                (input, counted.probe(), counted.capture())
+            });
+            input.send_batch(&mut vec![1, 2, 3]);
+            input.advance_to(2);
+            input.send_batch(&mut vec![4]);
+            input.close();
+            worker.step_while(|| !probe.done());
+            (seen.take(), captured)
+        });
+
+        assert_eq!(seen, vec![(0, 3), (2, 1)]);
+        assert_eq!(captured.extract(), vec![(0, vec![1, 2, 3]), (2, vec![4])]);
+    }
+
+    #[test]
+    fn inspect_frontier_sees_the_input_frontier() {
+        let seen = crate::execute_directly(|worker| {
+            let seen = Rc::new(RefCell::new(Vec::new()));
+            let seen_inner = Rc::clone(&seen);
+            let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<u64>();
+                let probe = stream
+                    .inspect_frontier(move |frontier, time, datum| seen_inner.borrow_mut().push((frontier.to_vec(), *time, *datum)))
+                    .probe();
+                (input, probe)
+            });
+            input.send(1);
+            input.flush();
+            worker.step_while(|| seen.borrow().is_empty());
+            input.advance_to(5);
+            input.send(2);
+            worker.step_while(|| probe.less_than(input.time()));
+            input.close();
+            worker.step_while(|| !probe.done());
+            seen.take()
+        });
+
+        assert_eq!(seen, vec![(vec![0], 0, 1), (vec![5], 5, 2)]);
+    }
 }
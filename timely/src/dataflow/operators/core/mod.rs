@@ -3,6 +3,7 @@
 
 pub mod capture;
 pub mod concat;
+pub mod convert;
 pub mod enterleave;
 pub mod exchange;
 pub mod feedback;
@@ -15,12 +16,14 @@ pub mod partition;
 pub mod probe;
 pub mod rc;
 pub mod reclock;
+pub mod sink;
 pub mod to_stream;
 pub mod unordered_input;
 
 pub use capture::Capture;
 pub use concat::{Concat, Concatenate};
-pub use enterleave::{Enter, Leave};
+pub use convert::ConvertContainer;
+pub use enterleave::{Enter, Leave, LeaveWithTime};
 pub use exchange::Exchange;
 pub use feedback::{Feedback, LoopVariable, ConnectLoop};
 pub use filter::Filter;
@@ -32,4 +35,5 @@ pub use partition::Partition;
 pub use probe::Probe;
 pub use to_stream::{ToStream, ToStreamBuilder};
 pub use reclock::Reclock;
+pub use sink::{TrySink, FrontierSink, SinkHandle};
 pub use unordered_input::{UnorderedInput, UnorderedHandle};
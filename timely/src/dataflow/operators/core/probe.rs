@@ -133,7 +133,7 @@ impl<G: Scope, C: Container> Probe<G, C> for StreamCore<G, C> {
 /// Reports information about progress at the probe.
 #[derive(Debug)]
 pub struct Handle<T:Timestamp> {
-    frontier: Rc<RefCell<MutableAntichain<T>>>
+    pub(crate) frontier: Rc<RefCell<MutableAntichain<T>>>
 }
 
 impl<T: Timestamp> Handle<T> {
@@ -10,6 +10,12 @@ use std::rc::Rc;
 pub trait SharedStream<S: Scope, C> {
     /// Convert a stream into a stream of shared data
     ///
+    /// A stream with several consumers clones each container for all but one of them. Shared
+    /// containers are reference counted, and cloning them only increments the count, which makes
+    /// fanning out large containers to many read-only consumers cheap: the stream's `Tee` clones
+    /// the `Rc` for each consumer, and the container itself is not cloned. Consumers that need to
+    /// mutate the data can use [`UnsharedStream::unshared`] to recover owned containers.
+    ///
     /// # Examples
     /// ```
     /// use timely::dataflow::operators::{ToStream, InspectCore};
@@ -39,13 +45,104 @@ impl<S: Scope, C: Container> SharedStream<S, C> for StreamCore<S, C> {
     }
 }
 
+/// Convert a stream of shared containers back into a stream of owned containers
+pub trait UnsharedStream<S: Scope, C> {
+    /// Convert a stream of shared data into a stream of owned data, copying on write.
+    ///
+    /// Each container is moved out of its `Rc` if this is the only remaining reference, and
+    /// cloned otherwise, so mutations downstream are never observed by other consumers.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, InspectCore};
+    /// use timely::dataflow::operators::rc::{SharedStream, UnsharedStream};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .shared()
+    ///            .unshared()
+    ///            .inspect_container(|x| println!("seen: {:?}", x));
+    /// });
+    /// ```
+    fn unshared(&self) -> StreamCore<S, C>;
+}
+
+impl<S: Scope, C: Container> UnsharedStream<S, C> for StreamCore<S, Rc<C>> {
+    fn unshared(&self) -> StreamCore<S, C> {
+        self.unary(Pipeline, "Unshared", move |_, _| {
+            move |input, output| {
+                input.for_each_time(|time, data| {
+                    let mut session = output.session(&time);
+                    for shared in data {
+                        let mut owned = Rc::unwrap_or_clone(std::mem::take(shared));
+                        session.give_container(&mut owned);
+                    }
+                });
+            }
+        })
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use crate::container::Accountable;
     use crate::dataflow::channels::pact::Pipeline;
     use crate::dataflow::operators::capture::Extract;
     use crate::dataflow::operators::rc::SharedStream;
+    use crate::dataflow::operators::core::Input;
     use crate::dataflow::operators::{Capture, Concatenate, InspectCore, Operator, ToStream};
 
+    /// A container that counts its clones.
+    #[derive(Default)]
+    struct Counted {
+        data: Vec<u64>,
+        clones: Rc<Cell<usize>>,
+    }
+
+    impl Clone for Counted {
+        fn clone(&self) -> Self {
+            self.clones.set(self.clones.get() + 1);
+            Counted { data: self.data.clone(), clones: Rc::clone(&self.clones) }
+        }
+    }
+
+    impl Accountable for Counted {
+        fn record_count(&self) -> i64 { self.data.len() as i64 }
+    }
+
+    /// The number of clones of a container sent to `consumers` consumers, of a shared stream if `shared`.
+    fn clones_across(consumers: usize, shared: bool) -> usize {
+        crate::execute_directly(move |worker| {
+            let clones = Rc::new(Cell::new(0));
+            let mut input = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<Counted>();
+                if shared {
+                    let stream = stream.shared();
+                    for _ in 0..consumers { stream.inspect_container(|_| {}); }
+                }
+                else {
+                    for _ in 0..consumers { stream.inspect_container(|_| {}); }
+                }
+                input
+            });
+            input.send_container(Counted { data: (0..1000).collect(), clones: Rc::clone(&clones) });
+            input.close();
+            while worker.step() { }
+            clones.get()
+        })
+    }
+
+    #[test]
+    fn shared_fan_out_does_not_clone_containers() {
+        for consumers in [1, 2, 5] {
+            assert_eq!(clones_across(consumers, false), consumers - 1);
+            assert_eq!(clones_across(consumers, true), 0);
+        }
+    }
+
     #[test]
     fn test_shared() {
         let output = crate::example(|scope| {
@@ -76,4 +173,27 @@ mod test {
         output.dedup();
         assert_eq!(output.len(), 1);
     }
+
+    #[test]
+    fn test_unshared_copy_on_write() {
+        use crate::dataflow::operators::rc::UnsharedStream;
+        let output = crate::example(|scope| {
+            let shared = (0..10).to_stream(scope).container::<Vec<_>>().shared();
+            let mutated = shared.unshared().unary(Pipeline, "mutate", |_, _| {
+                move |input, output| {
+                    input.for_each_time(|time, data| {
+                        let mut session = output.session(&time);
+                        for container in data {
+                            for datum in container.iter_mut() { *datum += 100; }
+                            session.give_container(container);
+                        }
+                    });
+                }
+            });
+            scope.concatenate([mutated, shared.unshared()]).capture()
+        });
+        let output = &mut output.extract()[0].1;
+        output.sort();
+        assert_eq!(output, &(0..10).chain(100..110).collect::<Vec<_>>());
+    }
 }
@@ -0,0 +1,181 @@
+//! Terminal consumers of streams with fallible logic, or that observe the completion of times.
+
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use crate::Container;
+use crate::dataflow::channels::pact::ParallelizationContract;
+use crate::dataflow::operators::generic::Operator;
+use crate::dataflow::{Scope, StreamCore};
+
+/// Consumes a stream with fallible logic.
+pub trait TrySink<G: Scope, C: Container> {
+    /// Hands each container of the stream, with its time, to `logic`, and stops consuming on its first error.
+    ///
+    /// Each time the operator is scheduled it drains all available input from `pact`, calling `logic`
+    /// once for each received container, which may for example write it to a file or database and
+    /// block until the write completes. The operator has no output, and unlike `inspect` it takes
+    /// ownership of the containers.
+    ///
+    /// The first error returned by `logic` is recorded in the returned [`SinkHandle`]. From then on
+    /// `logic` is no longer called, and input is discarded as it arrives, so that upstream operators
+    /// and the dataflow as a whole can still run to completion. Error recovery is up to the caller,
+    /// which can check the handle as it steps the worker and, for example, stop introducing input or
+    /// drop the dataflow.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, TrySink};
+    /// use timely::dataflow::channels::pact::Pipeline;
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     let handle = worker.dataflow::<u64,_,_>(|scope| {
+    ///         (0..10).to_stream(scope)
+    ///                .try_sink(Pipeline, "Writer", |_time, data: Vec<u64>| {
+    ///                    if data.contains(&7) { Err(format!("cannot write {:?}", data)) }
+    ///                    else { Ok(()) }
+    ///                })
+    ///     });
+    ///     while worker.step() { }
+    ///     assert!(handle.take_error().unwrap().starts_with("cannot write"));
+    /// });
+    /// ```
+    fn try_sink<E, L, P>(&self, pact: P, name: &str, logic: L) -> SinkHandle<E>
+    where
+        E: 'static,
+        L: FnMut(G::Timestamp, C) -> Result<(), E>+'static,
+        P: ParallelizationContract<G::Timestamp, C>;
+}
+
+impl<G: Scope, C: Container> TrySink<G, C> for StreamCore<G, C> {
+    fn try_sink<E, L, P>(&self, pact: P, name: &str, mut logic: L) -> SinkHandle<E>
+    where
+        E: 'static,
+        L: FnMut(G::Timestamp, C) -> Result<(), E>+'static,
+        P: ParallelizationContract<G::Timestamp, C>,
+    {
+        let handle = SinkHandle { error: Rc::new(RefCell::new(None)) };
+        let error = Rc::clone(&handle.error);
+        let mut failed = false;
+        self.sink(pact, name, move |(input, _frontier)| {
+            input.for_each(|time, data| {
+                if !failed {
+                    if let Err(e) = logic(time.time().clone(), std::mem::take(data)) {
+                        *error.borrow_mut() = Some(e);
+                        failed = true;
+                    }
+                }
+            });
+        });
+        handle
+    }
+}
+
+/// Consumes a stream, and reports when the times of its data are complete.
+pub trait FrontierSink<G: Scope, C: Container> {
+    /// Hands each container of the stream to `on_data`, and each time at which data arrived to `on_frontier` once it is complete.
+    ///
+    /// A time is complete once the input frontier is no longer less or equal to it, at which point no
+    /// more data at that time, or at any earlier time, can arrive. `on_frontier` is called once for each
+    /// time at which data arrived, after `on_data` has been called for all data at that time and at
+    /// all times less than it, which makes it the place to commit the effects of `on_data`. Times that
+    /// complete in the same invocation of the operator are reported in the order of the timestamp type.
+    /// Times at which no data arrived are not reported.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    /// use timely::dataflow::operators::{Input, FrontierSink};
+    /// use timely::dataflow::channels::pact::Pipeline;
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     let staged = Rc::new(RefCell::new(Vec::new()));
+    ///     let committed = Rc::new(RefCell::new(Vec::new()));
+    ///     let (staged1, staged2, committed1) = (Rc::clone(&staged), Rc::clone(&staged), Rc::clone(&committed));
+    ///     let mut input = worker.dataflow::<u64,_,_>(|scope| {
+    ///         let (input, stream) = scope.new_input::<u64>();
+    ///         stream.sink_with_frontier(
+    ///             Pipeline,
+    ///             "Transactional",
+    ///             move |time, data: Vec<u64>| staged1.borrow_mut().extend(data.into_iter().map(|x| (*time, x))),
+    ///             move |time| {
+    ///                 // Commit the staged records at or before `time`.
+    ///                 let mut staged = staged2.borrow_mut();
+    ///                 committed1.borrow_mut().extend(staged.iter().filter(|(t, _)| t <= time).cloned());
+    ///                 staged.retain(|(t, _)| t > time);
+    ///             },
+    ///         );
+    ///         input
+    ///     });
+    ///     for round in 0..3 {
+    ///         input.send(round);
+    ///         input.advance_to(round + 1);
+    ///         worker.step();
+    ///     }
+    ///     input.close();
+    ///     while worker.step() { }
+    ///     assert_eq!(*committed.borrow(), vec![(0, 0), (1, 1), (2, 2)]);
+    /// });
+    /// ```
+    fn sink_with_frontier<D, F, P>(&self, pact: P, name: &str, on_data: D, on_frontier: F)
+    where
+        D: FnMut(&G::Timestamp, C)+'static,
+        F: FnMut(&G::Timestamp)+'static,
+        P: ParallelizationContract<G::Timestamp, C>;
+}
+
+impl<G: Scope, C: Container> FrontierSink<G, C> for StreamCore<G, C> {
+    fn sink_with_frontier<D, F, P>(&self, pact: P, name: &str, mut on_data: D, mut on_frontier: F)
+    where
+        D: FnMut(&G::Timestamp, C)+'static,
+        F: FnMut(&G::Timestamp)+'static,
+        P: ParallelizationContract<G::Timestamp, C>,
+    {
+        // Times at which data arrived that are not yet complete.
+        let mut pending: Vec<G::Timestamp> = Vec::new();
+        let mut complete = Vec::new();
+        self.sink(pact, name, move |(input, frontier)| {
+            input.for_each(|time, data| {
+                if !pending.contains(time.time()) {
+                    pending.push(time.time().clone());
+                }
+                on_data(time.time(), std::mem::take(data));
+            });
+            pending.retain(|time| {
+                let done = !frontier.less_equal(time);
+                if done { complete.push(time.clone()); }
+                !done
+            });
+            complete.sort();
+            for time in complete.drain(..) {
+                on_frontier(&time);
+            }
+        });
+    }
+}
+
+/// Reports the error, if any, that stopped a [`TrySink`] operator.
+pub struct SinkHandle<E> {
+    error: Rc<RefCell<Option<E>>>,
+}
+
+impl<E> SinkHandle<E> {
+    /// Returns `true` if the operator has stopped on an error that has not been taken.
+    pub fn is_failed(&self) -> bool {
+        self.error.borrow().is_some()
+    }
+
+    /// Takes the error that stopped the operator, if any.
+    ///
+    /// The operator remains stopped once its error has been taken.
+    pub fn take_error(&self) -> Option<E> {
+        self.error.borrow_mut().take()
+    }
+}
+
+impl<E> Clone for SinkHandle<E> {
+    fn clone(&self) -> Self {
+        SinkHandle { error: Rc::clone(&self.error) }
+    }
+}
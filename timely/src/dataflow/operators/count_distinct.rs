@@ -0,0 +1,159 @@
+//! Extension methods for `Stream` that approximately count distinct records.
+
+use std::hash::Hash;
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::operators::sketch::{hash_of, summarize_by_key, HyperLogLog};
+
+/// Extension trait for `Stream`.
+pub trait CountDistinctApprox<G: Scope, D: Data> {
+    /// Estimates the number of distinct records for each key and time.
+    ///
+    /// Each worker summarizes its records in a [`HyperLogLog`] sketch of the given `precision`
+    /// per key and time. Once the input frontier has passed a time, the sketches for that time are
+    /// exchanged by key and merged, and an estimate `(key, count)` is produced for each key. Memory
+    /// use is proportional to the number of distinct keys times the sketch size, `2^precision`
+    /// bytes, rather than to the number of distinct records.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, CountDistinctApprox, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10_000u64).to_stream(scope)
+    ///                   .count_distinct_approx(|x| x % 2, 12)
+    ///                   .inspect(|(parity, count)| println!("about {} distinct values with parity {}", count, parity));
+    /// });
+    /// ```
+    fn count_distinct_approx<K, F>(&self, key_fn: F, precision: u8) -> Stream<G, (K, u64)>
+    where
+        D: Hash,
+        K: ExchangeData+Hash+Eq,
+        F: FnMut(&D)->K+'static;
+
+    /// Estimates the number of distinct values `value_fn` produces for the records of each key and time.
+    ///
+    /// This is [`count_distinct_approx`](CountDistinctApprox::count_distinct_approx), except that
+    /// the sketch of each key summarizes the values of its records rather than the records, for
+    /// example the visitors of each page of a stream of visits. Each worker's sketches are merged
+    /// with those of the other workers, and so a value seen by several workers is counted once.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, CountDistinctApprox, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     // Visits `(page, visitor)`, of which each page has 100 distinct visitors.
+    ///     (0..10_000u64).map(|x| (x % 3, x % 100))
+    ///                   .to_stream(scope)
+    ///                   .count_distinct_approx_by(|(page, _)| *page, |(_, visitor)| *visitor, 12)
+    ///                   .inspect(|(page, count)| println!("about {} distinct visitors of page {}", count, page));
+    /// });
+    /// ```
+    fn count_distinct_approx_by<K, V, F, L>(&self, key_fn: F, value_fn: L, precision: u8) -> Stream<G, (K, u64)>
+    where
+        K: ExchangeData+Hash+Eq,
+        V: Hash,
+        F: FnMut(&D)->K+'static,
+        L: FnMut(&D)->V+'static;
+}
+
+impl<G: Scope<Timestamp: Hash>, D: Data> CountDistinctApprox<G, D> for Stream<G, D> {
+    fn count_distinct_approx<K, F>(&self, mut key_fn: F, precision: u8) -> Stream<G, (K, u64)>
+    where
+        D: Hash,
+        K: ExchangeData+Hash+Eq,
+        F: FnMut(&D)->K+'static,
+    {
+        // Validate the precision while constructing the dataflow, rather than when running it.
+        drop(HyperLogLog::new(precision));
+
+        summarize_by_key(self, "CountDistinct", hash_of,
+            move |sketches, datum| sketches.entry(key_fn(&datum)).or_insert_with(|| HyperLogLog::new(precision)).insert(&datum),
+            |sketch, other| sketch.merge(&other),
+            |key, sketch| (key, sketch.estimate()),
+        )
+    }
+
+    fn count_distinct_approx_by<K, V, F, L>(&self, mut key_fn: F, mut value_fn: L, precision: u8) -> Stream<G, (K, u64)>
+    where
+        K: ExchangeData+Hash+Eq,
+        V: Hash,
+        F: FnMut(&D)->K+'static,
+        L: FnMut(&D)->V+'static,
+    {
+        // Validate the precision while constructing the dataflow, rather than when running it.
+        drop(HyperLogLog::new(precision));
+
+        summarize_by_key(self, "CountDistinctBy", hash_of,
+            move |sketches, datum| sketches.entry(key_fn(&datum)).or_insert_with(|| HyperLogLog::new(precision)).insert(&value_fn(&datum)),
+            |sketch, other| sketch.merge(&other),
+            |key, sketch| (key, sketch.estimate()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::Config;
+    use crate::dataflow::operators::{Capture, CountDistinctApprox, Input, Probe, ToStream};
+    use crate::dataflow::operators::capture::Extract;
+
+    /// Returns `true` if `estimate` is within 5% of `exact`, well beyond the error of precision 12.
+    fn close(estimate: u64, exact: u64) -> bool {
+        estimate.abs_diff(exact) * 20 <= exact
+    }
+
+    #[test]
+    fn values_are_counted_by_key_and_time() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<(u64, u64)>();
+                let counts = stream.count_distinct_approx_by(|(key, _)| *key, |(_, value)| *value, 12);
+                (input, counts.probe(), counts.capture())
+            });
+            // Key 0 has 1000 distinct values, each repeated, and key 1 has 5000; at time 1 key 0 has 10.
+            for value in 0..1000 { input.send((0, value)); input.send((0, value)); }
+            for value in 0..5000 { input.send((1, value)); }
+            input.advance_to(1);
+            for value in 0..30 { input.send((0, value % 10)); }
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        let counts = captured.extract();
+        assert_eq!(counts.len(), 2);
+        let (time0, ref counts0) = counts[0];
+        assert_eq!((time0, counts0.len()), (0, 2));
+        assert!(close(counts0[0].1, 1000) && close(counts0[1].1, 5000), "estimates {:?}", counts0);
+        assert_eq!(counts[1], (1, vec![(0, 10)]));
+    }
+
+    #[test]
+    fn values_seen_by_several_workers_are_counted_once() {
+        let (send, recv) = std::sync::mpsc::channel();
+        let send = Arc::new(Mutex::new(send));
+        crate::execute(Config::process(3), move |worker| {
+            let send = send.lock().unwrap().clone();
+            worker.dataflow::<u64,_,_>(|scope| {
+                // Every worker sees visitors 0..2000 of page 0, and its own 1000 visitors of page 1.
+                let first = 1000 * scope.index() as u64;
+                (0..2000u64).map(|visitor| (0, visitor))
+                    .chain((first..first + 1000).map(|visitor| (1, visitor)))
+                    .to_stream(scope)
+                    .count_distinct_approx_by(|(page, _)| *page, |(_, visitor)| *visitor, 12)
+                    .capture_into(send);
+            });
+        }).unwrap();
+
+        let counts = recv.extract();
+        assert_eq!(counts.len(), 1);
+        let (_time, ref pages) = counts[0];
+        assert_eq!(pages.iter().map(|(page, _)| *page).collect::<Vec<_>>(), vec![0, 1]);
+        assert!(close(pages[0].1, 2000) && close(pages[1].1, 3000), "estimates {:?}", pages);
+    }
+}
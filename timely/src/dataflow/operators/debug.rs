@@ -0,0 +1,56 @@
+//! Operators that check the invariants of streams, to pinpoint the operators that violate them.
+
+use crate::Container;
+use crate::dataflow::{Scope, StreamCore};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::Operator;
+
+/// Extension trait for `StreamCore`.
+pub trait AssertMonotone<G: Scope, C: Container> {
+    /// Passes the stream through unchanged, and panics if records arrive at a time the input frontier has passed.
+    ///
+    /// Records at a time must not arrive once the input frontier is no longer less or equal to that
+    /// time. An upstream operator that sends records at times it holds no capability for, such as a
+    /// custom source with an error in its bookkeeping, can violate this, and downstream operators then
+    /// misbehave in ways that are hard to trace back. Placed after such an operator, `assert_monotone`
+    /// panics with the offending time and the frontier it should have been greater or equal to.
+    ///
+    /// The check is only made in builds with debug assertions. In other builds the method returns the
+    /// stream itself, without adding an operator.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect};
+    /// use timely::dataflow::operators::debug::AssertMonotone;
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .assert_monotone()
+    ///            .inspect(|x| println!("seen: {:?}", x));
+    /// });
+    /// ```
+    fn assert_monotone(&self) -> StreamCore<G, C>;
+}
+
+impl<G: Scope, C: Container> AssertMonotone<G, C> for StreamCore<G, C> {
+    fn assert_monotone(&self) -> StreamCore<G, C> {
+        if !cfg!(debug_assertions) {
+            return self.clone();
+        }
+        self.unary_frontier(Pipeline, "AssertMonotone", move |_capability, info| {
+            let operator = info.global_id;
+            move |(input, frontier), output| {
+                input.for_each_time(|time, data| {
+                    assert!(
+                        frontier.less_equal(time.time()),
+                        "AssertMonotone (operator {}): expected a time greater or equal to the input frontier {:?}, got {:?}",
+                        operator,
+                        frontier.frontier(),
+                        time.time(),
+                    );
+                    output.session(&time).give_containers(data);
+                });
+            }
+        })
+    }
+}
@@ -0,0 +1,240 @@
+//! Extension methods for `Stream` that maintain the keys with the greatest exponentially decaying counts.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Exchange;
+use crate::dataflow::operators::{Capability, Map};
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::generic::stash::Stash;
+use crate::dataflow::operators::sketch::hash_of;
+
+/// The score below which a key is forgotten, as it is then indistinguishable from a new key.
+const FORGET_BELOW: f64 = 1e-3;
+
+/// Extension trait for `Stream`.
+pub trait DecayingTopK<G: Scope<Timestamp = u64>, D: Data> {
+    /// Maintains the `k` keys of `key_fn` with the greatest decaying counts, and produces them, with their scores, whenever they change.
+    ///
+    /// Each key has a score that every record of the key raises by one, and that decays
+    /// continuously, losing half its value every `half_life` times, so that the leaderboard favors
+    /// keys with recent records: of two keys with the same number of records, the one whose
+    /// records are more recent ranks higher. Decay is by the records' times, not by wall-clock
+    /// time, and so leaderboards are the same in each run and however the records are delivered.
+    ///
+    /// The leaderboard is produced as `(key, score)` pairs, greatest score first and keys of
+    /// equal scores in increasing order, with scores decayed to the time it is produced at. As all
+    /// scores decay at the same rate, only records change the order of keys. The records of each
+    /// time are applied once the input frontier has passed the time, in the order of the times, and
+    /// if the times completed by a frontier advance change the keys of the leaderboard or their
+    /// order, it is produced once, at the greatest of those times with records.
+    ///
+    /// Records are exchanged by key, and each worker sends the `k` keys with the greatest scores
+    /// among its own to the first worker, which forms the leaderboard from them. Scores persist
+    /// across times, and a key is forgotten once its score has decayed below a thousandth as of
+    /// the input frontier, so memory is proportional to the active keys, those with records within
+    /// about ten half-lives, and the first worker retains `k` keys for each worker.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `half_life` is not positive, or `k` is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Delay, DecayingTopK, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     // Each page is visited ten times, page `x` at times `x` to `x + 9`.
+    ///     (0..100u64).map(|visit| (visit / 10, visit / 10 + visit % 10))
+    ///         .to_stream(scope)
+    ///         .delay(|(_, time), _| *time)
+    ///         .decaying_top_k(|(page, _)| *page, 5.0, 3)
+    ///         .inspect(|leaders| println!("trending pages: {:?}", leaders));
+    /// });
+    /// ```
+    fn decaying_top_k<K, F>(&self, key_fn: F, half_life: f64, k: usize) -> Stream<G, Vec<(K, f64)>>
+    where
+        K: ExchangeData+Hash+Ord,
+        F: Fn(&D)->K+'static;
+}
+
+impl<G: Scope<Timestamp = u64>, D: Data> DecayingTopK<G, D> for Stream<G, D> {
+    fn decaying_top_k<K, F>(&self, key_fn: F, half_life: f64, k: usize) -> Stream<G, Vec<(K, f64)>>
+    where
+        K: ExchangeData+Hash+Ord,
+        F: Fn(&D)->K+'static,
+    {
+        assert!(half_life > 0.0, "DecayingTopK: half_life must be positive");
+        assert!(k > 0, "DecayingTopK: k must be positive");
+        // The rate at which scores decay, per time.
+        let decay = std::f64::consts::LN_2 / half_life;
+
+        let local = self.map(move |datum| key_fn(&datum)).unary_frontier(Exchange::new(|key: &K| hash_of(key)), "DecayingTopKLocal", move |_capability, info| {
+            let index = info.index;
+            // For each incomplete time, a capability for it and the keys of its records.
+            let mut pending = Stash::<Capability<u64>, Vec<K>>::new();
+            // For each active key, its score as of the time of its latest record, and that time.
+            let mut scores = HashMap::<K, (f64, u64)>::new();
+            move |(input, frontier), output| {
+                input.for_each_time(|time, data| {
+                    pending.get_or_retain(&time, Vec::new).extend(data.flat_map(|d| d.drain(..)));
+                });
+
+                let mut latest = None;
+                pending.release(|time| !frontier.less_equal(time), |capability, keys| {
+                    let time = *capability.time();
+                    for key in keys {
+                        let (score, at) = scores.entry(key).or_insert((0.0, time));
+                        *score = *score * (-decay * (time - *at) as f64).exp() + 1.0;
+                        *at = time;
+                    }
+                    latest = Some(capability);
+                });
+                if let Some(capability) = latest {
+                    let leaders = leaders(scores.iter().map(|(key, (score, at))| (key.clone(), *score, *at)), k, decay);
+                    output.session(&capability).give((index, leaders));
+                }
+
+                // Forget keys whose scores will have decayed away by the time of any future record.
+                if let Some(earliest) = frontier.frontier().first() {
+                    scores.retain(|_, (score, at)| *score * (-decay * earliest.saturating_sub(*at) as f64).exp() >= FORGET_BELOW);
+                }
+                else {
+                    scores.clear();
+                }
+            }
+        });
+
+        local.unary_frontier(Exchange::new(|_| 0), "DecayingTopK", move |_capability, _info| {
+            // For each incomplete time, a capability for it and the leaders workers sent at it.
+            let mut pending = Stash::<Capability<u64>, Vec<(usize, Vec<(K, f64, u64)>)>>::new();
+            // The latest leaders each worker sent.
+            let mut candidates = HashMap::<usize, Vec<(K, f64, u64)>>::new();
+            // The keys of the leaderboard last produced, in order.
+            let mut produced = Vec::<K>::new();
+            move |(input, frontier), output| {
+                input.for_each_time(|time, data| {
+                    pending.get_or_retain(&time, Vec::new).extend(data.flat_map(|d| d.drain(..)));
+                });
+
+                let mut latest = None;
+                pending.release(|time| !frontier.less_equal(time), |capability, sent| {
+                    candidates.extend(sent);
+                    latest = Some(capability);
+                });
+                if let Some(capability) = latest {
+                    let leaders = leaders(candidates.values().flatten().cloned(), k, decay);
+                    if leaders.iter().map(|(key, _, _)| key).ne(produced.iter()) {
+                        produced = leaders.iter().map(|(key, _, _)| key.clone()).collect();
+                        let time = *capability.time();
+                        let leaderboard = leaders.into_iter().map(|(key, score, at)| (key, score * (-decay * time.saturating_sub(at) as f64).exp())).collect::<Vec<_>>();
+                        output.session(&capability).give(leaderboard);
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// The `k` keys with the greatest scores as of any one time, greatest first, of keys with their score as of a time.
+fn leaders<K: Ord>(scores: impl Iterator<Item = (K, f64, u64)>, k: usize, decay: f64) -> Vec<(K, f64, u64)> {
+    // Scores as of different times compare by their logarithms advanced to a common time.
+    let rank = |score: f64, at: u64| score.ln() + decay * at as f64;
+    let mut leaders = scores.collect::<Vec<_>>();
+    leaders.sort_by(|x, y| rank(y.1, y.2).total_cmp(&rank(x.1, x.2)).then_with(|| x.0.cmp(&y.0)));
+    leaders.truncate(k);
+    leaders
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::Config;
+    use crate::dataflow::operators::{Capture, DecayingTopK, Input, Map, Probe};
+    use crate::dataflow::operators::capture::{Event, Extract};
+
+    #[test]
+    fn recent_keys_rank_above_older_keys_with_equal_counts() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<char>();
+                let leaders = stream.decaying_top_k(|key| *key, 5.0, 2);
+                (input, leaders.probe(), leaders.capture())
+            });
+            // Five records of 'a' at time 0, then five of 'b' at time 10, and another of 'b' at time 12.
+            for _ in 0..5 { input.send('a'); }
+            input.advance_to(10);
+            worker.step_while(|| probe.less_than(input.time()));
+            for _ in 0..5 { input.send('b'); }
+            input.advance_to(12);
+            worker.step_while(|| probe.less_than(input.time()));
+            input.send('b');
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        let leaderboards = captured.try_iter().filter_map(|event| match event {
+            Event::Messages(time, data) => Some(data.into_iter().map(move |leaders| (time, leaders))),
+            Event::Progress(_) => None,
+        }).flatten().collect::<Vec<_>>();
+        let keys = leaderboards.iter().map(|(time, leaders)| (*time, leaders.iter().map(|(key, _)| *key).collect::<Vec<_>>())).collect::<Vec<_>>();
+        // The record of 'b' at time 12 does not change the order of keys, and the leaderboard is not produced again.
+        assert_eq!(keys, vec![(0, vec!['a']), (10, vec!['b', 'a'])]);
+        // Scores are decayed to the time of the leaderboard, two half-lives after the records of 'a'.
+        let scores = leaderboards[1].1.iter().map(|(_, score)| *score).collect::<Vec<_>>();
+        assert!((scores[0] - 5.0).abs() < 1e-9 && (scores[1] - 1.25).abs() < 1e-9, "{:?}", scores);
+    }
+
+    #[test]
+    fn completed_times_produce_one_leaderboard() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<char>();
+                let leaders = stream.decaying_top_k(|key| *key, 1.0, 1).map(|leaders| leaders.into_iter().map(|(key, _)| key).collect::<Vec<_>>());
+                (input, leaders.probe(), leaders.capture())
+            });
+            // Three times complete in one frontier advance, and 'b' overtakes 'a' in the second.
+            input.send('a');
+            input.advance_to(1);
+            input.send('b');
+            input.advance_to(2);
+            input.send('a');
+            input.send('b');
+            input.advance_to(3);
+            worker.step_while(|| probe.less_than(input.time()));
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        assert_eq!(captured.extract(), vec![(2, vec![vec!['b']])]);
+    }
+
+    #[test]
+    fn leaderboards_merge_the_keys_of_all_workers() {
+        let (send, recv) = std::sync::mpsc::channel();
+        let send = Arc::new(Mutex::new(send));
+        crate::execute(Config::process(3), move |worker| {
+            let send = send.lock().unwrap().clone();
+            let index = worker.index() as u64;
+            worker.dataflow::<u64,_,_>(|scope| {
+                let (mut input, stream) = scope.new_input::<u64>();
+                stream.decaying_top_k(|key| *key, 10.0, 3)
+                      .map(|leaders| leaders.into_iter().map(|(key, score)| (key, (score * 1000.0).round() as u64)).collect::<Vec<_>>())
+                      .capture_into(send);
+                // Key `x` has `x` records, spread over the workers.
+                for key in 0..10u64 {
+                    for record in 0..key {
+                        if record % 3 == index { input.send(key); }
+                    }
+                }
+            });
+        }).unwrap();
+
+        assert_eq!(recv.extract(), vec![(0, vec![vec![(9, 9000), (8, 8000), (7, 7000)]])]);
+    }
+}
@@ -0,0 +1,172 @@
+//! Extension methods for `Stream` that drop records whose keys have been seen recently.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::rc::Rc;
+
+use crate::ExchangeData;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Exchange;
+use crate::dataflow::operators::Capability;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::generic::stash::Stash;
+use crate::dataflow::operators::sketch::hash_of;
+use crate::order::{PartialOrder, TotalOrder};
+use crate::progress::{PathSummary, Timestamp};
+
+/// Extension trait for `Stream`.
+pub trait DedupTtl<G: Scope, D: ExchangeData> {
+    /// Drops records whose key was produced within the time-to-live `ttl` before them.
+    ///
+    /// A record at time `s` is produced unless a record with the same key was produced at a time
+    /// `t` with `s` less than `t` advanced by `ttl`; a repeat at `t` advanced by `ttl` or later is
+    /// produced again, and its key is then remembered from `s`. Of the records with a key at one
+    /// time, the first to arrive is produced. Unlike per-time deduplication, the time-to-live may
+    /// span many times, and unlike global deduplication, keys are eventually forgotten.
+    ///
+    /// Records are exchanged by key, and each worker buffers the records of a time until its input
+    /// frontier has passed the time, so that times are applied in order. A key is forgotten once the
+    /// frontier reaches the time its time-to-live expires, which bounds the keys retained by those
+    /// produced within `ttl` of the frontier. Keys whose time cannot be advanced by `ttl`, because
+    /// the result would overflow the timestamp, are retained until the input completes.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, DedupTtl, Delay, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10u64).to_stream(scope)
+    ///               .delay(|x, _| *x)
+    ///               .dedup_ttl(|_| "key", 4)
+    ///               .inspect(|x| assert_eq!(x % 4, 0));
+    /// });
+    /// ```
+    fn dedup_ttl<K, F>(&self, key_fn: F, ttl: <G::Timestamp as Timestamp>::Summary) -> Stream<G, D>
+    where
+        G::Timestamp: TotalOrder,
+        K: Hash+Eq+'static,
+        F: Fn(&D)->K+'static;
+}
+
+impl<G: Scope, D: ExchangeData> DedupTtl<G, D> for Stream<G, D> {
+    fn dedup_ttl<K, F>(&self, key_fn: F, ttl: <G::Timestamp as Timestamp>::Summary) -> Stream<G, D>
+    where
+        G::Timestamp: TotalOrder,
+        K: Hash+Eq+'static,
+        F: Fn(&D)->K+'static,
+    {
+        let key_fn = Rc::new(key_fn);
+        let route = Rc::clone(&key_fn);
+        let pact = Exchange::new(move |datum: &D| hash_of(&route(datum)));
+
+        // For each incomplete time, a capability for it and its records.
+        let mut pending = Stash::<Capability<G::Timestamp>, Vec<D>>::new();
+        // For each remembered key, the time its time-to-live expires, or `None` if it never does.
+        let mut expiries = HashMap::<K, Option<G::Timestamp>>::new();
+        self.unary_frontier(pact, "DedupTtl", move |_capability, _info| {
+            move |(input, frontier), output| {
+                input.for_each_time(|time, data| {
+                    pending.get_or_retain(&time, Vec::new).extend(data.flat_map(|d| d.drain(..)));
+                });
+
+                pending.release(|time| !frontier.less_equal(time), |capability, records| {
+                    let time = capability.time();
+                    let mut session = output.session(&capability);
+                    for record in records {
+                        let key = key_fn(&record);
+                        let live = expiries.get(&key).is_some_and(|expiry| expiry.as_ref().is_none_or(|expiry| time.less_than(expiry)));
+                        if !live {
+                            expiries.insert(key, ttl.results_in(time));
+                            session.give(record);
+                        }
+                    }
+                });
+
+                // Forget keys that no future record can repeat within their time-to-live.
+                expiries.retain(|_, expiry| expiry.as_ref().is_none_or(|expiry| frontier.less_than(expiry)));
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::Config;
+    use crate::dataflow::operators::{Capture, Delay, Input, Probe, ToStream};
+    use crate::dataflow::operators::capture::Extract;
+    use super::DedupTtl;
+
+    #[test]
+    fn repeats_are_dropped_until_expired() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<(char, u64)>();
+                let deduped = stream.dedup_ttl(|record| record.0, 3);
+                (input, deduped.probe(), deduped.capture())
+            });
+            for round in 0..10u64 {
+                if [0, 2, 3, 5, 6, 8].contains(&round) { input.send(('a', round)); }
+                // A repeat at the same time is dropped, even with a time-to-live of one time.
+                if round == 4 { input.send(('b', 0)); input.send(('b', 1)); }
+                input.advance_to(round + 1);
+                worker.step_while(|| probe.less_than(input.time()));
+            }
+            captured
+        });
+
+        // A repeat within the time-to-live of the produced record does not extend it.
+        assert_eq!(captured.extract(), vec![(0, vec![('a', 0)]), (3, vec![('a', 3)]), (4, vec![('b', 0)]), (6, vec![('a', 6)])]);
+    }
+
+    #[test]
+    fn times_are_applied_in_order() {
+        let captured = crate::example(|scope| {
+            // Records arrive in reverse order of their times.
+            vec![('a', 5), ('a', 3), ('a', 0)]
+                .to_stream(scope)
+                .delay(|record, _| record.1)
+                .dedup_ttl(|record| record.0, 4)
+                .capture()
+        });
+
+        assert_eq!(captured.extract(), vec![(0, vec![('a', 0)]), (5, vec![('a', 5)])]);
+    }
+
+    #[test]
+    fn keys_without_expiry_are_retained() {
+        let captured = crate::example(|scope| {
+            // The time-to-live of the first record overflows the timestamp.
+            vec![('a', u64::MAX - 2), ('a', u64::MAX)]
+                .to_stream(scope)
+                .delay(|record, _| record.1)
+                .dedup_ttl(|record| record.0, 5)
+                .capture()
+        });
+
+        assert_eq!(captured.extract(), vec![(u64::MAX - 2, vec![('a', u64::MAX - 2)])]);
+    }
+
+    #[test]
+    fn repeats_across_workers_are_dropped() {
+        let (send, recv) = std::sync::mpsc::channel();
+        let send = Arc::new(Mutex::new(send));
+        crate::execute(Config::process(3), move |worker| {
+            let send = send.lock().unwrap().clone();
+            let index = worker.index() as u64;
+            worker.dataflow::<u64,_,_>(move |scope| {
+                // Each worker sends key 'a', and a key of its own.
+                vec![('a', index), (char::from(b'x' + index as u8), index)]
+                    .to_stream(scope)
+                    .dedup_ttl(|record| record.0, 1)
+                    .capture_into(send);
+            });
+        }).unwrap();
+
+        let produced = recv.extract();
+        assert_eq!(produced.len(), 1);
+        let keys = produced[0].1.iter().map(|record| record.0).collect::<Vec<_>>();
+        assert_eq!(keys, vec!['a', 'x', 'y', 'z']);
+    }
+}
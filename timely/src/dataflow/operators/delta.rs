@@ -0,0 +1,186 @@
+//! Extension methods for `Stream` that report the changes between consecutive values of each key.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::Sub;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Exchange;
+use crate::dataflow::operators::generic::operator::{empty, Operator};
+use crate::dataflow::operators::sketch::hash_of;
+
+/// What the first value of a key, without a previous value, produces.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FirstDelta {
+    /// The first value produces nothing, and only sets the previous value.
+    Skip,
+    /// The first value is produced unchanged, as its change from nothing.
+    Raw,
+}
+
+/// Extension trait for `Stream`.
+pub trait DeltaBy<G: Scope, D: ExchangeData> {
+    /// Reports, for each record, the change of its value from the previous value of its key, as `(key, current - previous)`.
+    ///
+    /// `key_fn` and `value_fn` extract the key and the value of each record. Records are exchanged by
+    /// key, and each worker applies the records of its keys in the order in which it receives them,
+    /// regardless of their times, producing each change at the time of the record that made it. The
+    /// previous value of each key persists across times, for as long as the operator runs, and the
+    /// first value of a key produces according to `first`.
+    ///
+    /// Each change is computed by `Sub`, and so a decrease of an unsigned value panics or wraps as its
+    /// subtraction does; counters that may reset call for signed values, or for
+    /// [`DeltaBy::delta_by_with_resets`]. The records of a key should come from a single worker, as
+    /// the records of several workers interleave in an order that may change between runs.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, DeltaBy, Inspect};
+    /// use timely::dataflow::operators::delta::FirstDelta;
+    ///
+    /// timely::example(|scope| {
+    ///     // Readings of a request counter for each host.
+    ///     vec![(1, 10i64), (2, 3), (1, 15), (1, 21)]
+    ///         .to_stream(scope)
+    ///         .delta_by(|(host, _)| *host, |(_, count)| *count, FirstDelta::Skip)
+    ///         .inspect(|(host, delta)| println!("{} served {} requests", host, delta));
+    /// });
+    /// ```
+    fn delta_by<K, V, KF, VF>(&self, key_fn: KF, value_fn: VF, first: FirstDelta) -> Stream<G, (K, V)>
+    where
+        K: ExchangeData+Hash+Eq,
+        V: Data+Sub<Output=V>,
+        KF: Fn(&D)->K+'static,
+        VF: Fn(&D)->V+'static;
+
+    /// Reports changes as [`DeltaBy::delta_by`], forgetting the previous value of each key received on `resets`.
+    ///
+    /// A reset key has no previous value, and so its next value produces according to `first`, as
+    /// if it were the first. Resets are exchanged by key alongside the records, and each worker
+    /// applies the resets it receives in an invocation before the records it receives in the same
+    /// invocation, regardless of their times.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, DeltaBy, Inspect};
+    /// use timely::dataflow::operators::delta::FirstDelta;
+    ///
+    /// timely::example(|scope| {
+    ///     let resets = vec![2].to_stream(scope);
+    ///     vec![(1, 10u64), (2, 3)]
+    ///         .to_stream(scope)
+    ///         .delta_by_with_resets(&resets, |(host, _)| *host, |(_, count)| *count, FirstDelta::Raw)
+    ///         .inspect(|(host, delta)| println!("{} served {} requests", host, delta));
+    /// });
+    /// ```
+    fn delta_by_with_resets<K, V, KF, VF>(&self, resets: &Stream<G, K>, key_fn: KF, value_fn: VF, first: FirstDelta) -> Stream<G, (K, V)>
+    where
+        K: ExchangeData+Hash+Eq,
+        V: Data+Sub<Output=V>,
+        KF: Fn(&D)->K+'static,
+        VF: Fn(&D)->V+'static;
+}
+
+impl<G: Scope, D: ExchangeData> DeltaBy<G, D> for Stream<G, D> {
+    fn delta_by<K, V, KF, VF>(&self, key_fn: KF, value_fn: VF, first: FirstDelta) -> Stream<G, (K, V)>
+    where
+        K: ExchangeData+Hash+Eq,
+        V: Data+Sub<Output=V>,
+        KF: Fn(&D)->K+'static,
+        VF: Fn(&D)->V+'static,
+    {
+        self.delta_by_with_resets(&empty(&self.scope()), key_fn, value_fn, first)
+    }
+
+    fn delta_by_with_resets<K, V, KF, VF>(&self, resets: &Stream<G, K>, key_fn: KF, value_fn: VF, first: FirstDelta) -> Stream<G, (K, V)>
+    where
+        K: ExchangeData+Hash+Eq,
+        V: Data+Sub<Output=V>,
+        KF: Fn(&D)->K+'static,
+        VF: Fn(&D)->V+'static,
+    {
+        let key_fn = Rc::new(key_fn);
+        let route = Rc::clone(&key_fn);
+        let pact = Exchange::new(move |datum: &D| hash_of(&route(datum)));
+
+        // For each key with a value since it was last reset, its latest value.
+        let mut previous = HashMap::<K, V>::new();
+        self.binary(resets, pact, Exchange::new(|key: &K| hash_of(key)), "DeltaBy", move |_capability, _info| move |input, reset_input, output| {
+            reset_input.for_each(|_time, data| {
+                for key in data.drain(..) { previous.remove(&key); }
+            });
+            input.for_each_time(|time, data| {
+                let mut session = output.session(&time);
+                for datum in data.flat_map(|d| d.drain(..)) {
+                    let key = key_fn(&datum);
+                    let value = value_fn(&datum);
+                    let delta = match previous.insert(key.clone(), value.clone()) {
+                        Some(prior) => Some(value - prior),
+                        None if first == FirstDelta::Raw => Some(value),
+                        None => None,
+                    };
+                    if let Some(delta) = delta { session.give((key, delta)); }
+                }
+            });
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dataflow::operators::{Capture, Input, Probe};
+    use crate::dataflow::operators::capture::Extract;
+    use super::{DeltaBy, FirstDelta};
+
+    /// The changes reported, with `first`, for `rounds` of readings and the keys reset before each round, each round at its own time.
+    fn deltas(rounds: Vec<(Vec<char>, Vec<(char, i64)>)>, first: FirstDelta) -> Vec<(u64, Vec<(char, i64)>)> {
+        let captured = crate::execute_directly(move |worker| {
+            let (mut input, mut resets, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<(char, i64)>();
+                let (resets, reset_stream) = scope.new_input::<char>();
+                let deltas = stream.delta_by_with_resets(&reset_stream, |(key, _)| *key, |(_, value)| *value, first);
+                (input, resets, deltas.probe(), deltas.capture())
+            });
+            for (round, (reset, readings)) in rounds.into_iter().enumerate() {
+                input.advance_to(round as u64);
+                resets.advance_to(round as u64);
+                for key in reset { resets.send(key); }
+                worker.step_while(|| probe.less_than(resets.time()));
+                for reading in readings { input.send(reading); }
+            }
+            input.close();
+            resets.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+        captured.extract()
+    }
+
+    #[test]
+    fn changes_persist_across_times() {
+        assert_eq!(deltas(vec![(vec![], vec![('a', 10), ('b', 3), ('a', 15)]), (vec![], vec![('a', 12), ('b', 3)])], FirstDelta::Skip), vec![
+            (0, vec![('a', 5)]),
+            (1, vec![('a', -3), ('b', 0)]),
+        ]);
+    }
+
+    #[test]
+    fn first_values_are_produced_raw() {
+        assert_eq!(deltas(vec![(vec![], vec![('a', 10), ('a', 15)])], FirstDelta::Raw), vec![
+            (0, vec![('a', 5), ('a', 10)]),
+        ]);
+    }
+
+    #[test]
+    fn reset_keys_start_over() {
+        // The reset of `a` forgets its value, and the reset of `c`, without a value, has no effect.
+        assert_eq!(deltas(vec![(vec![], vec![('a', 10), ('b', 3)]), (vec!['a', 'c'], vec![('a', 2), ('b', 4)])], FirstDelta::Raw), vec![
+            (0, vec![('a', 10), ('b', 3)]),
+            (1, vec![('a', 2), ('b', 1)]),
+        ]);
+    }
+}
@@ -0,0 +1,130 @@
+//! Extension methods for `Stream` that report the keys added and removed between epochs.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::rc::Rc;
+
+use crate::ExchangeData;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Exchange;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::sketch::hash_of;
+
+/// Extension trait for `Stream`.
+pub trait DiffBy<G: Scope, D: ExchangeData> {
+    /// Reports, for each epoch, the keys that appeared and disappeared since the previous epoch.
+    ///
+    /// Records are exchanged by `key_fn`, and each worker collects the set of keys present at each
+    /// time. Epochs are the times at which records arrive, considered once the input frontier has
+    /// passed them and in the order of the timestamp type. For each epoch the operator produces
+    /// `(key, 1)` for keys present in it but not in the previous epoch, and `(key, -1)` for keys
+    /// present in the previous epoch but not in it; keys present in both produce nothing. Times at
+    /// which no records arrive are not epochs, and leave the set of keys unchanged.
+    ///
+    /// Only the key set of the previous epoch is retained once an epoch has been reported,
+    /// alongside the key sets of epochs not yet passed by the input frontier.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{Input, DiffBy, Inspect, Probe};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+    ///         let (input, stream) = scope.new_input::<String>();
+    ///         let probe = stream
+    ///             .diff_by(|name| name.clone())
+    ///             .inspect_time(|time, (name, diff)| println!("{:?} at {:?}: {}", name, time, diff))
+    ///             .probe();
+    ///         (input, probe)
+    ///     });
+    ///     input.send("alice".to_string());
+    ///     input.send("bob".to_string());
+    ///     input.advance_to(1);
+    ///     // Produces ("carol", 1) and ("alice", -1) at time 1.
+    ///     input.send("bob".to_string());
+    ///     input.send("carol".to_string());
+    ///     input.advance_to(2);
+    ///     worker.step_while(|| probe.less_than(input.time()));
+    /// });
+    /// ```
+    fn diff_by<K, F>(&self, key_fn: F) -> Stream<G, (K, i64)>
+    where
+        K: ExchangeData+Hash+Eq,
+        F: Fn(&D)->K+'static;
+}
+
+impl<G: Scope<Timestamp: Hash>, D: ExchangeData> DiffBy<G, D> for Stream<G, D> {
+    fn diff_by<K, F>(&self, key_fn: F) -> Stream<G, (K, i64)>
+    where
+        K: ExchangeData+Hash+Eq,
+        F: Fn(&D)->K+'static,
+    {
+        let key_fn = Rc::new(key_fn);
+        let route_fn = Rc::clone(&key_fn);
+        let exchange = Exchange::new(move |datum: &D| hash_of(&route_fn(datum)));
+
+        let mut epochs = HashMap::<G::Timestamp, HashSet<K>>::new();
+        let mut previous = HashSet::<K>::new();
+        self.unary_notify(exchange, "DiffBy", vec![], move |input, output, notificator| {
+            input.for_each_time(|time, data| {
+                let keys = epochs.entry(time.time().clone()).or_default();
+                keys.extend(data.flat_map(|d| d.drain(..)).map(|datum| key_fn(&datum)));
+                notificator.notify_at(time.retain());
+            });
+            notificator.for_each(|time, _, _| {
+                if let Some(current) = epochs.remove(time.time()) {
+                    let mut session = output.session(&time);
+                    session.give_iterator(current.iter().filter(|key| !previous.contains(*key)).map(|key| (key.clone(), 1)));
+                    session.give_iterator(previous.drain().filter(|key| !current.contains(key)).map(|key| (key, -1)));
+                    previous = current;
+                }
+            });
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use crate::dataflow::operators::{Input, Inspect, Probe};
+    use super::DiffBy;
+
+    #[test]
+    fn keys_appear_disappear_and_persist() {
+        crate::execute_directly(|worker| {
+            let diffs = Rc::new(RefCell::new(Vec::new()));
+            let diffs_inner = Rc::clone(&diffs);
+            let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<(u64, u64)>();
+                let probe = stream
+                    .diff_by(|(key, _)| *key)
+                    .inspect_time(move |time, diff| diffs_inner.borrow_mut().push((*time, *diff)))
+                    .probe();
+                (input, probe)
+            });
+
+            let epochs: [&[u64]; 4] = [&[1, 2, 2], &[2, 3], &[2, 3], &[]];
+            for (round, keys) in epochs.iter().enumerate() {
+                for key in keys.iter() {
+                    input.send((*key, round as u64));
+                }
+                input.advance_to(round as u64 + 1);
+                worker.step_while(|| probe.less_than(input.time()));
+            }
+            // An epoch with records re-reports keys only against the previous epoch with records.
+            input.send((1, 4));
+            input.advance_to(10);
+            worker.step_while(|| probe.less_than(input.time()));
+
+            let mut diffs = diffs.borrow().clone();
+            diffs.sort();
+            assert_eq!(diffs, vec![
+                (0, (1, 1)), (0, (2, 1)),
+                (1, (1, -1)), (1, (3, 1)),
+                (4, (1, 1)), (4, (2, -1)), (4, (3, -1)),
+            ]);
+        });
+    }
+}
@@ -0,0 +1,153 @@
+//! Extension methods for `Stream` that retry failed records in later times, and route those that keep failing to a dead-letter queue.
+
+use crate::Data;
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::{Concat, ConnectLoop, Feedback, Map};
+use crate::dataflow::operators::generic::OutputBuilder;
+use crate::dataflow::operators::generic::builder_rc::OperatorBuilder;
+use crate::dataflow::{Scope, Stream};
+use crate::progress::{PathSummary, Timestamp};
+
+/// Extension trait for `Stream`.
+pub trait WithDlq<G: Scope, D: Data> {
+    /// Processes each record with the fallible `process`, retrying failed records `retry_delay` later, up to `max_attempts` attempts in all.
+    ///
+    /// Records that `process` succeeds on are transformed into the first returned stream. A record
+    /// that `process` fails on is fed back through a loop in the dataflow, and attempted again at
+    /// its time advanced by `retry_delay`. Once `max_attempts` attempts have failed, or its time
+    /// cannot be advanced by `retry_delay`, the record and the last error are sent to the second
+    /// returned stream, the dead-letter queue, at the time of the last attempt. Each attempt
+    /// receives a copy of the record.
+    ///
+    /// Unlike [`MapRetry::map_retry`](crate::dataflow::operators::MapRetry::map_retry), which waits
+    /// in real time, retries happen in logical time: a retry starts once the rest of the dataflow
+    /// has reached its time, and a successful retry appears in the output at that later time.
+    /// `retry_delay` should therefore advance times, or retries would circulate without the
+    /// frontier ever moving past them.
+    ///
+    /// Each record is attempted at least once, and possibly several times, and so the effects of
+    /// `process` on failing attempts, for example writes to an external system, may be repeated:
+    /// processing is at-least-once. Each record nonetheless ends in exactly one of the outputs.
+    ///
+    /// The records awaiting a retry are in flight in the loop, each with a copy of the record, and
+    /// are not bounded by the operator: memory grows with the records that fail within a span of
+    /// `retry_delay`, up to `max_attempts` times the failing records when every retry fails. Records
+    /// awaiting a retry hold back the frontiers of both outputs, for up to `max_attempts - 1`
+    /// advances by `retry_delay` past their first attempt.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_attempts` is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, WithDlq, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     // Fails the first attempt at each record, and always fails at odd records.
+    ///     let mut attempted = std::collections::HashSet::new();
+    ///     let (processed, dead) = (0..10u64).to_stream(scope).with_dlq(move |x| {
+    ///         if attempted.insert(x) || x % 2 == 1 { Err(format!("unavailable: {}", x)) } else { Ok(x * 10) }
+    ///     }, 1, 3);
+    ///     processed.inspect_time(|time, x| assert_eq!((*time, x % 20), (1, 0)));
+    ///     dead.inspect_time(|time, (x, error)| println!("gave up on {} at {}: {}", x, time, error));
+    /// });
+    /// ```
+    fn with_dlq<R, E, L>(&self, process: L, retry_delay: <G::Timestamp as Timestamp>::Summary, max_attempts: usize) -> (Stream<G, R>, Stream<G, (D, E)>)
+    where
+        R: Data,
+        E: Data,
+        L: FnMut(D)->Result<R, E>+'static;
+}
+
+impl<G: Scope, D: Data> WithDlq<G, D> for Stream<G, D> {
+    fn with_dlq<R, E, L>(&self, mut process: L, retry_delay: <G::Timestamp as Timestamp>::Summary, max_attempts: usize) -> (Stream<G, R>, Stream<G, (D, E)>)
+    where
+        R: Data,
+        E: Data,
+        L: FnMut(D)->Result<R, E>+'static,
+    {
+        assert!(max_attempts > 0, "WithDlq: max_attempts must be positive");
+
+        let mut scope = self.scope();
+        let (handle, retried) = scope.feedback::<Vec<(D, usize)>>(retry_delay.clone());
+        // Each record, with the number of its failed attempts.
+        let attempts = self.map(|datum| (datum, 0)).concat(&retried);
+
+        let mut builder = OperatorBuilder::new("WithDlq".to_owned(), scope);
+        let mut input = builder.new_input(&attempts, Pipeline);
+        let (processed_output, processed_stream) = builder.new_output();
+        let (retries_output, retries_stream) = builder.new_output();
+        let (dead_output, dead_stream) = builder.new_output();
+
+        let mut processed_output = OutputBuilder::from(processed_output);
+        let mut retries_output = OutputBuilder::from(retries_output);
+        let mut dead_output = OutputBuilder::from(dead_output);
+
+        builder.build(move |_| {
+            move |_frontiers| {
+                let mut processed_handle = processed_output.activate();
+                let mut retries_handle = retries_output.activate();
+                let mut dead_handle = dead_output.activate();
+
+                input.for_each_time(|time, data| {
+                    let mut processed = processed_handle.session(&time);
+                    let mut retries = retries_handle.session(&time);
+                    let mut dead = dead_handle.session(&time);
+                    // Records whose time cannot be advanced can not be retried.
+                    let retriable = retry_delay.results_in(time.time()).is_some();
+                    for (datum, failed) in data.flat_map(|d| d.drain(..)) {
+                        match process(datum.clone()) {
+                            Ok(result) => processed.give(result),
+                            Err(error) if failed + 1 >= max_attempts || !retriable => dead.give((datum, error)),
+                            Err(_) => retries.give((datum, failed + 1)),
+                        }
+                    }
+                });
+            }
+        });
+
+        retries_stream.connect_loop(handle);
+        (processed_stream, dead_stream)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::dataflow::operators::{Capture, Input, Probe};
+    use crate::dataflow::operators::capture::Extract;
+    use super::WithDlq;
+
+    #[test]
+    fn failed_records_are_retried_later_until_attempts_are_exhausted() {
+        let (processed, dead) = crate::execute_directly(|worker| {
+            let (mut input, probe, processed, dead) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<u64>();
+                // Record `x` fails its first `x % 10` attempts.
+                let mut attempted = HashMap::<u64, u64>::new();
+                let (processed, dead) = stream.with_dlq(move |x| {
+                    let count = attempted.entry(x).or_default();
+                    *count += 1;
+                    if *count > x % 10 { Ok(x) } else { Err(*count) }
+                }, 10, 3);
+                let probe = processed.probe();
+                dead.probe_with(&probe);
+                (input, probe, processed.capture(), dead.capture())
+            });
+
+            for x in 0..5 { input.send(x); }
+            // A record whose time cannot be advanced is not retried.
+            input.advance_to(u64::MAX - 5);
+            input.send(11);
+            input.close();
+            worker.step_while(|| !probe.done());
+            (processed, dead)
+        });
+
+        // Each retry happens `retry_delay` after the attempt before it.
+        assert_eq!(processed.extract(), vec![(0, vec![0]), (10, vec![1]), (20, vec![2])]);
+        assert_eq!(dead.extract(), vec![(20, vec![(3, 3), (4, 3)]), (u64::MAX - 5, vec![(11, 1)])]);
+    }
+}
@@ -0,0 +1,136 @@
+//! Extension methods for `Stream` that signal the end of the stream with a final record.
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::order::TotalOrder;
+
+/// Extension trait for `Stream`.
+pub trait WithEndSentinel<G: Scope, D: Data> {
+    /// Passes records through unchanged, and once the input frontier is empty, produces a final record of `make_sentinel`.
+    ///
+    /// The sentinel tells a sink that no more records will ever arrive, and that it may finalize,
+    /// for example by closing its files or committing its writes. Unlike an empty frontier, which a
+    /// sink observes only through progress tracking, the sentinel is a record, and arrives after all
+    /// other records of the stream. Each worker produces its sentinel exactly once, after it has
+    /// passed on all of its records, at the latest time of those records or of the input frontier,
+    /// so that a sink that processes records in order of time also processes the sentinel last.
+    ///
+    /// To this end the operator holds a capability at its input frontier until the frontier is
+    /// empty, which does not hold back the output frontier beyond the input's. Should the dataflow
+    /// be dropped before its input completes, no sentinel is produced.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Map, WithEndSentinel, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .map(Some)
+    ///            .with_end_sentinel(|| None)
+    ///            .inspect(|x| if x.is_none() { println!("stream complete") });
+    /// });
+    /// ```
+    fn with_end_sentinel<F: FnOnce()->D+'static>(&self, make_sentinel: F) -> Stream<G, D>
+    where
+        G::Timestamp: TotalOrder;
+}
+
+impl<G: Scope, D: Data> WithEndSentinel<G, D> for Stream<G, D> {
+    fn with_end_sentinel<F: FnOnce()->D+'static>(&self, make_sentinel: F) -> Stream<G, D>
+    where
+        G::Timestamp: TotalOrder,
+    {
+        self.unary_frontier(Pipeline, "WithEndSentinel", move |capability, _info| {
+            // The capability for the sentinel, at the input frontier, until the sentinel is produced.
+            let mut capability = Some(capability);
+            let mut make_sentinel = Some(make_sentinel);
+            // The latest time of the records passed on.
+            let mut latest = None::<G::Timestamp>;
+            move |(input, frontier), output| {
+                input.for_each_time(|time, data| {
+                    if latest.as_ref().is_none_or(|latest| latest < time.time()) {
+                        latest = Some(time.time().clone());
+                    }
+                    let mut session = output.session(&time);
+                    for container in data {
+                        session.give_container(container);
+                    }
+                });
+
+                if let Some(held) = capability.as_mut() {
+                    match frontier.frontier().first() {
+                        Some(time) => held.downgrade(time),
+                        None => {
+                            let last = capability.take().expect("capability present");
+                            let time = latest.take().filter(|latest| last.time() < latest).unwrap_or_else(|| last.time().clone());
+                            let sentinel = make_sentinel.take().expect("sentinel not yet produced");
+                            output.session(&last.delayed(&time)).give(sentinel());
+                        }
+                    }
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::Config;
+    use crate::dataflow::operators::{Capture, Concat, Input, Map, Probe, WithEndSentinel};
+    use crate::dataflow::operators::capture::{Event, Extract};
+
+    #[test]
+    fn sentinels_follow_all_records() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut input, mut late, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<u64>();
+                let (late, late_stream) = scope.new_input::<u64>();
+                let ended = stream.concat(&late_stream).map(Some).with_end_sentinel(|| None);
+                (input, late, ended.probe(), ended.capture())
+            });
+            // A record at a later time than the inputs' last frontier.
+            late.advance_to(7);
+            late.send(70);
+            late.close();
+            for time in 0..3 {
+                input.advance_to(time);
+                input.send(time * 10);
+                worker.step_while(|| probe.less_than(input.time()));
+            }
+            for _ in 0..3 { worker.step(); }
+            // The records so far have passed, but not the sentinel.
+            let mut early = captured.try_iter().filter_map(|event| match event {
+                Event::Messages(_time, data) => Some(data),
+                Event::Progress(_) => None,
+            }).flatten().collect::<Vec<_>>();
+            early.sort();
+            assert_eq!(early, vec![Some(0), Some(10), Some(70)]);
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        assert_eq!(captured.extract(), vec![(2, vec![Some(20)]), (7, vec![None])]);
+    }
+
+    #[test]
+    fn each_worker_produces_one_sentinel() {
+        let (send, recv) = std::sync::mpsc::channel();
+        let send = Arc::new(Mutex::new(send));
+        crate::execute(Config::process(3), move |worker| {
+            let send = send.lock().unwrap().clone();
+            let index = worker.index() as u64;
+            worker.dataflow::<u64,_,_>(|scope| {
+                let (mut input, stream) = scope.new_input::<u64>();
+                stream.map(Some).with_end_sentinel(|| None).capture_into(send);
+                input.send(index);
+            });
+        }).unwrap();
+
+        assert_eq!(recv.extract(), vec![(0, vec![None, None, None, Some(0), Some(1), Some(2)])]);
+    }
+}
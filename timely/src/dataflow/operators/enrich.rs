@@ -0,0 +1,218 @@
+//! Extension methods for `Stream` that enrich records with the values of a broadcast, slowly changing dimension.
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::{Broadcast, Capability};
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::generic::stash::Stash;
+use crate::order::TotalOrder;
+
+/// Extension trait for `Stream`.
+pub trait Enrich<G: Scope, D: Data> {
+    /// Enriches each record with the value of its key in `dimension`, as of the record's time.
+    ///
+    /// The records `(key, value)` of `dimension` are updates of a table from keys to values, each
+    /// setting the value of its key and replacing any value it had. They are broadcast, and each
+    /// worker maintains a copy of the table. For each record of `self` the operator produces
+    /// `merge_fn(record, value)`, where `value` is the value of the record's key `key_fn(record)`,
+    /// or `None` if the key has none.
+    ///
+    /// The value a record at time `t` sees is determined by the times alone: it reflects exactly the
+    /// updates at times up to and including `t`, and none at later times, however the records of the
+    /// two streams interleave as they arrive. Among several updates of a key at the same time, one
+    /// applies, and which one is not specified. To this end a record at `t` is held until the
+    /// frontier of `dimension` has passed `t`, and an update at `s` is applied to the table once the
+    /// frontier of `dimension` has passed `s` and that of `self` has reached it, when no record of an
+    /// earlier time can arrive. Records are produced at their own times, and are not exchanged.
+    ///
+    /// The table retains a value for every key ever updated, and each worker holds all of them, so
+    /// the dimension should be small, or at least slowly growing. In addition, records and updates
+    /// are held while they wait for the frontiers: a dimension that falls behind delays all records,
+    /// and a stream of records that falls behind delays the updates, which accumulate.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{Enrich, Input, Inspect, Probe};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     let (mut names, mut events, probe) = worker.dataflow::<u64,_,_>(|scope| {
+    ///         let (names, name_stream) = scope.new_input::<(u64, String)>();
+    ///         let (events, event_stream) = scope.new_input::<(u64, &str)>();
+    ///         let probe = event_stream
+    ///             .enrich(&name_stream, |(user, _)| *user, |(_, action), name| (name.cloned(), action))
+    ///             .inspect(|x| println!("seen: {:?}", x))
+    ///             .probe();
+    ///         (names, events, probe)
+    ///     });
+    ///
+    ///     // The event at time 0 sees no name, and the one at time 1 sees the name set at time 1.
+    ///     events.send((7, "login"));
+    ///     names.advance_to(1);
+    ///     events.advance_to(1);
+    ///     names.send((7, "alice".to_string()));
+    ///     events.send((7, "logout"));
+    ///     names.close();
+    ///     events.close();
+    ///     worker.step_while(|| !probe.done());
+    /// });
+    /// ```
+    fn enrich<K, V, R, KF, M>(&self, dimension: &Stream<G, (K, V)>, key_fn: KF, merge_fn: M) -> Stream<G, R>
+    where
+        G::Timestamp: TotalOrder,
+        K: ExchangeData+Hash+Eq,
+        V: ExchangeData,
+        R: Data,
+        KF: FnMut(&D)->K+'static,
+        M: FnMut(D, Option<&V>)->R+'static;
+}
+
+impl<G: Scope, D: Data> Enrich<G, D> for Stream<G, D> {
+    fn enrich<K, V, R, KF, M>(&self, dimension: &Stream<G, (K, V)>, mut key_fn: KF, mut merge_fn: M) -> Stream<G, R>
+    where
+        G::Timestamp: TotalOrder,
+        K: ExchangeData+Hash+Eq,
+        V: ExchangeData,
+        R: Data,
+        KF: FnMut(&D)->K+'static,
+        M: FnMut(D, Option<&V>)->R+'static,
+    {
+        self.binary_frontier(&dimension.broadcast(), Pipeline, Pipeline, "Enrich", move |_capability, _info| {
+            // The value of each key, as of the updates applied so far.
+            let mut table = HashMap::<K, V>::new();
+            // Updates not yet applied, by time.
+            let mut updates = BTreeMap::<G::Timestamp, Vec<(K, V)>>::new();
+            // Records held until the table reflects the updates of their times.
+            let mut held = Stash::<Capability<G::Timestamp>, Vec<D>>::new();
+            move |(input1, frontier1), (input2, frontier2), output| {
+                input2.for_each_time(|time, data| {
+                    let pending = updates.entry(time.time().clone()).or_default();
+                    for container in data { pending.append(container); }
+                });
+                input1.for_each_time(|time, data| {
+                    let records = held.get_or_retain(&time, Vec::new);
+                    for container in data { records.append(container); }
+                });
+
+                // Produce records and apply updates in order of time, the updates of a time before its records.
+                loop {
+                    let update = updates.keys().next().cloned();
+                    let record = held.iter_mut().map(|(capability, _)| capability.time().clone()).min();
+                    match (record, update) {
+                        (Some(time), update) if update.as_ref().is_none_or(|update| time < *update) => {
+                            if frontier2.less_equal(&time) { break; }
+                            held.release(|held_time| *held_time == time, |capability, records| {
+                                let mut session = output.session(&capability);
+                                for datum in records {
+                                    let value = table.get(&key_fn(&datum));
+                                    session.give(merge_fn(datum, value));
+                                }
+                            });
+                        }
+                        (_, Some(time)) => {
+                            if frontier2.less_equal(&time) || frontier1.less_than(&time) { break; }
+                            table.extend(updates.remove(&time).expect("update present"));
+                        }
+                        _ => break,
+                    }
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::Config;
+    use crate::dataflow::operators::{Capture, Enrich, Input, Probe};
+    use crate::dataflow::operators::capture::Extract;
+
+    #[test]
+    fn records_see_the_dimension_as_of_their_times() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut dimension, mut records, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (dimension, dimension_stream) = scope.new_input::<(char, u64)>();
+                let (records, record_stream) = scope.new_input::<(u64, char)>();
+                let enriched = record_stream.enrich(&dimension_stream, |(_, key)| *key, |(id, _), value| (id, value.copied()));
+                (dimension, records, enriched.probe(), enriched.capture())
+            });
+            // The dimension runs ahead of the records: its updates at times 1 and 3 arrive first.
+            dimension.send(('a', 10));
+            dimension.advance_to(1);
+            dimension.send(('a', 11));
+            dimension.send(('b', 20));
+            dimension.advance_to(3);
+            dimension.send(('a', 13));
+            dimension.close();
+            for _ in 0..3 { worker.step(); }
+            for time in 0..5 {
+                records.advance_to(time);
+                records.send((time, 'a'));
+                records.send((time, 'b'));
+                worker.step_while(|| probe.less_than(records.time()));
+            }
+            records.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        assert_eq!(captured.extract(), vec![
+            (0, vec![(0, None), (0, Some(10))]),
+            (1, vec![(1, Some(11)), (1, Some(20))]),
+            (2, vec![(2, Some(11)), (2, Some(20))]),
+            (3, vec![(3, Some(13)), (3, Some(20))]),
+            (4, vec![(4, Some(13)), (4, Some(20))]),
+        ]);
+    }
+
+    #[test]
+    fn records_wait_for_the_dimension() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut dimension, mut records, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (dimension, dimension_stream) = scope.new_input::<(u64, char)>();
+                let (records, record_stream) = scope.new_input::<u64>();
+                let enriched = record_stream.enrich(&dimension_stream, |key| *key, |key, value| (key, value.copied()));
+                (dimension, records, enriched.probe(), enriched.capture())
+            });
+            // Records run ahead of the dimension, and are held until it passes their times.
+            records.send(1);
+            records.advance_to(2);
+            records.send(1);
+            records.close();
+            for _ in 0..3 { worker.step(); }
+            assert!(captured.try_iter().all(|event| matches!(event, crate::dataflow::operators::capture::Event::Progress(_))));
+            dimension.advance_to(2);
+            dimension.send((1, 'x'));
+            dimension.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        assert_eq!(captured.extract(), vec![(0, vec![(1, None)]), (2, vec![(1, Some('x'))])]);
+    }
+
+    #[test]
+    fn each_worker_sees_the_whole_dimension() {
+        let (send, recv) = std::sync::mpsc::channel();
+        let send = Arc::new(Mutex::new(send));
+        crate::execute(Config::process(3), move |worker| {
+            let send = send.lock().unwrap().clone();
+            let index = worker.index() as u64;
+            worker.dataflow::<u64,_,_>(|scope| {
+                let (mut dimension, dimension_stream) = scope.new_input::<(u64, u64)>();
+                let (mut records, record_stream) = scope.new_input::<u64>();
+                record_stream.enrich(&dimension_stream, |key| *key, |key, value| (key, value.copied())).capture_into(send);
+                // Each worker updates one key, and has records of all workers' keys.
+                dimension.send((index, 10 * index));
+                for key in 0..3 { records.send(key); }
+            });
+        }).unwrap();
+
+        assert_eq!(recv.extract(), vec![(0, vec![(0, Some(0)), (0, Some(0)), (0, Some(0)), (1, Some(10)), (1, Some(10)), (1, Some(10)), (2, Some(20)), (2, Some(20)), (2, Some(20))])]);
+    }
+}
@@ -0,0 +1,167 @@
+//! Extension methods for `Stream` that keep records whose keys are in a broadcast set of the same time.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::{Broadcast, Capability};
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::generic::stash::Stash;
+
+/// Extension trait for `Stream`.
+pub trait FilterInSet<G: Scope, D: Data> {
+    /// Keeps the records that are members of the set formed by the records of `set` at the same time.
+    ///
+    /// Behaves as [`FilterInSet::filter_in_set_by`], with each record as its own key.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, FilterInSet, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     let allowed = vec![3, 5, 7].to_stream(scope);
+    ///     (0..10u64).to_stream(scope)
+    ///               .filter_in_set(&allowed)
+    ///               .inspect(|x| assert!([3, 5, 7].contains(x)));
+    /// });
+    /// ```
+    fn filter_in_set(&self, set: &Stream<G, D>) -> Stream<G, D>
+    where
+        D: ExchangeData+Hash+Eq,
+    {
+        self.filter_in_set_by(set, |x| x.clone())
+    }
+
+    /// Keeps the records whose keys `key_fn` extracts are in the set formed by the records of `set` at the same time.
+    ///
+    /// The records of `set` are broadcast, and each worker collects their set at each time. A record
+    /// of `self` is kept or dropped once the set of its time is complete, when the frontier of `set`
+    /// has passed the time: records that arrive after that are filtered at once, and those that
+    /// arrive before are held, with a capability for their time, until it is. A time without records
+    /// in `set` has an empty set, which drops that time's records. The records of `self` are not
+    /// exchanged, and each worker filters its own records.
+    ///
+    /// Each worker holds a copy of the set of each time until the frontiers of both inputs have
+    /// passed the time, and so `set` should be small, as for an allow list. The set of a time applies
+    /// only to the records of that time; a set that applies to all times can be entered at each time,
+    /// or delayed to the times of the records.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, FilterInSet, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     let allowed = vec!["alice".to_string()].to_stream(scope);
+    ///     vec![(1, "alice".to_string()), (2, "bob".to_string())]
+    ///         .to_stream(scope)
+    ///         .filter_in_set_by(&allowed, |(_, user)| user.clone())
+    ///         .inspect(|(event, _)| assert_eq!(*event, 1));
+    /// });
+    /// ```
+    fn filter_in_set_by<K, F>(&self, set: &Stream<G, K>, key_fn: F) -> Stream<G, D>
+    where
+        K: ExchangeData+Hash+Eq,
+        F: FnMut(&D)->K+'static;
+}
+
+impl<G: Scope<Timestamp: Hash>, D: Data> FilterInSet<G, D> for Stream<G, D> {
+    fn filter_in_set_by<K, F>(&self, set: &Stream<G, K>, mut key_fn: F) -> Stream<G, D>
+    where
+        K: ExchangeData+Hash+Eq,
+        F: FnMut(&D)->K+'static,
+    {
+        self.binary_frontier(&set.broadcast(), Pipeline, Pipeline, "FilterInSet", move |_capability, _info| {
+            // The members of each time whose set has not been discarded.
+            let mut sets = HashMap::<G::Timestamp, HashSet<K>>::new();
+            // Records held until the sets of their times are complete.
+            let mut held = Stash::<Capability<G::Timestamp>, Vec<D>>::new();
+            move |(input1, frontier1), (input2, frontier2), output| {
+                input2.for_each_time(|time, data| {
+                    sets.entry(time.time().clone()).or_default().extend(data.flat_map(|d| d.drain(..)));
+                });
+                input1.for_each_time(|time, data| {
+                    if frontier2.less_equal(time.time()) {
+                        let records = held.get_or_retain(&time, Vec::new);
+                        for container in data { records.append(container); }
+                    }
+                    else {
+                        let members = sets.get(time.time());
+                        let mut session = output.session(&time);
+                        for datum in data.flat_map(|d| d.drain(..)) {
+                            if members.is_some_and(|members| members.contains(&key_fn(&datum))) { session.give(datum); }
+                        }
+                    }
+                });
+
+                held.release(|time| !frontier2.less_equal(time), |capability, records| {
+                    let members = sets.get(capability.time());
+                    output.session(&capability).give_iterator(records.into_iter().filter(|datum| members.is_some_and(|members| members.contains(&key_fn(datum)))));
+                });
+                // Sets are discarded once no records of their times remain to arrive.
+                sets.retain(|time, _members| frontier1.less_equal(time) || frontier2.less_equal(time));
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::Config;
+    use crate::dataflow::operators::{Capture, FilterInSet, Input, Probe};
+    use crate::dataflow::operators::capture::Extract;
+
+    #[test]
+    fn records_wait_for_the_set_of_their_time() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut set, mut records, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (set, set_stream) = scope.new_input::<u64>();
+                let (records, record_stream) = scope.new_input::<u64>();
+                let kept = record_stream.filter_in_set(&set_stream);
+                (set, records, kept.probe(), kept.capture())
+            });
+            // Records arrive before the set of their time, whose members arrive over two steps.
+            for record in 0..10 { records.send(record); }
+            records.flush();
+            set.send(3);
+            set.flush();
+            for _ in 0..3 { worker.step(); }
+            set.send(7);
+            set.advance_to(1);
+            // Records after the set of their time is complete are filtered at once, and time 1 has no set.
+            records.send(5);
+            records.send(7);
+            records.advance_to(1);
+            records.send(3);
+            records.close();
+            set.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        assert_eq!(captured.extract(), vec![(0, vec![3, 7, 7])]);
+    }
+
+    #[test]
+    fn each_worker_filters_by_the_whole_set() {
+        let (send, recv) = std::sync::mpsc::channel();
+        let send = Arc::new(Mutex::new(send));
+        crate::execute(Config::process(3), move |worker| {
+            let send = send.lock().unwrap().clone();
+            let index = worker.index() as u64;
+            worker.dataflow::<u64,_,_>(|scope| {
+                let (mut set, set_stream) = scope.new_input::<u64>();
+                let (mut records, record_stream) = scope.new_input::<u64>();
+                record_stream.filter_in_set(&set_stream).capture_into(send);
+                // Each worker contributes one member, and has records of all workers' members.
+                set.send(index);
+                for record in 0..6 { records.send(record); }
+            });
+        }).unwrap();
+
+        assert_eq!(recv.extract(), vec![(0, vec![0, 0, 0, 1, 1, 1, 2, 2, 2])]);
+    }
+}
@@ -0,0 +1,120 @@
+//! Extension methods for `Stream` that separate records with frequent keys from those with rare keys.
+
+use std::hash::Hash;
+
+use crate::Data;
+use crate::dataflow::{Scope, Stream};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::OutputBuilder;
+use crate::dataflow::operators::generic::builder_rc::OperatorBuilder;
+use crate::dataflow::operators::sketch::CountMinSketch;
+
+/// Extension trait for `Stream`.
+pub trait PartitionByFrequency<G: Scope, D: Data> {
+    /// Splits the stream into records whose keys are frequent, and records whose keys are rare.
+    ///
+    /// The operator counts the keys of the records each worker sees in a [`CountMinSketch`] of
+    /// 4 rows of 4096 counters, whose memory is bounded regardless of the number of distinct keys.
+    /// A record is sent to the first, hot, returned stream if the estimated count of its key,
+    /// including the record itself, exceeds `threshold`, and otherwise to the second, cold, stream.
+    /// The first `threshold` records of a key are thus cold, and the following records hot, unless
+    /// the estimate, which may exceed the count of a key, has it cross the threshold sooner.
+    ///
+    /// Counts are kept by each worker, of its own records; to route by the counts of all workers,
+    /// first exchange the records by key. Counts accumulate across all times.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, PartitionByFrequency, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     let (hot, cold) = vec!["a", "b", "a", "a", "c", "a"]
+    ///         .to_stream(scope)
+    ///         .partition_by_frequency(|x| *x, 2);
+    ///
+    ///     hot.inspect(|x| assert_eq!(*x, "a"));
+    ///     cold.inspect(|x| println!("cold: {:?}", x));
+    /// });
+    /// ```
+    fn partition_by_frequency<K, F>(&self, key_fn: F, threshold: u64) -> (Stream<G, D>, Stream<G, D>)
+    where
+        K: Hash,
+        F: Fn(&D)->K+'static;
+}
+
+impl<G: Scope, D: Data> PartitionByFrequency<G, D> for Stream<G, D> {
+    fn partition_by_frequency<K, F>(&self, key_fn: F, threshold: u64) -> (Stream<G, D>, Stream<G, D>)
+    where
+        K: Hash,
+        F: Fn(&D)->K+'static,
+    {
+        let mut builder = OperatorBuilder::new("PartitionByFrequency".to_owned(), self.scope());
+        builder.set_notify(false);
+
+        let mut input = builder.new_input(self, Pipeline);
+        let (hot_output, hot_stream) = builder.new_output();
+        let (cold_output, cold_stream) = builder.new_output();
+
+        let mut hot_output = OutputBuilder::from(hot_output);
+        let mut cold_output = OutputBuilder::from(cold_output);
+
+        builder.build(move |_| {
+            let mut counts = CountMinSketch::new(4096, 4);
+            move |_frontiers| {
+                let mut hot_handle = hot_output.activate();
+                let mut cold_handle = cold_output.activate();
+
+                input.activate().for_each_time(|time, data| {
+                    let mut hot = hot_handle.session(&time);
+                    let mut cold = cold_handle.session(&time);
+                    for datum in data.flat_map(|d| d.drain(..)) {
+                        let key = key_fn(&datum);
+                        counts.insert(&key, 1);
+                        if counts.estimate(&key) > threshold {
+                            hot.give(datum);
+                        } else {
+                            cold.give(datum);
+                        }
+                    }
+                });
+            }
+        });
+
+        (hot_stream, cold_stream)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dataflow::operators::{Capture, Input, Probe};
+    use crate::dataflow::operators::capture::Extract;
+    use super::PartitionByFrequency;
+
+    #[test]
+    fn keys_crossing_the_threshold_turn_hot() {
+        let (hot, cold) = crate::execute_directly(|worker| {
+            let (mut input, probe, hot, cold) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<(u64, u64)>();
+                let (hot, cold) = stream.partition_by_frequency(|record| record.0, 3);
+                let probe = hot.probe();
+                cold.probe_with(&probe);
+                (input, probe, hot.capture(), cold.capture())
+            });
+            // Key 0 is seen in every round, and key `round` in only that one.
+            for round in 1..6u64 {
+                input.send((0, round));
+                input.send((round, round));
+                input.advance_to(round);
+                worker.step_while(|| probe.less_than(input.time()));
+            }
+            (hot, cold)
+        });
+
+        let hot = hot.extract();
+        let cold = cold.extract();
+        // Key 0 is cold through its third record, and then migrates to the hot output.
+        assert_eq!(hot, vec![(3, vec![(0, 4)]), (4, vec![(0, 5)])]);
+        assert_eq!(cold.iter().flat_map(|(_, data)| data.iter().filter(|record| record.0 == 0)).count(), 3);
+        assert_eq!(cold.iter().map(|(_, data)| data.len()).sum::<usize>(), 8);
+    }
+}
@@ -0,0 +1,127 @@
+//! Extension methods for `Stream` that report missing sequence numbers of sequenced records.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::rc::Rc;
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Exchange;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::sketch::hash_of;
+
+/// Extension trait for `Stream`.
+pub trait DetectGaps<G: Scope, D: ExchangeData> {
+    /// Reports, for each key, the sequence numbers skipped by its records, as `(key, expected, actual)`.
+    ///
+    /// `seq_fn` extracts the key and the sequence number of each record. The operator remembers for
+    /// each key the sequence number it expects next, one more than the largest it has seen, and a
+    /// record whose number exceeds the expected one produces a report, at the record's time, that the
+    /// numbers from `expected` up to but excluding `actual` are missing. The first record of a key
+    /// sets its expectation without a report. Records are exchanged by key, and each worker applies
+    /// the records of its keys in the order in which it receives them, regardless of their times.
+    ///
+    /// The operator assumes that the records of each key arrive in order of their sequence numbers.
+    /// A record whose number is less than the expected one, as a duplicate or a record that arrives
+    /// late, produces no report and leaves the expectation alone; a late record that fills part of a
+    /// gap does not retract its report. The records of a key should come from a single worker, as
+    /// the records of several workers interleave in an order that may change between runs. The
+    /// expectation of each key is retained for as long as the operator runs.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, DetectGaps, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     // Records of feed 7, with sequence numbers 2 and 3 missing.
+    ///     vec![(7, 0u64), (7, 1), (7, 4), (7, 5)]
+    ///         .to_stream(scope)
+    ///         .detect_gaps(|record| *record)
+    ///         .inspect(|gap| assert_eq!(*gap, (7, 2, 4)));
+    /// });
+    /// ```
+    fn detect_gaps<K, F>(&self, seq_fn: F) -> Stream<G, (K, u64, u64)>
+    where
+        K: Data+Hash+Eq,
+        F: Fn(&D)->(K, u64)+'static;
+}
+
+impl<G: Scope, D: ExchangeData> DetectGaps<G, D> for Stream<G, D> {
+    fn detect_gaps<K, F>(&self, seq_fn: F) -> Stream<G, (K, u64, u64)>
+    where
+        K: Data+Hash+Eq,
+        F: Fn(&D)->(K, u64)+'static,
+    {
+        let seq_fn = Rc::new(seq_fn);
+        let route = Rc::clone(&seq_fn);
+        let pact = Exchange::new(move |datum: &D| hash_of(&route(datum).0));
+
+        // For each key seen, the sequence number expected of its next record.
+        let mut expected = HashMap::<K, u64>::new();
+        self.unary(pact, "DetectGaps", move |_capability, _info| move |input, output| {
+            input.for_each_time(|time, data| {
+                let mut session = output.session(&time);
+                for datum in data.flat_map(|d| d.drain(..)) {
+                    let (key, actual) = seq_fn(&datum);
+                    match expected.get_mut(&key) {
+                        Some(next) => {
+                            if actual > *next {
+                                session.give((key, *next, actual));
+                            }
+                            *next = (*next).max(actual.saturating_add(1));
+                        },
+                        None => { expected.insert(key, actual.saturating_add(1)); },
+                    }
+                }
+            });
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dataflow::operators::{Capture, Input, Probe};
+    use crate::dataflow::operators::capture::Extract;
+    use super::DetectGaps;
+
+    /// The gaps reported for `rounds` of sequenced records, each round at its own time.
+    fn gaps(rounds: Vec<Vec<(char, u64)>>) -> Vec<(u64, Vec<(char, u64, u64)>)> {
+        let captured = crate::execute_directly(move |worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<(char, u64)>();
+                let gaps = stream.detect_gaps(|record| *record);
+                (input, gaps.probe(), gaps.capture())
+            });
+            for (round, records) in rounds.into_iter().enumerate() {
+                input.advance_to(round as u64);
+                for record in records { input.send(record); }
+            }
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+        captured.extract()
+    }
+
+    #[test]
+    fn in_order_sequences_have_no_gaps() {
+        // Sequences may start at any number, and continue across times.
+        assert_eq!(gaps(vec![vec![('a', 0), ('b', 7), ('a', 1)], vec![('b', 8), ('a', 2)]]), vec![]);
+    }
+
+    #[test]
+    fn skipped_numbers_are_reported_per_key() {
+        assert_eq!(gaps(vec![vec![('a', 0), ('a', 3), ('b', 5)], vec![('b', 6), ('a', 4), ('b', 9), ('a', 10)]]), vec![
+            (0, vec![('a', 1, 3)]),
+            (1, vec![('a', 5, 10), ('b', 7, 9)]),
+        ]);
+    }
+
+    #[test]
+    fn duplicate_and_late_numbers_are_not_gaps() {
+        // The late 1 fills part of the reported gap, and neither it nor the repeated 3 moves the expectation from 4.
+        assert_eq!(gaps(vec![vec![('a', 0), ('a', 0), ('a', 3)], vec![('a', 1), ('a', 3), ('a', 4)]]), vec![
+            (0, vec![('a', 1, 3)]),
+        ]);
+    }
+}
@@ -0,0 +1,150 @@
+//! Extension methods for `Stream` that hold records until a control stream releases their times.
+
+use crate::Data;
+use crate::dataflow::{Scope, Stream};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::Capability;
+use crate::dataflow::operators::generic::OutputBuilder;
+use crate::dataflow::operators::generic::builder_rc::OperatorBuilder;
+use crate::dataflow::operators::generic::stash::Stash;
+use crate::order::PartialOrder;
+
+/// Extension trait for `Stream`.
+pub trait Gate<G: Scope, D: Data> {
+    /// Holds the records of `self` until `control` releases their times.
+    ///
+    /// Each time `release` received from `control` releases the records at times less or equal to
+    /// `release`, those held and those yet to arrive, which are then produced at their times. This
+    /// lets a consumer that cannot keep up signal how far it is ready, pulling the records it wants
+    /// from a dataflow that otherwise pushes them. Once `control` completes, no further signals can
+    /// arrive, and all records are released.
+    ///
+    /// The operator retains held records and capabilities for their times, holding back the output
+    /// frontier, and releases them as their times are released. Memory use is thus bounded by the
+    /// records at times not yet released, and grows as far as `control` lags behind `self`. Each
+    /// worker applies the signals it receives from `control`, which can be broadcast to apply them
+    /// at all workers.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{Input, Gate, Inspect, Probe};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     let (mut input, mut control, probe) = worker.dataflow::<u64,_,_>(|scope| {
+    ///         let (input, stream) = scope.new_input::<u64>();
+    ///         let (control, releases) = scope.new_input::<u64>();
+    ///         let probe = stream
+    ///             .gate(&releases)
+    ///             .inspect_time(|time, x| println!("released {:?} at {:?}", x, time))
+    ///             .probe();
+    ///         (input, control, probe)
+    ///     });
+    ///     for round in 0..4 {
+    ///         input.send(round);
+    ///         input.advance_to(round + 1);
+    ///     }
+    ///     // Releases the records at times 0 through 2, and holds the record at time 3.
+    ///     control.send(2);
+    ///     control.advance_to(1);
+    ///     worker.step_while(|| probe.less_than(&3));
+    ///     assert!(probe.less_equal(&3));
+    /// });
+    /// ```
+    fn gate(&self, control: &Stream<G, G::Timestamp>) -> Stream<G, D>;
+}
+
+impl<G: Scope, D: Data> Gate<G, D> for Stream<G, D> {
+    fn gate(&self, control: &Stream<G, G::Timestamp>) -> Stream<G, D> {
+        let mut builder = OperatorBuilder::new("Gate".to_owned(), self.scope());
+
+        // The control stream does not hold back the output, as its signals only release records.
+        let (output, result) = builder.new_output_connection([]);
+        let mut output = OutputBuilder::from(output);
+        let mut input = builder.new_input(self, Pipeline);
+        let mut releases = builder.new_input_connection(control, Pipeline, []);
+
+        // The maximal released times; a time is released if less or equal to any of them.
+        let mut released: Vec<G::Timestamp> = Vec::new();
+        let mut held = Stash::<Capability<G::Timestamp>, Vec<D>>::new();
+
+        builder.build(move |_capabilities| {
+            move |frontiers| {
+                let mut output = output.activate();
+                releases.for_each(|_time, data| {
+                    for release in data.drain(..) {
+                        if !released.iter().any(|time| release.less_equal(time)) {
+                            released.retain(|time| !time.less_equal(&release));
+                            released.push(release);
+                        }
+                    }
+                });
+                let complete = frontiers[1].is_empty();
+                let is_released = |time: &G::Timestamp| complete || released.iter().any(|release| time.less_equal(release));
+                input.for_each_time(|time, data| {
+                    if is_released(time.time()) {
+                        output.session(&time).give_containers(data);
+                    }
+                    else {
+                        held.get_or_retain(&time, Vec::new).extend(data.flat_map(|d| d.drain(..)));
+                    }
+                });
+                held.release(is_released, |capability, mut data| {
+                    output.session(&capability).give_container(&mut data);
+                });
+            }
+        });
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use crate::dataflow::operators::{Input, Inspect, Probe};
+    use super::Gate;
+
+    #[test]
+    fn releases_held_records_as_signalled() {
+        crate::execute_directly(|worker| {
+            let seen = Rc::new(RefCell::new(Vec::new()));
+            let seen_inner = Rc::clone(&seen);
+            let (mut input, mut control, probe) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<u64>();
+                let (control, releases) = scope.new_input::<u64>();
+                let probe = stream
+                    .gate(&releases)
+                    .inspect_time(move |time, x| seen_inner.borrow_mut().push((*time, *x)))
+                    .probe();
+                (input, control, probe)
+            });
+            for round in 0..6 {
+                input.send(round);
+                input.advance_to(round + 1);
+            }
+            for _ in 0..10 { worker.step(); }
+            assert!(seen.borrow().is_empty());
+
+            // A release covers the records held at times up to it.
+            control.send(1);
+            control.advance_to(1);
+            worker.step_while(|| probe.less_than(&2));
+            assert_eq!(*seen.borrow(), vec![(0, 0), (1, 1)]);
+
+            // A lesser release after a greater one releases nothing more.
+            control.send(4);
+            control.send(3);
+            control.advance_to(2);
+            worker.step_while(|| probe.less_than(&5));
+            assert_eq!(seen.borrow().len(), 5);
+
+            // Once the control stream completes, all records are released.
+            control.close();
+            input.close();
+            worker.step_while(|| !probe.done());
+            assert_eq!(seen.borrow().last(), Some(&(5, 5)));
+        });
+    }
+}
@@ -8,7 +8,7 @@ use std::default::Default;
 use std::rc::Rc;
 use std::cell::RefCell;
 
-use crate::scheduling::{Schedule, Activations};
+use crate::scheduling::{Schedule, SchedulingHint, Activations};
 
 use crate::progress::{Source, Target};
 use crate::progress::{Timestamp, Operate, operate::SharedProgress, Antichain};
@@ -18,6 +18,7 @@ use crate::dataflow::{StreamCore, Scope};
 use crate::dataflow::channels::pushers::Tee;
 use crate::dataflow::channels::pact::ParallelizationContract;
 use crate::dataflow::operators::generic::operator_info::OperatorInfo;
+use crate::dataflow::operators::generic::{ErrorReporter, MemoryRegistry, MemoryReporter};
 
 /// Contains type-free information about the operator properties.
 #[derive(Debug)]
@@ -27,6 +28,9 @@ pub struct OperatorShape {
     peers: usize,   // The total number of workers in the computation.
     inputs: usize,  // The number of input ports.
     outputs: usize, // The number of output ports.
+    input_names: Vec<Option<String>>,   // The name of each input port, if named.
+    output_names: Vec<Option<String>>,  // The name of each output port, if named.
+    scheduling_hint: SchedulingHint,    // How the operator spends its time when scheduled.
 }
 
 /// Core data for the structure of an operator, minus scope and logic.
@@ -38,6 +42,9 @@ impl OperatorShape {
             peers,
             inputs: 0,
             outputs: 0,
+            input_names: Vec::new(),
+            output_names: Vec::new(),
+            scheduling_hint: SchedulingHint::default(),
         }
     }
 
@@ -50,6 +57,21 @@ impl OperatorShape {
     pub fn outputs(&self) -> usize {
         self.outputs
     }
+
+    /// The name of input `port`, if it has been named.
+    pub fn input_name(&self, port: usize) -> Option<&str> {
+        self.input_names.get(port)?.as_deref()
+    }
+
+    /// The name of output `port`, if it has been named.
+    pub fn output_name(&self, port: usize) -> Option<&str> {
+        self.output_names.get(port)?.as_deref()
+    }
+
+    /// How the operator spends its time when scheduled, as hinted to the scheduler.
+    pub fn scheduling_hint(&self) -> SchedulingHint {
+        self.scheduling_hint
+    }
 }
 
 /// Builds operators with generic shape.
@@ -61,6 +83,8 @@ pub struct OperatorBuilder<G: Scope> {
     address: Rc<[usize]>,    // path to the operator (ending with index).
     shape: OperatorShape,
     summary: Connectivity<<G::Timestamp as Timestamp>::Summary>,
+    memory: MemoryReporter,
+    errors: ErrorReporter,
 }
 
 impl<G: Scope> OperatorBuilder<G> {
@@ -72,6 +96,11 @@ impl<G: Scope> OperatorBuilder<G> {
         let index = scope.allocate_operator_index();
         let address = scope.addr_for_child(index);
         let peers = scope.peers();
+        let memory = match MemoryRegistry::get(&scope) {
+            Some(registry) => registry.borrow_mut().register(global, &name),
+            None => MemoryReporter::default(),
+        };
+        let errors = ErrorReporter::new(&scope, &name, global);
 
         OperatorBuilder {
             scope,
@@ -80,6 +109,8 @@ impl<G: Scope> OperatorBuilder<G> {
             address,
             shape: OperatorShape::new(name, peers),
             summary: vec![],
+            memory,
+            errors,
         }
     }
 
@@ -103,6 +134,31 @@ impl<G: Scope> OperatorBuilder<G> {
         self.shape.notify = notify;
     }
 
+    /// Hints to the scheduler how the operator spends its time when scheduled.
+    pub fn set_scheduling_hint(&mut self, hint: SchedulingHint) {
+        self.shape.scheduling_hint = hint;
+    }
+
+    /// Names input `port`, for tools that describe the dataflow, as reported in the logged [`OperatesEvent`](crate::logging::OperatesEvent).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the operator has no input `port`.
+    pub fn set_input_name(&mut self, port: usize, name: impl Into<String>) {
+        assert!(port < self.shape.inputs, "{}: no input port {} to name", self.shape.name, port);
+        self.shape.input_names[port] = Some(name.into());
+    }
+
+    /// Names output `port`, for tools that describe the dataflow, as reported in the logged [`OperatesEvent`](crate::logging::OperatesEvent).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the operator has no output `port`.
+    pub fn set_output_name(&mut self, port: usize, name: impl Into<String>) {
+        assert!(port < self.shape.outputs, "{}: no output port {} to name", self.shape.name, port);
+        self.shape.output_names[port] = Some(name.into());
+    }
+
     /// Adds a new input to a generic operator builder, returning the `Pull` implementor to use.
     pub fn new_input<C: Container, P>(&mut self, stream: &StreamCore<G, C>, pact: P) -> P::Puller
     where
@@ -120,11 +176,13 @@ impl<G: Scope> OperatorBuilder<G> {
     {
         let channel_id = self.scope.new_identifier();
         let logging = self.scope.logging();
+        let kind = pact.kind();
         let (sender, receiver) = pact.connect(&mut self.scope, channel_id, Rc::clone(&self.address), logging);
         let target = Target::new(self.index, self.shape.inputs);
-        stream.connect_to(target, sender, channel_id);
+        stream.connect_to_with_kind(target, sender, channel_id, kind);
 
         self.shape.inputs += 1;
+        self.shape.input_names.push(None);
         let connectivity: PortConnectivity<_> = connection.into_iter().collect();
         assert!(connectivity.iter_ports().all(|(o,_)| o < self.shape.outputs));
         self.summary.push(connectivity);
@@ -146,6 +204,7 @@ impl<G: Scope> OperatorBuilder<G> {
     {
         let new_output = self.shape.outputs;
         self.shape.outputs += 1;
+        self.shape.output_names.push(None);
         let (targets, registrar) = Tee::<G::Timestamp,C>::new();
         let source = Source::new(self.index, new_output);
         let stream = StreamCore::new(source, registrar, self.scope.clone());
@@ -177,9 +236,18 @@ impl<G: Scope> OperatorBuilder<G> {
         self.scope.add_operator_with_indices(Box::new(operator), self.index, self.global);
     }
 
+    /// An activator that schedules the operator.
+    pub(crate) fn activator(&self) -> crate::scheduling::Activator {
+        self.scope.activator_for(Rc::clone(&self.address))
+    }
+
     /// Information describing the operator.
     pub fn operator_info(&self) -> OperatorInfo {
-        OperatorInfo::new(self.index, self.global, Rc::clone(&self.address))
+        let mut info = OperatorInfo::new(self.index, self.global, Rc::clone(&self.address)).with_worker(self.scope.index(), self.shape.peers);
+        info.memory = self.memory.clone();
+        info.errors = self.errors.clone();
+        info.label = self.scope.label();
+        info
     }
 }
 
@@ -216,6 +284,8 @@ where
 {
     fn inputs(&self) -> usize { self.shape.inputs }
     fn outputs(&self) -> usize { self.shape.outputs }
+    fn input_name(&self, port: usize) -> Option<&str> { self.shape.input_name(port) }
+    fn output_name(&self, port: usize) -> Option<&str> { self.shape.output_name(port) }
 
     // announce internal topology as fully connected, and hold all default capabilities.
     fn get_internal_summary(&mut self) -> (Connectivity<T::Summary>, Rc<RefCell<SharedProgress<T>>>) {
@@ -240,4 +310,6 @@ where
     }
 
     fn notify_me(&self) -> bool { self.shape.notify }
+
+    fn scheduling_hint(&self) -> SchedulingHint { self.shape.scheduling_hint }
 }
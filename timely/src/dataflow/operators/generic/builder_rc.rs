@@ -1,13 +1,14 @@
 //! Types to build operators with general shapes.
 
 use std::rc::Rc;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::default::Default;
 
 use crate::progress::{ChangeBatch, Timestamp};
 use crate::progress::operate::SharedProgress;
 use crate::progress::frontier::{Antichain, MutableAntichain};
 
+use crate::scheduling::SchedulingHint;
 use crate::Container;
 use crate::dataflow::{Scope, StreamCore};
 use crate::dataflow::channels::pushers::Counter as PushCounter;
@@ -32,6 +33,10 @@ pub struct OperatorBuilder<G: Scope> {
     /// For each input, a shared list of summaries to each output.
     summaries: Vec<Rc<RefCell<PortConnectivity<<G::Timestamp as Timestamp>::Summary>>>>,
     produced: Vec<Rc<RefCell<ChangeBatch<G::Timestamp>>>>,
+    /// The limit on records read per invocation from inputs subsequently added.
+    input_limit: Option<usize>,
+    /// For each limited input, the records it has yielded in the current invocation.
+    input_yielded: Vec<Rc<Cell<usize>>>,
 }
 
 impl<G: Scope> OperatorBuilder<G> {
@@ -45,6 +50,8 @@ impl<G: Scope> OperatorBuilder<G> {
             internal: Rc::new(RefCell::new(Vec::new())),
             summaries: Vec::new(),
             produced: Vec::new(),
+            input_limit: None,
+            input_yielded: Vec::new(),
         }
     }
 
@@ -53,6 +60,127 @@ impl<G: Scope> OperatorBuilder<G> {
         self.builder.set_notify(notify);
     }
 
+    /// Limits the records that inputs subsequently added yield per invocation of the operator.
+    ///
+    /// By default, and with `None`, inputs yield all available records. With a limit of `records`,
+    /// an input that has yielded containers holding at least `records` records in an invocation of the
+    /// operator reports no further input until the next invocation, and schedules the operator to read
+    /// the remainder in a later invocation. Each invocation may read up to the limit, however many
+    /// records earlier invocations read. This bounds the records an operator stages from each input per invocation, as with
+    /// `for_each_time`, at the cost of more invocations. Containers are not split, so an invocation may
+    /// read up to one container beyond the limit. Unread records remain in their channel, where they
+    /// continue to hold back the input frontier until read.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    /// use timely::dataflow::operators::Input;
+    /// use timely::dataflow::operators::generic::builder_rc::OperatorBuilder;
+    /// use timely::dataflow::channels::pact::Pipeline;
+    /// use timely::dataflow::Scope;
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     let invocations = Rc::new(RefCell::new(Vec::new()));
+    ///     let invocations_inner = Rc::clone(&invocations);
+    ///     let mut input = worker.dataflow::<u64,_,_>(|scope| {
+    ///         let (input, stream) = scope.new_input::<u64>();
+    ///         let mut builder = OperatorBuilder::new("Limited".to_owned(), scope.clone());
+    ///         builder.set_input_limit(Some(10));
+    ///         let mut input_handle = builder.new_input(&stream, Pipeline);
+    ///         builder.build(move |_capabilities| {
+    ///             move |_frontiers| {
+    ///                 let mut records = 0;
+    ///                 input_handle.for_each(|_time, data: &mut Vec<u64>| records += data.len());
+    ///                 invocations_inner.borrow_mut().push(records);
+    ///             }
+    ///         });
+    ///         input
+    ///     });
+    ///     // Send a burst of one hundred single-record containers.
+    ///     for record in 0..100 {
+    ///         input.send_batch(&mut vec![record]);
+    ///     }
+    ///     input.close();
+    ///     while worker.step() { }
+    ///
+    ///     let invocations = invocations.borrow();
+    ///     assert!(invocations.iter().all(|records| *records <= 10));
+    ///     assert_eq!(invocations.iter().sum::<usize>(), 100);
+    /// });
+    /// ```
+    pub fn set_input_limit(&mut self, records: Option<usize>) {
+        self.input_limit = records;
+    }
+
+    /// Hints to the scheduler how the operator spends its time when scheduled.
+    ///
+    /// Operators are [`SchedulingHint::CpuBound`] unless hinted otherwise. An operator that may block
+    /// on IO when scheduled should be hinted as [`SchedulingHint::IoBound`], so that schedulers that
+    /// take hints into account can keep it from delaying the operators that only compute. The hint
+    /// is reported by [`Operate::scheduling_hint`](crate::progress::Operate::scheduling_hint), and
+    /// for now does not change how the worker schedules the operator.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::ToStream;
+    /// use timely::dataflow::operators::generic::builder_rc::OperatorBuilder;
+    /// use timely::dataflow::channels::pact::Pipeline;
+    /// use timely::scheduling::SchedulingHint;
+    ///
+    /// timely::example(|scope| {
+    ///     let stream = (0..10u64).to_stream(scope);
+    ///     let mut builder = OperatorBuilder::new("Writer".to_owned(), scope.clone());
+    ///     assert_eq!(builder.shape().scheduling_hint(), SchedulingHint::CpuBound);
+    ///     builder.set_scheduling_hint(SchedulingHint::IoBound);
+    ///     assert_eq!(builder.shape().scheduling_hint(), SchedulingHint::IoBound);
+    ///     let mut input = builder.new_input(&stream, Pipeline);
+    ///     builder.build(|_capabilities| move |_frontiers| input.for_each(|_time, _data| { }));
+    /// });
+    /// ```
+    pub fn set_scheduling_hint(&mut self, hint: SchedulingHint) {
+        self.builder.set_scheduling_hint(hint);
+    }
+
+    /// Names input `port`, for tools that describe the dataflow, as reported in the logged [`OperatesEvent`](crate::logging::OperatesEvent).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the operator has no input `port`.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::ToStream;
+    /// use timely::dataflow::operators::generic::builder_rc::OperatorBuilder;
+    /// use timely::dataflow::channels::pact::Pipeline;
+    /// use timely::dataflow::Scope;
+    ///
+    /// timely::example(|scope| {
+    ///     let stream = (0..10u64).to_stream(scope);
+    ///     let mut builder = OperatorBuilder::new("Splitter".to_owned(), scope.clone());
+    ///     let mut input = builder.new_input(&stream, Pipeline);
+    ///     let (_evens, _even_stream) = builder.new_output::<Vec<u64>>();
+    ///     let (_odds, _odd_stream) = builder.new_output::<Vec<u64>>();
+    ///     builder.set_input_name(0, "numbers");
+    ///     builder.set_output_name(0, "evens");
+    ///     builder.set_output_name(1, "odds");
+    ///     assert_eq!(builder.shape().output_name(1), Some("odds"));
+    ///     builder.build(|_capabilities| move |_frontiers| input.for_each(|_time, _data| { }));
+    /// });
+    /// ```
+    pub fn set_input_name(&mut self, port: usize, name: impl Into<String>) {
+        self.builder.set_input_name(port, name);
+    }
+
+    /// Names output `port`, for tools that describe the dataflow, as reported in the logged [`OperatesEvent`](crate::logging::OperatesEvent).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the operator has no output `port`.
+    pub fn set_output_name(&mut self, port: usize, name: impl Into<String>) {
+        self.builder.set_output_name(port, name);
+    }
+
     /// Adds a new input to a generic operator builder, returning the `Pull` implementor to use.
     pub fn new_input<C: Container, P>(&mut self, stream: &StreamCore<G, C>, pact: P) -> InputHandleCore<G::Timestamp, C, P::Puller>
     where
@@ -84,7 +212,13 @@ impl<G: Scope> OperatorBuilder<G> {
         let shared_summary = Rc::new(RefCell::new(connection.into_iter().collect()));
         self.summaries.push(Rc::clone(&shared_summary));
 
-        new_input_handle(input, Rc::clone(&self.internal), shared_summary)
+        let mut handle = new_input_handle(input, Rc::clone(&self.internal), shared_summary);
+        if let Some(records) = self.input_limit {
+            let yielded = Rc::new(Cell::new(0));
+            self.input_yielded.push(Rc::clone(&yielded));
+            handle.set_limit(records, yielded, self.builder.activator());
+        }
+        handle
     }
 
     /// Adds a new output to a generic operator builder, returning the `Push` implementor to use.
@@ -161,6 +295,7 @@ impl<G: Scope> OperatorBuilder<G> {
         let self_consumed = self.consumed;
         let self_internal = self.internal;
         let self_produced = self.produced;
+        let input_yielded = self.input_yielded;
 
         let raw_logic =
         move |progress: &mut SharedProgress<G::Timestamp>| {
@@ -170,6 +305,11 @@ impl<G: Scope> OperatorBuilder<G> {
                 frontier.update_iter(progress.drain());
             }
 
+            // limited inputs yield up to their limit anew in each invocation.
+            for yielded in input_yielded.iter() {
+                yielded.set(0);
+            }
+
             // invoke supplied logic
             let result = logic(&self_frontier[..]);
 
@@ -292,4 +432,64 @@ mod tests {
             "Hello".to_owned()
         });
     }
+
+    #[test]
+    fn input_limits_reset_each_invocation() {
+
+        // This tests that an invocation that stops reading before its limit does not reduce the
+        // limit of the next invocation, and that an input stays exhausted for the rest of an
+        // invocation that reached its limit.
+
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        use crate::dataflow::channels::pact::Pipeline;
+        use crate::dataflow::operators::Input;
+        use crate::dataflow::operators::generic::builder_rc::OperatorBuilder;
+        use crate::scheduling::Scheduler;
+
+        let invocations = crate::execute_directly(|worker| {
+            let invocations = Rc::new(RefCell::new(Vec::new()));
+            let invocations_inner = Rc::clone(&invocations);
+            let mut input = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<u64>();
+                let mut builder = OperatorBuilder::new("Limited".to_owned(), scope.clone());
+                builder.set_input_limit(Some(10));
+                let mut input_handle = builder.new_input(&stream, Pipeline);
+                let activator = scope.activator_for(builder.operator_info().address);
+                builder.build(move |_capabilities| {
+                    let mut partial = false;
+                    move |_frontiers| {
+                        let mut records = 0;
+                        if !partial {
+                            // Reads only five records, and asks to be invoked again for the rest.
+                            for _ in 0..5 {
+                                if let Some((_time, data)) = input_handle.next() { records += data.len(); }
+                            }
+                            partial = records > 0;
+                            activator.activate();
+                        }
+                        else {
+                            input_handle.for_each(|_time, data: &mut Vec<u64>| records += data.len());
+                            input_handle.for_each(|_time, data: &mut Vec<u64>| records += data.len());
+                        }
+                        if records > 0 {
+                            invocations_inner.borrow_mut().push(records);
+                        }
+                    }
+                });
+                input
+            });
+            for record in 0..100 {
+                input.send_batch(&mut vec![record]);
+            }
+            input.close();
+            while worker.step() { }
+            let invocations = invocations.borrow().clone();
+            invocations
+        });
+
+        assert_eq!(&invocations[..4], &[5, 10, 10, 10]);
+        assert_eq!(invocations.iter().sum::<usize>(), 100);
+    }
 }
@@ -0,0 +1,99 @@
+//! Typed errors reported by operators to the worker.
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use crate::worker::AsWorker;
+
+/// The key under which the error queue is stored in the worker's state registry.
+const QUEUE_KEY: &str = "timely::errors";
+
+/// An error reported by an operator.
+#[derive(Debug)]
+pub struct OperatorError<E> {
+    /// The name of the operator that reported the error.
+    pub operator: String,
+    /// The worker-unique identifier of the operator.
+    pub id: usize,
+    /// The reported error.
+    pub error: E,
+}
+
+/// A worker-level queue of the errors reported by its operators, in the order they were reported.
+///
+/// Errors remain queued until drained, with [`Worker::drain_errors`](crate::worker::Worker::drain_errors),
+/// however many times the worker steps in between.
+#[derive(Default)]
+pub(crate) struct ErrorQueue {
+    errors: VecDeque<OperatorError<Box<dyn Any + Send>>>,
+}
+
+impl ErrorQueue {
+    /// Returns the worker's error queue.
+    pub(crate) fn get<A: AsWorker>(worker: &A) -> Rc<RefCell<ErrorQueue>> {
+        worker.state_registry().get_or_insert::<RefCell<ErrorQueue>>(QUEUE_KEY)
+    }
+
+    /// Removes and returns the queued errors of type `E`, in the order they were reported.
+    ///
+    /// Errors of other types remain queued.
+    pub(crate) fn drain<E: Any + Send>(&mut self) -> Vec<OperatorError<E>> {
+        let mut drained = Vec::new();
+        let mut retained = VecDeque::with_capacity(self.errors.len());
+        for queued in self.errors.drain(..) {
+            let OperatorError { operator, id, error } = queued;
+            match error.downcast::<E>() {
+                Ok(error) => drained.push(OperatorError { operator, id, error: *error }),
+                Err(error) => retained.push_back(OperatorError { operator, id, error }),
+            }
+        }
+        self.errors = retained;
+        drained
+    }
+}
+
+/// A handle through which an operator reports errors to its worker.
+///
+/// The handle is available from the operator's [`OperatorInfo`](super::OperatorInfo). Reported
+/// errors are queued at the worker, where the driver of the computation can drain them between
+/// steps and decide whether to continue or abort. Reporting an error has no other effect on the
+/// operator or its dataflow.
+#[derive(Clone, Default)]
+pub struct ErrorReporter {
+    queue: Option<Rc<RefCell<ErrorQueue>>>,
+    operator: Rc<str>,
+    id: usize,
+}
+
+impl ErrorReporter {
+    /// Creates a reporter for the operator `operator` with identifier `id`, reporting to `worker`.
+    pub(crate) fn new<A: AsWorker>(worker: &A, operator: &str, id: usize) -> Self {
+        ErrorReporter { queue: Some(ErrorQueue::get(worker)), operator: operator.into(), id }
+    }
+
+    /// Queues `error` at the worker.
+    ///
+    /// Reporters not constructed by operator builders are not attached to a worker, and discard
+    /// their errors.
+    pub fn report<E: Any + Send>(&self, error: E) {
+        if let Some(queue) = &self.queue {
+            queue.borrow_mut().errors.push_back(OperatorError {
+                operator: self.operator.to_string(),
+                id: self.id,
+                error: Box::new(error),
+            });
+        }
+    }
+}
+
+impl std::fmt::Debug for ErrorReporter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ErrorReporter")
+            .field("operator", &self.operator)
+            .field("id", &self.id)
+            .field("attached", &self.queue.is_some())
+            .finish()
+    }
+}
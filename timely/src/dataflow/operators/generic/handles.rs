@@ -4,7 +4,7 @@
 //! the operator would with its input and output streams.
 
 use std::rc::Rc;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::VecDeque;
 
 use crate::progress::Timestamp;
@@ -18,6 +18,7 @@ use crate::container::{CapacityContainerBuilder, PushInto};
 
 use crate::dataflow::operators::InputCapability;
 use crate::dataflow::operators::capability::CapabilityTrait;
+use crate::scheduling::Activator;
 
 #[must_use]
 pub struct InputSession<'a, T: Timestamp, C, P: Pull<Message<T, C>>> {
@@ -50,6 +51,18 @@ pub struct InputHandleCore<T: Timestamp, C, P: Pull<Message<T, C>>> {
     /// Staged capabilities and containers.
     staging: VecDeque<(InputCapability<T>, C)>,
     staged: Vec<C>,
+    /// An optional limit on the records yielded before reporting no further input.
+    limit: Option<InputLimit>,
+}
+
+/// A limit on the records an input yields per invocation of its operator.
+struct InputLimit {
+    /// The number of records after which the input reports no further input.
+    records: usize,
+    /// The number of records yielded in the current invocation, reset by the operator as it is invoked.
+    yielded: Rc<Cell<usize>>,
+    /// Reschedules the operator to read the remaining input.
+    activator: Activator,
 }
 
 impl<T: Timestamp, C: Accountable, P: Pull<Message<T, C>>> InputHandleCore<T, C, P> {
@@ -60,14 +73,35 @@ impl<T: Timestamp, C: Accountable, P: Pull<Message<T, C>>> InputHandleCore<T, C,
     /// Reads the next input buffer (at some timestamp `t`) and a corresponding capability for `t`.
     /// The timestamp `t` of the input buffer can be retrieved by invoking `.time()` on the capability.
     /// Returns `None` when there's no more data available.
+    ///
+    /// If the input is limited, it returns `None` once it has yielded at least its limit of records in
+    /// the current invocation of the operator, and schedules the operator to read the remaining input
+    /// in a later invocation.
     #[inline]
     pub fn next(&mut self) -> Option<(InputCapability<T>, &mut C)> {
+        if let Some(limit) = &self.limit {
+            if limit.yielded.get() >= limit.records {
+                limit.activator.activate();
+                return None;
+            }
+        }
         let internal = &self.internal;
         let summaries = &self.summaries;
-        self.pull_counter.next_guarded().map(|(guard, bundle)| {
+        let next = self.pull_counter.next_guarded();
+        if let (Some(limit), Some((_, bundle))) = (&self.limit, &next) {
+            limit.yielded.set(limit.yielded.get() + usize::try_from(bundle.data.record_count()).unwrap_or(0));
+        }
+        next.map(|(guard, bundle)| {
             (InputCapability::new(Rc::clone(internal), Rc::clone(summaries), guard), &mut bundle.data)
         })
     }
+
+    /// Limits the input to yield about `records` records per invocation before reporting no further input.
+    ///
+    /// The operator resets `yielded` to zero as each invocation starts.
+    pub(crate) fn set_limit(&mut self, records: usize, yielded: Rc<Cell<usize>>, activator: Activator) {
+        self.limit = Some(InputLimit { records: records.max(1), yielded, activator });
+    }
     /// Iterates through pairs of capability and container.
     ///
     /// The `for_each_time` method is equivalent, but groups containers by capability and is preferred,
@@ -111,6 +145,7 @@ pub fn new_input_handle<T: Timestamp, C: Accountable, P: Pull<Message<T, C>>>(
         summaries,
         staging: Default::default(),
         staged: Default::default(),
+        limit: None,
     }
 }
 
@@ -198,6 +233,12 @@ impl<'a: 'b, 'b, T: Timestamp, CB: ContainerBuilder, CT: CapabilityTrait<T>> Ses
         for container in containers { self.buffer.session.give(&self.capability, container); }
     }
 
+    /// Sends a message without records at the time specified by the [Session], which schedules
+    /// the operators receiving it by a pipeline. See [`Progress::give_empty`](crate::dataflow::channels::pushers::progress::Progress::give_empty).
+    #[inline] pub fn give_empty(&mut self) {
+        self.buffer.session.give_empty::<CB::Container, _>(self.capability);
+    }
+
     /// Extracts built containers and sends them.
     pub fn extract_and_send(&mut self) {
         while let Some(container) = self.buffer.builder.extract() {
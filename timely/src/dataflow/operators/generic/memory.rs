@@ -0,0 +1,201 @@
+//! Opt-in accounting of the memory retained by operators.
+
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+
+use crate::worker::AsWorker;
+
+/// The key under which the memory registry is stored in the worker's state registry.
+const REGISTRY_KEY: &str = "timely::memory";
+/// The key under which the memory limit is stored in the worker's state registry.
+const LIMIT_KEY: &str = "timely::memory::limit";
+
+/// A worker-level registry of the memory that operators report retaining.
+///
+/// Memory accounting is disabled unless a registry is enabled for the worker, with
+/// [`MemoryRegistry::enable`], before the dataflows of interest are constructed. Once enabled,
+/// each operator constructed in the worker is given an enabled [`MemoryReporter`] through its
+/// [`OperatorInfo`](super::OperatorInfo), and stateful operators may use it to report an
+/// estimate of the bytes they currently hold.
+///
+/// # Examples
+/// ```
+/// use timely::container::CapacityContainerBuilder;
+/// use timely::dataflow::operators::{Input, Operator};
+/// use timely::dataflow::operators::generic::MemoryRegistry;
+/// use timely::dataflow::channels::pact::Pipeline;
+///
+/// timely::execute_directly(|worker| {
+///     let registry = MemoryRegistry::enable(worker);
+///     let mut input = worker.dataflow::<u64,_,_>(|scope| {
+///         let (input, stream) = scope.new_input::<u64>();
+///         stream.unary::<CapacityContainerBuilder<Vec<u64>>,_,_,_>(Pipeline, "Hoard", |_capability, info| {
+///             let mut hoard = Vec::new();
+///             move |input, _output| {
+///                 input.for_each(|_time, data| hoard.append(data));
+///                 info.memory.report(|| hoard.capacity() * std::mem::size_of::<u64>());
+///             }
+///         });
+///         input
+///     });
+///     for round in 0..100 {
+///         input.send(round);
+///     }
+///     input.advance_to(1);
+///     worker.step();
+///     worker.step();
+///
+///     let sizes = registry.borrow().sizes();
+///     assert_eq!(sizes.len(), 1);
+///     assert_eq!(sizes[0].1, "Hoard");
+///     assert!(sizes[0].2 >= 100 * std::mem::size_of::<u64>());
+/// });
+/// ```
+#[derive(Debug, Default)]
+pub struct MemoryRegistry {
+    operators: Vec<(usize, String, Weak<Cell<usize>>)>,
+}
+
+impl MemoryRegistry {
+    /// Enables memory accounting for operators subsequently constructed in `worker`.
+    ///
+    /// Returns a handle to the worker's registry, which is shared by repeated calls.
+    pub fn enable<A: AsWorker>(worker: &A) -> Rc<RefCell<MemoryRegistry>> {
+        worker.state_registry().get_or_insert::<RefCell<MemoryRegistry>>(REGISTRY_KEY)
+    }
+
+    /// Returns the worker's registry, if memory accounting has been enabled.
+    pub fn get<A: AsWorker>(worker: &A) -> Option<Rc<RefCell<MemoryRegistry>>> {
+        worker.state_registry().get(REGISTRY_KEY)
+    }
+
+    /// Registers an operator, returning the reporter through which it reports its memory.
+    pub(crate) fn register(&mut self, global_id: usize, name: &str) -> MemoryReporter {
+        self.operators.retain(|(_, _, bytes)| bytes.strong_count() > 0);
+        let bytes = Rc::new(Cell::new(0));
+        self.operators.push((global_id, name.to_owned(), Rc::downgrade(&bytes)));
+        MemoryReporter { bytes: Some(bytes) }
+    }
+
+    /// The most recent report `(global_id, name, bytes)` of each live operator that holds a reporter,
+    /// in decreasing order of bytes.
+    ///
+    /// Operators whose dataflows have been dropped, and those that discarded their reporter, are
+    /// not included.
+    pub fn sizes(&self) -> Vec<(usize, String, usize)> {
+        let mut sizes: Vec<_> = self.operators
+            .iter()
+            .filter_map(|(global_id, name, bytes)| bytes.upgrade().map(|bytes| (*global_id, name.clone(), bytes.get())))
+            .collect();
+        sizes.sort_by(|x, y| y.2.cmp(&x.2).then(x.0.cmp(&y.0)));
+        sizes
+    }
+
+    /// The sum of the most recent reports of the live operators that hold a reporter.
+    pub fn total(&self) -> usize {
+        self.operators.iter().filter_map(|(_, _, bytes)| bytes.upgrade()).map(|bytes| bytes.get()).sum()
+    }
+}
+
+/// A worker-level limit on the memory that operators report retaining, beyond which load is shed.
+///
+/// The limit is disabled unless enabled for the worker, with [`MemoryLimit::enable`], which also
+/// enables memory accounting. While the memory reported to the worker's [`MemoryRegistry`] exceeds
+/// the limit, operators designated as sheddable, those of
+/// [`ShedLoad::shed_load`](crate::dataflow::operators::ShedLoad::shed_load), drop the records they
+/// receive rather than passing them on, and count the records they drop, here and in their own
+/// counts. The memory is that reported by operators, an estimate only of what they retain, and
+/// shedding is a last resort to keep a worker alive under overload: the records shed are lost.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::generic::MemoryLimit;
+///
+/// timely::execute_directly(|worker| {
+///     let limit = MemoryLimit::enable(worker, 1 << 30);
+///     // No operators report retaining memory yet.
+///     assert!(!limit.exceeded());
+///     assert_eq!(limit.shed(), 0);
+/// });
+/// ```
+#[derive(Debug)]
+pub struct MemoryLimit {
+    registry: Rc<RefCell<MemoryRegistry>>,
+    bytes: Cell<usize>,
+    shed: Cell<u64>,
+}
+
+impl MemoryLimit {
+    /// Limits the memory operators subsequently constructed in `worker` report to `bytes`, and enables memory accounting.
+    ///
+    /// Returns a handle to the worker's limit, which is shared by repeated calls, each of which sets
+    /// the limit anew.
+    pub fn enable<A: AsWorker>(worker: &A, bytes: usize) -> Rc<MemoryLimit> {
+        let registry = MemoryRegistry::enable(worker);
+        let limit = worker.state_registry().get_or_insert_with(LIMIT_KEY, || MemoryLimit { registry, bytes: Cell::new(bytes), shed: Cell::new(0) });
+        limit.set_limit(bytes);
+        limit
+    }
+
+    /// Returns the worker's limit, if one has been enabled.
+    pub fn get<A: AsWorker>(worker: &A) -> Option<Rc<MemoryLimit>> {
+        worker.state_registry().get(LIMIT_KEY)
+    }
+
+    /// The limit, in bytes.
+    pub fn limit(&self) -> usize {
+        self.bytes.get()
+    }
+
+    /// Sets the limit to `bytes`.
+    pub fn set_limit(&self, bytes: usize) {
+        self.bytes.set(bytes);
+    }
+
+    /// The memory operators currently report retaining, in bytes.
+    pub fn retained(&self) -> usize {
+        self.registry.borrow().total()
+    }
+
+    /// Returns `true` if the memory operators currently report retaining exceeds the limit.
+    pub fn exceeded(&self) -> bool {
+        self.retained() > self.limit()
+    }
+
+    /// The number of records sheddable operators have dropped so far.
+    pub fn shed(&self) -> u64 {
+        self.shed.get()
+    }
+
+    /// Counts `records` records as shed.
+    pub(crate) fn record_shed(&self, records: u64) {
+        self.shed.set(self.shed.get() + records);
+    }
+}
+
+/// A handle through which an operator reports the memory it retains.
+///
+/// Reporters are disabled unless memory accounting is enabled for the worker. Reports to a
+/// disabled reporter have no effect, and their estimates are not computed.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryReporter {
+    bytes: Option<Rc<Cell<usize>>>,
+}
+
+impl MemoryReporter {
+    /// Returns `true` if reports are recorded.
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.bytes.is_some()
+    }
+
+    /// Records `estimate()` as the number of bytes the operator currently retains.
+    ///
+    /// The estimate replaces any previous report, and is only computed if the reporter is enabled.
+    #[inline]
+    pub fn report<F: FnOnce() -> usize>(&self, estimate: F) {
+        if let Some(bytes) = &self.bytes {
+            bytes.set(estimate());
+        }
+    }
+}
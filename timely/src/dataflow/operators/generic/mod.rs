@@ -4,12 +4,18 @@ pub mod operator;
 pub mod builder_rc;
 pub mod builder_raw;
 // pub mod builder_ref;
+mod errors;
 mod handles;
+mod memory;
 mod notificator;
 mod operator_info;
+pub(crate) mod stash;
 
 pub use self::handles::{InputHandleCore, OutputBuilder, OutputBuilderSession, Session};
 pub use self::notificator::{Notificator, FrontierNotificator};
 
-pub use self::operator::{Operator, source};
+pub use self::operator::{Operator, source, pausable_source, PauseHandle};
+pub use self::errors::{ErrorReporter, OperatorError};
+pub(crate) use self::errors::ErrorQueue;
+pub use self::memory::{MemoryLimit, MemoryRegistry, MemoryReporter};
 pub use self::operator_info::OperatorInfo;
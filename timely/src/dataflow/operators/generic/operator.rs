@@ -1,6 +1,9 @@
 
 //! Methods to construct generic streaming and blocking unary operators.
 
+use std::cell::Cell;
+use std::rc::Rc;
+
 use crate::progress::frontier::MutableAntichain;
 use crate::dataflow::channels::pact::ParallelizationContract;
 
@@ -14,6 +17,7 @@ use crate::dataflow::operators::generic::OperatorInfo;
 use crate::dataflow::operators::generic::notificator::{Notificator, FrontierNotificator};
 use crate::{Container, ContainerBuilder};
 use crate::container::CapacityContainerBuilder;
+use crate::scheduling::Activator;
 
 /// Methods to construct generic streaming and blocking operators.
 pub trait Operator<G: Scope, C1> {
@@ -186,6 +190,57 @@ pub trait Operator<G: Scope, C1> {
         P1: ParallelizationContract<G::Timestamp, C1>,
         P2: ParallelizationContract<G::Timestamp, C2>;
 
+    /// Creates a new dataflow operator with a primary input and an optional side input, which
+    /// repeatedly invokes `logic`, the function returned by the function passed as `constructor`.
+    ///
+    /// The primary input is partitioned by `pact`, and the side input, if there is one, by
+    /// `side_pact`. A side input of configuration or model updates that every worker should see
+    /// is usually broadcast, for example with [`Broadcast`](crate::dataflow::operators::Broadcast)
+    /// and a [`Pipeline`](crate::dataflow::channels::pact::Pipeline) pact. `logic` receives the primary
+    /// input and its frontier, and `Some` side input and its frontier if `side` is `Some`, or `None`
+    /// otherwise, in which case `side_pact` is not used.
+    ///
+    /// With a side input the operator has two inputs, and the frontier of its output reflects both
+    /// of them: a capability of the operator holds back the output as usual, and so does the side
+    /// input's frontier, up to the times of the primary input it may still affect. Without one, the
+    /// operator has a single input, as one built with [`Operator::unary_frontier`] does.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{Broadcast, ToStream, Inspect};
+    /// use timely::dataflow::operators::generic::operator::Operator;
+    /// use timely::dataflow::channels::pact::Pipeline;
+    ///
+    /// timely::example(|scope| {
+    ///     let factors = vec![10u64].to_stream(scope).broadcast();
+    ///     for side in [None, Some(&factors)] {
+    ///         (0u64..5).to_stream(scope)
+    ///             .unary_frontier_with_side_input(side, Pipeline, Pipeline, "Scale", |_capability, _info| {
+    ///                 // Records are scaled by the latest factor received, if any.
+    ///                 let mut factor = 1;
+    ///                 move |(input, _frontier), side, output| {
+    ///                     if let Some((side, _side_frontier)) = side {
+    ///                         side.for_each(|_time, data| if let Some(latest) = data.last() { factor = *latest; });
+    ///                     }
+    ///                     input.for_each(|time, data| output.session(&time).give_iterator(data.drain(..).map(|x| x * factor)));
+    ///                 }
+    ///             })
+    ///             .container::<Vec<_>>()
+    ///             .inspect(|x| println!("scaled: {:?}", x));
+    ///     }
+    /// });
+    /// ```
+    fn unary_frontier_with_side_input<C2, CB, B, L, P1, P2>(&self, side: Option<&StreamCore<G, C2>>, pact: P1, side_pact: P2, name: &str, constructor: B) -> StreamCore<G, CB::Container>
+    where
+        C2: Container,
+        CB: ContainerBuilder,
+        B: FnOnce(Capability<G::Timestamp>, OperatorInfo) -> L,
+        L: FnMut((InputSession<'_, G::Timestamp, C1, P1::Puller>, &MutableAntichain<G::Timestamp>),
+                 Option<(InputSession<'_, G::Timestamp, C2, P2::Puller>, &MutableAntichain<G::Timestamp>)>,
+                 &mut OutputBuilderSession<'_, G::Timestamp, CB>)+'static,
+        P1: ParallelizationContract<G::Timestamp, C1>,
+        P2: ParallelizationContract<G::Timestamp, C2>;
+
     /// Creates a new dataflow operator that partitions its input streams by a parallelization
     /// strategy `pact`, and repeatedly invokes `logic`, the function returned by the function passed as `constructor`.
     /// `logic` can read from the input streams, write to the output stream, and inspect the frontier at the inputs.
@@ -412,6 +467,40 @@ impl<G: Scope, C1: Container> Operator<G, C1> for StreamCore<G, C1> {
         stream
     }
 
+    fn unary_frontier_with_side_input<C2, CB, B, L, P1, P2>(&self, side: Option<&StreamCore<G, C2>>, pact: P1, side_pact: P2, name: &str, constructor: B) -> StreamCore<G, CB::Container>
+    where
+        C2: Container,
+        CB: ContainerBuilder,
+        B: FnOnce(Capability<G::Timestamp>, OperatorInfo) -> L,
+        L: FnMut((InputSession<'_, G::Timestamp, C1, P1::Puller>, &MutableAntichain<G::Timestamp>),
+                 Option<(InputSession<'_, G::Timestamp, C2, P2::Puller>, &MutableAntichain<G::Timestamp>)>,
+                 &mut OutputBuilderSession<'_, G::Timestamp, CB>)+'static,
+        P1: ParallelizationContract<G::Timestamp, C1>,
+        P2: ParallelizationContract<G::Timestamp, C2> {
+
+        let mut builder = OperatorBuilder::new(name.to_owned(), self.scope());
+        let operator_info = builder.operator_info();
+
+        let mut input = builder.new_input(self, pact);
+        // The side input, if any, is the operator's second input.
+        let mut side_input = side.map(|side| builder.new_input(side, side_pact));
+        let (output, stream) = builder.new_output();
+        let mut output = OutputBuilder::from(output);
+
+        builder.build(move |mut capabilities| {
+            // `capabilities` should be a single-element vector.
+            let capability = capabilities.pop().unwrap();
+            let mut logic = constructor(capability, operator_info);
+            move |frontiers| {
+                let mut output_handle = output.activate();
+                let side_session = side_input.as_mut().map(|side_input| (side_input.activate(), &frontiers[1]));
+                logic((input.activate(), &frontiers[0]), side_session, &mut output_handle);
+            }
+        });
+
+        stream
+    }
+
     fn binary_notify<C2: Container,
               CB: ContainerBuilder,
               L: FnMut(InputSession<'_, G::Timestamp, C1, P1::Puller>,
@@ -554,6 +643,104 @@ where
     stream
 }
 
+/// A handle to pause and resume a source created by `pausable_source`.
+///
+/// The handle is local to the worker that created the source, and only affects that worker's
+/// instance of the operator.
+#[derive(Clone, Debug)]
+pub struct PauseHandle {
+    paused: Rc<Cell<bool>>,
+    activator: Activator,
+}
+
+impl PauseHandle {
+    /// Pauses the source, which is not invoked until resumed.
+    pub fn pause(&self) {
+        self.paused.set(true);
+    }
+    /// Resumes the source, and schedules it to be invoked.
+    pub fn resume(&self) {
+        self.paused.set(false);
+        self.activator.activate();
+    }
+    /// Indicates whether the source is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused.get()
+    }
+}
+
+/// Creates a new data stream source for a scope that can be paused and resumed.
+///
+/// The source is defined as for `source`, and is returned along with a `PauseHandle`. While paused,
+/// the logic of the source is not invoked, and so it produces nothing, but any capabilities it holds
+/// are retained: the output frontier does not advance past them, and operators downstream of the
+/// source cannot complete those times until it is resumed and releases its capabilities. This keeps
+/// the frontier from overtaking data the source has yet to produce, but also delays the completion
+/// of times for as long as the source is paused. Once resumed, the source is scheduled again,
+/// without needing to have requested it.
+///
+/// # Examples
+/// ```
+/// use timely::scheduling::Scheduler;
+/// use timely::dataflow::operators::{Inspect, Probe};
+/// use timely::dataflow::operators::generic::operator::pausable_source;
+/// use timely::dataflow::Scope;
+///
+/// timely::execute_directly(|worker| {
+///     let (pause, probe) = worker.dataflow::<u64,_,_>(|scope| {
+///         let (pause, stream) = pausable_source(scope, "Source", |capability, info| {
+///             let activator = scope.activator_for(info.address);
+///             let mut cap = Some(capability);
+///             move |output| {
+///                 if let Some(cap) = cap.as_mut() {
+///                     let time = *cap.time();
+///                     output.session(&cap).give(time);
+///                     cap.downgrade(&(time + 1));
+///                 }
+///                 if cap.as_ref().map(|cap| *cap.time() > 20).unwrap_or(false) { cap = None; }
+///                 else { activator.activate(); }
+///             }
+///         });
+///         let probe = stream.container::<Vec<_>>().inspect(|x| println!("number: {:?}", x)).probe();
+///         (pause, probe)
+///     });
+///
+///     // While paused, the source holds its capability, and the frontier does not advance.
+///     pause.pause();
+///     for _ in 0..10 { worker.step(); }
+///     let frontier = probe.with_frontier(|frontier| frontier.to_vec());
+///     for _ in 0..10 { worker.step(); }
+///     assert_eq!(probe.with_frontier(|frontier| frontier.to_vec()), frontier);
+///
+///     pause.resume();
+///     worker.step_while(|| !probe.done());
+/// });
+/// ```
+pub fn pausable_source<G: Scope, CB, B, L>(scope: &G, name: &str, constructor: B) -> (PauseHandle, StreamCore<G, CB::Container>)
+where
+    CB: ContainerBuilder,
+    B: FnOnce(Capability<G::Timestamp>, OperatorInfo) -> L,
+    L: FnMut(&mut OutputBuilderSession<'_, G::Timestamp, CB>)+'static {
+
+    let paused = Rc::new(Cell::new(false));
+    let mut handle = None;
+    let stream = source(scope, name, |capability, info| {
+        handle = Some(PauseHandle {
+            paused: Rc::clone(&paused),
+            activator: scope.activator_for(Rc::clone(&info.address)),
+        });
+        let mut logic = constructor(capability, info);
+        move |output: &mut OutputBuilderSession<'_, G::Timestamp, CB>| {
+            // A paused source is not rescheduled; `resume` schedules it again.
+            if !paused.get() {
+                logic(output);
+            }
+        }
+    });
+
+    (handle.expect("source constructor not invoked"), stream)
+}
+
 /// Constructs an empty stream.
 ///
 /// This method is useful in patterns where an input is required, but there is no
@@ -1,6 +1,33 @@
 use std::rc::Rc;
 
+use super::{ErrorReporter, MemoryReporter};
+
 /// Information about the operator being constructed
+///
+/// The information is passed to the constructors of generic operators, and describes both the
+/// operator and the worker constructing it. Operator logic can use `index` and `peers`, for example
+/// to partition the work of a source among workers, rather than capturing them from the worker.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::Inspect;
+/// use timely::dataflow::operators::generic::operator::source;
+///
+/// timely::example(|scope| {
+///     source(scope, "Range", |capability, info| {
+///         let mut capability = Some(capability);
+///         move |output| {
+///             if let Some(cap) = capability.take() {
+///                 // Each worker produces the values of `0 .. 100` congruent to its index.
+///                 let mine = (0 .. 100u64).filter(|x| *x as usize % info.peers == info.index);
+///                 output.session(&cap).give_iterator(mine);
+///             }
+///         }
+///     })
+///     .container::<Vec<_>>()
+///     .inspect(|x| println!("produced: {:?}", x));
+/// });
+/// ```
 #[derive(Clone)]
 pub struct OperatorInfo {
     /// Scope-local index assigned to the operator being constructed.
@@ -9,15 +36,47 @@ pub struct OperatorInfo {
     pub global_id: usize,
     /// Operator address.
     pub address: Rc<[usize]>,
+    /// Index of the worker constructing the operator, among its peers.
+    pub index: usize,
+    /// Number of peer workers.
+    pub peers: usize,
+    /// Reporter for the memory retained by the operator, disabled unless memory accounting is enabled.
+    pub memory: MemoryReporter,
+    /// Reporter for errors, which are queued at the worker until drained.
+    pub errors: ErrorReporter,
+    /// The label of the scope containing the operator, if any.
+    pub label: Option<Rc<str>>,
 }
 
 impl OperatorInfo {
     /// Construct a new `OperatorInfo`.
+    ///
+    /// The operator is described as constructed by the only worker, until set with [`Self::with_worker`].
     pub fn new(local_id: usize, global_id: usize, address: Rc<[usize]>) -> OperatorInfo {
         OperatorInfo {
             local_id,
             global_id,
             address,
+            index: 0,
+            peers: 1,
+            memory: MemoryReporter::default(),
+            errors: ErrorReporter::default(),
+            label: None,
         }
     }
+
+    /// Sets the index of the worker constructing the operator, and the number of its peers.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::generic::OperatorInfo;
+    ///
+    /// let info = OperatorInfo::new(0, 0, [0].into()).with_worker(2, 4);
+    /// assert_eq!((info.index, info.peers), (2, 4));
+    /// ```
+    pub fn with_worker(mut self, index: usize, peers: usize) -> Self {
+        self.index = index;
+        self.peers = peers;
+        self
+    }
 }
@@ -0,0 +1,92 @@
+//! Values stashed by time until operators release them, as their input frontiers pass the times.
+
+use crate::dataflow::operators::{Capability, InputCapability};
+use crate::progress::Timestamp;
+
+/// The time under which a value is stashed: the time itself, or a capability for it.
+pub(crate) trait StashKey {
+    /// The type of the time.
+    type Time: Timestamp;
+    /// The time of the key.
+    fn stash_time(&self) -> &Self::Time;
+}
+
+impl<T: Timestamp> StashKey for T {
+    type Time = T;
+    fn stash_time(&self) -> &T { self }
+}
+
+impl<T: Timestamp> StashKey for Capability<T> {
+    type Time = T;
+    fn stash_time(&self) -> &T { self.time() }
+}
+
+/// A value for each of a set of distinct times, each stashed under a key for its time.
+///
+/// Operators that hold records until their input frontier passes the time of the records stash
+/// them here, usually under a capability for the time, and release them in order of time.
+pub(crate) struct Stash<K, S> {
+    entries: Vec<(K, S)>,
+}
+
+impl<K, S> Default for Stash<K, S> {
+    fn default() -> Self {
+        Stash { entries: Vec::new() }
+    }
+}
+
+impl<K: StashKey, S> Stash<K, S> {
+    /// Creates an empty stash.
+    pub(crate) fn new() -> Self { Self::default() }
+
+    /// The value stashed for `time`, first stashing `init()` under the key `key()` if there is none.
+    pub(crate) fn get_or_insert_with(&mut self, time: &K::Time, key: impl FnOnce()->K, init: impl FnOnce()->S) -> &mut S {
+        let position = match self.entries.iter().position(|(k, _)| k.stash_time() == time) {
+            Some(position) => position,
+            None => {
+                self.entries.push((key(), init()));
+                self.entries.len() - 1
+            },
+        };
+        &mut self.entries[position].1
+    }
+
+    /// The stashed keys and values, in no particular order.
+    pub(crate) fn iter_mut(&mut self) -> impl Iterator<Item = (&K, &mut S)> {
+        self.entries.iter_mut().map(|(key, value)| (&*key, value))
+    }
+
+    /// Returns `true` if no values are stashed.
+    pub(crate) fn is_empty(&self) -> bool { self.entries.is_empty() }
+
+    /// Removes and returns the value stashed for `time`, if any.
+    pub(crate) fn remove(&mut self, time: &K::Time) -> Option<S> {
+        let position = self.entries.iter().position(|(k, _)| k.stash_time() == time)?;
+        Some(self.entries.remove(position).1)
+    }
+
+    /// Removes the entries whose times satisfy `ready`, and passes them to `logic` in order of time.
+    pub(crate) fn release(&mut self, mut ready: impl FnMut(&K::Time)->bool, mut logic: impl FnMut(K, S)) {
+        self.entries.sort_by(|x, y| x.0.stash_time().cmp(y.0.stash_time()));
+        for (key, value) in std::mem::take(&mut self.entries) {
+            if ready(key.stash_time()) {
+                logic(key, value);
+            }
+            else {
+                self.entries.push((key, value));
+            }
+        }
+    }
+}
+
+impl<T: Timestamp, S> Stash<Capability<T>, S> {
+    /// The value stashed for the time of `time`, first stashing `init()` under a capability retained from `time`.
+    #[cfg_attr(feature = "capability-provenance", track_caller)]
+    pub(crate) fn get_or_retain(&mut self, time: &InputCapability<T>, init: impl FnOnce()->S) -> &mut S {
+        if let Some(position) = self.entries.iter().position(|(capability, _)| capability.time() == time.time()) {
+            return &mut self.entries[position].1;
+        }
+        self.entries.push((time.delayed(time.time()), init()));
+        &mut self.entries.last_mut().unwrap().1
+    }
+}
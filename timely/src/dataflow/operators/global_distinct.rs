@@ -0,0 +1,223 @@
+//! Extension methods for `Stream` that deduplicate records across all workers.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use crate::ExchangeData;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Exchange;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::sketch::hash_of;
+
+/// Extension trait for `Stream`.
+pub trait GlobalDistinct<G: Scope, D: ExchangeData> {
+    /// Produces each distinct record of each time once, across all workers.
+    ///
+    /// Records are exchanged by their hash, so that all copies of a record at a time meet at the
+    /// same worker, which produces the first copy as it arrives and discards the others. The result
+    /// is the union of the records of all workers at each time, and holds each record at one worker
+    /// only. Each worker retains the records it has seen at each time until its input frontier has
+    /// passed the time, and then releases them.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, GlobalDistinct, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10u64).map(|x| x % 3)
+    ///               .to_stream(scope)
+    ///               .global_distinct()
+    ///               .inspect(|x| println!("distinct: {:?}", x));
+    /// });
+    /// ```
+    fn global_distinct(&self) -> Stream<G, D>;
+
+    /// Produces each record present at a time in both `self` and `other` once, across all workers.
+    ///
+    /// Both streams are exchanged by the hash of their records, so that all copies of a record at
+    /// a time meet at the same worker, which produces the record once it has arrived on both inputs.
+    /// The result is the intersection of the records of all workers at each time, and holds each
+    /// record at one worker only. Each worker retains the records it has seen on either input at
+    /// each time until the frontiers of both inputs have passed the time, and then releases them.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, GlobalDistinct, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     let evens = (0..10u64).filter(|x| x % 2 == 0).to_stream(scope);
+    ///     let threes = (0..10u64).filter(|x| x % 3 == 0).to_stream(scope);
+    ///     evens.global_intersection(&threes)
+    ///          .inspect(|x| assert_eq!(x % 6, 0));
+    /// });
+    /// ```
+    fn global_intersection(&self, other: &Stream<G, D>) -> Stream<G, D>;
+}
+
+impl<G: Scope<Timestamp: Hash>, D: ExchangeData+Hash+Eq> GlobalDistinct<G, D> for Stream<G, D> {
+    fn global_distinct(&self) -> Stream<G, D> {
+        let mut seen = HashMap::<G::Timestamp, HashSet<D>>::new();
+        self.unary_notify(Exchange::new(|x| hash_of(x)), "GlobalDistinct", vec![], move |input, output, notificator| {
+            input.for_each_time(|time, data| {
+                let seen = seen.entry(time.time().clone()).or_default();
+                let capability = time.retain();
+                {
+                    let mut session = output.session(&capability);
+                    for datum in data.flat_map(|d| d.drain(..)) {
+                        if !seen.contains(&datum) {
+                            seen.insert(datum.clone());
+                            session.give(datum);
+                        }
+                    }
+                }
+                notificator.notify_at(capability);
+            });
+            notificator.for_each(|time, _, _| {
+                seen.remove(time.time());
+            });
+        })
+    }
+
+    fn global_intersection(&self, other: &Stream<G, D>) -> Stream<G, D> {
+        // For each time, the records seen on each input.
+        let mut seen = HashMap::<G::Timestamp, (HashSet<D>, HashSet<D>)>::new();
+        self.binary_notify(other, Exchange::new(|x| hash_of(x)), Exchange::new(|x| hash_of(x)), "GlobalIntersection", vec![], move |input1, input2, output, notificator| {
+            // A record is produced when it first arrives on one input, having arrived on the other.
+            input1.for_each_time(|time, data| {
+                let (seen1, seen2) = seen.entry(time.time().clone()).or_default();
+                let capability = time.retain();
+                {
+                    let mut session = output.session(&capability);
+                    for datum in data.flat_map(|d| d.drain(..)) {
+                        if !seen1.contains(&datum) {
+                            if seen2.contains(&datum) {
+                                session.give(datum.clone());
+                            }
+                            seen1.insert(datum);
+                        }
+                    }
+                }
+                notificator.notify_at(capability);
+            });
+            input2.for_each_time(|time, data| {
+                let (seen1, seen2) = seen.entry(time.time().clone()).or_default();
+                let capability = time.retain();
+                {
+                    let mut session = output.session(&capability);
+                    for datum in data.flat_map(|d| d.drain(..)) {
+                        if !seen2.contains(&datum) {
+                            if seen1.contains(&datum) {
+                                session.give(datum.clone());
+                            }
+                            seen2.insert(datum);
+                        }
+                    }
+                }
+                notificator.notify_at(capability);
+            });
+            notificator.for_each(|time, _, _| {
+                seen.remove(time.time());
+            });
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::Config;
+    use crate::dataflow::operators::{Capture, Input, Probe};
+    use crate::dataflow::operators::capture::Extract;
+    use super::GlobalDistinct;
+
+    #[test]
+    fn records_are_distinct_per_time() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<u64>();
+                let distinct = stream.global_distinct();
+                (input, distinct.probe(), distinct.capture())
+            });
+            for record in [1, 1, 2] { input.send(record); }
+            // Repeats in later invocations at the same time are discarded too.
+            worker.step();
+            input.send(2);
+            input.advance_to(1);
+            // Records seen at an earlier time are produced again.
+            for record in [1, 3] { input.send(record); }
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        assert_eq!(captured.extract(), vec![(0, vec![1, 2]), (1, vec![1, 3])]);
+    }
+
+    #[test]
+    fn intersections_match_records_of_the_same_time() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut input1, mut input2, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input1, stream1) = scope.new_input::<u64>();
+                let (input2, stream2) = scope.new_input::<u64>();
+                let common = stream1.global_intersection(&stream2);
+                (input1, input2, common.probe(), common.capture())
+            });
+            // Duplicates on either input produce a record once.
+            for record in [1, 2, 2, 3] { input1.send(record); }
+            for record in [2, 3, 3, 4] { input2.send(record); }
+            // A record at time 1 on one input, and at time 2 on the other, does not match.
+            input1.advance_to(1);
+            input2.advance_to(1);
+            input1.send(5);
+            input1.advance_to(2);
+            input2.advance_to(2);
+            input2.send(5);
+            // A record matches once it arrives on the second of the two inputs.
+            input1.advance_to(3);
+            input2.advance_to(3);
+            input2.send(6);
+            worker.step_while(|| probe.less_than(&3));
+            worker.step();
+            input1.send(6);
+            input1.close();
+            input2.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        assert_eq!(captured.extract(), vec![(0, vec![2, 3]), (3, vec![6])]);
+    }
+
+    #[test]
+    fn records_meet_across_workers() {
+        let (distinct_send, recv_distinct) = std::sync::mpsc::channel();
+        let (common_send, recv_common) = std::sync::mpsc::channel();
+        let send = Arc::new(Mutex::new((distinct_send, common_send)));
+        crate::execute(Config::process(4), move |worker| {
+            let (send_distinct, send_common) = send.lock().unwrap().clone();
+            let index = worker.index() as u64;
+            let (mut input1, mut input2, probe) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input1, stream1) = scope.new_input::<u64>();
+                let (input2, stream2) = scope.new_input::<u64>();
+                let distinct = stream1.global_distinct();
+                let probe = distinct.probe();
+                distinct.capture_into(send_distinct);
+                stream1.global_intersection(&stream2).probe_with(&probe).capture_into(send_common);
+                (input1, input2, probe)
+            });
+            for round in 0..2 {
+                // Every worker sends each of `0..10` on the first input, twice, and its own index on the second input.
+                for x in 0..20 { input1.send(x % 10); }
+                input2.send(index + round);
+                input1.advance_to(round + 1);
+                input2.advance_to(round + 1);
+                worker.step_while(|| probe.less_than(input1.time()));
+            }
+        }).unwrap();
+
+        // Each record is produced once per time, rather than once per worker.
+        assert_eq!(recv_distinct.extract(), (0..2).map(|time| (time, (0..10).collect())).collect::<Vec<_>>());
+        assert_eq!(recv_common.extract(), (0..2).map(|time| (time, (time..time + 4).collect())).collect::<Vec<_>>());
+    }
+}
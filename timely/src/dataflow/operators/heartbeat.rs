@@ -0,0 +1,154 @@
+//! Extension methods for `Stream` that mark the completion of times with heartbeat records.
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::CapabilitySet;
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// Extension trait for `Stream`.
+pub trait Heartbeat<G: Scope, D: Data> {
+    /// Passes records through unchanged, and emits a heartbeat record at each time the input
+    /// frontier advances past.
+    ///
+    /// The operator holds a capability for each element of its input frontier. When the frontier
+    /// advances past one of these times, the operator emits `heartbeat(time)` at that time, whether
+    /// or not any records were seen at it, and then moves on to the new frontier. Downstream operators
+    /// therefore receive a record for each epoch boundary, even for epochs without data.
+    ///
+    /// The heartbeats are records produced by this operator, and are accounted for as such by progress
+    /// tracking. Times the frontier jumps over without ever reaching do not receive heartbeats.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{Input, Heartbeat, Inspect, Filter};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     let mut input = worker.dataflow(|scope| {
+    ///         let (input, stream) = scope.new_input::<Option<u64>>();
+    ///         stream.with_heartbeat(|_time| None)
+    ///               .filter(|x| x.is_none())
+    ///               .inspect_time(|time, _| println!("epoch {:?} complete", time));
+    ///         input
+    ///     });
+    ///     for round in 0..10 {
+    ///         if round % 2 == 0 { input.send(Some(round)); }
+    ///         input.advance_to(round + 1);
+    ///         worker.step();
+    ///     }
+    /// });
+    /// ```
+    fn with_heartbeat<L: FnMut(&G::Timestamp)->D+'static>(&self, heartbeat: L) -> Stream<G, D>;
+}
+
+impl<G: Scope, D: Data> Heartbeat<G, D> for Stream<G, D> {
+    fn with_heartbeat<L: FnMut(&G::Timestamp)->D+'static>(&self, mut heartbeat: L) -> Stream<G, D> {
+        self.unary_frontier(Pipeline, "Heartbeat", move |capability, _info| {
+            let mut held = CapabilitySet::from_elem(capability);
+            move |(input, frontier), output| {
+                input.for_each(|time, data| {
+                    output.session(&time).give_container(data);
+                });
+                if held.iter().any(|cap| !frontier.less_equal(cap.time())) {
+                    for cap in held.iter().filter(|cap| !frontier.less_equal(cap.time())) {
+                        output.session(cap).give(heartbeat(cap.time()));
+                    }
+                    held.downgrade(&frontier.frontier());
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::Config;
+    use crate::dataflow::Scope;
+    use crate::dataflow::operators::{Capture, Heartbeat, Input, Probe, UnorderedInput};
+    use crate::dataflow::operators::capture::Extract;
+    use crate::order::Product;
+
+    #[test]
+    fn times_without_records_receive_heartbeats() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<Result<u64, u64>>();
+                let beating = stream.with_heartbeat(|time| Err(*time));
+                (input, beating.probe(), beating.capture())
+            });
+            // Records at time 0 only, and times 1 and 2 without records.
+            input.send(Ok(5));
+            for round in 1..3 {
+                input.advance_to(round);
+                worker.step_while(|| probe.less_than(input.time()));
+            }
+            // Times 3 and 4 are jumped over, and receive no heartbeats.
+            input.advance_to(5);
+            worker.step_while(|| probe.less_than(input.time()));
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        assert_eq!(captured.extract(), vec![
+            (0, vec![Ok(5), Err(0)]),
+            (1, vec![Err(1)]),
+            (2, vec![Err(2)]),
+            (5, vec![Err(5)]),
+        ]);
+    }
+
+    #[test]
+    fn each_worker_emits_heartbeats() {
+        let (send, recv) = std::sync::mpsc::channel();
+        let send = Arc::new(Mutex::new(send));
+        crate::execute(Config::process(3), move |worker| {
+            let send = send.lock().unwrap().clone();
+            let index = worker.index() as u64;
+            let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<Result<u64, u64>>();
+                let beating = stream.with_heartbeat(|time| Err(*time));
+                let probe = beating.probe();
+                beating.capture_into(send);
+                (input, probe)
+            });
+            // Only the first worker has records, and only at time 0.
+            if index == 0 { input.send(Ok(index)); }
+            input.advance_to(1);
+            worker.step_while(|| probe.less_than(input.time()));
+        }).unwrap();
+
+        assert_eq!(recv.extract(), vec![
+            (0, vec![Ok(0), Err(0), Err(0), Err(0)]),
+            (1, vec![Err(1), Err(1), Err(1)]),
+        ]);
+    }
+
+    #[test]
+    fn times_completed_together_each_receive_heartbeats() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut capabilities, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                scope.iterative::<u64,_,_>(|inner| {
+                    let ((_input, capability), stream) = inner.new_unordered_input::<Product<u64, u64>>();
+                    let beating = stream.with_heartbeat(|time| *time);
+                    // The frontier moves from the initial time to two incomparable times.
+                    let capabilities = vec![capability.delayed(&Product::new(0, 1)), capability.delayed(&Product::new(1, 0))];
+                    (capabilities, beating.probe(), beating.capture())
+                })
+            });
+            worker.step_while(|| probe.less_than(&Product::new(0, 1)));
+            // Both times complete in the same advance of the frontier.
+            capabilities.clear();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        assert_eq!(captured.extract(), vec![
+            (Product::new(0, 0), vec![Product::new(0, 0)]),
+            (Product::new(0, 1), vec![Product::new(0, 1)]),
+            (Product::new(1, 0), vec![Product::new(1, 0)]),
+        ]);
+    }
+}
@@ -0,0 +1,154 @@
+//! Extension methods for `Stream` that estimate the most frequent records of each time.
+
+use std::hash::Hash;
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::operators::sketch::{summarize_by_key, MisraGries};
+
+/// Extension trait for `Stream`.
+pub trait HeavyHitters<G: Scope, D: Data> {
+    /// Estimates the `k` most frequent records of each time, with their estimated counts.
+    ///
+    /// Each worker counts its records of a time in a [`MisraGries`] summary of `max(k, 1 / epsilon)`
+    /// counters, whose memory is bounded regardless of the number of distinct records, and the
+    /// summaries are merged at the first worker. Once the input frontier has passed a time with
+    /// records, the first worker produces the at most `k` records with the largest estimates, in
+    /// decreasing order of estimate. Each estimate is at most the count of its record, and at least
+    /// the count less `epsilon` times the number of records at the time, and so every record more
+    /// frequent than that fraction is among the candidates, although the `k` reported may include
+    /// records less frequent than some omitted with nearly equal estimates.
+    ///
+    /// Records are never buffered or exchanged, only the summaries of each worker.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is zero, or if `epsilon` is not strictly between zero and one.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, HeavyHitters, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..1000u64).map(|x| if x % 3 == 0 { 7 } else { x })
+    ///                 .to_stream(scope)
+    ///                 .heavy_hitters(1, 0.01)
+    ///                 .inspect(|top| assert_eq!(top[0].0, 7));
+    /// });
+    /// ```
+    fn heavy_hitters(&self, k: usize, epsilon: f64) -> Stream<G, Vec<(D, u64)>>
+    where
+        D: ExchangeData+Hash+Eq+Ord,
+    {
+        self.heavy_hitters_by(|x| x.clone(), k, epsilon)
+    }
+
+    /// Estimates the `k` most frequent keys `key_fn` extracts from the records of each time, with their estimated counts.
+    ///
+    /// Behaves as [`HeavyHitters::heavy_hitters`], for the keys extracted by `key_fn`.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, HeavyHitters, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     vec![("a", 1), ("b", 2), ("a", 3)]
+    ///         .to_stream(scope)
+    ///         .heavy_hitters_by(|(user, _)| user.to_string(), 1, 0.1)
+    ///         .inspect(|top| assert_eq!(top, &vec![("a".to_string(), 2)]));
+    /// });
+    /// ```
+    fn heavy_hitters_by<K, F>(&self, key_fn: F, k: usize, epsilon: f64) -> Stream<G, Vec<(K, u64)>>
+    where
+        K: ExchangeData+Hash+Eq+Ord,
+        F: FnMut(&D)->K+'static;
+}
+
+impl<G: Scope<Timestamp: Hash>, D: Data> HeavyHitters<G, D> for Stream<G, D> {
+    fn heavy_hitters_by<K, F>(&self, mut key_fn: F, k: usize, epsilon: f64) -> Stream<G, Vec<(K, u64)>>
+    where
+        K: ExchangeData+Hash+Eq+Ord,
+        F: FnMut(&D)->K+'static,
+    {
+        assert!(k > 0, "HeavyHitters: k must be positive");
+        let capacity = MisraGries::<K>::with_error(epsilon).capacity().max(k);
+
+        summarize_by_key(self, "HeavyHitters", |_| 0,
+            move |summaries, datum| {
+                summaries.entry(()).or_insert_with(|| MisraGries::new(capacity)).insert(key_fn(&datum), 1);
+            },
+            |summary, other| summary.merge(other),
+            move |(), summary| summary.top(k),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::Config;
+    use crate::dataflow::operators::{Capture, Input, Probe, ToStream};
+    use crate::dataflow::operators::capture::Extract;
+    use super::HeavyHitters;
+
+    #[test]
+    fn frequent_records_are_reported_per_time() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<u64>();
+                let top = stream.heavy_hitters(2, 0.01);
+                (input, top.probe(), top.capture())
+            });
+            // Among 14,000 records seen once each, 1 and 2 are a fifth and a tenth of the records of time 0.
+            for x in 0..20_000u64 {
+                input.send(match x % 10 { 0 | 1 => 1, 2 => 2, _ => 100 + x });
+            }
+            input.advance_to(2);
+            input.send(5);
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        let results = captured.extract();
+        assert_eq!(results.len(), 2);
+        let (time, tops) = &results[0];
+        assert_eq!(*time, 0);
+        let keys = tops[0].iter().map(|(key, _)| *key).collect::<Vec<_>>();
+        assert_eq!(keys, vec![1, 2]);
+        // Estimates are at most the counts, and at least the counts less a hundredth of all records.
+        assert!(tops[0].iter().zip([4_000, 2_000]).all(|((_, estimate), count)| *estimate <= count && estimate + 200 >= count));
+        assert_eq!(results[1], (2, vec![vec![(5, 1)]]));
+    }
+
+    #[test]
+    fn summaries_of_workers_are_merged() {
+        let (send, recv) = std::sync::mpsc::channel();
+        let send = Arc::new(Mutex::new(send));
+        crate::execute(Config::process(3), move |worker| {
+            let send = send.lock().unwrap().clone();
+            let index = worker.index() as u64;
+            worker.dataflow::<u64,_,_>(move |scope| {
+                // Each worker sees 7 as often as a key of its own, and only 7 is as frequent across workers.
+                (0..3_000u64).map(move |x| if x % 3 == 0 { 7 } else if x % 3 == 1 { 10 + index } else { 1_000 * (index + 1) + x })
+                             .to_stream(scope)
+                             .heavy_hitters(1, 0.01)
+                             .capture_into(send);
+            });
+        }).unwrap();
+
+        let results = recv.extract();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].1.len(), 1);
+        assert_eq!(results[0].1[0][0].0, 7);
+    }
+
+    #[test]
+    #[should_panic(expected = "k must be positive")]
+    fn zero_k_panics() {
+        crate::example(|scope| {
+            (0..10u64).to_stream(scope).heavy_hitters(0, 0.1);
+        });
+    }
+}
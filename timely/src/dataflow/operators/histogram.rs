@@ -0,0 +1,156 @@
+//! Extension methods for `Stream` that count values into histogram buckets.
+
+use std::hash::Hash;
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::operators::sketch::summarize_by_key;
+
+/// Extension trait for `Stream`.
+pub trait Histogram<G: Scope, D: Data> {
+    /// Counts the records of each time into the buckets delimited by `buckets`.
+    ///
+    /// The strictly increasing boundaries `b[0], .., b[n-1]` delimit `n + 1` buckets: values below
+    /// `b[0]`, values in `b[i] .. b[i+1]` for each `i`, and values of at least `b[n-1]`. A value
+    /// equal to a boundary is counted in the bucket the boundary starts, and a NaN value is counted
+    /// in the first bucket. Once the input frontier has passed a time with records, the first
+    /// worker produces the `n + 1` counts of the time.
+    ///
+    /// Each worker counts its own records, and the counts are added up at the first worker, so
+    /// records are never buffered or exchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buckets` is not strictly increasing.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Histogram, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     vec![-1.0, 0.0, 0.5, 1.0, 2.0, 10.0]
+    ///         .to_stream(scope)
+    ///         .histogram(&[0.0, 1.0, 5.0])
+    ///         .inspect(|counts| assert_eq!(counts, &vec![1, 2, 2, 1]));
+    /// });
+    /// ```
+    fn histogram(&self, buckets: &[f64]) -> Stream<G, Vec<u64>>
+    where
+        D: Into<f64>+Clone,
+    {
+        self.histogram_by(buckets, |x| x.clone().into())
+    }
+
+    /// Counts the values `value_fn` extracts from the records of each time into the buckets delimited by `buckets`.
+    ///
+    /// Behaves as [`Histogram::histogram`], for the values extracted by `value_fn`.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Histogram, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..100u64).to_stream(scope)
+    ///                .histogram_by(&[10.0, 50.0], |x| *x as f64)
+    ///                .inspect(|counts| assert_eq!(counts, &vec![10, 40, 50]));
+    /// });
+    /// ```
+    fn histogram_by<F>(&self, buckets: &[f64], value_fn: F) -> Stream<G, Vec<u64>>
+    where
+        F: FnMut(&D)->f64+'static;
+}
+
+impl<G: Scope<Timestamp: Hash>, D: Data> Histogram<G, D> for Stream<G, D> {
+    fn histogram_by<F>(&self, buckets: &[f64], mut value_fn: F) -> Stream<G, Vec<u64>>
+    where
+        F: FnMut(&D)->f64+'static,
+    {
+        assert!(buckets.windows(2).all(|pair| pair[0] < pair[1]), "Histogram: buckets must be strictly increasing");
+        let buckets = buckets.to_vec();
+        let width = buckets.len() + 1;
+
+        summarize_by_key(self, "Histogram", |_| 0,
+            move |counts, datum| {
+                let value = value_fn(&datum);
+                counts.entry(()).or_insert_with(|| vec![0u64; width])[buckets.partition_point(|boundary| *boundary <= value)] += 1;
+            },
+            |totals, counts| {
+                for (total, count) in totals.iter_mut().zip(counts) {
+                    *total += count;
+                }
+            },
+            |(), totals| totals,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::Config;
+    use crate::dataflow::operators::{Capture, Input, Map, Probe, ToStream};
+    use crate::dataflow::operators::capture::Extract;
+    use super::Histogram;
+
+    #[test]
+    fn boundary_values_start_their_buckets() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<f64>();
+                let counts = stream.histogram(&[0.0, 1.0, 2.5]);
+                (input, counts.probe(), counts.capture())
+            });
+            // The boundaries, values around them, and the overflowing extremes.
+            for value in [f64::NEG_INFINITY, -0.5, 0.0, 0.5, 1.0, 2.4, 2.5, 3.0, f64::INFINITY] {
+                input.send(value);
+            }
+            input.advance_to(1);
+            // No records, and no counts, at time 1.
+            input.advance_to(2);
+            input.send(f64::NAN);
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        assert_eq!(captured.extract(), vec![(0, vec![vec![2, 2, 2, 3]]), (2, vec![vec![1, 0, 0, 0]])]);
+    }
+
+    #[test]
+    fn no_boundaries_count_all_records() {
+        let captured = crate::example(|scope| {
+            vec![-1.0f64, 0.0, 1.0].to_stream(scope).histogram(&[]).capture()
+        });
+
+        assert_eq!(captured.extract(), vec![(0, vec![vec![3]])]);
+    }
+
+    #[test]
+    fn counts_are_added_up_at_the_first_worker() {
+        let (send, recv) = std::sync::mpsc::channel();
+        let send = Arc::new(Mutex::new(send));
+        crate::execute(Config::process(3), move |worker| {
+            let send = send.lock().unwrap().clone();
+            let index = worker.index();
+            worker.dataflow::<u64,_,_>(move |scope| {
+                // Each worker counts values in a different bucket.
+                vec![index as f64; index + 1]
+                    .to_stream(scope)
+                    .histogram(&[1.0, 2.0])
+                    .map(move |counts| (index, counts))
+                    .capture_into(send);
+            });
+        }).unwrap();
+
+        assert_eq!(recv.extract(), vec![(0, vec![(0, vec![1, 2, 3])])]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Histogram: buckets must be strictly increasing")]
+    fn unsorted_buckets_are_rejected() {
+        crate::example(|scope| {
+            vec![0.0f64].to_stream(scope).histogram(&[1.0, 1.0]);
+        });
+    }
+}
@@ -0,0 +1,229 @@
+//! Extension methods for `Stream` that attach deterministic keys to records, for sinks to deduplicate writes.
+
+use std::hash::Hash;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::generic::stash::Stash;
+use crate::dataflow::operators::sketch::{hash_of, summarize_by_key};
+
+/// A key identifying a record by its time, the worker that keyed it, and its position at the worker and time.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct IdempotencyKey<T> {
+    /// The time of the record.
+    pub time: T,
+    /// The index of the worker that keyed the record.
+    pub worker: usize,
+    /// The number of records of the time the worker keyed before this one.
+    pub sequence: u64,
+}
+
+/// Extension trait for `Stream`.
+pub trait WithIdempotencyKey<G: Scope, D: Data> {
+    /// Pairs each record with an [`IdempotencyKey`] of its time, the worker, and its sequence number.
+    ///
+    /// Each worker numbers the records of each time from zero, in the order in which the operator
+    /// receives them. The keys of distinct records are distinct, and a sink that writes each record
+    /// with its key can recognize, and skip, the records it has already written when a pipeline with
+    /// at-least-once delivery replays its input.
+    ///
+    /// The keys are deterministic only as far as the order of the records is: a rerun produces the
+    /// same keys if each worker receives the same records of each time in the same order, which holds
+    /// for records from deterministic sources that reach the operator without an exchange, or through
+    /// exchanges from a single worker. Records exchanged from several workers arrive in an order that
+    /// may change between runs, and should be keyed before they are exchanged. Counters are kept only
+    /// for incomplete times, and are discarded once the input frontier passes a time.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, WithIdempotencyKey, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..3).to_stream(scope)
+    ///           .idempotency_key()
+    ///           .inspect(|(key, x)| assert_eq!(key.sequence, *x));
+    /// });
+    /// ```
+    fn idempotency_key(&self) -> Stream<G, (IdempotencyKey<G::Timestamp>, D)>;
+}
+
+impl<G: Scope, D: Data> WithIdempotencyKey<G, D> for Stream<G, D> {
+    fn idempotency_key(&self) -> Stream<G, (IdempotencyKey<G::Timestamp>, D)> {
+        let worker = self.scope().index();
+        self.unary_frontier(Pipeline, "IdempotencyKey", move |_capability, _info| {
+            // For each incomplete time, the number of its records keyed so far.
+            let mut sequences = Stash::<G::Timestamp, u64>::new();
+            move |(input, frontier), output| {
+                input.for_each_time(|time, data| {
+                    let sequence = sequences.get_or_insert_with(time.time(), || time.time().clone(), || 0);
+                    let mut session = output.session(&time);
+                    for datum in data.flat_map(|d| d.drain(..)) {
+                        session.give((IdempotencyKey { time: time.time().clone(), worker, sequence: *sequence }, datum));
+                        *sequence += 1;
+                    }
+                });
+                sequences.release(|time| !frontier.less_equal(time), |_time, _sequence| { });
+            }
+        })
+    }
+}
+
+/// Extension trait for `Stream`.
+pub trait CountExactlyOnce<G: Scope, D: Data> {
+    /// Counts the records of each time with distinct keys of `dedup_key_fn`, across all workers.
+    ///
+    /// Records with the same key at the same time are counted once, wherever and however often
+    /// they occur, and so input that is delivered at least once, with records duplicated by
+    /// retries or replays, is counted as if delivered exactly once. Keys such as those of
+    /// [`WithIdempotencyKey::idempotency_key`], assigned before the records are duplicated, serve
+    /// this purpose. Once the input frontier has passed a time, a single worker produces the count
+    /// of the time. Times without records produce no count.
+    ///
+    /// Keys are exchanged by their hash and deduplicated at the worker they reach, which retains
+    /// the distinct keys of each time until its input frontier has passed the time. Duplicates at
+    /// different times are distinct, and each is counted at its own time.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, CountExactlyOnce, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     // The delivery of the second event is retried.
+    ///     vec![(1, "open"), (2, "close"), (2, "close")]
+    ///         .to_stream(scope)
+    ///         .count_exactly_once(|(id, _)| *id)
+    ///         .inspect(|count| assert_eq!(*count, 2));
+    /// });
+    /// ```
+    fn count_exactly_once<K, F>(&self, dedup_key_fn: F) -> Stream<G, u64>
+    where
+        K: ExchangeData+Hash+Eq,
+        F: FnMut(&D)->K+'static;
+}
+
+impl<G: Scope<Timestamp: Hash>, D: Data> CountExactlyOnce<G, D> for Stream<G, D> {
+    fn count_exactly_once<K, F>(&self, mut dedup_key_fn: F) -> Stream<G, u64>
+    where
+        K: ExchangeData+Hash+Eq,
+        F: FnMut(&D)->K+'static,
+    {
+        let keys = summarize_by_key(self, "CountExactlyOnceKeys", hash_of,
+            move |keys, datum| { keys.insert(dedup_key_fn(&datum), ()); },
+            |_key, _other| { },
+            |_key, ()| (),
+        );
+
+        summarize_by_key(&keys, "CountExactlyOnceTotal", hash_of,
+            |counts, ()| *counts.entry(()).or_insert(0) += 1,
+            |count, other| *count += other,
+            |(), count| count,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::sync::mpsc::channel;
+
+    use crate::Config;
+    use crate::dataflow::operators::{Capture, CountExactlyOnce, Input, Probe, ToStream, WithIdempotencyKey};
+    use crate::dataflow::operators::capture::Extract;
+    use super::IdempotencyKey;
+
+    #[test]
+    fn records_are_numbered_per_time() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<char>();
+                let keyed = stream.idempotency_key();
+                (input, keyed.probe(), keyed.capture())
+            });
+            // The records of time 0 arrive over two invocations, and continue the same sequence.
+            input.send('a');
+            worker.step();
+            input.send('b');
+            input.advance_to(2);
+            input.send('c');
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        let key = |time, sequence| IdempotencyKey { time, worker: 0, sequence };
+        assert_eq!(captured.extract(), vec![
+            (0, vec![(key(0, 0), 'a'), (key(0, 1), 'b')]),
+            (2, vec![(key(2, 0), 'c')]),
+        ]);
+    }
+
+    #[test]
+    fn keys_are_distinct_across_workers_and_reproducible() {
+        let keys = || {
+            let (send, recv) = channel();
+            let send = Arc::new(Mutex::new(send));
+            crate::execute(Config::process(3), move |worker| {
+                let send = send.lock().unwrap().clone();
+                let index = worker.index() as u64;
+                worker.dataflow::<u64,_,_>(|scope| {
+                    let (mut input, stream) = scope.new_input::<u64>();
+                    stream.idempotency_key().capture_into(send);
+                    for x in 0..4 { input.send(10 * index + x); }
+                });
+            }).unwrap();
+            let mut keys = recv.extract().into_iter().flat_map(|(_time, data)| data).collect::<Vec<_>>();
+            keys.sort();
+            keys
+        };
+
+        let first = keys();
+        assert_eq!(first.len(), 12);
+        for (key, x) in first.iter() {
+            assert_eq!((key.time, key.sequence), (0, x % 10));
+            assert_eq!(key.worker as u64, x / 10);
+        }
+        assert_eq!(first, keys());
+    }
+
+    #[test]
+    fn duplicates_are_counted_once() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<(u64, char)>();
+                let counts = stream.count_exactly_once(|(id, _)| *id);
+                (input, counts.probe(), counts.capture())
+            });
+            // Records 1 and 2 are redelivered at time 0, and record 1 again at time 1.
+            for record in [(1, 'a'), (2, 'b'), (1, 'a'), (3, 'c'), (2, 'b')] { input.send(record); }
+            input.advance_to(1);
+            for record in [(1, 'a'), (4, 'd'), (4, 'd')] { input.send(record); }
+            input.advance_to(3);
+            input.send((5, 'e'));
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        assert_eq!(captured.extract(), vec![(0, vec![3]), (1, vec![2]), (3, vec![1])]);
+    }
+
+    #[test]
+    fn duplicates_across_workers_are_counted_once() {
+        let (send, recv) = channel();
+        let send = Arc::new(Mutex::new(send));
+        crate::execute(Config::process(3), move |worker| {
+            let send = send.lock().unwrap().clone();
+            let index = worker.index() as u64;
+            worker.dataflow::<u64,_,_>(|scope| {
+                // Each worker replays all of the keys below 10, and holds ten keys of its own.
+                (0..10).chain(10 * (index + 1) .. 10 * (index + 2)).to_stream(scope).count_exactly_once(|x| *x).capture_into(send);
+            });
+        }).unwrap();
+
+        assert_eq!(recv.extract(), vec![(0, vec![40])]);
+    }
+}
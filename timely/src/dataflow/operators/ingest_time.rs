@@ -0,0 +1,145 @@
+//! Extension methods for `Stream` that stamp records with their ingestion wall-clock time and measure their latency.
+//!
+//! Wall-clock times are represented as milliseconds since the Unix epoch, in a `u64`, so that they
+//! can be exchanged between workers and processes. Latencies measured across machines are only as
+//! accurate as the agreement of their clocks. Unlike a [`LatencyProbe`](super::LatencyProbe), which
+//! measures how long times take to complete, these measure the latency of individual records.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::Data;
+use crate::dataflow::{Scope, Stream};
+use crate::dataflow::operators::Map;
+
+/// The current wall-clock time, in milliseconds since the Unix epoch.
+fn epoch_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Extension trait for `Stream`.
+pub trait WithIngestTime<S: Scope, D: Data> {
+    /// Pairs each record with the wall-clock time at which it passes the operator, in milliseconds since the Unix epoch.
+    ///
+    /// Applied close to the sources of a dataflow, the time is that at which each record entered the
+    /// dataflow, and can be carried with the record through subsequent operators to be compared with
+    /// the time at which it leaves, for example by `measure_latency`.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, WithIngestTime, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .with_ingest_time()
+    ///            .inspect(|(x, millis)| println!("{:?} ingested at {:?}", x, millis));
+    /// });
+    /// ```
+    fn with_ingest_time(&self) -> Stream<S, (D, u64)>;
+}
+
+impl<S: Scope, D: Data> WithIngestTime<S, D> for Stream<S, D> {
+    fn with_ingest_time(&self) -> Stream<S, (D, u64)> {
+        stamp_with(self, epoch_millis)
+    }
+}
+
+/// Pairs each record with the time read from `clock` as it passes the operator.
+fn stamp_with<S: Scope, D: Data>(stream: &Stream<S, D>, mut clock: impl FnMut()->u64+'static) -> Stream<S, (D, u64)> {
+    stream.map(move |datum| (datum, clock()))
+}
+
+/// Extension trait for `Stream`.
+pub trait MeasureLatency<S: Scope, D: Data> {
+    /// Replaces the ingestion time of each record by the milliseconds elapsed since it.
+    ///
+    /// The ingestion times are those attached by `with_ingest_time`, and the latency is the wall-clock
+    /// time at which a record passes this operator less its ingestion time, or zero if the clock now
+    /// reads earlier. The latencies can be aggregated into histograms or summaries downstream.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, WithIngestTime, MeasureLatency, Exchange, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .with_ingest_time()
+    ///            .exchange(|(x, _)| *x)
+    ///            .measure_latency()
+    ///            .inspect(|(x, millis)| println!("{:?} took {:?}ms", x, millis));
+    /// });
+    /// ```
+    fn measure_latency(&self) -> Stream<S, (D, u64)>;
+}
+
+impl<S: Scope, D: Data> MeasureLatency<S, D> for Stream<S, (D, u64)> {
+    fn measure_latency(&self) -> Stream<S, (D, u64)> {
+        measure_with(self, epoch_millis)
+    }
+}
+
+/// Replaces the ingestion time of each record by the time read from `clock` less it, or zero.
+fn measure_with<S: Scope, D: Data>(stream: &Stream<S, (D, u64)>, mut clock: impl FnMut()->u64+'static) -> Stream<S, (D, u64)> {
+    stream.map(move |(datum, ingested)| (datum, clock().saturating_sub(ingested)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use crate::dataflow::operators::{Capture, Input, Probe, ToStream};
+    use crate::dataflow::operators::capture::Extract;
+    use super::{epoch_millis, measure_with, stamp_with, WithIngestTime};
+
+    #[test]
+    fn records_are_stamped_with_the_clock() {
+        let captured = crate::execute_directly(|worker| {
+            let clock = Rc::new(Cell::new(1_000));
+            let clock_inner = Rc::clone(&clock);
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<char>();
+                let stamped = stamp_with(&stream, move || clock_inner.get());
+                (input, stamped.probe(), stamped.capture())
+            });
+            input.send('a');
+            input.send('b');
+            input.advance_to(1);
+            worker.step_while(|| probe.less_than(input.time()));
+            // Records are stamped as they pass the operator, not as they are sent.
+            clock.set(1_500);
+            input.send('c');
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        assert_eq!(captured.extract(), vec![
+            (0, vec![('a', 1_000), ('b', 1_000)]),
+            (1, vec![('c', 1_500)]),
+        ]);
+    }
+
+    #[test]
+    fn latency_is_the_clock_less_the_ingestion_time() {
+        let captured = crate::example(|scope| {
+            // A record ingested in the past, one ingested now, and one stamped in the future.
+            let stamped = vec![('a', 950), ('b', 1_000), ('c', 61_000)].to_stream(scope);
+            measure_with(&stamped, || 1_000).capture()
+        });
+        assert_eq!(captured.extract(), vec![(0, vec![('a', 50), ('b', 0), ('c', 0)])]);
+    }
+
+    #[test]
+    fn records_are_stamped_with_the_wall_clock() {
+        let captured = crate::example(|scope| (0..10).to_stream(scope).with_ingest_time().capture());
+        // The wall clock may be adjusted at any moment, so the stamps are only bounded by a time long past.
+        let since = 1_600_000_000_000;
+        let stamps = captured.extract().into_iter().flat_map(|(_, data)| data).map(|(_, millis)| millis).collect::<Vec<_>>();
+        assert_eq!(stamps.len(), 10);
+        assert!(stamps.iter().all(|millis| *millis >= since));
+        assert!(epoch_millis() >= since);
+    }
+}
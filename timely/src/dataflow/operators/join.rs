@@ -0,0 +1,180 @@
+//! Extension methods for `Stream` that join records of two streams whose times are close.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::rc::Rc;
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Exchange;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::sketch::hash_of;
+use crate::order::{PartialOrder, TotalOrder};
+use crate::progress::{PathSummary, Timestamp};
+
+/// Extension trait for `Stream`.
+pub trait IntervalJoin<G: Scope, D1: ExchangeData> {
+    /// Joins records of the two streams with equal keys whose times are within an interval of each other.
+    ///
+    /// A record of `self` at time `t` matches each record of `other` with the same key at a time `s`
+    /// from `t` less `before` through `t` plus `after`. As timestamps are only advanced by summaries,
+    /// this is stated as `s` being at most `t` advanced by `after`, and `t` being at most `s`
+    /// advanced by `before`. For each match the operator produces `join_fn(key, left, right)` at the
+    /// later of the two times. Records of both streams are exchanged by key.
+    ///
+    /// Each record is retained only while records of the other stream can still match it: a record of
+    /// `self` at `t` until the frontier of `other` has passed `t` advanced by `after`, and a record of
+    /// `other` at `s` until the frontier of `self` has passed `s` advanced by `before`. Memory use is
+    /// thus bounded by the records within the interval of the frontiers, and grows beyond that only
+    /// if one input falls behind the other. Records whose times cannot be advanced by the interval,
+    /// because the result would overflow the timestamp, are retained until the other input completes.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    /// use timely::dataflow::operators::{Input, Inspect, IntervalJoin, Probe};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     let joined = Rc::new(RefCell::new(Vec::new()));
+    ///     let joined_inner = Rc::clone(&joined);
+    ///     let (mut clicks, mut views, probe) = worker.dataflow::<u64,_,_>(|scope| {
+    ///         let (clicks, click_stream) = scope.new_input::<(u64, u64)>();
+    ///         let (views, view_stream) = scope.new_input::<(u64, u64)>();
+    ///         let probe = click_stream
+    ///             .interval_join(&view_stream, |click| click.0, |view| view.0, 1, 2, |user, click, view| (*user, click.1, view.1))
+    ///             .inspect_time(move |time, x| joined_inner.borrow_mut().push((*time, *x)))
+    ///             .probe();
+    ///         (clicks, views, probe)
+    ///     });
+    ///
+    ///     for round in 0..10 {
+    ///         if round == 5 { clicks.send((7, round)); }
+    ///         if round % 2 == 0 || round == 3 { views.send((7, round)); }
+    ///         clicks.advance_to(round + 1);
+    ///         views.advance_to(round + 1);
+    ///         worker.step_while(|| probe.less_than(clicks.time()));
+    ///     }
+    ///
+    ///     // The click at 5 matches views from 4 through 7.
+    ///     assert_eq!(*joined.borrow(), vec![(5, (7, 5, 4)), (6, (7, 5, 6))]);
+    /// });
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    fn interval_join<K, D2, R, F1, F2, J>(
+        &self,
+        other: &Stream<G, D2>,
+        key_fn_l: F1,
+        key_fn_r: F2,
+        before: <G::Timestamp as Timestamp>::Summary,
+        after: <G::Timestamp as Timestamp>::Summary,
+        join_fn: J,
+    ) -> Stream<G, R>
+    where
+        G::Timestamp: TotalOrder,
+        K: ExchangeData+Hash+Eq,
+        D2: ExchangeData,
+        R: Data,
+        F1: Fn(&D1)->K+'static,
+        F2: Fn(&D2)->K+'static,
+        J: FnMut(&K, &D1, &D2)->R+'static;
+}
+
+impl<G: Scope, D1: ExchangeData> IntervalJoin<G, D1> for Stream<G, D1> {
+    fn interval_join<K, D2, R, F1, F2, J>(
+        &self,
+        other: &Stream<G, D2>,
+        key_fn_l: F1,
+        key_fn_r: F2,
+        before: <G::Timestamp as Timestamp>::Summary,
+        after: <G::Timestamp as Timestamp>::Summary,
+        mut join_fn: J,
+    ) -> Stream<G, R>
+    where
+        G::Timestamp: TotalOrder,
+        K: ExchangeData+Hash+Eq,
+        D2: ExchangeData,
+        R: Data,
+        F1: Fn(&D1)->K+'static,
+        F2: Fn(&D2)->K+'static,
+        J: FnMut(&K, &D1, &D2)->R+'static,
+    {
+        let key_fn_l = Rc::new(key_fn_l);
+        let route_l = Rc::clone(&key_fn_l);
+        let key_fn_r = Rc::new(key_fn_r);
+        let route_r = Rc::clone(&key_fn_r);
+        let pact_l = Exchange::new(move |datum: &D1| hash_of(&route_l(datum)));
+        let pact_r = Exchange::new(move |datum: &D2| hash_of(&route_r(datum)));
+
+        // Whether records at `left` and `right` times are within the interval; unbounded if advancing overflows.
+        let (within_before, within_after) = (before.clone(), after.clone());
+        let within = move |left: &G::Timestamp, right: &G::Timestamp| {
+            within_after.results_in(left).is_none_or(|reach| right.less_equal(&reach)) &&
+            within_before.results_in(right).is_none_or(|reach| left.less_equal(&reach))
+        };
+        let later = |x: &G::Timestamp, y: &G::Timestamp| if x.less_equal(y) { y.clone() } else { x.clone() };
+
+        let mut lefts = HashMap::<K, Vec<(G::Timestamp, D1)>>::new();
+        let mut rights = HashMap::<K, Vec<(G::Timestamp, D2)>>::new();
+        let mut matches = Vec::<(G::Timestamp, R)>::new();
+
+        self.binary_frontier(other, pact_l, pact_r, "IntervalJoin", move |_capability, _info| {
+            move |(input1, frontier1), (input2, frontier2), output| {
+                input1.for_each_time(|capability, data| {
+                    let time = capability.time().clone();
+                    for datum in data.flat_map(|d| d.drain(..)) {
+                        let key = key_fn_l(&datum);
+                        for (other_time, matching) in rights.get(&key).into_iter().flatten() {
+                            if within(&time, other_time) {
+                                matches.push((later(&time, other_time), join_fn(&key, &datum, matching)));
+                            }
+                        }
+                        lefts.entry(key).or_default().push((time.clone(), datum));
+                    }
+                    matches.sort_by(|x, y| x.0.cmp(&y.0));
+                    let mut matched = matches.drain(..).peekable();
+                    while let Some((match_time, result)) = matched.next() {
+                        let delayed = capability.delayed(&match_time);
+                        let mut session = output.session(&delayed);
+                        session.give(result);
+                        while let Some((_, same_time)) = matched.next_if(|(next, _)| *next == match_time) {
+                            session.give(same_time);
+                        }
+                    }
+                });
+                input2.for_each_time(|capability, data| {
+                    let time = capability.time().clone();
+                    for datum in data.flat_map(|d| d.drain(..)) {
+                        let key = key_fn_r(&datum);
+                        for (other_time, matching) in lefts.get(&key).into_iter().flatten() {
+                            if within(other_time, &time) {
+                                matches.push((later(&time, other_time), join_fn(&key, matching, &datum)));
+                            }
+                        }
+                        rights.entry(key).or_default().push((time.clone(), datum));
+                    }
+                    matches.sort_by(|x, y| x.0.cmp(&y.0));
+                    let mut matched = matches.drain(..).peekable();
+                    while let Some((match_time, result)) = matched.next() {
+                        let delayed = capability.delayed(&match_time);
+                        let mut session = output.session(&delayed);
+                        session.give(result);
+                        while let Some((_, same_time)) = matched.next_if(|(next, _)| *next == match_time) {
+                            session.give(same_time);
+                        }
+                    }
+                });
+
+                // Release records that no future record of the other input can match.
+                lefts.retain(|_, records| {
+                    records.retain(|(time, _)| after.results_in(time).is_none_or(|reach| frontier2.less_equal(&reach)));
+                    !records.is_empty()
+                });
+                rights.retain(|_, records| {
+                    records.retain(|(time, _)| before.results_in(time).is_none_or(|reach| frontier1.less_equal(&reach)));
+                    !records.is_empty()
+                });
+            }
+        })
+    }
+}
@@ -0,0 +1,152 @@
+//! Extension methods for `Stream` that periodically schedule downstream operators.
+
+use std::time::{Duration, Instant};
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::CapabilitySet;
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// Extension trait for `Stream`.
+pub trait Keepalive<G: Scope, D: Data> {
+    /// Passes records through unchanged, and sends a message without records every `interval`.
+    ///
+    /// The operator holds a capability for each element of its input frontier, and downgrades them
+    /// as the frontier advances, so it never holds the frontier back. Using a timer activation, it
+    /// is scheduled every `interval` and sends an empty message at each of the times it holds,
+    /// which schedules the downstream operators even while no records arrive, for example so that
+    /// they can act on their own timers. The messages convey no records and do not affect progress,
+    /// and as exchanges do not forward them, they only reach operators connected by a pipeline.
+    ///
+    /// Once the input frontier is empty, the operator releases its capabilities and stops.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use timely::dataflow::operators::{Input, Keepalive, Inspect};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     let mut input = worker.dataflow::<u64,_,_>(|scope| {
+    ///         let (input, stream) = scope.new_input::<u64>();
+    ///         stream.keepalive(Duration::from_millis(10))
+    ///               .inspect_batch(|time, data| println!("scheduled at {:?} with {:?}", time, data));
+    ///         input
+    ///     });
+    ///     input.send(0);
+    ///     for _ in 0 .. 5 {
+    ///         worker.step_or_park(Some(Duration::from_millis(10)));
+    ///     }
+    /// });
+    /// ```
+    fn keepalive(&self, interval: Duration) -> Stream<G, D>;
+}
+
+impl<G: Scope, D: Data> Keepalive<G, D> for Stream<G, D> {
+    fn keepalive(&self, interval: Duration) -> Stream<G, D> {
+        let scope = self.scope();
+        self.unary_frontier(Pipeline, "Keepalive", move |capability, info| {
+            let activator = scope.activator_for(info.address);
+            let mut held = CapabilitySet::from_elem(capability);
+            let mut due = Instant::now() + interval;
+            activator.activate_after(interval);
+            move |(input, frontier), output| {
+                input.for_each(|time, data| {
+                    output.session(&time).give_container(data);
+                });
+                held.downgrade(&frontier.frontier());
+
+                // Other schedulings, prompted by records or progress, neither send nor re-arm the timer.
+                let now = Instant::now();
+                if !held.is_empty() && now >= due {
+                    for held_capability in held.iter() {
+                        output.session(held_capability).give_empty();
+                    }
+                    due = now + interval;
+                    activator.activate_after(interval);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    use crate::dataflow::channels::pact::Pipeline;
+    use crate::dataflow::operators::{Input, Operator, Probe};
+    use super::Keepalive;
+
+    /// The longest the tests wait for the timer, in steps of at most one interval each.
+    const MAX_STEPS: usize = 10_000;
+
+    #[test]
+    fn idle_downstream_is_scheduled() {
+        crate::execute_directly(|worker| {
+            let interval = Duration::from_millis(1);
+            // The times of each scheduling of the downstream operator, and the records it received.
+            let schedules = Rc::new(RefCell::new(Vec::new()));
+            let schedules_inner = Rc::clone(&schedules);
+            let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+                let (handle, stream) = scope.new_input::<u64>();
+                let probe = stream.keepalive(interval)
+                                  .unary(Pipeline, "Downstream", move |_capability, _info| {
+                                      move |input, output| {
+                                          input.for_each_time(|time, data| {
+                                              let mut session = output.session(&time);
+                                              let mut received = 0;
+                                              for container in data {
+                                                  received += container.len();
+                                                  session.give_container(container);
+                                              }
+                                              schedules_inner.borrow_mut().push((*time.time(), received));
+                                          });
+                                      }
+                                  })
+                                  .probe();
+                (handle, probe)
+            });
+
+            // Steps the worker, parking for at most an interval, until the downstream operator has been scheduled `count` times.
+            let mut step_until = |count: usize| {
+                let mut steps = 0;
+                while schedules.borrow().len() < count {
+                    assert!(steps < MAX_STEPS, "scheduled {} of {} times", schedules.borrow().len(), count);
+                    worker.step_or_park(Some(interval));
+                    steps += 1;
+                }
+            };
+
+            input.send(7);
+            input.advance_to(1);
+            step_until(1);
+            assert_eq!(schedules.borrow()[0], (0, 1));
+
+            // Without records or progress, the downstream operator is still scheduled, at the held time.
+            step_until(4);
+            assert!(schedules.borrow()[1..].iter().all(|schedule| schedule.1 == 0 && schedule.0 <= 1));
+            assert_eq!(schedules.borrow().last(), Some(&(1, 0)));
+            // The messages convey no records and hold no capabilities, so the frontier is that of the input.
+            assert!(probe.less_equal(&1) && !probe.less_than(&1));
+
+            // The operator does not hold the frontier back, and sends at the new frontier once it advances.
+            input.advance_to(5);
+            while schedules.borrow().last().map(|schedule| schedule.0) != Some(5) {
+                let seen = schedules.borrow().len();
+                step_until(seen + 1);
+            }
+            assert!(!probe.less_than(&5));
+
+            // The operator stops once its input completes, and the dataflow shuts down.
+            drop(input);
+            let mut steps = 0;
+            while worker.step_or_park(Some(interval)) {
+                assert!(steps < MAX_STEPS, "the dataflow did not shut down");
+                steps += 1;
+            }
+        });
+    }
+}
@@ -0,0 +1,155 @@
+//! Operators that separate records whose event times the input frontier has passed.
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use crate::Data;
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::OutputBuilder;
+use crate::dataflow::operators::generic::builder_rc::OperatorBuilder;
+use crate::dataflow::{Scope, Stream};
+
+/// Extension trait for `Stream`.
+pub trait DropLate<S: Scope, D: Data> {
+    /// Splits records into those on time and those late, by comparing their event times against the input frontier.
+    ///
+    /// For each record, `event_time` extracts the time at which the record happened. The record is late
+    /// if the input frontier, when the record is received, is no longer less or equal to its event time,
+    /// which is to say that its event time is already complete. Late records are sent to the second
+    /// returned stream, and all others to the first; both at the time at which the record was received.
+    ///
+    /// The operator holds no capabilities, and so never holds back the frontier of its outputs.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{Input, DropLate, Inspect, Probe};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+    ///         // Records are pairs of an event time and a value.
+    ///         let (input, stream) = scope.new_input::<(u64, &str)>();
+    ///         let (on_time, late) = stream.split_late(|(event_time, _)| *event_time);
+    ///         late.inspect(|(_, value)| assert_eq!(*value, "late"));
+    ///         (input, on_time.probe())
+    ///     });
+    ///     input.advance_to(5);
+    ///     worker.step_while(|| probe.less_than(input.time()));
+    ///     input.send((5, "on time"));
+    ///     input.send((2, "late"));
+    /// });
+    /// ```
+    fn split_late<F>(&self, event_time: F) -> (Stream<S, D>, Stream<S, D>)
+    where
+        F: Fn(&D) -> S::Timestamp + 'static;
+
+    /// Drops late records, and counts how many were dropped.
+    ///
+    /// Records are late as for [`DropLate::split_late`]. On-time records are produced as output, and
+    /// the number of dropped records is available from the returned [`LateCount`].
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{Input, DropLate, Probe};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     let (mut input, probe, dropped) = worker.dataflow::<u64,_,_>(|scope| {
+    ///         let (input, stream) = scope.new_input::<u64>();
+    ///         let (on_time, dropped) = stream.drop_late(|event_time| *event_time);
+    ///         (input, on_time.probe(), dropped)
+    ///     });
+    ///     input.advance_to(5);
+    ///     worker.step_while(|| probe.less_than(input.time()));
+    ///     input.send(7);
+    ///     input.send(3);
+    ///     input.advance_to(6);
+    ///     worker.step_while(|| probe.less_than(input.time()));
+    ///     assert_eq!(dropped.get(), 1);
+    /// });
+    /// ```
+    fn drop_late<F>(&self, event_time: F) -> (Stream<S, D>, LateCount)
+    where
+        F: Fn(&D) -> S::Timestamp + 'static;
+}
+
+impl<S: Scope, D: Data> DropLate<S, D> for Stream<S, D> {
+    fn split_late<F>(&self, event_time: F) -> (Stream<S, D>, Stream<S, D>)
+    where
+        F: Fn(&D) -> S::Timestamp + 'static,
+    {
+        let mut builder = OperatorBuilder::new("SplitLate".to_owned(), self.scope());
+        builder.set_notify(false);
+
+        let mut input = builder.new_input(self, Pipeline);
+        let (output1, stream1) = builder.new_output();
+        let (output2, stream2) = builder.new_output();
+
+        let mut output1 = OutputBuilder::from(output1);
+        let mut output2 = OutputBuilder::from(output2);
+
+        builder.build(move |_| {
+            move |frontiers| {
+                let mut output1_handle = output1.activate();
+                let mut output2_handle = output2.activate();
+
+                input.activate().for_each_time(|time, data| {
+                    let mut out1 = output1_handle.session(&time);
+                    let mut out2 = output2_handle.session(&time);
+                    for datum in data.flat_map(|d| d.drain(..)) {
+                        if frontiers[0].less_equal(&event_time(&datum)) {
+                            out1.give(datum);
+                        } else {
+                            out2.give(datum);
+                        }
+                    }
+                });
+            }
+        });
+
+        (stream1, stream2)
+    }
+
+    fn drop_late<F>(&self, event_time: F) -> (Stream<S, D>, LateCount)
+    where
+        F: Fn(&D) -> S::Timestamp + 'static,
+    {
+        let mut builder = OperatorBuilder::new("DropLate".to_owned(), self.scope());
+        builder.set_notify(false);
+
+        let mut input = builder.new_input(self, Pipeline);
+        let (output, stream) = builder.new_output();
+        let mut output = OutputBuilder::from(output);
+
+        let count = LateCount { dropped: Rc::new(Cell::new(0)) };
+        let dropped = Rc::clone(&count.dropped);
+        builder.build(move |_| {
+            move |frontiers| {
+                let mut output_handle = output.activate();
+                input.activate().for_each_time(|time, data| {
+                    let mut session = output_handle.session(&time);
+                    for datum in data.flat_map(|d| d.drain(..)) {
+                        if frontiers[0].less_equal(&event_time(&datum)) {
+                            session.give(datum);
+                        } else {
+                            dropped.set(dropped.get() + 1);
+                        }
+                    }
+                });
+            }
+        });
+
+        (stream, count)
+    }
+}
+
+/// The number of records dropped by [`DropLate::drop_late`].
+#[derive(Clone, Debug)]
+pub struct LateCount {
+    dropped: Rc<Cell<u64>>,
+}
+
+impl LateCount {
+    /// The number of late records dropped so far.
+    pub fn get(&self) -> u64 {
+        self.dropped.get()
+    }
+}
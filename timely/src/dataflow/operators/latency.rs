@@ -0,0 +1,137 @@
+//! Measure the wall-clock latency with which times complete at a point in a dataflow.
+
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+use crate::Container;
+use crate::dataflow::{Scope, StreamCore};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::Operator;
+
+/// Monitors the latency with which times complete at a `Stream`.
+pub trait LatencyProbe<G: Scope, C: Container> {
+    /// Records, for each time observed at the probe, the wall-clock time between its opening and its closing.
+    ///
+    /// A time opens when the probe first observes it, either as the time of received data or as an
+    /// element of the input frontier, and closes once the input frontier is no longer less or equal
+    /// to it. Each closed time and its latency is recorded in the returned [`LatencyHandle`], as is a
+    /// histogram of all latencies. Times that the frontier passes over without ever containing them,
+    /// and at which no data arrives, are not observed and not recorded.
+    ///
+    /// Latencies are measured when the probe is scheduled, and so include any delay in scheduling
+    /// it. The probe consumes its input, and has no output.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{Input, LatencyProbe, Probe};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     let (mut input, probe, latency) = worker.dataflow::<u64,_,_>(|scope| {
+    ///         let (input, stream) = scope.new_input::<u64>();
+    ///         (input, stream.probe(), stream.latency_probe())
+    ///     });
+    ///     for round in 0..10 {
+    ///         input.send(round);
+    ///         input.advance_to(round + 1);
+    ///         worker.step_while(|| probe.less_than(input.time()));
+    ///     }
+    ///
+    ///     let latencies = latency.take_latencies();
+    ///     assert_eq!(latencies.iter().map(|(time, _)| *time).collect::<Vec<_>>(), (0..10).collect::<Vec<_>>());
+    ///     assert_eq!(latency.histogram().iter().map(|(_, count)| count).sum::<u64>(), 10);
+    /// });
+    /// ```
+    fn latency_probe(&self) -> LatencyHandle<G::Timestamp>;
+}
+
+impl<G: Scope, C: Container> LatencyProbe<G, C> for StreamCore<G, C> {
+    fn latency_probe(&self) -> LatencyHandle<G::Timestamp> {
+        let handle = LatencyHandle::new();
+        let shared = Rc::downgrade(&handle.state);
+        // Open times, and the moment each was first observed.
+        let mut open: Vec<(G::Timestamp, Instant)> = Vec::new();
+        self.sink(Pipeline, "LatencyProbe", move |(input, frontier)| {
+            let now = Instant::now();
+            input.for_each(|time, _data| {
+                if !open.iter().any(|(t, _)| t == time.time()) {
+                    open.push((time.time().clone(), now));
+                }
+            });
+            for time in frontier.frontier().iter() {
+                if !open.iter().any(|(t, _)| t == time) {
+                    open.push((time.clone(), now));
+                }
+            }
+            if let Some(state) = shared.upgrade() {
+                let mut state = state.borrow_mut();
+                open.retain(|(time, opened)| {
+                    let closed = !frontier.less_equal(time);
+                    if closed {
+                        state.record(time.clone(), now.duration_since(*opened));
+                    }
+                    !closed
+                });
+            }
+        });
+        handle
+    }
+}
+
+/// Reports the latencies observed by a [`LatencyProbe`].
+#[derive(Debug)]
+pub struct LatencyHandle<T> {
+    state: Rc<RefCell<LatencyState<T>>>,
+}
+
+impl<T> LatencyHandle<T> {
+    fn new() -> Self {
+        LatencyHandle { state: Rc::new(RefCell::new(LatencyState { latencies: Vec::new(), histogram: Vec::new() })) }
+    }
+
+    /// Removes and returns the closed times and their latencies, in the order they closed.
+    pub fn take_latencies(&self) -> Vec<(T, Duration)> {
+        std::mem::take(&mut self.state.borrow_mut().latencies)
+    }
+
+    /// A histogram of the latencies of all closed times, including those already taken.
+    ///
+    /// Each entry `(bound, count)` counts the latencies less than `bound` and at least half of it,
+    /// or at least zero for the smallest bound of one microsecond. Only non-empty buckets are
+    /// reported, in increasing order of their bounds.
+    pub fn histogram(&self) -> Vec<(Duration, u64)> {
+        self.state
+            .borrow()
+            .histogram
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(bucket, count)| (Duration::from_micros(1 << bucket), *count))
+            .collect()
+    }
+}
+
+impl<T> Clone for LatencyHandle<T> {
+    fn clone(&self) -> Self {
+        LatencyHandle { state: Rc::clone(&self.state) }
+    }
+}
+
+#[derive(Debug)]
+struct LatencyState<T> {
+    latencies: Vec<(T, Duration)>,
+    /// Counts of latencies, where bucket `i` counts those less than `2^i` microseconds.
+    histogram: Vec<u64>,
+}
+
+impl<T> LatencyState<T> {
+    fn record(&mut self, time: T, latency: Duration) {
+        let micros = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+        let bucket = (u64::BITS - micros.leading_zeros()).min(63) as usize;
+        if self.histogram.len() <= bucket {
+            self.histogram.resize(bucket + 1, 0);
+        }
+        self.histogram[bucket] += 1;
+        self.latencies.push((time, latency));
+    }
+}
@@ -1,10 +1,12 @@
 //! Extension methods for `Stream` based on record-by-record transformation.
 
+use std::fmt::Debug;
+
 use crate::Data;
 use crate::dataflow::{Stream, Scope};
 use crate::dataflow::channels::pact::Pipeline;
 use crate::dataflow::operators::generic::operator::Operator;
-use crate::dataflow::operators::core::{Map as MapCore};
+use crate::dataflow::operators::core::{Map as MapCore, OkErr};
 
 /// Extension trait for `Stream`.
 pub trait Map<S: Scope, D: Data> {
@@ -49,6 +51,79 @@ pub trait Map<S: Scope, D: Data> {
     /// });
     /// ```
     fn flat_map<I: IntoIterator, L: FnMut(D)->I+'static>(&self, logic: L) -> Stream<S, I::Item> where I::Item: Data;
+    /// Consumes each element of the stream and yields exactly `k` new elements, panicking otherwise.
+    ///
+    /// Behaves as `flat_map`, and checks that `logic` yields exactly `k` elements for each input,
+    /// which guards fan-out logic such as cross products against accidentally producing more, or
+    /// fewer, records than intended. The check panics with the debug representation of the input
+    /// element and the number of elements yielded, counting at most `k + 1` of them so that even
+    /// unbounded iterators are detected. Each input element is cloned to report it, and each output
+    /// staged, which makes this method better suited to development than to production.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Map, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .flat_map_exact(3, |x| (0..3).map(move |y| (x, y)))
+    ///            .inspect(|x| println!("seen: {:?}", x));
+    /// });
+    /// ```
+    fn flat_map_exact<I, L>(&self, k: usize, mut logic: L) -> Stream<S, I::Item>
+    where
+        D: Debug,
+        I: IntoIterator,
+        I::Item: Data,
+        L: FnMut(D)->I+'static,
+    {
+        self.flat_map(move |x| {
+            let input = x.clone();
+            let outputs = logic(x).into_iter().take(k + 1).collect::<Vec<_>>();
+            if outputs.len() > k {
+                panic!("flat_map_exact: expected {} outputs for input {:?}, found more", k, input);
+            }
+            if outputs.len() < k {
+                panic!("flat_map_exact: expected {} outputs for input {:?}, found {}", k, input, outputs.len());
+            }
+            outputs
+        })
+    }
+    /// Consumes each element of the stream, and yields the `Ok` results to the first stream and the `Err` results to the second.
+    ///
+    /// Each element is transformed once, and its result produced at the element's own time in one
+    /// of the two streams, so that downstream operators can relate errors to the records of the same
+    /// time in the other stream. This is [`OkErr::ok_err`] for streams of vectors.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Map, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     let (numbers, errors) = vec!["1", "two", "3"].to_stream(scope)
+    ///                                                  .map_result(|x| x.parse::<u64>());
+    ///     numbers.inspect(|x| println!("parsed: {:?}", x));
+    ///     errors.inspect(|e| println!("failed: {}", e));
+    /// });
+    /// ```
+    fn map_result<D2: Data, E: Data, L: FnMut(D)->Result<D2, E>+'static>(&self, logic: L) -> (Stream<S, D2>, Stream<S, E>);
+    /// Consumes each element of the stream, a collection, and yields its elements at the element's time.
+    ///
+    /// This is `flat_map(|x| x)`, except that the elements are sent as each output container fills,
+    /// rather than after all elements of the input containers are staged, so that large collections
+    /// are not buffered in full.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Map, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     vec![vec![1, 2], vec![], vec![3]].to_stream(scope)
+    ///                                      .flatten()
+    ///                                      .inspect(|x| println!("seen: {:?}", x));
+    /// });
+    /// ```
+    fn flatten(&self) -> Stream<S, D::Item> where D: IntoIterator, D::Item: Data;
 }
 
 impl<S: Scope, D: Data> Map<S, D> for Stream<S, D> {
@@ -69,4 +144,104 @@ impl<S: Scope, D: Data> Map<S, D> for Stream<S, D> {
     fn flat_map<I: IntoIterator, L: FnMut(D)->I+'static>(&self, logic: L) -> Stream<S, I::Item> where I::Item: Data {
         MapCore::flat_map(self, logic)
     }
+    fn map_result<D2: Data, E: Data, L: FnMut(D)->Result<D2, E>+'static>(&self, logic: L) -> (Stream<S, D2>, Stream<S, E>) {
+        self.ok_err(logic)
+    }
+    fn flatten(&self) -> Stream<S, D::Item> where D: IntoIterator, D::Item: Data {
+        self.unary(Pipeline, "Flatten", move |_,_| move |input, output| {
+            input.for_each_time(|time, data| {
+                let mut session = output.session(&time);
+                // Each element is given on its own, which sends each output container once full.
+                for datum in data.flat_map(|d| d.drain(..)).flatten() {
+                    session.give(datum);
+                }
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use crate::container::buffer::default_capacity;
+    use crate::dataflow::operators::{Capture, Input, InspectCore, Probe, ToStream};
+    use crate::dataflow::operators::capture::Extract;
+    use super::Map;
+
+    #[test]
+    fn flatten_yields_elements_at_their_times() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<Vec<char>>();
+                let flattened = stream.flatten();
+                (input, flattened.probe(), flattened.capture())
+            });
+            input.send(vec!['a', 'b']);
+            input.send(vec![]);
+            input.advance_to(1);
+            input.send(vec!['c']);
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        assert_eq!(captured.extract(), vec![(0, vec!['a', 'b']), (1, vec!['c'])]);
+    }
+
+    #[test]
+    fn flatten_sends_large_collections_in_bounded_containers() {
+        let capacity = default_capacity::<u64>();
+        let sizes = crate::execute_directly(move |worker| {
+            let sizes = Rc::new(RefCell::new(Vec::new()));
+            let sizes_inner = Rc::clone(&sizes);
+            let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<Vec<u64>>();
+                let probe = stream.flatten()
+                                  .inspect_container(move |event| if let Ok((_, data)) = event { sizes_inner.borrow_mut().push(data.len()) })
+                                  .probe();
+                (input, probe)
+            });
+            input.send((0..10 * capacity as u64).collect::<Vec<_>>());
+            input.close();
+            worker.step_while(|| !probe.done());
+            sizes.take()
+        });
+
+        assert_eq!(sizes.iter().sum::<usize>(), 10 * capacity);
+        assert!(sizes.iter().all(|size| *size <= capacity));
+    }
+
+    #[test]
+    fn map_result_routes_results_at_their_times() {
+        let (oks, errs) = crate::execute_directly(|worker| {
+            let (mut input, probe, oks, errs) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<i64>();
+                let (oks, errs) = stream.map_result(|x| u64::try_from(x).map_err(|_| -x));
+                (input, errs.probe(), oks.capture(), errs.capture())
+            });
+            input.send(1);
+            input.send(-2);
+            input.advance_to(1);
+            input.send(-3);
+            input.advance_to(2);
+            input.send(4);
+            input.close();
+            worker.step_while(|| !probe.done());
+            (oks, errs)
+        });
+
+        assert_eq!(oks.extract(), vec![(0, vec![1]), (2, vec![4])]);
+        assert_eq!(errs.extract(), vec![(0, vec![2]), (1, vec![3])]);
+    }
+
+    #[test]
+    #[should_panic(expected = "flat_map_exact: expected 2 outputs for input 3, found more")]
+    fn flat_map_exact_reports_the_input() {
+        crate::example(|scope| {
+            (0..10u64).to_stream(scope)
+                      .flat_map_exact(2, |x| 0..(if x == 3 { 3 } else { 2 }));
+        });
+    }
 }
@@ -0,0 +1,185 @@
+//! Extension methods for `Stream` that maintain the latest record per key in a snapshot read from outside the dataflow.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, RwLock};
+
+use crate::Data;
+use crate::dataflow::{Scope, Stream};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::generic::stash::Stash;
+use crate::progress::{Antichain, Timestamp};
+use crate::progress::frontier::AntichainRef;
+
+/// Extension trait for `Stream`.
+pub trait Materialize<G: Scope, D: Data> {
+    /// Maintains the latest record of `self` for each key produced by `key_fn`, in a snapshot that
+    /// can be read from outside the dataflow.
+    ///
+    /// The operator retains the records of each time until its input frontier has passed the time,
+    /// and then applies them to the snapshot in order of time, each replacing the record of its key.
+    /// The snapshot thus reflects exactly the records at times not greater or equal to the input
+    /// frontier it reports, and never the records of incomplete times; records of a key at the same
+    /// time replace one another in the order they arrived. The snapshot is updated as the worker
+    /// steps, and can be read between steps, or from other threads.
+    ///
+    /// Each worker materializes the records it receives, and so `self` should be exchanged by key
+    /// first if each key should be found in a single snapshot.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{Input, Materialize, Probe};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     let (mut input, snapshot, probe) = worker.dataflow::<u64,_,_>(|scope| {
+    ///         let (input, stream) = scope.new_input::<(String, u64)>();
+    ///         let snapshot = stream.materialize(|(name, _)| name.clone());
+    ///         (input, snapshot, stream.probe())
+    ///     });
+    ///     input.send(("apples".to_owned(), 3));
+    ///     input.send(("pears".to_owned(), 5));
+    ///     input.advance_to(1);
+    ///     input.send(("apples".to_owned(), 4));
+    ///     input.advance_to(2);
+    ///     worker.step_while(|| probe.less_than(input.time()));
+    ///     assert_eq!(snapshot.get(&"apples".to_owned()), Some(("apples".to_owned(), 4)));
+    ///     assert_eq!(snapshot.len(), 2);
+    /// });
+    /// ```
+    fn materialize<K, F>(&self, key_fn: F) -> Snapshot<G::Timestamp, K, D>
+    where
+        K: Hash+Eq+Send+Sync+'static,
+        D: Send+Sync,
+        F: FnMut(&D)->K+'static;
+}
+
+impl<G: Scope, D: Data> Materialize<G, D> for Stream<G, D> {
+    fn materialize<K, F>(&self, mut key_fn: F) -> Snapshot<G::Timestamp, K, D>
+    where
+        K: Hash+Eq+Send+Sync+'static,
+        D: Send+Sync,
+        F: FnMut(&D)->K+'static,
+    {
+        let snapshot = Snapshot::new();
+        let shared = snapshot.clone();
+        // For each incomplete time, its records.
+        let mut pending = Stash::<G::Timestamp, Vec<D>>::new();
+        self.sink(Pipeline, "Materialize", move |(input, frontier)| {
+            input.for_each_time(|time, data| {
+                pending.get_or_insert_with(time.time(), || time.time().clone(), Vec::new).extend(data.flat_map(|d| d.drain(..)));
+            });
+
+            let mut state = shared.state.write().expect("snapshot lock poisoned");
+            if state.frontier.borrow() != frontier.frontier() {
+                pending.release(|time| !frontier.less_equal(time), |_time, records| {
+                    for record in records {
+                        state.records.insert(key_fn(&record), record);
+                    }
+                });
+                state.frontier = frontier.frontier().to_owned();
+            }
+        });
+        snapshot
+    }
+}
+
+/// The materialized records and the frontier they reflect.
+struct SnapshotState<T, K, V> {
+    frontier: Antichain<T>,
+    records: HashMap<K, V>,
+}
+
+/// A shared, read-only view of the latest record per key, maintained by `materialize`.
+///
+/// Cloning the snapshot produces another view of the same records.
+pub struct Snapshot<T, K, V> {
+    state: Arc<RwLock<SnapshotState<T, K, V>>>,
+}
+
+impl<T, K, V> Clone for Snapshot<T, K, V> {
+    fn clone(&self) -> Self {
+        Snapshot { state: Arc::clone(&self.state) }
+    }
+}
+
+impl<T: Timestamp, K: Hash+Eq, V> Snapshot<T, K, V> {
+    /// Creates an empty snapshot at the minimal frontier.
+    fn new() -> Self {
+        Snapshot {
+            state: Arc::new(RwLock::new(SnapshotState {
+                frontier: Antichain::from_elem(T::minimum()),
+                records: HashMap::new(),
+            })),
+        }
+    }
+}
+
+impl<T: Clone, K: Hash+Eq, V> Snapshot<T, K, V> {
+    /// Reads the records and the frontier they reflect.
+    ///
+    /// The records are those at times not greater or equal to the frontier. The snapshot is not
+    /// updated while `logic` runs.
+    pub fn read<R>(&self, logic: impl FnOnce(&HashMap<K, V>, AntichainRef<'_, T>)->R) -> R {
+        let state = self.state.read().expect("snapshot lock poisoned");
+        logic(&state.records, state.frontier.borrow())
+    }
+    /// The latest record of `key`, if any.
+    pub fn get(&self, key: &K) -> Option<V> where V: Clone {
+        self.read(|records, _| records.get(key).cloned())
+    }
+    /// The number of keys with a record.
+    pub fn len(&self) -> usize {
+        self.read(|records, _| records.len())
+    }
+    /// Indicates whether no key has a record.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// The frontier the records reflect.
+    pub fn frontier(&self) -> Antichain<T> {
+        self.read(|_, frontier| frontier.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dataflow::operators::{Input, Probe};
+    use crate::progress::Antichain;
+    use super::Materialize;
+
+    #[test]
+    fn snapshot_reflects_complete_times() {
+        crate::execute_directly(|worker| {
+            let (mut input, snapshot, probe) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<(u64, u64)>();
+                let snapshot = stream.materialize(|(key, _)| *key);
+                (input, snapshot, stream.probe())
+            });
+
+            input.send((0, 10));
+            input.send((1, 10));
+            input.advance_to(1);
+            input.send((0, 11));
+            input.flush();
+            worker.step_while(|| probe.less_than(&1));
+            for _ in 0..5 { worker.step(); }
+            // Only the records of the complete time are reflected.
+            assert_eq!(snapshot.frontier(), Antichain::from_elem(1));
+            assert_eq!(snapshot.get(&0), Some((0, 10)));
+            assert_eq!(snapshot.get(&1), Some((1, 10)));
+
+            input.advance_to(2);
+            worker.step_while(|| probe.less_than(&2));
+            for _ in 0..5 { worker.step(); }
+            assert_eq!(snapshot.frontier(), Antichain::from_elem(2));
+            assert_eq!(snapshot.get(&0), Some((0, 11)));
+            assert_eq!(snapshot.len(), 2);
+
+            input.close();
+            worker.step_while(|| !probe.done());
+            for _ in 0..5 { worker.step(); }
+            assert!(snapshot.frontier().is_empty());
+        });
+    }
+}
@@ -0,0 +1,283 @@
+//! Extension methods for `Stream` that compute the median of values by key, exactly or approximately.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::hash::Hash;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::operators::sketch::{hash_of, summarize_by_key, TDigest};
+
+/// The exact median of a collection of values, maintained in two balanced heaps as values are inserted.
+///
+/// The lower heap holds the smaller half of the values, and the upper heap the larger half, with
+/// at most one more value in the lower heap than in the upper one. Each insertion takes time
+/// logarithmic in the number of values, all of which are retained.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::median::RunningMedian;
+///
+/// let mut median = RunningMedian::default();
+/// for value in [5.0, 1.0, 3.0] {
+///     median.insert(value);
+/// }
+/// assert_eq!(median.median(), 3.0);
+/// median.insert(4.0);
+/// assert_eq!(median.median(), 3.5);
+/// ```
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct RunningMedian {
+    /// The smaller half of the values, with the largest of them on top.
+    lower: BinaryHeap<Value>,
+    /// The larger half of the values, with the smallest of them on top.
+    upper: BinaryHeap<Reverse<Value>>,
+}
+
+impl RunningMedian {
+    /// The number of values inserted.
+    pub fn len(&self) -> usize { self.lower.len() + self.upper.len() }
+
+    /// Returns `true` if no values have been inserted.
+    pub fn is_empty(&self) -> bool { self.lower.is_empty() }
+
+    /// Inserts a value. NaN values are ignored.
+    pub fn insert(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        match self.lower.peek() {
+            Some(top) if value > top.0 => self.upper.push(Reverse(Value(value))),
+            _ => self.lower.push(Value(value)),
+        }
+        // Rebalance, so that the lower heap holds as many values as the upper one, or one more.
+        if self.lower.len() > self.upper.len() + 1 {
+            let moved = self.lower.pop().expect("non-empty");
+            self.upper.push(Reverse(moved));
+        }
+        else if self.upper.len() > self.lower.len() {
+            let Reverse(moved) = self.upper.pop().expect("non-empty");
+            self.lower.push(moved);
+        }
+    }
+
+    /// Inserts the values of another collection, forming the median of the union of their values.
+    pub fn merge(&mut self, other: Self) {
+        for Value(value) in other.lower.into_iter().chain(other.upper.into_iter().map(|Reverse(value)| value)) {
+            self.insert(value);
+        }
+    }
+
+    /// The median of the values: the middle value of an odd number of them, or the mean of the two middle values.
+    ///
+    /// Returns NaN if no values have been inserted.
+    pub fn median(&self) -> f64 {
+        match (self.lower.peek(), self.upper.peek()) {
+            (Some(lower), Some(Reverse(upper))) if self.lower.len() == self.upper.len() => (lower.0 + upper.0) / 2.0,
+            (Some(lower), _) => lower.0,
+            (None, _) => f64::NAN,
+        }
+    }
+}
+
+/// A value other than NaN, ordered by `f64::total_cmp`.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+struct Value(f64);
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool { self.cmp(other) == Ordering::Equal }
+}
+
+impl Eq for Value { }
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl Ord for Value {
+    fn cmp(&self, other: &Self) -> Ordering { self.0.total_cmp(&other.0) }
+}
+
+/// Extension trait for `Stream`.
+pub trait Median<G: Scope, D: Data> {
+    /// Computes the exact median of the values of each key and time.
+    ///
+    /// The `key_fn` and `value_fn` closures extract a key and a value from each record. Once the
+    /// input frontier has passed a time, the operator produces `(key, median)` for each key seen at
+    /// the time, where the median of an even number of values is the mean of the two middle ones.
+    /// NaN values are ignored, and a key with only NaN values has a NaN median.
+    ///
+    /// Each worker maintains a [`RunningMedian`] per key and time as records arrive, and the medians
+    /// are exchanged by key and merged. All values are retained until their time completes, and so
+    /// memory grows with the number of values of each key and time, each of which is exchanged to the
+    /// worker of its key; [`Median::approximate_median_by`] bounds the memory instead.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Median, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     vec![(0, 1.0), (0, 10.0), (0, 2.0), (1, 4.0)]
+    ///         .to_stream(scope)
+    ///         .median_by(|(key, _)| *key, |(_, value)| *value)
+    ///         .inspect(|(key, median)| assert_eq!(*median, if *key == 0 { 2.0 } else { 4.0 }));
+    /// });
+    /// ```
+    fn median_by<K, KF, VF>(&self, key_fn: KF, value_fn: VF) -> Stream<G, (K, f64)>
+    where
+        K: ExchangeData+Hash+Eq,
+        KF: FnMut(&D)->K+'static,
+        VF: FnMut(&D)->f64+'static;
+
+    /// Estimates the median of the values of each key and time, in bounded memory.
+    ///
+    /// Behaves as [`Median::median_by`], except that values are summarized in a [`TDigest`] per key
+    /// and time with compression `compression`, whose memory is proportional to the compression
+    /// rather than the number of values, and only the digests are exchanged. The estimate is close
+    /// to the median in rank, more so for larger compressions, but need not be one of the values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `compression` is not a finite number of at least one.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Median, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..1001u64).to_stream(scope)
+    ///                 .approximate_median_by(|_| (), |x| *x as f64, 100.0)
+    ///                 .inspect(|(_, median)| assert!((490.0..510.0).contains(median)));
+    /// });
+    /// ```
+    fn approximate_median_by<K, KF, VF>(&self, key_fn: KF, value_fn: VF, compression: f64) -> Stream<G, (K, f64)>
+    where
+        K: ExchangeData+Hash+Eq,
+        KF: FnMut(&D)->K+'static,
+        VF: FnMut(&D)->f64+'static;
+}
+
+impl<G: Scope<Timestamp: Hash>, D: Data> Median<G, D> for Stream<G, D> {
+    fn median_by<K, KF, VF>(&self, mut key_fn: KF, mut value_fn: VF) -> Stream<G, (K, f64)>
+    where
+        K: ExchangeData+Hash+Eq,
+        KF: FnMut(&D)->K+'static,
+        VF: FnMut(&D)->f64+'static,
+    {
+        summarize_by_key(self, "Median", hash_of,
+            move |medians, datum| {
+                medians.entry(key_fn(&datum)).or_insert_with(RunningMedian::default).insert(value_fn(&datum));
+            },
+            |median, other| median.merge(other),
+            |key, median| (key, median.median()),
+        )
+    }
+
+    fn approximate_median_by<K, KF, VF>(&self, mut key_fn: KF, mut value_fn: VF, compression: f64) -> Stream<G, (K, f64)>
+    where
+        K: ExchangeData+Hash+Eq,
+        KF: FnMut(&D)->K+'static,
+        VF: FnMut(&D)->f64+'static,
+    {
+        // Validate the compression while constructing the dataflow, rather than when running it.
+        drop(TDigest::new(compression));
+
+        summarize_by_key(self, "ApproximateMedian", hash_of,
+            move |digests, datum| {
+                digests.entry(key_fn(&datum)).or_insert_with(|| TDigest::new(compression)).insert(value_fn(&datum));
+            },
+            |digest, other| digest.merge(&other),
+            |key, mut digest| {
+                digest.compress();
+                (key, digest.quantile(0.5))
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::sync::mpsc::Receiver;
+
+    use crate::Config;
+    use crate::dataflow::operators::{Capture, Input, Median, Probe, ToStream};
+    use crate::dataflow::operators::capture::Event;
+    use super::RunningMedian;
+
+    /// The captured records with their times, as medians are not ordered and cannot be extracted.
+    fn messages<K>(captured: Receiver<Event<u64, Vec<(K, f64)>>>) -> Vec<(u64, (K, f64))> {
+        captured.iter().flat_map(|event| match event {
+            Event::Messages(time, data) => data.into_iter().map(|datum| (time, datum)).collect(),
+            Event::Progress(_) => Vec::new(),
+        }).collect()
+    }
+
+    #[test]
+    fn running_median_balances_its_heaps() {
+        let mut median = RunningMedian::default();
+        assert!(median.median().is_nan());
+        // Descending values are all inserted into the lower heap, and must move to the upper one.
+        for (value, expected) in [(9.0, 9.0), (8.0, 8.5), (7.0, 8.0), (f64::NAN, 8.0), (1.0, 7.5), (20.0, 8.0)] {
+            median.insert(value);
+            assert_eq!(median.median(), expected);
+        }
+        assert_eq!(median.len(), 5);
+        let mut other = RunningMedian::default();
+        for value in [2.0, 3.0, 4.0] { other.insert(value); }
+        median.merge(other);
+        assert_eq!(median.median(), 5.5);
+    }
+
+    #[test]
+    fn medians_are_exact_per_key_and_time() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<(char, f64)>();
+                let medians = stream.median_by(|(key, _)| *key, |(_, value)| *value);
+                (input, medians.probe(), medians.capture())
+            });
+            for record in [('a', 3.0), ('b', 1.0), ('a', 1.0), ('a', 2.0), ('b', 4.0)] { input.send(record); }
+            input.advance_to(1);
+            input.send(('a', 7.0));
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        let mut results = messages(captured);
+        results.sort_by_key(|(time, (key, _))| (*time, *key));
+        assert_eq!(results, vec![(0, ('a', 2.0)), (0, ('b', 2.5)), (1, ('a', 7.0))]);
+    }
+
+    #[test]
+    fn medians_of_workers_are_merged() {
+        let (send, recv) = std::sync::mpsc::channel();
+        let send = Arc::new(Mutex::new(send));
+        crate::execute(Config::process(3), move |worker| {
+            let send = send.lock().unwrap().clone();
+            let index = worker.index() as u64;
+            worker.dataflow::<u64,_,_>(move |scope| {
+                // The workers hold the values 0 .. 301 between them, as 0, 3, 6, ..., 1, 4, 7, ..., and so on.
+                let values = (0..301u64).filter(move |x| x % 3 == index).to_stream(scope);
+                values.median_by(|_| (), |x| *x as f64).capture_into(send.clone());
+                values.approximate_median_by(|_| (), |x| *x as f64, 100.0).capture_into(send);
+            });
+        }).unwrap();
+
+        let medians = messages(recv).into_iter().map(|(_time, ((), median))| median).collect::<Vec<_>>();
+        assert_eq!(medians.len(), 2);
+        assert!(medians.contains(&150.0));
+        assert!(medians.iter().all(|median| (145.0..155.0).contains(median)));
+    }
+
+    #[test]
+    #[should_panic(expected = "compression must be a finite number")]
+    fn invalid_compression_panics() {
+        crate::example(|scope| {
+            (0..10u64).to_stream(scope).approximate_median_by(|_| (), |x| *x as f64, 0.0);
+        });
+    }
+}
@@ -0,0 +1,184 @@
+//! Extension methods for `Stream` that merge sorted streams into one sorted stream.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::Data;
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::OutputBuilder;
+use crate::dataflow::operators::generic::builder_rc::OperatorBuilder;
+use crate::dataflow::operators::generic::stash::Stash;
+use crate::dataflow::operators::Capability;
+use crate::dataflow::{Scope, Stream};
+
+/// Extension trait for `Stream`.
+pub trait MergeSorted<G: Scope, D: Data> {
+    /// Merges `self` and `others`, each sorted by `cmp` within each time, into one stream sorted within each time.
+    ///
+    /// Each input is expected to present the records of each time in sorted order, across all of
+    /// its containers at that time, which builds with debug assertions check. Once the frontiers of
+    /// all inputs have passed a time, the operator merges the inputs' records at that time with a
+    /// heap over their next records, and produces them as a single sorted container. Records that
+    /// compare equal are produced in the order of their inputs, `self` first.
+    ///
+    /// The records of each time are retained until all inputs have passed the time. Records are not
+    /// exchanged, and each worker merges only the streams it holds.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, MergeSorted, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     let evens = (0..10).filter(|x| x % 2 == 0).to_stream(scope);
+    ///     let odds = (0..10).filter(|x| x % 2 == 1).to_stream(scope);
+    ///     evens.merge_sorted([odds], |x: &u64, y: &u64| x.cmp(y))
+    ///          .inspect_batch(|_time, data| assert_eq!(*data, (0..10).collect::<Vec<_>>()));
+    /// });
+    /// ```
+    fn merge_sorted<I, F>(&self, others: I, cmp: F) -> Stream<G, D>
+    where
+        I: IntoIterator<Item=Stream<G, D>>,
+        F: Fn(&D, &D)->Ordering+'static;
+}
+
+impl<G: Scope, D: Data> MergeSorted<G, D> for Stream<G, D> {
+    fn merge_sorted<I, F>(&self, others: I, cmp: F) -> Stream<G, D>
+    where
+        I: IntoIterator<Item=Stream<G, D>>,
+        F: Fn(&D, &D)->Ordering+'static,
+    {
+        let mut builder = OperatorBuilder::new("MergeSorted".to_owned(), self.scope());
+
+        let mut handles = Some(self.clone()).into_iter().chain(others).map(|stream| builder.new_input(&stream, Pipeline)).collect::<Vec<_>>();
+        let (output, result) = builder.new_output();
+        let mut output = OutputBuilder::from(output);
+
+        // For each incomplete time, a capability for it and each input's records at it.
+        let mut pending = Stash::<Capability<G::Timestamp>, Vec<Vec<D>>>::new();
+        let inputs = handles.len();
+
+        builder.build(move |_capability| {
+            move |frontiers| {
+                let mut output = output.activate();
+                for (index, handle) in handles.iter_mut().enumerate() {
+                    handle.for_each_time(|time, data| {
+                        let run = &mut pending.get_or_retain(&time, || vec![Vec::new(); inputs])[index];
+                        for datum in data.flat_map(|d| d.drain(..)) {
+                            debug_assert!(
+                                run.last().is_none_or(|last| cmp(last, &datum) != Ordering::Greater),
+                                "MergeSorted: input {} is not sorted at time {:?}",
+                                index,
+                                time.time(),
+                            );
+                            run.push(datum);
+                        }
+                    });
+                }
+
+                pending.release(|time| !frontiers.iter().any(|frontier| frontier.less_equal(time)), |capability, runs| {
+                    let mut merged = merge(runs, &cmp);
+                    output.session(&capability).give_container(&mut merged);
+                });
+            }
+        });
+
+        result
+    }
+}
+
+/// Merges sorted `runs` into one sorted vector.
+fn merge<D, F: Fn(&D, &D)->Ordering>(runs: Vec<Vec<D>>, cmp: &F) -> Vec<D> {
+    let mut merged = Vec::with_capacity(runs.iter().map(|run| run.len()).sum());
+    let mut runs = runs.into_iter().map(|run| run.into_iter()).collect::<Vec<_>>();
+    let mut heap = BinaryHeap::with_capacity(runs.len());
+    for (input, run) in runs.iter_mut().enumerate() {
+        if let Some(datum) = run.next() {
+            heap.push(Head { datum, input, cmp });
+        }
+    }
+    while let Some(Head { datum, input, .. }) = heap.pop() {
+        merged.push(datum);
+        if let Some(next) = runs[input].next() {
+            heap.push(Head { datum: next, input, cmp });
+        }
+    }
+    merged
+}
+
+/// The next record of an input, ordered so that the heap yields the least record first.
+struct Head<'a, D, F> {
+    datum: D,
+    input: usize,
+    cmp: &'a F,
+}
+
+impl<D, F: Fn(&D, &D)->Ordering> Ord for Head<'_, D, F> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.cmp)(&other.datum, &self.datum).then(other.input.cmp(&self.input))
+    }
+}
+
+impl<D, F: Fn(&D, &D)->Ordering> PartialOrd for Head<'_, D, F> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<D, F: Fn(&D, &D)->Ordering> PartialEq for Head<'_, D, F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<D, F: Fn(&D, &D)->Ordering> Eq for Head<'_, D, F> { }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use crate::dataflow::operators::{Input, Inspect, Probe, ToStream};
+    use super::MergeSorted;
+
+    #[test]
+    fn merges_each_time_once_complete() {
+        crate::execute_directly(|worker| {
+            let merged = Rc::new(RefCell::new(Vec::new()));
+            let merged_inner = Rc::clone(&merged);
+            let (mut input1, mut input2, probe) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input1, stream1) = scope.new_input::<(u64, char)>();
+                let (input2, stream2) = scope.new_input::<(u64, char)>();
+                let probe = stream1
+                    .merge_sorted([stream2], |x, y| x.0.cmp(&y.0))
+                    .inspect_batch(move |time, data| merged_inner.borrow_mut().push((*time, data.clone())))
+                    .probe();
+                (input1, input2, probe)
+            });
+
+            for round in 0..2 {
+                // Records arrive in several containers per input and time.
+                for key in [1, 4] { input1.send((key + round, 'a')); input1.flush(); }
+                for key in [2, 4, 5] { input2.send((key + round, 'b')); input2.flush(); }
+                input1.advance_to(round + 1);
+                input2.advance_to(round + 1);
+                worker.step_while(|| probe.less_than(input1.time()));
+            }
+
+            assert_eq!(*merged.borrow(), vec![
+                (0, vec![(1, 'a'), (2, 'b'), (4, 'a'), (4, 'b'), (5, 'b')]),
+                (1, vec![(2, 'a'), (3, 'b'), (5, 'a'), (5, 'b'), (6, 'b')]),
+            ]);
+        });
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "not sorted")]
+    fn unsorted_input_panics() {
+        crate::example(|scope| {
+            let sorted = (0..3u64).to_stream(scope);
+            let unsorted = [2u64, 1].to_stream(scope);
+            sorted.merge_sorted([unsorted], |x, y| x.cmp(y));
+        });
+    }
+}
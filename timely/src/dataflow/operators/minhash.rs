@@ -0,0 +1,199 @@
+//! Extension methods for `Stream` that estimate the similarity of groups of records with MinHash signatures.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Exchange;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::sketch::{hash_of, summarize_by_key, MinHash};
+
+/// Extension trait for `Stream`.
+pub trait MinHashSignatures<G: Scope, D: Data> {
+    /// Produces a MinHash signature `(key, signature)` of the items that `item_fn` produces for the records of each key and time.
+    ///
+    /// Each worker summarizes the items of its records in a [`MinHash`] signature of `num_hashes`
+    /// hash functions per key and time. Once the input frontier has passed a time, the signatures
+    /// for that time are exchanged by key and merged, and so an item seen by several workers is
+    /// summarized once. Memory use is proportional to the number of keys times `num_hashes`, rather
+    /// than to the number of items. The hash functions derive from [`MinHash::DEFAULT_SEED`], and
+    /// the signatures of a set of items are the same in every run.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_hashes` is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, MinHashSignatures, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     // Documents `(document, word)`.
+    ///     vec![(0, "the"), (0, "quick"), (0, "fox"), (1, "the"), (1, "slow"), (1, "fox")]
+    ///         .to_stream(scope)
+    ///         .minhash(|(document, _)| *document, |(_, word)| *word, 128)
+    ///         .inspect(|(document, signature)| println!("document {}: {:?}", document, &signature.signature()[..4]));
+    /// });
+    /// ```
+    fn minhash<K, V, F, L>(&self, key_fn: F, item_fn: L, num_hashes: usize) -> Stream<G, (K, MinHash)>
+    where
+        K: ExchangeData+Hash+Eq,
+        V: Hash,
+        F: FnMut(&D)->K+'static,
+        L: FnMut(&D)->V+'static;
+}
+
+impl<G: Scope<Timestamp: Hash>, D: Data> MinHashSignatures<G, D> for Stream<G, D> {
+    fn minhash<K, V, F, L>(&self, mut key_fn: F, mut item_fn: L, num_hashes: usize) -> Stream<G, (K, MinHash)>
+    where
+        K: ExchangeData+Hash+Eq,
+        V: Hash,
+        F: FnMut(&D)->K+'static,
+        L: FnMut(&D)->V+'static,
+    {
+        // Validate the number of hashes while constructing the dataflow, rather than when running it.
+        drop(MinHash::new(num_hashes));
+
+        summarize_by_key(self, "MinHash", hash_of,
+            move |signatures, datum| signatures.entry(key_fn(&datum)).or_insert_with(|| MinHash::new(num_hashes)).insert(&item_fn(&datum)),
+            |signature, other| signature.merge(&other),
+            |key, signature| (key, signature),
+        )
+    }
+}
+
+/// Extension trait for `Stream`.
+pub trait MinHashSimilarity<G: Scope, K: ExchangeData> {
+    /// Estimates the Jaccard similarity of each pair of signatures of a time, and produces `((key1, key2), similarity)` for those of at least `threshold`.
+    ///
+    /// Each pair is produced once, with `key1 < key2`. The signatures of a time are exchanged to
+    /// the first worker, which compares them once the input frontier has passed the time: memory
+    /// use is proportional to the number of signatures of the time, and the work to its square.
+    /// A `threshold` of zero produces all pairs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if signatures of a time have different numbers of hashes or seeds.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, MinHashSignatures, MinHashSimilarity, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..3000u64).map(|x| (x % 3, x % 1000 + x % 3 * 100))
+    ///         .to_stream(scope)
+    ///         .minhash(|(document, _)| *document, |(_, word)| *word, 128)
+    ///         .jaccard_similarities(0.5)
+    ///         .inspect(|((left, right), similarity)| println!("documents {} and {} are about {:.2} similar", left, right, similarity));
+    /// });
+    /// ```
+    fn jaccard_similarities(&self, threshold: f64) -> Stream<G, ((K, K), f64)>
+    where
+        K: Ord;
+}
+
+impl<G: Scope<Timestamp: Hash>, K: ExchangeData> MinHashSimilarity<G, K> for Stream<G, (K, MinHash)> {
+    fn jaccard_similarities(&self, threshold: f64) -> Stream<G, ((K, K), f64)>
+    where
+        K: Ord,
+    {
+        let mut signatures = HashMap::<G::Timestamp, Vec<(K, MinHash)>>::new();
+        self.unary_notify(Exchange::new(|_| 0), "MinHashSimilarity", vec![], move |input, output, notificator| {
+            input.for_each_time(|time, data| {
+                signatures.entry(time.time().clone()).or_default().extend(data.flat_map(|d| d.drain(..)));
+                notificator.notify_at(time.retain());
+            });
+            notificator.for_each(|time, _, _| {
+                if let Some(mut signatures) = signatures.remove(time.time()) {
+                    signatures.sort_by(|x, y| x.0.cmp(&y.0));
+                    let mut session = output.session(&time);
+                    for (index, (left, signature)) in signatures.iter().enumerate() {
+                        for (right, other) in &signatures[index + 1 ..] {
+                            let similarity = signature.jaccard(other);
+                            if similarity >= threshold {
+                                session.give(((left.clone(), right.clone()), similarity));
+                            }
+                        }
+                    }
+                }
+            });
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::Config;
+    use crate::dataflow::operators::{Capture, Input, MinHashSignatures, MinHashSimilarity};
+    use crate::dataflow::operators::capture::Event;
+    use crate::dataflow::operators::sketch::MinHash;
+
+    /// The captured similarities, by time and pair, as similarities are not `Ord` and cannot be extracted.
+    fn similarities<K: Ord>(captured: std::sync::mpsc::Receiver<Event<u64, Vec<((K, K), f64)>>>) -> Vec<(u64, (K, K), f64)> {
+        let mut similarities = captured.try_iter().flat_map(|event| match event {
+            Event::Messages(time, data) => data.into_iter().map(|(pair, similarity)| (time, pair, similarity)).collect(),
+            Event::Progress(_) => Vec::new(),
+        }).collect::<Vec<_>>();
+        similarities.sort_by(|x, y| (x.0, &x.1).cmp(&(y.0, &y.1)));
+        similarities
+    }
+
+    #[test]
+    fn signatures_are_merged_across_workers() {
+        let (send, recv) = std::sync::mpsc::channel();
+        let send = Arc::new(Mutex::new(send));
+        crate::execute(Config::process(3), move |worker| {
+            let send = send.lock().unwrap().clone();
+            let (index, peers) = (worker.index() as u64, worker.peers() as u64);
+            worker.dataflow::<u64,_,_>(|scope| {
+                let (mut input, stream) = scope.new_input::<(char, u64)>();
+                let signatures = stream.minhash(|(key, _)| *key, |(_, item)| *item, 256);
+                signatures.jaccard_similarities(0.0).capture_into(send);
+                // Sets 'a' and 'c' are equal, and share a third of their union with 'b'; each worker has a share of each.
+                for item in (0..1000).filter(|item| item % peers == index) {
+                    input.send(('a', item));
+                    input.send(('b', item + 500));
+                    input.send(('c', 999 - item));
+                }
+            });
+        }).unwrap();
+
+        let pairs = similarities(recv);
+        assert_eq!(pairs.iter().map(|(time, pair, _)| (*time, *pair)).collect::<Vec<_>>(), vec![(0, ('a', 'b')), (0, ('a', 'c')), (0, ('b', 'c'))]);
+        // The signatures are merged as those of the full sets, and so equal sets have equal signatures.
+        assert_eq!(pairs[1].2, 1.0);
+        for (_, _, similarity) in [pairs[0], pairs[2]] {
+            assert!((similarity - 1.0 / 3.0).abs() < 0.1, "similarity {} far from 1/3", similarity);
+        }
+        // The estimates are those of the signatures of the full sets, whichever worker saw each item.
+        let (mut a, mut b) = (MinHash::new(256), MinHash::new(256));
+        for item in 0..1000u64 { a.insert(&item); b.insert(&(item + 500)); }
+        assert_eq!(pairs[0].2, a.jaccard(&b));
+    }
+
+    #[test]
+    fn pairs_below_the_threshold_are_not_produced() {
+        let captured = crate::execute_directly(|worker| {
+            worker.dataflow::<u64,_,_>(|scope| {
+                let (mut input, stream) = scope.new_input::<(u64, u64)>();
+                let captured = stream.minhash(|(key, _)| *key, |(_, item)| *item, 128).jaccard_similarities(0.5).capture();
+                // Keys 0 and 1 share 90 of 110 items, and key 2 shares none with either.
+                for item in 0..100 {
+                    input.send((0, item));
+                    input.send((1, item + 10));
+                    input.send((2, item + 1000));
+                }
+                input.advance_to(1);
+                input.send((0, 0));
+                captured
+            })
+        });
+
+        let pairs = similarities(captured);
+        assert_eq!(pairs.iter().map(|(time, pair, _)| (*time, *pair)).collect::<Vec<_>>(), vec![(0, (0, 1))]);
+        assert!((pairs[0].2 - 90.0 / 110.0).abs() < 0.15);
+    }
+}
@@ -10,7 +10,7 @@
 
 pub use self::input::Input;
 pub use self::unordered_input::UnorderedInput;
-pub use self::partition::Partition;
+pub use self::partition::{Partition, LocalPartition};
 pub use self::map::Map;
 pub use self::inspect::{Inspect, InspectCore};
 pub use self::filter::Filter;
@@ -27,10 +27,72 @@ pub use self::generic::{Notificator, FrontierNotificator};
 
 pub use self::reclock::Reclock;
 pub use self::count::Accumulate;
+pub use self::assign_ids::AssignIds;
+pub use self::heartbeat::Heartbeat;
+pub use self::count_distinct::CountDistinctApprox;
+pub use self::quantiles::Quantiles;
+pub use self::bloom_filter::BloomFilterBy;
+pub use self::repartition::RepartitionBalanced;
+pub use self::latency::LatencyProbe;
+pub use self::late::DropLate;
+pub use self::checkpoint::Checkpoint;
+pub use self::diff::DiffBy;
+pub use self::join::IntervalJoin;
+pub use self::merge_sorted::MergeSorted;
+pub use self::suppress::SuppressUntil;
+pub use self::gate::Gate;
+pub use self::checksum::Checksum;
+pub use self::retry::MapRetry;
+pub use self::ordered_output::OrderedOutput;
+pub use self::global_distinct::GlobalDistinct;
+pub use self::materialize::{Materialize, Snapshot};
+pub use self::ingest_time::{WithIngestTime, MeasureLatency};
+pub use self::parallel_sort::ParallelSort;
+pub use self::histogram::Histogram;
+pub use self::running_top_k::RunningTopK;
+pub use self::dedup_ttl::DedupTtl;
+pub use self::session_id::AssignSessionId;
+pub use self::keepalive::Keepalive;
+pub use self::frequency::PartitionByFrequency;
+pub use self::rechunk::Rechunk;
+pub use self::zip::ZipByIndex;
+pub use self::idempotency::{WithIdempotencyKey, CountExactlyOnce};
+pub use self::gaps::DetectGaps;
+pub use self::windowed_count::WindowedCount;
+pub use self::heavy_hitters::HeavyHitters;
+pub use self::watermark::WatermarkStream;
+pub use self::processing_time::ProcessingTimeWindow;
+pub use self::slice::Slice;
+pub use self::delta::DeltaBy;
+pub use self::filter_in_set::FilterInSet;
+pub use self::batched_exchange::BatchedExchange;
+pub use self::median::Median;
+pub use self::enrich::Enrich;
+pub use self::cooccurrence::Cooccurrence;
+pub use self::reshard::Reshard;
+pub use self::chain::Chain;
+pub use self::connected_components::ConnectedComponents;
+pub use self::shedding::ShedLoad;
+pub use self::periodic_snapshot::SnapshotPeriodic;
+pub use self::progress_data::ProgressToData;
+pub use self::validate::Validate;
+pub use self::buffered_delay::BufferedDelay;
+pub use self::control::WithControl;
+pub use self::unique_per_time::AssertUniquePerTime;
+pub use self::rate_estimate::RateEstimateBy;
+pub use self::window_join::WindowJoin;
+pub use self::end_sentinel::WithEndSentinel;
+pub use self::session_aggregate::SessionAggregate;
+pub use self::dlq::WithDlq;
+pub use self::minhash::{MinHashSignatures, MinHashSimilarity};
+pub use self::decaying_top_k::DecayingTopK;
+pub use self::throttle::Throttle;
+#[cfg(feature = "roaring")]
+pub use self::bitmap::{ToBitmap, BitmapSetOps};
 
 pub mod core;
 
-pub use self::core::enterleave::{self, Enter, Leave};
+pub use self::core::enterleave::{self, Enter, Leave, LeaveWithTime};
 pub mod input;
 pub mod flow_controlled;
 pub mod unordered_input;
@@ -49,6 +111,8 @@ pub use self::core::capture::{self, Capture};
 pub mod branch;
 pub use self::core::ok_err::{self, OkErr};
 pub use self::core::rc;
+pub use self::core::convert::{self, ConvertContainer};
+pub use self::core::sink::{self, TrySink, FrontierSink};
 pub mod result;
 
 pub mod aggregation;
@@ -56,7 +120,74 @@ pub mod generic;
 
 pub use self::core::reclock;
 pub mod count;
+pub mod assign_ids;
+pub mod heartbeat;
+pub mod sketch;
+pub mod count_distinct;
+pub mod quantiles;
+pub mod bloom_filter;
+pub mod repartition;
+pub mod latency;
+pub mod late;
+pub mod checkpoint;
+pub mod diff;
+pub mod debug;
+pub mod join;
+pub mod merge_sorted;
+pub mod suppress;
+pub mod gate;
+pub mod checksum;
+pub mod retry;
+pub mod ordered_output;
+pub mod global_distinct;
+pub mod materialize;
+pub mod ingest_time;
+pub mod parallel_sort;
+pub mod histogram;
+pub mod running_top_k;
+pub mod dedup_ttl;
+pub mod session_id;
+pub mod keepalive;
+pub mod frequency;
+pub mod stall;
+pub mod rechunk;
+pub mod zip;
+pub mod idempotency;
+pub mod gaps;
+pub mod windowed_count;
+pub mod heavy_hitters;
+pub mod watermark;
+pub mod processing_time;
+pub mod slice;
+pub mod delta;
+pub mod filter_in_set;
+pub mod batched_exchange;
+pub mod median;
+pub mod enrich;
+pub mod cooccurrence;
+pub mod reshard;
+pub mod chain;
+pub mod connected_components;
+pub mod shedding;
+pub mod periodic_snapshot;
+pub mod progress_data;
+pub mod validate;
+pub mod buffered_delay;
+pub mod control;
+pub mod unique_per_time;
+pub mod rate_estimate;
+pub mod window_join;
+pub mod end_sentinel;
+pub mod session_aggregate;
+pub mod dlq;
+pub mod minhash;
+pub mod decaying_top_k;
+pub mod throttle;
+#[cfg(feature = "roaring")]
+pub mod bitmap;
 
 // keep "mint" module-private
 mod capability;
 pub use self::capability::{ActivateCapability, Capability, CapabilityTrait, InputCapability, CapabilitySet, DowngradeError};
+#[cfg(feature = "capability-provenance")]
+pub use self::capability::provenance;
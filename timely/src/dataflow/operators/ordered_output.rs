@@ -0,0 +1,148 @@
+//! Extension methods for `Stream` that produce the records of each time in a canonical order.
+
+use std::cmp::Ordering;
+
+use crate::Data;
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::Capability;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::generic::stash::Stash;
+use crate::dataflow::{Scope, Stream};
+
+/// Extension trait for `Stream`.
+pub trait OrderedOutput<G: Scope, D: Data> {
+    /// Produces the records of each time sorted by `cmp`, once the input frontier has passed the time.
+    ///
+    /// The order in which records arrive at a worker can vary from run to run, for example as
+    /// exchanged records from several workers interleave. This operator makes the order of its
+    /// output deterministic: each worker retains the records of each time until its input frontier
+    /// has passed the time, and then produces them as a single container, sorted by `cmp`. Records
+    /// that `cmp` considers equal may appear in any order relative to one another, and so for
+    /// reproducible output `cmp` should only consider equal records that are identical.
+    ///
+    /// The guarantee comes at a cost. No record of a time is produced before the time is complete,
+    /// which delays all output until the frontier advances, and each worker retains all records of
+    /// each incomplete time, which bounds memory use only by the volume of those times. It orders
+    /// records on each worker, and does not move records between workers.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Exchange, OrderedOutput, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10u64).rev()
+    ///               .to_stream(scope)
+    ///               .exchange(|x| *x)
+    ///               .ordered_output(|x, y| x.cmp(y))
+    ///               .inspect_batch(|_time, data| assert_eq!(*data, (0..10).collect::<Vec<_>>()));
+    /// });
+    /// ```
+    fn ordered_output<F>(&self, cmp: F) -> Stream<G, D>
+    where
+        F: Fn(&D, &D)->Ordering+'static;
+}
+
+impl<G: Scope, D: Data> OrderedOutput<G, D> for Stream<G, D> {
+    fn ordered_output<F>(&self, cmp: F) -> Stream<G, D>
+    where
+        F: Fn(&D, &D)->Ordering+'static,
+    {
+        // For each incomplete time, a capability for it and its records.
+        let mut pending = Stash::<Capability<G::Timestamp>, Vec<D>>::new();
+        self.unary_frontier(Pipeline, "OrderedOutput", move |_capability, _info| {
+            move |(input, frontier), output| {
+                input.for_each_time(|time, data| {
+                    pending.get_or_retain(&time, Vec::new).extend(data.flat_map(|d| d.drain(..)));
+                });
+
+                pending.release(|time| !frontier.less_equal(time), |capability, mut records| {
+                    records.sort_by(&cmp);
+                    output.session(&capability).give_container(&mut records);
+                });
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::sync::mpsc::Receiver;
+
+    use crate::Config;
+    use crate::dataflow::operators::{Capture, Exchange, Input, Map, Probe, ToStream};
+    use crate::dataflow::operators::capture::Event;
+    use super::OrderedOutput;
+
+    /// The captured containers, with their times, in the order they were produced.
+    fn containers<T, D>(captured: Receiver<Event<T, Vec<D>>>) -> Vec<(T, Vec<D>)> {
+        captured.iter().filter_map(|event| match event {
+            Event::Messages(time, data) => Some((time, data)),
+            Event::Progress(_) => None,
+        }).collect()
+    }
+
+    #[test]
+    fn records_of_a_time_are_produced_together_in_order() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<(u64, char)>();
+                let ordered = stream.ordered_output(|x, y| x.0.cmp(&y.0));
+                (input, ordered.probe(), ordered.capture())
+            });
+            // The records of time 0 arrive over several invocations; ties under the order keep their arrival order.
+            input.send((1, 'b'));
+            worker.step();
+            input.send((0, 'x'));
+            input.send((1, 'a'));
+            worker.step();
+            // Times 1 and 3 have records, and complete together after time 0.
+            input.advance_to(1);
+            input.send((5, 'c'));
+            input.advance_to(3);
+            input.send((2, 'd'));
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        assert_eq!(containers(captured), vec![
+            (0, vec![(0, 'x'), (1, 'b'), (1, 'a')]),
+            (1, vec![(5, 'c')]),
+            (3, vec![(2, 'd')]),
+        ]);
+    }
+
+    /// The containers produced by each worker, with their times, when four workers order exchanged records.
+    fn batches() -> Vec<(u64, Vec<(usize, (u64, u64))>)> {
+        let (send, recv) = std::sync::mpsc::channel();
+        let send = Arc::new(Mutex::new(send));
+        crate::execute(Config::process(4), move |worker| {
+            let send = send.lock().unwrap().clone();
+            let index = worker.index();
+            worker.dataflow::<u64,_,_>(|scope| {
+                (0..100u64).to_stream(scope)
+                           .map(move |x| (x % 7, x * 4 + index as u64))
+                           .exchange(|(key, _)| *key)
+                           .ordered_output(|x, y| x.cmp(y))
+                           .map(move |record| (index, record))
+                           .capture_into(send);
+            });
+        }).unwrap();
+        let mut batches = containers(recv);
+        batches.sort();
+        batches
+    }
+
+    #[test]
+    fn output_is_reproducible_across_runs() {
+        let first = batches();
+        // Each worker produces its records of the one time in a single sorted container.
+        assert_eq!(first.len(), 4);
+        assert!(first.iter().all(|(_, data)| data.windows(2).all(|pair| pair[0] <= pair[1])));
+        assert_eq!(first.iter().map(|(_, data)| data.len()).sum::<usize>(), 400);
+        for _ in 0..5 {
+            assert_eq!(batches(), first);
+        }
+    }
+}
@@ -0,0 +1,309 @@
+//! Extension methods for `Stream` that sort the records of each time across all workers.
+
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+use crate::ExchangeData;
+use crate::dataflow::{Scope, Stream};
+use crate::dataflow::channels::pact::{Exchange, Pipeline};
+use crate::dataflow::operators::{Broadcast, Capability, OrderedOutput};
+use crate::dataflow::operators::generic::OutputBuilder;
+use crate::dataflow::operators::generic::builder_rc::OperatorBuilder;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::generic::stash::Stash;
+
+/// The number of records each worker samples at each time, used by [`ParallelSort::parallel_sort`].
+pub const DEFAULT_SAMPLE_SIZE: usize = 64;
+
+/// Extension trait for `Stream`.
+pub trait ParallelSort<G: Scope, D: ExchangeData> {
+    /// Sorts the records of each time across all workers, so that the records produced by workers
+    /// `0, 1, ..` at a time, concatenated in that order, are sorted by `cmp`.
+    ///
+    /// Each worker samples its records at each time, and once the input frontier has passed the
+    /// time broadcasts its sample to all workers. From the samples of all workers, each worker picks
+    /// the same splitters, quantiles of the samples that divide the records into one contiguous range
+    /// per worker, and sends each of its records to the worker of its range. Each worker then sorts
+    /// the records of its range, and produces them as a single container once its input frontier
+    /// has passed the time.
+    ///
+    /// Ranges are balanced only as well as the samples represent the records, and all records that
+    /// `cmp` considers equal are sent to the same worker. Each worker samples [`DEFAULT_SAMPLE_SIZE`]
+    /// records at each time, and retains all of its records of each time until they are sent on.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, ParallelSort, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..100u64).rev()
+    ///                .to_stream(scope)
+    ///                .parallel_sort(|x, y| x.cmp(y))
+    ///                .inspect_batch(|_time, data| assert_eq!(*data, (0..100).collect::<Vec<_>>()));
+    /// });
+    /// ```
+    fn parallel_sort<F>(&self, cmp: F) -> Stream<G, D>
+    where
+        F: Fn(&D, &D)->Ordering+'static,
+    {
+        self.parallel_sort_with_sample_size(cmp, DEFAULT_SAMPLE_SIZE)
+    }
+
+    /// Sorts the records of each time across all workers, sampling `sample_size` records per worker and time.
+    ///
+    /// Behaves as [`ParallelSort::parallel_sort`], with samples of `sample_size` records. Larger
+    /// samples balance the ranges more evenly, at the cost of broadcasting more records.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `sample_size` is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, ParallelSort, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..100u64).to_stream(scope)
+    ///                .parallel_sort_with_sample_size(|x, y| y.cmp(x), 16)
+    ///                .inspect_batch(|_time, data| assert_eq!(*data, (0..100).rev().collect::<Vec<_>>()));
+    /// });
+    /// ```
+    fn parallel_sort_with_sample_size<F>(&self, cmp: F, sample_size: usize) -> Stream<G, D>
+    where
+        F: Fn(&D, &D)->Ordering+'static;
+}
+
+impl<G: Scope, D: ExchangeData> ParallelSort<G, D> for Stream<G, D> {
+    fn parallel_sort_with_sample_size<F>(&self, cmp: F, sample_size: usize) -> Stream<G, D>
+    where
+        F: Fn(&D, &D)->Ordering+'static,
+    {
+        assert!(sample_size > 0, "ParallelSort: sample_size must be positive");
+        let cmp = Rc::new(cmp);
+        let (records_stream, samples_stream) = sample(self, sample_size);
+
+        let cmp_route = Rc::clone(&cmp);
+        let peers = self.scope().peers();
+        // For each incomplete time, a capability for it and its records, and the samples received for it.
+        let mut pending = Stash::<Capability<G::Timestamp>, Vec<D>>::new();
+        let mut sampled = Stash::<G::Timestamp, Vec<D>>::new();
+        let routed: Stream<G, (usize, D)> = records_stream.binary_frontier(&samples_stream.broadcast(), Pipeline, Pipeline, "ParallelSortRoute", move |_capability, _info| {
+            move |(input1, frontier1), (input2, frontier2), output| {
+                input1.for_each_time(|time, data| {
+                    pending.get_or_retain(&time, Vec::new).extend(data.flat_map(|d| d.drain(..)));
+                });
+                input2.for_each_time(|time, data| {
+                    sampled.get_or_insert_with(time.time(), || time.time().clone(), Vec::new).extend(data.flat_map(|d| d.drain(..)));
+                });
+
+                let complete = |time: &G::Timestamp| !frontier1.less_equal(time) && !frontier2.less_equal(time);
+                pending.release(complete, |capability, records| {
+                    let mut samples = sampled.remove(capability.time()).unwrap_or_default();
+                    samples.sort_by(|x, y| cmp_route(x, y));
+                    let splitters = splitters(samples, peers);
+                    let mut session = output.session(&capability);
+                    for record in records {
+                        let target = splitters.partition_point(|splitter| cmp_route(splitter, &record) != Ordering::Greater);
+                        session.give((target, record));
+                    }
+                });
+                // Samples of times without records at this worker.
+                sampled.release(complete, |_time, _samples| { });
+            }
+        });
+
+        routed.exchange_by_worker()
+              .ordered_output(move |x, y| cmp(x, y))
+    }
+}
+
+/// Passes the records of `stream` through, and samples up to `sample_size` of them per time,
+/// produced once the input frontier has passed the time.
+fn sample<G: Scope, D: ExchangeData>(stream: &Stream<G, D>, sample_size: usize) -> (Stream<G, D>, Stream<G, D>) {
+    let mut builder = OperatorBuilder::new("ParallelSortSample".to_owned(), stream.scope());
+    let mut input = builder.new_input(stream, Pipeline);
+    let (records, records_stream) = builder.new_output();
+    let (samples, samples_stream) = builder.new_output();
+    let mut records = OutputBuilder::from(records);
+    let mut samples = OutputBuilder::from(samples);
+
+    // The worker's generator, seeded differently at each worker.
+    let rng = stream.scope().rng();
+    // For each incomplete time, a capability for its samples, the sample, and the number of records seen.
+    let mut reservoirs = Stash::<Capability<G::Timestamp>, (Vec<D>, u64)>::new();
+
+    builder.build(move |_capabilities| {
+        move |frontiers| {
+            let mut records = records.activate();
+            let mut samples = samples.activate();
+            input.for_each_time(|time, data| {
+                let (reservoir, seen) = reservoirs.get_or_insert_with(time.time(), || time.delayed_for_output(time.time(), 1), Default::default);
+                let mut session = records.session(&time);
+                for datum in data.flat_map(|d| d.drain(..)) {
+                    // Reservoir sampling: the `n`th record replaces a sampled record with probability `sample_size / n`.
+                    *seen += 1;
+                    if reservoir.len() < sample_size {
+                        reservoir.push(datum.clone());
+                    }
+                    else {
+                        let index = rng.below(*seen) as usize;
+                        if index < sample_size {
+                            reservoir[index] = datum.clone();
+                        }
+                    }
+                    session.give(datum);
+                }
+            });
+            reservoirs.release(|time| !frontiers[0].less_equal(time), |capability, (mut reservoir, _)| {
+                samples.session(&capability).give_container(&mut reservoir);
+            });
+        }
+    });
+
+    (records_stream, samples_stream)
+}
+
+/// Picks from sorted `samples` the `peers - 1` quantiles that divide them into `peers` ranges.
+fn splitters<D>(samples: Vec<D>, peers: usize) -> Vec<D> {
+    let count = samples.len();
+    if count == 0 {
+        return Vec::new();
+    }
+    let positions = (1..peers).map(|index| index * count / peers).collect::<Vec<_>>();
+    samples.into_iter()
+           .enumerate()
+           .filter(|(position, _)| positions.contains(position))
+           .map(|(_, sample)| sample)
+           .collect()
+}
+
+/// Sends records `(worker, datum)` to `worker`, and drops the worker index.
+trait ExchangeByWorker<G: Scope, D: ExchangeData> {
+    fn exchange_by_worker(&self) -> Stream<G, D>;
+}
+
+impl<G: Scope, D: ExchangeData> ExchangeByWorker<G, D> for Stream<G, (usize, D)> {
+    fn exchange_by_worker(&self) -> Stream<G, D> {
+        self.unary(Exchange::new(|(worker, _): &(usize, D)| *worker as u64), "ParallelSortExchange", |_capability, _info| {
+            move |input, output| {
+                input.for_each_time(|time, data| {
+                    output.session(&time).give_iterator(data.flat_map(|d| d.drain(..)).map(|(_, datum)| datum));
+                });
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::Config;
+    use crate::dataflow::operators::{Capture, Input, Map, Probe, ToStream};
+    use crate::dataflow::operators::capture::{Event, Extract};
+    use super::{ParallelSort, splitters};
+
+    /// The records each of four workers produces at each time, as `(time, [(worker, record)])`,
+    /// when each worker sends at time `round` the records `records(worker, round)`.
+    fn sorted_by_workers(rounds: u64, sample_size: usize, records: fn(usize, u64) -> Vec<u64>) -> Vec<(u64, Vec<(usize, u64)>)> {
+        let (send, recv) = std::sync::mpsc::channel();
+        let send = Arc::new(Mutex::new(send));
+        crate::execute(Config::process(4), move |worker| {
+            let send = send.lock().unwrap().clone();
+            let index = worker.index();
+            let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<u64>();
+                let sorted = stream.parallel_sort_with_sample_size(|x, y| x.cmp(y), sample_size)
+                                   .map(move |x| (index, x));
+                let probe = sorted.probe();
+                sorted.capture_into(send);
+                (input, probe)
+            });
+            for round in 0..rounds {
+                for x in records(index, round) { input.send(x); }
+                input.advance_to(round + 1);
+                worker.step_while(|| probe.less_than(input.time()));
+            }
+        }).unwrap();
+        recv.extract()
+    }
+
+    /// Asserts that the records of each worker, concatenated in order of workers, are `expected` sorted.
+    fn assert_sorted(produced: &[(usize, u64)], mut expected: Vec<u64>) {
+        // Captured records are sorted by worker first, and then by record.
+        let concatenated = produced.iter().map(|(_, x)| *x).collect::<Vec<_>>();
+        expected.sort();
+        assert_eq!(concatenated, expected);
+    }
+
+    #[test]
+    fn concatenation_is_sorted_per_time() {
+        // Scrambled records, with duplicates across workers.
+        let records = |index: usize, round: u64| (0..1000u64).map(|x| (x * 7919 + index as u64 * 13 + round) % 997).collect();
+        let produced = sorted_by_workers(3, 8, records);
+        assert_eq!(produced.iter().map(|(time, _)| *time).collect::<Vec<_>>(), vec![0, 1, 2]);
+        for (round, data) in produced.iter() {
+            // The records are spread over several workers.
+            let mut workers = data.iter().map(|(worker, _)| *worker).collect::<Vec<_>>();
+            workers.dedup();
+            assert!(workers.len() > 1);
+            assert_sorted(data, (0..4).flat_map(|index| records(index, *round)).collect());
+        }
+    }
+
+    #[test]
+    fn equal_records_are_sent_to_one_worker() {
+        let produced = sorted_by_workers(1, 4, |index, _| vec![5; 10 + index]);
+        assert_eq!(produced.len(), 1);
+        let mut workers = produced[0].1.iter().map(|(worker, _)| *worker).collect::<Vec<_>>();
+        workers.dedup();
+        assert_eq!(workers.len(), 1);
+        assert_sorted(&produced[0].1, vec![5; 46]);
+    }
+
+    #[test]
+    fn times_with_records_at_some_workers() {
+        // Only worker 2 has records at time 0, only worker 0 at time 2, and none at time 1.
+        let records = |index: usize, round: u64| match (index, round) {
+            (2, 0) => (0..50u64).rev().collect(),
+            (0, 2) => vec![3, 1, 2],
+            _ => Vec::new(),
+        };
+        let produced = sorted_by_workers(3, 8, records);
+        assert_eq!(produced.iter().map(|(time, _)| *time).collect::<Vec<_>>(), vec![0, 2]);
+        assert_sorted(&produced[0].1, (0..50).collect());
+        assert_sorted(&produced[1].1, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn single_worker_produces_one_sorted_container_per_time() {
+        let captured = crate::example(|scope| {
+            vec![3u64, 1, 2, 0, 2].into_iter()
+                .to_stream(scope)
+                .parallel_sort(|x, y| y.cmp(x))
+                .capture()
+        });
+        let batches = captured.iter().filter_map(|event| match event {
+            Event::Messages(time, data) => Some((time, data)),
+            Event::Progress(_) => None,
+        }).collect::<Vec<_>>();
+        assert_eq!(batches, vec![(0, vec![3, 2, 2, 1, 0])]);
+    }
+
+    #[test]
+    fn splitters_divide_samples_into_ranges() {
+        assert_eq!(splitters((0..8).collect(), 4), vec![2, 4, 6]);
+        assert_eq!(splitters((0..8).collect(), 1), Vec::<u64>::new());
+        assert_eq!(splitters(Vec::<u64>::new(), 4), Vec::<u64>::new());
+        // Fewer samples than workers yield fewer splitters, leaving the last ranges empty.
+        assert_eq!(splitters(vec![7, 9], 4), vec![7, 9]);
+    }
+
+    #[test]
+    #[should_panic(expected = "sample_size must be positive")]
+    fn zero_sample_size_panics() {
+        crate::example(|scope| {
+            (0..10u64).to_stream(scope).parallel_sort_with_sample_size(|x, y| x.cmp(y), 0);
+        });
+    }
+}
@@ -1,7 +1,10 @@
 //! Partition a stream of records into multiple streams.
 
+use std::hash::Hash;
+
 use crate::container::CapacityContainerBuilder;
 use crate::dataflow::operators::core::Partition as PartitionCore;
+use crate::dataflow::operators::sketch::hash_of;
 use crate::dataflow::{Scope, Stream};
 use crate::Data;
 
@@ -30,3 +33,39 @@ impl<G: Scope, D: Data, D2: Data, F: Fn(D)->(u64, D2)+'static> Partition<G, D, D
         PartitionCore::partition::<CapacityContainerBuilder<_>, _, _>(self, parts, route)
     }
 }
+
+/// Partition a stream of records into multiple streams by the hash of a key.
+pub trait LocalPartition<G: Scope, D: Data> {
+    /// Produces `parts` output streams, each containing the records whose key hashes to its index
+    /// modulo `parts`.
+    ///
+    /// All output streams remain on the same worker as their input, and no records are exchanged
+    /// between workers. Records with equal keys are routed to the same output stream, so that each
+    /// stream can be processed by its own operator instance with its own share of the keys.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `parts` is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, LocalPartition, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     let streams = (0..100u64).to_stream(scope)
+    ///                              .local_partition(4, |x| x % 10);
+    ///
+    ///     for (index, stream) in streams.into_iter().enumerate() {
+    ///         stream.inspect(move |x| println!("part {}: {:?}", index, x));
+    ///     }
+    /// });
+    /// ```
+    fn local_partition<K: Hash, F: FnMut(&D)->K+'static>(&self, parts: u64, key_fn: F) -> Vec<Stream<G, D>>;
+}
+
+impl<G: Scope, D: Data> LocalPartition<G, D> for Stream<G, D> {
+    fn local_partition<K: Hash, F: FnMut(&D)->K+'static>(&self, parts: u64, mut key_fn: F) -> Vec<Stream<G, D>> {
+        assert!(parts > 0, "local_partition requires at least one part");
+        PartitionCore::partition::<CapacityContainerBuilder<_>, _, _>(self, parts, move |datum| (hash_of(&key_fn(&datum)) % parts, datum))
+    }
+}
@@ -0,0 +1,183 @@
+//! Extension methods for `Stream` that periodically produce snapshots of the latest record per key.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::CapabilitySet;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::generic::stash::Stash;
+
+/// Whether a record produced by `snapshot_periodic` is an incremental update or part of a snapshot.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Tagged {
+    /// A record of the input, passed through at its time.
+    Update,
+    /// The latest record of its key at times before the time of the snapshot.
+    Snapshot,
+}
+
+/// Extension trait for `Stream`.
+pub trait SnapshotPeriodic<G: Scope, D: Data> {
+    /// Passes the records of `self` through as updates, and every `interval` produces a snapshot of the latest record for each key produced by `key_fn`.
+    ///
+    /// Each record of `self` is produced at its time as `(Tagged::Update, record)`. The operator
+    /// also maintains the latest record of each key, as [`Materialize::materialize`] does: the
+    /// records of each time are retained until the input frontier has passed the time, and are then
+    /// applied in order of time, each replacing the record of its key. Using a timer activation,
+    /// every `interval` the operator produces the record of each key as `(Tagged::Snapshot, record)`,
+    /// at each time of its input frontier.
+    ///
+    /// The snapshots are consistent: a snapshot at time `t` holds exactly the latest records of the
+    /// times not greater or equal to the input frontier, which contains `t`, and every update at
+    /// those times precedes it, while every later update is produced at a time greater or equal to
+    /// `t`. A consumer that joins late may thus start from any snapshot at `t` and apply the updates
+    /// at times greater or equal to `t` to follow the state from then on. Records of incomplete
+    /// times are never part of a snapshot, and a snapshot may repeat the previous one if no time
+    /// has completed since.
+    ///
+    /// Each worker snapshots the records it receives, and so `self` should be exchanged by key first
+    /// if each key should be found in a single snapshot. The operator holds a capability for each
+    /// element of its input frontier, and downgrades them as the frontier advances, so it never holds
+    /// the frontier back; once the input frontier is empty it releases them and stops.
+    ///
+    /// [`Materialize::materialize`]: crate::dataflow::operators::Materialize::materialize
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use timely::dataflow::operators::{Input, SnapshotPeriodic, Inspect};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     let mut input = worker.dataflow::<u64,_,_>(|scope| {
+    ///         let (input, stream) = scope.new_input::<(String, u64)>();
+    ///         stream.snapshot_periodic(|(name, _)| name.clone(), Duration::from_millis(10))
+    ///               .inspect_time(|time, (tagged, record)| println!("{:?} at {:?}: {:?}", tagged, time, record));
+    ///         input
+    ///     });
+    ///     input.send(("apples".to_owned(), 3));
+    ///     input.advance_to(1);
+    ///     for _ in 0 .. 5 {
+    ///         worker.step_or_park(Some(Duration::from_millis(10)));
+    ///     }
+    /// });
+    /// ```
+    fn snapshot_periodic<K, F>(&self, key_fn: F, interval: Duration) -> Stream<G, (Tagged, D)>
+    where
+        K: Hash+Eq+'static,
+        F: FnMut(&D)->K+'static;
+}
+
+impl<G: Scope, D: Data> SnapshotPeriodic<G, D> for Stream<G, D> {
+    fn snapshot_periodic<K, F>(&self, mut key_fn: F, interval: Duration) -> Stream<G, (Tagged, D)>
+    where
+        K: Hash+Eq+'static,
+        F: FnMut(&D)->K+'static,
+    {
+        let scope = self.scope();
+        self.unary_frontier(Pipeline, "SnapshotPeriodic", move |capability, info| {
+            let activator = scope.activator_for(info.address);
+            let mut held = CapabilitySet::from_elem(capability);
+            // For each incomplete time, its records.
+            let mut pending = Stash::<G::Timestamp, Vec<D>>::new();
+            // The latest record of each key, at the times the input frontier has passed.
+            let mut latest = HashMap::new();
+            let mut due = Instant::now() + interval;
+            activator.activate_after(interval);
+            move |(input, frontier), output| {
+                input.for_each_time(|time, data| {
+                    let records = pending.get_or_insert_with(time.time(), || time.time().clone(), Vec::new);
+                    let mut session = output.session(&time);
+                    for record in data.flat_map(|d| d.drain(..)) {
+                        session.give((Tagged::Update, record.clone()));
+                        records.push(record);
+                    }
+                });
+                pending.release(|time| !frontier.less_equal(time), |_time, records| {
+                    for record in records {
+                        latest.insert(key_fn(&record), record);
+                    }
+                });
+                held.downgrade(&frontier.frontier());
+
+                // Other schedulings, prompted by records or progress, neither snapshot nor re-arm the timer.
+                let now = Instant::now();
+                if !held.is_empty() && now >= due {
+                    for held_capability in held.iter() {
+                        let snapshot = latest.values().map(|record| (Tagged::Snapshot, record.clone()));
+                        output.session(held_capability).give_iterator(snapshot);
+                    }
+                    due = now + interval;
+                    activator.activate_after(interval);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::dataflow::operators::{Capture, Input, Probe, SnapshotPeriodic};
+    use crate::dataflow::operators::capture::Event;
+    use super::Tagged;
+
+    /// The longest the test waits for the timer, in steps of at most one interval each.
+    const MAX_STEPS: usize = 10_000;
+
+    #[test]
+    fn snapshots_reflect_the_completed_times() {
+        crate::execute_directly(|worker| {
+            let interval = Duration::from_millis(1);
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<(&'static str, u64)>();
+                let tagged = stream.snapshot_periodic(|(name, _)| *name, interval);
+                (input, tagged.probe(), tagged.capture())
+            });
+
+            // Steps the worker until a snapshot at `at` appears, and returns the records produced until then.
+            let mut step_until_snapshot = |at: u64| {
+                let mut records = Vec::new();
+                for _ in 0 .. MAX_STEPS {
+                    worker.step_or_park(Some(interval));
+                    records.extend(captured.try_iter().filter_map(|event| match event {
+                        Event::Messages(time, data) => Some((time, data)),
+                        Event::Progress(_) => None,
+                    }).flat_map(|(time, data)| data.into_iter().map(move |record| (time, record))));
+                    if records.iter().any(|(t, (tagged, _))| *t == at && *tagged == Tagged::Snapshot) { break; }
+                }
+                let (mut updates, mut snapshots): (Vec<_>, Vec<_>) = records.into_iter().partition(|(_, (tagged, _))| *tagged == Tagged::Update);
+                updates.sort();
+                // Only the last snapshot matters, as earlier ones may be repeated.
+                snapshots.retain(|(t, _)| *t == at);
+                snapshots.sort();
+                snapshots.dedup();
+                (updates, snapshots)
+            };
+
+            input.send(("apples", 3));
+            input.send(("pears", 5));
+            input.advance_to(1);
+            input.send(("apples", 4));
+            input.flush();
+            let (updates, snapshot) = step_until_snapshot(1);
+            assert_eq!(updates, vec![(0, (Tagged::Update, ("apples", 3))), (0, (Tagged::Update, ("pears", 5))), (1, (Tagged::Update, ("apples", 4)))]);
+            // The record at the incomplete time 1 is not part of the snapshot.
+            assert_eq!(snapshot, vec![(1, (Tagged::Snapshot, ("apples", 3))), (1, (Tagged::Snapshot, ("pears", 5)))]);
+
+            input.advance_to(2);
+            let (later_updates, later_snapshot) = step_until_snapshot(2);
+            assert!(later_updates.is_empty());
+            assert_eq!(later_snapshot, vec![(2, (Tagged::Snapshot, ("apples", 4))), (2, (Tagged::Snapshot, ("pears", 5)))]);
+
+            input.close();
+            worker.step_while(|| !probe.done());
+        });
+    }
+}
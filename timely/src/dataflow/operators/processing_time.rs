@@ -0,0 +1,188 @@
+//! Extension methods for `Stream` that window records by the wall-clock time of their arrival.
+
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::Capability;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::generic::stash::Stash;
+
+/// Extension trait for `Stream`.
+pub trait ProcessingTimeWindow<G: Scope, D: Data> {
+    /// Groups records into tumbling windows of `size` of wall-clock time, by the time of their arrival, as `(start, records)`.
+    ///
+    /// The windows are the intervals of `size` of wall-clock time since the Unix epoch, and `start` is
+    /// the duration from the epoch to the start of a window. A record belongs to the window of the
+    /// wall-clock time at which the operator receives it, and needs no timestamp of its own. Using a
+    /// timer activation, the operator produces the records of a window once its end has passed,
+    /// without waiting for further input, and once the input is complete it produces the records of
+    /// the open window at once.
+    ///
+    /// Window boundaries are wall-clock times, independent of the timestamps of the dataflow: a
+    /// window holds the records of every time that arrive during it, and the records of a time may
+    /// be spread across several windows. The records of each window are produced separately for each
+    /// of their times, at that time, and the operator holds a capability for each time with records
+    /// in the open window, and so the output frontier lags the input by up to a window. Windows are
+    /// formed at each worker, as the input is not exchanged, and their boundaries agree across workers
+    /// to the extent that their clocks do. Windows without records produce nothing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use timely::dataflow::operators::{ToStream, ProcessingTimeWindow, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .processing_time_window(Duration::from_secs(60))
+    ///            .inspect(|(start, records)| println!("{} records in the minute from {:?}", records.len(), start));
+    /// });
+    /// ```
+    fn processing_time_window(&self, size: Duration) -> Stream<G, (Duration, Vec<D>)>;
+}
+
+impl<G: Scope, D: Data> ProcessingTimeWindow<G, D> for Stream<G, D> {
+    fn processing_time_window(&self, size: Duration) -> Stream<G, (Duration, Vec<D>)> {
+        assert!(!size.is_zero(), "ProcessingTimeWindow: size must be positive");
+        let scope = self.scope();
+        self.unary_frontier(Pipeline, "ProcessingTimeWindow", move |_capability, info| {
+            let activator = scope.activator_for(info.address);
+            // The start of the open window, and its records by time.
+            let mut start = Duration::ZERO;
+            let mut records = Stash::<Capability<G::Timestamp>, Vec<D>>::new();
+            // When the timer for the end of the open window expires, if it is set.
+            let mut due = None;
+            move |(input, frontier), output| {
+                let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default();
+                let current = window_of(now, size);
+
+                // The open window has ended, or will receive no more records.
+                if current != start || frontier.is_empty() {
+                    records.release(|_time| true, |capability, data| {
+                        output.session(&capability).give((start, data));
+                    });
+                    start = current;
+                }
+
+                input.for_each_time(|time, data| {
+                    let buffer = records.get_or_retain(&time, Vec::new);
+                    for datum in data { buffer.append(datum); }
+                });
+                if frontier.is_empty() {
+                    records.release(|_time| true, |capability, data| {
+                        output.session(&capability).give((start, data));
+                    });
+                }
+
+                // The timer is set only while the open window has records, and set again if it expires early.
+                if due.is_some_and(|due| Instant::now() >= due) {
+                    due = None;
+                }
+                if due.is_none() && !records.is_empty() {
+                    let remaining = start + size - now;
+                    due = Some(Instant::now() + remaining);
+                    activator.activate_after(remaining);
+                }
+            }
+        })
+    }
+}
+
+/// The start of the window of `size` containing `time`.
+fn window_of(time: Duration, size: Duration) -> Duration {
+    let size_nanos = size.as_nanos();
+    let start_nanos = time.as_nanos() - time.as_nanos() % size_nanos;
+    Duration::new((start_nanos / 1_000_000_000) as u64, (start_nanos % 1_000_000_000) as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::dataflow::operators::{Capture, Input, Probe, ProcessingTimeWindow, ToStream};
+    use crate::dataflow::operators::capture::{Event, Extract};
+    use super::window_of;
+
+    /// The longest the timer test waits for the timer, in steps of at most one window each.
+    const MAX_STEPS: usize = 10_000;
+
+    #[test]
+    fn windows_are_aligned_to_multiples_of_their_size() {
+        let size = Duration::from_millis(1_500);
+        assert_eq!(window_of(Duration::from_millis(4_400), size), Duration::from_millis(3_000));
+        assert_eq!(window_of(Duration::from_millis(4_500), size), Duration::from_millis(4_500));
+        assert_eq!(window_of(Duration::new(1, 7), Duration::from_nanos(5)), Duration::new(1, 5));
+    }
+
+    #[test]
+    fn completed_input_closes_the_open_window() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<char>();
+                // A window far longer than the test.
+                let windows = stream.processing_time_window(Duration::from_secs(1 << 40));
+                (input, windows.probe(), windows.capture())
+            });
+            input.send('a');
+            input.advance_to(1);
+            input.send('b');
+            input.send('c');
+            worker.step();
+            // The open window holds the frontier at the times of its records.
+            assert!(probe.less_equal(&0));
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        let results = captured.extract();
+        assert_eq!(results.len(), 2);
+        assert_eq!((results[0].0, &results[0].1[0].1), (0, &vec!['a']));
+        assert_eq!((results[1].0, &results[1].1[0].1), (1, &vec!['b', 'c']));
+        // Both times arrived in the same window.
+        assert_eq!(results[0].1[0].0, results[1].1[0].0);
+    }
+
+    #[test]
+    fn windows_close_on_the_wall_clock() {
+        crate::execute_directly(|worker| {
+            let size = Duration::from_millis(1);
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<u64>();
+                let windows = stream.processing_time_window(size);
+                (input, windows.probe(), windows.capture())
+            });
+            input.send(4);
+            input.advance_to(1);
+            // Without further input or progress, the timer produces the window once it ends.
+            let mut results = Vec::new();
+            for _ in 0 .. MAX_STEPS {
+                worker.step_or_park(Some(size));
+                results.extend(captured.try_iter().filter_map(|event| match event {
+                    Event::Messages(time, data) => Some((time, data)),
+                    Event::Progress(_) => None,
+                }));
+                if !results.is_empty() { break; }
+            }
+            assert_eq!(results.len(), 1);
+            assert_eq!((results[0].0, &results[0].1[0].1), (0, &vec![4]));
+            // The produced window releases the time of its records.
+            worker.step_while(|| probe.less_equal(&0));
+            input.close();
+            worker.step_while(|| !probe.done());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "size must be positive")]
+    fn zero_size_panics() {
+        crate::example(|scope| {
+            (0..10u64).to_stream(scope).processing_time_window(Duration::ZERO);
+        });
+    }
+}
@@ -0,0 +1,137 @@
+//! Extension methods for `Stream` that produce the changes of its frontier as records.
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::CapabilitySet;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::progress::{Antichain, ChangeBatch, Timestamp};
+
+/// Extension trait for `Stream`.
+pub trait ProgressToData<G: Scope, D: Data> {
+    /// Produces the changes of the frontier of `self` as records `(time, diff)`, and discards its records.
+    ///
+    /// Each time the operator is scheduled and finds its input frontier changed, it produces the
+    /// difference from the frontier it last saw: `(time, -1)` for each time that left the frontier
+    /// and `(time, 1)` for each that joined it. Only actual changes are produced, and the first
+    /// scheduling produces the frontier it finds as changes from the empty frontier, so that the
+    /// records accumulated to any point are the frontier the operator saw then, and once the input
+    /// completes they accumulate to nothing. Changes between schedulings are coalesced, and so an
+    /// external tracker mirroring the frontier sees it advance in steps at least as coarse as those
+    /// of the input.
+    ///
+    /// The changes are produced at the times of the frontier they leave. The operator holds a
+    /// capability for each element of its input frontier, and downgrades them as the frontier
+    /// advances, so its output frontier is that of its input and it does not hold progress back.
+    /// The frontier is shared by all workers, and so each worker produces the same changes, at
+    /// possibly different schedulings.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{Input, Inspect, ProgressToData};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     let mut input = worker.dataflow::<u64,_,_>(|scope| {
+    ///         let (input, stream) = scope.new_input::<String>();
+    ///         stream.progress_to_data()
+    ///               .inspect(|(time, diff)| println!("frontier change: {:?} {:+}", time, diff));
+    ///         input
+    ///     });
+    ///     for round in 0 .. 3 {
+    ///         input.advance_to(round + 1);
+    ///         worker.step();
+    ///     }
+    /// });
+    /// ```
+    fn progress_to_data(&self) -> Stream<G, (G::Timestamp, i64)>;
+}
+
+impl<G: Scope, D: Data> ProgressToData<G, D> for Stream<G, D> {
+    fn progress_to_data(&self) -> Stream<G, (G::Timestamp, i64)> {
+        self.unary_frontier(Pipeline, "ProgressToData", move |capability, _info| {
+            let mut held = CapabilitySet::from_elem(capability);
+            // The frontier last produced, initially empty so that the first scheduling produces the initial frontier.
+            let mut seen = Antichain::<G::Timestamp>::new();
+            let mut changes = ChangeBatch::<G::Timestamp>::new();
+            move |(input, frontier), output| {
+                input.for_each(|_time, _data| { });
+                if seen.borrow() != frontier.frontier() {
+                    changes.extend(seen.iter().map(|time| (time.clone(), -1)));
+                    changes.extend(frontier.frontier().iter().map(|time| (time.clone(), 1)));
+                    if let Some(held_capability) = held.first() {
+                        output.session(held_capability).give_iterator(changes.drain());
+                    }
+                    seen = frontier.frontier().to_owned();
+                    held.downgrade(&frontier.frontier());
+                }
+            }
+        })
+    }
+}
+
+/// Accumulates frontier changes as produced by `progress_to_data` into the frontier they describe.
+///
+/// The changes may be those of any prefix of the records produced, for example by an external
+/// tracker mirroring the frontier, and times whose changes accumulate to zero are not included.
+pub fn accumulate_frontier<T: Timestamp>(changes: impl IntoIterator<Item = (T, i64)>) -> Antichain<T> {
+    let mut batch = ChangeBatch::<T>::new();
+    batch.extend(changes.into_iter());
+    let mut frontier = Antichain::new();
+    for (time, diff) in batch.into_inner() {
+        debug_assert_eq!(diff, 1, "frontier changes must accumulate to single elements");
+        frontier.insert(time);
+    }
+    frontier
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dataflow::operators::{Capture, Input, Probe, ProgressToData};
+    use crate::dataflow::operators::capture::Event;
+    use super::accumulate_frontier;
+
+    #[test]
+    fn changes_accumulate_to_the_frontier() {
+        let (changes, frontiers) = crate::execute_directly(|worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<u64>();
+                let changes = stream.progress_to_data();
+                (input, changes.probe(), changes.capture())
+            });
+            // The records accumulated so far, and the frontier they describe, after each round.
+            let mut changes = Vec::new();
+            let mut frontiers = Vec::new();
+            for round in [1, 2, 5] {
+                input.send(round);
+                input.advance_to(round);
+                worker.step_while(|| probe.less_than(&round));
+                // Records are not the frontier's to hold back.
+                assert!(!probe.less_than(&round));
+                changes.extend(captured.try_iter().filter_map(|event| match event {
+                    Event::Messages(time, data) => Some(data.into_iter().map(move |change| (time, change))),
+                    Event::Progress(_) => None,
+                }).flatten());
+                frontiers.push(accumulate_frontier(changes.iter().map(|(_time, change)| *change)));
+            }
+            input.close();
+            worker.step_while(|| !probe.done());
+            changes.extend(captured.try_iter().filter_map(|event| match event {
+                Event::Messages(time, data) => Some(data.into_iter().map(move |change| (time, change))),
+                Event::Progress(_) => None,
+            }).flatten());
+            frontiers.push(accumulate_frontier(changes.iter().map(|(_time, change)| *change)));
+            (changes, frontiers)
+        });
+
+        let mut ordered = changes.clone();
+        ordered.sort();
+        assert_eq!(ordered, vec![
+            (0, (0, -1)), (0, (0, 1)), (0, (1, 1)),
+            (1, (1, -1)), (1, (2, 1)),
+            (2, (2, -1)), (2, (5, 1)),
+            (5, (5, -1)),
+        ]);
+        let frontiers = frontiers.iter().map(|frontier| frontier.elements().to_vec()).collect::<Vec<_>>();
+        assert_eq!(frontiers, vec![vec![1], vec![2], vec![5], vec![]]);
+    }
+}
@@ -0,0 +1,155 @@
+//! Extension methods for `Stream` that estimate quantiles of values.
+
+use std::hash::Hash;
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::operators::Map;
+use crate::dataflow::operators::sketch::{hash_of, summarize_by_key, TDigest};
+
+/// The compression used by [`Quantiles::quantiles`].
+pub const DEFAULT_COMPRESSION: f64 = 100.0;
+
+/// Extension trait for `Stream`.
+pub trait Quantiles<G: Scope, D: Data> {
+    /// Estimates quantiles of the values for each key and time.
+    ///
+    /// The `key_fn` closure extracts a key and a value from each record. Once the input frontier
+    /// has passed a time, the operator produces `(key, estimates)` for each key seen at the time,
+    /// where `estimates[i]` estimates the `quantiles[i]` quantile of the key's values. Quantiles
+    /// are given in the range `0 ..= 1`, so that `0.99` requests the 99th percentile.
+    ///
+    /// Values are summarized in a [`TDigest`] per key and time with the compression
+    /// [`DEFAULT_COMPRESSION`]. The digests are formed on each worker, then exchanged by key and
+    /// merged, so records are never buffered.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Quantiles, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..1000u64).to_stream(scope)
+    ///                 .quantiles(|x| ((), *x as f64), &[0.5, 0.99])
+    ///                 .inspect(|(_, estimates)| println!("p50 {}, p99 {}", estimates[0], estimates[1]));
+    /// });
+    /// ```
+    fn quantiles<K, F>(&self, key_fn: F, quantiles: &[f64]) -> Stream<G, (K, Vec<f64>)>
+    where
+        K: ExchangeData+Hash+Eq,
+        F: FnMut(&D)->(K, f64)+'static,
+    {
+        self.quantiles_with_compression(key_fn, quantiles, DEFAULT_COMPRESSION)
+    }
+
+    /// Estimates quantiles of the values for each key and time, with a supplied compression.
+    ///
+    /// Behaves as [`Quantiles::quantiles`], with digests of compression `compression`. Larger
+    /// compressions produce more accurate estimates, and use proportionally more memory.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Quantiles, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..1000u64).to_stream(scope)
+    ///                 .quantiles_with_compression(|x| (x % 2, *x as f64), &[0.5], 200.0)
+    ///                 .inspect(|(parity, estimates)| println!("median of parity {}: {}", parity, estimates[0]));
+    /// });
+    /// ```
+    fn quantiles_with_compression<K, F>(&self, key_fn: F, quantiles: &[f64], compression: f64) -> Stream<G, (K, Vec<f64>)>
+    where
+        K: ExchangeData+Hash+Eq,
+        F: FnMut(&D)->(K, f64)+'static;
+
+    /// Estimates quantiles of the values of all workers for each time.
+    ///
+    /// The `value_fn` closure extracts a value from each record. Once the input frontier has passed
+    /// a time, a single worker produces the estimates for the values at that time across all
+    /// workers, where `estimates[i]` estimates the `quantiles[i]` quantile, and other workers produce
+    /// nothing. As for [`Quantiles::quantiles`], each worker summarizes its values in a [`TDigest`]
+    /// of compression [`DEFAULT_COMPRESSION`], and only the digests are exchanged.
+    ///
+    /// The digests of each time are merged at the one worker, which receives a digest from each
+    /// worker with values. A digest holds at most a small multiple of its compression of centroids,
+    /// whatever the number of values, so that the merging worker receives and merges a number of
+    /// centroids proportional to the number of workers. This is modest for clusters of up to
+    /// hundreds of workers; for larger clusters, a reduction tree could merge groups of digests
+    /// before the last merge, at the cost of an exchange per level of the tree.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Quantiles, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..1000u64).to_stream(scope)
+    ///                 .global_quantiles(|x| *x as f64, &[0.5, 0.99])
+    ///                 .inspect(|estimates| println!("p50 {}, p99 {}", estimates[0], estimates[1]));
+    /// });
+    /// ```
+    fn global_quantiles<F>(&self, mut value_fn: F, quantiles: &[f64]) -> Stream<G, Vec<f64>>
+    where
+        F: FnMut(&D)->f64+'static,
+    {
+        self.quantiles(move |datum| ((), value_fn(datum)), quantiles)
+            .map(|((), estimates)| estimates)
+    }
+}
+
+impl<G: Scope<Timestamp: Hash>, D: Data> Quantiles<G, D> for Stream<G, D> {
+    fn quantiles_with_compression<K, F>(&self, mut key_fn: F, quantiles: &[f64], compression: f64) -> Stream<G, (K, Vec<f64>)>
+    where
+        K: ExchangeData+Hash+Eq,
+        F: FnMut(&D)->(K, f64)+'static,
+    {
+        // Validate the compression while constructing the dataflow, rather than when running it.
+        drop(TDigest::new(compression));
+        let quantiles = quantiles.to_vec();
+
+        summarize_by_key(self, "Quantiles", hash_of,
+            move |digests, datum| {
+                let (key, value) = key_fn(&datum);
+                digests.entry(key).or_insert_with(|| TDigest::new(compression)).insert(value);
+            },
+            |digest, other| digest.merge(&other),
+            move |key, mut digest| {
+                digest.compress();
+                (key, quantiles.iter().map(|q| digest.quantile(*q)).collect())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::Config;
+    use crate::dataflow::operators::{Capture, Quantiles, ToStream};
+    use crate::dataflow::operators::capture::Event;
+
+    #[test]
+    fn global_quantiles_merge_the_values_of_all_workers() {
+        let (send, recv) = std::sync::mpsc::channel();
+        let send = Arc::new(Mutex::new(send));
+        crate::execute(Config::process(4), move |worker| {
+            let send = send.lock().unwrap().clone();
+            let index = worker.index() as u64;
+            worker.dataflow::<u64,_,_>(move |scope| {
+                // Each worker holds a quarter of the values, and its own quantiles would be far from the global ones.
+                (1_000 * index .. 1_000 * (index + 1)).to_stream(scope)
+                                                      .global_quantiles(|x| *x as f64, &[0.1, 0.5, 0.9])
+                                                      .capture_into(send);
+            });
+        }).unwrap();
+
+        let estimates = recv.iter().flat_map(|event| match event {
+            Event::Messages(_time, data) => data,
+            Event::Progress(_) => Vec::new(),
+        }).collect::<Vec<_>>();
+        assert_eq!(estimates.len(), 1);
+        // Estimates are within two percent of the values in rank, whatever the order in which the digests are merged.
+        for (estimate, expected) in estimates[0].iter().zip([400.0, 2_000.0, 3_600.0]) {
+            assert!((estimate - expected).abs() < 80.0, "estimate {} for {}", estimate, expected);
+        }
+    }
+}
@@ -0,0 +1,176 @@
+//! Extension methods for `Stream` that estimate the rate of records of each key with decaying counters.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::rc::Rc;
+
+use crate::ExchangeData;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Exchange;
+use crate::dataflow::operators::Capability;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::generic::stash::Stash;
+use crate::dataflow::operators::sketch::hash_of;
+
+/// The counter below which a key is forgotten, as it is then indistinguishable from a new key.
+const FORGET_BELOW: f64 = 1e-3;
+
+/// Extension trait for `Stream`.
+pub trait RateEstimateBy<G: Scope<Timestamp = u64>, D: ExchangeData> {
+    /// Pairs each record with the estimated rate of records with its key of `key_fn`, in records per time.
+    ///
+    /// Each key has a counter, a leaky bucket that every record fills by one and that drains
+    /// continuously, losing half its contents every `half_life` times. A record at time `t` first
+    /// decays the counter of its key from the time of the key's previous record to `t`, and then
+    /// adds one, and is produced with the estimate `counter * ln 2 / half_life`. For records
+    /// arriving at a steady rate the estimate approaches that rate, a burst raises it by about
+    /// `ln 2 / half_life` per record, and a quiet period lets it decay back toward zero, halving
+    /// every `half_life` times. Of the records with a key at one time, later ones see the earlier
+    /// ones in their estimate, in the order in which they are received.
+    ///
+    /// Decay is by the records' times, not by wall-clock time, and so estimates are the same in
+    /// each run and however the records are delivered. To this end records are exchanged by key,
+    /// and each worker buffers the records of a time until its input frontier has passed the time,
+    /// so that times are applied in order. Counters persist across times, and a key is forgotten
+    /// once its counter has decayed below a thousandth as of the input frontier, so memory is
+    /// proportional to the active keys, those with records within about ten half-lives.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `half_life` is not positive.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, RateEstimateBy, Delay, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..100u64).to_stream(scope)
+    ///                .delay(|x, _| *x / 10)
+    ///                .rate_estimate_by(|_| "key", 5.0)
+    ///                .inspect(|(x, rate)| println!("{:?}: {:.2} records per time", x, rate));
+    /// });
+    /// ```
+    fn rate_estimate_by<K, F>(&self, key_fn: F, half_life: f64) -> Stream<G, (D, f64)>
+    where
+        K: Hash+Eq+'static,
+        F: Fn(&D)->K+'static;
+}
+
+impl<G: Scope<Timestamp = u64>, D: ExchangeData> RateEstimateBy<G, D> for Stream<G, D> {
+    fn rate_estimate_by<K, F>(&self, key_fn: F, half_life: f64) -> Stream<G, (D, f64)>
+    where
+        K: Hash+Eq+'static,
+        F: Fn(&D)->K+'static,
+    {
+        assert!(half_life > 0.0, "RateEstimateBy: half_life must be positive");
+        let key_fn = Rc::new(key_fn);
+        let route = Rc::clone(&key_fn);
+        let pact = Exchange::new(move |datum: &D| hash_of(&route(datum)));
+        // The rate at which counters decay, per time.
+        let decay = std::f64::consts::LN_2 / half_life;
+
+        self.unary_frontier(pact, "RateEstimateBy", move |_capability, _info| {
+            // For each incomplete time, a capability for it and its records.
+            let mut pending = Stash::<Capability<u64>, Vec<D>>::new();
+            // For each active key, its counter as of the time of its latest record.
+            let mut counters = HashMap::<K, (f64, u64)>::new();
+            move |(input, frontier), output| {
+                input.for_each_time(|time, data| {
+                    pending.get_or_retain(&time, Vec::new).extend(data.flat_map(|d| d.drain(..)));
+                });
+
+                pending.release(|time| !frontier.less_equal(time), |capability, records| {
+                    let time = *capability.time();
+                    let mut session = output.session(&capability);
+                    for record in records {
+                        let (counter, at) = counters.entry(key_fn(&record)).or_insert((0.0, time));
+                        *counter = *counter * (-decay * (time - *at) as f64).exp() + 1.0;
+                        *at = time;
+                        let rate = *counter * decay;
+                        session.give((record, rate));
+                    }
+                });
+
+                // Forget keys whose counters will have decayed away by the time of any future record.
+                if let Some(earliest) = frontier.frontier().first() {
+                    counters.retain(|_, (counter, at)| *counter * (-decay * earliest.saturating_sub(*at) as f64).exp() >= FORGET_BELOW);
+                }
+                else {
+                    counters.clear();
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::Config;
+    use crate::dataflow::operators::{Capture, Input, Map, Probe, RateEstimateBy};
+    use crate::dataflow::operators::capture::{Event, Extract};
+
+    #[test]
+    fn estimates_rise_under_bursts_and_decay_when_quiet() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<(char, u64)>();
+                let rates = stream.rate_estimate_by(|(key, _)| *key, 4.0);
+                (input, rates.probe(), rates.capture())
+            });
+            // A burst of key 'a' at times 0 to 4, a single record of 'b' at time 0, and a record of
+            // each after a quiet period.
+            input.send(('b', 0));
+            for time in 0..5 {
+                input.advance_to(time);
+                for _ in 0..10 { input.send(('a', time)); }
+                worker.step_while(|| probe.less_than(input.time()));
+            }
+            input.advance_to(60);
+            input.send(('a', 60));
+            input.send(('b', 60));
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        let rates = captured.try_iter().filter_map(|event| match event {
+            Event::Messages(_time, data) => Some(data),
+            Event::Progress(_) => None,
+        }).flatten().collect::<Vec<_>>();
+        let rate = |key, time| rates.iter().filter(|((k, t), _)| *k == key && *t == time).map(|(_, rate)| *rate).fold(0.0, f64::max);
+        // The burst raises the estimate of 'a' at each time, and well above that of 'b'.
+        for time in 1..5 {
+            assert!(rate('a', time) > rate('a', time - 1));
+        }
+        assert!(rate('a', 4) > 10.0 * rate('b', 0));
+        // After fourteen half-lives the estimates are back to those of a single record.
+        let single = std::f64::consts::LN_2 / 4.0;
+        assert!((rate('b', 0) - single).abs() < 1e-9);
+        assert!(rate('a', 60) < 1.01 * single);
+        assert!(rate('b', 60) < 1.01 * single);
+    }
+
+    #[test]
+    fn estimates_count_the_records_of_all_workers() {
+        let (send, recv) = std::sync::mpsc::channel();
+        let send = Arc::new(Mutex::new(send));
+        crate::execute(Config::process(3), move |worker| {
+            let send = send.lock().unwrap().clone();
+            let index = worker.index() as u64;
+            worker.dataflow::<u64,_,_>(|scope| {
+                let (mut input, stream) = scope.new_input::<(u64, u64)>();
+                stream.rate_estimate_by(|(key, _)| *key, 1.0)
+                      .map(|((key, _), rate)| (key, (rate / std::f64::consts::LN_2).round() as u64))
+                      .capture_into(send);
+                // Each worker has one record of key 0, and one of its own key.
+                input.send((0, index));
+                input.send((index + 1, index));
+            });
+        }).unwrap();
+
+        // The records of key 0 meet at one worker, and see one, two, and three records.
+        assert_eq!(recv.extract(), vec![(0, vec![(0, 1), (0, 2), (0, 3), (1, 1), (2, 1), (3, 1)])]);
+    }
+}
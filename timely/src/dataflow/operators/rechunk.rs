@@ -0,0 +1,209 @@
+//! Extension methods for `Stream` that regroup records into containers of a fixed number of records.
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::{Exchange, ParallelizationContract, Pipeline};
+use crate::dataflow::operators::Capability;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::generic::stash::Stash;
+
+/// Extension trait for `Stream`.
+pub trait Rechunk<G: Scope, D: Data> {
+    /// Regroups the records of each time into containers of exactly `n` records.
+    ///
+    /// The operator buffers the records of each time, and produces a container, at the time, as
+    /// soon as it has `n` records for it. Once the input frontier has passed a time, the remaining
+    /// records of the time, fewer than `n`, are produced as one last container; times with a multiple
+    /// of `n` records have no partial container, and times without records produce nothing. At most
+    /// `n - 1` records are buffered for each incomplete time.
+    ///
+    /// Downstream operators connected by a pipeline receive the containers as they are produced,
+    /// while an exchange regroups the records it sends.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Rechunk, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .rechunk(4)
+    ///            .inspect_batch(|_time, data| assert!(data.len() == 4 || *data == vec![8, 9]));
+    /// });
+    /// ```
+    fn rechunk(&self, n: usize) -> Stream<G, D>;
+
+    /// Exchanges records to the worker `route` selects, and regroups the records each worker receives at each time into containers of exactly `n` records.
+    ///
+    /// This is `exchange(route).rechunk(n)` in one operator: the records are regrouped as they are
+    /// received, rather than by a second operator after the exchange, and so containers fill with the
+    /// records of all senders, with one partial container for each time and receiving worker once the
+    /// input frontier has passed the time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Rechunk, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10u64).to_stream(scope)
+    ///               .exchange_rechunk(|x| *x, 4)
+    ///               .inspect_batch(|_time, data| assert!(data.len() == 4 || *data == vec![8, 9]));
+    /// });
+    /// ```
+    fn exchange_rechunk(&self, route: impl FnMut(&D)->u64+'static, n: usize) -> Stream<G, D> where D: ExchangeData;
+}
+
+impl<G: Scope, D: Data> Rechunk<G, D> for Stream<G, D> {
+    fn rechunk(&self, n: usize) -> Stream<G, D> {
+        rechunk_with(self, Pipeline, "Rechunk", n)
+    }
+
+    fn exchange_rechunk(&self, route: impl FnMut(&D)->u64+'static, n: usize) -> Stream<G, D> where D: ExchangeData {
+        rechunk_with(self, Exchange::new(route), "ExchangeRechunk", n)
+    }
+}
+
+/// Regroups the records of each time that `stream` delivers through `pact` into containers of exactly `n` records.
+fn rechunk_with<G, D, P>(stream: &Stream<G, D>, pact: P, name: &str, n: usize) -> Stream<G, D>
+where
+    G: Scope,
+    D: Data,
+    P: ParallelizationContract<G::Timestamp, Vec<D>>,
+{
+    assert!(n > 0, "{}: n must be positive", name);
+    stream.unary_frontier(pact, name, move |_capability, _info| {
+        // For each incomplete time, a capability for it and its fewer than `n` buffered records.
+        let mut buffered = Stash::<Capability<G::Timestamp>, Vec<D>>::new();
+        move |(input, frontier), output| {
+            input.for_each_time(|time, data| {
+                let buffer = buffered.get_or_retain(&time, || Vec::with_capacity(n));
+                let mut session = output.session(&time);
+                for datum in data.flat_map(|d| d.drain(..)) {
+                    buffer.push(datum);
+                    if buffer.len() == n {
+                        session.give_container(&mut std::mem::replace(buffer, Vec::with_capacity(n)));
+                    }
+                }
+            });
+            buffered.release(|time| !frontier.less_equal(time), |capability, mut buffer| {
+                if !buffer.is_empty() {
+                    output.session(&capability).give_container(&mut buffer);
+                }
+            });
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::Config;
+    use crate::dataflow::operators::{Capture, Concat, Delay, Exchange, Input, Operator, Probe, Rechunk, ToStream};
+    use crate::dataflow::operators::capture::{Event, Extract};
+
+    #[test]
+    fn containers_have_exactly_n_records() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<u64>();
+                let rechunked = stream.rechunk(3);
+                (input, rechunked.probe(), rechunked.capture())
+            });
+            // The records of time 0 arrive over several invocations, and fill containers across them.
+            for x in 0..2 { input.send(x); }
+            worker.step();
+            for x in 2..7 { input.send(x); }
+            worker.step();
+            // Time 1 has a multiple of three records, time 2 none, and time 3 fewer than three.
+            input.advance_to(1);
+            for x in 10..16 { input.send(x); }
+            input.advance_to(3);
+            input.send(30);
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        let containers = captured.iter().filter_map(|event| match event {
+            Event::Messages(time, data) => Some((time, data)),
+            Event::Progress(_) => None,
+        }).collect::<Vec<_>>();
+        // Full containers are produced as soon as they fill, and partial ones once their time completes.
+        assert_eq!(containers, vec![
+            (0, vec![0, 1, 2]),
+            (0, vec![3, 4, 5]),
+            (1, vec![10, 11, 12]),
+            (1, vec![13, 14, 15]),
+            (0, vec![6]),
+            (3, vec![30]),
+        ]);
+    }
+
+    #[test]
+    fn exchange_rechunk_matches_exchange_then_rechunk() {
+        let (send, recv) = std::sync::mpsc::channel::<Event<u64, Vec<(u64, u64, usize, Vec<(u64, u64)>)>>>();
+        let send = Arc::new(Mutex::new(send));
+        crate::execute(Config::process(3), move |worker| {
+            let send = send.lock().unwrap().clone();
+            let index = worker.index() as u64;
+            worker.dataflow::<u64,_,_>(|scope| {
+                let records = (0..100u64).filter(move |x| x % 3 == index).map(|x| (x % 7, x)).to_stream(scope).delay(|(_, x), _| x / 40);
+                let fused = records.exchange_rechunk(|(key, _)| *key, 4);
+                let separate = records.exchange(|(key, _)| *key).rechunk(4);
+                // Tags each worker's containers with the worker, their number of records, and their records.
+                let tag = move |stream: &crate::dataflow::Stream<_, (u64, u64)>, which| stream
+                    .unary(crate::dataflow::channels::pact::Pipeline, "Tag", move |_, _| move |input, output| {
+                        input.for_each_time(|time, data| {
+                            let mut session = output.session(&time);
+                            for container in data {
+                                let mut sorted = container.clone();
+                                sorted.sort();
+                                session.give((which, index, container.len(), sorted));
+                            }
+                        });
+                    });
+                tag(&fused, 0).concat(&tag(&separate, 1)).capture_into(send);
+            });
+        }).unwrap();
+
+        let mut fused = Vec::new();
+        let mut separate = Vec::new();
+        for (time, containers) in recv.extract() {
+            for (which, worker, len, records) in containers {
+                if which == 0 { fused.push((time, worker, len, records)); } else { separate.push((time, worker, len, records)); }
+            }
+        }
+        // Each worker receives the same records at each time, as containers of four records and at
+        // most one partial container per time.
+        let records = |containers: &[(u64, u64, usize, Vec<(u64, u64)>)]| {
+            let mut records = containers.iter().flat_map(|(time, worker, _, records)| records.iter().map(move |record| (*time, *worker, *record))).collect::<Vec<_>>();
+            records.sort();
+            records
+        };
+        assert_eq!(records(&fused), records(&separate));
+        assert_eq!(records(&fused).len(), 100);
+        for containers in [&fused, &separate] {
+            let mut partials = containers.iter().filter(|(_, _, len, _)| *len != 4).map(|(time, worker, _, _)| (*time, *worker)).collect::<Vec<_>>();
+            let count = partials.len();
+            partials.sort();
+            partials.dedup();
+            assert_eq!(partials.len(), count);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "n must be positive")]
+    fn zero_n_panics() {
+        crate::example(|scope| {
+            (0..10u64).to_stream(scope).rechunk(0);
+        });
+    }
+}
@@ -0,0 +1,173 @@
+//! Extension methods for `Stream` that exchange records and report the resulting distribution.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::ExchangeData;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::{Broadcast, Exchange};
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::sketch::hash_of;
+
+/// Extension trait for `Stream`.
+pub trait RepartitionBalanced<G: Scope, D: ExchangeData> {
+    /// Exchanges records by the hash of `key_fn`, and reports how many records each worker received.
+    ///
+    /// The first returned stream contains the exchanged records. The second contains, for each time,
+    /// a record `(worker, count)` from each worker that received `count > 0` records at that time;
+    /// workers that receive no records at a time do not report it. The counts are broadcast, so that
+    /// every worker observes the full distribution and can compare its share against that of its
+    /// peers, for example to detect skewed keys or to choose a different `key_fn`.
+    ///
+    /// Keys are hashed with a hasher of a fixed algorithm, which routes each key to the same worker
+    /// in each run of the program and spreads keys that are close in value (for example, sequential
+    /// integers) across all workers. The routing may change should the `Hash` implementation of the
+    /// key change, for example between versions of the Rust standard library.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, RepartitionBalanced, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     let (records, balance) = (0..1000u64).to_stream(scope)
+    ///                                          .repartition_balanced(|x| x % 10);
+    ///     records.inspect(|x| assert!(*x < 1000));
+    ///     balance.inspect(|(worker, count)| println!("worker {} received {} records", worker, count));
+    /// });
+    /// ```
+    fn repartition_balanced<K, F>(&self, key_fn: F) -> (Stream<G, D>, Stream<G, (usize, u64)>)
+    where
+        K: Hash,
+        F: FnMut(&D)->K+'static;
+}
+
+impl<G: Scope<Timestamp: Hash>, D: ExchangeData> RepartitionBalanced<G, D> for Stream<G, D> {
+    fn repartition_balanced<K, F>(&self, mut key_fn: F) -> (Stream<G, D>, Stream<G, (usize, u64)>)
+    where
+        K: Hash,
+        F: FnMut(&D)->K+'static,
+    {
+        let index = self.scope().index();
+        let exchanged = self.exchange(move |datum| hash_of(&key_fn(datum)));
+
+        let mut counts = HashMap::new();
+        let balance = exchanged.unary_notify(Pipeline, "RepartitionBalance", vec![], move |input, output, notificator| {
+            input.for_each_time(|time, data| {
+                let count = counts.entry(time.time().clone()).or_insert(0u64);
+                for container in data {
+                    *count += container.len() as u64;
+                }
+                notificator.notify_at(time.retain());
+            });
+            notificator.for_each(|time, _, _| {
+                if let Some(count) = counts.remove(time.time()) {
+                    output.session(&time).give((index, count));
+                }
+            });
+        });
+
+        (exchanged, balance.broadcast())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::Config;
+    use crate::dataflow::operators::{Capture, Input, Map, Probe, RepartitionBalanced};
+    use crate::dataflow::operators::capture::Extract;
+
+    /// Captured records, with the worker that produced each, by time.
+    type ByWorker<D> = Vec<(u64, Vec<(usize, D)>)>;
+
+    /// The records received by each of three workers, as `(time, [(worker, record)])`, and the reports
+    /// observed by each worker, as `(time, [(observer, (worker, count))])`, when each worker sends at
+    /// time `round` the records `records(round)` keyed by `key`.
+    fn repartition(rounds: u64, records: fn(u64) -> Vec<u64>, key: fn(&u64) -> u64) -> (ByWorker<u64>, ByWorker<(usize, u64)>) {
+        let (records_send, records_recv) = std::sync::mpsc::channel();
+        let (balance_send, balance_recv) = std::sync::mpsc::channel();
+        let senders = Arc::new(Mutex::new((records_send, balance_send)));
+        crate::execute(Config::process(3), move |worker| {
+            let (records_into, balance_into) = senders.lock().unwrap().clone();
+            let index = worker.index();
+            let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<u64>();
+                let (exchanged, balance) = stream.repartition_balanced(key);
+                let balance = balance.map(move |report| (index, report));
+                let probe = balance.probe();
+                exchanged.map(move |x| (index, x)).capture_into(records_into);
+                balance.capture_into(balance_into);
+                (input, probe)
+            });
+            for round in 0..rounds {
+                for x in records(round) { input.send(x); }
+                input.advance_to(round + 1);
+                worker.step_while(|| probe.less_than(input.time()));
+            }
+        }).unwrap();
+        (records_recv.extract(), balance_recv.extract())
+    }
+
+    /// The number of records each worker received in `received`, for the workers that received some.
+    fn counts(received: &[(usize, u64)]) -> Vec<(usize, u64)> {
+        let mut counts: Vec<(usize, u64)> = Vec::new();
+        for (worker, _) in received {
+            match counts.last_mut() {
+                Some((last, count)) if last == worker => *count += 1,
+                _ => counts.push((*worker, 1)),
+            }
+        }
+        counts
+    }
+
+    #[test]
+    fn every_worker_observes_the_distribution() {
+        let (received, balance) = repartition(2, |round| (0..1000).map(|x| x + round).collect(), |x| *x);
+        assert_eq!(received.iter().map(|(time, _)| *time).collect::<Vec<_>>(), vec![0, 1]);
+        assert_eq!(balance.iter().map(|(time, _)| *time).collect::<Vec<_>>(), vec![0, 1]);
+        for ((_, records), (_, reports)) in received.iter().zip(balance.iter()) {
+            // Each of the three workers introduces all 1000 records, spread across all workers.
+            assert_eq!(records.len(), 3000);
+            let counts = counts(records);
+            assert_eq!(counts.len(), 3);
+            // Each worker observes the count of each worker.
+            for observer in 0..3 {
+                let observed = reports.iter().filter(|(o, _)| *o == observer).map(|(_, report)| *report).collect::<Vec<_>>();
+                assert_eq!(observed, counts);
+            }
+        }
+    }
+
+    #[test]
+    fn workers_without_records_do_not_report() {
+        // All records have the same key, and time 1 has no records at all.
+        let (received, balance) = repartition(3, |round| if round == 1 { Vec::new() } else { vec![round; 10] }, |_| 0);
+        assert_eq!(received.len(), 2);
+        assert_eq!(balance.iter().map(|(time, _)| *time).collect::<Vec<_>>(), vec![0, 2]);
+        for ((_, records), (_, reports)) in received.iter().zip(balance.iter()) {
+            let counts = counts(records);
+            assert_eq!(counts.len(), 1);
+            assert_eq!(counts[0].1, 30);
+            assert_eq!(reports, &(0..3).map(|observer| (observer, counts[0])).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn routing_is_reproducible_across_runs() {
+        let records = |_| (0..100).collect();
+        let (first, _) = repartition(1, records, |x| *x % 10);
+        for _ in 0..3 {
+            assert_eq!(repartition(1, records, |x| *x % 10).0, first);
+        }
+        // Records of the same key are received by the same worker.
+        for (_, received) in first.iter() {
+            for key in 0..10 {
+                let mut workers = received.iter().filter(|(_, x)| x % 10 == key).map(|(worker, _)| *worker).collect::<Vec<_>>();
+                workers.dedup();
+                assert_eq!(workers.len(), 1);
+            }
+        }
+    }
+}
@@ -0,0 +1,156 @@
+//! Extension methods for `Stream` that report the keys moving between shards when the number of shards changes.
+
+use std::hash::Hash;
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::operators::{Filter, Map};
+use crate::dataflow::operators::sketch::{hash_of, summarize_by_key};
+
+/// The shard of `key` among `shards` shards, by jump consistent hashing.
+///
+/// Keys are hashed with a hasher of a fixed algorithm, so that each key has the same shard on all
+/// workers and in all runs of the program. Hashes are assigned to shards by the jump consistent
+/// hash of Lamping and Veach, which spreads keys evenly across the shards and moves as few keys as
+/// possible when the number of shards changes: from `n` to `n + 1` shards, only a fraction
+/// `1 / (n + 1)` of keys move, all of them to the new shard, and from `n + 1` to `n` shards only
+/// the keys of the removed shard move.
+///
+/// # Panics
+///
+/// Panics if `shards` is zero.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::reshard::shard_of;
+///
+/// // Keys either stay in their shard, or move to the added one.
+/// for key in 0..100u64 {
+///     let before = shard_of(&key, 4);
+///     let after = shard_of(&key, 5);
+///     assert!(before == after || after == 4);
+/// }
+/// ```
+pub fn shard_of<K: Hash + ?Sized>(key: &K, shards: usize) -> usize {
+    assert!(shards > 0, "shard_of: shards must be positive");
+    let mut state = hash_of(key);
+    let mut shard: i64 = -1;
+    let mut next: i64 = 0;
+    while next < shards as i64 {
+        shard = next;
+        state = state.wrapping_mul(2862933555777941757).wrapping_add(1);
+        next = ((shard + 1) as f64 * ((1u64 << 31) as f64 / ((state >> 33) + 1) as f64)) as i64;
+    }
+    shard as usize
+}
+
+/// Extension trait for `Stream`.
+pub trait Reshard<G: Scope, D: Data> {
+    /// Reports the keys that move to another shard when the number of shards changes from `old_shards` to `new_shards`.
+    ///
+    /// For each distinct key `key_fn` extracts from the records of each time, whose shard by
+    /// [`shard_of`] differs between `old_shards` and `new_shards` shards, the operator produces
+    /// `(key, old_shard, new_shard)` once the input frontier has passed the time. Keys that stay in
+    /// their shard are not reported. The moves are instructions for migrating the state of each key,
+    /// and by consistent hashing they are as few as possible: about `|new - old| / max(old, new)`
+    /// of the keys move.
+    ///
+    /// Each key is reported once per time across all workers: keys that move are exchanged by key,
+    /// and deduplicated at the worker they reach. Keys that do not move are discarded where they are.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `old_shards` or `new_shards` is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Reshard, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..100u64).to_stream(scope)
+    ///                .reshard(3, 4, |x| *x)
+    ///                .inspect(|(key, old, new)| println!("move {} from shard {} to {}", key, old, new));
+    /// });
+    /// ```
+    fn reshard<K, F>(&self, old_shards: usize, new_shards: usize, key_fn: F) -> Stream<G, (K, usize, usize)>
+    where
+        K: ExchangeData+Hash+Eq,
+        F: FnMut(&D)->K+'static;
+}
+
+impl<G: Scope<Timestamp: Hash>, D: Data> Reshard<G, D> for Stream<G, D> {
+    fn reshard<K, F>(&self, old_shards: usize, new_shards: usize, mut key_fn: F) -> Stream<G, (K, usize, usize)>
+    where
+        K: ExchangeData+Hash+Eq,
+        F: FnMut(&D)->K+'static,
+    {
+        assert!(old_shards > 0 && new_shards > 0, "Reshard: shard counts must be positive");
+
+        let moves = self
+            .map(move |datum| {
+                let key = key_fn(&datum);
+                let shards = (shard_of(&key, old_shards), shard_of(&key, new_shards));
+                (key, shards)
+            })
+            .filter(|(_key, (old, new))| old != new);
+
+        summarize_by_key(&moves, "Reshard", hash_of,
+            |moved, (key, shards)| { moved.insert(key, shards); },
+            |_shards, _other| { },
+            |key, (old, new)| (key, old, new),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::Config;
+    use crate::dataflow::operators::{Capture, Reshard, ToStream};
+    use crate::dataflow::operators::capture::Extract;
+    use super::shard_of;
+
+    #[test]
+    fn shards_change_minimally() {
+        let keys = 0..10_000u64;
+        // Growing from 8 to 10 shards moves about a fifth of the keys, all to the added shards.
+        let moved = keys.clone().filter(|key| shard_of(key, 8) != shard_of(key, 10)).collect::<Vec<_>>();
+        assert!((1_700..2_300).contains(&moved.len()), "{}", moved.len());
+        assert!(moved.iter().all(|key| shard_of(key, 10) >= 8));
+        // Shrinking moves only the keys of the removed shards.
+        assert!(keys.clone().filter(|key| shard_of(key, 10) < 8).all(|key| shard_of(&key, 10) == shard_of(&key, 8)));
+        // Keys are spread evenly.
+        let mut counts = [0; 10];
+        for key in keys { counts[shard_of(&key, 10)] += 1; }
+        assert!(counts.iter().all(|count| (800..1_200).contains(count)), "{:?}", counts);
+    }
+
+    #[test]
+    fn moved_keys_are_reported_once_per_time() {
+        let (send, recv) = std::sync::mpsc::channel();
+        let send = Arc::new(Mutex::new(send));
+        crate::execute(Config::process(3), move |worker| {
+            let send = send.lock().unwrap().clone();
+            worker.dataflow::<u64,_,_>(|scope| {
+                // Each worker holds every key, twice.
+                (0..200u64).chain(0..200).to_stream(scope).reshard(4, 5, |x| *x).capture_into(send);
+            });
+        }).unwrap();
+
+        let expected = (0..200u64)
+            .filter(|key| shard_of(key, 4) != shard_of(key, 5))
+            .map(|key| (key, shard_of(&key, 4), 4))
+            .collect::<Vec<_>>();
+        assert!(!expected.is_empty());
+        assert_eq!(recv.extract(), vec![(0, expected)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "shard counts must be positive")]
+    fn zero_shards_panic() {
+        crate::example(|scope| {
+            (0..10u64).to_stream(scope).reshard(0, 4, |x| *x);
+        });
+    }
+}
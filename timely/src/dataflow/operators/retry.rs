@@ -0,0 +1,183 @@
+//! Extension methods for `Stream` that retry fallible transformations of records.
+
+use std::time::{Duration, Instant};
+
+use crate::Data;
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::Capability;
+use crate::dataflow::operators::generic::OutputBuilder;
+use crate::dataflow::operators::generic::builder_rc::OperatorBuilder;
+use crate::dataflow::{Scope, Stream};
+
+/// Extension trait for `Stream`.
+pub trait MapRetry<S: Scope, D: Data> {
+    /// Transforms each record with the fallible `logic`, attempting each record up to `attempts` times.
+    ///
+    /// Records for which `logic` succeeds are transformed into the first returned stream. A record
+    /// for which `logic` fails is attempted again once `backoff` has elapsed, and once `attempts`
+    /// attempts have failed the record and the last error are sent to the second returned stream,
+    /// to be handled as permanent failures. Each attempt receives a copy of the record.
+    ///
+    /// The operator does not block the worker while it waits: it retains the records to retry, and
+    /// capabilities for their times, and asks to be scheduled again once the next retry is due.
+    /// Retained records hold back the frontiers of both outputs until they are resolved.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `attempts` is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use timely::dataflow::operators::{ToStream, MapRetry, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     // Fails the first attempt at each record, and always fails at odd records.
+    ///     let mut attempted = std::collections::HashSet::new();
+    ///     let (enriched, failed) = (0..10u64).to_stream(scope).map_retry(3, Duration::from_millis(1), move |x| {
+    ///         if attempted.insert(x) || x % 2 == 1 { Err(format!("unavailable: {}", x)) } else { Ok(x * 10) }
+    ///     });
+    ///     enriched.inspect(|x| assert_eq!(x % 20, 0));
+    ///     failed.inspect(|(x, error)| println!("gave up on {}: {}", x, error));
+    /// });
+    /// ```
+    fn map_retry<D2, E, L>(&self, attempts: usize, backoff: Duration, logic: L) -> (Stream<S, D2>, Stream<S, (D, E)>)
+    where
+        D2: Data,
+        E: Data,
+        L: FnMut(D)->Result<D2, E>+'static;
+}
+
+/// A record awaiting another attempt.
+struct Retry<T: crate::progress::Timestamp, D> {
+    /// Capabilities for the record's time, for the transformed records and for the failures.
+    capabilities: (Capability<T>, Capability<T>),
+    datum: D,
+    /// The number of attempts left.
+    remaining: usize,
+    /// The moment at which to attempt the record again.
+    due: Instant,
+}
+
+impl<S: Scope, D: Data> MapRetry<S, D> for Stream<S, D> {
+    fn map_retry<D2, E, L>(&self, attempts: usize, backoff: Duration, mut logic: L) -> (Stream<S, D2>, Stream<S, (D, E)>)
+    where
+        D2: Data,
+        E: Data,
+        L: FnMut(D)->Result<D2, E>+'static,
+    {
+        assert!(attempts > 0, "MapRetry: attempts must be positive");
+
+        let mut builder = OperatorBuilder::new("MapRetry".to_owned(), self.scope());
+        let activator = self.scope().activator_for(builder.operator_info().address);
+
+        let mut input = builder.new_input(self, Pipeline);
+        let (output1, stream1) = builder.new_output();
+        let (output2, stream2) = builder.new_output();
+
+        let mut output1 = OutputBuilder::from(output1);
+        let mut output2 = OutputBuilder::from(output2);
+
+        let mut retries: Vec<Retry<S::Timestamp, D>> = Vec::new();
+
+        builder.build(move |_| {
+            move |_frontiers| {
+                let mut output1_handle = output1.activate();
+                let mut output2_handle = output2.activate();
+                let now = Instant::now();
+
+                input.for_each_time(|time, data| {
+                    let mut succeeded = output1_handle.session(&time);
+                    let mut failed = output2_handle.session(&time);
+                    for datum in data.flat_map(|d| d.drain(..)) {
+                        match logic(datum.clone()) {
+                            Ok(result) => succeeded.give(result),
+                            Err(error) if attempts == 1 => failed.give((datum, error)),
+                            Err(_) => retries.push(Retry {
+                                capabilities: (time.delayed_for_output(time.time(), 0), time.delayed_for_output(time.time(), 1)),
+                                datum,
+                                remaining: attempts - 1,
+                                due: now + backoff,
+                            }),
+                        }
+                    }
+                });
+
+                retries.retain_mut(|retry| {
+                    if retry.due > now {
+                        return true;
+                    }
+                    match logic(retry.datum.clone()) {
+                        Ok(result) => {
+                            output1_handle.session(&retry.capabilities.0).give(result);
+                            false
+                        },
+                        Err(error) if retry.remaining == 1 => {
+                            output2_handle.session(&retry.capabilities.1).give((retry.datum.clone(), error));
+                            false
+                        },
+                        Err(_) => {
+                            retry.remaining -= 1;
+                            retry.due = now + backoff;
+                            true
+                        },
+                    }
+                });
+
+                if let Some(due) = retries.iter().map(|retry| retry.due).min() {
+                    activator.activate_after(due.saturating_duration_since(now));
+                }
+            }
+        });
+
+        (stream1, stream2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    use crate::dataflow::operators::{Input, Inspect, Probe};
+    use super::MapRetry;
+
+    #[test]
+    fn retries_until_success_or_attempts_exhausted() {
+        crate::execute_directly(|worker| {
+            let results = Rc::new(RefCell::new(Vec::new()));
+            let results_inner = Rc::clone(&results);
+            let failures = Rc::new(RefCell::new(Vec::new()));
+            let failures_inner = Rc::clone(&failures);
+            let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<u64>();
+                // Record `x` fails its first `x` attempts.
+                let mut attempted = HashMap::<u64, u64>::new();
+                let (succeeded, failed) = stream.map_retry(3, Duration::from_millis(5), move |x| {
+                    let count = attempted.entry(x).or_default();
+                    *count += 1;
+                    if *count > x { Ok(x) } else { Err(*count) }
+                });
+                let probe = succeeded.inspect_time(move |time, x| results_inner.borrow_mut().push((*time, *x))).probe();
+                failed.inspect_time(move |time, x| failures_inner.borrow_mut().push((*time, *x))).probe_with(&probe);
+                (input, probe)
+            });
+
+            for x in 0..5 { input.send(x); }
+            input.advance_to(1);
+            // The records to retry hold back the output until they are resolved.
+            worker.step();
+            assert!(probe.less_than(&1));
+            worker.step_while(|| probe.less_than(&1));
+
+            let mut results = results.borrow().clone();
+            results.sort();
+            assert_eq!(results, vec![(0, 0), (0, 1), (0, 2)]);
+            let mut failures = failures.borrow().clone();
+            failures.sort();
+            assert_eq!(failures, vec![(0, (3, 3)), (0, (4, 3))]);
+        });
+    }
+}
@@ -0,0 +1,264 @@
+//! Extension methods for `Stream` that maintain the top records seen across all times.
+
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+use crate::ExchangeData;
+use crate::dataflow::{Scope, Stream};
+use crate::dataflow::channels::pact::{Exchange, Pipeline};
+use crate::dataflow::operators::Capability;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::generic::stash::Stash;
+
+/// Extension trait for `Stream`.
+pub trait RunningTopK<G: Scope, D: ExchangeData> {
+    /// Maintains the `k` greatest records by `cmp` seen at any time, and produces them whenever they change.
+    ///
+    /// Unlike per-time aggregations, the leaderboard persists across times. Each time a batch of
+    /// records changes it, the first worker produces the current leaderboard at the time of the
+    /// batch, greatest record first. Records equal by `cmp` to the least record of a full leaderboard
+    /// do not displace it. Records of different times are applied in the order they arrive.
+    ///
+    /// Each worker forwards to the first worker only the records that enter its own leaderboard of
+    /// `k` records, which are the only records that can enter the global leaderboard, and so each
+    /// worker retains at most `k` records.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, RunningTopK, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     vec![3, 1, 4, 1, 5, 9, 2, 6]
+    ///         .to_stream(scope)
+    ///         .running_top_k(|x, y| x.cmp(y), 3)
+    ///         .inspect(|leaders| println!("leaders: {:?}", leaders));
+    /// });
+    /// ```
+    fn running_top_k<F>(&self, cmp: F, k: usize) -> Stream<G, Vec<D>>
+    where
+        F: Fn(&D, &D)->Ordering+'static;
+
+    /// Maintains the `k` greatest records by `cmp` seen at any time, and produces them at most once per frontier advance.
+    ///
+    /// Behaves as [`RunningTopK::running_top_k`], but rather than producing the leaderboard for
+    /// each batch that changes it, the first worker applies the records of each time once its input
+    /// frontier has passed the time, in the order of the times. If the times completed by a frontier
+    /// advance change the leaderboard, it is produced once, at the greatest of them with records
+    /// that entered the leaderboard of their worker. The records of incomplete times are retained,
+    /// at most `k` for each time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, RunningTopK, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     vec![3, 1, 4, 1, 5, 9, 2, 6]
+    ///         .to_stream(scope)
+    ///         .running_top_k_debounced(|x, y| x.cmp(y), 3)
+    ///         .inspect(|leaders| assert_eq!(leaders, &vec![9, 6, 5]));
+    /// });
+    /// ```
+    fn running_top_k_debounced<F>(&self, cmp: F, k: usize) -> Stream<G, Vec<D>>
+    where
+        F: Fn(&D, &D)->Ordering+'static;
+}
+
+impl<G: Scope, D: ExchangeData> RunningTopK<G, D> for Stream<G, D> {
+    fn running_top_k<F>(&self, cmp: F, k: usize) -> Stream<G, Vec<D>>
+    where
+        F: Fn(&D, &D)->Ordering+'static,
+    {
+        let cmp = Rc::new(cmp);
+        let cmp_leaders = Rc::clone(&cmp);
+        let mut leaders = Vec::with_capacity(k);
+        local_leaders(self, cmp, k).unary(Exchange::new(|_| 0), "RunningTopK", move |_capability, _info| {
+            move |input, output| {
+                input.for_each_time(|time, data| {
+                    let mut changed = false;
+                    for datum in data.flat_map(|d| d.drain(..)) {
+                        changed |= offer(&mut leaders, datum, k, &*cmp_leaders);
+                    }
+                    if changed {
+                        output.session(&time).give(leaders.clone());
+                    }
+                });
+            }
+        })
+    }
+
+    fn running_top_k_debounced<F>(&self, cmp: F, k: usize) -> Stream<G, Vec<D>>
+    where
+        F: Fn(&D, &D)->Ordering+'static,
+    {
+        let cmp = Rc::new(cmp);
+        let cmp_leaders = Rc::clone(&cmp);
+        let mut leaders = Vec::with_capacity(k);
+        // For each incomplete time, a capability for it and the leaderboard of its records alone.
+        let mut pending = Stash::<Capability<G::Timestamp>, Vec<D>>::new();
+        local_leaders(self, cmp, k).unary_frontier(Exchange::new(|_| 0), "RunningTopKDebounced", move |_capability, _info| {
+            move |(input, frontier), output| {
+                input.for_each_time(|time, data| {
+                    let candidates = pending.get_or_retain(&time, || Vec::with_capacity(k));
+                    for datum in data.flat_map(|d| d.drain(..)) {
+                        offer(candidates, datum, k, &*cmp_leaders);
+                    }
+                });
+
+                let mut changed = false;
+                let mut latest = None;
+                pending.release(|time| !frontier.less_equal(time), |capability, candidates| {
+                    for candidate in candidates {
+                        changed |= offer(&mut leaders, candidate, k, &*cmp_leaders);
+                    }
+                    latest = Some(capability);
+                });
+                if let Some(capability) = latest.filter(|_| changed) {
+                    output.session(&capability).give(leaders.clone());
+                }
+            }
+        })
+    }
+}
+
+/// Passes on the records of `stream` that enter the leaderboard of `k` records of the worker.
+fn local_leaders<G: Scope, D: ExchangeData, F>(stream: &Stream<G, D>, cmp: Rc<F>, k: usize) -> Stream<G, D>
+where
+    F: Fn(&D, &D)->Ordering+'static,
+{
+    assert!(k > 0, "RunningTopK: k must be positive");
+    let mut leaders = Vec::with_capacity(k);
+    stream.unary(Pipeline, "RunningTopKLocal", move |_capability, _info| {
+        move |input, output| {
+            input.for_each_time(|time, data| {
+                let mut session = output.session(&time);
+                for datum in data.flat_map(|d| d.drain(..)) {
+                    if offer(&mut leaders, datum.clone(), k, &*cmp) {
+                        session.give(datum);
+                    }
+                }
+            });
+        }
+    })
+}
+
+/// Inserts `record` into `leaders`, sorted greatest first, if it is among the `k` greatest, and reports whether it was.
+fn offer<D, F: Fn(&D, &D)->Ordering>(leaders: &mut Vec<D>, record: D, k: usize, cmp: &F) -> bool {
+    if leaders.len() == k && leaders.last().is_some_and(|least| cmp(&record, least) != Ordering::Greater) {
+        return false;
+    }
+    let position = leaders.partition_point(|leader| cmp(leader, &record) != Ordering::Less);
+    leaders.insert(position, record);
+    leaders.truncate(k);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::Config;
+    use crate::dataflow::operators::{Capture, Input, Probe, ToStream};
+    use crate::dataflow::operators::capture::Extract;
+    use super::RunningTopK;
+
+    /// Runs `rounds` on three workers, each sending its records of a round at the round's time,
+    /// and returns the leaderboards of `k` records produced at each time.
+    fn leaderboards(rounds: Vec<Vec<u64>>, k: usize, debounced: bool) -> Vec<(u64, Vec<Vec<u64>>)> {
+        let (send, recv) = std::sync::mpsc::channel();
+        let send = Arc::new(Mutex::new(send));
+        crate::execute(Config::process(3), move |worker| {
+            let send = send.lock().unwrap().clone();
+            let index = worker.index() as u64;
+            let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<u64>();
+                let boards = if debounced { stream.running_top_k_debounced(|x, y| x.cmp(y), k) } else { stream.running_top_k(|x, y| x.cmp(y), k) };
+                let probe = boards.probe();
+                boards.capture_into(send);
+                (input, probe)
+            });
+            for (round, records) in rounds.iter().enumerate() {
+                // Workers send the records of each round in turn.
+                for record in records.iter().filter(|record| *record % 3 == index) { input.send(*record); }
+                input.advance_to(round as u64 + 1);
+                worker.step_while(|| probe.less_than(input.time()));
+            }
+        }).unwrap();
+        recv.extract()
+    }
+
+    #[test]
+    fn leaderboard_persists_across_times() {
+        let produced = leaderboards(vec![vec![10, 4, 8], vec![1, 2], vec![9, 12, 3], vec![8]], 3, true);
+        // No change at times 1 and 3, whose records all rank below the leaderboard.
+        assert_eq!(produced, vec![(0, vec![vec![10, 8, 4]]), (2, vec![vec![12, 10, 9]])]);
+    }
+
+    #[test]
+    fn leaderboards_may_hold_fewer_than_k_records() {
+        let produced = leaderboards(vec![vec![2], vec![], vec![1, 3]], 5, true);
+        assert_eq!(produced, vec![(0, vec![vec![2]]), (2, vec![vec![3, 2, 1]])]);
+    }
+
+    #[test]
+    fn eager_leaderboard_ends_with_the_top_records() {
+        let produced = leaderboards(vec![vec![5, 7, 6], vec![20, 1], vec![0, 15, 16, 17]], 3, false);
+        let boards = produced.iter().flat_map(|(_, boards)| boards.iter()).collect::<Vec<_>>();
+        assert!(boards.iter().all(|leaders| leaders.len() <= 3 && leaders.windows(2).all(|pair| pair[0] > pair[1])));
+        // The record 1 at time 1 does not change the leaderboard, and 20 changes it once.
+        assert_eq!(produced[1], (1, vec![vec![20, 7, 6]]));
+        assert!(produced[2].1.contains(&vec![20, 17, 16]));
+        assert!(produced[2].1.iter().all(|leaders| leaders <= &vec![20, 17, 16]));
+    }
+
+    #[test]
+    fn completed_times_produce_one_leaderboard() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<(u64, char)>();
+                let boards = stream.running_top_k_debounced(|x, y| x.0.cmp(&y.0), 1);
+                (input, boards.probe(), boards.capture())
+            });
+            // Three times complete in one frontier advance.
+            input.send((1, 'a'));
+            input.advance_to(1);
+            input.send((3, 'b'));
+            input.advance_to(2);
+            // A later record equal to the leader does not displace it, nor is it forwarded.
+            input.send((3, 'c'));
+            input.advance_to(3);
+            worker.step_while(|| probe.less_than(input.time()));
+            input.send((2, 'd'));
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        // The leaderboard is produced once, at the latest of the times whose records were forwarded.
+        assert_eq!(captured.extract(), vec![(1, vec![vec![(3, 'b')]])]);
+    }
+
+    #[test]
+    #[should_panic(expected = "RunningTopK: k must be positive")]
+    fn empty_leaderboards_are_rejected() {
+        crate::example(|scope| {
+            vec![0u64].to_stream(scope).running_top_k(|x, y| x.cmp(y), 0);
+        });
+    }
+
+    #[test]
+    fn ties_do_not_displace_leaders() {
+        let mut leaders = Vec::new();
+        for record in [(5, 'a'), (5, 'b'), (3, 'c'), (5, 'd')] {
+            super::offer(&mut leaders, record, 2, &|x: &(u64, char), y: &(u64, char)| x.0.cmp(&y.0));
+        }
+        assert_eq!(leaders, vec![(5, 'a'), (5, 'b')]);
+    }
+}
@@ -0,0 +1,225 @@
+//! Extension methods for `Stream` that aggregate the records of each key by session.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::rc::Rc;
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Exchange;
+use crate::dataflow::operators::Capability;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::sketch::hash_of;
+use crate::order::{PartialOrder, TotalOrder};
+use crate::progress::{PathSummary, Timestamp};
+
+/// A session of one key: the times of its first and last records, and their aggregate.
+struct Session<T: Timestamp, A> {
+    /// A capability for the time of the first record.
+    capability: Capability<T>,
+    last: T,
+    aggregate: A,
+}
+
+/// Extension trait for `Stream`.
+pub trait SessionAggregate<G: Scope, D: ExchangeData> {
+    /// Aggregates the records of each key of `key_fn` by session, and produces the result of `emit` for each session once it closes.
+    ///
+    /// The records of a key form sessions: two records are in the same session if their times, or
+    /// those of a chain of records between them, are at most `gap` apart. Each session has an
+    /// aggregate, which starts as `init()` and to which `add` adds each record of the session. Once
+    /// the input frontier has passed the time of the session's last record advanced by `gap`, no
+    /// record can join the session, which closes and produces `emit(key, aggregate)` at the time of
+    /// its last record.
+    ///
+    /// Records may arrive in any order of their times. A record within `gap` of an open session
+    /// joins it, and a record within `gap` of two sessions bridges them: the aggregate of the later
+    /// session is merged into that of the earlier with `merge`, and the record is then added. A
+    /// session's aggregate is thus assembled from its records in an order that depends on their
+    /// arrival, and `add` and `merge` should agree, as for counts, sums, and sets, so that its
+    /// result does not. The sessions of a time-ordered stream never merge.
+    ///
+    /// Records are exchanged by key, and each worker retains the aggregate of each open session of
+    /// its keys, and a capability for the time of its first record, which holds back the output
+    /// frontier until the session closes. A `gap` that cannot be added to a time never closes the
+    /// sessions that end there, until the input completes.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Delay, SessionAggregate, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     // User 'a' acts at times 0, 2, and 3, and again at 10.
+    ///     vec![('a', 0), ('a', 2), ('a', 3), ('a', 10), ('b', 1)]
+    ///         .to_stream(scope)
+    ///         .delay(|(_, time), _| *time)
+    ///         .session_aggregate(|(user, _)| *user, 5, || 0, |count, _| *count += 1, |count, other| *count += other, |user, count| (*user, count))
+    ///         .inspect_time(|time, x| println!("session of {:?} ending at {:?}", x, time));
+    /// });
+    /// ```
+    fn session_aggregate<K, A, R, F, I, Add, M, E>(&self, key_fn: F, gap: <G::Timestamp as Timestamp>::Summary, init: I, add: Add, merge: M, emit: E) -> Stream<G, R>
+    where
+        G::Timestamp: TotalOrder,
+        K: Hash+Eq+'static,
+        A: 'static,
+        R: Data,
+        F: Fn(&D)->K+'static,
+        I: FnMut()->A+'static,
+        Add: FnMut(&mut A, D)+'static,
+        M: FnMut(&mut A, A)+'static,
+        E: FnMut(&K, A)->R+'static;
+}
+
+impl<G: Scope, D: ExchangeData> SessionAggregate<G, D> for Stream<G, D> {
+    fn session_aggregate<K, A, R, F, I, Add, M, E>(&self, key_fn: F, gap: <G::Timestamp as Timestamp>::Summary, mut init: I, mut add: Add, mut merge: M, mut emit: E) -> Stream<G, R>
+    where
+        G::Timestamp: TotalOrder,
+        K: Hash+Eq+'static,
+        A: 'static,
+        R: Data,
+        F: Fn(&D)->K+'static,
+        I: FnMut()->A+'static,
+        Add: FnMut(&mut A, D)+'static,
+        M: FnMut(&mut A, A)+'static,
+        E: FnMut(&K, A)->R+'static,
+    {
+        let key_fn = Rc::new(key_fn);
+        let route = Rc::clone(&key_fn);
+        let pact = Exchange::new(move |datum: &D| hash_of(&route(datum)));
+        // Whether `later` is within `gap` of `earlier`; a gap that cannot be added to `earlier` is never exceeded.
+        let within = move |earlier: &G::Timestamp, later: &G::Timestamp| gap.results_in(earlier).is_none_or(|limit| !limit.less_than(later));
+
+        self.unary_frontier(pact, "SessionAggregate", move |_capability, _info| {
+            // For each key, its open sessions, in order of time.
+            let mut sessions = HashMap::<K, Vec<Session<G::Timestamp, A>>>::new();
+            move |(input, frontier), output| {
+                input.for_each_time(|time, data| {
+                    for datum in data.flat_map(|d| d.drain(..)) {
+                        let open = sessions.entry(key_fn(&datum)).or_default();
+                        // The sessions the record joins, those within `gap` of its time, are adjacent.
+                        let first = open.partition_point(|session| session.last < *time.time() && !within(&session.last, time.time()));
+                        let count = open[first..].iter().take_while(|session| within(time.time(), session.capability.time())).count();
+                        let mut joined = open.drain(first .. first + count);
+                        let mut session = match joined.next() {
+                            Some(mut session) => {
+                                for later in joined {
+                                    merge(&mut session.aggregate, later.aggregate);
+                                    session.last = later.last;
+                                }
+                                if time.time() < session.capability.time() {
+                                    session.capability = time.delayed(time.time());
+                                }
+                                session
+                            }
+                            None => {
+                                drop(joined);
+                                Session { capability: time.delayed(time.time()), last: time.time().clone(), aggregate: init() }
+                            }
+                        };
+                        if session.last < *time.time() {
+                            session.last = time.time().clone();
+                        }
+                        add(&mut session.aggregate, datum);
+                        open.insert(first, session);
+                    }
+                });
+
+                // Close the sessions no further record can join.
+                sessions.retain(|key, open| {
+                    let closed = open.iter().take_while(|session| frontier.frontier().iter().all(|time| !within(&session.last, time))).count();
+                    for session in open.drain(..closed) {
+                        output.session(&session.capability.delayed(&session.last)).give(emit(key, session.aggregate));
+                    }
+                    !open.is_empty()
+                });
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::Config;
+    use crate::dataflow::operators::{Capture, Input, Probe, SessionAggregate, UnorderedInput};
+    use crate::dataflow::operators::capture::{Event, Extract};
+
+    #[test]
+    fn sessions_close_once_the_gap_has_passed() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<(char, u64)>();
+                let totals = stream.session_aggregate(|(key, _)| *key, 2, || 0, |total, (_, value)| *total += value, |total, other| *total += other, |key, total| (*key, total));
+                // Open sessions hold back the output frontier, and so the input is probed instead.
+                (input, stream.probe(), totals.capture())
+            });
+            for time in 0..12u64 {
+                input.advance_to(time);
+                // Gaps of exactly two continue a session, gaps of three start the next.
+                if [0, 2, 4, 7, 8].contains(&time) { input.send(('a', time)); }
+                if time == 3 { input.send(('b', 100)); input.send(('b', 200)); }
+                worker.step_while(|| probe.less_than(input.time()));
+                // The first session of 'a' closes only once the frontier passes 4 + 2.
+                if time == 6 {
+                    assert!(captured.try_iter().all(|event| match event {
+                        Event::Messages(_time, data) => data == vec![('b', 300)],
+                        Event::Progress(_) => true,
+                    }));
+                }
+            }
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        assert_eq!(captured.extract(), vec![(4, vec![('a', 6)]), (8, vec![('a', 15)])]);
+    }
+
+    #[test]
+    fn bridging_records_merge_sessions() {
+        let captured = crate::execute_directly(|worker| {
+            let ((mut input, capability), captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_unordered_input::<u64>();
+                let sessions = stream.session_aggregate(|_| (), 5, Vec::new, |times, time| times.push(time), |times, mut other| times.append(&mut other), |_, times| times);
+                (input, sessions.capture())
+            });
+            // Two sessions, from 0 to 2 and of 10, which the record at 6 bridges.
+            for time in [2, 0, 10] {
+                input.activate().session(&capability.delayed(&time)).give(time);
+                worker.step();
+            }
+            input.activate().session(&capability.delayed(&6)).give(6);
+            worker.step();
+            // A record within the merged session joins it.
+            input.activate().session(&capability.delayed(&1)).give(20);
+            drop(capability);
+            captured
+        });
+
+        // The later session's aggregate is merged into the earlier's, and the bridging record added.
+        assert_eq!(captured.extract(), vec![(10, vec![vec![2, 0, 10, 6, 20]])]);
+    }
+
+    #[test]
+    fn sessions_are_kept_per_key_across_workers() {
+        let (send, recv) = std::sync::mpsc::channel();
+        let send = Arc::new(Mutex::new(send));
+        crate::execute(Config::process(3), move |worker| {
+            let send = send.lock().unwrap().clone();
+            let index = worker.index() as u64;
+            worker.dataflow::<u64,_,_>(|scope| {
+                let (mut input, stream) = scope.new_input::<(char, u64)>();
+                stream.session_aggregate(|(key, _)| *key, 1, || 0, |count, _| *count += 1, |count, other| *count += other, |key, count| (*key, count))
+                      .capture_into(send);
+                // The workers each send a record of 'a' and of 'b' at their own times.
+                input.advance_to(index);
+                input.send(('a', index));
+                input.advance_to(index * 3);
+                input.send(('b', index));
+            });
+        }).unwrap();
+
+        assert_eq!(recv.extract(), vec![(0, vec![('b', 1)]), (2, vec![('a', 3)]), (3, vec![('b', 1)]), (6, vec![('b', 1)])]);
+    }
+}
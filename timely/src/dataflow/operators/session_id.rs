@@ -0,0 +1,181 @@
+//! Extension methods for `Stream` that number the sessions of records with equal keys.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::rc::Rc;
+
+use crate::ExchangeData;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Exchange;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::sketch::hash_of;
+use crate::order::{PartialOrder, TotalOrder};
+use crate::progress::{PathSummary, Timestamp};
+
+/// Extension trait for `Stream`.
+pub trait AssignSessionId<G: Scope, D: ExchangeData> {
+    /// Pairs each record with the number of its session among the records with its key.
+    ///
+    /// The records of a key form a session until one arrives at a time later than the previous
+    /// record of the key advanced by `gap`, which starts the next session. Sessions of each key are
+    /// numbered from zero, and each record is produced at its time with the number of its session.
+    /// Unlike session windows, records are not buffered or aggregated, and downstream operators
+    /// can group them by key and session.
+    ///
+    /// Records are exchanged by key, and applied in the order they arrive, which assumes that the
+    /// records of each key arrive roughly in the order of their times. A record that arrives after a
+    /// record of its key at a later time joins the current session, and so records out of order by
+    /// more than `gap` may be assigned inconsistent sessions; streams that are not in order should be
+    /// re-stamped, for example by event time, first. Each worker retains the time of the latest record
+    /// and the current session of each key it has seen.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, AssignSessionId, Delay, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     vec![0, 1, 2, 10, 11, 20]
+    ///         .to_stream(scope)
+    ///         .delay(|x, _| *x)
+    ///         .assign_session_id(|_| "user", 3)
+    ///         .inspect(|(x, session)| assert_eq!(*session, x / 10));
+    /// });
+    /// ```
+    fn assign_session_id<K, F>(&self, key_fn: F, gap: <G::Timestamp as Timestamp>::Summary) -> Stream<G, (D, u64)>
+    where
+        G::Timestamp: TotalOrder,
+        K: Hash+Eq+'static,
+        F: Fn(&D)->K+'static;
+}
+
+impl<G: Scope, D: ExchangeData> AssignSessionId<G, D> for Stream<G, D> {
+    fn assign_session_id<K, F>(&self, key_fn: F, gap: <G::Timestamp as Timestamp>::Summary) -> Stream<G, (D, u64)>
+    where
+        G::Timestamp: TotalOrder,
+        K: Hash+Eq+'static,
+        F: Fn(&D)->K+'static,
+    {
+        let key_fn = Rc::new(key_fn);
+        let route = Rc::clone(&key_fn);
+        let pact = Exchange::new(move |datum: &D| hash_of(&route(datum)));
+
+        // For each key, the time of its latest record and its current session.
+        let mut sessions = HashMap::<K, (G::Timestamp, u64)>::new();
+        self.unary(pact, "AssignSessionId", move |_capability, _info| {
+            move |input, output| {
+                input.for_each_time(|time, data| {
+                    let mut session = output.session(&time);
+                    for datum in data.flat_map(|d| d.drain(..)) {
+                        let key = key_fn(&datum);
+                        let number = match sessions.get_mut(&key) {
+                            Some((latest, number)) => {
+                                // A gap that cannot be added to the latest time is never exceeded.
+                                if gap.results_in(latest).is_some_and(|limit| limit.less_than(time.time())) {
+                                    *number += 1;
+                                }
+                                if latest.less_than(time.time()) {
+                                    *latest = time.time().clone();
+                                }
+                                *number
+                            },
+                            None => {
+                                sessions.insert(key, (time.time().clone(), 0));
+                                0
+                            },
+                        };
+                        session.give((datum, number));
+                    }
+                });
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::Config;
+    use crate::dataflow::operators::{Capture, Input, Probe, UnorderedInput};
+    use crate::dataflow::operators::capture::Extract;
+    use super::AssignSessionId;
+
+    #[test]
+    fn gaps_beyond_the_limit_start_sessions() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<(u64, char)>();
+                let sessions = stream.assign_session_id(|record| record.1, 2);
+                (input, sessions.probe(), sessions.capture())
+            });
+            for round in 0..12u64 {
+                // Gaps of exactly two continue a session, gaps of three start the next.
+                if [0, 2, 4, 7, 8, 11].contains(&round) { input.send((round, 'a')); }
+                // Records of another key, and several at one time, share a session.
+                if round == 3 { input.send((round, 'b')); input.send((round, 'b')); }
+                if round == 5 { input.send((round, 'b')); }
+                input.advance_to(round + 1);
+                worker.step_while(|| probe.less_than(input.time()));
+            }
+            captured
+        });
+
+        assert_eq!(captured.extract(), vec![
+            (0, vec![((0, 'a'), 0)]),
+            (2, vec![((2, 'a'), 0)]),
+            (3, vec![((3, 'b'), 0), ((3, 'b'), 0)]),
+            (4, vec![((4, 'a'), 0)]),
+            (5, vec![((5, 'b'), 0)]),
+            (7, vec![((7, 'a'), 1)]),
+            (8, vec![((8, 'a'), 1)]),
+            (11, vec![((11, 'a'), 2)]),
+        ]);
+    }
+
+    #[test]
+    fn late_records_join_the_current_session() {
+        let captured = crate::execute_directly(|worker| {
+            let ((mut input, capability), captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_unordered_input::<u64>();
+                (input, stream.assign_session_id(|_| (), 3).capture())
+            });
+            input.activate().session(&capability).give(0);
+            worker.step();
+            input.activate().session(&capability.delayed(&10)).give(10);
+            worker.step();
+            // The record at time 1 arrives after that at time 10.
+            input.activate().session(&capability.delayed(&1)).give(1);
+            drop(capability);
+            captured
+        });
+
+        assert_eq!(captured.extract(), vec![(0, vec![(0, 0)]), (1, vec![(1, 1)]), (10, vec![(10, 1)])]);
+    }
+
+    #[test]
+    fn sessions_are_kept_per_key_across_workers() {
+        let (send, recv) = std::sync::mpsc::channel();
+        let send = Arc::new(Mutex::new(send));
+        crate::execute(Config::process(3), move |worker| {
+            let send = send.lock().unwrap().clone();
+            let index = worker.index() as u64;
+            let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<(u64, char)>();
+                let sessions = stream.assign_session_id(|record| record.1, 2);
+                let probe = sessions.probe();
+                sessions.capture_into(send);
+                (input, probe)
+            });
+            // The workers take turns sending the records of one key.
+            for round in 0..6u64 {
+                input.advance_to(round * 2);
+                if round % 3 == index { input.send((round * 2, 'a')); }
+                input.advance_to(round * 2 + 1);
+                worker.step_while(|| probe.less_than(input.time()));
+            }
+        }).unwrap();
+
+        // Gaps of exactly two continue the one session, whichever worker sends each record.
+        assert_eq!(recv.extract(), (0..6u64).map(|round| (round * 2, vec![((round * 2, 'a'), 0)])).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,150 @@
+//! Operators that shed load while the memory of the worker exceeds its limit.
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::MemoryLimit;
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// Extension trait for `Stream`.
+pub trait ShedLoad<G: Scope, D: Data> {
+    /// Passes records on, and drops them instead while the worker's [`MemoryLimit`] is exceeded.
+    ///
+    /// The operator is a valve that the worker closes under memory pressure: each time it is
+    /// scheduled it checks whether the memory operators report exceeds the worker's limit, and if so
+    /// drops all records it receives in that invocation, counting them in the returned [`ShedCount`]
+    /// and in the limit's own count. Records pass through unchanged otherwise, and always if no
+    /// limit was enabled for the worker, with [`MemoryLimit::enable`], when the operator was built.
+    ///
+    /// Shedding is opt in for each stream, and suits streams whose records may be lost without harm,
+    /// for example samples or best-effort metrics, ahead of the operators whose memory the limit
+    /// protects. The operator holds no capabilities, and so never holds back the frontier.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{Input, Inspect, Probe, ShedLoad};
+    /// use timely::dataflow::operators::generic::MemoryLimit;
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     let limit = MemoryLimit::enable(worker, 1 << 30);
+    ///     let (mut input, probe, shed) = worker.dataflow::<u64,_,_>(|scope| {
+    ///         let (input, stream) = scope.new_input::<u64>();
+    ///         let (kept, shed) = stream.shed_load();
+    ///         (input, kept.inspect(|x| println!("kept: {}", x)).probe(), shed)
+    ///     });
+    ///     input.send(0);
+    ///     input.advance_to(1);
+    ///     worker.step_while(|| probe.less_than(&1));
+    ///     // No operator reports memory, and so no record is shed.
+    ///     assert_eq!((shed.get(), limit.shed()), (0, 0));
+    /// });
+    /// ```
+    fn shed_load(&self) -> (Stream<G, D>, ShedCount);
+}
+
+impl<G: Scope, D: Data> ShedLoad<G, D> for Stream<G, D> {
+    fn shed_load(&self) -> (Stream<G, D>, ShedCount) {
+        let limit = MemoryLimit::get(&self.scope());
+        let count = ShedCount { shed: Rc::new(Cell::new(0)) };
+        let shed = Rc::clone(&count.shed);
+        let stream = self.unary(Pipeline, "ShedLoad", move |_capability, _info| move |input, output| {
+            let shedding = limit.as_ref().filter(|limit| limit.exceeded());
+            input.for_each_time(|time, data| {
+                match shedding {
+                    Some(exceeded) => {
+                        let records = data.map(|records| records.len() as u64).sum::<u64>();
+                        shed.set(shed.get() + records);
+                        exceeded.record_shed(records);
+                    }
+                    None => {
+                        let mut session = output.session(&time);
+                        for container in data { session.give_container(container); }
+                    }
+                }
+            });
+        });
+        (stream, count)
+    }
+}
+
+/// The number of records dropped by [`ShedLoad::shed_load`].
+#[derive(Clone, Debug)]
+pub struct ShedCount {
+    shed: Rc<Cell<u64>>,
+}
+
+impl ShedCount {
+    /// The number of records shed so far.
+    pub fn get(&self) -> u64 {
+        self.shed.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use crate::container::CapacityContainerBuilder;
+    use crate::dataflow::channels::pact::Pipeline;
+    use crate::dataflow::operators::{Capture, Input, Operator, Probe, ShedLoad};
+    use crate::dataflow::operators::capture::Extract;
+    use crate::dataflow::operators::generic::MemoryLimit;
+
+    #[test]
+    fn records_are_shed_while_the_limit_is_exceeded() {
+        let (shed, total, captured) = crate::execute_directly(|worker| {
+            let limit = MemoryLimit::enable(worker, 100);
+            // The memory an operator reports, set from outside the dataflow, and reported as its input frontier advances.
+            let reported = Rc::new(Cell::new(0));
+            let reported_inner = Rc::clone(&reported);
+            let (mut input, probe, shed, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<u64>();
+                let (kept, shed) = stream.shed_load();
+                let kept = kept.unary_frontier::<CapacityContainerBuilder<Vec<u64>>,_,_,_>(Pipeline, "Report", move |_capability, info| move |(records, _frontier), output| {
+                    records.for_each_time(|time, data| {
+                        let mut session = output.session(&time);
+                        for container in data { session.give_container(container); }
+                    });
+                    info.memory.report(|| reported_inner.get());
+                });
+                (input, kept.probe(), shed, kept.capture())
+            });
+            for (round, bytes) in [0, 200, 50].into_iter().enumerate() {
+                reported.set(bytes);
+                // Bring the operator to report the memory set, before sending the records of the round.
+                input.advance_to(2 * round as u64 + 1);
+                worker.step_while(|| probe.less_than(input.time()));
+                input.send_batch(&mut vec![10 * round as u64, 10 * round as u64 + 1]);
+                input.advance_to(2 * round as u64 + 2);
+                worker.step_while(|| probe.less_than(input.time()));
+            }
+            input.close();
+            worker.step_while(|| !probe.done());
+            (shed.get(), limit.shed(), captured)
+        });
+
+        assert_eq!((shed, total), (2, 2));
+        assert_eq!(captured.extract(), vec![(1, vec![0, 1]), (5, vec![20, 21])]);
+    }
+
+    #[test]
+    fn records_pass_without_a_limit() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<u64>();
+                let (kept, _shed) = stream.shed_load();
+                (input, kept.probe(), kept.capture())
+            });
+            input.send(7);
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        assert_eq!(captured.extract(), vec![(0, vec![7])]);
+    }
+}
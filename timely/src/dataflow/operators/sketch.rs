@@ -0,0 +1,970 @@
+//! Mergeable summaries of collections of records, used by approximate aggregation operators.
+//!
+//! Each sketch summarizes the records inserted into it in bounded space, and two sketches of the
+//! same configuration can be merged into a sketch of the union of their records. Operators use
+//! this to summarize records locally on each worker, and then exchange and merge the summaries
+//! rather than the records themselves.
+
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::hash::{Hash, Hasher};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::{Exchange, Pipeline};
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// Summarizes the records of each time by key on each worker.
+///
+/// Each worker folds its records at a time into summaries by key with `summarize`, and once the
+/// input frontier has passed the time produces a `(key, summary)` pair for each key.
+pub(crate) fn summarize_local<G, D, K, S, L>(stream: &Stream<G, D>, name: &str, mut summarize: L) -> Stream<G, (K, S)>
+where
+    G: Scope<Timestamp: Hash>,
+    D: Data,
+    K: Data+Hash+Eq,
+    S: Data,
+    L: FnMut(&mut HashMap<K, S>, D)+'static,
+{
+    let mut local = HashMap::<G::Timestamp, HashMap<K, S>>::new();
+    stream.unary_notify(Pipeline, name, vec![], move |input, output, notificator| {
+        input.for_each_time(|time, data| {
+            let summaries = local.entry(time.time().clone()).or_default();
+            for datum in data.flat_map(|d| d.drain(..)) {
+                summarize(summaries, datum);
+            }
+            notificator.notify_at(time.retain());
+        });
+        notificator.for_each(|time, _, _| {
+            if let Some(summaries) = local.remove(time.time()) {
+                output.session(&time).give_iterator(summaries.into_iter());
+            }
+        });
+    })
+}
+
+/// Summarizes the records of each time by key on each worker, and merges the summaries of all workers.
+///
+/// The summaries of [`summarize_local`] are exchanged to the worker `route` selects for their key,
+/// which merges those of the same time and key with `merge`. Once the frontier has passed the time,
+/// that worker produces the result of `finish` for each key. Records themselves are not exchanged.
+pub(crate) fn summarize_by_key<G, D, K, S, R, L, M, F>(stream: &Stream<G, D>, name: &str, route: fn(&K)->u64, summarize: L, mut merge: M, mut finish: F) -> Stream<G, R>
+where
+    G: Scope<Timestamp: Hash>,
+    D: Data,
+    K: ExchangeData+Hash+Eq,
+    S: ExchangeData,
+    R: Data,
+    L: FnMut(&mut HashMap<K, S>, D)+'static,
+    M: FnMut(&mut S, S)+'static,
+    F: FnMut(K, S)->R+'static,
+{
+    let mut merged = HashMap::<G::Timestamp, HashMap<K, S>>::new();
+    summarize_local(stream, &format!("{}Local", name), summarize)
+        .unary_notify(Exchange::new(move |(key, _): &(K, S)| route(key)), &format!("{}Merge", name), vec![], move |input, output, notificator| {
+            input.for_each_time(|time, data| {
+                let summaries = merged.entry(time.time().clone()).or_default();
+                for (key, summary) in data.flat_map(|d| d.drain(..)) {
+                    match summaries.entry(key) {
+                        Entry::Occupied(mut entry) => merge(entry.get_mut(), summary),
+                        Entry::Vacant(entry) => { entry.insert(summary); },
+                    }
+                }
+                notificator.notify_at(time.retain());
+            });
+            notificator.for_each(|time, _, _| {
+                if let Some(summaries) = merged.remove(time.time()) {
+                    output.session(&time).give_iterator(summaries.into_iter().map(|(key, summary)| finish(key, summary)));
+                }
+            });
+        })
+}
+
+/// Hashes `value` with [`StableHasher`].
+///
+/// The hash of a value is the same on all workers, all platforms, and all runs of a program, and
+/// sketches summarized by one run can be merged with those of another. It may change should the
+/// `Hash` implementation of the value change, for example between versions of the Rust standard
+/// library.
+pub(crate) fn hash_of<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = StableHasher::default();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// A hasher with a fixed algorithm, unlike `DefaultHasher`, whose algorithm is unspecified.
+///
+/// The hasher starts from `0x243f6a8885a308d3`, combines each 64-bit word written to it as FxHash
+/// does, rotating and multiplying by `0x517cc1b727220a95`, and finishes with the 64-bit finalizer of
+/// MurmurHash3 so that all bits of the hash depend on all bits written. Integers are written as
+/// little-endian words, and `usize` and `isize` as 64-bit integers, so that the hash does not depend
+/// on the platform.
+#[derive(Clone, Debug)]
+pub(crate) struct StableHasher {
+    hash: u64,
+}
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        StableHasher { hash: 0x243f6a8885a308d3 }
+    }
+}
+
+impl StableHasher {
+    #[inline]
+    fn add(&mut self, word: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(0x517cc1b727220a95);
+    }
+}
+
+impl Hasher for StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        let mut chunks = bytes.chunks_exact(8);
+        for chunk in chunks.by_ref() {
+            self.add(u64::from_le_bytes(chunk.try_into().unwrap()));
+        }
+        let remainder = chunks.remainder();
+        if !remainder.is_empty() {
+            let mut word = [0u8; 8];
+            word[.. remainder.len()].copy_from_slice(remainder);
+            self.add(u64::from_le_bytes(word));
+        }
+        // Distinguishes writes of different lengths, as of the same bytes split differently.
+        self.add(bytes.len() as u64);
+    }
+    fn write_u8(&mut self, i: u8) { self.add(i.into()) }
+    fn write_u16(&mut self, i: u16) { self.add(i.into()) }
+    fn write_u32(&mut self, i: u32) { self.add(i.into()) }
+    fn write_u64(&mut self, i: u64) { self.add(i) }
+    fn write_u128(&mut self, i: u128) {
+        self.add(i as u64);
+        self.add((i >> 64) as u64);
+    }
+    fn write_usize(&mut self, i: usize) { self.add(i as u64) }
+    fn write_i8(&mut self, i: i8) { self.write_u8(i as u8) }
+    fn write_i16(&mut self, i: i16) { self.write_u16(i as u16) }
+    fn write_i32(&mut self, i: i32) { self.write_u32(i as u32) }
+    fn write_i64(&mut self, i: i64) { self.write_u64(i as u64) }
+    fn write_i128(&mut self, i: i128) { self.write_u128(i as u128) }
+    fn write_isize(&mut self, i: isize) { self.add(i as i64 as u64) }
+    fn finish(&self) -> u64 {
+        mix(self.hash)
+    }
+}
+
+/// A HyperLogLog sketch, estimating the number of distinct records inserted.
+///
+/// The sketch maintains `2^precision` one-byte registers. The relative standard error of its
+/// estimate is about `1.04 / sqrt(2^precision)`, for example 1.6% at the precision 12.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::sketch::HyperLogLog;
+///
+/// let mut sketch = HyperLogLog::new(12);
+/// for i in 0..10_000 {
+///     sketch.insert(&(i % 1_000));
+/// }
+/// let estimate = sketch.estimate();
+/// assert!((950..1050).contains(&estimate));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HyperLogLog {
+    precision: u8,
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    /// Allocates an empty sketch with `2^precision` registers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `precision` is not in the range `4 ..= 16`.
+    pub fn new(precision: u8) -> Self {
+        assert!((4..=16).contains(&precision), "HyperLogLog precision must be in 4 ..= 16, found {}", precision);
+        Self { precision, registers: vec![0; 1 << precision] }
+    }
+
+    /// The precision the sketch was allocated with.
+    pub fn precision(&self) -> u8 { self.precision }
+
+    /// Inserts a record into the sketch.
+    #[inline]
+    pub fn insert<T: Hash + ?Sized>(&mut self, record: &T) {
+        self.insert_hash(hash_of(record));
+    }
+
+    /// Inserts a record by its 64 bit hash.
+    ///
+    /// The hash should be uniformly distributed; [`Self::insert`] uses an appropriate hash.
+    #[inline]
+    pub fn insert_hash(&mut self, hash: u64) {
+        let precision = u32::from(self.precision);
+        let index = (hash >> (64 - precision)) as usize;
+        // The rank of the remaining bits: the position of their leftmost one bit.
+        let rest = hash << precision;
+        let rank = (rest.leading_zeros() + 1).min(64 - precision + 1) as u8;
+        if self.registers[index] < rank {
+            self.registers[index] = rank;
+        }
+    }
+
+    /// Merges another sketch into this one, forming a sketch of the union of their records.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sketches have different precisions.
+    pub fn merge(&mut self, other: &Self) {
+        assert_eq!(self.precision, other.precision, "cannot merge HyperLogLog sketches of different precisions");
+        for (mine, theirs) in self.registers.iter_mut().zip(other.registers.iter()) {
+            *mine = (*mine).max(*theirs);
+        }
+    }
+
+    /// Estimates the number of distinct records inserted into the sketch.
+    pub fn estimate(&self) -> u64 {
+        let m = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m),
+        };
+        let sum: f64 = self.registers.iter().map(|r| 2f64.powi(-i32::from(*r))).sum();
+        let raw = alpha * m * m / sum;
+        let zeros = self.registers.iter().filter(|r| **r == 0).count();
+        let estimate = if raw <= 2.5 * m && zeros > 0 {
+            // Small range correction: linear counting over the empty registers.
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        };
+        estimate.round() as u64
+    }
+}
+
+/// A t-digest, estimating quantiles of the values inserted.
+///
+/// The digest summarizes values as weighted centroids, using small centroids near the extreme
+/// quantiles and larger ones near the median. The `compression` parameter bounds the number of
+/// centroids at around `compression`, trading memory for accuracy; values around 100 are typical.
+/// Values are buffered and folded into the centroids in batches.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::sketch::TDigest;
+///
+/// let mut digest = TDigest::new(100.0);
+/// for i in 0..1000 {
+///     digest.insert(f64::from(i));
+/// }
+/// let median = digest.quantile(0.5);
+/// assert!((490.0..510.0).contains(&median));
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TDigest {
+    compression: f64,
+    /// Centroids `(mean, weight)`, sorted by mean.
+    centroids: Vec<(f64, f64)>,
+    /// Values and centroids not yet folded into `centroids`.
+    unmerged: Vec<(f64, f64)>,
+    /// The total weight of `centroids` and `unmerged`.
+    total: f64,
+    min: f64,
+    max: f64,
+}
+
+impl TDigest {
+    /// Allocates an empty digest with the given compression.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `compression` is not a finite number of at least one.
+    pub fn new(compression: f64) -> Self {
+        assert!(compression.is_finite() && compression >= 1.0, "t-digest compression must be a finite number of at least one, found {}", compression);
+        Self {
+            compression,
+            centroids: Vec::new(),
+            unmerged: Vec::new(),
+            total: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    /// The compression the digest was allocated with.
+    pub fn compression(&self) -> f64 { self.compression }
+
+    /// The number of values inserted into the digest.
+    pub fn count(&self) -> f64 { self.total }
+
+    /// Inserts a value into the digest. NaN values are ignored.
+    #[inline]
+    pub fn insert(&mut self, value: f64) {
+        if !value.is_nan() {
+            self.push((value, 1.0));
+        }
+    }
+
+    /// Merges another digest into this one, forming a digest of the union of their values.
+    pub fn merge(&mut self, other: &Self) {
+        for centroid in other.centroids.iter().chain(other.unmerged.iter()) {
+            self.push(*centroid);
+        }
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    #[inline]
+    fn push(&mut self, (mean, weight): (f64, f64)) {
+        self.unmerged.push((mean, weight));
+        self.total += weight;
+        self.min = self.min.min(mean);
+        self.max = self.max.max(mean);
+        if self.unmerged.len() >= 4 * (self.compression as usize).max(4) {
+            self.compress();
+        }
+    }
+
+    /// The scale function `k1`, mapping a quantile to a centroid index.
+    fn scale(&self, quantile: f64) -> f64 {
+        self.compression / (2.0 * std::f64::consts::PI) * (2.0 * quantile - 1.0).asin()
+    }
+
+    /// The inverse of [`Self::scale`].
+    fn scale_inverse(&self, scale: f64) -> f64 {
+        ((scale * 2.0 * std::f64::consts::PI / self.compression).sin() + 1.0) / 2.0
+    }
+
+    /// Folds all unmerged values into the centroids.
+    pub fn compress(&mut self) {
+        if self.unmerged.is_empty() {
+            return;
+        }
+        let mut all = std::mem::take(&mut self.centroids);
+        all.append(&mut self.unmerged);
+        all.sort_by(|x, y| x.0.total_cmp(&y.0));
+
+        let max_scale = self.scale(1.0);
+        let limit = |digest: &Self, before: f64| {
+            let scale = digest.scale(before / digest.total) + 1.0;
+            if scale >= max_scale { digest.total } else { digest.total * digest.scale_inverse(scale) }
+        };
+
+        let mut centroids = Vec::with_capacity(all.len().min(2 * self.compression as usize));
+        let mut all = all.into_iter();
+        let mut current = all.next().expect("non-empty");
+        let mut before = 0.0;
+        let mut bound = limit(self, before);
+        for next in all {
+            if before + current.1 + next.1 <= bound {
+                current.1 += next.1;
+                current.0 += (next.0 - current.0) * next.1 / current.1;
+            } else {
+                before += current.1;
+                centroids.push(current);
+                bound = limit(self, before);
+                current = next;
+            }
+        }
+        centroids.push(current);
+        self.centroids = centroids;
+    }
+
+    /// Estimates the value at quantile `q`, which is clamped to the range `0 ..= 1`.
+    ///
+    /// Returns NaN if the digest is empty.
+    pub fn quantile(&self, q: f64) -> f64 {
+        if !self.unmerged.is_empty() {
+            let mut compressed = self.clone();
+            compressed.compress();
+            return compressed.quantile(q);
+        }
+        let centroids = &self.centroids;
+        match centroids.len() {
+            0 => return f64::NAN,
+            1 => return centroids[0].0,
+            _ => { },
+        }
+        let target = q.clamp(0.0, 1.0) * self.total;
+
+        // Between the minimum and the center of the first centroid.
+        let (first_mean, first_weight) = centroids[0];
+        if target < first_weight / 2.0 {
+            return self.min + (first_mean - self.min) * target / (first_weight / 2.0);
+        }
+        // Between the centers of adjacent centroids.
+        let mut before = 0.0;
+        for pair in centroids.windows(2) {
+            let (mean, weight) = pair[0];
+            let (next_mean, next_weight) = pair[1];
+            let center = before + weight / 2.0;
+            let next_center = before + weight + next_weight / 2.0;
+            if target <= next_center {
+                let fraction = (target - center) / (next_center - center);
+                return mean + fraction * (next_mean - mean);
+            }
+            before += weight;
+        }
+        // Between the center of the last centroid and the maximum.
+        let (last_mean, last_weight) = centroids[centroids.len() - 1];
+        let fraction = (target - (self.total - last_weight / 2.0)) / (last_weight / 2.0);
+        last_mean + fraction.min(1.0) * (self.max - last_mean)
+    }
+}
+
+/// A Bloom filter, testing whether a record may have been inserted.
+///
+/// The filter never reports that an inserted record is absent, but may report that a record
+/// that was not inserted is present, at a rate determined by its size and number of hashes.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::sketch::BloomFilter;
+///
+/// let mut filter = BloomFilter::with_rate(1000, 0.01);
+/// for i in 0..1000 {
+///     filter.insert(&i);
+/// }
+/// assert!((0..1000).all(|i| filter.contains(&i)));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BloomFilter {
+    bits: Vec<u64>,
+    hashes: u32,
+}
+
+impl BloomFilter {
+    /// Allocates an empty filter of at least `bits` bits, testing `hashes` positions per record.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` or `hashes` is zero.
+    pub fn new(bits: usize, hashes: u32) -> Self {
+        assert!(bits > 0 && hashes > 0, "Bloom filter requires a positive number of bits and hashes");
+        Self { bits: vec![0; bits.div_ceil(64)], hashes }
+    }
+
+    /// Allocates an empty filter sized for `expected` records at the false positive rate `rate`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` is not strictly between zero and one.
+    pub fn with_rate(expected: usize, rate: f64) -> Self {
+        assert!(rate > 0.0 && rate < 1.0, "Bloom filter false positive rate must be in (0, 1), found {}", rate);
+        let expected = expected.max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let bits = (-expected * rate.ln() / (ln2 * ln2)).ceil();
+        let hashes = (bits / expected * ln2).round().max(1.0);
+        Self::new(bits as usize, hashes as u32)
+    }
+
+    /// The positions tested for a record with hash `hash`, by double hashing.
+    #[inline]
+    fn positions(words: usize, hashes: u32, hash: u64) -> impl Iterator<Item = usize> {
+        let bits = (words * 64) as u64;
+        let step = hash_of(&hash) | 1;
+        (0..u64::from(hashes)).map(move |i| (hash.wrapping_add(i.wrapping_mul(step)) % bits) as usize)
+    }
+
+    /// Inserts a record into the filter.
+    #[inline]
+    pub fn insert<T: Hash + ?Sized>(&mut self, record: &T) {
+        for position in Self::positions(self.bits.len(), self.hashes, hash_of(record)) {
+            self.bits[position / 64] |= 1 << (position % 64);
+        }
+    }
+
+    /// Returns `false` if `record` was certainly not inserted, and `true` if it may have been.
+    #[inline]
+    pub fn contains<T: Hash + ?Sized>(&self, record: &T) -> bool {
+        Self::positions(self.bits.len(), self.hashes, hash_of(record)).all(|position| self.bits[position / 64] & (1 << (position % 64)) != 0)
+    }
+
+    /// Merges another filter into this one, forming a filter of the union of their records.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the filters have different sizes or numbers of hashes.
+    pub fn merge(&mut self, other: &Self) {
+        assert!(self.bits.len() == other.bits.len() && self.hashes == other.hashes, "cannot merge Bloom filters of different shapes");
+        for (mine, theirs) in self.bits.iter_mut().zip(other.bits.iter()) {
+            *mine |= *theirs;
+        }
+    }
+}
+
+/// A count-min sketch, estimating the number of times each record was inserted.
+///
+/// The sketch maintains `depth` rows of `width` counters, and counts each record in one counter of
+/// each row. Its estimate for a record is never less than the record's count, and exceeds it by at
+/// most `e / width` times the total count with probability at least `1 - e^-depth`, regardless of
+/// the number of distinct records.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::sketch::CountMinSketch;
+///
+/// let mut sketch = CountMinSketch::with_error(0.001, 0.01);
+/// for i in 0..10_000 {
+///     sketch.insert(&(i % 100), 1);
+/// }
+/// assert!(sketch.estimate(&7) >= 100);
+/// assert!(sketch.estimate(&7) <= 100 + 10);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CountMinSketch {
+    width: usize,
+    depth: u32,
+    counts: Vec<u64>,
+}
+
+impl CountMinSketch {
+    /// Allocates an empty sketch of `depth` rows of `width` counters.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` or `depth` is zero.
+    pub fn new(width: usize, depth: u32) -> Self {
+        assert!(width > 0 && depth > 0, "count-min sketch requires a positive width and depth");
+        Self { width, depth, counts: vec![0; width * depth as usize] }
+    }
+
+    /// Allocates an empty sketch whose estimates exceed counts by at most `epsilon` times the total
+    /// count, with probability at least `1 - delta`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `epsilon` or `delta` is not strictly between zero and one.
+    pub fn with_error(epsilon: f64, delta: f64) -> Self {
+        assert!(epsilon > 0.0 && epsilon < 1.0, "count-min sketch error must be in (0, 1), found {}", epsilon);
+        assert!(delta > 0.0 && delta < 1.0, "count-min sketch failure probability must be in (0, 1), found {}", delta);
+        let width = (std::f64::consts::E / epsilon).ceil();
+        let depth = (1.0 / delta).ln().ceil().max(1.0);
+        Self::new(width as usize, depth as u32)
+    }
+
+    /// The counters of each row for a record with hash `hash`, by double hashing.
+    #[inline]
+    fn positions(width: usize, depth: u32, hash: u64) -> impl Iterator<Item = usize> {
+        let step = hash_of(&hash) | 1;
+        (0..depth as usize).map(move |row| row * width + (hash.wrapping_add((row as u64).wrapping_mul(step)) % width as u64) as usize)
+    }
+
+    /// Adds `count` insertions of a record to the sketch.
+    #[inline]
+    pub fn insert<T: Hash + ?Sized>(&mut self, record: &T, count: u64) {
+        for position in Self::positions(self.width, self.depth, hash_of(record)) {
+            self.counts[position] += count;
+        }
+    }
+
+    /// An estimate of the number of insertions of `record`, which is never less than the number.
+    #[inline]
+    pub fn estimate<T: Hash + ?Sized>(&self, record: &T) -> u64 {
+        Self::positions(self.width, self.depth, hash_of(record)).map(|position| self.counts[position]).min().unwrap_or(0)
+    }
+
+    /// Merges another sketch into this one, forming a sketch of the insertions into both.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sketches have different widths or depths.
+    pub fn merge(&mut self, other: &Self) {
+        assert!(self.width == other.width && self.depth == other.depth, "cannot merge count-min sketches of different shapes");
+        for (mine, theirs) in self.counts.iter_mut().zip(other.counts.iter()) {
+            *mine += *theirs;
+        }
+    }
+}
+
+/// A Misra-Gries summary, counting the records inserted into it most often.
+///
+/// The summary keeps at most `capacity` counters. A record without a counter while all are taken
+/// is counted by decrementing every counter, and releasing those that reach zero, which bounds the
+/// amount by which the summary underestimates any record's count by `total / (capacity + 1)`, for
+/// `total` the number of insertions. Every record inserted more often than that has a counter.
+/// Merged summaries keep the same bound for the total of their insertions.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::sketch::MisraGries;
+///
+/// let mut summary = MisraGries::with_error(0.01);
+/// for i in 0..10_000u64 {
+///     summary.insert(if i % 4 == 0 { 7 } else { i }, 1);
+/// }
+/// assert_eq!(summary.top(1)[0].0, 7);
+/// assert!(summary.estimate(&7) >= 2_500 - 100);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MisraGries<T: Hash+Eq> {
+    capacity: usize,
+    counts: HashMap<T, u64>,
+    total: u64,
+}
+
+impl<T: Hash+Eq> MisraGries<T> {
+    /// Allocates an empty summary of at most `capacity` counters.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "Misra-Gries summary requires a positive capacity");
+        Self { capacity, counts: HashMap::new(), total: 0 }
+    }
+
+    /// Allocates an empty summary whose estimates are less than counts by at most `epsilon` times the total count.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `epsilon` is not strictly between zero and one.
+    pub fn with_error(epsilon: f64) -> Self {
+        assert!(epsilon > 0.0 && epsilon < 1.0, "Misra-Gries summary error must be in (0, 1), found {}", epsilon);
+        Self::new((1.0 / epsilon).ceil() as usize)
+    }
+
+    /// The most counters the summary keeps.
+    pub fn capacity(&self) -> usize { self.capacity }
+
+    /// The number of counters the summary keeps.
+    pub fn len(&self) -> usize { self.counts.len() }
+
+    /// Returns `true` if the summary keeps no counters.
+    pub fn is_empty(&self) -> bool { self.counts.is_empty() }
+
+    /// The number of insertions into the summary.
+    pub fn total(&self) -> u64 { self.total }
+
+    /// Adds `count` insertions of a record to the summary.
+    pub fn insert(&mut self, record: T, count: u64) {
+        *self.counts.entry(record).or_insert(0) += count;
+        self.total += count;
+        self.shrink();
+    }
+
+    /// An estimate of the number of insertions of `record`, which is never more than the number.
+    pub fn estimate(&self, record: &T) -> u64 {
+        self.counts.get(record).copied().unwrap_or(0)
+    }
+
+    /// The at most `k` records with the largest estimates, and their estimates, in decreasing order of estimate and then of record.
+    pub fn top(&self, k: usize) -> Vec<(T, u64)> where T: Ord+Clone {
+        let mut top = self.counts.iter().map(|(record, count)| (record.clone(), *count)).collect::<Vec<_>>();
+        top.sort_by(|x, y| y.1.cmp(&x.1).then_with(|| x.0.cmp(&y.0)));
+        top.truncate(k);
+        top
+    }
+
+    /// Merges another summary into this one, forming a summary of the insertions into both.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the summaries have different capacities.
+    pub fn merge(&mut self, other: Self) {
+        assert!(self.capacity == other.capacity, "cannot merge Misra-Gries summaries of different capacities");
+        for (record, count) in other.counts {
+            *self.counts.entry(record).or_insert(0) += count;
+        }
+        self.total += other.total;
+        self.shrink();
+    }
+
+    /// Decrements all counters by the largest count beyond the capacity, and releases those that reach zero.
+    fn shrink(&mut self) {
+        if self.counts.len() > self.capacity {
+            let mut counts = self.counts.values().copied().collect::<Vec<_>>();
+            counts.sort_unstable_by(|x, y| y.cmp(x));
+            let decrement = counts[self.capacity];
+            self.counts.retain(|_, count| {
+                *count -= decrement.min(*count);
+                *count > 0
+            });
+        }
+    }
+}
+
+/// A MinHash signature, estimating the Jaccard similarity of the sets of records inserted into two signatures.
+///
+/// The signature keeps, for each of `num_hashes` hash functions, the least hash of the records
+/// inserted. Two sets agree on the least hash of a function with probability equal to their
+/// Jaccard similarity, the size of their intersection over that of their union, and so the
+/// fraction of functions on which two signatures agree estimates the similarity of their sets,
+/// with standard error `sqrt(J (1 - J) / num_hashes)` for similarity `J`.
+///
+/// The hash functions derive from a seed, and signatures with the same number of hashes and seed
+/// are the same on all workers and in all runs, and can be merged and compared.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::sketch::MinHash;
+///
+/// let mut left = MinHash::new(256);
+/// let mut right = MinHash::new(256);
+/// for i in 0..1000 { left.insert(&i); }
+/// for i in 500..1500 { right.insert(&i); }
+/// // The sets share 500 of their 1500 records.
+/// assert!((left.jaccard(&right) - 1.0 / 3.0).abs() < 0.1);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MinHash {
+    seed: u64,
+    minimums: Vec<u64>,
+}
+
+impl MinHash {
+    /// The seed of the hash functions of [`Self::new`].
+    pub const DEFAULT_SEED: u64 = 0x13198a2e03707344;
+
+    /// Allocates an empty signature of `num_hashes` hash functions, derived from [`Self::DEFAULT_SEED`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_hashes` is zero.
+    pub fn new(num_hashes: usize) -> Self {
+        Self::with_seed(num_hashes, Self::DEFAULT_SEED)
+    }
+
+    /// Allocates an empty signature of `num_hashes` hash functions, derived from `seed`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_hashes` is zero.
+    pub fn with_seed(num_hashes: usize, seed: u64) -> Self {
+        assert!(num_hashes > 0, "MinHash signature requires a positive number of hashes");
+        Self { seed, minimums: vec![u64::MAX; num_hashes] }
+    }
+
+    /// The number of hash functions of the signature.
+    pub fn num_hashes(&self) -> usize { self.minimums.len() }
+
+    /// The seed the hash functions of the signature derive from.
+    pub fn seed(&self) -> u64 { self.seed }
+
+    /// The least hash of the inserted records for each hash function, or `u64::MAX` if none were inserted.
+    pub fn signature(&self) -> &[u64] { &self.minimums }
+
+    /// Inserts a record into the signature.
+    #[inline]
+    pub fn insert<T: Hash + ?Sized>(&mut self, record: &T) {
+        self.insert_hash(hash_of(record));
+    }
+
+    /// Inserts a record by its 64 bit hash.
+    ///
+    /// The hash should be uniformly distributed; [`Self::insert`] uses an appropriate hash.
+    #[inline]
+    pub fn insert_hash(&mut self, hash: u64) {
+        // Each function XORs the hash with a key of its own, drawn by SplitMix64 from the seed, and mixes the result.
+        let mut key = self.seed;
+        for minimum in self.minimums.iter_mut() {
+            key = key.wrapping_add(0x9e3779b97f4a7c15);
+            let value = mix(hash ^ mix(key));
+            if value < *minimum {
+                *minimum = value;
+            }
+        }
+    }
+
+    /// Merges another signature into this one, forming the signature of the union of their records.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the signatures have different numbers of hashes or seeds.
+    pub fn merge(&mut self, other: &Self) {
+        self.assert_comparable(other);
+        for (mine, theirs) in self.minimums.iter_mut().zip(other.minimums.iter()) {
+            *mine = (*mine).min(*theirs);
+        }
+    }
+
+    /// Estimates the Jaccard similarity of the records inserted into this signature and another.
+    ///
+    /// Two empty signatures agree on all their hashes, and have an estimated similarity of one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the signatures have different numbers of hashes or seeds.
+    pub fn jaccard(&self, other: &Self) -> f64 {
+        self.assert_comparable(other);
+        let agreements = self.minimums.iter().zip(other.minimums.iter()).filter(|(mine, theirs)| mine == theirs).count();
+        agreements as f64 / self.minimums.len() as f64
+    }
+
+    fn assert_comparable(&self, other: &Self) {
+        assert!(self.minimums.len() == other.minimums.len() && self.seed == other.seed, "cannot combine MinHash signatures of different hash functions");
+    }
+}
+
+/// The 64-bit finalizer of MurmurHash3, in which all bits of the result depend on all bits of `value`.
+#[inline]
+fn mix(mut value: u64) -> u64 {
+    value ^= value >> 33;
+    value = value.wrapping_mul(0xff51afd7ed558ccd);
+    value ^= value >> 33;
+    value = value.wrapping_mul(0xc4ceb9fe1a85ec53);
+    value ^= value >> 33;
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hash_of, BloomFilter, CountMinSketch, HyperLogLog, MinHash, MisraGries, TDigest};
+
+    #[test]
+    fn hashes_are_fixed() {
+        // Summaries merge across runs and platforms only if these never change.
+        assert_eq!(hash_of(&0u64), 0x0f8443730f99d79a);
+        assert_eq!(hash_of(&1usize), 0x4d7c5f98406ec4f0);
+        assert_eq!(hash_of(&1u64), hash_of(&1usize));
+        assert_eq!(hash_of("timely"), 0x58d8a6bfb204c930);
+        assert_eq!(hash_of(&(7u32, -1i64)), 0xa57ba4eff4b28448);
+    }
+
+    #[test]
+    fn hyperloglog_error_bound() {
+        for &(precision, cardinality) in &[(10u8, 500u64), (12, 10_000), (14, 250_000)] {
+            let mut sketch = HyperLogLog::new(precision);
+            for i in 0..cardinality {
+                sketch.insert(&i);
+                // Repeated insertions must not affect the estimate.
+                sketch.insert(&i);
+            }
+            let error = (sketch.estimate() as f64 - cardinality as f64).abs() / cardinality as f64;
+            let bound = 3.0 * 1.04 / f64::from(1u32 << precision).sqrt();
+            assert!(error < bound, "precision {}: error {} exceeds {}", precision, error, bound);
+        }
+    }
+
+    #[test]
+    fn hyperloglog_merge() {
+        let mut left = HyperLogLog::new(12);
+        let mut right = HyperLogLog::new(12);
+        let mut both = HyperLogLog::new(12);
+        for i in 0..20_000u64 {
+            if i % 2 == 0 { left.insert(&i) } else { right.insert(&i) }
+            both.insert(&i);
+        }
+        left.merge(&right);
+        assert_eq!(left, both);
+    }
+
+    #[test]
+    fn tdigest_quantiles() {
+        let count = 100_000u64;
+        for &compression in &[50.0, 100.0, 200.0] {
+            let mut digest = TDigest::new(compression);
+            // A permutation of `0 .. count`, so values arrive in no particular order.
+            for i in 0..count {
+                digest.insert(((i * 7919) % count) as f64);
+            }
+            for &(q, tolerance) in &[(0.5, 0.01), (0.99, 0.002), (0.01, 0.002)] {
+                let estimate = digest.quantile(q) / count as f64;
+                assert!((estimate - q).abs() < tolerance, "compression {}: q{} estimated at {}", compression, q, estimate);
+            }
+            assert!(digest.centroids.len() <= 2 * compression as usize);
+        }
+    }
+
+    #[test]
+    fn tdigest_merge() {
+        let mut left = TDigest::new(100.0);
+        let mut right = TDigest::new(100.0);
+        for i in 0..10_000 {
+            if i % 3 == 0 { left.insert(f64::from(i)) } else { right.insert(f64::from(i)) }
+        }
+        left.merge(&right);
+        assert_eq!(left.count(), 10_000.0);
+        assert!((left.quantile(0.5) - 5_000.0).abs() < 100.0);
+        assert!((left.quantile(0.99) - 9_900.0).abs() < 20.0);
+    }
+
+    #[test]
+    fn bloom_filter_rate() {
+        let mut filter = BloomFilter::with_rate(10_000, 0.01);
+        for i in 0..10_000u64 {
+            filter.insert(&i);
+        }
+        assert!((0..10_000u64).all(|i| filter.contains(&i)));
+        let false_positives = (10_000..110_000u64).filter(|i| filter.contains(i)).count();
+        assert!(false_positives < 2_000, "false positive rate {} exceeds twice the target", false_positives as f64 / 100_000.0);
+    }
+
+    #[test]
+    fn count_min_error_bound() {
+        let mut sketch = CountMinSketch::with_error(0.01, 0.001);
+        let mut other = CountMinSketch::with_error(0.01, 0.001);
+        // Key `i` is inserted `i` times, for a total of about 50,000 insertions.
+        for i in 0..317u64 {
+            if i % 2 == 0 { sketch.insert(&i, i) } else { other.insert(&i, i) }
+        }
+        sketch.merge(&other);
+        let total = (0..317u64).sum::<u64>();
+        let within = (0..317u64).filter(|i| sketch.estimate(i) >= *i && sketch.estimate(i) <= i + total / 100).count();
+        assert_eq!(within, 317);
+    }
+
+    #[test]
+    fn misra_gries_error_bound() {
+        let mut summary = MisraGries::with_error(0.01);
+        let mut other = MisraGries::with_error(0.01);
+        // Keys below 10 are inserted 1,000 times each, among 90,000 distinct insertions.
+        let mut total = 0;
+        for i in 0..100_000u64 {
+            let record = if i % 10 == 0 { i / 10 % 10 } else { 100 + i };
+            if i % 2 == 0 { summary.insert(record, 1) } else { other.insert(record, 1) }
+            total += 1;
+            assert!(summary.len() <= summary.capacity() && other.len() <= other.capacity());
+        }
+        summary.merge(other);
+        assert_eq!(summary.total(), total);
+        assert!(summary.len() <= 100);
+        for key in 0..10u64 {
+            let estimate = summary.estimate(&key);
+            assert!(estimate <= 1_000 && estimate + total / 101 >= 1_000, "key {} estimated at {}", key, estimate);
+        }
+        let mut top = summary.top(10).into_iter().map(|(key, _)| key).collect::<Vec<_>>();
+        top.sort();
+        assert_eq!(top, (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn minhash_error_bound() {
+        let num_hashes = 512;
+        // Sets of 1,000 records, overlapping in 0, 250, 500, 750, and 1,000 records.
+        for overlap in (0..=1000u64).step_by(250) {
+            let mut left = MinHash::new(num_hashes);
+            let mut right = MinHash::new(num_hashes);
+            for i in 0..1000u64 {
+                left.insert(&i);
+                right.insert(&(i + 1000 - overlap));
+            }
+            let jaccard = overlap as f64 / (2000 - overlap) as f64;
+            let bound = 3.0 * (jaccard * (1.0 - jaccard) / num_hashes as f64).sqrt() + 0.01;
+            let estimate = left.jaccard(&right);
+            assert!((estimate - jaccard).abs() < bound, "overlap {}: estimate {} of {} exceeds {}", overlap, estimate, jaccard, bound);
+        }
+    }
+
+    #[test]
+    fn minhash_merge() {
+        let mut left = MinHash::with_seed(64, 7);
+        let mut right = MinHash::with_seed(64, 7);
+        let mut both = MinHash::with_seed(64, 7);
+        for i in 0..1000u64 {
+            if i % 2 == 0 { left.insert(&i) } else { right.insert(&i) }
+            both.insert(&i);
+        }
+        left.merge(&right);
+        assert_eq!(left, both);
+        // Signatures of other seeds differ.
+        let mut reseeded = MinHash::with_seed(64, 8);
+        reseeded.insert(&0u64);
+        let mut seeded = MinHash::with_seed(64, 7);
+        seeded.insert(&0u64);
+        assert_ne!(reseeded.signature(), seeded.signature());
+    }
+}
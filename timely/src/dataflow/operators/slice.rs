@@ -0,0 +1,120 @@
+//! Extension methods for `Stream` that keep or drop the first records of each time.
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::generic::stash::Stash;
+
+/// Extension trait for `Stream`.
+pub trait Slice<G: Scope, D: Data> {
+    /// Keeps the first `n` records of each time, and drops the rest.
+    ///
+    /// The records of a time are counted in the order in which the operator receives them, at each
+    /// worker, so each worker keeps up to `n` records of each time; exchange the stream to a single
+    /// worker first to keep `n` records of each time overall. A time with at most `n` records keeps
+    /// them all. Counters are kept only for incomplete times, and are discarded once the input
+    /// frontier passes a time.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Slice, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .take_per_epoch(3)
+    ///            .inspect(|x| assert!(*x < 3));
+    /// });
+    /// ```
+    fn take_per_epoch(&self, n: u64) -> Stream<G, D>;
+
+    /// Drops the first `n` records of each time, and keeps the rest.
+    ///
+    /// Records are counted as for [`Slice::take_per_epoch`], at each worker and per time, and a time
+    /// with at most `n` records keeps none of them.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Slice, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .skip_per_epoch(3)
+    ///            .inspect(|x| assert!(*x >= 3));
+    /// });
+    /// ```
+    fn skip_per_epoch(&self, n: u64) -> Stream<G, D>;
+}
+
+impl<G: Scope, D: Data> Slice<G, D> for Stream<G, D> {
+    fn take_per_epoch(&self, n: u64) -> Stream<G, D> {
+        slice(self, "TakePerEpoch", move |index| index < n)
+    }
+
+    fn skip_per_epoch(&self, n: u64) -> Stream<G, D> {
+        slice(self, "SkipPerEpoch", move |index| index >= n)
+    }
+}
+
+/// Keeps the records whose positions among the records of their time satisfy `keep`.
+fn slice<G: Scope, D: Data>(stream: &Stream<G, D>, name: &str, keep: impl Fn(u64)->bool+'static) -> Stream<G, D> {
+    stream.unary_frontier(Pipeline, name, move |_capability, _info| {
+        // For each incomplete time, the number of its records received so far.
+        let mut counts = Stash::<G::Timestamp, u64>::new();
+        move |(input, frontier), output| {
+            input.for_each_time(|time, data| {
+                let count = counts.get_or_insert_with(time.time(), || time.time().clone(), || 0);
+                let mut session = output.session(&time);
+                for datum in data.flat_map(|d| d.drain(..)) {
+                    if keep(*count) { session.give(datum); }
+                    *count += 1;
+                }
+            });
+            counts.release(|time| !frontier.less_equal(time), |_time, _count| { });
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dataflow::operators::{Capture, Input, Probe, Slice};
+    use crate::dataflow::operators::capture::Extract;
+
+    /// The records of `rounds` kept by `take_per_epoch(n)`, or `skip_per_epoch(n)`, each round at its own time and sent in two batches.
+    fn sliced(rounds: Vec<Vec<u64>>, take: bool, n: u64) -> Vec<(u64, Vec<u64>)> {
+        let captured = crate::execute_directly(move |worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<u64>();
+                let sliced = if take { stream.take_per_epoch(n) } else { stream.skip_per_epoch(n) };
+                (input, sliced.probe(), sliced.capture())
+            });
+            for (round, records) in rounds.into_iter().enumerate() {
+                input.advance_to(round as u64);
+                let (first, second) = records.split_at(records.len() / 2);
+                for record in first { input.send(*record); }
+                worker.step();
+                for record in second { input.send(*record); }
+            }
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+        captured.extract()
+    }
+
+    #[test]
+    fn take_keeps_the_first_records_of_each_time() {
+        // Counts continue across batches of a time, and restart at each time; short times keep all their records.
+        assert_eq!(sliced(vec![vec![1, 2, 3, 4, 5], vec![6, 7]], true, 3), vec![
+            (0, vec![1, 2, 3]),
+            (1, vec![6, 7]),
+        ]);
+    }
+
+    #[test]
+    fn skip_drops_the_first_records_of_each_time() {
+        assert_eq!(sliced(vec![vec![1, 2, 3, 4, 5], vec![6, 7]], false, 3), vec![
+            (0, vec![4, 5]),
+        ]);
+    }
+}
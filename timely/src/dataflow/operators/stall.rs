@@ -0,0 +1,112 @@
+//! Detection of dataflows whose progress has stalled.
+
+use std::fmt::{self, Display};
+use std::time::{Duration, Instant};
+
+use crate::dataflow::operators::probe::Handle;
+use crate::progress::{Antichain, Timestamp};
+#[cfg(feature = "capability-provenance")]
+use crate::dataflow::operators::provenance::{self, CapabilityOrigin};
+
+/// Reports when the frontier at a probe stops advancing.
+///
+/// A dataflow whose frontier stays put while it still has times to complete has most often stalled
+/// on a capability that some operator holds and never drops. The detector is checked by the worker,
+/// for example between calls to `step`, and reports a stall once the frontier at the probe has not
+/// changed for `timeout`; it reports nothing once the frontier is empty.
+///
+/// With the `capability-provenance` feature, a report lists the capabilities the worker still holds,
+/// at the locations in the source they were created at, which usually points at the one leaked.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use timely::dataflow::operators::{Input, Probe};
+/// use timely::dataflow::operators::stall::StallDetector;
+///
+/// timely::execute_directly(|worker| {
+///     let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+///         let (input, stream) = scope.new_input::<u64>();
+///         (input, stream.probe())
+///     });
+///
+///     let mut detector = StallDetector::new(&probe, Duration::ZERO);
+///     input.advance_to(1);
+///     worker.step_while(|| probe.less_than(&1));
+///     // The frontier has advanced since the detector last saw it.
+///     assert!(detector.check().is_none());
+///     // The input no longer advances, and so the frontier is stalled.
+///     let report = detector.check().unwrap();
+///     assert_eq!(report.frontier.elements(), &[1]);
+///     println!("{}", report);
+/// });
+/// ```
+pub struct StallDetector<T: Timestamp> {
+    probe: Handle<T>,
+    timeout: Duration,
+    /// The frontier at the last check, and when it was first seen.
+    frontier: Antichain<T>,
+    since: Instant,
+}
+
+impl<T: Timestamp> StallDetector<T> {
+    /// Creates a detector reporting stalls of the frontier at `probe` lasting at least `timeout`.
+    pub fn new(probe: &Handle<T>, timeout: Duration) -> Self {
+        StallDetector {
+            probe: probe.clone(),
+            timeout,
+            frontier: probe.with_frontier(|frontier| frontier.to_owned()),
+            since: Instant::now(),
+        }
+    }
+
+    /// Returns a report if the frontier at the probe has not changed for at least the timeout.
+    ///
+    /// Each check while the frontier stays put after the timeout reports the stall again.
+    pub fn check(&mut self) -> Option<StallReport<T>> {
+        let current = self.probe.with_frontier(|frontier| frontier.to_owned());
+        if current != self.frontier {
+            self.frontier = current;
+            self.since = Instant::now();
+            return None;
+        }
+        let stalled_for = self.since.elapsed();
+        if self.frontier.is_empty() || stalled_for < self.timeout {
+            return None;
+        }
+        Some(StallReport {
+            frontier: self.frontier.clone(),
+            stalled_for,
+            #[cfg(feature = "capability-provenance")]
+            capabilities: provenance::outstanding(),
+        })
+    }
+}
+
+/// A frontier that has not advanced, and what may hold it back.
+#[derive(Clone, Debug)]
+pub struct StallReport<T> {
+    /// The frontier at the probe.
+    pub frontier: Antichain<T>,
+    /// How long the frontier has not changed.
+    pub stalled_for: Duration,
+    /// The capabilities held by the worker, and where they were created.
+    #[cfg(feature = "capability-provenance")]
+    pub capabilities: Vec<CapabilityOrigin>,
+}
+
+impl<T: fmt::Debug> Display for StallReport<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no progress beyond {:?} in {:?}", self.frontier.elements(), self.stalled_for)?;
+        #[cfg(feature = "capability-provenance")]
+        {
+            write!(f, "; outstanding capabilities:")?;
+            for origin in self.capabilities.iter() {
+                write!(f, "\n  {}", origin)?;
+            }
+        }
+        #[cfg(not(feature = "capability-provenance"))]
+        write!(f, "; enable the `capability-provenance` feature to list outstanding capabilities")?;
+        Ok(())
+    }
+}
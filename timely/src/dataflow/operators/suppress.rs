@@ -0,0 +1,189 @@
+//! Extension methods for `Stream` that suppress output for times below a threshold, as while catching up.
+
+use crate::Data;
+use crate::dataflow::{Scope, Stream};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::Capability;
+use crate::dataflow::operators::generic::OutputBuilder;
+use crate::dataflow::operators::generic::builder_rc::OperatorBuilder;
+use crate::dataflow::operators::generic::operator::empty;
+use crate::dataflow::operators::generic::stash::Stash;
+use crate::order::PartialOrder;
+
+/// What to do with records at times below the threshold of [`SuppressUntil`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Suppression {
+    /// Retain the records, and produce them at their times once the input frontier passes the threshold.
+    ///
+    /// The records are retained in memory without bound until then.
+    Buffer,
+    /// Discard the records.
+    Drop,
+}
+
+/// Extension trait for `Stream`.
+pub trait SuppressUntil<G: Scope, D: Data> {
+    /// Suppresses records at times less than `threshold`, buffering or dropping them according to `mode`.
+    ///
+    /// Records at times not less than `threshold` pass through unchanged. This suits reprocessing
+    /// historical data on restart, where output for times before the live frontier would repeat
+    /// side effects downstream. With [`Suppression::Drop`] the operator only filters records, and
+    /// holds no capabilities. With [`Suppression::Buffer`] it retains the suppressed records and
+    /// capabilities for their times, holding back the output frontier, until the input frontier is
+    /// no longer less than `threshold`; the records are then produced at their times.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, SuppressUntil, Inspect};
+    /// use timely::dataflow::operators::suppress::Suppression;
+    /// use timely::dataflow::operators::generic::operator::Operator;
+    /// use timely::dataflow::channels::pact::Pipeline;
+    ///
+    /// timely::example(|scope| {
+    ///     // Records at their own times 0 through 9.
+    ///     (0..10u64).to_stream(scope)
+    ///               .unary(Pipeline, "AtOwnTime", |_, _| |input, output| {
+    ///                   input.for_each_time(|time, data| {
+    ///                       for datum in data.flat_map(|d| d.drain(..)) {
+    ///                           output.session(&time.delayed(&datum)).give(datum);
+    ///                       }
+    ///                   });
+    ///               })
+    ///               .suppress_until(5, Suppression::Drop)
+    ///               .inspect(|x| assert!(*x >= 5));
+    /// });
+    /// ```
+    fn suppress_until(&self, threshold: G::Timestamp, mode: Suppression) -> Stream<G, D>;
+
+    /// Suppresses records as [`SuppressUntil::suppress_until`] does, with a threshold updated by `control`.
+    ///
+    /// Each time received from `control` replaces the threshold, from `threshold` initially, and
+    /// applies to records received from then on. Raising the threshold again cuts over back to
+    /// suppressing records. The frontier of `control` does not hold back the output. Each worker
+    /// applies the thresholds it receives from `control`, which can be broadcast to apply them at
+    /// all workers.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{Input, SuppressUntil, Inspect, Probe};
+    /// use timely::dataflow::operators::suppress::Suppression;
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     let (mut input, mut control, probe) = worker.dataflow::<u64,_,_>(|scope| {
+    ///         let (input, stream) = scope.new_input::<u64>();
+    ///         let (control, thresholds) = scope.new_input::<u64>();
+    ///         let probe = stream
+    ///             .suppress_until_control(u64::MAX, &thresholds, Suppression::Buffer)
+    ///             .inspect_time(|time, x| println!("seen {:?} at {:?}", x, time))
+    ///             .probe();
+    ///         (input, control, probe)
+    ///     });
+    ///     input.send(0);
+    ///     input.advance_to(1);
+    ///     // Cut over at time 1: the record at time 0 is produced once the input reaches 1.
+    ///     control.send(1);
+    ///     control.advance_to(1);
+    ///     worker.step_while(|| probe.less_than(input.time()));
+    /// });
+    /// ```
+    fn suppress_until_control(&self, threshold: G::Timestamp, control: &Stream<G, G::Timestamp>, mode: Suppression) -> Stream<G, D>;
+}
+
+impl<G: Scope, D: Data> SuppressUntil<G, D> for Stream<G, D> {
+    fn suppress_until(&self, threshold: G::Timestamp, mode: Suppression) -> Stream<G, D> {
+        self.suppress_until_control(threshold, &empty(&self.scope()), mode)
+    }
+
+    fn suppress_until_control(&self, threshold: G::Timestamp, control: &Stream<G, G::Timestamp>, mode: Suppression) -> Stream<G, D> {
+        let mut builder = OperatorBuilder::new("SuppressUntil".to_owned(), self.scope());
+
+        // Thresholds apply to records as they arrive, and so do not hold back the output.
+        let (output, result) = builder.new_output_connection([]);
+        let mut output = OutputBuilder::from(output);
+        let mut input = builder.new_input(self, Pipeline);
+        let mut thresholds = builder.new_input_connection(control, Pipeline, []);
+
+        let mut threshold = threshold;
+        let mut buffered = Stash::<Capability<G::Timestamp>, Vec<D>>::new();
+
+        builder.build(move |_capabilities| {
+            move |frontiers| {
+                let mut output = output.activate();
+                thresholds.for_each(|_time, data| {
+                    if let Some(latest) = data.last() {
+                        threshold = latest.clone();
+                    }
+                });
+                input.for_each_time(|time, data| {
+                    if !time.time().less_than(&threshold) {
+                        output.session(&time).give_containers(data);
+                    }
+                    else if mode == Suppression::Buffer {
+                        buffered.get_or_retain(&time, Vec::new).extend(data.flat_map(|d| d.drain(..)));
+                    }
+                });
+                // No further record can be suppressed once the input frontier has passed the threshold.
+                if !frontiers[0].frontier().iter().any(|time| time.less_than(&threshold)) {
+                    buffered.release(|_| true, |capability, mut data| {
+                        output.session(&capability).give_container(&mut data);
+                    });
+                }
+            }
+        });
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use crate::dataflow::operators::{Input, Inspect, Probe};
+    use super::{Suppression, SuppressUntil};
+
+    /// Sends one record at each of times 0 through 5, cutting over to a threshold of 3 at time 2.
+    ///
+    /// The control input is never advanced, and so must not hold back the output.
+    fn suppressed(mode: Suppression) -> Vec<(u64, u64)> {
+        crate::execute_directly(move |worker| {
+            let seen = Rc::new(RefCell::new(Vec::new()));
+            let seen_inner = Rc::clone(&seen);
+            let (mut input, mut control, probe) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<u64>();
+                let (control, thresholds) = scope.new_input::<u64>();
+                let probe = stream
+                    .suppress_until_control(u64::MAX, &thresholds, mode)
+                    .inspect_time(move |time, x| seen_inner.borrow_mut().push((*time, *x)))
+                    .probe();
+                (input, control, probe)
+            });
+            for round in 0..6 {
+                if round == 2 { control.send(3); control.flush(); }
+                input.send(round);
+                input.advance_to(round + 1);
+                if mode == Suppression::Buffer && round < 2 {
+                    // Buffered records hold back the output frontier until the input frontier passes the threshold.
+                    for _ in 0..10 { worker.step(); }
+                    assert!(probe.less_equal(&0));
+                }
+                else {
+                    worker.step_while(|| probe.less_than(input.time()));
+                }
+            }
+            let seen = seen.borrow().clone();
+            seen
+        })
+    }
+
+    #[test]
+    fn drop_filters_records_below_threshold() {
+        assert_eq!(suppressed(Suppression::Drop), vec![(3, 3), (4, 4), (5, 5)]);
+    }
+
+    #[test]
+    fn buffer_releases_records_once_caught_up() {
+        assert_eq!(suppressed(Suppression::Buffer), vec![(0, 0), (1, 1), (2, 2), (3, 3), (4, 4), (5, 5)]);
+    }
+}
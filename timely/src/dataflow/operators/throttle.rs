@@ -0,0 +1,131 @@
+//! Extension methods for `Stream` that limit the rate of records by wall-clock time.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::Capability;
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// Extension trait for `Stream`.
+pub trait Throttle<G: Scope, D: Data> {
+    /// Passes records through unchanged, at most `records` of them in each `interval` of wall-clock time.
+    ///
+    /// Intervals start with the first record, and follow each other for as long as records await
+    /// their turn. Records beyond an interval's allowance are held, in the order of their arrival,
+    /// and passed on in later intervals, at their own times. The operator does not poll while it
+    /// holds records: it asks to be scheduled again at the start of the next interval with
+    /// [`Activator::activate_after`](crate::scheduling::Activator::activate_after), and a worker
+    /// with nothing else to do parks until then.
+    ///
+    /// Held records retain capabilities for their times, and so hold back the output frontier until
+    /// they are passed on. The records of a worker are throttled at that worker, as the input is
+    /// not exchanged, and each worker has an allowance of its own.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `records` or `interval` is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use timely::dataflow::operators::{ToStream, Throttle, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .throttle(5, Duration::from_millis(10))
+    ///            .inspect(|x| println!("seen: {:?}", x));
+    /// });
+    /// ```
+    fn throttle(&self, records: usize, interval: Duration) -> Stream<G, D>;
+}
+
+impl<G: Scope, D: Data> Throttle<G, D> for Stream<G, D> {
+    fn throttle(&self, records: usize, interval: Duration) -> Stream<G, D> {
+        assert!(records > 0 && !interval.is_zero(), "Throttle: records and interval must be positive");
+        let scope = self.scope();
+        self.unary(Pipeline, "Throttle", move |_capability, info| {
+            let activator = scope.activator_for(info.address);
+            // Records awaiting their turn, in the order of their arrival, with capabilities for their times.
+            let mut backlog = VecDeque::<(Capability<G::Timestamp>, VecDeque<D>)>::new();
+            // The start of the current interval, and the number of records passed on in it.
+            let mut window = None::<(Instant, usize)>;
+            // The start of the next interval, if the timer for it is set.
+            let mut due = None;
+            move |input, output| {
+                input.for_each_time(|time, data| {
+                    let mut batch = VecDeque::new();
+                    for datum in data { batch.extend(datum.drain(..)); }
+                    backlog.push_back((time.retain(), batch));
+                });
+
+                let now = Instant::now();
+                let (start, sent) = window.get_or_insert((now, 0));
+                if now >= *start + interval {
+                    // Intervals follow each other while records await their turn, and start anew after a pause.
+                    *start = if now < *start + 2 * interval { *start + interval } else { now };
+                    *sent = 0;
+                }
+
+                while *sent < records {
+                    let Some((capability, batch)) = backlog.front_mut() else { break };
+                    let count = batch.len().min(records - *sent);
+                    output.session(capability).give_iterator(batch.drain(.. count));
+                    *sent += count;
+                    if batch.is_empty() {
+                        backlog.pop_front();
+                    }
+                }
+
+                if !backlog.is_empty() {
+                    let next = *start + interval;
+                    if due != Some(next) {
+                        activator.activate_after(next.saturating_duration_since(now));
+                        due = Some(next);
+                    }
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::{Duration, Instant};
+
+    use crate::dataflow::operators::{Input, Inspect, Probe, Throttle};
+
+    #[test]
+    fn held_records_are_passed_on_as_each_interval_starts() {
+        let interval = Duration::from_millis(100);
+        let seen = crate::execute_directly(move |worker| {
+            let seen = Rc::new(RefCell::new(Vec::new()));
+            let seen_inner = Rc::clone(&seen);
+            let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<u64>();
+                let probe = stream.throttle(2, interval).inspect(move |x| seen_inner.borrow_mut().push((*x, Instant::now()))).probe();
+                (input, probe)
+            });
+            for record in 0..5 { input.send(record); }
+            input.advance_to(1);
+            input.send(5);
+            input.close();
+            // Parking for long would delay the records well past their intervals, unless the timer wakes the worker.
+            while !probe.done() { worker.step_or_park(Some(Duration::from_secs(10))); }
+            seen.take()
+        });
+
+        assert_eq!(seen.iter().map(|(x, _)| *x).collect::<Vec<_>>(), (0..6).collect::<Vec<_>>());
+        let first = seen[0].1;
+        for (record, at) in &seen {
+            // Two records pass in each interval, never before it starts, and promptly once it has.
+            let start = interval * (*record as u32 / 2);
+            let offset = at.duration_since(first);
+            assert!(offset + Duration::from_millis(5) >= start && offset < start + Duration::from_secs(1), "record {} at {:?}", record, offset);
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Extension methods for `Stream` that check that records are unique per key and time.
+
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::generic::stash::Stash;
+
+/// Extension trait for `Stream`.
+pub trait AssertUniquePerTime<G: Scope, D: Data> {
+    /// Passes records through unchanged, and panics if two records at the same time have the same key of `key_fn`.
+    ///
+    /// This checks the invariant of logic that assumes at most one record per key and time, and the
+    /// panic names the key and the time of the first collision, which fails the worker and so the
+    /// computation. Records with the same key at different times are unrelated, and so the operator
+    /// retains the keys of each time only until its input frontier passes the time. When the
+    /// invariant holds, the cost is that of a hash set insertion per record.
+    ///
+    /// Each worker checks the records it receives. Records with the same key at different workers
+    /// are not compared, and to check the invariant across all workers the stream should first be
+    /// exchanged by key.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, AssertUniquePerTime, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     vec![(1, "open"), (2, "open"), (1, "close")].to_stream(scope)
+    ///         .assert_unique_per_time(|(id, action)| (*id, *action))
+    ///         .inspect(|x| println!("seen: {:?}", x));
+    /// });
+    /// ```
+    fn assert_unique_per_time<K, F>(&self, key_fn: F) -> Stream<G, D>
+    where
+        K: Hash+Eq+Debug+'static,
+        F: FnMut(&D)->K+'static;
+}
+
+impl<G: Scope, D: Data> AssertUniquePerTime<G, D> for Stream<G, D> {
+    fn assert_unique_per_time<K, F>(&self, mut key_fn: F) -> Stream<G, D>
+    where
+        K: Hash+Eq+Debug+'static,
+        F: FnMut(&D)->K+'static,
+    {
+        self.unary_frontier(Pipeline, "AssertUniquePerTime", move |_capability, _info| {
+            // For each incomplete time, the keys of its records seen so far.
+            let mut seen = Stash::<G::Timestamp, HashSet<K>>::new();
+            move |(input, frontier), output| {
+                input.for_each_time(|time, data| {
+                    let keys = seen.get_or_insert_with(time.time(), || time.time().clone(), HashSet::new);
+                    let mut session = output.session(&time);
+                    for container in data {
+                        for datum in container.iter() {
+                            let key = key_fn(datum);
+                            if keys.contains(&key) {
+                                panic!("AssertUniquePerTime: two records have the key {:?} at time {:?}", key, time.time());
+                            }
+                            keys.insert(key);
+                        }
+                        session.give_container(container);
+                    }
+                });
+                seen.release(|time| !frontier.less_equal(time), |_time, _keys| { });
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dataflow::operators::{AssertUniquePerTime, Capture, Input, Probe};
+    use crate::dataflow::operators::capture::Extract;
+
+    #[test]
+    fn keys_may_repeat_at_other_times() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<(char, u64)>();
+                let checked = stream.assert_unique_per_time(|(key, _)| *key);
+                (input, checked.probe(), checked.capture())
+            });
+            input.send(('a', 1));
+            input.send(('b', 2));
+            worker.step();
+            input.advance_to(1);
+            input.send(('a', 3));
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        assert_eq!(captured.extract(), vec![(0, vec![('a', 1), ('b', 2)]), (1, vec![('a', 3)])]);
+    }
+
+    #[test]
+    #[should_panic(expected = "two records have the key 'a' at time 2")]
+    fn repeated_keys_at_a_time_panic() {
+        crate::execute_directly(|worker| {
+            let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<(char, u64)>();
+                (input, stream.assert_unique_per_time(|(key, _)| *key).probe())
+            });
+            input.advance_to(2);
+            input.send(('a', 1));
+            worker.step();
+            input.send(('b', 2));
+            input.send(('a', 3));
+            input.close();
+            worker.step_while(|| !probe.done());
+        });
+    }
+}
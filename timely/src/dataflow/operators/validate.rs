@@ -0,0 +1,69 @@
+//! Extension methods for `Stream` that separate valid records from invalid ones.
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::operators::Map;
+
+/// Extension trait for `Stream`.
+pub trait Validate<G: Scope, D: Data> {
+    /// Checks each record with `check`, and produces the valid records in the first stream and the invalid ones, with the reason they failed, in the second.
+    ///
+    /// A record is valid if `check` returns `Ok(())`, and passes through unchanged, and is otherwise
+    /// produced as `(record, reason)` with the reason `check` returned. Each record is checked once,
+    /// and produced at its own time in one of the two streams, so that downstream operators can
+    /// relate failures to the valid records of the same time. This is
+    /// [`Map::map_result`](crate::dataflow::operators::Map::map_result) for checks of records.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Validate, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     let (valid, invalid) = vec![("ann", 34), ("bob", -3)].to_stream(scope)
+    ///         .validate(|(_name, age)| if *age >= 0 { Ok(()) } else { Err(format!("negative age {}", age)) });
+    ///     valid.inspect(|record| println!("valid: {:?}", record));
+    ///     invalid.inspect(|(record, reason)| println!("invalid: {:?}: {}", record, reason));
+    /// });
+    /// ```
+    fn validate<F: FnMut(&D)->Result<(), String>+'static>(&self, check: F) -> (Stream<G, D>, Stream<G, (D, String)>);
+}
+
+impl<G: Scope, D: Data> Validate<G, D> for Stream<G, D> {
+    fn validate<F: FnMut(&D)->Result<(), String>+'static>(&self, mut check: F) -> (Stream<G, D>, Stream<G, (D, String)>) {
+        self.map_result(move |record| match check(&record) {
+            Ok(()) => Ok(record),
+            Err(reason) => Err((record, reason)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dataflow::operators::{Capture, Input, Probe, Validate};
+    use crate::dataflow::operators::capture::Extract;
+
+    #[test]
+    fn records_land_by_validity_at_their_times() {
+        let (valid, invalid) = crate::execute_directly(|worker| {
+            let (mut input, probe, valid, invalid) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<i64>();
+                let (valid, invalid) = stream.validate(|x| if *x >= 0 { Ok(()) } else { Err(format!("{} is negative", x)) });
+                (input, valid.probe(), valid.capture(), invalid.capture())
+            });
+            input.send(1);
+            input.send(-2);
+            input.advance_to(3);
+            input.send(-4);
+            input.send(5);
+            input.close();
+            worker.step_while(|| !probe.done());
+            (valid, invalid)
+        });
+
+        assert_eq!(valid.extract(), vec![(0, vec![1]), (3, vec![5])]);
+        assert_eq!(invalid.extract(), vec![
+            (0, vec![(-2, "-2 is negative".to_owned())]),
+            (3, vec![(-4, "-4 is negative".to_owned())]),
+        ]);
+    }
+}
@@ -0,0 +1,155 @@
+//! Extension methods for `Stream` that report the input frontier as an explicit stream of watermarks.
+
+use crate::Data;
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::CapabilitySet;
+use crate::dataflow::operators::generic::OutputBuilder;
+use crate::dataflow::operators::generic::builder_rc::OperatorBuilder;
+use crate::dataflow::{Scope, Stream};
+
+/// Extension trait for `Stream`.
+pub trait WatermarkStream<G: Scope, D: Data> {
+    /// Passes records through unchanged on the first output, and emits the input frontier as watermarks on the second.
+    ///
+    /// Each time the input frontier advances, the operator emits each time newly in the frontier on
+    /// the second output, at that time. A watermark `t` indicates that the records of all times not
+    /// greater or equal to `t` have been emitted on the first output, as the operator passes along
+    /// its received records before it reports the frontier that follows them; records at `t` itself,
+    /// or later, may follow. The initial frontier produces no watermark, nor does the empty frontier
+    /// once the input is complete, although the watermark output then completes too. For totally
+    /// ordered timestamps the frontier holds one time, and the watermarks increase.
+    ///
+    /// Each worker emits watermarks for its own input frontier, which agree once the frontier has
+    /// been communicated between the workers.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{Input, WatermarkStream, Inspect};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     let mut input = worker.dataflow::<u64,_,_>(|scope| {
+    ///         let (input, stream) = scope.new_input::<char>();
+    ///         let (data, watermarks) = stream.watermark_stream();
+    ///         data.inspect_time(|time, x| println!("{:?} at {:?}", x, time));
+    ///         watermarks.inspect(|watermark| println!("complete before {:?}", watermark));
+    ///         input
+    ///     });
+    ///     for round in 0..5 {
+    ///         input.send('a');
+    ///         input.advance_to(round + 1);
+    ///         worker.step();
+    ///     }
+    /// });
+    /// ```
+    fn watermark_stream(&self) -> (Stream<G, D>, Stream<G, G::Timestamp>);
+}
+
+impl<G: Scope, D: Data> WatermarkStream<G, D> for Stream<G, D> {
+    fn watermark_stream(&self) -> (Stream<G, D>, Stream<G, G::Timestamp>) {
+        let mut builder = OperatorBuilder::new("WatermarkStream".to_owned(), self.scope());
+
+        let mut input = builder.new_input(self, Pipeline);
+        let (data_output, passed) = builder.new_output();
+        let (watermark_output, watermarks) = builder.new_output();
+
+        let mut data_output = OutputBuilder::from(data_output);
+        let mut watermark_output = OutputBuilder::from(watermark_output);
+
+        builder.build(move |mut capabilities| {
+            // Capabilities for the times of the input frontier, which the watermarks are emitted at.
+            let mut held = CapabilitySet::from_elem(capabilities.pop().unwrap());
+            move |frontiers| {
+                let mut data_handle = data_output.activate();
+                input.activate().for_each_time(|time, data| {
+                    let mut session = data_handle.session(&time);
+                    for datum in data { session.give_container(datum); }
+                });
+
+                let frontier = frontiers[0].frontier();
+                if held.iter().any(|cap| !frontier.less_equal(cap.time())) {
+                    let previous = held.iter().map(|cap| cap.time().clone()).collect::<Vec<_>>();
+                    held.downgrade(&frontier);
+                    let mut watermark_handle = watermark_output.activate();
+                    for cap in held.iter().filter(|cap| !previous.contains(cap.time())) {
+                        watermark_handle.session(cap).give(cap.time().clone());
+                    }
+                }
+            }
+        });
+
+        (passed, watermarks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dataflow::Scope;
+    use crate::dataflow::operators::{Capture, Concat, Input, Map, Probe, UnorderedInput, WatermarkStream};
+    use crate::dataflow::operators::capture::{Event, Extract};
+    use crate::order::Product;
+
+    #[test]
+    fn watermarks_follow_the_records_they_complete() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<char>();
+                let (data, watermarks) = stream.watermark_stream();
+                // Both outputs into one capture, to observe their relative order.
+                let merged = data.map(Ok).concat(&watermarks.map(Err));
+                (input, merged.probe(), merged.capture())
+            });
+            input.send('a');
+            input.advance_to(1);
+            input.send('b');
+            worker.step_while(|| probe.less_than(&1));
+            // Times 2 and 3 are jumped over.
+            input.advance_to(4);
+            worker.step_while(|| probe.less_than(&4));
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        let results = captured.iter().filter_map(|event| match event {
+            Event::Messages(time, data) => Some((time, data)),
+            Event::Progress(_) => None,
+        }).flat_map(|(time, data)| data.into_iter().map(move |datum| (time, datum))).collect::<Vec<_>>();
+        let watermarks = results.iter().filter_map(|(_time, datum)| datum.err()).collect::<Vec<_>>();
+        assert_eq!(watermarks, vec![1, 4]);
+        // Each watermark follows the records of the times it completes, and the records at later times may follow it.
+        for (position, (time, datum)) in results.iter().enumerate() {
+            if let Err(watermark) = datum {
+                assert_eq!(time, watermark);
+                assert!(results[position..].iter().all(|(later, _datum)| later >= watermark));
+            }
+        }
+        assert_eq!(results.iter().filter(|(_time, datum)| datum.is_ok()).count(), 2);
+    }
+
+    #[test]
+    fn each_new_time_of_a_partially_ordered_frontier_is_a_watermark() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut capabilities, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                scope.iterative::<u64,_,_>(|inner| {
+                    let ((_input, capability), stream) = inner.new_unordered_input::<()>();
+                    let (_data, watermarks) = stream.watermark_stream();
+                    // The frontier moves from the initial time to two incomparable times.
+                    let capabilities = vec![capability.delayed(&Product::new(0, 1)), capability.delayed(&Product::new(1, 0))];
+                    (capabilities, watermarks.probe(), watermarks.capture())
+                })
+            });
+            worker.step_while(|| probe.less_than(&Product::new(0, 1)));
+            // Releasing one time leaves the other in the frontier, which is not reported again.
+            capabilities.remove(0);
+            worker.step_while(|| probe.less_equal(&Product::new(0, 1)));
+            capabilities.clear();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        assert_eq!(captured.extract(), vec![
+            (Product::new(0, 1), vec![Product::new(0, 1)]),
+            (Product::new(1, 0), vec![Product::new(1, 0)]),
+        ]);
+    }
+}
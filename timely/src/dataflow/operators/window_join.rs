@@ -0,0 +1,182 @@
+//! Extension methods for `Stream` that join records of two streams within tumbling windows of times.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::rc::Rc;
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Exchange;
+use crate::dataflow::operators::Capability;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::generic::stash::Stash;
+use crate::dataflow::operators::sketch::hash_of;
+
+/// Extension trait for `Stream`.
+pub trait WindowJoin<G: Scope<Timestamp = u64>, D1: ExchangeData> {
+    /// Joins records of the two streams with equal keys whose times are in the same window of `size` times.
+    ///
+    /// The window starting at `start`, a multiple of `size`, holds the records at times from `start`
+    /// up to but excluding `start + size`. A record of `self` matches each record of `other` with the
+    /// same key in its window, and for each match the operator produces `join_fn(key, left, right)`
+    /// at the window's last time, `start + size - 1`. Both streams are exchanged by key.
+    ///
+    /// The operator retains the records of each open window, and once the frontiers of both inputs
+    /// have passed the window's last time, produces all the window's matches at once and discards
+    /// its records. Each window is thus joined exactly once, with all its records, and memory use is
+    /// bounded by the records of the open windows: one window, unless the inputs fall behind one
+    /// another. Each worker holds a capability for each of its open windows, and so the output
+    /// frontier lags by up to a window. Windows without matches produce nothing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Delay, WindowJoin, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     let clicks = vec![(7, 1), (7, 12)].to_stream(scope).delay(|(_, time), _| *time);
+    ///     let views = vec![(7, 5), (7, 25)].to_stream(scope).delay(|(_, time), _| *time);
+    ///     // Only the click at 1 and the view at 5 share a window.
+    ///     clicks.window_join(&views, 10, |click| click.0, |view| view.0, |user, click, view| (*user, click.1, view.1))
+    ///           .inspect_time(|time, x| assert_eq!((*time, *x), (9, (7, 1, 5))));
+    /// });
+    /// ```
+    fn window_join<K, D2, R, F1, F2, J>(&self, other: &Stream<G, D2>, size: u64, key_fn_l: F1, key_fn_r: F2, join_fn: J) -> Stream<G, R>
+    where
+        K: ExchangeData+Hash+Eq,
+        D2: ExchangeData,
+        R: Data,
+        F1: Fn(&D1)->K+'static,
+        F2: Fn(&D2)->K+'static,
+        J: FnMut(&K, &D1, &D2)->R+'static;
+}
+
+impl<G: Scope<Timestamp = u64>, D1: ExchangeData> WindowJoin<G, D1> for Stream<G, D1> {
+    fn window_join<K, D2, R, F1, F2, J>(&self, other: &Stream<G, D2>, size: u64, key_fn_l: F1, key_fn_r: F2, mut join_fn: J) -> Stream<G, R>
+    where
+        K: ExchangeData+Hash+Eq,
+        D2: ExchangeData,
+        R: Data,
+        F1: Fn(&D1)->K+'static,
+        F2: Fn(&D2)->K+'static,
+        J: FnMut(&K, &D1, &D2)->R+'static,
+    {
+        assert!(size > 0, "WindowJoin: size must be positive");
+        let key_fn_l = Rc::new(key_fn_l);
+        let route_l = Rc::clone(&key_fn_l);
+        let key_fn_r = Rc::new(key_fn_r);
+        let route_r = Rc::clone(&key_fn_r);
+        let pact_l = Exchange::new(move |datum: &D1| hash_of(&route_l(datum)));
+        let pact_r = Exchange::new(move |datum: &D2| hash_of(&route_r(datum)));
+        let last_of = move |time: &u64| (time - time % size).saturating_add(size - 1);
+
+        self.binary_frontier(other, pact_l, pact_r, "WindowJoin", move |_capability, _info| {
+            // For each open window, a capability for its last time, and the records of each input by key.
+            let mut windows = Stash::<Capability<u64>, (HashMap<K, Vec<D1>>, HashMap<K, Vec<D2>>)>::new();
+            move |(input1, frontier1), (input2, frontier2), output| {
+                input1.for_each_time(|time, data| {
+                    let last = last_of(time.time());
+                    let (lefts, _rights) = windows.get_or_insert_with(&last, || time.delayed(&last), Default::default);
+                    for datum in data.flat_map(|d| d.drain(..)) {
+                        lefts.entry(key_fn_l(&datum)).or_default().push(datum);
+                    }
+                });
+                input2.for_each_time(|time, data| {
+                    let last = last_of(time.time());
+                    let (_lefts, rights) = windows.get_or_insert_with(&last, || time.delayed(&last), Default::default);
+                    for datum in data.flat_map(|d| d.drain(..)) {
+                        rights.entry(key_fn_r(&datum)).or_default().push(datum);
+                    }
+                });
+
+                windows.release(|last| !frontier1.less_equal(last) && !frontier2.less_equal(last), |capability, (lefts, rights)| {
+                    let mut session = output.session(&capability);
+                    for (key, matching) in lefts {
+                        for right in rights.get(&key).into_iter().flatten() {
+                            for left in matching.iter() {
+                                session.give(join_fn(&key, left, right));
+                            }
+                        }
+                    }
+                });
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::Config;
+    use crate::dataflow::operators::{Capture, Input, Probe, WindowJoin};
+    use crate::dataflow::operators::capture::{Event, Extract};
+
+    #[test]
+    fn records_match_within_their_windows() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut lefts, mut rights, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (lefts, left_stream) = scope.new_input::<(char, u64)>();
+                let (rights, right_stream) = scope.new_input::<(char, u64)>();
+                let joined = left_stream.window_join(&right_stream, 5, |(key, _)| *key, |(key, _)| *key, |key, (_, left), (_, right)| (*key, *left, *right));
+                (lefts, rights, joined.probe(), joined.capture())
+            });
+            // Windows from 0 and from 5; the left record at 4 and the right record at 5 do not match.
+            for time in 0..10 {
+                lefts.advance_to(time);
+                rights.advance_to(time);
+                if [1, 4, 6].contains(&time) { lefts.send(('a', time)); }
+                if time == 2 { lefts.send(('b', time)); }
+                if [3, 5, 9].contains(&time) { rights.send(('a', time)); }
+                worker.step_while(|| probe.less_than(lefts.time()));
+                // The window from 0 is produced only once the frontiers pass its last time.
+                if time == 4 {
+                    assert!(captured.try_iter().all(|event| matches!(event, Event::Progress(_))));
+                }
+            }
+            lefts.close();
+            rights.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        assert_eq!(captured.extract(), vec![
+            (4, vec![('a', 1, 3), ('a', 4, 3)]),
+            (9, vec![('a', 6, 5), ('a', 6, 9)]),
+        ]);
+    }
+
+    #[test]
+    fn each_window_is_joined_once_across_workers() {
+        let (send, recv) = std::sync::mpsc::channel();
+        let send = Arc::new(Mutex::new(send));
+        crate::execute(Config::process(3), move |worker| {
+            let send = send.lock().unwrap().clone();
+            let index = worker.index() as u64;
+            worker.dataflow::<u64,_,_>(|scope| {
+                let (mut lefts, left_stream) = scope.new_input::<(u64, u64)>();
+                let (mut rights, right_stream) = scope.new_input::<(u64, u64)>();
+                left_stream.window_join(&right_stream, 10, |(key, _)| *key, |(key, _)| *key, |key, (_, left), (_, right)| (*key, *left, *right))
+                           .capture_into(send);
+                // Each worker has a left record of key 0 in each of two windows, and the last worker
+                // the right records.
+                lefts.send((0, index));
+                lefts.advance_to(10);
+                lefts.send((0, 10 + index));
+                if index == 2 {
+                    rights.send((0, 100));
+                    rights.advance_to(15);
+                    rights.send((0, 200));
+                }
+            });
+        }).unwrap();
+
+        assert_eq!(recv.extract(), vec![
+            (9, vec![(0, 0, 100), (0, 1, 100), (0, 2, 100)]),
+            (19, vec![(0, 10, 200), (0, 11, 200), (0, 12, 200)]),
+        ]);
+    }
+}
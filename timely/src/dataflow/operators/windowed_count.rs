@@ -0,0 +1,193 @@
+//! Extension methods for `Stream` that count the records of tumbling windows of times, with early results.
+
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::Capability;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::generic::stash::Stash;
+
+/// Whether a window count is a partial result for an open window, or the result for a closed one.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Firing {
+    /// The count of an open window so far, which later results for the window supersede.
+    Early,
+    /// The count of a closed window, the last result for it.
+    Final,
+}
+
+/// Extension trait for `Stream`.
+pub trait WindowedCount<G: Scope, D: Data> {
+    /// Counts the records of each window of `size` times, as `(start, count, firing)`, early every `early_interval` and finally once it closes.
+    ///
+    /// The window starting at `start`, a multiple of `size`, holds the records at times from `start`
+    /// up to but excluding `start + size`, and each of its results is produced at its last time,
+    /// `start + size - 1`. Using a timer activation, every `early_interval` the operator produces an
+    /// [`Firing::Early`] count for each open window whose count has changed since its last result.
+    /// Once the input frontier has passed the last time of a window, the operator produces its
+    /// [`Firing::Final`] count, which is its last result and supersedes any early ones. A window
+    /// closes in the scheduling that sees its frontier pass, before any early results for it are due.
+    /// Windows without records produce nothing.
+    ///
+    /// Windows are counted at each worker, as the input is not exchanged, and the results of the
+    /// workers for a window sum to its count. Each worker holds a capability for each of its open
+    /// windows, and so the output frontier lags by up to a window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use timely::dataflow::operators::{ToStream, Delay, WindowedCount, Inspect};
+    /// use timely::dataflow::operators::windowed_count::Firing;
+    ///
+    /// timely::example(|scope| {
+    ///     (0..25u64).to_stream(scope)
+    ///               .delay(|x, _| *x)
+    ///               .windowed_count(10, Duration::from_millis(100))
+    ///               .inspect(|(start, count, firing)| {
+    ///                   if *firing == Firing::Final { assert_eq!(*count, if *start < 20 { 10 } else { 5 }); }
+    ///               });
+    /// });
+    /// ```
+    fn windowed_count(&self, size: u64, early_interval: Duration) -> Stream<G, (u64, u64, Firing)>;
+}
+
+impl<G: Scope<Timestamp = u64>, D: Data> WindowedCount<G, D> for Stream<G, D> {
+    fn windowed_count(&self, size: u64, early_interval: Duration) -> Stream<G, (u64, u64, Firing)> {
+        assert!(size > 0, "WindowedCount: size must be positive");
+        let scope = self.scope();
+        self.unary_frontier(Pipeline, "WindowedCount", move |_capability, info| {
+            let activator = scope.activator_for(info.address);
+            // For each open window, a capability for its last time, its count, and its count when last produced.
+            let mut windows = Stash::<Capability<u64>, (u64, Option<u64>)>::new();
+            // When early results are next due, if the timer is set.
+            let mut due = None;
+            move |(input, frontier), output| {
+                input.for_each_time(|time, data| {
+                    let last = (time.time() - time.time() % size).saturating_add(size - 1);
+                    let (count, _produced) = windows.get_or_insert_with(&last, || time.delayed(&last), || (0, None));
+                    *count += data.map(|d| d.len() as u64).sum::<u64>();
+                });
+
+                windows.release(|last| !frontier.less_equal(last), |capability, (count, _produced)| {
+                    output.session(&capability).give((start_of(*capability.time(), size), count, Firing::Final));
+                });
+
+                let now = Instant::now();
+                if due.is_some_and(|due| now >= due) {
+                    for (capability, (count, produced)) in windows.iter_mut() {
+                        if *produced != Some(*count) {
+                            output.session(capability).give((start_of(*capability.time(), size), *count, Firing::Early));
+                            *produced = Some(*count);
+                        }
+                    }
+                    due = None;
+                }
+                // The timer runs only while windows are open.
+                if due.is_none() && !windows.is_empty() {
+                    due = Some(now + early_interval);
+                    activator.activate_after(early_interval);
+                }
+            }
+        })
+    }
+}
+
+/// The start of the window of `size` times whose last time is `last`.
+fn start_of(last: u64, size: u64) -> u64 {
+    last - last % size
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::dataflow::operators::{Capture, Input, Probe, ToStream, WindowedCount};
+    use crate::dataflow::operators::capture::Event;
+    use super::Firing;
+
+    /// The longest the timer test waits for the timer, in steps of at most one interval each.
+    const MAX_STEPS: usize = 10_000;
+
+    #[test]
+    fn early_counts_precede_the_final_count() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<u64>();
+                // Without an interval, early results are due at each scheduling.
+                let counts = stream.windowed_count(10, Duration::ZERO);
+                (input, counts.probe(), counts.capture())
+            });
+            input.send(1);
+            input.send(2);
+            for _ in 0..3 { worker.step(); }
+            // Unchanged counts are not produced again, and so one early result appears per change.
+            input.advance_to(5);
+            input.send(3);
+            for _ in 0..3 { worker.step(); }
+            // The record at 12 reaches the operator before the input frontier does, and so the window
+            // from 10 is produced early before either window closes.
+            input.advance_to(12);
+            input.send(12);
+            input.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        let results = captured.iter().filter_map(|event| match event {
+            Event::Messages(time, data) => Some((time, data)),
+            Event::Progress(_) => None,
+        }).collect::<Vec<_>>();
+        assert_eq!(results, vec![
+            (9, vec![(0, 2, Firing::Early)]),
+            (9, vec![(0, 3, Firing::Early)]),
+            (19, vec![(10, 1, Firing::Early)]),
+            (9, vec![(0, 3, Firing::Final)]),
+            (19, vec![(10, 1, Firing::Final)]),
+        ]);
+    }
+
+    #[test]
+    fn early_counts_follow_the_timer() {
+        crate::execute_directly(|worker| {
+            let interval = Duration::from_millis(1);
+            let (mut input, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<u64>();
+                let counts = stream.windowed_count(10, interval);
+                (input, counts.probe(), counts.capture())
+            });
+            input.send(4);
+            input.advance_to(1);
+            // Without further input, the timer produces the early result of the open window.
+            let mut results = Vec::new();
+            for _ in 0 .. MAX_STEPS {
+                worker.step_or_park(Some(interval));
+                results.extend(captured.try_iter().filter_map(|event| match event {
+                    Event::Messages(time, data) => Some((time, data)),
+                    Event::Progress(_) => None,
+                }));
+                if !results.is_empty() { break; }
+            }
+            assert_eq!(results, vec![(9, vec![(0, 1, Firing::Early)])]);
+            // The open window holds the frontier at its last time.
+            assert!(probe.less_equal(&9));
+            input.close();
+            worker.step_while(|| !probe.done());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "size must be positive")]
+    fn zero_size_panics() {
+        crate::example(|scope| {
+            (0..10u64).to_stream(scope).windowed_count(0, Duration::ZERO);
+        });
+    }
+}
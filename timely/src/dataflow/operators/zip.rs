@@ -0,0 +1,130 @@
+//! Extension methods for `Stream` that pair the records of two streams by their position at each time.
+
+use std::collections::VecDeque;
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::Capability;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::generic::stash::Stash;
+
+/// What to do with the records of a time left without a partner once the time completes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Unpaired {
+    /// Discard the records.
+    Drop,
+    /// Panic, reporting the time and how many records of each input were left.
+    Panic,
+}
+
+/// Extension trait for `Stream`.
+pub trait ZipByIndex<G: Scope, D1: Data> {
+    /// Pairs the `n`th record of `self` with the `n`th record of `other` at each time.
+    ///
+    /// The records of each time are counted in the order in which the operator receives them, at
+    /// each worker, and a pair is produced at the time as soon as both of its records have arrived.
+    /// Records of the input that is ahead are buffered until their partners arrive, and so memory is
+    /// bounded by how far one input leads the other. Once the frontiers of both inputs have passed a
+    /// time, the records of the time left without a partner are handled as `unpaired` says.
+    ///
+    /// Records are paired at each worker, as the inputs are not exchanged, and the order of records
+    /// is only meaningful when both streams are produced in a known order, for example by the same
+    /// workers from co-produced sources.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, ZipByIndex, Inspect};
+    /// use timely::dataflow::operators::zip::Unpaired;
+    ///
+    /// timely::example(|scope| {
+    ///     let names = vec!["a", "b", "c"].into_iter().to_stream(scope);
+    ///     (0..4u64).to_stream(scope)
+    ///              .zip_by_index(&names, Unpaired::Drop)
+    ///              .inspect(|pair| println!("paired: {:?}", pair));
+    /// });
+    /// ```
+    fn zip_by_index<D2: Data>(&self, other: &Stream<G, D2>, unpaired: Unpaired) -> Stream<G, (D1, D2)>;
+}
+
+impl<G: Scope, D1: Data> ZipByIndex<G, D1> for Stream<G, D1> {
+    fn zip_by_index<D2: Data>(&self, other: &Stream<G, D2>, unpaired: Unpaired) -> Stream<G, (D1, D2)> {
+        self.binary_frontier(other, Pipeline, Pipeline, "ZipByIndex", move |_capability, _info| {
+            // For each incomplete time, a capability for it and the records of the input ahead.
+            let mut pending = Stash::<Capability<G::Timestamp>, (VecDeque<D1>, VecDeque<D2>)>::new();
+            move |(input1, frontier1), (input2, frontier2), output| {
+                input1.for_each_time(|time, data| {
+                    let (lefts, rights) = pending.get_or_retain(&time, Default::default);
+                    lefts.extend(data.flat_map(|d| d.drain(..)));
+                    output.session(&time).give_iterator(pairs(lefts, rights));
+                });
+                input2.for_each_time(|time, data| {
+                    let (lefts, rights) = pending.get_or_retain(&time, Default::default);
+                    rights.extend(data.flat_map(|d| d.drain(..)));
+                    output.session(&time).give_iterator(pairs(lefts, rights));
+                });
+                pending.release(|time| !frontier1.less_equal(time) && !frontier2.less_equal(time), |capability, (lefts, rights)| {
+                    if unpaired == Unpaired::Panic && (!lefts.is_empty() || !rights.is_empty()) {
+                        panic!("ZipByIndex: {} and {} records left unpaired at {:?}", lefts.len(), rights.len(), capability.time());
+                    }
+                });
+            }
+        })
+    }
+}
+
+/// Removes from the fronts of `lefts` and `rights` as many records as both have, in pairs.
+fn pairs<'a, D1, D2>(lefts: &'a mut VecDeque<D1>, rights: &'a mut VecDeque<D2>) -> impl Iterator<Item = (D1, D2)> + 'a {
+    let count = lefts.len().min(rights.len());
+    lefts.drain(..count).zip(rights.drain(..count))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dataflow::operators::{Capture, Input, Probe, ToStream, ZipByIndex};
+    use crate::dataflow::operators::capture::Extract;
+    use super::Unpaired;
+
+    #[test]
+    fn records_are_paired_by_position_at_each_time() {
+        let captured = crate::execute_directly(|worker| {
+            let (mut lefts, mut rights, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+                let (lefts, left_stream) = scope.new_input::<u64>();
+                let (rights, right_stream) = scope.new_input::<char>();
+                let zipped = left_stream.zip_by_index(&right_stream, Unpaired::Drop);
+                (lefts, rights, zipped.probe(), zipped.capture())
+            });
+            // The left input leads at time 0, and the right input catches up in a later invocation.
+            for x in 0..3 { lefts.send(x); }
+            worker.step();
+            rights.send('a');
+            worker.step();
+            rights.send('b');
+            rights.send('c');
+            // At time 1 the right input has a record left without a partner, which is dropped.
+            lefts.advance_to(1);
+            rights.advance_to(1);
+            lefts.send(10);
+            rights.send('x');
+            rights.send('y');
+            lefts.close();
+            rights.close();
+            worker.step_while(|| !probe.done());
+            captured
+        });
+
+        assert_eq!(captured.extract(), vec![
+            (0, vec![(0, 'a'), (1, 'b'), (2, 'c')]),
+            (1, vec![(10, 'x')]),
+        ]);
+    }
+
+    #[test]
+    #[should_panic(expected = "1 and 0 records left unpaired at 0")]
+    fn unpaired_records_can_panic() {
+        crate::example(|scope| {
+            let rights = vec!['a', 'b'].into_iter().to_stream(scope);
+            (0..3u64).to_stream(scope).zip_by_index(&rights, Unpaired::Panic);
+        });
+    }
+}
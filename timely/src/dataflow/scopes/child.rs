@@ -3,7 +3,7 @@
 use std::rc::Rc;
 use std::cell::RefCell;
 
-use crate::communication::{Exchangeable, Push, Pull};
+use crate::communication::{Exchangeable, Push, Pull, Topology};
 use crate::communication::allocator::thread::{ThreadPusher, ThreadPuller};
 use crate::scheduling::Scheduler;
 use crate::scheduling::activate::Activations;
@@ -48,6 +48,18 @@ where
     pub fn index(&self) -> usize { self.parent.index() }
     /// The total number of workers in the computation.
     pub fn peers(&self) -> usize { self.parent.peers() }
+    /// The worker-unique identifier of the scope, as reported in its logged events.
+    ///
+    /// Progress events, and the operators and channels of the scope, carry this identifier.
+    pub fn identifier(&self) -> usize { self.subgraph.borrow().identifier() }
+    /// Labels the operators and channels subsequently built in this scope, and in scopes nested in it.
+    ///
+    /// The label appears in the logged `OperatesEvent` and `ChannelsEvent` events of the operators
+    /// and channels, and so allows filtering logged events by region, including the `MessagesEvent`
+    /// events of the labeled channels. Scopes take the label of their parent, until they set their own.
+    pub fn set_label(&self, label: &str) {
+        self.subgraph.borrow_mut().label = Some(label.into());
+    }
 }
 
 impl<G, T> AsWorker for Child<'_, G, T>
@@ -58,6 +70,8 @@ where
     fn config(&self) -> &Config { self.parent.config() }
     fn index(&self) -> usize { self.parent.index() }
     fn peers(&self) -> usize { self.parent.peers() }
+    fn topology(&self) -> Topology { self.parent.topology() }
+    fn exchange_by_pipeline(&self) -> bool { self.parent.exchange_by_pipeline() }
     fn allocate<D: Exchangeable>(&mut self, identifier: usize, address: Rc<[usize]>) -> (Vec<Box<dyn Push<D>>>, Box<dyn Pull<D>>) {
         self.parent.allocate(identifier, address)
     }
@@ -76,6 +90,12 @@ where
     fn log_register(&self) -> Option<::std::cell::RefMut<'_, crate::logging_core::Registry>> {
         self.parent.log_register()
     }
+    fn label(&self) -> Option<Rc<str>> {
+        self.subgraph.borrow().label.clone()
+    }
+    fn state_registry(&self) -> ::std::cell::RefMut<'_, crate::worker::StateRegistry> {
+        self.parent.state_registry()
+    }
 }
 
 impl<G, T> Scheduler for Child<'_, G, T>
@@ -139,6 +159,7 @@ where
         let summary_logging  = self.logger_for(&format!("timely/summary/{type_name}"));
 
         let subscope = RefCell::new(SubgraphBuilder::new_from(path, identifier, self.logging(), summary_logging, name));
+        subscope.borrow_mut().label = self.label();
         let result = {
             let mut builder = Child {
                 subgraph: &subscope,
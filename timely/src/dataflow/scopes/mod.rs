@@ -192,4 +192,37 @@ pub trait Scope: ScopeParent {
         self.scoped::<<Self as ScopeParent>::Timestamp,R,F>(name, func)
     }
 
+    /// Creates a dataflow region with the same timestamp, whose operators and channels are labeled `label`.
+    ///
+    /// This method is a specialization of `region_named`, which names the region `label`, and labels
+    /// the operators and channels built in it, and in scopes nested in it, as by [`Child::set_label`].
+    /// The label appears in the logged events of the operators and channels, which allows filtering
+    /// logged events by region, for example into ingestion, transformation, and output.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::Scope;
+    /// use timely::dataflow::operators::{Input, Enter, Leave, Map};
+    ///
+    /// timely::execute_from_args(std::env::args(), |worker| {
+    ///     // must specify types as nothing else drives inference.
+    ///     let input = worker.dataflow::<u64,_,_>(|child1| {
+    ///         let (input, stream) = child1.new_input::<String>();
+    ///         let output = child1.region_labeled("transform", |child2| {
+    ///             stream.enter(child2).map(|x| x.len()).leave()
+    ///         });
+    ///         input
+    ///     });
+    /// });
+    /// ```
+    fn region_labeled<R, F>(&mut self, label: &str, func: F) -> R
+    where
+        F: FnOnce(&mut Child<Self, <Self as ScopeParent>::Timestamp>) -> R,
+    {
+        self.region_named(label, |child| {
+            child.set_label(label);
+            func(child)
+        })
+    }
+
 }
@@ -10,6 +10,7 @@ use crate::communication::Push;
 use crate::dataflow::Scope;
 use crate::dataflow::channels::pushers::tee::TeeHelper;
 use crate::dataflow::channels::Message;
+use crate::dataflow::channels::pact::PactKind;
 use std::fmt::{self, Debug};
 
 // use dataflow::scopes::root::loggers::CHANNELS_Q;
@@ -50,8 +51,14 @@ impl<S: Scope, C> StreamCore<S, C> {
     /// Connects the stream to a destination.
     ///
     /// The destination is described both by a `Target`, for progress tracking information, and a `P: Push` where the
-    /// records should actually be sent. The identifier is unique to the edge and is used only for logging purposes.
+    /// records should actually be sent. The identifier is unique to the edge and is used only for logging purposes,
+    /// where the pattern in which the edge moves data is reported as unknown.
     pub fn connect_to<P: Push<Message<S::Timestamp, C>>+'static>(&self, target: Target, pusher: P, identifier: usize) {
+        self.connect_to_with_kind(target, pusher, identifier, PactKind::Unknown)
+    }
+
+    /// Connects the stream to a destination, as `connect_to`, and logs `kind` as the pattern in which the edge moves data.
+    pub fn connect_to_with_kind<P: Push<Message<S::Timestamp, C>>+'static>(&self, target: Target, pusher: P, identifier: usize, kind: PactKind) {
 
         let mut logging = self.scope().logging();
         logging.as_mut().map(|l| l.log(crate::logging::ChannelsEvent {
@@ -60,6 +67,8 @@ impl<S: Scope, C> StreamCore<S, C> {
             source: (self.name.node, self.name.port),
             target: (target.node, target.port),
             typ: std::any::type_name::<C>().to_string(),
+            pact: kind,
+            label: self.scope.label().as_deref().map(str::to_owned),
         }));
 
         self.scope.add_edge(self.name, target);
@@ -76,6 +85,81 @@ impl<S: Scope, C> StreamCore<S, C> {
 
     /// Allows the assertion of a container type, for the benefit of type inference.
     pub fn container<C2>(self) -> StreamCore<S, C2> where Self: AsStream<S, C2> { self.as_stream() }
+
+    /// A handle through which consumers can be attached to the stream once its dataflow is running.
+    ///
+    /// See [`AttachHandle::attach_consumer`].
+    pub fn attach_handle(&self) -> AttachHandle<S::Timestamp, C> {
+        AttachHandle { ports: self.ports.clone() }
+    }
+}
+
+/// Attaches consumers to a stream of a running dataflow.
+///
+/// Obtained from [`StreamCore::attach_handle`] while constructing the dataflow, the handle can be
+/// kept and used between steps of the worker, for example to start observing a stream of a
+/// long-running computation without restarting it.
+pub struct AttachHandle<T, C> {
+    ports: TeeHelper<T, C>,
+}
+
+impl<T: 'static, C: 'static> AttachHandle<T, C> {
+    /// Attaches `logic` as a consumer of the stream, called with the time and contents of each message.
+    ///
+    /// The consumer observes the messages sent on the stream after it is attached. It is not an
+    /// operator of the dataflow, and takes no part in progress tracking: it holds back no frontier,
+    /// observes none, and cannot outlive the operator producing the stream. This is what makes it
+    /// safe to add at any time the stream is not sending, which includes any time between steps of
+    /// the worker; attaching a consumer from within a consumer of the same stream panics.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    /// use timely::dataflow::operators::{Input, Probe};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     let (mut input, probe, handle) = worker.dataflow::<u64,_,_>(|scope| {
+    ///         let (input, stream) = scope.new_input::<u64>();
+    ///         (input, stream.probe(), stream.attach_handle())
+    ///     });
+    ///
+    ///     let seen = Rc::new(RefCell::new(Vec::new()));
+    ///     for round in 0..10 {
+    ///         if round == 5 {
+    ///             let seen = Rc::clone(&seen);
+    ///             handle.attach_consumer(move |_time, data: &Vec<u64>| seen.borrow_mut().extend(data.iter().cloned()));
+    ///         }
+    ///         input.send(round);
+    ///         input.advance_to(round + 1);
+    ///         worker.step_while(|| probe.less_than(input.time()));
+    ///     }
+    ///     assert_eq!(*seen.borrow(), vec![5, 6, 7, 8, 9]);
+    /// });
+    /// ```
+    pub fn attach_consumer<F: FnMut(&T, &C)+'static>(&self, logic: F) {
+        self.ports.add_pusher(Consumer { logic, phantom: std::marker::PhantomData });
+    }
+}
+
+impl<T, C> Clone for AttachHandle<T, C> {
+    fn clone(&self) -> Self {
+        AttachHandle { ports: self.ports.clone() }
+    }
+}
+
+/// Forwards messages by reference to a consumer, leaving them in place.
+struct Consumer<T, C, F> {
+    logic: F,
+    phantom: std::marker::PhantomData<(T, C)>,
+}
+
+impl<T, C, F: FnMut(&T, &C)> Push<Message<T, C>> for Consumer<T, C, F> {
+    fn push(&mut self, message: &mut Option<Message<T, C>>) {
+        if let Some(message) = message {
+            (self.logic)(&message.time, &message.data);
+        }
+    }
 }
 
 /// A type that can be translated to a [StreamCore].
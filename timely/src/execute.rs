@@ -159,6 +159,9 @@ where
     while worker.has_dataflows() {
         worker.step_or_park(None);
     }
+    if let Some(panic) = worker.operator_panic() {
+        panic!("{}", panic);
+    }
     result
 }
 
@@ -294,7 +297,8 @@ pub fn execute_from_args<I, T, F>(iter: I, func: F) -> Result<WorkerGuards<T>,St
 
 /// Executes a timely dataflow from supplied allocators and logging.
 ///
-/// Refer to [`execute`](execute()) for more details.
+/// Refer to [`execute`](execute()) for more details, and to [`execute_with_allocators`] for
+/// allocators that need no other handles kept alive.
 ///
 /// ```rust
 /// use timely::dataflow::operators::{ToStream, Inspect};
@@ -325,6 +329,46 @@ where
         while worker.has_dataflows() {
             worker.step_or_park(None);
         }
+        if let Some(panic) = worker.operator_panic() {
+            panic!("{}", panic);
+        }
         result
     })
 }
+
+/// Executes a timely dataflow on workers built from custom allocators.
+///
+/// Each element of `builders` is moved to a new worker thread, where it builds the allocator
+/// through which that worker communicates; the workers' indices and peers are those reported by
+/// their allocators. This is the seam through which a computation can run on a transport other
+/// than those configured by [`CommunicationConfig`](crate::CommunicationConfig), for example an
+/// in-memory test harness. Allocators that rely on other threads, such as network senders and
+/// receivers, should instead use [`execute_from`], which keeps their handles alive until the
+/// computation completes.
+///
+/// Refer to [`execute`](execute()) for more details.
+///
+/// # Examples
+/// ```rust
+/// use timely::dataflow::operators::{ToStream, Inspect};
+/// use timely::communication::allocator::thread::ThreadBuilder;
+/// use timely::WorkerConfig;
+///
+/// timely::execute_with_allocators(vec![ThreadBuilder], WorkerConfig::default(), |worker| {
+///     worker.dataflow::<(),_,_>(|scope| {
+///         (0..10).to_stream(scope)
+///                .inspect(|x| println!("seen: {:?}", x));
+///     })
+/// }).unwrap();
+/// ```
+pub fn execute_with_allocators<A, T, F>(
+    builders: Vec<A>,
+    worker_config: WorkerConfig,
+    func: F,
+) -> Result<WorkerGuards<T>, String>
+where
+    A: AllocateBuilder+'static,
+    T: Send+'static,
+    F: Fn(&mut Worker<<A as AllocateBuilder>::Allocator>)->T+Send+Sync+'static {
+    execute_from(builders, Box::new(()), worker_config, func)
+}
@@ -56,7 +56,7 @@
 
 #![forbid(missing_docs)]
 
-pub use execute::{execute, execute_directly, example};
+pub use execute::{execute, execute_directly, execute_with_allocators, example};
 #[cfg(feature = "getopts")]
 pub use execute::execute_from_args;
 pub use order::PartialOrder;
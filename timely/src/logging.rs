@@ -15,13 +15,20 @@ pub type TimelySummaryEventBuilder<TS> = CapacityContainerBuilder<Vec<(Duration,
 /// Logger for timely dataflow operator summary events (the "timely/summary/*" log streams).
 pub type TimelySummaryLogger<TS> = crate::logging_core::Logger<TimelySummaryEventBuilder<TS>>;
 
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
 use std::time::Duration;
 use columnar::Columnar;
 use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
 
-use crate::Container;
+use crate::{Container, Data};
 use crate::container::CapacityContainerBuilder;
-use crate::dataflow::operators::capture::{Event, EventPusher};
+use crate::dataflow::{Scope, Stream};
+use crate::dataflow::channels::pact::PactKind;
+use crate::dataflow::operators::Delay;
+use crate::dataflow::operators::capture::{Event, EventPusher, EventReader, Replay};
 use crate::progress::operate::Connectivity;
 
 /// Logs events as a timely stream, with progress statements.
@@ -59,8 +66,62 @@ impl<P, C> Drop for BatchLogger<P, C> where P: EventPusher<Duration, C> {
     }
 }
 
+/// Replays logged events captured to `readers` as a timely stream.
+///
+/// Each reader should present the bytes written by an [`EventWriter`](crate::dataflow::operators::capture::EventWriter) behind a [`BatchLogger`],
+/// for example one registered for the `"timely"` log stream with `E` set to [`TimelyEvent`].
+/// Each reader contributes the events of one logging worker; readers can be distributed among
+/// the workers of the analysis dataflow in any way.
+///
+/// The logger batches events, and a batch is timestamped by the time of the previous flush. The
+/// replayed records are delayed from their batch timestamps to their logged times, so that the
+/// logical time of each `(time, event)` record is `time` and downstream operators observe the
+/// logged execution in time order.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use timely::dataflow::operators::Inspect;
+/// use timely::dataflow::operators::capture::{Event, EventPusher, EventWriter};
+/// use timely::logging::{replay_logged_events, TimelyEvent};
+///
+/// let path = std::env::temp_dir().join(format!("timely-replay-doctest-{}", std::process::id()));
+///
+/// // Write a log file, as a `BatchLogger` would.
+/// let mut writer = EventWriter::new(std::fs::File::create(&path).unwrap());
+/// let event = TimelyEvent::Text("hello".to_string());
+/// writer.push(Event::Messages(Duration::ZERO, vec![(Duration::from_secs(1), event)]));
+/// writer.push(Event::Progress(vec![(Duration::ZERO, -1)]));
+/// drop(writer);
+///
+/// timely::execute_directly(move |worker| {
+///     worker.dataflow::<Duration,_,_>(|scope| {
+///         let file = std::fs::File::open(&path).unwrap();
+///         replay_logged_events::<_, TimelyEvent, _, _>(scope, Some(file))
+///             .inspect_time(|time, (logged, _event)| assert_eq!(time, logged));
+///     });
+/// });
+/// ```
+pub fn replay_logged_events<S, E, R, I>(scope: &mut S, readers: I) -> Stream<S, (Duration, E)>
+where
+    S: Scope<Timestamp = Duration>,
+    E: Data + DeserializeOwned,
+    R: std::io::Read + 'static,
+    I: IntoIterator<Item = R>,
+{
+    readers
+        .into_iter()
+        .map(EventReader::<Duration, Vec<(Duration, E)>, R>::new)
+        .collect::<Vec<_>>()
+        .replay_into(scope)
+        .delay(|(logged, _), batch| std::cmp::max(*logged, *batch))
+}
+
 #[derive(Serialize, Deserialize, Columnar, Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
 /// The creation of an `Operate` implementor.
+///
+/// The event may gain fields, and so cannot be constructed or destructured exhaustively outside this crate.
+#[non_exhaustive]
 pub struct OperatesEvent {
     /// Worker-unique identifier for the operator.
     pub id: usize,
@@ -68,6 +129,12 @@ pub struct OperatesEvent {
     pub addr: Vec<usize>,
     /// A helpful name.
     pub name: String,
+    /// The label of the scope containing the operator, if any.
+    pub label: Option<String>,
+    /// The name of each input port, in order, if named; there is one entry per input.
+    pub inputs: Vec<Option<String>>,
+    /// The name of each output port, in order, if named; there is one entry per output.
+    pub outputs: Vec<Option<String>>,
 }
 
 
@@ -82,6 +149,9 @@ pub struct OperatesSummaryEvent<TS> {
 
 #[derive(Serialize, Deserialize, Columnar, Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
 /// The creation of a channel between operators.
+///
+/// The event may gain fields, and so cannot be constructed or destructured exhaustively outside this crate.
+#[non_exhaustive]
 pub struct ChannelsEvent {
     /// Worker-unique identifier for the channel
     pub id: usize,
@@ -93,6 +163,12 @@ pub struct ChannelsEvent {
     pub target: (usize, usize),
     /// The type of data on the channel, as a string.
     pub typ: String,
+    /// The pattern in which the channel moves data between workers.
+    pub pact: PactKind,
+    /// The label of the scope containing the channel, if any.
+    ///
+    /// Messages are not labeled themselves, and take the label of their channel.
+    pub label: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -295,3 +371,176 @@ impl From<InputEvent> for TimelyEvent {
 impl From<ParkEvent> for TimelyEvent {
     fn from(v: ParkEvent) -> TimelyEvent { TimelyEvent::Park(v) }
 }
+
+/// Per-operator scheduling times and the channels between operators, from logged timely events, to suggest operators to fuse.
+///
+/// Every scheduling of an operator costs the worker some overhead, which dominates for operators
+/// that do little work, and a chain of such operators can often be written as one operator that
+/// does the work of all of them. The hints identify candidates: pairs of operators connected by a
+/// pipeline channel, which moves data without leaving the worker, that both take less than a
+/// threshold per scheduling on average. They are advisory, and fusing the operators, where their
+/// logic allows it, remains the user's job.
+///
+/// The hints observe the events of the `"timely"` log stream, and so must be registered, for
+/// example with [`FusionHints::register`], before the dataflows they analyze are constructed, so
+/// that they see the creation of operators and channels. Scheduling times are those between the
+/// logged start and stop of each scheduling, and so include the logging itself, which is the same
+/// for all operators. Operators that contain others, such as regions and iterative scopes, are not
+/// candidates, as their schedulings include those of their operators.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use timely::dataflow::operators::{Exchange, Filter, Input, Map, Probe};
+/// use timely::logging::FusionHints;
+///
+/// timely::execute_directly(|worker| {
+///     let hints = FusionHints::register(&mut worker.log_register().unwrap());
+///     let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+///         let (input, stream) = scope.new_input::<u64>();
+///         let probe = stream.map(|x| x + 1)
+///                           .filter(|x| x % 2 == 0)
+///                           .exchange(|x| *x)
+///                           .probe();
+///         (input, probe)
+///     });
+///     for round in 0..10 {
+///         input.send(round);
+///         input.advance_to(round + 1);
+///         worker.step_while(|| probe.less_than(input.time()));
+///     }
+///     print!("{}", hints.borrow().report(Duration::from_millis(1)));
+/// });
+/// ```
+#[derive(Debug, Default)]
+pub struct FusionHints {
+    /// For each operator, its address and name.
+    operators: HashMap<usize, (Vec<usize>, String)>,
+    /// The channels between operators, as their scope's address, source and target indexes, and pattern.
+    channels: Vec<(Vec<usize>, usize, usize, PactKind)>,
+    /// For each operator being scheduled, when its scheduling started.
+    started: HashMap<usize, Duration>,
+    /// For each operator, its number of schedulings and their total duration.
+    schedulings: HashMap<usize, (u64, Duration)>,
+}
+
+/// Two operators connected by a pipeline channel, both cheap to schedule, that could be fused into one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FusionCandidate {
+    /// The identifier of the operator producing the channel's data.
+    pub upstream: usize,
+    /// The name of the upstream operator.
+    pub upstream_name: String,
+    /// The mean duration of a scheduling of the upstream operator.
+    pub upstream_mean: Duration,
+    /// The identifier of the operator consuming the channel's data.
+    pub downstream: usize,
+    /// The name of the downstream operator.
+    pub downstream_name: String,
+    /// The mean duration of a scheduling of the downstream operator.
+    pub downstream_mean: Duration,
+}
+
+impl FusionHints {
+    /// Creates hints that have observed no events.
+    pub fn new() -> Self { Self::default() }
+
+    /// Registers hints that observe the `"timely"` log stream of `registry`, replacing any logger registered for it.
+    pub fn register(registry: &mut crate::logging_core::Registry) -> Rc<RefCell<Self>> {
+        let hints = Rc::new(RefCell::new(Self::new()));
+        let observer = Rc::clone(&hints);
+        registry.insert::<TimelyEventBuilder, _>("timely", move |_time, data| {
+            if let Some(data) = data {
+                let mut observing = observer.borrow_mut();
+                for (time, event) in data.iter() {
+                    observing.observe(time, event);
+                }
+            }
+        });
+        hints
+    }
+
+    /// Observes a logged event, at the logged time `time`.
+    pub fn observe(&mut self, time: &Duration, event: &TimelyEvent) {
+        match event {
+            TimelyEvent::Operates(operates) => {
+                self.operators.insert(operates.id, (operates.addr.clone(), operates.name.clone()));
+            }
+            TimelyEvent::Channels(channels) => {
+                self.channels.push((channels.scope_addr.clone(), channels.source.0, channels.target.0, channels.pact));
+            }
+            TimelyEvent::Schedule(schedule) => match schedule.start_stop {
+                StartStop::Start => { self.started.insert(schedule.id, *time); }
+                StartStop::Stop => {
+                    if let Some(start) = self.started.remove(&schedule.id) {
+                        let (count, total) = self.schedulings.entry(schedule.id).or_default();
+                        *count += 1;
+                        *total += time.saturating_sub(start);
+                    }
+                }
+            },
+            _ => { }
+        }
+    }
+
+    /// The mean duration of a scheduling of the operator `id`, if it has been scheduled.
+    pub fn mean(&self, id: usize) -> Option<Duration> {
+        let (count, total) = self.schedulings.get(&id)?;
+        Some(*total / u32::try_from(*count).unwrap_or(u32::MAX))
+    }
+
+    /// The pairs of operators connected by a pipeline channel whose mean scheduling durations are both less than `threshold`.
+    ///
+    /// Candidates are listed in order of their upstream and then downstream identifiers, once for
+    /// each pair of operators, however many channels connect them.
+    pub fn candidates(&self, threshold: Duration) -> Vec<FusionCandidate> {
+        let identifiers = self.operators.iter().map(|(id, (addr, _name))| (addr.as_slice(), *id)).collect::<HashMap<_, _>>();
+        // Operators containing others, whose schedulings include those of their operators.
+        let scopes = self.operators.values().filter_map(|(addr, _name)| addr.split_last().map(|(_, scope)| scope)).collect::<std::collections::HashSet<_>>();
+        let cheap = |index: usize, scope: &[usize]| {
+            // Index zero is the scope's own boundary, rather than an operator within it.
+            if index == 0 { return None; }
+            let mut addr = scope.to_vec();
+            addr.push(index);
+            let id = *identifiers.get(addr.as_slice())?;
+            if scopes.contains(addr.as_slice()) { return None; }
+            let mean = self.mean(id)?;
+            (mean < threshold).then_some((id, mean))
+        };
+
+        let mut candidates = Vec::new();
+        for (scope, source, target, pact) in self.channels.iter() {
+            if *pact != PactKind::Pipeline { continue; }
+            if let (Some((upstream, upstream_mean)), Some((downstream, downstream_mean))) = (cheap(*source, scope), cheap(*target, scope)) {
+                candidates.push(FusionCandidate {
+                    upstream,
+                    upstream_name: self.operators[&upstream].1.clone(),
+                    upstream_mean,
+                    downstream,
+                    downstream_name: self.operators[&downstream].1.clone(),
+                    downstream_mean,
+                });
+            }
+        }
+        candidates.sort_by_key(|candidate| (candidate.upstream, candidate.downstream));
+        candidates.dedup_by_key(|candidate| (candidate.upstream, candidate.downstream));
+        candidates
+    }
+
+    /// A readable report of the [`candidates`](Self::candidates) for `threshold`, one per line.
+    pub fn report(&self, threshold: Duration) -> String {
+        let candidates = self.candidates(threshold);
+        if candidates.is_empty() {
+            return format!("no fusion candidates below {:?} per scheduling\n", threshold);
+        }
+        let mut report = format!("fusion candidates below {:?} per scheduling:\n", threshold);
+        for candidate in candidates {
+            report.push_str(&format!(
+                "  {} (id {}, {:?}) -> {} (id {}, {:?})\n",
+                candidate.upstream_name, candidate.upstream, candidate.upstream_mean,
+                candidate.downstream_name, candidate.downstream, candidate.downstream_mean,
+            ));
+        }
+        report
+    }
+}
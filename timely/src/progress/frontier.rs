@@ -241,6 +241,32 @@ impl<T> Antichain<T> {
     /// Sorts the elements so that comparisons between antichains can be made.
     pub fn sort(&mut self) where T: Ord { self.elements.sort() }
 
+    /// Iterates over the elements in the antichain in the order of `Ord`.
+    ///
+    /// The elements of an antichain are in no particular order, which depends on the order of
+    /// their insertion. This method requires `T: Ord`, a total order beyond the partial order
+    /// of the antichain, to visit the elements in a canonical order, for example when printing
+    /// frontiers. Unlike `sort`, it leaves the antichain unchanged.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use timely::progress::frontier::Antichain;
+    /// use timely::order::Product;
+    ///
+    /// let mut frontier = Antichain::new();
+    /// frontier.insert(Product::new(3, 1));
+    /// frontier.insert(Product::new(1, 3));
+    /// frontier.insert(Product::new(2, 2));
+    /// let sorted = frontier.iter_sorted().map(|time| (time.outer, time.inner)).collect::<Vec<_>>();
+    /// assert_eq!(sorted, vec![(1, 3), (2, 2), (3, 1)]);
+    ///```
+    pub fn iter_sorted(&self) -> impl Iterator<Item=&T> where T: Ord {
+        let mut elements = self.elements.iter().collect::<Vec<_>>();
+        elements.sort();
+        elements.into_iter()
+    }
+
     /// Reveals the elements in the antichain.
     ///
     /// This method is redundant with `<Antichain<T> as Deref>`, but the method
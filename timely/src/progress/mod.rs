@@ -15,6 +15,7 @@ pub mod operate;
 pub mod broadcast;
 pub mod reachability;
 pub mod subgraph;
+pub mod spread;
 
 /// A timely dataflow location.
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize, Columnar)]
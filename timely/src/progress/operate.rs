@@ -3,7 +3,7 @@
 use std::rc::Rc;
 use std::cell::RefCell;
 
-use crate::scheduling::Schedule;
+use crate::scheduling::{Schedule, SchedulingHint};
 use crate::progress::{Timestamp, ChangeBatch, Antichain};
 
 /// Methods for describing an operators topology, and the progress it makes.
@@ -32,6 +32,10 @@ pub trait Operate<T: Timestamp> : Schedule {
     fn inputs(&self) -> usize;
     /// The number of outputs.
     fn outputs(&self) -> usize;
+    /// The name of input `port`, if it has one.
+    fn input_name(&self, _port: usize) -> Option<&str> { None }
+    /// The name of output `port`, if it has one.
+    fn output_name(&self, _port: usize) -> Option<&str> { None }
 
     /// Fetches summary information about internal structure of the operator.
     ///
@@ -56,6 +60,9 @@ pub trait Operate<T: Timestamp> : Schedule {
 
     /// Indicates of whether the operator requires `push_external_progress` information or not.
     fn notify_me(&self) -> bool { true }
+
+    /// Indicates how the operator spends its time when scheduled, by default as [`SchedulingHint::CpuBound`].
+    fn scheduling_hint(&self) -> SchedulingHint { SchedulingHint::CpuBound }
 }
 
 /// Operator internal connectivity, from inputs to outputs.
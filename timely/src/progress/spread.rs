@@ -0,0 +1,131 @@
+//! The spread of the frontiers of workers at the sources of channels, for diagnosing stragglers.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use crate::logging::TimelyProgressEventBuilder;
+use crate::order::TotalOrder;
+use crate::progress::{Antichain, Source, Timestamp};
+use crate::progress::frontier::MutableAntichain;
+use crate::worker::AsWorker;
+
+/// Tracks the frontier of each worker at the sources of channels, from the progress updates they report.
+///
+/// The frontier of a dataflow location is shared by all workers, and advances only once each of them
+/// has released its capabilities there. The frontier of each worker at a source, the times from which
+/// it may still produce records, instead shows which worker holds the shared frontier back: workers
+/// whose frontiers have advanced beyond those of the others wait on them, and a worker whose frontier
+/// lags far behind the others is a straggler.
+///
+/// The tracker listens to the progress updates each worker broadcasts, through the worker's
+/// `"timely/progress/{T}"` log stream, and installs itself as the logger of that stream when
+/// registered, replacing any other. It must be registered before the dataflows it tracks are built,
+/// which acquire their loggers when built, and it only sees the updates of scopes with timestamp `T`.
+/// Updates reach the tracker as the logger flushes them, once its buffer has filled or when the log
+/// registry is flushed, for example with `worker.log_register().unwrap().flush()`, and so the
+/// frontiers reported may trail those of the workers by the updates not yet flushed or broadcast.
+/// With the default [`ProgressMode::Demand`](crate::worker::ProgressMode::Demand) workers withhold
+/// updates that do not advance the shared frontier, and so the frontier of the slowest worker, which
+/// holds the shared frontier, is reported as it advances, but those of the workers ahead of it may
+/// trail until it catches up. With `ProgressMode::Eager` all frontiers are reported as they advance.
+///
+/// The frontiers of a source are accumulated from the updates to its capabilities, starting with a
+/// capability of each worker at the minimal timestamp, as operators built with the operator builders
+/// and inputs hold. Sources are identified by the [identifier](crate::dataflow::scopes::Child::identifier)
+/// of their scope and the `Source` of their stream, as reported by `stream.name()`.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::{Input, Probe};
+/// use timely::progress::spread::FrontierSpread;
+///
+/// timely::execute_directly(|worker| {
+///     let spread = FrontierSpread::<u64>::register(worker);
+///     let (mut input, (scope, source), probe) = worker.dataflow::<u64,_,_>(|scope| {
+///         let (input, stream) = scope.new_input::<u64>();
+///         (input, (scope.identifier(), *stream.name()), stream.probe())
+///     });
+///
+///     input.advance_to(10);
+///     worker.step_while(|| probe.less_than(&10));
+///     worker.log_register().unwrap().flush();
+///     assert_eq!(spread.frontiers(scope, source)[0].elements(), &[10]);
+///     let lag = spread.spread(scope, source).unwrap();
+///     assert_eq!((lag.slowest, lag.fastest), ((0, 10), (0, 10)));
+/// });
+/// ```
+pub struct FrontierSpread<T: Timestamp> {
+    /// The number of workers.
+    peers: usize,
+    /// For each scope identifier and source, the capabilities of each worker at the source.
+    sources: Rc<RefCell<BTreeMap<(usize, Source), Vec<MutableAntichain<T>>>>>,
+}
+
+impl<T: Timestamp> FrontierSpread<T> {
+    /// Creates a tracker of the frontiers of the workers, installed as the worker's progress logger for `T`.
+    ///
+    /// The tracker sees no updates if the worker has no log registry.
+    pub fn register<A: AsWorker>(worker: &A) -> Self {
+        let peers = worker.peers();
+        let sources = Rc::new(RefCell::new(BTreeMap::new()));
+        if let Some(mut registry) = worker.log_register() {
+            let sources = Rc::clone(&sources);
+            let name = format!("timely/progress/{}", std::any::type_name::<T>());
+            registry.insert::<TimelyProgressEventBuilder<T>, _>(&name, move |_time, data| {
+                let mut sources = sources.borrow_mut();
+                // Each worker receives the updates of all workers, including its own.
+                for (_logged, event) in data.iter().flatten().filter(|(_logged, event)| !event.is_send) {
+                    for (node, port, time, diff) in event.internal.iter() {
+                        sources
+                            .entry((event.identifier, Source::new(*node, *port)))
+                            .or_insert_with(|| bottoms(peers))[event.source]
+                            .update_iter(Some((time.clone(), *diff)));
+                    }
+                }
+            });
+        }
+        FrontierSpread { peers, sources }
+    }
+
+    /// The frontier of each worker at `source` of the scope with identifier `scope`, indexed by worker.
+    ///
+    /// Workers that have reported no updates at the source have the minimal frontier, and workers
+    /// that will produce no further records there have the empty frontier.
+    pub fn frontiers(&self, scope: usize, source: Source) -> Vec<Antichain<T>> {
+        match self.sources.borrow().get(&(scope, source)) {
+            Some(workers) => workers.iter().map(|worker| worker.frontier().to_owned()).collect(),
+            None => (0 .. self.peers).map(|_| Antichain::from_elem(T::minimum())).collect(),
+        }
+    }
+
+    /// The slowest and fastest workers at `source` of the scope with identifier `scope`.
+    ///
+    /// Workers with empty frontiers, which will produce no further records at the source, are not
+    /// considered, and the result is `None` if all frontiers are empty. Of workers with the same
+    /// frontier, the slowest and fastest are those of least index.
+    pub fn spread(&self, scope: usize, source: Source) -> Option<Spread<T>>
+    where
+        T: TotalOrder,
+    {
+        let frontiers = self.frontiers(scope, source);
+        let times = frontiers.iter().enumerate().filter_map(|(worker, frontier)| frontier.as_option().map(|time| (worker, time.clone())));
+        let slowest = times.clone().min_by(|(_, time1), (_, time2)| time1.cmp(time2))?;
+        let fastest = times.rev().max_by(|(_, time1), (_, time2)| time1.cmp(time2))?;
+        Some(Spread { slowest, fastest })
+    }
+}
+
+/// The slowest and fastest workers at a source, with their frontiers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Spread<T> {
+    /// The worker with the least frontier, and the frontier.
+    pub slowest: (usize, T),
+    /// The worker with the greatest frontier, and the frontier.
+    pub fastest: (usize, T),
+}
+
+/// The initial capabilities of `peers` workers, one each at the minimal timestamp.
+fn bottoms<T: Timestamp>(peers: usize) -> Vec<MutableAntichain<T>> {
+    (0 .. peers).map(|_| MutableAntichain::new_bottom(T::minimum())).collect()
+}
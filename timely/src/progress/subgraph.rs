@@ -9,6 +9,7 @@ use std::rc::Rc;
 use std::cell::RefCell;
 use std::collections::BinaryHeap;
 use std::cmp::Reverse;
+use std::time::{Duration, Instant};
 
 use crate::logging::TimelyLogger as Logger;
 use crate::logging::TimelySummaryLogger as SummaryLogger;
@@ -47,6 +48,9 @@ where
     /// A sequence of integers uniquely identifying the subgraph.
     pub path: Rc<[usize]>,
 
+    /// A label for the operators and channels of the subgraph, reported in logged events.
+    pub label: Option<Rc<str>>,
+
     /// The index assigned to the subgraph by its parent.
     index: usize,
 
@@ -114,6 +118,7 @@ where
         SubgraphBuilder {
             name: name.to_owned(),
             path,
+            label: None,
             index,
             identifier,
             children,
@@ -126,6 +131,11 @@ where
         }
     }
 
+    /// The worker-unique identifier of the subgraph, as reported in its logged events.
+    pub fn identifier(&self) -> usize {
+        self.identifier
+    }
+
     /// Allocates a new child identifier, for later use.
     pub fn allocate_child_id(&mut self) -> usize {
         self.child_count += 1;
@@ -143,6 +153,9 @@ where
                 id: identifier,
                 addr: child_path,
                 name: child.name().to_owned(),
+                label: self.label.as_deref().map(str::to_owned),
+                inputs: (0 .. child.inputs()).map(|port| child.input_name(port).map(str::to_owned)).collect(),
+                outputs: (0 .. child.outputs()).map(|port| child.output_name(port).map(str::to_owned)).collect(),
             });
         }
         self.children.push(PerOperatorState::new(child, index, identifier, self.logging.clone(), &mut self.summary_logging));
@@ -165,6 +178,11 @@ where
         // Create empty child zero representative.
         self.children[0] = PerOperatorState::empty(outputs, inputs);
 
+        let catch_panics = worker.config().catch_panics || worker.config().panic_context;
+        for child in self.children.iter_mut() {
+            child.catch_panics = catch_panics;
+        }
+
         let mut builder = reachability::Builder::new();
 
         // Child 0 has `inputs` outputs and `outputs` inputs, not yet connected.
@@ -220,6 +238,9 @@ where
             scope_summary,
 
             progress_mode: worker.config().progress_mode,
+            progress_interval: worker.config().progress_interval,
+            last_progress: None,
+            progress_deferred: false,
         }
     }
 }
@@ -272,6 +293,12 @@ where
     scope_summary: Connectivity<TInner::Summary>,
 
     progress_mode: ProgressMode,
+    /// The minimum interval between progress broadcasts.
+    progress_interval: Duration,
+    /// The moment of the most recent progress broadcast, if broadcasts are rate limited.
+    last_progress: Option<Instant>,
+    /// Whether a broadcast has been held back, and the subgraph scheduled to send it.
+    progress_deferred: bool,
 }
 
 impl<TOuter, TInner> Schedule for Subgraph<TOuter, TInner>
@@ -283,6 +310,16 @@ where
 
     fn path(&self) -> &[usize] { &self.path }
 
+    fn held_capabilities(&self, counts: &mut Vec<(usize, usize)>) {
+        // The first child is the scope's view of its outside, rather than an operator.
+        for child in self.children.iter().skip(1) {
+            if let Some(operator) = child.operator.as_ref() {
+                counts.push((child.id, usize::try_from(child.held).unwrap_or(0)));
+                operator.held_capabilities(counts);
+            }
+        }
+    }
+
     fn schedule(&mut self) -> bool {
 
         // This method performs several actions related to progress tracking
@@ -315,6 +352,18 @@ where
             // De-duplicate, and don't revisit.
             if index > previous {
                 // TODO: This is a moment where a scheduling decision happens.
+                if !self.activations.borrow_mut().consume_fuel() {
+                    // Out of fuel; re-activate this and the remaining children for a later step.
+                    let mut activations = self.activations.borrow_mut();
+                    let mut path = self.path.to_vec();
+                    path.push(index);
+                    activations.activate(&path[..]);
+                    for Reverse(deferred) in self.temp_active.drain() {
+                        *path.last_mut().unwrap() = deferred;
+                        activations.activate(&path[..]);
+                    }
+                    break;
+                }
                 self.activate_child(index);
                 previous = index;
             }
@@ -528,7 +577,22 @@ where
         };
 
         if must_send {
+            // Hold back updates within `progress_interval` of the last broadcast, and return once it has passed.
+            if let Some(last) = self.last_progress {
+                let elapsed = last.elapsed();
+                if elapsed < self.progress_interval {
+                    if !self.progress_deferred {
+                        self.activations.borrow_mut().activate_after(&self.path[..], self.progress_interval - elapsed);
+                        self.progress_deferred = true;
+                    }
+                    return;
+                }
+            }
             self.progcaster.send(&mut self.local_pointstamp);
+            self.progress_deferred = false;
+            if !self.progress_interval.is_zero() {
+                self.last_progress = Some(Instant::now());
+            }
         }
     }
 }
@@ -589,10 +653,31 @@ where
     fn set_external_summary(&mut self) {
         self.accept_frontier();
         self.propagate_pointstamps();  // ensure propagation of input frontiers.
-        self.children
-            .iter_mut()
-            .flat_map(|child| child.operator.as_mut())
-            .for_each(|op| op.set_external_summary());
+        for child in self.children.iter_mut() {
+            if let Some(op) = child.operator.as_mut() {
+                attribute_panics(op, child.catch_panics, &child.name, child.id, |op| op.set_external_summary());
+            }
+        }
+    }
+}
+
+/// Applies `logic` to `operator`, re-raising any panic as an `OperatorPanic` naming the operator if `attribute` is set.
+fn attribute_panics<T: Timestamp, R>(
+    operator: &mut Box<dyn Operate<T>>,
+    attribute: bool,
+    name: &str,
+    id: usize,
+    logic: impl FnOnce(&mut Box<dyn Operate<T>>) -> R,
+) -> R {
+    if !attribute {
+        return logic(operator);
+    }
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| logic(operator))) {
+        Ok(result) => result,
+        Err(payload) => {
+            let panic = crate::worker::OperatorPanic::from_payload(payload, name, id, operator.path());
+            std::panic::resume_unwind(Box::new(panic));
+        }
     }
 }
 
@@ -615,7 +700,11 @@ struct PerOperatorState<T: Timestamp> {
 
     internal_summary: Connectivity<T::Summary>,   // cached result from get_internal_summary.
 
+    held: i64,          // the accumulated changes to the operator's internal capabilities.
+
     logging: Option<Logger>,
+
+    catch_panics: bool, // attribute panics during scheduling to the operator.
 }
 
 impl<T: Timestamp> PerOperatorState<T> {
@@ -633,10 +722,14 @@ impl<T: Timestamp> PerOperatorState<T> {
 
             edges: vec![Vec::new(); outputs],
 
+            held: 0,
+
             logging: None,
 
             shared_progress: Rc::new(RefCell::new(SharedProgress::new(inputs,outputs))),
             internal_summary: Vec::new(),
+
+            catch_panics: false,
         }
     }
 
@@ -689,6 +782,10 @@ impl<T: Timestamp> PerOperatorState<T> {
 
             shared_progress,
             internal_summary,
+
+            held: 0,
+
+            catch_panics: false,
         }
     }
 
@@ -709,7 +806,7 @@ impl<T: Timestamp> PerOperatorState<T> {
                 l.log(crate::logging::ScheduleEvent::start(self.id));
             }
 
-            let incomplete = operator.schedule();
+            let incomplete = attribute_panics(operator, self.catch_panics, &self.name, self.id, |operator| operator.schedule());
 
             // Perhaps log information about the stop of the schedule call.
             if let Some(l) = self.logging.as_mut() {
@@ -743,7 +840,7 @@ impl<T: Timestamp> PerOperatorState<T> {
     }
 
     /// Extracts shared progress information and converts to pointstamp changes.
-    fn extract_progress(&self, pointstamps: &mut ChangeBatch<(Location, T)>, temp_active: &mut BinaryHeap<Reverse<usize>>) {
+    fn extract_progress(&mut self, pointstamps: &mut ChangeBatch<(Location, T)>, temp_active: &mut BinaryHeap<Reverse<usize>>) {
 
         let shared_progress = &mut *self.shared_progress.borrow_mut();
 
@@ -757,6 +854,7 @@ impl<T: Timestamp> PerOperatorState<T> {
         for (output, internal) in shared_progress.internals.iter_mut().enumerate() {
             let source = Location::new_source(self.index, output);
             for (time, delta) in internal.drain() {
+                self.held += delta;
                 pointstamps.update((source, time.clone()), delta);
             }
         }
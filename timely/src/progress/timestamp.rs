@@ -4,6 +4,8 @@ use std::fmt::Debug;
 use std::any::Any;
 use std::default::Default;
 
+use serde::{Deserialize, Serialize};
+
 use crate::ExchangeData;
 use crate::order::PartialOrder;
 
@@ -124,6 +126,79 @@ impl PathSummary<::std::time::Duration> for ::std::time::Duration {
     fn followed_by(&self, other: &::std::time::Duration) -> Option<::std::time::Duration> { self.checked_add(*other) }
 }
 
+/// A path summary that leaves timestamps unchanged, for timestamps defined by [`implement_timestamp`](crate::implement_timestamp).
+///
+/// Timestamps with this summary never advance along dataflow paths, and so cannot be used in the
+/// timestamps of loops, which must advance around each cycle. Loops can instead nest a scope over
+/// a `Product` of the timestamp and an integer, with an `Iterative` scope.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Unchanged;
+
+impl PartialOrder for Unchanged {
+    #[inline] fn less_equal(&self, _other: &Self) -> bool { true }
+}
+
+impl<T: Clone> PathSummary<T> for Unchanged {
+    #[inline] fn results_in(&self, src: &T) -> Option<T> { Some(src.clone()) }
+    #[inline] fn followed_by(&self, _other: &Self) -> Option<Self> { Some(Unchanged) }
+}
+
+/// Implements [`Timestamp`] for a partially ordered type, with the path summary [`Unchanged`].
+///
+/// The type must implement the supertraits of `Timestamp`, with an `Ord` compatible with its
+/// `PartialOrder`, and `$minimum` must be less or equal to all values of the type. The macro also
+/// implements `Refines<()>`, so that the type can be the timestamp of a dataflow. Progress tracking
+/// relies only on the partial order; lattice operations, if the type has them, are not needed.
+///
+/// # Examples
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use timely::order::PartialOrder;
+/// use timely::dataflow::operators::{UnorderedInput, Probe};
+///
+/// /// A pair of versions, ordered if both are.
+/// #[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+/// struct Versions { schema: u32, data: u32 }
+///
+/// impl PartialOrder for Versions {
+///     fn less_equal(&self, other: &Self) -> bool { self.schema <= other.schema && self.data <= other.data }
+/// }
+///
+/// timely::implement_timestamp!(Versions, Versions { schema: 0, data: 0 });
+///
+/// timely::execute_directly(|worker| {
+///     let ((mut input, capability), probe) = worker.dataflow::<Versions,_,_>(|scope| {
+///         let (input, stream) = scope.new_unordered_input::<u64>();
+///         (input, stream.probe())
+///     });
+///     input.activate().session(&capability).give(0);
+///     // Capabilities for two incomparable times, each of which holds back the frontier.
+///     let schema = capability.delayed(&Versions { schema: 1, data: 0 });
+///     let data = capability.delayed(&Versions { schema: 0, data: 1 });
+///     drop(capability);
+///     worker.step_while(|| probe.less_equal(&Versions { schema: 0, data: 0 }));
+///     drop(schema);
+///     worker.step_while(|| probe.less_equal(&Versions { schema: 1, data: 0 }));
+///     assert!(probe.less_equal(&Versions { schema: 0, data: 1 }));
+///     drop((input, data));
+///     worker.step_while(|| !probe.done());
+/// });
+/// ```
+#[macro_export]
+macro_rules! implement_timestamp {
+    ($type:ty, $minimum:expr) => {
+        impl $crate::progress::Timestamp for $type {
+            type Summary = $crate::progress::timestamp::Unchanged;
+            fn minimum() -> Self { $minimum }
+        }
+        impl $crate::progress::timestamp::Refines<()> for $type {
+            fn to_inner(_: ()) -> Self { <Self as $crate::progress::Timestamp>::minimum() }
+            fn to_outer(self) { }
+            fn summarize(_: $crate::progress::timestamp::Unchanged) { }
+        }
+    };
+}
+
 pub use self::refines::Refines;
 mod refines {
 
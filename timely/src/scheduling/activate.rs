@@ -50,6 +50,9 @@ pub struct Activations {
     // Delayed activations.
     timer: Option<Instant>,
     queue: BinaryHeap<Reverse<(Duration, Vec<usize>)>>,
+
+    // Operator schedulings remaining in the current step, if bounded.
+    fuel: Option<usize>,
 }
 
 impl Activations {
@@ -66,6 +69,7 @@ impl Activations {
             rx,
             timer,
             queue: BinaryHeap::new(),
+            fuel: None,
         }
     }
 
@@ -92,6 +96,38 @@ impl Activations {
         }
     }
 
+    /// Bounds the number of operators that may be scheduled, or removes the bound with `None`.
+    pub fn set_fuel(&mut self, fuel: Option<usize>) {
+        self.fuel = fuel;
+    }
+
+    /// Consumes fuel to schedule one operator, and reports whether there was fuel to consume.
+    ///
+    /// Without a bound on the number of operators, this method always succeeds.
+    pub fn consume_fuel(&mut self) -> bool {
+        match self.fuel.as_mut() {
+            Some(0) => false,
+            Some(fuel) => { *fuel -= 1; true },
+            None => true,
+        }
+    }
+
+    /// Indicates whether tasks have been activated since the active set was last presented.
+    ///
+    /// This includes delayed activations now due, but not activations from other threads.
+    pub fn has_pending(&self) -> bool {
+        self.bounds.len() > self.clean ||
+        self.timer.is_some_and(|timer| self.queue.peek().is_some_and(|Reverse((moment, _))| *moment <= timer.elapsed()))
+    }
+
+    /// Indicates whether tasks at or within `path` have been activated since the active set was last presented.
+    ///
+    /// This includes delayed activations, whether or not they are due, but not activations from other threads.
+    pub fn is_pending(&self, path: &[usize]) -> bool {
+        self.bounds[self.clean..].iter().any(|(offset, length)| self.slices[*offset .. (*offset + *length)].starts_with(path)) ||
+        self.queue.iter().any(|Reverse((_moment, task))| task.starts_with(path))
+    }
+
     /// Discards the current active set and presents the next active set.
     pub fn advance(&mut self) {
 
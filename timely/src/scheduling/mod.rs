@@ -18,6 +18,23 @@ pub trait Schedule {
     /// The return value indicates whether `self` has outstanding
     /// work and would be upset if the computation terminated.
     fn schedule(&mut self) -> bool;
+    /// Appends `(identifier, count)` for each operator within `self`, with the number of capabilities it holds.
+    ///
+    /// Operators other than scopes contain no operators, and append nothing.
+    fn held_capabilities(&self, _counts: &mut Vec<(usize, usize)>) { }
+}
+
+/// Describes how an operator spends its time when scheduled, for schedulers to take into account.
+///
+/// The hint is advisory: the worker currently schedules all operators alike, whatever their hints.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SchedulingHint {
+    /// The operator computes when scheduled, and returns once it has no more work. The default.
+    #[default]
+    CpuBound,
+    /// The operator may block on IO when scheduled, for example reading from or writing to a
+    /// network or a file, and may be better scheduled apart from operators that only compute.
+    IoBound,
 }
 
 /// Methods for types which schedule fibers.
@@ -1,7 +1,7 @@
 //! The root of each single-threaded worker.
 
 use std::rc::Rc;
-use std::cell::{RefCell, RefMut};
+use std::cell::{Cell, RefCell, RefMut};
 use std::any::Any;
 use std::str::FromStr;
 use std::time::{Instant, Duration};
@@ -9,7 +9,7 @@ use std::collections::HashMap;
 use std::collections::hash_map::Entry;
 use std::sync::Arc;
 
-use crate::communication::{Allocate, Exchangeable, Push, Pull};
+use crate::communication::{Allocate, Exchangeable, Push, Pull, Topology};
 use crate::communication::allocator::thread::{ThreadPusher, ThreadPuller};
 use crate::scheduling::{Schedule, Scheduler, Activations};
 use crate::progress::timestamp::{Refines};
@@ -82,6 +82,16 @@ impl FromStr for ProgressMode {
 pub struct Config {
     /// The progress mode to use.
     pub(crate) progress_mode: ProgressMode,
+    /// The minimum interval between progress broadcasts of each scope.
+    pub(crate) progress_interval: Duration,
+    /// Whether operator panics are caught and reported as an [`OperatorPanic`].
+    pub(crate) catch_panics: bool,
+    /// Whether operator panics continue to unwind with a message naming the operator.
+    pub(crate) panic_context: bool,
+    /// How long the worker must be idle before `step` parks it, if it should ever.
+    pub(crate) park_when_idle: Option<Duration>,
+    /// The seed from which each worker derives its [`WorkerRng`].
+    pub(crate) seed: u64,
     /// A map from parameter name to typed parameter values.
     registry: HashMap<String, Arc<dyn Any + Send + Sync>>,
 }
@@ -99,6 +109,9 @@ impl Config {
     #[cfg(feature = "getopts")]
     pub fn install_options(opts: &mut getopts::Options) {
         opts.optopt("", "progress-mode", "progress tracking mode (eager or demand)", "MODE");
+        opts.optopt("", "progress-interval", "minimum milliseconds between progress broadcasts", "MILLIS");
+        opts.optopt("", "park-when-idle", "milliseconds idle after which stepping parks the worker", "MILLIS");
+        opts.optopt("", "seed", "seed of the workers' random number generators", "SEED");
     }
 
     /// Instantiates a configuration based upon the parsed options in `matches`.
@@ -113,7 +126,23 @@ impl Config {
     pub fn from_matches(matches: &getopts::Matches) -> Result<Config, String> {
         let progress_mode = matches
             .opt_get_default("progress-mode", ProgressMode::Eager)?;
-        Ok(Config::default().progress_mode(progress_mode))
+        let progress_interval = matches
+            .opt_get_default("progress-interval", 0u64)
+            .map_err(|e| format!("invalid progress interval: {}", e))?;
+        let park_when_idle = matches
+            .opt_get::<u64>("park-when-idle")
+            .map_err(|e| format!("invalid idle duration: {}", e))?;
+        let seed = matches
+            .opt_get_default("seed", 0u64)
+            .map_err(|e| format!("invalid seed: {}", e))?;
+        let mut config = Config::default()
+            .progress_mode(progress_mode)
+            .progress_interval(Duration::from_millis(progress_interval))
+            .seed(seed);
+        if let Some(millis) = park_when_idle {
+            config = config.park_when_idle(Duration::from_millis(millis));
+        }
+        Ok(config)
     }
 
     /// Sets the progress mode to `progress_mode`.
@@ -122,6 +151,164 @@ impl Config {
         self
     }
 
+    /// Sets the minimum interval between progress broadcasts of each scope to `progress_interval`.
+    ///
+    /// By default, and with a zero interval, each scope broadcasts its progress updates as soon as
+    /// the progress mode requires it. With a non-zero interval, a scope that has broadcast within the
+    /// last `progress_interval` instead holds its updates, accumulating them with any that follow,
+    /// and broadcasts them once the interval has elapsed. This reduces the number of progress messages
+    /// when updates are frequent, at the cost of delaying the advance of frontiers at other workers by
+    /// at most `progress_interval` per scope. Latency-sensitive dataflows should keep the default.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let config = timely::Config {
+    ///     worker: timely::WorkerConfig::default().progress_interval(Duration::from_millis(1)),
+    ///     ..timely::Config::process(2)
+    /// };
+    /// timely::execute(config, |worker| {
+    ///     use timely::dataflow::operators::{ToStream, Inspect};
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         (0..10).to_stream(scope)
+    ///                .inspect(|x| println!("seen: {:?}", x));
+    ///     });
+    /// }).unwrap();
+    /// ```
+    pub fn progress_interval(mut self, progress_interval: Duration) -> Self {
+        self.progress_interval = progress_interval;
+        self
+    }
+
+    /// Sets whether the worker catches panics raised by operators.
+    ///
+    /// By default a panicking operator unwinds through the worker, and takes down the worker thread.
+    /// When panics are caught, the worker instead records the panic as an [`OperatorPanic`] naming
+    /// the operator, and drops all of its dataflows, as state shared between operators may have been
+    /// left inconsistent. The panic is available from [`Worker::operator_panic`], and the worker
+    /// refuses to step again: further calls to [`Worker::step_or_park`] panic with the recorded
+    /// error, as does returning from the worker closure passed to [`execute`](crate::execute()).
+    /// The error is reported by [`WorkerGuards::join`](crate::communication::WorkerGuards::join).
+    ///
+    /// Only the panicking worker shuts down. Other workers sharing the dataflow are not informed,
+    /// and will wait on the progress of the panicked worker.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect};
+    ///
+    /// let config = timely::Config {
+    ///     worker: timely::WorkerConfig::default().catch_panics(true),
+    ///     ..timely::Config::thread()
+    /// };
+    /// let results = timely::execute(config, |worker| {
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         (0..10).to_stream(scope)
+    ///                .inspect(|x| assert!(*x < 5, "large record"));
+    ///     });
+    ///     while worker.step() { }
+    ///     assert_eq!(worker.operator_panic().unwrap().operator, "Inspect");
+    /// }).unwrap().join();
+    /// assert!(results[0].as_ref().unwrap_err().contains("large record"));
+    /// ```
+    pub fn catch_panics(mut self, catch_panics: bool) -> Self {
+        self.catch_panics = catch_panics;
+        self
+    }
+
+    /// Sets whether operator panics continue to unwind with a message naming the operator.
+    ///
+    /// By default a panicking operator unwinds through the worker with its own panic payload, and
+    /// the error reported for the worker thread does not say which operator panicked. With panic
+    /// context, each scheduling of an operator is wrapped so that its panic resumes unwinding with
+    /// a message prefixed by the operator's name, identifier, and address, as an [`OperatorPanic`]
+    /// displays it, which is the error [`WorkerGuards::join`](crate::communication::WorkerGuards::join)
+    /// reports. The panic hook is neither replaced nor called again, and so still reports the
+    /// original panic as it happens, without the context.
+    ///
+    /// If the worker also catches panics, with [`Config::catch_panics`], the panic is recorded as
+    /// such instead.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect};
+    ///
+    /// let config = timely::Config {
+    ///     worker: timely::WorkerConfig::default().panic_context(true),
+    ///     ..timely::Config::thread()
+    /// };
+    /// let results = timely::execute(config, |worker| {
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         (0..10).to_stream(scope)
+    ///                .inspect(|x| assert!(*x < 5, "large record"));
+    ///     });
+    /// }).unwrap().join();
+    /// let error = results[0].as_ref().unwrap_err();
+    /// assert!(error.starts_with("operator \"InspectBatch\" (id "));
+    /// assert!(error.ends_with("panicked: large record"));
+    /// ```
+    pub fn panic_context(mut self, panic_context: bool) -> Self {
+        self.panic_context = panic_context;
+        self
+    }
+
+    /// Sets the duration for which the worker must be idle before [`Worker::step`] parks it.
+    ///
+    /// By default `step` never parks the worker, and a loop that repeatedly steps an idle worker
+    /// keeps its thread busy. Once configured, a worker that has had no active operators, no pending
+    /// channel events, and no due timed activations for at least `grace` instead parks within `step`,
+    /// as [`Worker::step_or_park`] with no timeout does, until woken by data or progress from other
+    /// workers, by a [`SyncActivator`](crate::scheduling::SyncActivator), or by the next timed
+    /// activation. Any activity resets the grace period, and calls to `step_or_park` with a timeout
+    /// are unaffected.
+    ///
+    /// A parked worker does not return from `step` to its caller, and so only work that activates
+    /// the worker wakes it. Callers that poll sources outside of the dataflow between steps, without
+    /// activating the worker when they find work, should not configure this.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let config = timely::Config {
+    ///     worker: timely::WorkerConfig::default().park_when_idle(Duration::from_millis(10)),
+    ///     ..timely::Config::process(2)
+    /// };
+    /// timely::execute(config, |worker| {
+    ///     use timely::dataflow::operators::{ToStream, Inspect};
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         (0..10).to_stream(scope)
+    ///                .inspect(|x| println!("seen: {:?}", x));
+    ///     });
+    ///     while worker.step() { }
+    /// }).unwrap();
+    /// ```
+    pub fn park_when_idle(mut self, grace: Duration) -> Self {
+        self.park_when_idle = Some(grace);
+        self
+    }
+
+    /// Sets the seed from which each worker derives its random number generator, [`Worker::rng`].
+    ///
+    /// By default the seed is zero. Each worker seeds its generator from the seed and its index,
+    /// and so workers draw different numbers, but the same numbers in each run with the same seed.
+    ///
+    /// # Examples
+    /// ```
+    /// let config = timely::Config {
+    ///     worker: timely::WorkerConfig::default().seed(42),
+    ///     ..timely::Config::thread()
+    /// };
+    /// let draws = timely::execute(config.clone(), |worker| worker.rng().next_u64()).unwrap().join();
+    /// let again = timely::execute(config, |worker| worker.rng().next_u64()).unwrap().join();
+    /// assert_eq!(draws[0].as_ref().unwrap(), again[0].as_ref().unwrap());
+    /// ```
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
     /// Sets a typed configuration parameter for the given `key`.
     ///
     /// It is recommended to install a single configuration struct using a key
@@ -166,6 +353,196 @@ impl Config {
     }
 }
 
+/// A panic raised by an operator, caught by a worker configured with [`Config::catch_panics`].
+#[derive(Debug, Clone)]
+pub struct OperatorPanic {
+    /// The name of the operator that panicked.
+    pub operator: String,
+    /// The worker-unique identifier of the operator.
+    pub id: usize,
+    /// The address of the operator, the indices of its dataflow and of each enclosing scope and itself within their parents.
+    pub address: Vec<usize>,
+    /// The panic message, if the panic payload was a string.
+    pub message: String,
+}
+
+impl OperatorPanic {
+    /// Converts a panic payload into an `OperatorPanic`, attributing it to the operator `id` at
+    /// `address` if the payload is not already attributed to an operator.
+    pub(crate) fn from_payload(payload: Box<dyn Any + Send>, operator: &str, id: usize, address: &[usize]) -> Self {
+        let payload = match payload.downcast::<OperatorPanic>() {
+            Ok(panic) => return *panic,
+            Err(payload) => payload,
+        };
+        let message = match payload.downcast::<String>() {
+            Ok(message) => *message,
+            Err(payload) => match payload.downcast::<&'static str>() {
+                Ok(message) => (*message).to_owned(),
+                Err(_) => "<non-string panic payload>".to_owned(),
+            },
+        };
+        OperatorPanic { operator: operator.to_owned(), id, address: address.to_vec(), message }
+    }
+}
+
+impl std::fmt::Display for OperatorPanic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "operator {:?} (id {}, address {:?}) panicked: {}", self.operator, self.id, self.address, self.message)
+    }
+}
+
+impl std::error::Error for OperatorPanic { }
+
+/// A dataflow installed in a worker, as listed by [`Worker::dataflows`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataflowInfo {
+    /// The index of the dataflow, the first coordinate of the addresses of its operators.
+    pub index: usize,
+    /// The name the dataflow was constructed with.
+    pub name: String,
+    /// Whether the dataflow has work to perform at the next step.
+    pub status: DataflowStatus,
+}
+
+/// Whether an installed dataflow has work to perform at the next step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DataflowStatus {
+    /// Some operators of the dataflow are activated, and will be scheduled by the next step.
+    Active,
+    /// No operators of the dataflow are activated, and the dataflow awaits input or messages from other workers.
+    Idle,
+}
+
+/// A worker-local registry of shared state, keyed by name.
+///
+/// Operators on the same worker can use the registry to share resources, for example a loaded
+/// model or a dictionary, without threading `Rc<RefCell<_>>` handles through their closures.
+/// Values are stored behind an `Rc`, and each access checks that the stored value has the
+/// requested type. The registry is owned by the worker, and its values are dropped once the
+/// worker and all handles returned from the registry are dropped.
+#[derive(Default)]
+pub struct StateRegistry {
+    map: HashMap<String, Rc<dyn Any>>,
+}
+
+impl StateRegistry {
+    /// Returns the value registered under `key`, inserting the result of `init` if absent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a value of a type other than `T` is registered under `key`.
+    ///
+    /// # Examples
+    /// ```
+    /// timely::execute_directly(|worker| {
+    ///     use timely::worker::AsWorker;
+    ///     let first = worker.state_registry().get_or_insert_with("model", || vec![1, 2, 3]);
+    ///     let second = worker.state_registry().get_or_insert_with::<Vec<i32>, _>("model", || unreachable!());
+    ///     assert!(std::rc::Rc::ptr_eq(&first, &second));
+    /// });
+    /// ```
+    pub fn get_or_insert_with<T: 'static, F: FnOnce() -> T>(&mut self, key: &str, init: F) -> Rc<T> {
+        let value = self.map.entry(key.to_owned()).or_insert_with(|| Rc::new(init()));
+        match Rc::clone(value).downcast() {
+            Ok(value) => value,
+            Err(_) => panic!("worker state {key:?} is registered with a different type than {}", std::any::type_name::<T>()),
+        }
+    }
+    /// Returns the value registered under `key`, inserting `T::default()` if absent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a value of a type other than `T` is registered under `key`.
+    pub fn get_or_insert<T: Default + 'static>(&mut self, key: &str) -> Rc<T> {
+        self.get_or_insert_with(key, T::default)
+    }
+    /// Returns the value registered under `key`, if it exists and has type `T`.
+    pub fn get<T: 'static>(&self, key: &str) -> Option<Rc<T>> {
+        self.map.get(key).and_then(|value| Rc::clone(value).downcast().ok())
+    }
+    /// Removes the value registered under `key`, returning whether a value was present.
+    ///
+    /// Handles to the value that were previously returned remain valid.
+    pub fn remove(&mut self, key: &str) -> bool {
+        self.map.remove(key).is_some()
+    }
+}
+
+impl std::fmt::Debug for StateRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.map.keys()).finish()
+    }
+}
+
+/// A handle to the random number generator of a worker.
+///
+/// Each worker has one generator, seeded from the seed of its [`Config`] and its index, and all
+/// handles to it, from [`Worker::rng`] or [`AsWorker::rng`], draw from the same sequence. Operators
+/// that need randomness, for example to sample records, can draw from it so that a dataflow run with
+/// the same seed draws the same numbers.
+///
+/// Each draw advances the shared sequence, and so the numbers an operator draws depend on the draws
+/// of all other operators on the worker before it. A run is only reproducible if the order of the
+/// draws is: besides the seed, this requires the operators to be scheduled in the same order, and
+/// the records they draw for to arrive in the same order, both of which depend on the timing of
+/// messages between workers. Single-worker dataflows, and dataflows that exchange no records, draw
+/// reproducibly; others may not.
+///
+/// The generator is a SplitMix64 generator, which is fast and statistically sound, but not
+/// cryptographically secure.
+#[derive(Clone)]
+pub struct WorkerRng {
+    state: Rc<Cell<u64>>,
+}
+
+impl WorkerRng {
+    /// The key under which the generator state is registered in the worker's [`StateRegistry`].
+    const KEY: &'static str = "timely::rng";
+
+    /// Returns the generator of `worker`, seeding it if it does not yet exist.
+    fn get<A: AsWorker + ?Sized>(worker: &A) -> Self {
+        let seed = worker.config().seed ^ (worker.index() as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        let state = worker.state_registry().get_or_insert_with(Self::KEY, || Cell::new(seed));
+        WorkerRng { state }
+    }
+
+    /// Draws a uniformly distributed `u64`.
+    pub fn next_u64(&self) -> u64 {
+        let state = self.state.get().wrapping_add(0x9E37_79B9_7F4A_7C15);
+        self.state.set(state);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Draws a uniformly distributed `f64` in `[0, 1)`.
+    pub fn next_f64(&self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Draws a `u64` in `[0, bound)`, uniformly up to a bias that is negligible unless `bound` is near `u64::MAX`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bound` is zero.
+    pub fn below(&self, bound: u64) -> u64 {
+        assert!(bound > 0, "WorkerRng: bound must be positive");
+        ((self.next_u64() as u128 * bound as u128) >> 64) as u64
+    }
+
+    /// Returns `true` with probability `probability`.
+    pub fn chance(&self, probability: f64) -> bool {
+        self.next_f64() < probability
+    }
+}
+
+impl std::fmt::Debug for WorkerRng {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WorkerRng").finish_non_exhaustive()
+    }
+}
+
 /// Methods provided by the root Worker.
 ///
 /// These methods are often proxied by child scopes, and this trait provides access.
@@ -176,6 +553,10 @@ pub trait AsWorker : Scheduler {
     fn index(&self) -> usize;
     /// Number of peer workers.
     fn peers(&self) -> usize;
+    /// The process and host of each peer worker, indexed by worker.
+    ///
+    /// By default, all peers are assumed to share one process.
+    fn topology(&self) -> Topology { Topology::single_process(self.peers()) }
     /// Allocates a new channel from a supplied identifier and address.
     ///
     /// The identifier is used to identify the underlying channel and route
@@ -192,6 +573,10 @@ pub trait AsWorker : Scheduler {
     /// By default this method uses the native channel allocation mechanism, but the expectation is
     /// that this behavior will be overridden to be more efficient.
     fn pipeline<T: 'static>(&mut self, identifier: usize, address: Rc<[usize]>) -> (ThreadPusher<T>, ThreadPuller<T>);
+    /// True if channels among all workers may be constructed with `pipeline` when there is one worker.
+    ///
+    /// By default this is `false`, and all channels are constructed with `allocate`.
+    fn exchange_by_pipeline(&self) -> bool { false }
 
     /// Allocates a broadcast channel, where each pushed message is received by all.
     fn broadcast<T: Exchangeable + Clone>(&mut self, identifier: usize, address: Rc<[usize]>) -> (Box<dyn Push<T>>, Box<dyn Pull<T>>);
@@ -210,6 +595,14 @@ pub trait AsWorker : Scheduler {
     }
     /// Provides access to the timely logging stream.
     fn logging(&self) -> Option<crate::logging::TimelyLogger> { self.logger_for("timely").map(Into::into) }
+    /// The label of the scope under construction, reported in the logged events of its operators and channels.
+    ///
+    /// Workers themselves have no label.
+    fn label(&self) -> Option<Rc<str>> { None }
+    /// Provides access to the worker-local registry of shared state.
+    fn state_registry(&self) -> RefMut<'_, StateRegistry>;
+    /// A handle to the worker's random number generator. See [`WorkerRng`].
+    fn rng(&self) -> WorkerRng { WorkerRng::get(self) }
 }
 
 /// A `Worker` is the entry point to a timely dataflow computation. It wraps a `Allocate`,
@@ -235,12 +628,46 @@ pub struct Worker<A: Allocate> {
     // Temporary storage for channel identifiers during dataflow construction.
     // These are then associated with a dataflow once constructed.
     temp_channel_ids: Rc<RefCell<Vec<usize>>>,
+
+    state: Rc<RefCell<StateRegistry>>,
+
+    /// A caught operator panic, after which the worker no longer schedules dataflows.
+    panic: Rc<RefCell<Option<OperatorPanic>>>,
+
+    /// The moment from which the worker has been continually idle, if it is idle.
+    idle_since: Rc<Cell<Option<Instant>>>,
+
+    /// Callbacks invoked before and after each step.
+    step_hooks: Rc<RefCell<StepHooks>>,
+}
+
+/// Callbacks registered with [`Worker::on_step_begin`] and [`Worker::on_step_end`].
+#[derive(Default)]
+struct StepHooks {
+    begin: Vec<Box<dyn FnMut()>>,
+    end: Vec<Box<dyn FnMut()>>,
+}
+
+impl StepHooks {
+    /// Invokes the hooks `select` selects, and drops those that panic.
+    ///
+    /// The hooks are taken out of `hooks` while they run, so that they may register further hooks.
+    fn invoke(hooks: &RefCell<StepHooks>, select: fn(&mut StepHooks) -> &mut Vec<Box<dyn FnMut()>>) {
+        let mut invoked = std::mem::take(select(&mut hooks.borrow_mut()));
+        invoked.retain_mut(|hook| std::panic::catch_unwind(std::panic::AssertUnwindSafe(hook)).is_ok());
+        let mut borrow = hooks.borrow_mut();
+        let registered = select(&mut borrow);
+        invoked.append(registered);
+        *registered = invoked;
+    }
 }
 
 impl<A: Allocate> AsWorker for Worker<A> {
     fn config(&self) -> &Config { &self.config }
     fn index(&self) -> usize { self.allocator.borrow().index() }
     fn peers(&self) -> usize { self.allocator.borrow().peers() }
+    fn topology(&self) -> Topology { self.allocator.borrow().topology() }
+    fn exchange_by_pipeline(&self) -> bool { self.allocator.borrow().exchange_by_pipeline() }
     fn allocate<D: Exchangeable>(&mut self, identifier: usize, address: Rc<[usize]>) -> (Vec<Box<dyn Push<D>>>, Box<dyn Pull<D>>) {
         if address.is_empty() { panic!("Unacceptable address: Length zero"); }
         let mut paths = self.paths.borrow_mut();
@@ -268,6 +695,7 @@ impl<A: Allocate> AsWorker for Worker<A> {
     fn log_register(&self) -> Option<RefMut<'_, crate::logging_core::Registry>> {
         self.log_register()
     }
+    fn state_registry(&self) -> RefMut<'_, StateRegistry> { self.state.borrow_mut() }
 }
 
 impl<A: Allocate> Scheduler for Worker<A> {
@@ -291,6 +719,10 @@ impl<A: Allocate> Worker<A> {
             activations: Rc::new(RefCell::new(Activations::new(now))),
             active_dataflows: Default::default(),
             temp_channel_ids:  Default::default(),
+            state: Default::default(),
+            panic: Default::default(),
+            idle_since: Default::default(),
+            step_hooks: Default::default(),
         }
     }
 
@@ -348,6 +780,12 @@ impl<A: Allocate> Worker<A> {
     /// ```
     pub fn step_or_park(&mut self, duration: Option<Duration>) -> bool {
 
+        if let Some(panic) = self.panic.borrow().as_ref() {
+            panic!("worker stepped after {}", panic);
+        }
+
+        StepHooks::invoke(&self.step_hooks, |hooks| &mut hooks.begin);
+
         {   // Process channel events. Activate responders.
             let mut allocator = self.allocator.borrow_mut();
             allocator.receive();
@@ -377,6 +815,19 @@ impl<A: Allocate> Worker<A> {
 
         // Consider parking only if we have no pending events, some dataflows, and a non-zero duration.
         let empty_for = self.activations.borrow().empty_for();
+
+        // Once idle for the configured grace period, park a worker asked not to, until woken by work.
+        let mut duration = duration;
+        if empty_for == Some(Duration::new(0,0)) {
+            self.idle_since.set(None);
+        }
+        else if let Some(grace) = self.config.park_when_idle {
+            let idle_since = self.idle_since.get().unwrap_or_else(Instant::now);
+            self.idle_since.set(Some(idle_since));
+            if duration == Some(Duration::new(0,0)) && idle_since.elapsed() >= grace && !self.dataflows.borrow().is_empty() {
+                duration = None;
+            }
+        }
         // Determine the minimum park duration, where `None` are an absence of a constraint.
         let delay = match (duration, empty_for) {
             (Some(x), Some(y)) => Some(std::cmp::min(x,y)),
@@ -410,7 +861,23 @@ impl<A: Allocate> Worker<A> {
                 // Step dataflow if it exists, remove if not incomplete.
                 if let Entry::Occupied(mut entry) = dataflows.entry(index) {
                     // TODO: This is a moment at which a scheduling decision is being made.
-                    let incomplete = entry.get_mut().step();
+                    let incomplete = if self.config.catch_panics || self.config.panic_context {
+                        let wrapper = entry.get_mut();
+                        match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| wrapper.step())) {
+                            Ok(incomplete) => incomplete,
+                            Err(payload) => {
+                                let panic = OperatorPanic::from_payload(payload, "Dataflow", wrapper.identifier, &[index]);
+                                if !self.config.catch_panics {
+                                    std::panic::resume_unwind(Box::new(panic.to_string()));
+                                }
+                                *self.panic.borrow_mut() = Some(panic);
+                                break;
+                            }
+                        }
+                    }
+                    else {
+                        entry.get_mut().step()
+                    };
                     if !incomplete {
                         let mut paths = self.paths.borrow_mut();
                         for channel in entry.get_mut().channel_ids.drain(..) {
@@ -422,12 +889,121 @@ impl<A: Allocate> Worker<A> {
             }
         }
 
+        // Drop all dataflows after a caught panic, as their shared state may be inconsistent.
+        if self.panic.borrow().is_some() {
+            self.active_dataflows.clear();
+            let dataflows = std::mem::take(&mut *self.dataflows.borrow_mut());
+            self.paths.borrow_mut().clear();
+            drop(dataflows);
+        }
+
         // Clean up, indicate if dataflows remain.
         self.logging.as_ref().map(|l| l.borrow_mut().flush());
         self.allocator.borrow_mut().release();
+        StepHooks::invoke(&self.step_hooks, |hooks| &mut hooks.end);
         !self.dataflows.borrow().is_empty()
     }
 
+    /// Registers `hook` to be invoked at the start of each step, before the worker receives messages and schedules operators.
+    ///
+    /// Hooks integrate housekeeping, such as polling an external source or flushing metrics, into
+    /// the cadence of the worker without a separate thread. They run on the worker's thread in
+    /// the order they were registered, once for each call to `step_or_park` and the methods built
+    /// on it, however long the step parks, and so they should be cheap. A hook that panics is
+    /// dropped, and the panic does not reach the worker; the panic is reported as any other, by
+    /// the panic hook of the program.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// timely::execute_directly(|worker| {
+    ///
+    ///     use std::cell::Cell;
+    ///     use std::rc::Rc;
+    ///
+    ///     let steps = Rc::new(Cell::new(0));
+    ///     let counter = Rc::clone(&steps);
+    ///     worker.on_step_begin(move || counter.set(counter.get() + 1));
+    ///
+    ///     worker.step();
+    ///     worker.step();
+    ///     assert_eq!(steps.get(), 2);
+    /// });
+    /// ```
+    pub fn on_step_begin<F: FnMut()+'static>(&mut self, hook: F) {
+        self.step_hooks.borrow_mut().begin.push(Box::new(hook));
+    }
+
+    /// Registers `hook` to be invoked at the end of each step, after the worker has scheduled operators and released its messages.
+    ///
+    /// See [`Self::on_step_begin`] for how hooks are invoked. An operator panic caught by the
+    /// worker, when [`Config::catch_panics`] is set, ends the step as usual, and the hooks are
+    /// invoked, but later steps panic before they start.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// timely::execute_directly(|worker| {
+    ///
+    ///     use std::cell::RefCell;
+    ///     use std::rc::Rc;
+    ///     use timely::dataflow::operators::{ToStream, Inspect};
+    ///
+    ///     let events = Rc::new(RefCell::new(Vec::new()));
+    ///     let (seen, ended) = (Rc::clone(&events), Rc::clone(&events));
+    ///     worker.dataflow::<usize,_,_>(|scope| {
+    ///         (0 .. 1).to_stream(scope).inspect(move |_| seen.borrow_mut().push("record"));
+    ///     });
+    ///     worker.on_step_end(move || ended.borrow_mut().push("step end"));
+    ///
+    ///     worker.step();
+    ///     assert_eq!(*events.borrow(), vec!["record", "step end"]);
+    /// });
+    /// ```
+    pub fn on_step_end<F: FnMut()+'static>(&mut self, hook: F) {
+        self.step_hooks.borrow_mut().end.push(Box::new(hook));
+    }
+
+    /// Performs one step of the computation, scheduling at most `fuel` operators.
+    ///
+    /// This behaves as `self.step()`, except that once `fuel` operators have been scheduled, any
+    /// further active operators are left activated for a later step. Each scheduling consumes one
+    /// unit of fuel, including the scheduling of a scope within a dataflow. This allows a worker to
+    /// be interleaved with other work in a loop that bounds the time spent in each step.
+    ///
+    /// The return value indicates that dataflows remain and that some operators are activated, either
+    /// for want of fuel or by the step itself, and so the worker should be stepped again promptly.
+    /// Operators may also be activated by messages or other threads, which a `false` result does not
+    /// account for; calling `self.step_or_park()` when idle waits for these.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// timely::execute_from_args(::std::env::args(), |worker| {
+    ///
+    ///     use timely::dataflow::operators::{ToStream, Inspect};
+    ///
+    ///     worker.dataflow::<usize,_,_>(|scope| {
+    ///         (0 .. 10)
+    ///             .to_stream(scope)
+    ///             .inspect(|x| println!("{:?}", x));
+    ///     });
+    ///
+    ///     // Schedule two operators at a time, doing other work in between.
+    ///     while worker.step_with_fuel(2) {
+    ///         println!("doing other work");
+    ///     }
+    /// });
+    /// ```
+    pub fn step_with_fuel(&mut self, fuel: usize) -> bool {
+        self.activations.borrow_mut().set_fuel(Some(fuel));
+        let remaining = self.step();
+        let mut activations = self.activations.borrow_mut();
+        activations.set_fuel(None);
+        let events = !self.allocator.borrow().events().borrow().is_empty();
+        remaining && (activations.has_pending() || events)
+    }
+
     /// Calls `self.step()` as long as `func` evaluates to `true`.
     ///
     /// This method will continually execute even if there is not work
@@ -516,6 +1092,26 @@ impl<A: Allocate> Worker<A> {
     /// });
     /// ```
     pub fn peers(&self) -> usize { self.allocator.borrow().peers() }
+    /// The process and host of each peer worker, indexed by worker.
+    ///
+    /// Workers in the same process exchange data without serialization, and workers on the same
+    /// host without crossing the network. Hosts are identified by the host names of the process
+    /// addresses, and workers of a single process share one process and host.
+    ///
+    /// # Examples
+    /// ```
+    /// timely::execute_from_args(::std::env::args(), |worker| {
+    ///
+    ///     let topology = worker.topology();
+    ///     let local = (0 .. worker.peers())
+    ///         .filter(|peer| topology.same_process(worker.index(), *peer))
+    ///         .count();
+    ///
+    ///     println!("Worker {} shares its process with {} workers", worker.index(), local);
+    ///
+    /// });
+    /// ```
+    pub fn topology(&self) -> Topology { self.allocator.borrow().topology() }
 
     /// A timer started at the initiation of the timely computation.
     ///
@@ -533,6 +1129,25 @@ impl<A: Allocate> Worker<A> {
     /// ```
     pub fn timer(&self) -> Option<Instant> { self.timer }
 
+    /// A handle to the worker's random number generator, seeded from [`Config::seed`] and the worker's index.
+    ///
+    /// All handles draw from the same sequence, as do those operators obtain from their scopes with
+    /// [`AsWorker::rng`]. See [`WorkerRng`] for what reproducible draws require.
+    ///
+    /// # Examples
+    /// ```
+    /// timely::execute_from_args(::std::env::args(), |worker| {
+    ///
+    ///     let rng = worker.rng();
+    ///     let coin = rng.chance(0.5);
+    ///     let die = 1 + rng.below(6);
+    ///
+    ///     println!("Worker {} flipped {} and rolled {}", worker.index(), coin, die);
+    ///
+    /// });
+    /// ```
+    pub fn rng(&self) -> WorkerRng { WorkerRng::get(self) }
+
     /// Allocate a new worker-unique identifier.
     ///
     /// This method is public, though it is not expected to be widely used outside
@@ -669,12 +1284,33 @@ impl<A: Allocate> Worker<A> {
                 id: identifier,
                 addr: operator.path().to_vec(),
                 name: operator.name().to_string(),
+                label: None,
+                inputs: Vec::new(),
+                outputs: Vec::new(),
             });
             l.flush();
         }
 
         operator.get_internal_summary();
-        operator.set_external_summary();
+        // Setting the external summary schedules operators, whose panics are handled as in `step_or_park`.
+        if self.config.catch_panics || self.config.panic_context {
+            if let Err(payload) = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| operator.set_external_summary())) {
+                let panic = OperatorPanic::from_payload(payload, "Dataflow", identifier, &[dataflow_index]);
+                if !self.config.catch_panics {
+                    std::panic::resume_unwind(Box::new(panic.to_string()));
+                }
+                *self.panic.borrow_mut() = Some(panic);
+                // The dataflow is not installed, and its channels are forgotten.
+                let mut paths = self.paths.borrow_mut();
+                for channel in self.temp_channel_ids.borrow_mut().drain(..) {
+                    paths.remove(&channel);
+                }
+                return result;
+            }
+        }
+        else {
+            operator.set_external_summary();
+        }
 
         let mut temp_channel_ids = self.temp_channel_ids.borrow_mut();
         let channel_ids = temp_channel_ids.drain(..).collect::<Vec<_>>();
@@ -682,6 +1318,7 @@ impl<A: Allocate> Worker<A> {
         let wrapper = Wrapper {
             logging,
             identifier,
+            name: name.to_owned(),
             operate: Some(Box::new(operator)),
             resources: Some(Box::new(resources)),
             channel_ids,
@@ -721,6 +1358,129 @@ impl<A: Allocate> Worker<A> {
         self.dataflows.borrow().keys().cloned().collect()
     }
 
+    /// Lists the installed dataflows, in order of their indices, with their names and statuses.
+    ///
+    /// A dataflow remains installed until it completes, once its inputs are closed and its
+    /// operators have drained all of their work, or until it is dropped with `self.drop_dataflow()`.
+    /// Delayed activations count towards [`DataflowStatus::Active`] whether or not they are due,
+    /// and activations from other threads count once they have been received by a step.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::InputHandle;
+    /// use timely::worker::DataflowStatus;
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     let mut input = InputHandle::<u64, Vec<u64>>::new();
+    ///     worker.dataflow_named("Numbers", |scope| { input.to_stream(scope); });
+    ///     worker.step();
+    ///
+    ///     let dataflows = worker.dataflows();
+    ///     assert_eq!(dataflows.len(), 1);
+    ///     assert_eq!(dataflows[0].name, "Numbers");
+    ///     assert_eq!(dataflows[0].status, DataflowStatus::Idle);
+    ///
+    ///     worker.drop_dataflow(dataflows[0].index);
+    ///     assert!(worker.dataflows().is_empty());
+    /// });
+    /// ```
+    pub fn dataflows(&self) -> Vec<DataflowInfo> {
+        let activations = self.activations.borrow();
+        let mut dataflows = self.dataflows.borrow().iter().map(|(index, wrapper)| {
+            let status = if activations.is_pending(&[*index]) { DataflowStatus::Active } else { DataflowStatus::Idle };
+            DataflowInfo { index: *index, name: wrapper.name.clone(), status }
+        }).collect::<Vec<_>>();
+        dataflows.sort_by_key(|dataflow| dataflow.index);
+        dataflows
+    }
+
+    /// Lists `(identifier, count)` for each operator of the installed dataflows, with the number of capabilities it holds.
+    ///
+    /// Identifiers are the worker-unique identifiers of operators, as in their
+    /// [`OperatorInfo::global_id`](crate::dataflow::operators::generic::OperatorInfo) and in logged
+    /// events, and operators are listed in order of them. Counts are of the capabilities held by this
+    /// worker's instance of each operator, once for each output they are for, as reported to progress
+    /// tracking when the operator last returned from scheduling. A scope is listed among the operators
+    /// of its parent with the capabilities it reports at its outputs, and its operators are listed
+    /// with their own. Operators that have shut down are not listed, and hold no capabilities.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{Input, Probe};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+    ///         let (input, stream) = scope.new_input::<u64>();
+    ///         (input, stream.probe())
+    ///     });
+    ///     worker.step();
+    ///     // The input holds a capability, for its current time.
+    ///     assert!(worker.held_capabilities().iter().any(|(_operator, count)| *count == 1));
+    ///     input.close();
+    ///     worker.step_while(|| !probe.done());
+    ///     assert!(worker.held_capabilities().is_empty());
+    /// });
+    /// ```
+    pub fn held_capabilities(&self) -> Vec<(usize, usize)> {
+        let mut counts = Vec::new();
+        for wrapper in self.dataflows.borrow().values() {
+            if let Some(operate) = wrapper.operate.as_ref() {
+                operate.held_capabilities(&mut counts);
+            }
+        }
+        counts.sort();
+        counts
+    }
+
+    /// The operator panic caught by the worker, if any.
+    ///
+    /// Panics are only caught if the worker is configured with [`Config::catch_panics`]. Once a
+    /// panic has been caught the worker has dropped its dataflows and must not be stepped again.
+    pub fn operator_panic(&self) -> Option<OperatorPanic> {
+        self.panic.borrow().clone()
+    }
+
+    /// Removes and returns the errors of type `E` reported by operators, in the order they were reported.
+    ///
+    /// Operators report errors through the [`ErrorReporter`](crate::dataflow::operators::generic::ErrorReporter)
+    /// in their [`OperatorInfo`](crate::dataflow::operators::generic::OperatorInfo). Reported errors are
+    /// queued until drained, across any number of steps. Errors of types other than `E` remain queued.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::container::CapacityContainerBuilder;
+    /// use timely::dataflow::operators::{ToStream, Operator};
+    /// use timely::dataflow::channels::pact::Pipeline;
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         vec!["1", "two", "3"].to_stream(scope)
+    ///             .unary::<CapacityContainerBuilder<Vec<u64>>,_,_,_>(Pipeline, "Parse", |_capability, info| {
+    ///                 move |input, output| {
+    ///                     input.for_each(|time, data| {
+    ///                         let mut session = output.session(&time);
+    ///                         for text in data.drain(..) {
+    ///                             match text.parse::<u64>() {
+    ///                                 Ok(number) => session.give(number),
+    ///                                 Err(error) => info.errors.report(error),
+    ///                             }
+    ///                         }
+    ///                     });
+    ///                 }
+    ///             });
+    ///     });
+    ///     while worker.step() { }
+    ///
+    ///     let errors = worker.drain_errors::<std::num::ParseIntError>();
+    ///     assert_eq!(errors.len(), 1);
+    ///     assert_eq!(errors[0].operator, "Parse");
+    ///     assert!(worker.drain_errors::<std::num::ParseIntError>().is_empty());
+    /// });
+    /// ```
+    pub fn drain_errors<E: Any + Send>(&self) -> Vec<crate::dataflow::operators::generic::OperatorError<E>> {
+        crate::dataflow::operators::generic::ErrorQueue::get(self).borrow_mut().drain()
+    }
+
     /// Returns `true` if there is at least one dataflow under management.
     pub fn has_dataflows(&self) -> bool {
         !self.dataflows.borrow().is_empty()
@@ -747,6 +1507,10 @@ impl<A: Allocate> Clone for Worker<A> {
             activations: Rc::clone(&self.activations),
             active_dataflows: Vec::new(),
             temp_channel_ids: Rc::clone(&self.temp_channel_ids),
+            state: Rc::clone(&self.state),
+            panic: Rc::clone(&self.panic),
+            idle_since: Rc::clone(&self.idle_since),
+            step_hooks: Rc::clone(&self.step_hooks),
         }
     }
 }
@@ -754,6 +1518,7 @@ impl<A: Allocate> Clone for Worker<A> {
 struct Wrapper {
     logging: Option<TimelyLogger>,
     identifier: usize,
+    name: String,
     operate: Option<Box<dyn Schedule>>,
     resources: Option<Box<dyn Any>>,
     channel_ids: Vec<usize>,
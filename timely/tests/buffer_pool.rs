@@ -0,0 +1,38 @@
+use timely::communication::allocator::zero_copy::buffer_pool::BufferPool;
+
+#[test]
+fn buffers_are_recycled_by_size_class() {
+    let mut pool = BufferPool::new(1 << 10);
+    let small = pool.acquire(100);
+    let large = pool.acquire(200);
+    assert_eq!((small.capacity(), large.capacity()), (128, 256));
+    let (small_ptr, large_ptr) = (small.as_ptr(), large.as_ptr());
+    pool.release(small);
+    pool.release(large);
+    assert_eq!(pool.retained_bytes(), 384);
+
+    // Each size class hands back its own buffers, emptied.
+    let recycled_large = pool.acquire(129);
+    let recycled_small = pool.acquire(65);
+    assert_eq!((recycled_small.as_ptr(), recycled_large.as_ptr()), (small_ptr, large_ptr));
+    assert!(recycled_small.is_empty() && recycled_large.is_empty());
+    assert_eq!(pool.retained_bytes(), 0);
+    assert_eq!(pool.acquire(0).capacity(), 1);
+}
+
+#[test]
+fn retained_bytes_are_bounded() {
+    let mut pool = BufferPool::new(1 << 10);
+    // A burst of buffers, of which only those within the bound are retained.
+    let burst = (0 .. 10).map(|_| pool.acquire(256)).collect::<Vec<_>>();
+    for buffer in burst { pool.release(buffer); }
+    assert_eq!(pool.retained_bytes(), 1 << 10);
+    pool.release(Vec::with_capacity(1));
+    assert_eq!(pool.retained_bytes(), 1 << 10);
+
+    // Buffers outside every size class are freed rather than retained.
+    let mut unclassed = BufferPool::new(1 << 10);
+    unclassed.release(Vec::with_capacity(100));
+    unclassed.release(Vec::new());
+    assert_eq!(unclassed.retained_bytes(), 0);
+}
@@ -0,0 +1,60 @@
+#![cfg(feature = "capability-provenance")]
+
+use std::time::Duration;
+
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::{Input, Operator, Probe};
+use timely::dataflow::operators::provenance;
+use timely::dataflow::operators::stall::StallDetector;
+
+#[test]
+fn stalls_report_leaked_capabilities() {
+    timely::execute_directly(|worker| {
+        let (mut input, probe, line) = worker.dataflow::<u64,_,_>(|scope| {
+            let (handle, stream) = scope.new_input::<u64>();
+            // The line on which the leaked capabilities are retained.
+            let line = line!() + 6;
+            let probe = stream.unary_frontier(Pipeline, "Leaky", move |_capability, _info| {
+                let mut leaked = Vec::new();
+                move |(input, frontier), output| {
+                    input.for_each_time(|time, data| {
+                        output.session(&time).give_containers(data);
+                        let mut capability = time.retain();
+                        // Downgrading keeps the location the capability was created at.
+                        capability.downgrade(&(capability.time() + 1));
+                        leaked.push(capability);
+                    });
+                    // The capabilities are only released once the input completes.
+                    if frontier.is_empty() {
+                        leaked.clear();
+                    }
+                }
+            })
+            .probe();
+            (handle, probe, line)
+        });
+
+        let mut detector = StallDetector::new(&probe, Duration::ZERO);
+        input.send(0);
+        input.advance_to(5);
+        worker.step_while(|| probe.less_than(&1));
+        for _ in 0 .. 10 { worker.step(); }
+
+        // The operator holds the frontier at the leaked capability.
+        assert!(probe.less_equal(&1) && !probe.less_than(&1));
+        assert!(detector.check().is_none());
+        let report = detector.check().unwrap();
+        assert_eq!(report.frontier.elements(), &[1]);
+        let leaks = report.capabilities.iter().filter(|origin| origin.location.file().ends_with("capability_provenance.rs")).collect::<Vec<_>>();
+        assert_eq!(leaks.len(), 1, "{}", report);
+        assert_eq!(leaks[0].time, "1");
+        assert_eq!(leaks[0].location.line(), line);
+        assert!(report.to_string().contains(&format!("capability at 1 created at {}", leaks[0].location)));
+
+        // Once the capability is released, the dataflow completes and no stall is reported.
+        drop(input);
+        worker.step_while(|| !probe.done());
+        assert!(detector.check().is_none());
+        assert!(provenance::outstanding().iter().all(|origin| !origin.location.file().ends_with("capability_provenance.rs")));
+    });
+}
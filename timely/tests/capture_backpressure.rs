@@ -0,0 +1,51 @@
+use std::sync::Mutex;
+use std::sync::mpsc::{Receiver, Sender};
+
+use timely::dataflow::operators::{Capture, Input};
+use timely::dataflow::operators::capture::{Event, EventPusher, Extract};
+
+/// Forwards events to a channel, waiting for a permit before each one.
+struct Gated {
+    permits: Receiver<()>,
+    events: Sender<Event<u64, Vec<u64>>>,
+}
+
+impl EventPusher<u64, Vec<u64>> for Gated {
+    fn push(&mut self, event: Event<u64, Vec<u64>>) {
+        // Once the permits are dropped, events pass freely.
+        let _ = self.permits.recv();
+        let _ = self.events.send(event);
+    }
+}
+
+#[test]
+fn slow_pushers_hold_back_the_frontier_until_they_catch_up() {
+    let (permit, permits) = std::sync::mpsc::channel();
+    let (events, received) = std::sync::mpsc::channel();
+    let pusher = Mutex::new(Some(Gated { permits, events }));
+    timely::execute_directly(move |worker| {
+        let pusher = pusher.lock().unwrap().take().unwrap();
+        let (mut input, handle) = worker.dataflow::<u64,_,_>(|scope| {
+            let (input, stream) = scope.new_input::<u64>();
+            (input, stream.capture_into_with_backpressure(pusher, 2))
+        });
+        for round in 0..10 {
+            input.send(round);
+            input.advance_to(round + 1);
+        }
+        for _ in 0..20 { worker.step(); }
+        // The pusher has received nothing, and the frontier waits at the first unqueued records.
+        assert!(handle.less_equal(&0));
+
+        // Permits let events through, and the frontier advances at the pusher's pace.
+        for _ in 0..6 { permit.send(()).unwrap(); }
+        worker.step_while(|| handle.less_equal(&0));
+        assert!(!handle.done());
+
+        drop(permit);
+        input.close();
+        worker.step_while(|| !handle.done());
+    });
+
+    assert_eq!(received.extract(), (0..10).map(|round| (round, vec![round])).collect::<Vec<_>>());
+}
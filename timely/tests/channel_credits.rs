@@ -0,0 +1,153 @@
+use std::cell::RefCell;
+use std::io::Write;
+use std::ops::DerefMut;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+use timely::bytes::arc::Bytes;
+use timely::communication::{Bytesable, Push};
+use timely::communication::allocator::zero_copy::buffer_pool::BufferPool;
+use timely::communication::allocator::zero_copy::bytes_exchange::{BytesPush, SendEndpoint};
+use timely::communication::allocator::zero_copy::bytes_slab::BytesRefill;
+use timely::communication::allocator::zero_copy::credits::{self, SendCredits, CREDIT_CHANNEL};
+use timely::communication::allocator::zero_copy::push_pull::Pusher;
+use timely::communication::networking::MessageHeader;
+use timely::dataflow::operators::{Exchange, Input, Inspect, Probe};
+
+/// A message serialized as its length in bytes.
+struct Payload(usize);
+
+impl Bytesable for Payload {
+    fn from_bytes(bytes: Bytes) -> Self { Payload(bytes.len()) }
+    fn length_in_bytes(&self) -> usize { self.0 }
+    fn into_bytes<W: Write>(&self, writer: &mut W) { writer.write_all(&vec![0; self.0]).unwrap(); }
+}
+
+/// Records the frames sent, in place of a network connection.
+#[derive(Clone, Default)]
+struct Frames(Rc<RefCell<Vec<Bytes>>>);
+
+impl BytesPush for Frames {
+    fn extend<I: IntoIterator<Item=Bytes>>(&mut self, iter: I) { self.0.borrow_mut().extend(iter); }
+}
+
+impl Frames {
+    fn headers(&self) -> Vec<MessageHeader> {
+        self.0.borrow().iter().map(|frame| MessageHeader::try_read(&frame[..]).unwrap()).collect()
+    }
+}
+
+fn sender(frames: &Frames) -> Rc<RefCell<SendEndpoint<Frames>>> {
+    let refill = BytesRefill {
+        logic: Arc::new(|size| Box::new(vec![0_u8; size]) as Box<dyn DerefMut<Target=[u8]>>),
+        limit: None,
+    };
+    Rc::new(RefCell::new(SendEndpoint::new(frames.clone(), refill)))
+}
+
+#[test]
+fn pusher_queues_messages_without_credits() {
+    let frames = Frames::default();
+    let sender = sender(&frames);
+    let credits = Rc::new(RefCell::new(SendCredits::new(2)));
+    let template = MessageHeader { channel: 7, source: 0, target_lower: 1, target_upper: 2, length: 0, seqno: 0 };
+    let mut pusher = Pusher::new(template, Rc::clone(&sender)).with_credits(Rc::clone(&credits));
+
+    for length in 1 ..= 5 { pusher.push(&mut Some(Payload(length))); }
+    assert_eq!(frames.headers().iter().map(|header| header.seqno).collect::<Vec<_>>(), vec![0, 1]);
+    assert_eq!((credits.borrow().available(), credits.borrow().queued()), (0, 3));
+
+    // Granted credits send queued messages in order, and leave the surplus for later messages.
+    credits.borrow_mut().grant(1, &mut sender.borrow_mut());
+    assert_eq!(frames.headers().iter().map(|header| header.seqno).collect::<Vec<_>>(), vec![0, 1, 2]);
+    credits.borrow_mut().grant(4, &mut sender.borrow_mut());
+    pusher.push(&mut Some(Payload(6)));
+    let headers = frames.headers();
+    assert_eq!(headers.iter().map(|header| (header.length, header.seqno)).collect::<Vec<_>>(), (1 ..= 6).zip(0 ..).collect::<Vec<_>>());
+    assert_eq!(frames.0.borrow().iter().map(|frame| frame.len()).sum::<usize>(), 6 * 48 + 21);
+    assert_eq!((credits.borrow().available(), credits.borrow().queued()), (1, 0));
+}
+
+#[test]
+fn sent_queued_messages_return_their_buffers_to_the_pool() {
+    let frames = Frames::default();
+    let sender = sender(&frames);
+    let pool = Rc::new(RefCell::new(BufferPool::new(256)));
+    let credits = Rc::new(RefCell::new(SendCredits::new(0).with_pool(Rc::clone(&pool))));
+    let template = MessageHeader { channel: 7, source: 0, target_lower: 1, target_upper: 2, length: 0, seqno: 0 };
+    let mut pusher = Pusher::new(template, Rc::clone(&sender)).with_credits(Rc::clone(&credits));
+
+    // Frames of 48 + 10 bytes are queued in buffers of 64 bytes, of which the pool retains four.
+    for _ in 0 .. 6 { pusher.push(&mut Some(Payload(10))); }
+    assert_eq!(pool.borrow().retained_bytes(), 0);
+    credits.borrow_mut().grant(6, &mut sender.borrow_mut());
+    assert_eq!(frames.headers().len(), 6);
+    assert_eq!(pool.borrow().retained_bytes(), 256);
+
+    // Later queued messages reuse the retained buffers.
+    for _ in 0 .. 3 { pusher.push(&mut Some(Payload(10))); }
+    assert_eq!(pool.borrow().retained_bytes(), 64);
+}
+
+#[test]
+fn grants_round_trip() {
+    let frames = Frames::default();
+    credits::write_grant(&mut sender(&frames).borrow_mut(), 7, 3, 1, 12);
+    let frames = frames.0.borrow();
+    let header = MessageHeader::try_read(&frames[0][..]).unwrap();
+    assert_eq!((header.channel, header.source, header.target_lower, header.target_upper), (CREDIT_CHANNEL, 3, 1, 2));
+    assert_eq!(credits::read_grant(&frames[0][header.header_bytes() ..]), Some((7, 12)));
+}
+
+#[test]
+fn processes_exchange_beyond_their_credits() {
+    let addresses = vec!["localhost:52101".to_owned(), "localhost:52102".to_owned()];
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let processes = (0 .. 2).map(|process| {
+        let addresses = addresses.clone();
+        let received = Arc::clone(&received);
+        std::thread::spawn(move || {
+            let communication = timely::CommunicationConfig::Cluster {
+                threads: 1,
+                process,
+                addresses,
+                report: false,
+                zerocopy: false,
+                max_frame_bytes: None,
+                channel_credits: Some(2),
+                buffer_pool_bytes: Some(4096),
+                startup_timeout: None,
+                pretouch: false,
+                log_fn: Arc::new(|_| None),
+            };
+            let config = timely::Config { communication, worker: timely::WorkerConfig::default() };
+            timely::execute(config, move |worker| {
+                let index = worker.index();
+                let received = Arc::clone(&received);
+                let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+                    let (input, stream) = scope.new_input::<u64>();
+                    let probe = stream.exchange(|x| *x)
+                                      .inspect(move |x| received.lock().unwrap().push((index, *x)))
+                                      .probe();
+                    (input, probe)
+                });
+                // Each round sends many more messages to the other process than it has credits for.
+                for round in 0 .. 10u64 {
+                    for x in 0 .. 100u64 {
+                        input.send(100 * round + x);
+                        input.flush();
+                    }
+                    input.advance_to(round + 1);
+                    worker.step_while(|| probe.less_than(input.time()));
+                }
+            }).unwrap().join();
+        })
+    }).collect::<Vec<_>>();
+    for process in processes { process.join().unwrap(); }
+
+    let mut received = received.lock().unwrap().clone();
+    received.sort();
+    let mut expected = (0 .. 2).flat_map(|_| 0 .. 1000u64).map(|x| ((x % 2) as usize, x)).collect::<Vec<_>>();
+    expected.sort();
+    assert_eq!(received, expected);
+}
@@ -0,0 +1,65 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+use timely::communication::{Allocate, Exchangeable, Pull, Push};
+use timely::communication::allocator::{AllocateBuilder, Thread};
+use timely::dataflow::operators::{Exchange, Inspect, ToStream};
+use timely::WorkerConfig;
+
+/// Builds a single-worker allocator that records the identifiers of the channels it allocates.
+struct MockBuilder {
+    allocated: Arc<Mutex<Vec<usize>>>,
+}
+
+impl AllocateBuilder for MockBuilder {
+    type Allocator = MockAllocator;
+    fn build(self) -> MockAllocator {
+        MockAllocator { allocated: self.allocated, events: Rc::new(RefCell::new(Vec::new())) }
+    }
+}
+
+struct MockAllocator {
+    allocated: Arc<Mutex<Vec<usize>>>,
+    events: Rc<RefCell<Vec<usize>>>,
+}
+
+impl Allocate for MockAllocator {
+    fn index(&self) -> usize { 0 }
+    fn peers(&self) -> usize { 1 }
+    fn allocate<T: Exchangeable>(&mut self, identifier: usize) -> (Vec<Box<dyn Push<T>>>, Box<dyn Pull<T>>) {
+        self.allocated.lock().unwrap().push(identifier);
+        let (pusher, puller) = Thread::new_from(identifier, Rc::clone(&self.events));
+        (vec![Box::new(pusher)], Box::new(puller))
+    }
+    fn events(&self) -> &Rc<RefCell<Vec<usize>>> {
+        &self.events
+    }
+}
+
+#[test]
+fn execute_with_mock_allocator() {
+    let allocated = Arc::new(Mutex::new(Vec::new()));
+    let builders = vec![MockBuilder { allocated: Arc::clone(&allocated) }];
+    let results = timely::execute_with_allocators(builders, WorkerConfig::default(), |worker| {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let seen_inner = Rc::clone(&seen);
+        worker.dataflow::<u64, _, _>(move |scope| {
+            (0..10u64)
+                .to_stream(scope)
+                .exchange(|x| *x)
+                .inspect(move |x| seen_inner.borrow_mut().push(*x));
+        });
+        while worker.step() { }
+        let seen = seen.borrow().clone();
+        (worker.index(), worker.peers(), seen)
+    })
+    .unwrap()
+    .join();
+
+    let (index, peers, seen) = results.into_iter().next().unwrap().unwrap();
+    assert_eq!((index, peers), (0, 1));
+    assert_eq!(seen, (0..10).collect::<Vec<_>>());
+    // The exchange and progress channels were allocated through the mock.
+    assert!(allocated.lock().unwrap().len() >= 2);
+}
@@ -0,0 +1,34 @@
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::Probe;
+use timely::worker::DataflowStatus;
+
+#[test]
+fn dataflows_are_listed_until_dropped_or_complete() {
+    timely::execute_directly(|worker| {
+        let mut first = InputHandle::<u64, Vec<u64>>::new();
+        let mut second = InputHandle::<u64, Vec<u64>>::new();
+        worker.dataflow_named("First", |scope| { first.to_stream(scope).probe(); });
+        let probe = worker.dataflow_named("Second", |scope| second.to_stream(scope).probe());
+        while worker.dataflows().iter().any(|dataflow| dataflow.status == DataflowStatus::Active) {
+            worker.step();
+        }
+
+        let dataflows = worker.dataflows();
+        let names = dataflows.iter().map(|dataflow| (dataflow.name.as_str(), dataflow.status)).collect::<Vec<_>>();
+        assert_eq!(names, vec![("First", DataflowStatus::Idle), ("Second", DataflowStatus::Idle)]);
+        assert!(dataflows[0].index < dataflows[1].index);
+
+        // Advancing an input activates its dataflow alone.
+        second.advance_to(1);
+        let statuses = worker.dataflows().iter().map(|dataflow| dataflow.status).collect::<Vec<_>>();
+        assert_eq!(statuses, vec![DataflowStatus::Idle, DataflowStatus::Active]);
+
+        // A dropped dataflow is no longer listed, and a completed dataflow is removed.
+        worker.drop_dataflow(dataflows[0].index);
+        assert_eq!(worker.dataflows().iter().map(|dataflow| dataflow.index).collect::<Vec<_>>(), vec![dataflows[1].index]);
+        second.close();
+        worker.step_while(|| !probe.done());
+        while worker.step() { }
+        assert!(worker.dataflows().is_empty());
+    });
+}
@@ -0,0 +1,67 @@
+use std::cell::RefCell;
+use std::io::Write;
+use std::ops::DerefMut;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use timely::bytes::arc::Bytes;
+use timely::communication::{Bytesable, Push};
+use timely::communication::allocator::zero_copy::bytes_exchange::{BytesPush, SendEndpoint};
+use timely::communication::allocator::zero_copy::bytes_slab::BytesRefill;
+use timely::communication::allocator::zero_copy::push_pull::{FrameTooLarge, Pusher};
+use timely::communication::networking::MessageHeader;
+
+/// A message serialized as its length in bytes.
+struct Payload(usize);
+
+impl Bytesable for Payload {
+    fn from_bytes(bytes: Bytes) -> Self { Payload(bytes.len()) }
+    fn length_in_bytes(&self) -> usize { self.0 }
+    fn into_bytes<W: Write>(&self, writer: &mut W) { writer.write_all(&vec![0; self.0]).unwrap(); }
+}
+
+/// Records the frames sent, in place of a network connection.
+#[derive(Clone, Default)]
+struct Frames(Rc<RefCell<Vec<Bytes>>>);
+
+impl BytesPush for Frames {
+    fn extend<I: IntoIterator<Item=Bytes>>(&mut self, iter: I) { self.0.borrow_mut().extend(iter); }
+}
+
+fn pusher(frames: &Frames, max_frame_bytes: usize) -> Pusher<Payload, Frames> {
+    let refill = BytesRefill {
+        logic: Arc::new(|size| Box::new(vec![0_u8; size]) as Box<dyn DerefMut<Target=[u8]>>),
+        limit: None,
+    };
+    let header = MessageHeader { channel: 7, source: 0, target_lower: 1, target_upper: 2, length: 0, seqno: 0 };
+    let sender = Rc::new(RefCell::new(SendEndpoint::new(frames.clone(), refill)));
+    Pusher::new(header, sender).with_max_frame_bytes(Some(max_frame_bytes))
+}
+
+#[test]
+fn oversized_message_is_rejected_with_named_error() {
+    let frames = Frames::default();
+    let mut pusher = pusher(&frames, 64);
+
+    // A frame of the 48 byte header and 16 bytes of payload fits exactly.
+    assert_eq!(pusher.try_push(&mut Some(Payload(16))), Ok(()));
+    let mut oversized = Some(Payload(1024));
+    let error = pusher.try_push(&mut oversized).unwrap_err();
+    assert_eq!(error, FrameTooLarge { channel: 7, frame_bytes: 1072, max_frame_bytes: 64 });
+    assert!(error.to_string().contains("channel 7"));
+    assert!(oversized.is_some());
+    assert_eq!(pusher.try_push(&mut Some(Payload(8))), Ok(()));
+
+    // The rejected message wrote nothing, and the frames around it are intact and in sequence.
+    let frames = frames.0.borrow();
+    let headers = frames.iter().map(|frame| MessageHeader::try_read(&frame[..]).unwrap()).collect::<Vec<_>>();
+    assert_eq!(headers.iter().map(|header| (header.length, header.seqno)).collect::<Vec<_>>(), vec![(16, 0), (8, 1)]);
+    assert_eq!(frames.iter().map(|frame| frame.len()).sum::<usize>(), 64 + 56);
+}
+
+#[test]
+#[should_panic(expected = "message on channel 7 serializes to a frame of 1072 bytes")]
+fn push_panics_with_named_error() {
+    let frames = Frames::default();
+    pusher(&frames, 64).push(&mut Some(Payload(1024)));
+}
@@ -0,0 +1,57 @@
+use std::sync::{Arc, Barrier};
+
+use timely::dataflow::operators::{Exchange, Input, Probe};
+use timely::progress::Source;
+use timely::progress::spread::{FrontierSpread, Spread};
+use timely::worker::ProgressMode;
+use timely::{Config, WorkerConfig};
+
+/// Steps `worker` until the frontiers `spread` reports at `source` of `scope` satisfy `done`.
+fn step_until<A: timely::communication::Allocate>(worker: &mut timely::worker::Worker<A>, spread: &FrontierSpread<u64>, scope: usize, source: Source, done: impl Fn(&[Vec<u64>])->bool) {
+    loop {
+        worker.log_register().unwrap().flush();
+        let frontiers = spread.frontiers(scope, source).into_iter().map(|frontier| frontier.elements().to_vec()).collect::<Vec<_>>();
+        if done(&frontiers) { break; }
+        worker.step();
+    }
+}
+
+#[test]
+fn stragglers_are_slowest() {
+    // Workers report all of their updates, rather than only those that advance the shared frontier.
+    let config = Config {
+        worker: WorkerConfig::default().progress_mode(ProgressMode::Eager),
+        ..Config::process(3)
+    };
+    // Workers wait for one another between rounds, once each has seen the updates of all.
+    let barrier = Arc::new(Barrier::new(3));
+    timely::execute(config, move |worker| {
+        let index = worker.index() as u64;
+        let spread = FrontierSpread::<u64>::register(worker);
+        let (mut input, (scope, source), probe) = worker.dataflow::<u64,_,_>(|scope| {
+            let (input, stream) = scope.new_input::<u64>();
+            (input, (scope.identifier(), *stream.name()), stream.exchange(|x| *x).probe())
+        });
+
+        // Before any updates, all workers are at the minimal frontier.
+        assert_eq!(spread.spread(scope, source), Some(Spread { slowest: (0, 0), fastest: (0, 0) }));
+
+        // Each worker advances its input to a time of its own, and the last worker lags.
+        input.advance_to(10 * (2 - index));
+        step_until(worker, &spread, scope, source, |frontiers| frontiers == [vec![20], vec![10], vec![0]]);
+        assert_eq!(spread.spread(scope, source), Some(Spread { slowest: (2, 0), fastest: (0, 20) }));
+        barrier.wait();
+
+        // Workers that are done are not considered, and once all are the spread is undefined.
+        let mut input = Some(input);
+        if index == 0 { input = None; }
+        else if let Some(input) = input.as_mut() { input.advance_to(30); }
+        step_until(worker, &spread, scope, source, |frontiers| frontiers == [vec![], vec![30], vec![30]]);
+        assert_eq!(spread.spread(scope, source), Some(Spread { slowest: (1, 30), fastest: (1, 30) }));
+        barrier.wait();
+        input.take();
+        worker.step_while(|| !probe.done());
+        step_until(worker, &spread, scope, source, |frontiers| frontiers.iter().all(|frontier| frontier.is_empty()));
+        assert_eq!(spread.spread(scope, source), None);
+    }).unwrap();
+}
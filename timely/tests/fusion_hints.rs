@@ -0,0 +1,40 @@
+use std::time::Duration;
+
+use timely::dataflow::operators::{Exchange, Filter, Input, Map, Probe};
+use timely::logging::FusionHints;
+
+#[test]
+fn pipelined_operators_are_candidates() {
+    timely::execute_directly(|worker| {
+        let hints = FusionHints::register(&mut worker.log_register().unwrap());
+        let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+            let (input, stream) = scope.new_input::<u64>();
+            let probe = stream.map(|x| x + 1)
+                              .filter(|x| x % 2 == 0)
+                              .exchange(|x| *x)
+                              .map(|x| x * 2)
+                              .probe();
+            (input, probe)
+        });
+        for round in 0..10 {
+            input.send(round);
+            input.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(input.time()));
+        }
+
+        let hints = hints.borrow();
+        let pairs = hints.candidates(Duration::from_secs(1)).into_iter().map(|candidate| (candidate.upstream_name, candidate.downstream_name)).collect::<Vec<_>>();
+        // The filter sends its records to the exchange through an exchange channel, and so they are
+        // not candidates, and neither is the dataflow's scope. Maps are named `FlatMap`.
+        assert_eq!(pairs, vec![
+            ("Input".to_owned(), "FlatMap".to_owned()),
+            ("FlatMap".to_owned(), "Filter".to_owned()),
+            ("Exchange".to_owned(), "FlatMap".to_owned()),
+            ("FlatMap".to_owned(), "Probe".to_owned()),
+        ]);
+        assert!(hints.report(Duration::from_secs(1)).contains("FlatMap (id"));
+        // No operator is scheduled in no time.
+        assert!(hints.candidates(Duration::ZERO).is_empty());
+        assert!(hints.report(Duration::ZERO).starts_with("no fusion candidates"));
+    });
+}
@@ -0,0 +1,48 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::{Input, Inspect, Operator, Probe};
+
+#[test]
+fn leaked_capabilities_are_counted() {
+    timely::execute_directly(|worker| {
+        let leaky_id = Rc::new(Cell::new(None));
+        let leaky_id_inner = Rc::clone(&leaky_id);
+        let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+            let (handle, stream) = scope.new_input::<u64>();
+            let probe = stream
+                .unary(Pipeline, "Leaky", move |_capability, info| {
+                    leaky_id_inner.set(Some(info.global_id));
+                    let mut leaked = Vec::new();
+                    move |input, output| {
+                        input.for_each_time(|time, data| {
+                            output.session(&time).give_containers(data);
+                            // Each capability is retained and never released.
+                            leaked.push(time.retain());
+                        });
+                    }
+                })
+                .inspect(|_| { })
+                .probe();
+            (handle, probe)
+        });
+        let leaky_id = leaky_id.get().unwrap();
+
+        input.send(0);
+        input.advance_to(1);
+        input.send(1);
+        input.close();
+        for _ in 0 .. 10 { worker.step(); }
+
+        // The leaky operator holds both capabilities, and the input and the others hold none.
+        let counts = worker.held_capabilities();
+        assert!(counts.contains(&(leaky_id, 2)), "{:?}", counts);
+        assert!(counts.iter().all(|(operator, count)| *operator == leaky_id || *count == 0), "{:?}", counts);
+        assert!(probe.less_equal(&0));
+
+        // The dataflow cannot complete, and is dropped instead.
+        for dataflow in worker.installed_dataflows() { worker.drop_dataflow(dataflow); }
+        assert!(worker.held_capabilities().is_empty());
+    });
+}
@@ -0,0 +1,58 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::{Concat, ToStream};
+use timely::dataflow::operators::generic::builder_rc::OperatorBuilder;
+use timely::logging::{TimelyEvent, TimelyEventBuilder};
+
+#[test]
+fn operators_report_their_ports() {
+    timely::execute_directly(|worker| {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let events_inner = Rc::clone(&events);
+        worker.log_register().unwrap().insert::<TimelyEventBuilder,_>("timely", move |_time, data| {
+            if let Some(data) = data {
+                events_inner.borrow_mut().extend(data.iter().map(|(_, event)| event.clone()));
+            }
+        });
+
+        worker.dataflow::<u64, _, _>(|scope| {
+            let numbers = (0..10u64).to_stream(scope);
+            let others = (10..20u64).to_stream(scope);
+            let mut builder = OperatorBuilder::new("Named".to_owned(), scope.clone());
+            let mut numbers_input = builder.new_input(&numbers, Pipeline);
+            let mut others_input = builder.new_input(&others, Pipeline);
+            let (_output, stream) = builder.new_output::<Vec<u64>>();
+            builder.set_input_name(1, "others");
+            builder.set_output_name(0, "sums");
+            builder.build(|_capabilities| move |_frontiers| {
+                numbers_input.for_each(|_time, _data| { });
+                others_input.for_each(|_time, _data| { });
+            });
+            stream.concat(&numbers);
+        });
+        while worker.step() { }
+        worker.log_register().unwrap().flush();
+
+        let events = events.borrow();
+        let ports = |name: &str| events.iter().find_map(|event| match event {
+            TimelyEvent::Operates(operates) if operates.name == name => Some((operates.inputs.clone(), operates.outputs.clone())),
+            _ => None,
+        }).unwrap();
+        // Ports are counted whether or not they are named.
+        assert_eq!(ports("Named"), (vec![None, Some("others".to_owned())], vec![Some("sums".to_owned())]));
+        assert_eq!(ports("Concatenate"), (vec![None, None], vec![None]));
+        assert_eq!(ports("ToStreamBuilder"), (vec![], vec![None]));
+    });
+}
+
+#[test]
+#[should_panic(expected = "Named: no output port 1 to name")]
+fn naming_a_missing_port_panics() {
+    timely::example(|scope| {
+        let mut builder = OperatorBuilder::new("Named".to_owned(), scope.clone());
+        let _output = builder.new_output::<Vec<u64>>();
+        builder.set_output_name(1, "missing");
+    });
+}
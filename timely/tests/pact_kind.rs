@@ -0,0 +1,37 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use timely::dataflow::channels::pact::PactKind;
+use timely::dataflow::operators::{Exchange, Inspect, Map, ToStream};
+use timely::logging::{TimelyEvent, TimelyEventBuilder};
+
+#[test]
+fn channels_report_pact_kind() {
+    timely::execute_directly(|worker| {
+        let kinds = Rc::new(RefCell::new(Vec::new()));
+        let kinds_inner = Rc::clone(&kinds);
+        worker.log_register().unwrap().insert::<TimelyEventBuilder,_>("timely", move |_time, data| {
+            if let Some(data) = data {
+                for (_, event) in data.iter() {
+                    if let TimelyEvent::Channels(channel) = event {
+                        kinds_inner.borrow_mut().push((channel.target, channel.pact));
+                    }
+                }
+            }
+        });
+
+        worker.dataflow::<u64, _, _>(|scope| {
+            (0..10u64)
+                .to_stream(scope)
+                .map(|x| x + 1)
+                .exchange(|x| *x)
+                .inspect(|_| ());
+        });
+        worker.log_register().unwrap().flush();
+
+        // Sorted by target operator, which are numbered in construction order: map, exchange, inspect.
+        let mut kinds = kinds.borrow().clone();
+        kinds.sort();
+        assert_eq!(kinds.iter().map(|(_, kind)| *kind).collect::<Vec<_>>(), vec![PactKind::Pipeline, PactKind::Exchange, PactKind::Pipeline]);
+    });
+}
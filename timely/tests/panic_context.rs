@@ -0,0 +1,47 @@
+use timely::{Config, WorkerConfig};
+use timely::dataflow::Scope;
+use timely::dataflow::operators::{Enter, Input, Inspect, Leave, Probe};
+
+/// The errors of a single worker that panics on its second record, in a region, with `worker_config`.
+fn errors(worker_config: WorkerConfig) -> Vec<Result<(), String>> {
+    let config = Config { worker: worker_config, ..Config::thread() };
+    timely::execute(config, |worker| {
+        let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+            let (input, stream) = scope.new_input::<u64>();
+            let inspected = scope.region(|region| {
+                stream.enter(region)
+                      .inspect(|x| assert!(*x < 1, "record {}", x))
+                      .leave()
+            });
+            (input, inspected.probe())
+        });
+        // The first record passes the operator, and the second panics in a later step.
+        input.send(0);
+        worker.step();
+        input.send(1);
+        input.close();
+        // A worker that caught the panic must not be stepped again.
+        while !probe.done() && worker.operator_panic().is_none() { worker.step(); }
+    }).unwrap().join()
+}
+
+#[test]
+fn panics_name_the_operator_and_its_address() {
+    let errors = errors(WorkerConfig::default().panic_context(true));
+    let error = errors[0].as_ref().unwrap_err();
+    assert!(error.starts_with("operator \"InspectBatch\" (id "), "{}", error);
+    assert!(error.ends_with(", address [0, 2, 1]) panicked: record 1"), "{}", error);
+}
+
+#[test]
+fn caught_panics_are_reported_with_the_same_context() {
+    let errors = errors(WorkerConfig::default().panic_context(true).catch_panics(true));
+    let error = errors[0].as_ref().unwrap_err();
+    assert!(error.ends_with(", address [0, 2, 1]) panicked: record 1"), "{}", error);
+}
+
+#[test]
+fn panics_are_unchanged_by_default() {
+    let errors = errors(WorkerConfig::default());
+    assert_eq!(errors[0], Err("record 1".to_string()));
+}
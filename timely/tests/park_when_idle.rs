@@ -0,0 +1,56 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use timely::dataflow::operators::Inspect;
+use timely::dataflow::operators::generic::operator::source;
+use timely::scheduling::Scheduler;
+
+/// Steps a worker with `worker_config` until a source, activated from another thread after `delay`,
+/// completes. Returns the number of steps taken, and the time from activation to completion.
+fn steps_until_woken(worker_config: timely::WorkerConfig, delay: Duration) -> (usize, Duration) {
+    let config = timely::Config { worker: worker_config, ..timely::Config::thread() };
+    timely::execute(config, move |worker| {
+        let (send, recv) = mpsc::channel();
+        let woken = Arc::new(AtomicBool::new(false));
+        let woken_source = Arc::clone(&woken);
+        worker.dataflow::<u64,_,_>(|scope| {
+            source(scope, "Woken", |capability, info| {
+                send.send(scope.sync_activator_for(info.address.to_vec())).unwrap();
+                let mut capability = Some(capability);
+                move |output| {
+                    if woken_source.load(Ordering::SeqCst) {
+                        if let Some(capability) = capability.take() {
+                            output.session(&capability).give(0u64);
+                        }
+                    }
+                }
+            })
+            .container::<Vec<_>>()
+            .inspect(|_| { });
+        });
+        let activator = recv.recv().unwrap();
+        let activated = std::thread::spawn(move || {
+            std::thread::sleep(delay);
+            let activated = Instant::now();
+            woken.store(true, Ordering::SeqCst);
+            activator.activate().unwrap();
+            activated
+        });
+        let mut steps = 0;
+        while worker.step() { steps += 1; }
+        let completed = Instant::now();
+        (steps, completed.duration_since(activated.join().unwrap()))
+    }).unwrap().join().pop().unwrap().unwrap()
+}
+
+#[test]
+fn idle_worker_parks_and_wakes_on_activation() {
+    let delay = Duration::from_millis(200);
+    let (parked_steps, latency) = steps_until_woken(timely::WorkerConfig::default().park_when_idle(Duration::from_millis(10)), delay);
+    let (spinning_steps, _) = steps_until_woken(timely::WorkerConfig::default(), delay);
+    // A spinning worker steps throughout the delay, while a parked worker only steps in its grace period.
+    assert!(parked_steps < spinning_steps / 2, "parked {} spinning {}", parked_steps, spinning_steps);
+    assert!(latency < Duration::from_secs(1), "woken after {:?}", latency);
+}
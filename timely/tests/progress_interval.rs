@@ -0,0 +1,76 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::Duration;
+
+use timely::dataflow::operators::{Exchange, Input, Inspect, Probe};
+use timely::dataflow::InputHandle;
+use timely::logging::TimelyProgressEventBuilder;
+use timely::worker::ProgressMode;
+use timely::{Config, WorkerConfig};
+
+/// Runs a dataflow on two workers, returning for each worker the number of progress messages
+/// it sent and the records it received in each round.
+fn run(progress_interval: Duration) -> Vec<(usize, Vec<(u64, u64)>)> {
+    let config = Config {
+        worker: WorkerConfig::default()
+            .progress_mode(ProgressMode::Eager)
+            .progress_interval(progress_interval),
+        ..Config::process(2)
+    };
+    timely::execute(config, |worker| {
+        let sent = Rc::new(Cell::new(0));
+        let sent_inner = Rc::clone(&sent);
+        worker.log_register().unwrap().insert::<TimelyProgressEventBuilder<u64>,_>("timely/progress/u64", move |_time, data| {
+            if let Some(data) = data {
+                sent_inner.set(sent_inner.get() + data.iter().filter(|(_, event)| event.is_send).count());
+            }
+        });
+
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let received_inner = Rc::clone(&received);
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow::<u64, _, _>(|scope| {
+            scope
+                .input_from(&mut input)
+                .exchange(|x| *x)
+                .inspect_time(move |time, x| received_inner.borrow_mut().push((*time, *x)))
+                .probe()
+        });
+
+        // Introduce records in many rounds, without waiting for each to complete.
+        for round in 0..100 {
+            input.send(round);
+            input.advance_to(round + 1);
+            worker.step();
+        }
+        input.close();
+        while !probe.done() {
+            worker.step();
+        }
+
+        worker.log_register().unwrap().flush();
+        let mut received = received.borrow().clone();
+        received.sort();
+        (sent.get(), received)
+    })
+    .unwrap()
+    .join()
+    .into_iter()
+    .map(|result| result.unwrap())
+    .collect()
+}
+
+#[test]
+fn progress_interval_coalesces_messages() {
+    let immediate = run(Duration::ZERO);
+    let coalesced = run(Duration::from_millis(50));
+
+    let sent = |results: &[(usize, Vec<(u64, u64)>)]| results.iter().map(|(sent, _)| sent).sum::<usize>();
+    assert!(sent(&coalesced) < sent(&immediate), "coalesced {} messages, immediate {}", sent(&coalesced), sent(&immediate));
+
+    // Both complete all rounds, with the same records at the same times.
+    for ((_, received_immediate), (_, received_coalesced)) in immediate.iter().zip(coalesced.iter()) {
+        assert_eq!(received_immediate, received_coalesced);
+    }
+    assert_eq!(immediate.iter().map(|(_, received)| received.len()).sum::<usize>(), 200);
+}
@@ -0,0 +1,59 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use timely::communication::allocator::recording::{DeliveryLog, RecordingBuilder};
+use timely::dataflow::channels::pact::Exchange;
+use timely::dataflow::operators::{Input, Operator};
+use timely::{CommunicationConfig, WorkerConfig};
+
+/// Runs three workers that each send records to worker 0, pausing for `pause(index, round)`
+/// before each round, and returns the order in which worker 0 received them.
+fn run<B, F>(builders: Vec<B>, pause: F) -> Vec<(usize, u64)>
+where
+    B: timely::communication::allocator::AllocateBuilder+'static,
+    F: Fn(usize, u64)->Duration+Send+Sync+'static,
+{
+    let guards = timely::execute_with_allocators(builders, WorkerConfig::default(), move |worker| {
+        let index = worker.index();
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let received_inner = Rc::clone(&received);
+        let mut input = worker.dataflow::<u64,_,_>(|scope| {
+            let (input, stream) = scope.new_input::<(usize, u64)>();
+            // Records in the order their messages are pulled, rather than grouped by time.
+            stream.sink(Exchange::new(|_| 0), "Received", move |(messages, _frontier)| {
+                messages.for_each(|_time, data| received_inner.borrow_mut().extend(data.drain(..)));
+            });
+            input
+        });
+        for round in 0..10 {
+            std::thread::sleep(pause(index, round));
+            input.send((index, round));
+            input.advance_to(round + 1);
+            worker.step();
+        }
+        input.close();
+        while worker.step() { }
+        received.take()
+    }).unwrap();
+    guards.join().into_iter().map(|result| result.unwrap()).next().unwrap()
+}
+
+fn builders() -> Vec<timely::communication::allocator::GenericBuilder> {
+    CommunicationConfig::Process(3).try_build().unwrap().0
+}
+
+#[test]
+fn replays_deliver_messages_in_the_recorded_order() {
+    let log = DeliveryLog::new();
+    // Workers with larger indexes send later in the recorded run, and earlier in the replays.
+    let recorded = run(RecordingBuilder::record(builders(), &log), |index, round| Duration::from_millis((index as u64 * 3 + round) % 5));
+    assert_eq!(recorded.len(), 30);
+
+    for run_index in 0..3 {
+        let replayed = run(RecordingBuilder::replay(builders(), &log, Duration::from_millis(100)), move |index, round| {
+            Duration::from_millis(((2 - index as u64) * 3 + round + run_index) % 5)
+        });
+        assert_eq!(replayed, recorded);
+    }
+}
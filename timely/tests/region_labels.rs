@@ -0,0 +1,64 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use timely::dataflow::Scope;
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::{Enter, Exchange, Inspect, Leave, Map, Operator, ToStream};
+use timely::logging::{TimelyEvent, TimelyEventBuilder};
+
+#[test]
+fn regions_label_their_events() {
+    timely::execute_directly(|worker| {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let events_inner = Rc::clone(&events);
+        worker.log_register().unwrap().insert::<TimelyEventBuilder,_>("timely", move |_time, data| {
+            if let Some(data) = data {
+                events_inner.borrow_mut().extend(data.iter().map(|(_, event)| event.clone()));
+            }
+        });
+
+        let infos = Rc::new(RefCell::new(Vec::new()));
+        let infos_inner = Rc::clone(&infos);
+        worker.dataflow::<u64, _, _>(|scope| {
+            let stream = (0..10u64).to_stream(scope);
+            scope.region_labeled("transform", |region| {
+                stream.enter(region)
+                      .map(|x| x + 1)
+                      .exchange(|x| *x)
+                      .unary(Pipeline, "Labeled", move |_capability, info| {
+                          infos_inner.borrow_mut().push(info.label.clone());
+                          move |input, output| input.for_each_time(|time, data| output.session(&time).give_containers(data))
+                      })
+                      .leave()
+            })
+            .container::<Vec<_>>()
+            .inspect(|_| ());
+        });
+        while worker.step() { }
+        worker.log_register().unwrap().flush();
+
+        assert_eq!(*infos.borrow(), vec![Some("transform".into())]);
+        let events = events.borrow();
+        let operator_labels = events.iter().filter_map(|event| match event {
+            TimelyEvent::Operates(operates) => Some((operates.name.as_str(), operates.label.as_deref())),
+            _ => None,
+        }).collect::<Vec<_>>();
+        // Operators in the region are labeled, and those outside it, including the region itself, are not.
+        for (name, label) in operator_labels.iter() {
+            let expected = if ["FlatMap", "Exchange", "Labeled"].contains(name) { Some("transform") } else { None };
+            assert_eq!(*label, expected, "operator {}", name);
+        }
+        assert!(operator_labels.iter().any(|(name, _)| *name == "transform"));
+        // The channels within the region are labeled, and their messages are found by channel.
+        let channel_labels = events.iter().filter_map(|event| match event {
+            TimelyEvent::Channels(channel) => Some((channel.id, channel.label.clone())),
+            _ => None,
+        }).collect::<Vec<_>>();
+        assert_eq!(channel_labels.iter().filter(|(_, label)| label.as_deref() == Some("transform")).count(), 4);
+        let labeled_messages = events.iter().filter(|event| match event {
+            TimelyEvent::Messages(message) => channel_labels.iter().any(|(id, label)| *id == message.channel && label.is_some()),
+            _ => false,
+        }).count();
+        assert!(labeled_messages > 0);
+    });
+}
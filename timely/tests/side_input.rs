@@ -0,0 +1,60 @@
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::{Broadcast, Capture, Input, Probe};
+use timely::dataflow::operators::capture::Extract;
+use timely::dataflow::operators::generic::operator::Operator;
+
+#[test]
+fn side_inputs_hold_back_the_output() {
+    let captured = timely::execute_directly(|worker| {
+        let (mut input, mut side, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+            let (input_handle, stream) = scope.new_input::<u64>();
+            let (side_handle, side_stream) = scope.new_input::<u64>();
+            let offsets = side_stream.broadcast();
+            let shifted = stream.unary_frontier_with_side_input(Some(&offsets), Pipeline, Pipeline, "Shift", |_capability, _info| {
+                let mut offset = 0;
+                move |(input, _frontier), side, output| {
+                    let (side, _side_frontier) = side.expect("side input present");
+                    side.for_each(|_time, data| offset += data.iter().sum::<u64>());
+                    input.for_each(|time, data| output.session(&time).give_iterator(data.drain(..).map(|x| x + offset)));
+                }
+            });
+            (input_handle, side_handle, shifted.probe(), shifted.capture())
+        });
+        side.send(100);
+        input.advance_to(1);
+        // The output frontier is held back by the side input, even though the primary input advanced.
+        for _ in 0..5 { worker.step(); }
+        assert!(probe.less_than(&1));
+        side.advance_to(1);
+        worker.step_while(|| probe.less_than(&1));
+        // The offset at time 0 is applied to later records.
+        input.send(1);
+        input.close();
+        side.close();
+        worker.step_while(|| !probe.done());
+        captured
+    });
+
+    assert_eq!(captured.extract(), vec![(1, vec![101])]);
+}
+
+#[test]
+fn absent_side_inputs_are_not_inputs() {
+    timely::execute_directly(|worker| {
+        let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+            let (input_handle, stream) = scope.new_input::<u64>();
+            let passed = stream.unary_frontier_with_side_input::<Vec<u64>, _, _, _, _, _>(None, Pipeline, Pipeline, "Pass", |_capability, _info| {
+                move |(input, _frontier), side, output| {
+                    assert!(side.is_none());
+                    input.for_each(|time, data| output.session(&time).give_container(data));
+                }
+            });
+            (input_handle, passed.container::<Vec<u64>>().probe())
+        });
+        // The output follows the primary input alone.
+        input.send(1);
+        input.advance_to(3);
+        worker.step_while(|| probe.less_than(&3));
+        assert!(!probe.less_than(&3));
+    });
+}
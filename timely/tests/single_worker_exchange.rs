@@ -0,0 +1,64 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde::{Deserialize, Serialize, Serializer};
+
+use timely::CommunicationConfig;
+use timely::dataflow::channels::codec::Bincode;
+use timely::dataflow::channels::pact::{Exchange, WithCodec};
+use timely::dataflow::operators::{Inspect, Operator, ToStream};
+
+/// The number of records serialized so far.
+static SERIALIZED: AtomicUsize = AtomicUsize::new(0);
+
+/// A record that counts its serializations.
+#[derive(Clone, Deserialize)]
+struct Counted(u64);
+
+impl Serialize for Counted {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SERIALIZED.fetch_add(1, Ordering::SeqCst);
+        self.0.serialize(serializer)
+    }
+}
+
+/// Exchanges 100 records among `workers` workers that serialize exchanged data, and returns the number of serializations.
+///
+/// With `codec`, the exchange serializes records with the `Bincode` codec.
+fn serializations(workers: usize, codec: bool) -> usize {
+    let config = timely::Config {
+        communication: CommunicationConfig::ProcessBinary(workers),
+        worker: timely::WorkerConfig::default(),
+    };
+    SERIALIZED.store(0, Ordering::SeqCst);
+    let received = timely::execute(config, move |worker| {
+        let received = std::rc::Rc::new(std::cell::Cell::new(0));
+        let received_inner = std::rc::Rc::clone(&received);
+        worker.dataflow::<u64,_,_>(|scope| {
+            let stream = (0..100).map(Counted).to_stream(scope);
+            let exchange = Exchange::new(|x: &Counted| x.0);
+            let exchanged = if codec {
+                stream.unary(WithCodec::<_, Bincode>::new(exchange), "Bincode", |_, _| |input, output| {
+                    input.for_each(|time, data| output.session(&time).give_container(data));
+                })
+            }
+            else {
+                stream.unary(exchange, "Native", |_, _| |input, output| {
+                    input.for_each(|time, data| output.session(&time).give_container(data));
+                })
+            };
+            exchanged.inspect(move |_| received_inner.set(received_inner.get() + 1));
+        });
+        while worker.step() { }
+        received.get()
+    }).unwrap().join().into_iter().map(|result| result.unwrap()).sum::<usize>();
+    assert_eq!(received, 100 * workers);
+    SERIALIZED.load(Ordering::SeqCst)
+}
+
+#[test]
+fn single_worker_exchange_skips_serialization() {
+    for codec in [false, true] {
+        assert!(serializations(2, codec) > 0);
+        assert_eq!(serializations(1, codec), 0);
+    }
+}
@@ -0,0 +1,102 @@
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use timely::dataflow::operators::{Exchange, Input, Probe};
+
+fn cluster(process: usize, addresses: &[&str], startup_timeout: Duration) -> timely::Config {
+    let communication = timely::CommunicationConfig::Cluster {
+        threads: 1,
+        process,
+        addresses: addresses.iter().map(|address| address.to_string()).collect(),
+        report: false,
+        zerocopy: false,
+        max_frame_bytes: None,
+        channel_credits: None,
+        buffer_pool_bytes: None,
+        startup_timeout: Some(startup_timeout),
+        pretouch: false,
+        log_fn: Arc::new(|_| None),
+    };
+    timely::Config { communication, worker: timely::WorkerConfig::default() }
+}
+
+#[test]
+fn missing_process_fails_to_connect() {
+    // Nothing listens for process 0, which process 1 connects to.
+    let started = Instant::now();
+    let result = timely::execute(cluster(1, &["localhost:52201", "localhost:52202"], Duration::from_millis(200)), |_worker| { });
+    let error = result.err().expect("connected to a missing process");
+    assert!(error.contains("process 1 did not connect to process 0"), "{}", error);
+    assert!(started.elapsed() < Duration::from_secs(10));
+}
+
+#[test]
+fn missing_connections_are_named() {
+    // Process 0 of three awaits connections from processes 1 and 2, which never start.
+    let addresses = ["localhost:52211", "localhost:52212", "localhost:52213"];
+    let error = timely::execute(cluster(0, &addresses, Duration::from_millis(200)), |_worker| { }).err().expect("connected without peers");
+    assert!(error.contains("process 0 did not receive connections from processes 1, 2 within 200ms"), "{}", error);
+}
+
+#[test]
+fn unallocated_channel_fails_its_worker() {
+    let addresses = ["localhost:52221", "localhost:52222"];
+    let sender = std::thread::spawn(move || {
+        timely::execute(cluster(0, &addresses, Duration::from_secs(5)), |worker| {
+            let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<u64>();
+                (input, stream.exchange(|_| 1).probe())
+            });
+            input.send(0);
+            input.advance_to(1);
+            // The other worker never constructs the dataflow, and it cannot complete.
+            let started = Instant::now();
+            while started.elapsed() < Duration::from_secs(1) && probe.less_than(input.time()) {
+                worker.step();
+            }
+            for dataflow in worker.installed_dataflows() { worker.drop_dataflow(dataflow); }
+        }).unwrap().join()
+    });
+    let receiver = timely::execute(cluster(1, &addresses, Duration::from_millis(200)), |worker| {
+        // Catch the failure, rather than have it poison the connections of both processes.
+        let started = Instant::now();
+        std::panic::catch_unwind(AssertUnwindSafe(|| {
+            while started.elapsed() < Duration::from_secs(5) { worker.step(); }
+        })).err().map(|payload| *payload.downcast::<String>().unwrap())
+    }).unwrap().join();
+
+    let error = receiver[0].as_ref().unwrap().as_ref().expect("worker did not fail");
+    assert!(error.contains("worker 1 did not allocate channel"), "{}", error);
+    assert!(error.contains("of receiving messages for it from worker 0"), "{}", error);
+    sender.join().unwrap();
+}
+
+#[test]
+fn late_dataflows_are_not_timed_out() {
+    let addresses = ["localhost:52231", "localhost:52232"];
+    let guards = [0, 1].map(|process| std::thread::spawn(move || {
+        timely::execute(cluster(process, &addresses, Duration::from_secs(1)), move |worker| {
+            worker.dataflow::<u64,_,_>(|scope| scope.new_input::<u64>().0).close();
+            while worker.step() { }
+            // The second process constructs the second dataflow well after receiving messages for it.
+            if process == 1 {
+                let started = Instant::now();
+                while started.elapsed() < Duration::from_millis(1500) { worker.step_or_park(Some(Duration::from_millis(10))); }
+            }
+            let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+                let (input, stream) = scope.new_input::<u64>();
+                (input, stream.exchange(|x| *x).probe())
+            });
+            input.send(process as u64);
+            input.send(1 - process as u64);
+            input.close();
+            let started = Instant::now();
+            while started.elapsed() < Duration::from_secs(10) && !probe.done() { worker.step(); }
+            assert!(probe.done());
+        }).unwrap().join()
+    }));
+    for guard in guards {
+        assert!(guard.join().unwrap().into_iter().all(|result| result.is_ok()));
+    }
+}
@@ -0,0 +1,62 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use timely::dataflow::operators::{Input, Inspect, Probe};
+
+#[test]
+fn hooks_surround_each_step() {
+    timely::execute_directly(|worker| {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+            let (input, stream) = scope.new_input::<u64>();
+            let seen = Rc::clone(&events);
+            (input, stream.inspect(move |x| seen.borrow_mut().push(format!("record {}", x))).probe())
+        });
+        for (name, begun) in [("first", Rc::clone(&events)), ("second", Rc::clone(&events))] {
+            let ended = Rc::clone(&begun);
+            worker.on_step_begin(move || begun.borrow_mut().push(format!("{} begin", name)));
+            worker.on_step_end(move || ended.borrow_mut().push(format!("{} end", name)));
+        }
+
+        input.send(7);
+        input.advance_to(1);
+        worker.step_while(|| probe.less_than(input.time()));
+        let events = events.take();
+        // Hooks run in the order registered, and each step is surrounded by all of them.
+        let step = ["first begin", "second begin", "first end", "second end"];
+        assert_eq!(events.len() % step.len(), 1);
+        assert_eq!(events.iter().filter(|event| event.starts_with("record")).collect::<Vec<_>>(), vec!["record 7"]);
+        let hooks = events.iter().filter(|event| !event.starts_with("record")).collect::<Vec<_>>();
+        assert!(hooks.chunks(step.len()).all(|chunk| chunk == step));
+        let record = events.iter().position(|event| event == "record 7").unwrap();
+        assert_eq!(&events[record - 2 .. record], &["first begin", "second begin"]);
+        assert_eq!(&events[record + 1 .. record + 3], &["first end", "second end"]);
+    });
+}
+
+#[test]
+fn panicking_hooks_are_dropped() {
+    timely::execute_directly(|worker| {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let (panicking, registering) = (Rc::clone(&calls), Rc::clone(&calls));
+        worker.on_step_begin(move || {
+            panicking.borrow_mut().push("panicking");
+            panic!("hook failed");
+        });
+        // A hook may register further hooks, which run from the next step.
+        let mut registered = false;
+        let mut handle = worker.clone();
+        worker.on_step_end(move || {
+            registering.borrow_mut().push("registering");
+            if !registered {
+                registered = true;
+                let added = Rc::clone(&registering);
+                handle.on_step_end(move || added.borrow_mut().push("registered"));
+            }
+        });
+
+        worker.step();
+        worker.step();
+        assert_eq!(*calls.borrow(), vec!["panicking", "registering", "registering", "registered"]);
+    });
+}
@@ -0,0 +1,40 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use timely::dataflow::operators::{Input, Inspect, Probe};
+
+#[test]
+fn fuel_bounds_operators_per_step() {
+    timely::execute_directly(|worker| {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let (mut input, probe) = worker.dataflow::<u64,_,_>(|scope| {
+            let (input, stream) = scope.new_input::<u64>();
+            let probe = stream.probe();
+            // Three operators, all activated by each record.
+            for branch in 0..3 {
+                let seen = Rc::clone(&seen);
+                stream.inspect(move |x| seen.borrow_mut().push((branch, *x))).probe_with(&probe);
+            }
+            (input, probe)
+        });
+
+        // With one unit of fuel, each step schedules at most one of the operators.
+        input.send(0);
+        input.advance_to(1);
+        let mut counts = vec![0];
+        while worker.step_with_fuel(1) {
+            counts.push(seen.borrow().len());
+        }
+        assert!(counts.windows(2).all(|pair| pair[1] - pair[0] <= 1), "{:?}", counts);
+        assert_eq!(seen.borrow().len(), 3);
+        assert!(!probe.less_than(input.time()));
+
+        // With enough fuel, one step schedules all of them.
+        input.send(1);
+        input.advance_to(2);
+        worker.step_with_fuel(usize::MAX);
+        assert_eq!(seen.borrow().len(), 6);
+        while worker.step_with_fuel(usize::MAX) { }
+        assert!(!probe.less_than(input.time()));
+    });
+}
@@ -0,0 +1,73 @@
+use timely::{Config, WorkerConfig};
+use timely::dataflow::operators::{Filter, Inspect, ToStream};
+use timely::worker::AsWorker;
+
+/// The first draws of each of `workers` workers, with `seed`.
+fn draws(workers: usize, seed: u64) -> Vec<Vec<u64>> {
+    let config = Config { worker: WorkerConfig::default().seed(seed), ..Config::process(workers) };
+    timely::execute(config, |worker| {
+        let rng = worker.rng();
+        (0..4).map(|_| rng.next_u64()).collect::<Vec<_>>()
+    }).unwrap().join().into_iter().map(Result::unwrap).collect()
+}
+
+#[test]
+fn draws_are_reproducible_per_worker() {
+    let first = draws(3, 7);
+    assert_eq!(first, draws(3, 7));
+    // Workers draw different numbers, as do runs with other seeds.
+    assert_ne!(first[0], first[1]);
+    assert_ne!(first[1], first[2]);
+    assert_ne!(first, draws(3, 8));
+}
+
+#[test]
+fn handles_share_the_worker_sequence() {
+    let config = Config { worker: WorkerConfig::default().seed(3), ..Config::thread() };
+    let alternating = timely::execute(config.clone(), |worker| {
+        let scoped = worker.dataflow::<u64,_,_>(|scope| scope.rng());
+        let rng = worker.rng();
+        vec![rng.next_u64(), scoped.next_u64(), rng.next_u64(), scoped.next_u64()]
+    }).unwrap().join();
+    let sequence = draws(1, 3);
+    assert_eq!(alternating[0].as_ref().unwrap(), &sequence[0]);
+}
+
+#[test]
+fn sampling_operators_sample_reproducibly() {
+    let sample = |seed| {
+        let config = Config { worker: WorkerConfig::default().seed(seed), ..Config::thread() };
+        timely::execute(config, |worker| {
+            let sampled = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+            let sampled_inner = std::rc::Rc::clone(&sampled);
+            worker.dataflow::<u64,_,_>(|scope| {
+                let rng = scope.rng();
+                (0..1000u64).to_stream(scope)
+                            .filter(move |_| rng.chance(0.1))
+                            .inspect(move |x| sampled_inner.borrow_mut().push(*x));
+            });
+            while worker.step() { }
+            let sampled = sampled.borrow().clone();
+            sampled
+        }).unwrap().join().pop().unwrap().unwrap()
+    };
+    let first = sample(11);
+    assert!((50..150).contains(&first.len()), "{}", first.len());
+    assert_eq!(first, sample(11));
+    assert_ne!(first, sample(12));
+}
+
+#[test]
+fn bounded_draws_are_in_range() {
+    timely::execute_directly(|worker| {
+        let rng = worker.rng();
+        let mut seen = [false; 6];
+        for _ in 0..1000 {
+            let die = rng.below(6);
+            seen[die as usize] = true;
+            let unit = rng.next_f64();
+            assert!((0.0..1.0).contains(&unit));
+        }
+        assert!(seen.iter().all(|seen| *seen));
+    });
+}